@@ -4,9 +4,15 @@ use std::sync::Arc;
 use anyhow::Result;
 use forge_domain::*;
 use forge_infra::ForgeInfra;
-use forge_services::{CommandExecutorService, ForgeServices, Infrastructure};
+use forge_services::{CommandExecutorService, ForgeServices, FsSnapshotService, Infrastructure};
+use forge_snaps::{
+    ExportReport, ImportReport, ImportStrategy, Snapshot, SnapshotId, SnapshotSelector,
+    SnapshotSummary, TreeRestoreReport, VerificationIssue,
+};
 use forge_stream::MpscStream;
-use tracing::error;
+use tracing::{debug, error};
+
+use crate::SnapshotApi;
 
 pub struct ForgeAPI<F> {
     app: Arc<F>,
@@ -52,16 +58,21 @@ impl<F: Services + Infrastructure> API for ForgeAPI<F> {
             .unwrap_or_default()
             .expect("conversation for the request should've been created at this point.");
 
-        Ok(MpscStream::spawn(move |tx| async move {
-            let tx = Arc::new(tx);
+        let request_id = RequestId::generate();
+        debug!(request_id = %request_id, conversation_id = %chat.conversation_id, "Chat request");
+
+        Ok(MpscStream::spawn(move |tx| {
+            request_id.scope(async move {
+                let tx = Arc::new(tx);
 
-            let orch = Orchestrator::new(app, conversation, Some(tx.clone()));
+                let orch = Orchestrator::new(app, conversation, Some(tx.clone()));
 
-            if let Err(err) = orch.dispatch(chat.event).await {
-                if let Err(e) = tx.send(Err(err)).await {
-                    error!("Failed to send error to stream: {:#?}", e);
+                if let Err(err) = orch.dispatch(chat.event).await {
+                    if let Err(e) = tx.send(Err(err)).await {
+                        error!("Failed to send error to stream: {:#?}", e);
+                    }
                 }
-            }
+            })
         }))
     }
 
@@ -69,10 +80,16 @@ impl<F: Services + Infrastructure> API for ForgeAPI<F> {
         &self,
         workflow: W,
     ) -> anyhow::Result<Conversation> {
-        self.app
-            .conversation_service()
-            .create(workflow.into())
-            .await
+        // The configured model and sampling settings apply where the workflow
+        // doesn't set its own
+        let env = self.environment();
+        let mut workflow: Workflow = workflow.into();
+        workflow.model = workflow.model.or(env.model);
+        workflow.temperature = workflow.temperature.or(env.temperature);
+        workflow.top_p = workflow.top_p.or(env.top_p);
+        workflow.top_k = workflow.top_k.or(env.top_k);
+
+        self.app.conversation_service().create(workflow).await
     }
 
     async fn upsert_conversation(&self, conversation: Conversation) -> anyhow::Result<()> {
@@ -89,6 +106,25 @@ impl<F: Services + Infrastructure> API for ForgeAPI<F> {
             .await
     }
 
+    async fn pin_last_message(
+        &self,
+        conversation_id: &ConversationId,
+    ) -> anyhow::Result<Option<String>> {
+        self.app
+            .conversation_service()
+            .update(conversation_id, |conversation| {
+                conversation.pin_last_user_message()
+            })
+            .await
+    }
+
+    async fn approve_plan(&self, conversation_id: &ConversationId) -> anyhow::Result<Option<Plan>> {
+        self.app
+            .conversation_service()
+            .update(conversation_id, |conversation| conversation.approve_plan())
+            .await
+    }
+
     fn environment(&self) -> Environment {
         Services::environment_service(self.app.as_ref())
             .get_environment()
@@ -124,7 +160,13 @@ impl<F: Services + Infrastructure> API for ForgeAPI<F> {
     ) -> anyhow::Result<CommandOutput> {
         self.app
             .command_executor_service()
-            .execute_command(command.to_string(), working_dir)
+            .execute_command(
+                command.to_string(),
+                working_dir,
+                CommandEnv::default(),
+                None,
+                None,
+            )
             .await
     }
     async fn read_mcp_config(&self) -> Result<McpConfig> {
@@ -153,3 +195,76 @@ impl<F: Services + Infrastructure> API for ForgeAPI<F> {
             .await
     }
 }
+
+#[async_trait::async_trait]
+impl<F: Services + Infrastructure> SnapshotApi for ForgeAPI<F> {
+    async fn restore_snapshot(
+        &self,
+        file_path: &Path,
+        selector: SnapshotSelector,
+        dest: &Path,
+        overwrite: bool,
+    ) -> anyhow::Result<()> {
+        self.app
+            .file_snapshot_service()
+            .restore_to(file_path, selector, dest, overwrite)
+            .await
+    }
+
+    async fn tag_snapshot(
+        &self,
+        file_path: &Path,
+        timestamp: &str,
+        tag: &str,
+    ) -> anyhow::Result<()> {
+        self.app
+            .file_snapshot_service()
+            .tag_snapshot(file_path, timestamp, tag)
+            .await
+    }
+
+    async fn snapshots(&self) -> anyhow::Result<Vec<SnapshotSummary>> {
+        self.app.file_snapshot_service().list_all().await
+    }
+
+    async fn file_snapshots(&self, file_path: &Path) -> anyhow::Result<Vec<Snapshot>> {
+        self.app
+            .file_snapshot_service()
+            .list_snapshots(file_path)
+            .await
+    }
+
+    async fn restore_tree_snapshot(&self, id: &SnapshotId) -> anyhow::Result<TreeRestoreReport> {
+        self.app.file_snapshot_service().restore_tree(id).await
+    }
+
+    async fn verify_snapshots(
+        &self,
+        file_path: Option<&Path>,
+    ) -> anyhow::Result<Vec<VerificationIssue>> {
+        match file_path {
+            Some(file_path) => self.app.file_snapshot_service().verify(file_path).await,
+            None => self.app.file_snapshot_service().verify_all().await,
+        }
+    }
+
+    async fn export_snapshots(&self, dest: &Path) -> anyhow::Result<ExportReport> {
+        let cwd = self.environment().cwd;
+        self.app
+            .file_snapshot_service()
+            .export_archive(dest, &cwd)
+            .await
+    }
+
+    async fn import_snapshots(
+        &self,
+        src: &Path,
+        strategy: ImportStrategy,
+    ) -> anyhow::Result<ImportReport> {
+        let cwd = self.environment().cwd;
+        self.app
+            .file_snapshot_service()
+            .import_archive(src, &cwd, strategy)
+            .await
+    }
+}
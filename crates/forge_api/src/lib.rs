@@ -1,4 +1,6 @@
 mod forge_api;
+mod snapshot_api;
 
 pub use forge_api::*;
 pub use forge_domain::*;
+pub use snapshot_api::*;
@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use anyhow::Result;
+use forge_snaps::{
+    ExportReport, ImportReport, ImportStrategy, Snapshot, SnapshotId, SnapshotSelector,
+    SnapshotSummary, TreeRestoreReport, VerificationIssue,
+};
+
+/// Snapshot operations offered alongside [`forge_domain::API`], kept apart so
+/// that the domain doesn't depend on the snapshot store
+#[async_trait::async_trait]
+pub trait SnapshotApi: Sync + Send {
+    /// Writes the selected snapshot of `file_path` to `dest`, leaving the
+    /// original file untouched. Fails if `dest` exists unless `overwrite` is
+    /// set.
+    async fn restore_snapshot(
+        &self,
+        file_path: &Path,
+        selector: SnapshotSelector,
+        dest: &Path,
+        overwrite: bool,
+    ) -> Result<()>;
+
+    /// Tags the snapshot of `file_path` taken at `timestamp`, so that it can
+    /// be restored by tag
+    async fn tag_snapshot(&self, file_path: &Path, timestamp: &str, tag: &str) -> Result<()>;
+
+    /// Lists every file with snapshots, most recently snapshotted files first
+    async fn snapshots(&self) -> Result<Vec<SnapshotSummary>>;
+
+    /// Lists the snapshots of `file_path`, most recent first
+    async fn file_snapshots(&self, file_path: &Path) -> Result<Vec<Snapshot>>;
+
+    /// Restores the files captured by a tree snapshot, files created since
+    /// the snapshot are left untouched
+    async fn restore_tree_snapshot(&self, id: &SnapshotId) -> Result<TreeRestoreReport>;
+
+    /// Checks the stored snapshots against their payloads, only the snapshots
+    /// of `file_path` when given
+    async fn verify_snapshots(&self, file_path: Option<&Path>) -> Result<Vec<VerificationIssue>>;
+
+    /// Writes the snapshots of the files in the current working directory to
+    /// an archive at `dest`
+    async fn export_snapshots(&self, dest: &Path) -> Result<ExportReport>;
+
+    /// Imports the snapshots of an archive written by `export_snapshots` into
+    /// the current working directory
+    async fn import_snapshots(&self, src: &Path, strategy: ImportStrategy) -> Result<ImportReport>;
+}
@@ -0,0 +1,161 @@
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// Where the parser is within an escape sequence
+#[derive(Clone, Copy)]
+enum State {
+    /// Plain text, which is kept
+    Text,
+    /// Right after `ESC`
+    Escape,
+    /// Inside a control sequence `ESC[`, such as colors or cursor movement
+    Csi,
+    /// Inside an operating system command `ESC]`, such as window titles or
+    /// hyperlinks
+    Osc,
+    /// An `ESC` inside an operating system command, which ends it when followed
+    /// by `\`
+    OscEscape,
+    /// Right after `ESC(` or `ESC)`, which select a character set
+    Charset,
+}
+
+/// Removes ANSI escape sequences from `input`, leaving the text that would be
+/// shown in a terminal
+pub fn strip_ansi(input: &str) -> String {
+    // Only ASCII bytes and whole sequences are removed, so the result is still
+    // valid UTF-8
+    String::from_utf8_lossy(&strip_ansi_bytes(input.as_bytes())).into_owned()
+}
+
+/// Removes ANSI escape sequences from raw output, such as that of a command
+pub fn strip_ansi_bytes(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut state = State::Text;
+    for &byte in input {
+        state = match (state, byte) {
+            (State::Text | State::Escape | State::Csi | State::Charset, ESC) => State::Escape,
+            (State::Text, byte) => {
+                output.push(byte);
+                State::Text
+            }
+            (State::Escape, b'[') => State::Csi,
+            (State::Escape, b']') => State::Osc,
+            (State::Escape, b'(' | b')') => State::Charset,
+            // Other sequences, such as saving the cursor with `ESC 7`, are a
+            // single character long
+            (State::Escape, 0x30..=0x7e) => State::Text,
+            // Parameters and intermediate characters of a control sequence
+            (State::Csi, 0x20..=0x3f) => State::Csi,
+            // The final character
+            (State::Csi, 0x40..=0x7e) => State::Text,
+            (State::Osc, BEL) => State::Text,
+            (State::Osc | State::OscEscape, ESC) => State::OscEscape,
+            (State::Osc, _) => State::Osc,
+            (State::OscEscape, b'\\') => State::Text,
+            (State::OscEscape, b'[') => State::Csi,
+            (State::OscEscape, b']') => State::Osc,
+            (State::OscEscape, b'(' | b')') => State::Charset,
+            (State::OscEscape, 0x30..=0x7e) => State::Text,
+            (State::Charset, 0x20..=0x7e) => State::Text,
+            // A byte that can't be part of the sequence ends it and is kept
+            (State::Escape | State::Csi | State::OscEscape | State::Charset, byte) => {
+                output.push(byte);
+                State::Text
+            }
+        };
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi_clean_text() {
+        let fixture = "plain text, with ümlauts and emoji 🦀\nand [brackets]";
+
+        let actual = strip_ansi(fixture);
+
+        assert_eq!(actual, fixture);
+    }
+
+    #[test]
+    fn test_strip_ansi_empty() {
+        assert_eq!(strip_ansi(""), "");
+    }
+
+    #[test]
+    fn test_strip_ansi_colors() {
+        let fixture = "\x1b[31mred\x1b[0m \x1b[1;32mbold green\x1b[m \x1b[38;5;208morange\x1b[39m \x1b[38;2;255;0;0mtrue color\x1b[0m";
+
+        let actual = strip_ansi(fixture);
+
+        let expected = "red bold green orange true color";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_strip_ansi_nested_colors() {
+        let fixture = "\x1b[1mbold \x1b[4munderlined \x1b[34mblue\x1b[24m still bold\x1b[0m done";
+
+        let actual = strip_ansi(fixture);
+
+        let expected = "bold underlined blue still bold done";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_strip_ansi_cursor_movement() {
+        let fixture = "\x1b[2K\x1b[1G⠋ Loading\x1b[3A\x1b[10;20H\x1b[?25l\x1b[?25hdone\x1b7\x1b8";
+
+        let actual = strip_ansi(fixture);
+
+        let expected = "⠋ Loadingdone";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_strip_ansi_osc() {
+        let fixture =
+            "\x1b]0;window title\x07text \x1b]8;;https://example.com/ü\x1b\\link\x1b]8;;\x1b\\ end";
+
+        let actual = strip_ansi(fixture);
+
+        let expected = "text link end";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_strip_ansi_charset() {
+        let fixture = "\x1b(Bplain\x1b(0lqk\x1b(B \x1b)0text";
+
+        let actual = strip_ansi(fixture);
+
+        let expected = "plainlqk text";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_strip_ansi_incomplete_sequences() {
+        let fixture = ["text\x1b[31", "text\x1b", "text\x1b]0;title", "\x1b[é"];
+
+        let actual: Vec<String> = fixture.iter().map(|input| strip_ansi(input)).collect();
+
+        let expected = vec!["text", "text", "text", "é"];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_strip_ansi_bytes() {
+        let fixture = b"\x1b[32m\xff raw\x1b[0m\r\n";
+
+        let actual = strip_ansi_bytes(fixture);
+
+        let expected = b"\xff raw\r\n".to_vec();
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,7 +1,69 @@
 use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
 
-use console::{style, Style};
-use similar::{ChangeTag, TextDiff};
+use console::{measure_text_width, pad_str, style, truncate_str, Alignment, Style};
+use similar::{ChangeTag, DiffOp, DiffTag, InlineChange, TextDiff};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+/// Theme providing the token colours of highlighted diffs
+const THEME: &str = "base16-ocean.dark";
+
+/// Background of removed lines in highlighted diffs
+const REMOVED_BACKGROUND: &str = "\x1b[48;2;80;24;24m";
+
+/// Background of added lines in highlighted diffs
+const ADDED_BACKGROUND: &str = "\x1b[48;2;24;64;24m";
+
+const RESET: &str = "\x1b[0m";
+
+/// Columns taken by the line number and sign in front of each side of a
+/// side-by-side diff
+const GUTTER: usize = 6;
+
+/// Divider between the two sides of a side-by-side diff
+const SEPARATOR: &str = " │ ";
+
+/// Narrowest text column of a side-by-side diff, however small the terminal
+const MIN_COLUMN: usize = 10;
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme() -> &'static Theme {
+    static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+    &THEME_SET.get_or_init(ThemeSet::load_defaults).themes[THEME]
+}
+
+/// Infers the language of a file from its extension, or from its name for
+/// files such as `Makefile`. The result can be passed to
+/// [`DiffFormat::with_syntax_highlight`].
+pub fn detect_language_from_path(path: &Path) -> Option<String> {
+    let hint = path.extension().or_else(|| path.file_name())?.to_str()?;
+    syntax_set()
+        .find_syntax_by_extension(hint)
+        .map(|_| hint.to_string())
+}
+
+/// Highlights every line of `text`, returning the lines without their line
+/// endings
+fn highlight(text: &str, syntax: &SyntaxReference) -> Vec<String> {
+    let mut highlighter = HighlightLines::new(syntax, theme());
+    LinesWithEndings::from(text)
+        .map(|line| {
+            let highlighted = match highlighter.highlight_line(line, syntax_set()) {
+                Ok(ranges) => as_24_bit_terminal_escaped(&ranges, false),
+                Err(_) => line.to_string(),
+            };
+            highlighted.trim_end_matches('\n').to_string()
+        })
+        .collect()
+}
 
 struct Line(Option<usize>);
 
@@ -14,19 +76,141 @@ impl fmt::Display for Line {
     }
 }
 
-pub struct DiffFormat;
+/// Renders one side of a side-by-side row: the line number, the sign and the
+/// line cut to `column`, with the changed words in bold
+fn cell(change: &InlineChange<str>, index: Option<usize>, column: usize) -> String {
+    let (sign, s) = match change.tag() {
+        ChangeTag::Delete => ("-", Style::new().blue()),
+        ChangeTag::Insert => ("+", Style::new().yellow()),
+        ChangeTag::Equal => (" ", Style::new().dim()),
+    };
+    let text: String = change
+        .iter_strings_lossy()
+        .map(|(emphasized, value)| {
+            let value = value.trim_end_matches(['\r', '\n']).replace('\t', "    ");
+            let s = if emphasized {
+                s.clone().bold()
+            } else {
+                s.clone()
+            };
+            s.apply_to(value).to_string()
+        })
+        .collect();
+    format!(
+        "{} {}{}",
+        style(Line(index)).dim(),
+        s.apply_to(sign),
+        truncate_str(&text, column, "…")
+    )
+}
+
+#[derive(Default)]
+pub struct DiffFormat {
+    syntax: Option<&'static SyntaxReference>,
+    /// Total width of a side-by-side diff, which is unified when unset
+    width: Option<usize>,
+}
 
 impl DiffFormat {
+    /// Creates a formatter that also colours the tokens of changed lines,
+    /// using the syntax of `language`, a file extension hint such as `rs`.
+    /// Unknown languages get the plain diff colours.
+    pub fn with_syntax_highlight(language: Option<&str>) -> Self {
+        let syntax_set = syntax_set();
+        let syntax = language.and_then(|language| {
+            syntax_set
+                .find_syntax_by_extension(language)
+                .or_else(|| syntax_set.find_syntax_by_token(language))
+        });
+        Self { syntax, width: None }
+    }
+
+    /// Creates a formatter that shows the old file on the left half and the
+    /// new file on the right half of `width` columns, defaulting to the width
+    /// of the terminal. Lines that were only added or removed leave the other
+    /// half blank.
+    pub fn side_by_side(width: Option<u16>) -> Self {
+        Self { syntax: None, width: Some(crate::width::resolve(width)) }
+    }
+
     pub fn format(old: &str, new: &str) -> String {
+        Self::default().diff(old, new)
+    }
+
+    pub fn diff(&self, old: &str, new: &str) -> String {
         let diff = TextDiff::from_lines(old, new);
         let ops = diff.grouped_ops(3);
-        let mut output = String::new();
 
         if ops.is_empty() {
-            output.push_str(&format!("{}\n", style("No changes applied").dim()));
-            return output;
+            return format!("{}\n", style("No changes applied").dim());
         }
 
+        match self.width {
+            Some(width) => Self::side_by_side_diff(&diff, &ops, width),
+            None => self.unified_diff(&diff, &ops, old, new),
+        }
+    }
+
+    fn side_by_side_diff<'a>(
+        diff: &'a TextDiff<'a, 'a, 'a, str>,
+        ops: &[Vec<DiffOp>],
+        width: usize,
+    ) -> String {
+        let separator_width = measure_text_width(SEPARATOR);
+        let column = (width.saturating_sub(2 * GUTTER + separator_width) / 2).max(MIN_COLUMN);
+        let mut output = String::new();
+
+        for (idx, group) in ops.iter().enumerate() {
+            if idx > 0 {
+                output.push_str(&format!("{}\n", style("...").dim()));
+            }
+            for op in group {
+                let changes: Vec<_> = diff.iter_inline_changes(op).collect();
+                // Unchanged lines appear on both sides, while the removed and
+                // added lines of a replacement are paired up row by row
+                let (left, right): (Vec<_>, Vec<_>) = match op.tag() {
+                    DiffTag::Equal => (changes.iter().collect(), changes.iter().collect()),
+                    _ => changes
+                        .iter()
+                        .partition(|change| change.tag() == ChangeTag::Delete),
+                };
+
+                for row in 0..left.len().max(right.len()) {
+                    let left = left
+                        .get(row)
+                        .map(|change| cell(change, change.old_index(), column))
+                        .unwrap_or_default();
+                    let right = right
+                        .get(row)
+                        .map(|change| cell(change, change.new_index(), column))
+                        .unwrap_or_default();
+                    output.push_str(&format!(
+                        "{}{}{right}\n",
+                        pad_str(&left, GUTTER + column, Alignment::Left, None),
+                        style(SEPARATOR).dim()
+                    ));
+                }
+            }
+        }
+        output
+    }
+
+    fn unified_diff<'a>(
+        &self,
+        diff: &'a TextDiff<'a, 'a, 'a, str>,
+        ops: &[Vec<DiffOp>],
+        old: &str,
+        new: &str,
+    ) -> String {
+        let mut output = String::new();
+
+        // Highlighting is skipped when colours are disabled, as it would only
+        // add escape codes
+        let highlighted = self
+            .syntax
+            .filter(|_| console::colors_enabled())
+            .map(|syntax| (highlight(old, syntax), highlight(new, syntax)));
+
         for (idx, group) in ops.iter().enumerate() {
             if idx > 0 {
                 output.push_str(&format!("{}\n", style("...").dim()));
@@ -40,12 +224,28 @@ impl DiffFormat {
                     };
 
                     output.push_str(&format!(
-                        "{}{} |{}",
+                        "{}{} |",
                         style(Line(change.old_index())).dim(),
                         style(Line(change.new_index())).dim(),
-                        s.apply_to(sign),
                     ));
 
+                    let syntax_line = match (change.tag(), &highlighted) {
+                        (ChangeTag::Delete, Some((old_lines, _))) => change
+                            .old_index()
+                            .and_then(|idx| old_lines.get(idx))
+                            .map(|line| (REMOVED_BACKGROUND, line)),
+                        (ChangeTag::Insert, Some((_, new_lines))) => change
+                            .new_index()
+                            .and_then(|idx| new_lines.get(idx))
+                            .map(|line| (ADDED_BACKGROUND, line)),
+                        _ => None,
+                    };
+                    if let Some((background, line)) = syntax_line {
+                        output.push_str(&format!("{background}{sign}{line}{RESET}\n"));
+                        continue;
+                    }
+
+                    output.push_str(&format!("{}", s.apply_to(sign)));
                     for (_, value) in change.iter_strings_lossy() {
                         output.push_str(&format!("{}", s.apply_to(value)));
                     }
@@ -63,6 +263,7 @@ impl DiffFormat {
 mod tests {
     use console::strip_ansi_codes;
     use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
 
     use super::*;
 
@@ -90,6 +291,105 @@ mod tests {
         assert_snapshot!(clean_diff);
     }
 
+    #[test]
+    fn test_syntax_highlight_keeps_diff_text() {
+        console::set_colors_enabled(true);
+        let old = "fn main() {\n    let a = 1;\n}\n";
+        let new = "fn main() {\n    let a = 2;\n}\n";
+
+        let actual = DiffFormat::with_syntax_highlight(Some("rs")).diff(old, new);
+
+        assert!(actual.contains(REMOVED_BACKGROUND));
+        assert!(actual.contains(ADDED_BACKGROUND));
+        assert!(actual.contains("\x1b[38;2;"));
+        assert_eq!(
+            strip_ansi_codes(&actual),
+            strip_ansi_codes(&DiffFormat::format(old, new))
+        );
+    }
+
+    #[test]
+    fn test_syntax_highlight_unknown_language() {
+        console::set_colors_enabled(true);
+        let old = "a\nb\n";
+        let new = "a\nc\n";
+
+        let actual = DiffFormat::with_syntax_highlight(Some("not-a-language")).diff(old, new);
+
+        assert_eq!(actual, DiffFormat::format(old, new));
+    }
+
+    #[test]
+    fn test_detect_language_from_path() {
+        let actual = [
+            "src/main.rs",
+            "scripts/build.py",
+            "Makefile",
+            "notes.unknownext",
+        ]
+        .map(|path| detect_language_from_path(Path::new(path)));
+
+        let expected = [
+            Some("rs".to_string()),
+            Some("py".to_string()),
+            Some("Makefile".to_string()),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_side_by_side_blank_half() {
+        let old = "a\nb\nc\n";
+        let new = "a\nc\nd\n";
+
+        let actual =
+            strip_ansi_codes(&DiffFormat::side_by_side(Some(40)).diff(old, new)).to_string();
+
+        let rows: Vec<_> = actual
+            .lines()
+            .map(|row| row.split_once(SEPARATOR).unwrap())
+            .map(|(left, right)| (left.trim_end(), right))
+            .collect();
+        let expected = vec![
+            ("1     a", "1     a"),
+            ("2    -b", ""),
+            ("3     c", "2     c"),
+            ("", "3    +d"),
+        ];
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn test_side_by_side_fits_width() {
+        let old = "short\n";
+        let new = format!("{}\n", "long ".repeat(40));
+
+        let actual =
+            strip_ansi_codes(&DiffFormat::side_by_side(Some(60)).diff(old, &new)).to_string();
+
+        assert!(actual.contains('…'));
+        assert!(actual.lines().all(|row| measure_text_width(row) <= 60));
+    }
+
+    #[test]
+    fn test_side_by_side_bolds_changed_words() {
+        console::set_colors_enabled(true);
+        let old = "let value = old;\n";
+        let new = "let value = new;\n";
+
+        let actual = DiffFormat::side_by_side(Some(80)).diff(old, new);
+
+        assert!(actual.contains("\x1b[1m"));
+        let (left, right) = strip_ansi_codes(&actual)
+            .trim_end()
+            .split_once(SEPARATOR)
+            .map(|(left, right)| (left.trim_end().to_string(), right.to_string()))
+            .unwrap();
+        assert_eq!(left, "1    -let value = old;");
+        assert_eq!(right, "1    +let value = new;");
+    }
+
     #[test]
     fn test_diff_printer_simple_diff() {
         let old = "line 1\nline 2\nline 3\nline 5\nline 6\nline 7\nline 8\nline 9";
@@ -1,27 +1,140 @@
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 
-use console::style;
+use console::{measure_text_width, style, truncate_str};
 use derive_setters::Setters;
 use regex::Regex;
+use serde::Deserialize;
 
 /// RipGrepFormatter formats search results in ripgrep-like style.
 #[derive(Clone, Setters)]
 #[setters(into, strip_option)]
 pub struct GrepFormat {
     lines: Vec<String>,
+    /// Structured results, which are shown instead of `lines` when given
+    #[setters(skip)]
+    results: Vec<GrepResult>,
     regex: Option<Regex>,
+    /// Width that lines are cut to, if any
+    #[setters(skip)]
+    width: Option<usize>,
+}
+
+/// A line that matched a search, along with the lines around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepResult {
+    /// File the match was found in
+    pub file: PathBuf,
+    /// Line number of the match, starting at 1
+    pub line_number: usize,
+    /// Column the first match on the line starts at, starting at 1, if known
+    pub column: Option<usize>,
+    /// Content of the matching line, without its line ending
+    pub text: String,
+    /// Lines right before the match, in order
+    pub context_before: Vec<String>,
+    /// Lines right after the match, in order
+    pub context_after: Vec<String>,
+}
+
+/// A message of ripgrep's `--json` output
+#[derive(Deserialize)]
+struct RipgrepMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    data: serde_json::Value,
+}
+
+/// The data of a `match` or `context` message
+#[derive(Deserialize)]
+struct RipgrepLine {
+    path: RipgrepText,
+    lines: RipgrepText,
+    line_number: Option<usize>,
+    #[serde(default)]
+    submatches: Vec<RipgrepSubmatch>,
+}
+
+/// Text that ripgrep reports as `bytes` instead when it isn't valid UTF-8
+#[derive(Deserialize)]
+struct RipgrepText {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RipgrepSubmatch {
+    start: usize,
+}
+
+impl GrepResult {
+    /// Parses the output of `rg --json`. Context lines that follow a match
+    /// directly are attributed to it, and the others to the next match in the
+    /// same file. Lines that aren't valid UTF-8 or lack a line number are
+    /// skipped.
+    pub fn from_ripgrep_json(json: &str) -> serde_json::Result<Vec<GrepResult>> {
+        let mut results: Vec<GrepResult> = Vec::new();
+        // Context lines waiting for the match they come before
+        let mut pending: Vec<(PathBuf, usize, String)> = Vec::new();
+
+        for line in json.lines().filter(|line| !line.trim().is_empty()) {
+            let message: RipgrepMessage = serde_json::from_str(line)?;
+            if message.kind != "match" && message.kind != "context" {
+                continue;
+            }
+            let data: RipgrepLine = serde_json::from_value(message.data)?;
+            let (Some(path), Some(text), Some(line_number)) =
+                (data.path.text, data.lines.text, data.line_number)
+            else {
+                continue;
+            };
+            let file = PathBuf::from(path);
+            let text = text.trim_end_matches(['\r', '\n']).to_string();
+
+            if message.kind == "match" {
+                let context_before = pending
+                    .drain(..)
+                    .filter(|(path, ..)| *path == file)
+                    .map(|(.., text)| text)
+                    .collect();
+                results.push(GrepResult {
+                    file,
+                    line_number,
+                    column: data.submatches.first().map(|submatch| submatch.start + 1),
+                    text,
+                    context_before,
+                    context_after: Vec::new(),
+                });
+                continue;
+            }
+
+            match results.last_mut() {
+                Some(last)
+                    if pending.is_empty()
+                        && last.file == file
+                        && line_number == last.line_number + last.context_after.len() + 1 =>
+                {
+                    last.context_after.push(text)
+                }
+                _ => pending.push((file, line_number, text)),
+            }
+        }
+
+        Ok(results)
+    }
 }
 
 /// Represents a parsed line from grep-like output format
-/// (path:line_num:content)
+/// (path:line_num:content for matches, path-line_num-content for context)
 #[derive(Debug)]
-struct ParsedLine<'a> {
+pub(crate) struct ParsedLine<'a> {
     /// File path where the match was found
-    path: &'a str,
+    pub(crate) path: &'a str,
     /// Line number of the match
-    line_num: &'a str,
+    pub(crate) line_num: &'a str,
     /// Content of the matching line
-    content: &'a str,
+    pub(crate) content: &'a str,
+    /// Whether the line matched or is shown as context around a match
+    pub(crate) is_match: bool,
 }
 
 impl<'a> ParsedLine<'a> {
@@ -33,7 +146,7 @@ impl<'a> ParsedLine<'a> {
     /// # Returns
     /// * `Some(ParsedLine)` if the line matches the expected format
     /// * `None` if the line is malformed
-    fn parse(line: &'a str) -> Option<Self> {
+    pub(crate) fn parse(line: &'a str) -> Option<Self> {
         let parts: Vec<_> = line.split(':').collect();
         if parts.len() != 3 {
             return None;
@@ -52,68 +165,190 @@ impl<'a> ParsedLine<'a> {
             path: parts[0].trim(),
             line_num: parts[1].trim(),
             content: parts[2].trim(),
+            is_match: true,
+        })
+    }
+
+    /// Parse a context line in the format "path-line_num-content", taking the
+    /// first dash delimited number as the line number
+    fn parse_context(line: &'a str) -> Option<Self> {
+        line.match_indices('-').find_map(|(start, _)| {
+            let (line_num, content) = line[start + 1..].split_once('-')?;
+            let valid =
+                start > 0 && !line_num.is_empty() && line_num.chars().all(|c| c.is_ascii_digit());
+            valid.then(|| Self {
+                path: line[..start].trim(),
+                line_num,
+                content: content.trim(),
+                is_match: false,
+            })
         })
     }
 }
 
-type Lines<'a> = Vec<(&'a str, &'a str)>;
+pub(crate) type Lines<'a> = Vec<ParsedLine<'a>>;
 impl GrepFormat {
     /// Create a new GrepFormat without a specific regex
     pub fn new(lines: Vec<String>) -> Self {
-        Self { lines, regex: None }
+        Self { lines, results: Vec::new(), regex: None, width: None }
+    }
+
+    /// Create a new GrepFormat that cuts lines longer than `width` columns,
+    /// defaulting to the width of the terminal
+    pub fn with_width(lines: Vec<String>, width: Option<u16>) -> Self {
+        Self {
+            lines,
+            results: Vec::new(),
+            regex: None,
+            width: Some(crate::width::resolve(width)),
+        }
+    }
+
+    /// Create a new GrepFormat for structured results, which are shown in the
+    /// traditional `file:line:col: text` format
+    pub fn from_results(results: &[GrepResult]) -> Self {
+        Self {
+            lines: Vec::new(),
+            results: results.to_vec(),
+            regex: None,
+            width: None,
+        }
     }
 
     /// Collect file entries and determine the maximum line number width
-    fn collect_entries(&self) -> (BTreeMap<&str, Lines>, usize) {
+    pub(crate) fn collect_entries(&self) -> (BTreeMap<&str, Lines<'_>>, usize) {
         self.lines
             .iter()
             .map(String::as_str)
-            .filter_map(ParsedLine::parse)
+            .filter_map(|line| ParsedLine::parse(line).or_else(|| ParsedLine::parse_context(line)))
             .fold((BTreeMap::new(), 0), |(mut entries, max_width), parsed| {
                 let new_width = max_width.max(parsed.line_num.len());
-                entries
-                    .entry(parsed.path)
-                    .or_default()
-                    .push((parsed.line_num, parsed.content));
+                entries.entry(parsed.path).or_default().push(parsed);
                 (entries, new_width)
             })
     }
 
-    /// Format a single line with colorization and consistent padding
-    fn format_line(&self, num: &str, content: &str, padding: usize) -> String {
-        let num = style(format!("{num:>padding$}: ")).dim();
-
-        // Format the content with highlighting if regex is available
-        let line = match self.regex {
-            Some(ref regex) => regex.find(content).map_or_else(
-                || content.to_string(),
-                |mat| {
-                    format!(
-                        "{}{}{}",
-                        &content[..mat.start()],
-                        style(&content[mat.start()..mat.end()]).yellow().bold(),
-                        &content[mat.end()..]
-                    )
-                },
-            ),
-            None => content.to_string(),
+    /// Format a single line with colorization and consistent padding. When
+    /// context is shown, matching lines are marked with `>`.
+    fn format_line(&self, line: &ParsedLine, padding: usize, with_context: bool) -> String {
+        let ParsedLine { line_num: num, content, is_match, .. } = *line;
+        let num = match (with_context, is_match) {
+            (false, _) => style(format!("{num:>padding$}: ")).dim(),
+            (true, true) => style(format!("> {num:>padding$}: ")).dim(),
+            (true, false) => style(format!("  {num:>padding$}- ")).dim(),
+        };
+
+        let line = if is_match {
+            self.highlight(content)
+        } else {
+            content.to_string()
+        };
+        self.fit(&num.to_string(), &line)
+    }
+
+    /// Highlights the exact span of every match if regex is available
+    fn highlight(&self, content: &str) -> String {
+        let Some(regex) = &self.regex else {
+            return content.to_string();
         };
+        let mut line = String::new();
+        let mut last = 0;
+        for mat in regex.find_iter(content).filter(|mat| !mat.is_empty()) {
+            line.push_str(&content[last..mat.start()]);
+            line.push_str(&style(mat.as_str()).yellow().bold().to_string());
+            last = mat.end();
+        }
+        line.push_str(&content[last..]);
+        line
+    }
 
-        format!("{num}{line}\n")
+    /// Joins the prefix of a line with its content, cutting the content to the
+    /// width if one is set
+    fn fit(&self, prefix: &str, line: &str) -> String {
+        match self.width {
+            Some(width) => {
+                let available = width.saturating_sub(measure_text_width(prefix));
+                format!("{prefix}{}\n", truncate_str(line, available, "…"))
+            }
+            None => format!("{prefix}{line}\n"),
+        }
     }
 
-    /// Format a group of lines for a single file
+    /// Format structured results as `file:line:col: text`, with context lines
+    /// as `file-line-text`. Blocks of lines that aren't consecutive are
+    /// separated by `--` when context is shown.
+    fn format_results(&self) -> String {
+        let with_context = self
+            .results
+            .iter()
+            .any(|result| !result.context_before.is_empty() || !result.context_after.is_empty());
+
+        let mut formatted = String::new();
+        // File and number of the last line shown
+        let mut previous: Option<(&PathBuf, usize)> = None;
+        for result in &self.results {
+            let path = result.file.display().to_string();
+            let first = result
+                .line_number
+                .saturating_sub(result.context_before.len());
+            let context = |num: usize, text: &str| {
+                self.fit(&style(format!("{path}-{num}-")).dim().to_string(), text)
+            };
+
+            // Context shared with the previous result is only shown once
+            let shown = match previous {
+                Some((file, last)) if *file == result.file => Some(last),
+                _ => None,
+            };
+            if with_context && previous.is_some() && shown.is_none_or(|last| first > last + 1) {
+                formatted.push_str(&format!("{}\n", style("--").dim()));
+            }
+
+            for (num, text) in (first..).zip(&result.context_before) {
+                if shown.is_none_or(|last| num > last) {
+                    formatted.push_str(&context(num, text));
+                }
+            }
+
+            let position = match result.column {
+                Some(column) => format!(":{}:{column}: ", result.line_number),
+                None => format!(":{}: ", result.line_number),
+            };
+            let prefix = format!("{}{}", style(&path).cyan(), style(position).dim());
+            formatted.push_str(&self.fit(&prefix, &self.highlight(&result.text)));
+
+            for (num, text) in (result.line_number + 1..).zip(&result.context_after) {
+                formatted.push_str(&context(num, text));
+            }
+            previous = Some((
+                &result.file,
+                result.line_number + result.context_after.len(),
+            ));
+        }
+        formatted
+    }
+
+    /// Format a group of lines for a single file. When context is shown,
+    /// blocks of consecutive lines are separated by `--`.
     fn format_file_group(
         &self,
         path: &str,
-        group: Vec<(&str, &str)>,
+        group: Lines,
         max_num_width: usize,
+        with_context: bool,
     ) -> String {
         let file_header = style(path).cyan();
-        let formatted_lines = group
-            .into_iter()
-            .map(|(num, content)| self.format_line(num, content, max_num_width))
-            .collect::<String>();
+        let mut formatted_lines = String::new();
+        let mut previous: Option<u64> = None;
+        for line in &group {
+            let num = line.line_num.parse::<u64>().ok();
+            let is_gap = previous.is_some_and(|previous| num != Some(previous + 1));
+            if with_context && is_gap {
+                formatted_lines.push_str(&format!("{}\n", style("--").dim()));
+            }
+            formatted_lines.push_str(&self.format_line(line, max_num_width, with_context));
+            previous = num;
+        }
         format!("{file_header}\n{formatted_lines}")
     }
 
@@ -132,6 +367,10 @@ impl GrepFormat {
 
     /// Format search results with colorized output grouped by path
     pub fn format(&self) -> String {
+        if !self.results.is_empty() {
+            return self.format_results();
+        }
+
         if self.lines.is_empty() {
             return String::new();
         }
@@ -149,11 +388,12 @@ impl GrepFormat {
 
         // First pass: collect entries and find max width
         let (entries, max_num_width) = self.collect_entries();
+        let with_context = entries.values().flatten().any(|parsed| !parsed.is_match);
 
         // Print the results on separate lines
         let formatted_entries: Vec<_> = entries
             .into_iter()
-            .map(|(path, group)| self.format_file_group(path, group, max_num_width))
+            .map(|(path, group)| self.format_file_group(path, group, max_num_width, with_context))
             .collect();
 
         // Join all results with newlines
@@ -315,6 +555,62 @@ mod tests {
         assert_snapshot!(suite);
     }
 
+    #[test]
+    fn test_highlights_every_match() {
+        console::set_colors_enabled(true);
+        let grep = GrepFormat::new(vec!["file.txt:1:cat concat cat".to_string()])
+            .regex(Regex::new(r"\bcat\b").unwrap());
+
+        let actual = grep.format();
+
+        let highlighted = style("cat").yellow().bold().to_string();
+        assert_eq!(actual.matches(&highlighted).count(), 2);
+        assert!(actual.contains(" concat "));
+    }
+
+    #[test]
+    fn test_context_blocks() {
+        let lines = vec![
+            "a.rs-1-fn main() {",
+            "a.rs:2:let x = 1;",
+            "a.rs-3-}",
+            "--",
+            "a.rs-9-fn b() {",
+            "a.rs:10:let y = 2;",
+        ];
+        let grep = GrepFormat::new(lines.into_iter().map(String::from).collect())
+            .regex(Regex::new("let").unwrap());
+
+        let actual = strip_ansi_escapes::strip_str(grep.format()).to_string();
+
+        let expected = "a.rs\n   1- fn main() {\n>  2: let x = 1;\n   3- }\n--\n   9- fn b() {\n> 10: let y = 2;\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_with_width_truncates_long_lines() {
+        let lines = vec![
+            "file.txt:1:short".to_string(),
+            format!("file.txt:2:{}", "long ".repeat(20)),
+        ];
+        let grep = GrepFormat::with_width(lines, Some(29)).regex(Regex::new("long").unwrap());
+
+        let actual = strip_ansi_escapes::strip_str(grep.format()).to_string();
+
+        let expected = "file.txt\n1: short\n2: long long long long long …\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_context_line_with_dashed_path() {
+        let actual = ParsedLine::parse_context("my-crate/src/lib.rs-12-let a = b - 1;").unwrap();
+
+        assert_eq!(
+            (actual.path, actual.line_num, actual.content),
+            ("my-crate/src/lib.rs", "12", "let a = b - 1;")
+        );
+    }
+
     #[test]
     fn test_with_and_without_regex() {
         let lines = vec!["a/b/c.md".to_string(), "p/q/r.rs".to_string()];
@@ -326,4 +622,96 @@ mod tests {
         assert!(output.contains("c.md"));
         assert!(output.contains("r.rs"));
     }
+
+    fn result(file: &str, line_number: usize, column: Option<usize>, text: &str) -> GrepResult {
+        GrepResult {
+            file: PathBuf::from(file),
+            line_number,
+            column,
+            text: text.to_string(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_from_ripgrep_json() {
+        let fixture = [
+            r#"{"type":"begin","data":{"path":{"text":"a.rs"}}}"#,
+            r#"{"type":"context","data":{"path":{"text":"a.rs"},"lines":{"text":"fn main() {\n"},"line_number":1,"absolute_offset":0,"submatches":[]}}"#,
+            r#"{"type":"match","data":{"path":{"text":"a.rs"},"lines":{"text":"    let x = 1;\n"},"line_number":2,"absolute_offset":12,"submatches":[{"match":{"text":"let"},"start":4,"end":7}]}}"#,
+            r#"{"type":"context","data":{"path":{"text":"a.rs"},"lines":{"text":"}\r\n"},"line_number":3,"absolute_offset":27,"submatches":[]}}"#,
+            r#"{"type":"context","data":{"path":{"text":"a.rs"},"lines":{"text":"fn b() {\n"},"line_number":9,"absolute_offset":60,"submatches":[]}}"#,
+            r#"{"type":"match","data":{"path":{"text":"a.rs"},"lines":{"text":"    let y = 2;\n"},"line_number":10,"absolute_offset":69,"submatches":[{"match":{"text":"let"},"start":4,"end":7}]}}"#,
+            r#"{"type":"end","data":{"path":{"text":"a.rs"},"binary_offset":null,"stats":{}}}"#,
+            r#"{"type":"match","data":{"path":{"bytes":"/w=="},"lines":{"text":"let\n"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"let"},"start":0,"end":3}]}}"#,
+            r#"{"type":"match","data":{"path":{"text":"b.rs"},"lines":{"text":"let z = 3;"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"let"},"start":0,"end":3}]}}"#,
+            "",
+            r#"{"type":"summary","data":{"elapsed_total":{"secs":0,"nanos":1},"stats":{}}}"#,
+        ]
+        .join("\n");
+
+        let actual = GrepResult::from_ripgrep_json(&fixture).unwrap();
+
+        let expected = vec![
+            GrepResult {
+                context_before: vec!["fn main() {".to_string()],
+                context_after: vec!["}".to_string()],
+                ..result("a.rs", 2, Some(5), "    let x = 1;")
+            },
+            GrepResult {
+                context_before: vec!["fn b() {".to_string()],
+                ..result("a.rs", 10, Some(5), "    let y = 2;")
+            },
+            result("b.rs", 1, Some(1), "let z = 3;"),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_from_ripgrep_json_invalid() {
+        let actual = GrepResult::from_ripgrep_json("not json");
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_format_results() {
+        let fixture = vec![
+            GrepResult {
+                context_before: vec!["fn main() {".to_string()],
+                context_after: vec!["    let y = 2;".to_string()],
+                ..result("src/a.rs", 2, Some(5), "    let x = 1;")
+            },
+            GrepResult {
+                context_before: vec!["    let y = 2;".to_string()],
+                context_after: vec!["}".to_string()],
+                ..result("src/a.rs", 4, Some(5), "    let z = x + y;")
+            },
+            GrepResult {
+                context_before: vec!["fn b() {".to_string()],
+                ..result("src/a.rs", 10, None, "    let w = 0;")
+            },
+            result("src/b.rs", 1, Some(1), "let v = 3;"),
+        ];
+        let grep = GrepFormat::from_results(&fixture).regex(Regex::new("let").unwrap());
+
+        let actual = strip_ansi_escapes::strip_str(grep.format()).to_string();
+
+        let expected = [
+            "src/a.rs-1-fn main() {",
+            "src/a.rs:2:5:     let x = 1;",
+            "src/a.rs-3-    let y = 2;",
+            "src/a.rs:4:5:     let z = x + y;",
+            "src/a.rs-5-}",
+            "--",
+            "src/a.rs-9-fn b() {",
+            "src/a.rs:10:     let w = 0;",
+            "--",
+            "src/b.rs:1:1: let v = 3;",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(actual, expected);
+    }
 }
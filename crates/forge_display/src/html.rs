@@ -0,0 +1,256 @@
+use std::path::Path;
+
+use derive_setters::Setters;
+
+use crate::grep::ParsedLine;
+use crate::GrepFormat;
+
+/// Box around every snippet. Lines keep their whitespace on their own so the
+/// markup between them doesn't add blank lines.
+const CONTAINER: &str = "font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; \
+font-size: 13px; line-height: 1.45; padding: 8px 0; margin: 8px 0; background: #f6f8fa; \
+color: #24292f; border: 1px solid #d0d7de; border-radius: 6px; overflow-x: auto;";
+
+const LINE: &str = "display: block; white-space: pre; padding: 0 12px;";
+
+const HEADER: &str = "display: block; white-space: pre; padding: 0 12px; font-weight: bold;";
+
+const HUNK: &str = "display: block; white-space: pre; padding: 0 12px; color: #0969da; \
+background: #ddf4ff;";
+
+const REMOVED: &str = "display: block; white-space: pre; padding: 0 12px; \
+text-decoration: none; color: #82071e; background: #ffebe9;";
+
+const ADDED: &str = "display: block; white-space: pre; padding: 0 12px; \
+text-decoration: none; color: #116329; background: #dafbe1;";
+
+const CONTEXT: &str = "display: block; white-space: pre; padding: 0 12px; color: #6e7781;";
+
+const LINE_NUMBER: &str = "color: #6e7781;";
+
+/// HtmlFormat renders diffs and search results as self-contained HTML, for
+/// reports that are viewed in a browser rather than the terminal.
+#[derive(Clone, Setters)]
+#[setters(into)]
+pub struct HtmlFormat {
+    /// Title of the pages created by [`HtmlFormat::page`]
+    title: String,
+}
+
+impl Default for HtmlFormat {
+    fn default() -> Self {
+        Self { title: "Forge report".to_string() }
+    }
+}
+
+/// Escapes the characters that HTML gives a meaning to
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Wraps a line of text in `tag`. Empty lines keep a space so that they still
+/// take up a line.
+fn line(tag: &str, style: &str, text: &str) -> String {
+    let text = if text.is_empty() { " " } else { text };
+    format!("<{tag} style=\"{style}\">{}</{tag}>\n", escape(text))
+}
+
+fn container(lines: &str) -> String {
+    format!("<div style=\"{CONTAINER}\">\n{lines}</div>\n")
+}
+
+impl HtmlFormat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders a unified diff, such as the output of `git diff`, marking
+    /// removed lines with `<del>` and added lines with `<ins>`
+    pub fn render_diff(&self, diff: &str) -> String {
+        let mut lines = String::new();
+        // File headers such as `--- a/file` look like removed lines, so the
+        // signs are only read between a hunk header and the next file
+        let mut in_hunk = false;
+        for text in diff.lines() {
+            let rendered = if text.starts_with("@@") {
+                in_hunk = true;
+                line("span", HUNK, text)
+            } else if text.starts_with("diff ") || !in_hunk {
+                in_hunk = false;
+                line("span", HEADER, text)
+            } else if text.starts_with('-') {
+                line("del", REMOVED, text)
+            } else if text.starts_with('+') {
+                line("ins", ADDED, text)
+            } else {
+                line("span", LINE, text)
+            };
+            lines.push_str(&rendered);
+        }
+        container(&lines)
+    }
+
+    /// Renders search results in the `path:line_num:content` format, with
+    /// `path-line_num-content` for context lines, grouped by file the same
+    /// way as [`GrepFormat`]. Results that are only paths are listed as such.
+    pub fn render_grep_result(&self, lines: &[String]) -> String {
+        if !lines.iter().any(|line| ParsedLine::parse(line).is_some()) {
+            let paths: String = lines
+                .iter()
+                .map(|path| line("span", HEADER, path))
+                .collect();
+            return container(&paths);
+        }
+
+        let grep = GrepFormat::new(lines.to_vec());
+        let (entries, max_num_width) = grep.collect_entries();
+
+        let with_context = entries.values().flatten().any(|parsed| !parsed.is_match);
+        let mut groups = String::new();
+        for (path, group) in entries {
+            let mut rendered = line("span", HEADER, path);
+            let mut previous: Option<u64> = None;
+            for parsed in &group {
+                let num = parsed.line_num.parse::<u64>().ok();
+                let is_gap = previous.is_some_and(|previous| num != Some(previous + 1));
+                if with_context && is_gap {
+                    rendered.push_str(&line("span", CONTEXT, "--"));
+                }
+                rendered.push_str(&grep_line(parsed, max_num_width, with_context));
+                previous = num;
+            }
+            groups.push_str(&container(&rendered));
+        }
+        groups
+    }
+
+    /// Wraps rendered snippets in a complete HTML5 page
+    pub fn page(&self, snippet: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n\
+             <html lang=\"en\">\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <title>{}</title>\n\
+             </head>\n\
+             <body style=\"margin: 16px;\">\n\
+             {snippet}\
+             </body>\n\
+             </html>\n",
+            escape(&self.title)
+        )
+    }
+
+    /// Writes rendered snippets to `path` as a complete HTML5 page
+    pub fn render_to_file(&self, snippet: &str, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.page(snippet))
+    }
+}
+
+/// Renders a single search result line. When context is shown, matching lines
+/// are marked with `>` like in the terminal.
+fn grep_line(parsed: &ParsedLine, padding: usize, with_context: bool) -> String {
+    let ParsedLine { line_num: num, content, is_match, .. } = *parsed;
+    let num = match (with_context, is_match) {
+        (false, _) => format!("{num:>padding$}: "),
+        (true, true) => format!("> {num:>padding$}: "),
+        (true, false) => format!("  {num:>padding$}- "),
+    };
+    let style = if is_match { LINE } else { CONTEXT };
+    format!(
+        "<span style=\"{style}\"><span style=\"{LINE_NUMBER}\">{}</span>{}</span>\n",
+        escape(&num),
+        escape(content)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Keeps the tag and text of every rendered line, dropping the styles
+    fn outline(html: &str) -> Vec<String> {
+        html.lines()
+            .filter_map(|line| {
+                let tag = line.strip_prefix('<')?.split([' ', '>']).next()?;
+                let text = line.split_once("\">")?.1.rsplit_once("</")?.0;
+                Some(format!("{tag} {text}"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let fixture = "diff --git a/lib.rs b/lib.rs\n--- a/lib.rs\n+++ b/lib.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    a < b\n+    a <= b\n--- not a header\n }";
+
+        let actual = outline(&HtmlFormat::new().render_diff(fixture));
+
+        let expected = vec![
+            "span diff --git a/lib.rs b/lib.rs",
+            "span --- a/lib.rs",
+            "span +++ b/lib.rs",
+            "span @@ -1,3 +1,3 @@",
+            "span  fn main() {",
+            "del -    a &lt; b",
+            "ins +    a &lt;= b",
+            "del --- not a header",
+            "span  }",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_render_grep_result() {
+        let fixture = vec![
+            "src/main.rs:1:fn main() {".to_string(),
+            "src/main.rs-2-    let x = \"<1>\";".to_string(),
+            "src/lib.rs:10:fn main() {}".to_string(),
+        ];
+
+        let actual = HtmlFormat::new().render_grep_result(&fixture);
+
+        assert_eq!(actual.matches(CONTAINER).count(), 2);
+        assert!(actual.contains("&gt;  1: </span>fn main() {</span>"));
+        assert!(actual.contains("   2- </span>let x = &quot;&lt;1&gt;&quot;;</span>"));
+        assert!(actual.find("src/lib.rs").unwrap() < actual.find("src/main.rs").unwrap());
+    }
+
+    #[test]
+    fn test_render_grep_result_paths_only() {
+        let fixture = vec!["src/main.rs".to_string(), "src/lib.rs".to_string()];
+
+        let actual = outline(&HtmlFormat::new().render_grep_result(&fixture));
+
+        let expected = vec!["span src/main.rs", "span src/lib.rs"];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_render_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.html");
+        let format = HtmlFormat::new().title("Review of <main>");
+        let snippet = format.render_diff("@@ -1 +1 @@\n-a\n+b");
+
+        format.render_to_file(&snippet, &path).unwrap();
+
+        let actual = std::fs::read_to_string(&path).unwrap();
+        assert!(actual.starts_with("<!DOCTYPE html>\n<html lang=\"en\">\n"));
+        assert!(actual.contains("<title>Review of &lt;main&gt;</title>"));
+        assert!(actual.contains(&format!("<body style=\"margin: 16px;\">\n{snippet}</body>")));
+        assert!(actual.ends_with("</html>\n"));
+    }
+}
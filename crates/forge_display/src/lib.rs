@@ -1,9 +1,16 @@
+pub mod ansi;
 pub mod diff;
 pub mod grep;
+pub mod html;
 pub mod markdown;
 pub mod title;
+pub mod truncate;
+mod width;
 
-pub use diff::DiffFormat;
-pub use grep::GrepFormat;
-pub use markdown::MarkdownFormat;
+pub use ansi::{strip_ansi, strip_ansi_bytes};
+pub use diff::{detect_language_from_path, DiffFormat};
+pub use grep::{GrepFormat, GrepResult};
+pub use html::HtmlFormat;
+pub use markdown::{render_code_block, render_table, MarkdownFormat};
 pub use title::*;
+pub use truncate::{truncate, truncate_lines};
@@ -1,3 +1,4 @@
+use console::measure_text_width;
 use derive_setters::Setters;
 use regex::Regex;
 use termimad::crossterm::style::{Attribute, Color};
@@ -44,6 +45,16 @@ impl MarkdownFormat {
             .to_string()
     }
 
+    /// Renders a GFM pipe table, see [`render_table`]
+    pub fn render_table(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        render_table(headers, rows)
+    }
+
+    /// Renders a fenced code block, see [`render_code_block`]
+    pub fn render_code_block(&self, code: &str, language: &str) -> String {
+        render_code_block(code, language)
+    }
+
     /// Strip excessive consecutive newlines from content
     ///
     /// Reduces any sequence of more than max_consecutive_newlines to exactly
@@ -61,6 +72,76 @@ impl MarkdownFormat {
     }
 }
 
+/// Smallest width of a column, so that the delimiter row stays valid
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// Makes text safe to put in a table cell, which can't span lines or contain
+/// an unescaped pipe
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace("\r\n", " ")
+        .replace('\n', " ")
+}
+
+fn table_row(cells: &[String], widths: &[usize]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| {
+            let padding = width.saturating_sub(measure_text_width(cell));
+            format!("{cell}{}", " ".repeat(padding))
+        })
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+/// Renders a GFM pipe table with every column as wide as its longest cell.
+/// Rows have one cell per header: missing cells are left empty and extra cells
+/// are dropped.
+pub fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let headers: Vec<String> = headers.iter().map(|header| table_cell(header)).collect();
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            (0..headers.len())
+                .map(|i| row.get(i).map(|cell| table_cell(cell)).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|i| {
+            std::iter::once(&headers[i])
+                .chain(rows.iter().map(|row| &row[i]))
+                .map(|cell| measure_text_width(cell))
+                .max()
+                .unwrap_or_default()
+                .max(MIN_COLUMN_WIDTH)
+        })
+        .collect();
+
+    let delimiter: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    let mut table = table_row(&headers, &widths);
+    table.push_str(&table_row(&delimiter, &widths));
+    for row in &rows {
+        table.push_str(&table_row(row, &widths));
+    }
+    table
+}
+
+/// Renders a fenced code block. The fence is longer than any run of backticks
+/// in `code`, so the code can't close the block early.
+pub fn render_code_block(code: &str, language: &str) -> String {
+    let longest_run = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let newline = if code.ends_with('\n') { "" } else { "\n" };
+    format!("{fence}{language}\n{code}{newline}{fence}\n")
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -142,4 +223,49 @@ mod tests {
 
         assert_eq!(actual_clean, expected_clean);
     }
+
+    #[test]
+    fn test_render_table() {
+        let headers = ["Model", "Cost"];
+        let rows = vec![
+            vec!["gpt-4o".to_string(), "$2.50".to_string()],
+            vec!["claude | sonnet".to_string()],
+            vec!["o1".to_string(), "$15".to_string(), "ignored".to_string()],
+        ];
+
+        let actual = MarkdownFormat::new().render_table(&headers, &rows);
+
+        let expected = "\
+| Model            | Cost  |
+| ---------------- | ----- |
+| gpt-4o           | $2.50 |
+| claude \\| sonnet |       |
+| o1               | $15   |
+";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_render_table_min_width() {
+        let actual = render_table(&["a"], &[vec!["x\ny".to_string()]]);
+
+        let expected = "| a   |\n| --- |\n| x y |\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_render_code_block() {
+        let actual = MarkdownFormat::new().render_code_block("fn main() {}", "rust");
+
+        let expected = "```rust\nfn main() {}\n```\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_render_code_block_with_fence() {
+        let actual = render_code_block("```sh\nls\n```\n", "markdown");
+
+        let expected = "````markdown\n```sh\nls\n```\n````\n";
+        assert_eq!(actual, expected);
+    }
 }
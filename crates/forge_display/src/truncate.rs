@@ -0,0 +1,126 @@
+/// Shortens `text` to at most `max_chars` characters, cutting at the last word
+/// boundary that fits and appending `ellipsis`. A single word longer than
+/// `max_chars` is cut in the middle. Text that already fits is returned as is,
+/// and the ellipsis isn't counted towards `max_chars`.
+pub fn truncate(text: &str, max_chars: usize, ellipsis: &str) -> String {
+    // Byte offset of the character right after the first `max_chars`
+    let Some((limit, next)) = text.char_indices().nth(max_chars) else {
+        return text.to_string();
+    };
+
+    // The cut falls between two words when the next character is a space,
+    // otherwise it goes back to the space before the word it splits
+    let end = if next.is_whitespace() {
+        limit
+    } else {
+        text[..limit]
+            .rfind(char::is_whitespace)
+            .filter(|&space| !text[..space].trim_end().is_empty())
+            .unwrap_or(limit)
+    };
+
+    format!("{}{ellipsis}", text[..end].trim_end())
+}
+
+/// Keeps the first `max_lines` lines of `text`, dropping the line break after
+/// the last one kept
+pub fn truncate_lines(text: &str, max_lines: usize) -> String {
+    if max_lines == 0 {
+        return String::new();
+    }
+    match text.match_indices('\n').nth(max_lines - 1) {
+        // A line break that ends the text doesn't start another line
+        Some((end, _)) if end + 1 < text.len() => text[..end].trim_end_matches('\r').to_string(),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_truncate_fits() {
+        let fixture = "short text";
+
+        let actual = [truncate(fixture, 10, "..."), truncate(fixture, 50, "...")];
+
+        let expected = ["short text", "short text"];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_truncate_at_word_boundary() {
+        let fixture = "the quick brown fox jumps";
+
+        let actual = [
+            truncate(fixture, 12, "..."),
+            truncate(fixture, 15, "..."),
+            truncate(fixture, 9, "…"),
+            truncate(fixture, 10, "…"),
+        ];
+
+        let expected = [
+            "the quick...",
+            "the quick brown...",
+            "the quick…",
+            "the quick…",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_truncate_long_word() {
+        let fixture = "incomprehensibilities abound";
+
+        let actual = truncate(fixture, 6, "...");
+
+        assert_eq!(actual, "incomp...");
+    }
+
+    #[test]
+    fn test_truncate_multibyte() {
+        let fixture = "héllo wörld ünïcode 🦀🦀🦀";
+
+        let actual = [
+            truncate(fixture, 14, "…"),
+            truncate("🦀🦀🦀🦀", 2, "…"),
+            truncate("日本語のテキスト", 3, ""),
+        ];
+
+        let expected = ["héllo wörld…", "🦀🦀…", "日本語"];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_truncate_zero() {
+        assert_eq!(truncate("anything", 0, "..."), "...");
+        assert_eq!(truncate("", 0, "..."), "");
+    }
+
+    #[test]
+    fn test_truncate_lines() {
+        let fixture = "first\nsecond\r\nthird\nfourth";
+
+        let actual = [
+            truncate_lines(fixture, 0),
+            truncate_lines(fixture, 2),
+            truncate_lines(fixture, 4),
+            truncate_lines(fixture, 10),
+            truncate_lines("one\ntwo\n", 2),
+            truncate_lines("ünï\ncödé", 1),
+        ];
+
+        let expected = [
+            "",
+            "first\nsecond",
+            "first\nsecond\r\nthird\nfourth",
+            "first\nsecond\r\nthird\nfourth",
+            "one\ntwo\n",
+            "ünï",
+        ];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,12 @@
+use terminal_size::{terminal_size, Width};
+
+/// Width assumed when output isn't going to a terminal
+const DEFAULT_WIDTH: u16 = 120;
+
+/// Returns `width`, falling back to the width of the terminal when it isn't
+/// given
+pub(crate) fn resolve(width: Option<u16>) -> usize {
+    width
+        .or_else(|| terminal_size().map(|(Width(width), _)| width))
+        .unwrap_or(DEFAULT_WIDTH) as usize
+}
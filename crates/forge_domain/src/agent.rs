@@ -10,8 +10,8 @@ use crate::merge::Key;
 use crate::temperature::Temperature;
 use crate::template::Template;
 use crate::{
-    Context, Error, Event, EventContext, ModelId, Result, Role, SystemContext, ToolDefinition,
-    ToolName, TopK, TopP,
+    CommandPolicy, Context, Error, Event, EventContext, ModelId, Result, Role, SystemContext,
+    ToolDefinition, ToolName, TopK, TopP,
 };
 
 // Unique identifier for an agent
@@ -47,6 +47,13 @@ pub struct Compact {
     #[merge(strategy = crate::merge::option)]
     pub token_threshold: Option<u64>,
 
+    /// Fraction of the model's context window (0.0 to 1.0) that, once
+    /// reached, triggers compaction. For eg: 0.8 compacts the context when it
+    /// uses 80% of the model's context length.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub context_window_ratio: Option<f64>,
+
     /// Maximum number of conversation turns before triggering compaction
     #[serde(skip_serializing_if = "Option::is_none")]
     #[merge(strategy = crate::merge::option)]
@@ -96,6 +103,7 @@ impl Compact {
         Self {
             max_tokens: None,
             token_threshold: None,
+            context_window_ratio: None,
             turn_threshold: None,
             message_threshold: None,
             prompt: None,
@@ -105,9 +113,21 @@ impl Compact {
         }
     }
 
+    /// Returns the number of tokens at which compaction is triggered for a
+    /// model with the given context length, if a ratio is configured
+    pub fn context_window_threshold(&self, context_length: u64) -> Option<u64> {
+        self.context_window_ratio
+            .map(|ratio| (context_length as f64 * ratio.clamp(0.0, 1.0)) as u64)
+    }
+
     /// Determines if compaction should be triggered based on the current
-    /// context
-    pub fn should_compact(&self, context: &Context, token_count: u64) -> bool {
+    /// context. `context_length` is the model's context window, when known.
+    pub fn should_compact(
+        &self,
+        context: &Context,
+        token_count: u64,
+        context_length: Option<u64>,
+    ) -> bool {
         // Check if any of the thresholds have been exceeded
         if let Some(token_threshold) = self.token_threshold {
             debug!(tokens = ?token_count, "Token count");
@@ -117,6 +137,15 @@ impl Compact {
             }
         }
 
+        if let Some(threshold) =
+            context_length.and_then(|length| self.context_window_threshold(length))
+        {
+            debug!(tokens = ?token_count, threshold, "Context window threshold");
+            if token_count >= threshold {
+                return true;
+            }
+        }
+
         if let Some(turn_threshold) = self.turn_threshold {
             if context
                 .messages
@@ -209,6 +238,12 @@ pub struct Agent {
     #[merge(strategy = crate::merge::option)]
     pub max_turns: Option<u64>,
 
+    /// Maximum number of consecutive think tool calls before the agent is
+    /// asked to act. Defaults to 3.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub max_consecutive_thinks: Option<usize>,
+
     /// Maximum depth to which the file walker should traverse for this agent
     /// If not provided, the maximum possible depth will be used
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -264,6 +299,18 @@ pub struct Agent {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[merge(strategy = crate::merge::option)]
     pub top_k: Option<TopK>,
+
+    /// Environment variables holding provider API keys that shell commands
+    /// run by the agent may inherit, see `SECRET_ENV_VARS`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub env_allowlist: Option<Vec<String>>,
+
+    /// Which shell commands the agent may run without asking, see
+    /// [`CommandPolicy`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub command_policy: Option<CommandPolicy>,
 }
 
 fn merge_subscription(base: &mut Option<Vec<String>>, other: Option<Vec<String>>) {
@@ -277,6 +324,10 @@ fn merge_subscription(base: &mut Option<Vec<String>>, other: Option<Vec<String>>
 }
 
 impl Agent {
+    /// Number of consecutive think calls allowed when the agent doesn't
+    /// configure `max_consecutive_thinks`
+    pub const DEFAULT_MAX_CONSECUTIVE_THINKS: usize = 3;
+
     pub fn new(id: impl ToString) -> Self {
         Self {
             id: AgentId::new(id),
@@ -291,6 +342,7 @@ impl Agent {
             // transforms field removed
             subscribe: None,
             max_turns: None,
+            max_consecutive_thinks: None,
             max_walker_depth: None,
             compact: None,
             custom_rules: None,
@@ -298,6 +350,8 @@ impl Agent {
             temperature: None,
             top_p: None,
             top_k: None,
+            env_allowlist: None,
+            command_policy: None,
         }
     }
 
@@ -309,10 +363,15 @@ impl Agent {
             .description(self.description.clone().unwrap()))
     }
     /// Checks if compaction should be applied
-    pub fn should_compact(&self, context: &Context, token_count: u64) -> bool {
+    pub fn should_compact(
+        &self,
+        context: &Context,
+        token_count: u64,
+        context_length: Option<u64>,
+    ) -> bool {
         // Return false if compaction is not configured
         if let Some(compact) = &self.compact {
-            compact.should_compact(context, token_count)
+            compact.should_compact(context, token_count, context_length)
         } else {
             false
         }
@@ -634,4 +693,19 @@ mod tests {
         let agent: Agent = serde_json::from_value(json).unwrap();
         assert_eq!(agent.top_k, None);
     }
+
+    #[test]
+    fn test_should_compact_context_window_ratio() {
+        let fixture = Compact::new(ModelId::new("test-model")).context_window_ratio(0.8);
+        let context = Context::default();
+
+        let actual = (
+            fixture.should_compact(&context, 7_999, Some(10_000)),
+            fixture.should_compact(&context, 8_000, Some(10_000)),
+            fixture.should_compact(&context, 8_000, None),
+        );
+
+        let expected = (false, true, false);
+        assert_eq!(actual, expected);
+    }
 }
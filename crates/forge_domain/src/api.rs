@@ -65,6 +65,15 @@ pub trait API: Sync + Send {
         conversation_id: &ConversationId,
     ) -> Result<CompactionResult>;
 
+    /// Pins the most recent user message of the main agent so that it is kept
+    /// verbatim when the context is compacted. Returns the content of the
+    /// pinned message, or `None` if there is nothing to pin.
+    async fn pin_last_message(&self, conversation_id: &ConversationId) -> Result<Option<String>>;
+
+    /// Approves the plan proposed by the agent so that it can be carried out.
+    /// Returns the approved plan, or `None` if no plan was proposed.
+    async fn approve_plan(&self, conversation_id: &ConversationId) -> Result<Option<Plan>>;
+
     /// Executes a shell command using the shell tool infrastructure
     async fn execute_shell_command(
         &self,
@@ -1,6 +1,6 @@
 use serde::Serialize;
 
-use crate::{ToolCallFull, ToolResult, Usage};
+use crate::{OutputLine, ToolCallFull, ToolResult, Usage};
 
 /// Events that are emitted by the agent for external consumption. This includes
 /// events for all internal state changes.
@@ -13,7 +13,15 @@ pub enum ChatResponse {
         is_md: bool,
         is_summary: bool,
     },
+    /// The provider declined to answer on policy grounds, with its
+    /// explanation when it gave one
+    Refusal {
+        reason: Option<String>,
+    },
     ToolCallStart(ToolCallFull),
+    /// A line printed by a command that a tool is still running, so that it
+    /// can be shown as it arrives
+    ToolOutputChunk(OutputLine),
     ToolCallEnd(ToolResult),
     Usage(Usage),
 }
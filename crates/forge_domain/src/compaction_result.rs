@@ -31,13 +31,17 @@ impl CompactionResult {
         }
     }
 
+    /// Number of tokens removed by the compaction, zero if the context grew
+    pub fn tokens_saved(&self) -> usize {
+        self.original_tokens.saturating_sub(self.compacted_tokens)
+    }
+
     /// Calculate the percentage reduction in tokens
     pub fn token_reduction_percentage(&self) -> f64 {
         if self.original_tokens == 0 {
             return 0.0;
         }
-        ((self.original_tokens - self.compacted_tokens) as f64 / self.original_tokens as f64)
-            * 100.0
+        (self.tokens_saved() as f64 / self.original_tokens as f64) * 100.0
     }
 
     /// Calculate the percentage reduction in messages
@@ -45,7 +49,10 @@ impl CompactionResult {
         if self.original_messages == 0 {
             return 0.0;
         }
-        ((self.original_messages - self.compacted_messages) as f64 / self.original_messages as f64)
+        (self
+            .original_messages
+            .saturating_sub(self.compacted_messages) as f64
+            / self.original_messages as f64)
             * 100.0
     }
 }
@@ -64,6 +71,10 @@ mod tests {
         // Edge case: no original tokens
         let result = CompactionResult::new(0, 0, 20, 10);
         assert_eq!(result.token_reduction_percentage(), 0.0);
+
+        // Edge case: the summary is longer than what it replaced
+        let result = CompactionResult::new(100, 120, 3, 2);
+        assert_eq!(result.token_reduction_percentage(), 0.0);
     }
 
     #[test]
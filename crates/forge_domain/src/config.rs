@@ -0,0 +1,927 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use derive_setters::Setters;
+use merge::Merge;
+use schemars::schema::RootSchema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    Migrated, Migration, MigrationError, Migrator, ModelId, RetryConfig, Temperature,
+    ToolOutputLimit, TopK, TopP,
+};
+
+/// Current schema version of the config file
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Default timeout applied to a single tool call
+const DEFAULT_TOOL_TIMEOUT_SECS: u64 = 300;
+
+/// Default timeout of a shell command run by the agent
+const DEFAULT_SHELL_TIMEOUT_SECS: u64 = 300;
+
+/// Largest response body downloaded from the web unless configured otherwise
+const DEFAULT_FETCH_MAX_BYTES: usize = 512 * 1024;
+
+/// Prefix used by all environment variables that configure forge
+const ENV_PREFIX: &str = "FORGE_";
+
+/// How snapshots of files are compressed when they are stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Stored as a plain copy of the file
+    #[default]
+    None,
+    /// Compressed with zstd, which is fast to compress and decompress
+    Zstd,
+    /// Compressed with gzip, which is slower but more widely supported
+    Gzip,
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "zstd" => Ok(Self::Zstd),
+            "gzip" => Ok(Self::Gzip),
+            _ => Err(format!(
+                "unknown compression '{value}', expected none, zstd or gzip"
+            )),
+        }
+    }
+}
+
+/// Decides which of the configured providers serve a request when more than
+/// one has a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackStrategy {
+    /// Tries the providers in order, moving to the next one only while they
+    /// are unavailable
+    FirstSuccess,
+    /// Sends the request to every provider at once and uses the first to
+    /// respond successfully. Every provider is billed for the request.
+    Fastest,
+    /// Like `first_success`, but every request starts with the provider after
+    /// the one that started the previous request
+    RoundRobin,
+}
+
+impl FromStr for FallbackStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "first_success" => Ok(Self::FirstSuccess),
+            "fastest" => Ok(Self::Fastest),
+            "round_robin" => Ok(Self::RoundRobin),
+            _ => Err(format!(
+                "unknown fallback strategy '{value}', expected first_success, fastest or round_robin"
+            )),
+        }
+    }
+}
+
+/// Identifies where a configuration value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    File,
+    Env,
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::File => write!(f, "config file"),
+            ConfigSource::Env => write!(f, "environment"),
+            ConfigSource::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to parse config file: {0}")]
+    File(serde_yml::Error),
+
+    #[error("Invalid config file: {0}")]
+    Document(serde_json::Error),
+
+    #[error("Failed to upgrade config file: {0}")]
+    Migration(MigrationError),
+
+    #[error("Invalid value {value:?} for environment variable {key}: {message}")]
+    Env {
+        key: String,
+        value: String,
+        message: String,
+    },
+
+    #[error("Invalid value for `{key}` in {origin}: {message}")]
+    Invalid {
+        origin: ConfigSource,
+        key: &'static str,
+        message: String,
+    },
+}
+
+/// A partially specified configuration as provided by a single source.
+///
+/// Every field is optional so that layers can be stacked on top of each other;
+/// values from a higher priority layer overwrite those of a lower one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Merge, Setters, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(title = "Forge configuration")]
+#[setters(strip_option, into)]
+pub struct ConfigLayer {
+    /// Schema version of the config file, older files are upgraded when loaded
+    #[merge(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+
+    /// Default model ID to use for all agents
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelId>,
+
+    /// Sampling temperature, valid range is 0.0 to 2.0
+    #[schemars(range(min = 0.0, max = 2.0))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Nucleus sampling threshold, valid range is 0.0 to 1.0
+    #[schemars(range(min = 0.0, max = 1.0))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Number of most likely tokens to sample from, valid range is 1 to 1000
+    #[schemars(range(min = 1, max = 1000))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+
+    /// Maximum time in seconds a single tool call is allowed to run
+    #[schemars(range(min = 1))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_timeout_secs: Option<u64>,
+
+    /// Use a restricted shell (rbash) for command execution
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restricted: Option<bool>,
+
+    /// Show additional debugging information
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbose: Option<bool>,
+
+    /// Initial backoff delay in milliseconds for retry operations
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_initial_backoff_ms: Option<u64>,
+
+    /// Backoff multiplication factor for each retry attempt
+    #[schemars(range(min = 1))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_backoff_factor: Option<u64>,
+
+    /// Maximum number of retry attempts
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_max_attempts: Option<usize>,
+
+    /// HTTP status codes that should trigger retries
+    #[schemars(inner(range(min = 100, max = 599)))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_status_codes: Option<Vec<u16>>,
+
+    /// Gitignore style patterns of noisy files skipped by listings and
+    /// searches, added to the built-in ones. Patterns starting with `!` bring
+    /// back files skipped by default.
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_patterns: Option<Vec<String>>,
+
+    /// Seconds a shell command run by the agent may take unless the call sets
+    /// its own timeout
+    #[schemars(range(min = 1))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell_timeout_secs: Option<u64>,
+
+    /// Check code written by the agent for syntax errors
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub syntax_check: Option<bool>,
+
+    /// Maximum number of bytes kept from the output of a tool call
+    #[schemars(range(min = 1))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_output_max_bytes: Option<usize>,
+
+    /// Maximum number of lines kept from the output of a tool call
+    #[schemars(range(min = 1))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_output_max_lines: Option<usize>,
+
+    /// Write the full output of a clipped tool call to a temp file
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_output_save_full: Option<bool>,
+
+    /// Number of lines kept from the start of a clipped shell command output
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell_head_lines: Option<usize>,
+
+    /// Number of lines kept from the end of a clipped shell command output
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell_tail_lines: Option<usize>,
+
+    /// How snapshots of files are compressed when they are stored
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_compression: Option<Compression>,
+
+    /// Largest response body downloaded by the fetch tool or when a URL is
+    /// read in place of a file
+    #[schemars(range(min = 1))]
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_max_bytes: Option<usize>,
+
+    /// Serves requests from every provider with a key, in the order the keys
+    /// are looked up, instead of only the first one
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_fallback: Option<FallbackStrategy>,
+}
+
+impl ConfigLayer {
+    /// JSON schema of the config file, used by editors for autocompletion and
+    /// validation
+    pub fn json_schema() -> RootSchema {
+        schemars::schema_for!(ConfigLayer)
+    }
+
+    /// Parses a layer from the YAML contents of a config file, upgrading files
+    /// written for an older schema version
+    pub fn from_yaml(content: &str) -> Result<Self, ConfigError> {
+        let document = Self::migrate(content)?.document;
+        Self::deserialize(document).map_err(ConfigError::Document)
+    }
+
+    /// Returns the contents of the config file upgraded to the current schema
+    /// version, or `None` if the file is already up to date and doesn't need
+    /// to be written back
+    pub fn upgrade_yaml(content: &str) -> Result<Option<String>, ConfigError> {
+        let migrated = Self::migrate(content)?;
+        if !migrated.upgraded {
+            return Ok(None);
+        }
+
+        // Make sure the upgraded document is valid before it replaces the file
+        let layer = Self::deserialize(migrated.document).map_err(ConfigError::Document)?;
+        serde_yml::to_string(&layer)
+            .map(Some)
+            .map_err(ConfigError::File)
+    }
+
+    fn migrate(content: &str) -> Result<Migrated, ConfigError> {
+        let document = match serde_yml::from_str(content).map_err(ConfigError::File)? {
+            // An empty file is an empty config
+            Value::Null => Value::Object(Default::default()),
+            document => document,
+        };
+
+        Migrator::new(CONFIG_VERSION)
+            .register(RetryStatusCodesList)
+            .migrate(document)
+            .map_err(ConfigError::Migration)
+    }
+
+    /// Builds a layer from `FORGE_*` environment variables. The variable name
+    /// is the upper-cased key prefixed with `FORGE_`, for eg:
+    /// `FORGE_TEMPERATURE` or `FORGE_RETRY_MAX_ATTEMPTS`.
+    pub fn from_env(env: &HashMap<String, String>) -> Result<Self, ConfigError> {
+        Ok(Self {
+            version: None,
+            model: parse_env::<String>(env, "model")?.map(ModelId::new),
+            temperature: parse_env(env, "temperature")?,
+            top_p: parse_env(env, "top_p")?,
+            top_k: parse_env(env, "top_k")?,
+            tool_timeout_secs: parse_env(env, "tool_timeout_secs")?,
+            restricted: parse_env(env, "restricted")?,
+            verbose: parse_env(env, "verbose")?,
+            retry_initial_backoff_ms: parse_env(env, "retry_initial_backoff_ms")?,
+            retry_backoff_factor: parse_env(env, "retry_backoff_factor")?,
+            retry_max_attempts: parse_env(env, "retry_max_attempts")?,
+            retry_status_codes: parse_env_list(env, "retry_status_codes")?,
+            ignore_patterns: parse_env_list(env, "ignore_patterns")?,
+            shell_timeout_secs: parse_env(env, "shell_timeout_secs")?,
+            syntax_check: parse_env(env, "syntax_check")?,
+            tool_output_max_bytes: parse_env(env, "tool_output_max_bytes")?,
+            tool_output_max_lines: parse_env(env, "tool_output_max_lines")?,
+            tool_output_save_full: parse_env(env, "tool_output_save_full")?,
+            shell_head_lines: parse_env(env, "shell_head_lines")?,
+            shell_tail_lines: parse_env(env, "shell_tail_lines")?,
+            snapshot_compression: parse_env(env, "snapshot_compression")?,
+            fetch_max_bytes: parse_env(env, "fetch_max_bytes")?,
+            provider_fallback: parse_env(env, "provider_fallback")?,
+        })
+    }
+
+    /// Checks that every value present in this layer is within its valid
+    /// range, reporting the offending key and its origin on failure.
+    pub fn validate(&self, origin: ConfigSource) -> Result<(), ConfigError> {
+        let invalid =
+            |key: &'static str, message: String| ConfigError::Invalid { origin, key, message };
+
+        if let Some(value) = self.temperature {
+            Temperature::new(value).map_err(|e| invalid("temperature", e))?;
+        }
+        if let Some(value) = self.top_p {
+            TopP::new(value).map_err(|e| invalid("top_p", e))?;
+        }
+        if let Some(value) = self.top_k {
+            TopK::new(value).map_err(|e| invalid("top_k", e))?;
+        }
+        if self.tool_timeout_secs == Some(0) {
+            return Err(invalid(
+                "tool_timeout_secs",
+                "must be greater than 0".to_string(),
+            ));
+        }
+        if self.retry_backoff_factor == Some(0) {
+            return Err(invalid(
+                "retry_backoff_factor",
+                "must be greater than 0".to_string(),
+            ));
+        }
+        if self.shell_timeout_secs == Some(0) {
+            return Err(invalid(
+                "shell_timeout_secs",
+                "must be greater than 0".to_string(),
+            ));
+        }
+        if self.tool_output_max_bytes == Some(0) {
+            return Err(invalid(
+                "tool_output_max_bytes",
+                "must be greater than 0".to_string(),
+            ));
+        }
+        if self.tool_output_max_lines == Some(0) {
+            return Err(invalid(
+                "tool_output_max_lines",
+                "must be greater than 0".to_string(),
+            ));
+        }
+        if self.fetch_max_bytes == Some(0) {
+            return Err(invalid(
+                "fetch_max_bytes",
+                "must be greater than 0".to_string(),
+            ));
+        }
+        if let Some(code) = self
+            .retry_status_codes
+            .iter()
+            .flatten()
+            .find(|code| !(100..=599).contains(*code))
+        {
+            return Err(invalid(
+                "retry_status_codes",
+                format!("{code} is not a valid HTTP status code"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// The fully resolved forge configuration with defaults applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Setters)]
+#[setters(strip_option, into)]
+pub struct Config {
+    /// Default model ID, used when the workflow doesn't set one
+    pub model: Option<ModelId>,
+
+    /// Sampling temperature, if not specified the provider's default is used
+    pub temperature: Option<Temperature>,
+
+    /// Top-p, if not specified the provider's default is used
+    pub top_p: Option<TopP>,
+
+    /// Top-k, if not specified the provider's default is used
+    pub top_k: Option<TopK>,
+
+    /// Maximum time in seconds a single tool call is allowed to run.
+    /// Defaults to 300 seconds.
+    pub tool_timeout_secs: u64,
+
+    /// Use a restricted shell for command execution. Defaults to false.
+    pub restricted: bool,
+
+    /// Show additional debugging information. Defaults to false.
+    pub verbose: bool,
+
+    /// Configuration for the retry mechanism
+    pub retry: RetryConfig,
+
+    /// Ignore patterns added to the built-in ones. Defaults to none.
+    pub ignore_patterns: Vec<String>,
+
+    /// Seconds a shell command run by the agent may take unless the call sets
+    /// its own timeout. Defaults to 300 seconds.
+    pub shell_timeout_secs: u64,
+
+    /// Check code written by the agent for syntax errors. Defaults to true.
+    pub syntax_check: bool,
+
+    /// Budget for the output of a single tool call
+    pub tool_output_limit: ToolOutputLimit,
+
+    /// How snapshots of files are compressed when they are stored. Defaults
+    /// to none.
+    pub snapshot_compression: Compression,
+
+    /// Largest response body downloaded by the fetch tool or when a URL is
+    /// read in place of a file. Defaults to 512 KB.
+    pub fetch_max_bytes: usize,
+
+    /// How requests are spread over the providers that have a key. Defaults
+    /// to none, using only the first provider found.
+    pub provider_fallback: Option<FallbackStrategy>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            model: None,
+            temperature: None,
+            top_p: None,
+            top_k: None,
+            tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+            restricted: false,
+            verbose: false,
+            retry: RetryConfig::default(),
+            ignore_patterns: Vec::new(),
+            shell_timeout_secs: DEFAULT_SHELL_TIMEOUT_SECS,
+            syntax_check: true,
+            tool_output_limit: ToolOutputLimit::default(),
+            snapshot_compression: Compression::default(),
+            fetch_max_bytes: DEFAULT_FETCH_MAX_BYTES,
+            provider_fallback: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the configuration by layering the contents of a config file, the
+    /// environment and command line overrides, in increasing order of
+    /// priority. Each layer is validated before it is applied so that errors
+    /// point at the source that provided the offending value.
+    pub fn load(
+        file: Option<&str>,
+        env: &HashMap<String, String>,
+        cli: ConfigLayer,
+    ) -> Result<Self, ConfigError> {
+        let mut layer = ConfigLayer::default();
+
+        if let Some(content) = file {
+            let file = ConfigLayer::from_yaml(content)?;
+            file.validate(ConfigSource::File)?;
+            layer.merge(file);
+        }
+
+        let env = ConfigLayer::from_env(env)?;
+        env.validate(ConfigSource::Env)?;
+        layer.merge(env);
+
+        cli.validate(ConfigSource::Cli)?;
+        layer.merge(cli);
+
+        Ok(Self::from(layer))
+    }
+}
+
+impl From<ConfigLayer> for Config {
+    fn from(layer: ConfigLayer) -> Self {
+        let default = Self::default();
+        let retry = default.retry;
+        let limit = default.tool_output_limit;
+        Self {
+            model: layer.model,
+            temperature: layer.temperature.map(Temperature::new_unchecked),
+            top_p: layer.top_p.map(TopP::new_unchecked),
+            top_k: layer.top_k.map(TopK::new_unchecked),
+            tool_timeout_secs: layer.tool_timeout_secs.unwrap_or(default.tool_timeout_secs),
+            restricted: layer.restricted.unwrap_or(default.restricted),
+            verbose: layer.verbose.unwrap_or(default.verbose),
+            retry: RetryConfig {
+                initial_backoff_ms: layer
+                    .retry_initial_backoff_ms
+                    .unwrap_or(retry.initial_backoff_ms),
+                backoff_factor: layer.retry_backoff_factor.unwrap_or(retry.backoff_factor),
+                max_retry_attempts: layer.retry_max_attempts.unwrap_or(retry.max_retry_attempts),
+                retry_status_codes: layer.retry_status_codes.unwrap_or(retry.retry_status_codes),
+            },
+            ignore_patterns: layer.ignore_patterns.unwrap_or(default.ignore_patterns),
+            shell_timeout_secs: layer
+                .shell_timeout_secs
+                .unwrap_or(default.shell_timeout_secs),
+            syntax_check: layer.syntax_check.unwrap_or(default.syntax_check),
+            tool_output_limit: ToolOutputLimit {
+                max_bytes: layer.tool_output_max_bytes.unwrap_or(limit.max_bytes),
+                max_lines: layer.tool_output_max_lines.unwrap_or(limit.max_lines),
+                save_full_output: layer
+                    .tool_output_save_full
+                    .unwrap_or(limit.save_full_output),
+                shell_head_lines: layer.shell_head_lines.unwrap_or(limit.shell_head_lines),
+                shell_tail_lines: layer.shell_tail_lines.unwrap_or(limit.shell_tail_lines),
+            },
+            snapshot_compression: layer
+                .snapshot_compression
+                .unwrap_or(default.snapshot_compression),
+            fetch_max_bytes: layer.fetch_max_bytes.unwrap_or(default.fetch_max_bytes),
+            provider_fallback: layer.provider_fallback.or(default.provider_fallback),
+        }
+    }
+}
+
+/// Version 1 config files listed `retry_status_codes` as a comma separated
+/// string, the same format used by `FORGE_RETRY_STATUS_CODES`
+struct RetryStatusCodesList;
+
+impl Migration for RetryStatusCodesList {
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn migrate(&self, mut document: Value) -> Result<Value, MigrationError> {
+        if let Some(Value::String(codes)) = document.get("retry_status_codes") {
+            let codes = codes
+                .split(',')
+                .map(|code| code.trim().parse::<u16>().map(Value::from))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| MigrationError::Failed {
+                    version: self.version(),
+                    message: format!("invalid `retry_status_codes`: {error}"),
+                })?;
+            document["retry_status_codes"] = Value::Array(codes);
+        }
+
+        Ok(document)
+    }
+}
+
+fn env_key(key: &str) -> String {
+    format!("{ENV_PREFIX}{}", key.to_uppercase())
+}
+
+fn parse_env<T>(env: &HashMap<String, String>, key: &str) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let key = env_key(key);
+    env.get(&key)
+        .map(|value| {
+            value.trim().parse::<T>().map_err(|e| ConfigError::Env {
+                key: key.clone(),
+                value: value.clone(),
+                message: e.to_string(),
+            })
+        })
+        .transpose()
+}
+
+fn parse_env_list<T>(
+    env: &HashMap<String, String>,
+    key: &str,
+) -> Result<Option<Vec<T>>, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let key = env_key(key);
+    env.get(&key)
+        .map(|value| {
+            // Empty items, such as the one left by a trailing comma, are skipped
+            value
+                .split(',')
+                .filter(|item| !item.trim().is_empty())
+                .map(|item| {
+                    item.trim().parse::<T>().map_err(|e| ConfigError::Env {
+                        key: key.clone(),
+                        value: value.clone(),
+                        message: e.to_string(),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_load_defaults() {
+        let actual = Config::load(None, &HashMap::new(), ConfigLayer::default()).unwrap();
+        let expected = Config::default();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_layering_precedence() {
+        let file = r#"
+model: file-model
+temperature: 0.2
+tool_timeout_secs: 10
+retry_max_attempts: 5
+"#;
+        let env = env(&[
+            ("FORGE_TEMPERATURE", "0.5"),
+            ("FORGE_TOOL_TIMEOUT_SECS", "20"),
+            ("FORGE_RETRY_STATUS_CODES", "429, 503"),
+        ]);
+        let cli = ConfigLayer::default()
+            .tool_timeout_secs(30u64)
+            .verbose(true);
+
+        let actual = Config::load(Some(file), &env, cli).unwrap();
+
+        let expected = Config::default()
+            .model(ModelId::new("file-model"))
+            .temperature(Temperature::new(0.5).unwrap())
+            .tool_timeout_secs(30u64)
+            .verbose(true)
+            .retry(
+                RetryConfig::default()
+                    .max_retry_attempts(5usize)
+                    .retry_status_codes(vec![429, 503]),
+            );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_tool_settings_from_env() {
+        let env = env(&[
+            ("FORGE_IGNORE_PATTERNS", "*.snap, !Cargo.lock,"),
+            ("FORGE_SHELL_TIMEOUT_SECS", "60"),
+            ("FORGE_SYNTAX_CHECK", "false"),
+            ("FORGE_TOOL_OUTPUT_MAX_LINES", "500"),
+            ("FORGE_SHELL_TAIL_LINES", "50"),
+        ]);
+
+        let actual = Config::load(None, &env, ConfigLayer::default()).unwrap();
+
+        let expected = Config::default()
+            .ignore_patterns(vec!["*.snap".to_string(), "!Cargo.lock".to_string()])
+            .shell_timeout_secs(60u64)
+            .syntax_check(false)
+            .tool_output_limit(
+                ToolOutputLimit::default()
+                    .max_lines(500usize)
+                    .shell_tail_lines(50usize),
+            );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_snapshot_compression() {
+        let from_file = Config::load(
+            Some("snapshot_compression: gzip"),
+            &HashMap::new(),
+            ConfigLayer::default(),
+        )
+        .unwrap();
+        let from_env = Config::load(
+            Some("snapshot_compression: gzip"),
+            &env(&[("FORGE_SNAPSHOT_COMPRESSION", "Zstd")]),
+            ConfigLayer::default(),
+        )
+        .unwrap();
+        let invalid = Config::load(
+            None,
+            &env(&[("FORGE_SNAPSHOT_COMPRESSION", "lz4")]),
+            ConfigLayer::default(),
+        );
+
+        assert_eq!(from_file.snapshot_compression, Compression::Gzip);
+        assert_eq!(from_env.snapshot_compression, Compression::Zstd);
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_load_fetch_max_bytes() {
+        let default = Config::load(None, &HashMap::new(), ConfigLayer::default()).unwrap();
+        let from_env = Config::load(
+            Some("fetch_max_bytes: 1024"),
+            &env(&[("FORGE_FETCH_MAX_BYTES", "2048")]),
+            ConfigLayer::default(),
+        )
+        .unwrap();
+        let zero = Config::load(
+            Some("fetch_max_bytes: 0"),
+            &HashMap::new(),
+            ConfigLayer::default(),
+        );
+
+        assert_eq!(default.fetch_max_bytes, 512 * 1024);
+        assert_eq!(from_env.fetch_max_bytes, 2048);
+        assert!(zero.is_err());
+    }
+
+    #[test]
+    fn test_load_provider_fallback() {
+        let from_file = Config::load(
+            Some("provider_fallback: round_robin"),
+            &HashMap::new(),
+            ConfigLayer::default(),
+        )
+        .unwrap();
+        let from_env = Config::load(
+            None,
+            &env(&[("FORGE_PROVIDER_FALLBACK", "first_success")]),
+            ConfigLayer::default(),
+        )
+        .unwrap();
+        let invalid = Config::load(
+            None,
+            &env(&[("FORGE_PROVIDER_FALLBACK", "random")]),
+            ConfigLayer::default(),
+        );
+
+        assert_eq!(
+            from_file.provider_fallback,
+            Some(FallbackStrategy::RoundRobin)
+        );
+        assert_eq!(
+            from_env.provider_fallback,
+            Some(FallbackStrategy::FirstSuccess)
+        );
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_load_invalid_temperature_in_file() {
+        let actual = Config::load(
+            Some("temperature: 2.5"),
+            &HashMap::new(),
+            ConfigLayer::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        let expected = "Invalid value for `temperature` in config file: temperature must be between 0.0 and 2.0, got 2.5";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_invalid_env_value() {
+        let env = env(&[("FORGE_TOOL_TIMEOUT_SECS", "soon")]);
+        let actual = Config::load(None, &env, ConfigLayer::default())
+            .unwrap_err()
+            .to_string();
+        let expected = "Invalid value \"soon\" for environment variable FORGE_TOOL_TIMEOUT_SECS: invalid digit found in string";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_zero_timeout_from_cli() {
+        let cli = ConfigLayer::default().tool_timeout_secs(0u64);
+        let actual = Config::load(None, &HashMap::new(), cli)
+            .unwrap_err()
+            .to_string();
+        let expected =
+            "Invalid value for `tool_timeout_secs` in command line: must be greater than 0";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_invalid_status_code() {
+        let env = env(&[("FORGE_RETRY_STATUS_CODES", "429,999")]);
+        let actual = Config::load(None, &env, ConfigLayer::default())
+            .unwrap_err()
+            .to_string();
+        let expected =
+            "Invalid value for `retry_status_codes` in environment: 999 is not a valid HTTP status code";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_unknown_key_in_file() {
+        let actual = Config::load(
+            Some("temprature: 0.5"),
+            &HashMap::new(),
+            ConfigLayer::default(),
+        );
+        let error = actual.unwrap_err().to_string();
+        assert!(error.contains("unknown field `temprature`"), "{error}");
+    }
+
+    #[test]
+    fn test_json_schema_validates_config() {
+        let schema = serde_json::to_value(ConfigLayer::json_schema()).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let fixture: serde_json::Value = serde_yml::from_str(
+            r#"
+model: anthropic/claude-3.7-sonnet
+temperature: 0.7
+top_k: 40
+tool_timeout_secs: 120
+restricted: true
+retry_status_codes: [429, 503]
+"#,
+        )
+        .unwrap();
+
+        let actual = validator.is_valid(&fixture);
+        assert!(actual);
+    }
+
+    #[test]
+    fn test_json_schema_rejects_invalid_config() {
+        let schema = serde_json::to_value(ConfigLayer::json_schema()).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let fixtures = [
+            serde_json::json!({"temperature": 2.5}),
+            serde_json::json!({"tool_timeout_secs": 0}),
+            serde_json::json!({"retry_status_codes": [999]}),
+            serde_json::json!({"temprature": 0.5}),
+        ];
+
+        let actual = fixtures
+            .iter()
+            .map(|fixture| validator.is_valid(fixture))
+            .collect::<Vec<_>>();
+
+        let expected = vec![false; 4];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_migrates_v1_config() {
+        let file = r#"
+model: file-model
+retry_status_codes: "429, 503"
+"#;
+        let actual = Config::load(Some(file), &HashMap::new(), ConfigLayer::default()).unwrap();
+        let expected = Config::default()
+            .model(ModelId::new("file-model"))
+            .retry(RetryConfig::default().retry_status_codes(vec![429, 503]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_upgrade_yaml_v1_config() {
+        let fixture = r#"
+model: file-model
+retry_status_codes: "429, 503"
+"#;
+        let actual = ConfigLayer::upgrade_yaml(fixture).unwrap();
+        insta::assert_snapshot!(actual.unwrap());
+    }
+
+    #[test]
+    fn test_upgrade_yaml_current_config() {
+        let fixture = "version: 2\nretry_status_codes: [429]\n";
+        let actual = ConfigLayer::upgrade_yaml(fixture).unwrap();
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_load_newer_config_version() {
+        let actual = Config::load(Some("version: 3"), &HashMap::new(), ConfigLayer::default())
+            .unwrap_err()
+            .to_string();
+        let expected =
+            "Failed to upgrade config file: Schema version 3 is newer than the supported version 2";
+        assert_eq!(actual, expected);
+    }
+}
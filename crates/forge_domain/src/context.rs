@@ -27,6 +27,7 @@ impl ContextMessage {
             content: content.to_string(),
             tool_calls: None,
             model,
+            pinned: false,
         }
         .into()
     }
@@ -37,6 +38,7 @@ impl ContextMessage {
             content: content.to_string(),
             tool_calls: None,
             model: None,
+            pinned: false,
         }
         .into()
     }
@@ -49,6 +51,7 @@ impl ContextMessage {
             content: content.to_string(),
             tool_calls,
             model: None,
+            pinned: false,
         }
         .into()
     }
@@ -65,6 +68,14 @@ impl ContextMessage {
         }
     }
 
+    pub fn is_pinned(&self) -> bool {
+        match self {
+            ContextMessage::Text(message) => message.pinned,
+            ContextMessage::Tool(_) => false,
+            ContextMessage::Image(_) => false,
+        }
+    }
+
     pub fn has_tool_call(&self) -> bool {
         match self {
             ContextMessage::Text(message) => message.tool_calls.is_some(),
@@ -84,6 +95,9 @@ pub struct TextMessage {
     pub tool_calls: Option<Vec<ToolCallFull>>,
     // note: this used to track model used for this message.
     pub model: Option<ModelId>,
+    /// Pinned messages are kept verbatim when the context is compacted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 impl TextMessage {
@@ -93,6 +107,7 @@ impl TextMessage {
             content: content.to_string(),
             tool_calls: None,
             model,
+            pinned: false,
         }
     }
 }
@@ -178,6 +193,21 @@ impl Context {
         }
     }
 
+    /// Pins the most recent user message so that it survives compaction,
+    /// returns the pinned message or `None` if there are no user messages
+    pub fn pin_last_user_message(&mut self) -> Option<&TextMessage> {
+        self.messages
+            .iter_mut()
+            .rev()
+            .find_map(|message| match message {
+                ContextMessage::Text(message) if message.role == Role::User => {
+                    message.pinned = true;
+                    Some(&*message)
+                }
+                _ => None,
+            })
+    }
+
     /// Converts the context to textual format
     pub fn to_text(&self) -> String {
         let mut lines = String::new();
@@ -339,6 +369,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pin_last_user_message() {
+        let mut fixture = Context::default()
+            .add_message(ContextMessage::user("First", None))
+            .add_message(ContextMessage::user("Second", None))
+            .add_message(ContextMessage::assistant("Answer", None));
+
+        let actual = fixture.pin_last_user_message().map(|m| m.content.clone());
+
+        assert_eq!(actual, Some("Second".to_string()));
+        let pinned = fixture
+            .messages
+            .iter()
+            .map(|message| message.is_pinned())
+            .collect::<Vec<_>>();
+        assert_eq!(pinned, vec![false, true, false]);
+    }
+
     #[test]
     fn test_set_system_message() {
         let request = Context::default().set_first_system_message("A system message");
@@ -7,7 +7,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
 
-use crate::{Agent, AgentId, Compact, Context, Error, Event, ModelId, Result, ToolName, Workflow};
+use crate::{
+    Agent, AgentId, Compact, Context, Error, Event, ModelId, Plan, Result, ToolName, Workflow,
+};
 
 #[derive(Debug, Display, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 #[serde(transparent)]
@@ -37,11 +39,17 @@ pub struct Conversation {
     pub variables: HashMap<String, Value>,
     pub agents: Vec<Agent>,
     pub events: Vec<Event>,
+    /// The latest plan proposed by the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<Plan>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AgentState {
     pub turn_count: u64,
+    /// Number of think tool calls made since the last call to another tool
+    #[serde(default)]
+    pub consecutive_thinks: usize,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub context: Option<Context>,
     /// holds the events that are waiting to be processed
@@ -127,10 +135,29 @@ impl Conversation {
                 }
             }
 
+            // Summarization settings take precedence over the workflow model
+            if let (Some(summarize), Some(compact)) = (&workflow.summarize, &mut agent.compact) {
+                summarize.apply(compact);
+            }
+
+            if let (Some(threshold), Some(compact)) =
+                (workflow.compact_threshold, &mut agent.compact)
+            {
+                compact.context_window_ratio = Some(threshold);
+            }
+
             if let Some(tool_supported) = workflow.tool_supported {
                 agent.tool_supported = Some(tool_supported);
             }
 
+            if let Some(env_allowlist) = workflow.env_allowlist.clone() {
+                agent.env_allowlist = Some(env_allowlist);
+            }
+
+            if let Some(command_policy) = workflow.command_policy.clone() {
+                agent.command_policy = Some(command_policy);
+            }
+
             // Subscribe the main agent to all commands
             if agent.id.as_str() == Conversation::MAIN_AGENT_NAME {
                 let commands = workflow
@@ -166,6 +193,7 @@ impl Conversation {
             variables: workflow.variables.clone(),
             agents,
             events: Default::default(),
+            plan: None,
         }
     }
 
@@ -173,6 +201,46 @@ impl Conversation {
         self.state.get(id).map(|s| s.turn_count)
     }
 
+    /// Records a tool call made by the agent, returning `false` if the call
+    /// exceeds the agent's budget of consecutive think calls and should not
+    /// be executed. Calling any other tool resets the budget.
+    pub fn record_tool_call(&mut self, id: &AgentId, tool_name: &ToolName) -> bool {
+        let max_thinks = self
+            .get_agent(id)
+            .ok()
+            .and_then(|agent| agent.max_consecutive_thinks)
+            .unwrap_or(Agent::DEFAULT_MAX_CONSECUTIVE_THINKS);
+        let state = self.state.entry(id.clone()).or_default();
+
+        if *tool_name == ToolName::think() {
+            state.consecutive_thinks += 1;
+            state.consecutive_thinks <= max_thinks
+        } else {
+            state.consecutive_thinks = 0;
+            true
+        }
+    }
+
+    /// Records a plan proposed by the agent, replacing any previous one. The
+    /// plan needs the user's approval, even if it only revises an approved
+    /// one.
+    pub fn propose_plan(&mut self, plan: Plan) {
+        self.plan = Some(plan);
+    }
+
+    /// Approves the proposed plan, returning it, or `None` if there is no plan
+    pub fn approve_plan(&mut self) -> Option<Plan> {
+        let plan = self.plan.as_mut()?;
+        plan.approved = true;
+        Some(plan.clone())
+    }
+
+    /// Whether a proposed plan waits for the user's approval, during which
+    /// only calls that don't change anything are allowed
+    pub fn awaits_plan_approval(&self) -> bool {
+        self.plan.as_ref().is_some_and(|plan| !plan.approved)
+    }
+
     /// Returns all the agents that are subscribed to the given event.
     pub fn subscriptions(&self, event_name: &str) -> Vec<Agent> {
         self.agents
@@ -205,6 +273,17 @@ impl Conversation {
         self.state.get(id).and_then(|s| s.context.as_ref())
     }
 
+    /// Pins the most recent user message of the main agent so that it survives
+    /// compaction, returns the content of the pinned message
+    pub fn pin_last_user_message(&mut self) -> Option<String> {
+        self.state
+            .get_mut(&AgentId::new(Self::MAIN_AGENT_NAME))?
+            .context
+            .as_mut()?
+            .pin_last_user_message()
+            .map(|message| message.content.clone())
+    }
+
     pub fn rfind_event(&self, event_name: &str) -> Option<&Event> {
         self.state
             .values()
@@ -324,7 +403,10 @@ mod tests {
 
     use serde_json::json;
 
-    use crate::{Agent, AgentId, Command, Compact, Error, ModelId, Temperature, Workflow};
+    use crate::{
+        Agent, AgentId, Command, CommandPolicy, Compact, Error, ModelId, PolicyDefault,
+        SummarizeConfig, Temperature, ToolName, Workflow,
+    };
 
     #[test]
     fn test_conversation_new_with_empty_workflow() {
@@ -749,4 +831,139 @@ mod tests {
         assert_eq!(compact.model, ModelId::new("workflow-model"));
         assert_eq!(agent2.model, Some(ModelId::new("workflow-model")));
     }
+
+    #[test]
+    fn test_record_tool_call_think_budget() {
+        // Arrange
+        let id = super::ConversationId::generate();
+        let workflow = Workflow::new().agents(vec![Agent::new("agent1")]);
+        let mut conversation = super::Conversation::new_inner(id, workflow, vec![]);
+        let agent_id = AgentId::new("agent1");
+
+        // Act
+        let actual = (0..4)
+            .map(|_| conversation.record_tool_call(&agent_id, &ToolName::think()))
+            .collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(actual, vec![true, true, true, false]);
+    }
+
+    #[test]
+    fn test_record_tool_call_resets_on_other_tool() {
+        // Arrange
+        let id = super::ConversationId::generate();
+        let agent = Agent::new("agent1").max_consecutive_thinks(1usize);
+        let workflow = Workflow::new().agents(vec![agent]);
+        let mut conversation = super::Conversation::new_inner(id, workflow, vec![]);
+        let agent_id = AgentId::new("agent1");
+        let think = ToolName::think();
+        let read = ToolName::new("forge_tool_fs_read");
+
+        // Act
+        let actual = vec![
+            conversation.record_tool_call(&agent_id, &think),
+            conversation.record_tool_call(&agent_id, &think),
+            conversation.record_tool_call(&agent_id, &read),
+            conversation.record_tool_call(&agent_id, &think),
+        ];
+
+        // Assert
+        assert_eq!(actual, vec![true, false, true, true]);
+    }
+
+    #[test]
+    fn test_conversation_new_applies_summarize_config() {
+        // Arrange
+        let id = super::ConversationId::generate();
+        let agent = Agent::new("agent1").compact(Compact::new(ModelId::new("agent-model")));
+        let workflow = Workflow::new()
+            .agents(vec![agent])
+            .model(ModelId::new("workflow-model"))
+            .summarize(
+                SummarizeConfig::default()
+                    .model(ModelId::new("cheap-model"))
+                    .prompt("Summarize tersely: {{context}}"),
+            );
+
+        // Act
+        let conversation = super::Conversation::new_inner(id, workflow, vec![]);
+
+        // Assert
+        let agent = conversation.get_agent(&AgentId::new("agent1")).unwrap();
+        let compact = agent.compact.as_ref().unwrap();
+        assert_eq!(compact.model, ModelId::new("cheap-model"));
+        assert_eq!(
+            compact.prompt,
+            Some("Summarize tersely: {{context}}".to_string())
+        );
+        assert_eq!(agent.model, Some(ModelId::new("workflow-model")));
+    }
+
+    #[test]
+    fn test_conversation_new_applies_compact_threshold() {
+        // Arrange
+        let id = super::ConversationId::generate();
+        let workflow = Workflow::new()
+            .agents(vec![
+                Agent::new("agent1")
+                    .compact(Compact::new(ModelId::new("agent-model")).context_window_ratio(0.9)),
+                Agent::new("agent2"),
+            ])
+            .compact_threshold(0.5);
+
+        // Act
+        let conversation = super::Conversation::new_inner(id, workflow, vec![]);
+
+        // Assert
+        let actual = ["agent1", "agent2"].map(|id| {
+            conversation
+                .get_agent(&AgentId::new(id))
+                .unwrap()
+                .compact
+                .as_ref()
+                .and_then(|compact| compact.context_window_ratio)
+        });
+        assert_eq!(actual, [Some(0.5), None]);
+    }
+
+    #[test]
+    fn test_conversation_new_applies_env_allowlist() {
+        // Arrange
+        let id = super::ConversationId::generate();
+        let workflow = Workflow::new()
+            .agents(vec![Agent::new("agent1")])
+            .env_allowlist(vec!["OPENAI_API_KEY".to_string()]);
+
+        // Act
+        let conversation = super::Conversation::new_inner(id, workflow, vec![]);
+
+        // Assert
+        let agent = conversation.get_agent(&AgentId::new("agent1")).unwrap();
+        assert_eq!(
+            agent.env_allowlist,
+            Some(vec!["OPENAI_API_KEY".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_conversation_new_applies_command_policy() {
+        // Arrange
+        let id = super::ConversationId::generate();
+        let policy = CommandPolicy {
+            allow: vec!["cargo".to_string()],
+            deny: vec![r"^git\s+push".parse().unwrap()],
+            default: PolicyDefault::Deny,
+        };
+        let workflow = Workflow::new()
+            .agents(vec![Agent::new("agent1")])
+            .command_policy(policy.clone());
+
+        // Act
+        let conversation = super::Conversation::new_inner(id, workflow, vec![]);
+
+        // Assert
+        let agent = conversation.get_agent(&AgentId::new("agent1")).unwrap();
+        assert_eq!(agent.command_policy, Some(policy));
+    }
 }
@@ -3,7 +3,10 @@ use std::path::PathBuf;
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 
-use crate::{Provider, RetryConfig};
+use crate::{
+    Compression, FallbackStrategy, ModelId, Provider, RetryConfig, Temperature, ToolOutputLimit,
+    TopK, TopP,
+};
 
 const VERSION: &str = match option_env!("APP_VERSION") {
     Some(val) => val,
@@ -31,6 +34,43 @@ pub struct Environment {
     pub provider: Provider,
     /// Configuration for the retry mechanism
     pub retry_config: RetryConfig,
+    /// Budget for the output of a single tool call
+    pub tool_output_limit: ToolOutputLimit,
+    /// Gitignore style patterns of noisy files, such as lock files, that
+    /// listings and searches skip unless ignored files are requested
+    pub ignore_patterns: Vec<String>,
+    /// Seconds a shell command run by the agent may take unless the call sets
+    /// its own timeout
+    pub shell_timeout_secs: u64,
+    /// Whether code written by the agent is parsed and reported when it has
+    /// syntax errors
+    pub syntax_check: bool,
+    /// Whether commands run in a restricted shell, which also keeps them
+    /// inside the project directory
+    pub restricted: bool,
+    /// Seconds a tool call may take unless the tool defines its own timeout
+    pub tool_timeout_secs: u64,
+    /// Whether additional debugging information is shown
+    pub verbose: bool,
+    /// Model used when the workflow doesn't set one
+    pub model: Option<ModelId>,
+    /// Sampling temperature used when the workflow doesn't set one
+    pub temperature: Option<Temperature>,
+    /// Top-p used when the workflow doesn't set one
+    pub top_p: Option<TopP>,
+    /// Top-k used when the workflow doesn't set one
+    pub top_k: Option<TopK>,
+    /// How snapshots of files are compressed when they are stored
+    pub snapshot_compression: Compression,
+    /// Largest response body downloaded from the web unless a fetch allows
+    /// more
+    pub fetch_max_bytes: usize,
+    /// How requests are spread over `provider` and `fallback_providers`, if
+    /// at all
+    pub provider_fallback: Option<FallbackStrategy>,
+    /// Providers with a key other than `provider`, in the order they are
+    /// looked up. Empty unless a fallback strategy is configured.
+    pub fallback_providers: Vec<Provider>,
 }
 
 impl Environment {
@@ -48,6 +88,12 @@ impl Environment {
     pub fn snapshot_path(&self) -> PathBuf {
         self.base_path.join("snapshots")
     }
+    pub fn fetch_cache_path(&self) -> PathBuf {
+        self.base_path.join("fetch_cache")
+    }
+    pub fn config_path(&self) -> PathBuf {
+        self.base_path.join("config.yaml")
+    }
     pub fn mcp_user_config(&self) -> PathBuf {
         self.base_path.join(".mcp.json")
     }
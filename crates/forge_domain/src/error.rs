@@ -3,7 +3,7 @@ use std::pin::Pin;
 use derive_more::From;
 use thiserror::Error;
 
-use crate::{AgentId, ConversationId};
+use crate::{AgentId, ConversationId, ToolName};
 
 // NOTE: Deriving From for error is a really bad idea. This is because you end
 // up converting errors incorrectly without much context. For eg: You don't want
@@ -17,6 +17,14 @@ pub enum Error {
     #[error("Invalid tool call arguments: {0}")]
     ToolCallArgument(serde_json::Error),
 
+    #[error("Invalid arguments for tool '{name}':\n{}", .errors.join("\n"))]
+    #[from(skip)]
+    ToolCallSchema { name: ToolName, errors: Vec<String> },
+
+    #[error("Command '{command}' was not run: {reason}")]
+    #[from(skip)]
+    CommandDenied { command: String, reason: String },
+
     #[error("Invalid tool call XML: {0}")]
     #[from(skip)]
     ToolCallParse(String),
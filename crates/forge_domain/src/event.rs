@@ -62,6 +62,8 @@ impl Event {
             description: "Dispatches an event with the provided name and value".to_string(),
             input_schema: schema_for!(EventMessage),
             output_schema: None,
+            timeout: None,
+            limits_output: false,
         }
     }
 
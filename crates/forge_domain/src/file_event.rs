@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+/// Kind of change observed on a watched path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileEventKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// A change to a file or directory under a watched path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEvent {
+    pub path: PathBuf,
+    pub kind: FileEventKind,
+}
+
+impl FileEvent {
+    pub fn new(path: impl Into<PathBuf>, kind: FileEventKind) -> Self {
+        Self { path: path.into(), kind }
+    }
+}
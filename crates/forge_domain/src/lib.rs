@@ -3,7 +3,9 @@ mod api;
 mod attachment;
 mod chat_request;
 mod chat_response;
+mod command_policy;
 mod compaction_result;
+mod config;
 mod conversation_html;
 mod update;
 
@@ -13,30 +15,38 @@ mod env;
 mod error;
 mod event;
 mod file;
+mod file_event;
 mod image;
 mod mcp;
 mod merge;
 mod message;
+mod migration;
 mod model;
 mod orch;
+mod plan;
 mod point;
 mod provider;
+mod request_id;
 mod retry_config;
 mod services;
 mod shell;
 mod suggestion;
+mod summarize;
 mod system_context;
 mod temperature;
 mod template;
 mod text_utils;
+mod token_counter;
 mod tool;
 mod tool_call;
 mod tool_call_context;
 mod tool_call_parser;
 mod tool_choice;
 mod tool_definition;
+mod tool_impact;
 mod tool_input;
 mod tool_name;
+mod tool_output_limit;
 mod tool_result;
 mod tool_usage;
 mod top_k;
@@ -48,7 +58,9 @@ pub use api::*;
 pub use attachment::*;
 pub use chat_request::*;
 pub use chat_response::*;
+pub use command_policy::*;
 pub use compaction_result::*;
+pub use config::*;
 pub use context::*;
 pub use conversation::*;
 pub use conversation_html::*;
@@ -56,29 +68,37 @@ pub use env::*;
 pub use error::*;
 pub use event::*;
 pub use file::*;
+pub use file_event::*;
 pub use image::*;
 pub use mcp::*;
 pub use message::*;
+pub use migration::*;
 pub use model::*;
 pub use orch::*;
+pub use plan::*;
 pub use point::*;
 pub use provider::*;
+pub use request_id::*;
 pub use retry_config::*;
 pub use services::*;
 pub use shell::*;
 pub use suggestion::*;
+pub use summarize::*;
 pub use system_context::*;
 pub use temperature::*;
 pub use template::*;
 pub use text_utils::*;
+pub use token_counter::*;
 pub use tool::*;
 pub use tool_call::*;
 pub use tool_call_context::*;
 pub use tool_call_parser::*;
 pub use tool_choice::*;
 pub use tool_definition::*;
+pub use tool_impact::*;
 pub use tool_input::*;
 pub use tool_name::*;
+pub use tool_output_limit::*;
 pub use tool_result::*;
 pub use tool_usage::*;
 pub use top_k::*;
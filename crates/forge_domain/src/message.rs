@@ -5,25 +5,31 @@ use strum_macros::EnumString;
 
 use super::ToolCall;
 
-#[derive(Default, Clone, Debug, Serialize, PartialEq, Eq)]
+#[derive(Default, Clone, Debug, Serialize, PartialEq)]
 pub struct Usage {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub total_tokens: u64,
     pub estimated_tokens: u64,
     pub content_length: u64,
+    /// Price of the request in USD, when the provider's pricing is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
 }
 
 /// Represents a message that was received from the LLM provider
 /// NOTE: Tool call messages are part of the larger Response object and not part
 /// of the message.
-#[derive(Default, Clone, Debug, Setters, PartialEq, Eq)]
+#[derive(Default, Clone, Debug, Setters, PartialEq)]
 #[setters(into, strip_option)]
 pub struct ChatCompletionMessage {
     pub content: Option<Content>,
     pub tool_calls: Vec<ToolCall>,
     pub finish_reason: Option<FinishReason>,
     pub usage: Option<Usage>,
+    /// Explanation given by the provider when it declines to answer on policy
+    /// grounds
+    pub refusal: Option<String>,
 }
 
 /// Represents partial or full content of a message
@@ -78,7 +84,7 @@ pub enum FinishReason {
     Length,
     /// The model stopped generating output because it encountered content that
     /// violated filters.
-    #[strum(serialize = "content_filter")]
+    #[strum(serialize = "content_filter", serialize = "refusal")]
     ContentFilter,
     /// The model stopped generating output because it made a tool call.
     #[strum(serialize = "tool_calls")]
@@ -137,6 +143,10 @@ mod tests {
             FinishReason::from_str("content_filter").unwrap(),
             FinishReason::ContentFilter
         );
+        assert_eq!(
+            FinishReason::from_str("refusal").unwrap(),
+            FinishReason::ContentFilter
+        );
         assert_eq!(
             FinishReason::from_str("tool_calls").unwrap(),
             FinishReason::ToolCalls
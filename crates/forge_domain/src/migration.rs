@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+use thiserror::Error;
+
+/// Key holding the schema version of a stored artifact
+pub const VERSION_KEY: &str = "version";
+
+/// Version assumed for artifacts written before versioning was introduced
+const UNVERSIONED: u32 = 1;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum MigrationError {
+    #[error("Expected an object at the root of the document")]
+    NotAnObject,
+
+    #[error("Invalid schema version: {0}")]
+    InvalidVersion(Value),
+
+    #[error("Schema version {found} is newer than the supported version {current}")]
+    Unsupported { found: u32, current: u32 },
+
+    #[error("No migration registered to upgrade from version {0}")]
+    Missing(u32),
+
+    #[error("Failed to migrate from version {version}: {message}")]
+    Failed { version: u32, message: String },
+}
+
+/// Upgrades a document from one schema version to the next
+pub trait Migration: Send + Sync {
+    /// Version of the documents this migration applies to, the migrated
+    /// document is at `version() + 1`
+    fn version(&self) -> u32;
+
+    /// Transforms the document, the version key is updated by the
+    /// [`Migrator`]
+    fn migrate(&self, document: Value) -> Result<Value, MigrationError>;
+}
+
+/// Result of running a document through a [`Migrator`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migrated {
+    /// The document at the current schema version
+    pub document: Value,
+
+    /// True when at least one migration was applied and the document should
+    /// be written back
+    pub upgraded: bool,
+}
+
+/// Brings stored documents up to the current schema version by running the
+/// registered migrations in order. Documents without a version are treated as
+/// version 1.
+#[derive(Clone)]
+pub struct Migrator {
+    current_version: u32,
+    migrations: Vec<Arc<dyn Migration>>,
+}
+
+impl Migrator {
+    pub fn new(current_version: u32) -> Self {
+        Self { current_version, migrations: Vec::new() }
+    }
+
+    /// Registers a migration, migrations can be registered in any order
+    pub fn register(mut self, migration: impl Migration + 'static) -> Self {
+        self.migrations.push(Arc::new(migration));
+        self
+    }
+
+    pub fn current_version(&self) -> u32 {
+        self.current_version
+    }
+
+    /// Returns the schema version of the document
+    pub fn version_of(document: &Value) -> Result<u32, MigrationError> {
+        let object = document.as_object().ok_or(MigrationError::NotAnObject)?;
+        match object.get(VERSION_KEY) {
+            None => Ok(UNVERSIONED),
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| MigrationError::InvalidVersion(version.clone())),
+        }
+    }
+
+    /// Upgrades the document to the current schema version
+    pub fn migrate(&self, mut document: Value) -> Result<Migrated, MigrationError> {
+        let mut version = Self::version_of(&document)?;
+        if version > self.current_version {
+            return Err(MigrationError::Unsupported {
+                found: version,
+                current: self.current_version,
+            });
+        }
+
+        let upgraded = version < self.current_version;
+        while version < self.current_version {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|migration| migration.version() == version)
+                .ok_or(MigrationError::Missing(version))?;
+
+            document = migration.migrate(document)?;
+            version += 1;
+        }
+
+        if upgraded {
+            document
+                .as_object_mut()
+                .ok_or(MigrationError::NotAnObject)?
+                .insert(VERSION_KEY.to_string(), Value::from(version));
+        }
+
+        Ok(Migrated { document, upgraded })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Appends its version to the `steps` array of the document
+    struct Step(u32);
+
+    impl Migration for Step {
+        fn version(&self) -> u32 {
+            self.0
+        }
+
+        fn migrate(&self, mut document: Value) -> Result<Value, MigrationError> {
+            document["steps"]
+                .as_array_mut()
+                .unwrap()
+                .push(Value::from(self.0));
+            Ok(document)
+        }
+    }
+
+    #[test]
+    fn test_migrate_runs_migrations_in_order() {
+        let fixture = Migrator::new(4)
+            .register(Step(3))
+            .register(Step(1))
+            .register(Step(2));
+        let actual = fixture.migrate(json!({"steps": []})).unwrap();
+        let expected = Migrated {
+            document: json!({"version": 4, "steps": [1, 2, 3]}),
+            upgraded: true,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_migrate_current_version_is_unchanged() {
+        let fixture = Migrator::new(2).register(Step(1));
+        let actual = fixture.migrate(json!({"version": 2, "steps": []})).unwrap();
+        let expected = Migrated {
+            document: json!({"version": 2, "steps": []}),
+            upgraded: false,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_migrate_newer_version() {
+        let fixture = Migrator::new(2);
+        let actual = fixture.migrate(json!({"version": 3})).unwrap_err();
+        let expected = MigrationError::Unsupported { found: 3, current: 2 };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_migrate_missing_migration() {
+        let fixture = Migrator::new(3).register(Step(1));
+        let actual = fixture.migrate(json!({"steps": []})).unwrap_err();
+        let expected = MigrationError::Missing(2);
+        assert_eq!(actual, expected);
+    }
+}
@@ -11,7 +11,7 @@ use futures::future::join_all;
 use futures::{Stream, StreamExt};
 use serde_json::Value;
 use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use tracing::{debug, info, info_span, warn, Instrument};
 
 // Use retry_config default values directly in this file
 use crate::services::Services;
@@ -42,6 +42,8 @@ struct ChatCompletionResult {
     pub content: String,
     pub tool_calls: Vec<ToolCallFull>,
     pub usage: Usage,
+    /// Whether the provider declined to answer on policy grounds
+    pub refused: bool,
 }
 
 impl<A: Services> Orchestrator<A> {
@@ -75,20 +77,65 @@ impl<A: Services> Orchestrator<A> {
 
         for tool_call in tool_calls {
             // Send the start notification
-            self.send(agent, ChatResponse::ToolCallStart(tool_call.clone()))
+            self.send(agent, ChatResponse::ToolCallStart(tool_call.redacted()))
                 .await?;
 
-            // Execute the tool
-            let tool_result = self
-                .services
-                .tool_service()
-                .call(tool_context.clone(), tool_call.clone())
-                .await;
+            // Execute the tool unless it would change something before the plan is
+            // approved, or the agent has exhausted its think budget. Calls refused
+            // for the plan don't count towards the budget.
+            let blocked_by_plan = self.conversation.read().await.awaits_plan_approval()
+                && !self.is_read_only(tool_call).await;
+            let within_budget = !blocked_by_plan
+                && self
+                    .conversation
+                    .write()
+                    .await
+                    .record_tool_call(&agent.id, &tool_call.name);
+            let tool_result = if blocked_by_plan {
+                info!(
+                    agent_id = %agent.id,
+                    tool = %tool_call.name,
+                    "Plan awaits approval, refusing the tool call"
+                );
+                let content = self.services.template_service().render(
+                    "{{> partial-plan-approval.hbs}}",
+                    &serde_json::json!({ "tool_name": tool_call.name.as_str() }),
+                )?;
+                ToolResult::from(tool_call.clone()).failure(anyhow::anyhow!(content))
+            } else if within_budget {
+                self.services
+                    .tool_service()
+                    .call(tool_context.clone(), tool_call.clone())
+                    .instrument(info_span!("tool_call", tool = %tool_call.name))
+                    .await
+            } else {
+                warn!(
+                    agent_id = %agent.id,
+                    "Think budget exceeded, asking the agent to act"
+                );
+                let content = self.services.template_service().render(
+                    "{{> partial-think-limit.hbs}}",
+                    &serde_json::json!({
+                        "max_consecutive_thinks": agent
+                            .max_consecutive_thinks
+                            .unwrap_or(Agent::DEFAULT_MAX_CONSECUTIVE_THINKS)
+                    }),
+                )?;
+                ToolResult::from(tool_call.clone()).failure(anyhow::anyhow!(content))
+            };
+
+            // The proposed plan is kept on the conversation for the user to approve
+            if tool_call.name == ToolName::plan() && !tool_result.is_error() {
+                match serde_json::from_value::<PlanInput>(tool_call.arguments.clone()) {
+                    Ok(input) => self.conversation.write().await.propose_plan(input.into()),
+                    Err(error) => warn!(error = ?error, "Failed to record the proposed plan"),
+                }
+            }
 
             if tool_result.is_error() {
                 warn!(
                     agent_id = %agent.id,
-                    tool_call = ?tool_call,
+                    tool_call = ?tool_call.redacted(),
                     output = ?tool_result.output,
                     "Tool call failed",
                 );
@@ -98,9 +145,10 @@ impl<A: Services> Orchestrator<A> {
             self.send(agent, ChatResponse::ToolCallEnd(tool_result.clone()))
                 .await?;
 
-            // Add the result to our collection if completion wasn't achieved
+            // Add the result to our collection if completion wasn't achieved. The
+            // call is kept in the conversation, so its credentials are redacted
             if !tool_context.get_complete().await {
-                tool_call_records.push((tool_call.clone(), tool_result));
+                tool_call_records.push((tool_call.redacted(), tool_result));
             }
         }
 
@@ -171,7 +219,9 @@ impl<A: Services> Orchestrator<A> {
     ) -> anyhow::Result<Context> {
         Ok(if let Some(system_prompt) = &agent.system_prompt {
             let env = self.services.environment_service().get_environment();
-            let walker = Walker::max_all().max_depth(agent.max_walker_depth.unwrap_or(1));
+            let walker = Walker::max_all()
+                .max_depth(agent.max_walker_depth.unwrap_or(1))
+                .ignore_patterns(env.ignore_patterns.clone());
             let mut files = walker
                 .cwd(env.cwd.clone())
                 .get()
@@ -216,15 +266,15 @@ impl<A: Services> Orchestrator<A> {
     fn update_usage(
         &self,
         message: &ChatCompletionMessage,
-        context: &Context,
+        content_length: usize,
+        estimated_tokens: usize,
         request_usage: Usage,
     ) -> Usage {
         // If usage information is provided by provider use that else depend on
         // estimates.
 
         let mut usage = message.usage.clone().unwrap_or(request_usage);
-        let content_length = context.to_text().len();
-        usage.estimated_tokens = estimate_token_count(content_length) as u64;
+        usage.estimated_tokens = estimated_tokens as u64;
         usage.content_length = content_length as u64;
         usage
     }
@@ -232,11 +282,16 @@ impl<A: Services> Orchestrator<A> {
     async fn collect_messages(
         &self,
         agent: &Agent,
+        model_id: &ModelId,
         context: &Context,
         mut response: impl Stream<Item = anyhow::Result<ChatCompletionMessage>> + std::marker::Unpin,
     ) -> anyhow::Result<ChatCompletionResult> {
         let mut messages = Vec::new();
         let mut usage: Usage = Default::default();
+
+        // The request doesn't change while the response streams, so estimate it once
+        let content_length = context.to_text().len();
+        let estimated_tokens = token_counter(model_id).estimate_tokens(context);
         let mut content = String::new();
         let mut xml_tool_calls = None;
         let mut tool_interrupted = false;
@@ -249,7 +304,7 @@ impl<A: Services> Orchestrator<A> {
             messages.push(message.clone());
 
             // Process usage information
-            usage = self.update_usage(&message, context, usage);
+            usage = self.update_usage(&message, content_length, estimated_tokens, usage);
 
             // Process content
             if let Some(content_part) = message.content.as_ref() {
@@ -326,6 +381,21 @@ impl<A: Services> Orchestrator<A> {
         )
         .await?;
 
+        // Refusals come as a refusal message, possibly streamed in parts, or only as a
+        // content filter finish reason
+        let refusal: String = messages
+            .iter()
+            .filter_map(|message| message.refusal.as_deref())
+            .collect();
+        let refused = !refusal.is_empty()
+            || messages
+                .iter()
+                .any(|message| message.finish_reason == Some(FinishReason::ContentFilter));
+        if refused {
+            let reason = Some(refusal).filter(|refusal| !refusal.trim().is_empty());
+            self.send(agent, ChatResponse::Refusal { reason }).await?;
+        }
+
         // Extract all tool calls in a fully declarative way with combined sources
         // Start with complete tool calls (for non-streaming mode)
         let initial_tool_calls: Vec<ToolCallFull> = messages
@@ -352,7 +422,7 @@ impl<A: Services> Orchestrator<A> {
             .chain(xml_tool_calls)
             .collect();
 
-        Ok(ChatCompletionResult { content, tool_calls, usage })
+        Ok(ChatCompletionResult { content, tool_calls, usage, refused })
     }
 
     pub async fn dispatch(&self, event: Event) -> anyhow::Result<()> {
@@ -409,11 +479,22 @@ impl<A: Services> Orchestrator<A> {
     }
 
     // Get the ToolCallContext for an agent
-    fn get_tool_call_context(&self, agent: &Agent) -> ToolCallContext {
+    async fn get_tool_call_context(&self, agent: &Agent) -> ToolCallContext {
         // Create a new ToolCallContext with the agent ID
+        let conversation_id = self.conversation.read().await.id.clone();
         ToolCallContext::default()
             .agent(agent.clone())
             .sender(self.sender.clone())
+            .conversation_id(conversation_id)
+    }
+
+    /// Whether the tool called by `tool_call` considers the call read-only.
+    /// Calls to unknown tools aren't.
+    async fn is_read_only(&self, tool_call: &ToolCallFull) -> bool {
+        match self.services.tool_service().find(&tool_call.name).await {
+            Ok(Some(tool)) => tool.executable.is_read_only(&tool_call.arguments),
+            _ => false,
+        }
     }
 
     async fn chat(
@@ -426,8 +507,10 @@ impl<A: Services> Orchestrator<A> {
             .services
             .provider_service()
             .chat(model_id, context.clone())
+            .instrument(info_span!("provider_chat", model = %model_id))
             .await?;
-        self.collect_messages(agent, &context, response).await
+        self.collect_messages(agent, model_id, &context, response)
+            .await
     }
 
     // Create a helper method with the core functionality
@@ -497,7 +580,7 @@ impl<A: Services> Orchestrator<A> {
 
         self.set_context(&agent.id, context.clone()).await?;
 
-        let tool_context = self.get_tool_call_context(agent);
+        let tool_context = self.get_tool_call_context(agent).await;
 
         let mut empty_tool_call_count = 0;
 
@@ -507,11 +590,32 @@ impl<A: Services> Orchestrator<A> {
             .get_environment()
             .retry_config;
 
+        // Context window of the model, only needed when compaction is relative to it.
+        // Without it compaction falls back to the token threshold.
+        let context_length = match agent
+            .compact
+            .as_ref()
+            .and_then(|compact| compact.context_window_ratio)
+        {
+            Some(_) => match self.services.provider_service().model(&model_id).await {
+                Ok(model) => model.and_then(|model| model.context_length),
+                Err(error) => {
+                    warn!(
+                        model = %model_id,
+                        error = ?error,
+                        "Failed to look up the context window of the model"
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
         while !tool_context.get_complete().await {
             // Set context for the current loop iteration
             self.set_context(&agent.id, context.clone()).await?;
 
-            let ChatCompletionResult { tool_calls, content, usage } =
+            let ChatCompletionResult { tool_calls, content, usage, refused } =
                 (|| self.chat(agent, &model_id, context.clone()))
                     .retry(
                         ExponentialBuilder::default()
@@ -532,8 +636,23 @@ impl<A: Services> Orchestrator<A> {
             );
             self.send(agent, ChatResponse::Usage(usage.clone())).await?;
 
+            // Asking again would only be refused again, so the turn ends here and the
+            // refused response is left out of the context
+            if refused {
+                warn!(
+                    agent_id = %agent.id,
+                    model_id = %model_id,
+                    "Provider declined to answer"
+                );
+                break;
+            }
+
             // Check if context requires compression and decide to compact
-            if agent.should_compact(&context, max(usage.prompt_tokens, usage.estimated_tokens)) {
+            if agent.should_compact(
+                &context,
+                max(usage.prompt_tokens, usage.estimated_tokens),
+                context_length,
+            ) {
                 info!(agent_id = %agent.id, "Compaction needed, applying compaction");
                 context = self
                     .services
@@ -553,14 +672,24 @@ impl<A: Services> Orchestrator<A> {
                 empty_tool_calls
             );
 
+            // Messages can carry prose along with tool calls, store the prose before
+            // executing the tools so that it's kept even if a tool call fails
+            if !empty_tool_calls && !content.trim().is_empty() {
+                let context = context.clone().append_message(
+                    &content,
+                    model_id.clone(),
+                    vec![],
+                    tool_supported,
+                );
+                self.set_context(&agent.id, context).await?;
+            }
+
             // Process tool calls and update context
-            context = context.append_message(
-                content,
-                model_id.clone(),
-                self.get_all_tool_results(agent, &tool_calls, tool_context.clone())
-                    .await?,
-                tool_supported,
-            );
+            let tool_records = self
+                .get_all_tool_results(agent, &tool_calls, tool_context.clone())
+                .await?;
+            context =
+                context.append_message(content, model_id.clone(), tool_records, tool_supported);
 
             if empty_tool_calls {
                 // No tool calls present, which doesn't mean task is complete so reprompt the
@@ -651,3 +780,594 @@ fn should_retry(error: &anyhow::Error) -> bool {
     warn!(error = %error, retry = retry, "Retrying on error");
     retry
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::services::*;
+
+    /// Services answering each chat request with the next scripted response
+    #[derive(Clone)]
+    struct Fixture {
+        responses: Arc<Mutex<Vec<ChatCompletionMessage>>>,
+    }
+
+    impl Fixture {
+        fn new(responses: Vec<ChatCompletionMessage>) -> Self {
+            Self { responses: Arc::new(Mutex::new(responses)) }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderService for Fixture {
+        async fn chat(
+            &self,
+            _: &ModelId,
+            _: Context,
+        ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+            let response = self.responses.lock().unwrap().remove(0);
+            Ok(Box::pin(tokio_stream::iter(vec![Ok(response)])))
+        }
+
+        async fn models(&self) -> anyhow::Result<Vec<Model>> {
+            unimplemented!()
+        }
+
+        async fn model(&self, _: &ModelId) -> anyhow::Result<Option<Model>> {
+            Ok(None)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ToolService for Fixture {
+        async fn call(&self, context: ToolCallContext, call: ToolCallFull) -> ToolResult {
+            if matches!(
+                call.name.as_str(),
+                "complete" | "forge_tool_attempt_completion"
+            ) {
+                context.set_complete().await;
+            }
+            ToolResult::from(call.clone()).success(format!("{} done", call.name))
+        }
+
+        async fn list(&self) -> anyhow::Result<Vec<ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn find(&self, name: &ToolName) -> anyhow::Result<Option<Arc<Tool>>> {
+            let read_only = matches!(
+                name.as_str(),
+                "read" | "forge_tool_plan" | "forge_tool_think" | "forge_tool_attempt_completion"
+            );
+            Ok(Some(Arc::new(Tool {
+                executable: Box::new(StubTool { read_only }),
+                definition: ToolDefinition::new(name.as_str()),
+            })))
+        }
+    }
+
+    /// A tool that is only consulted about whether its calls are read-only
+    struct StubTool {
+        read_only: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl ExecutableTool for StubTool {
+        type Input = serde_json::Value;
+
+        async fn call(&self, _: ToolCallContext, _: Self::Input) -> anyhow::Result<ToolOutput> {
+            unimplemented!()
+        }
+
+        fn is_read_only(&self, _: &Self::Input) -> bool {
+            self.read_only
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ConversationService for Fixture {
+        async fn find(&self, _: &ConversationId) -> anyhow::Result<Option<Conversation>> {
+            unimplemented!()
+        }
+
+        async fn upsert(&self, _: Conversation) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn create(&self, _: Workflow) -> anyhow::Result<Conversation> {
+            unimplemented!()
+        }
+
+        async fn update<F, T>(&self, _: &ConversationId, _: F) -> anyhow::Result<T>
+        where
+            F: FnOnce(&mut Conversation) -> T + Send,
+        {
+            unimplemented!()
+        }
+
+        async fn compact_conversation(
+            &self,
+            _: &ConversationId,
+        ) -> anyhow::Result<CompactionResult> {
+            unimplemented!()
+        }
+    }
+
+    impl TemplateService for Fixture {
+        fn render(
+            &self,
+            template: impl ToString,
+            _: &impl serde::Serialize,
+        ) -> anyhow::Result<String> {
+            Ok(template.to_string())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AttachmentService for Fixture {
+        async fn attachments(&self, _: &str) -> anyhow::Result<Vec<Attachment>> {
+            Ok(vec![])
+        }
+    }
+
+    impl EnvironmentService for Fixture {
+        fn get_environment(&self) -> Environment {
+            Environment {
+                os: std::env::consts::OS.to_string(),
+                pid: std::process::id(),
+                cwd: PathBuf::from("."),
+                home: None,
+                shell: "/bin/sh".to_string(),
+                base_path: PathBuf::from("."),
+                provider: Provider::anthropic("test-key"),
+                retry_config: Default::default(),
+                tool_output_limit: Default::default(),
+                ignore_patterns: vec![],
+                shell_timeout_secs: 300,
+                syntax_check: true,
+                restricted: false,
+                tool_timeout_secs: 300,
+                verbose: false,
+                model: None,
+                temperature: None,
+                top_p: None,
+                top_k: None,
+                snapshot_compression: Compression::None,
+                fetch_max_bytes: 512 * 1024,
+                provider_fallback: None,
+                fallback_providers: vec![],
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl CompactionService for Fixture {
+        async fn compact_context(&self, _: &Agent, _: Context) -> anyhow::Result<Context> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl WorkflowService for Fixture {
+        async fn resolve(&self, _: Option<PathBuf>) -> PathBuf {
+            unimplemented!()
+        }
+
+        async fn read(&self, _: Option<&Path>) -> anyhow::Result<Workflow> {
+            unimplemented!()
+        }
+
+        async fn write(&self, _: Option<&Path>, _: &Workflow) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        async fn update_workflow<F>(&self, _: Option<&Path>, _: F) -> anyhow::Result<Workflow>
+        where
+            F: FnOnce(&mut Workflow) + Send,
+        {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SuggestionService for Fixture {
+        async fn suggestions(&self) -> anyhow::Result<Vec<File>> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl McpConfigManager for Fixture {
+        async fn read(&self) -> anyhow::Result<McpConfig> {
+            unimplemented!()
+        }
+
+        async fn write(&self, _: &McpConfig, _: &Scope) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+    }
+
+    impl Services for Fixture {
+        type ToolService = Self;
+        type ProviderService = Self;
+        type ConversationService = Self;
+        type TemplateService = Self;
+        type AttachmentService = Self;
+        type EnvironmentService = Self;
+        type CompactionService = Self;
+        type WorkflowService = Self;
+        type SuggestionService = Self;
+        type McpConfigManager = Self;
+
+        fn tool_service(&self) -> &Self::ToolService {
+            self
+        }
+
+        fn provider_service(&self) -> &Self::ProviderService {
+            self
+        }
+
+        fn conversation_service(&self) -> &Self::ConversationService {
+            self
+        }
+
+        fn template_service(&self) -> &Self::TemplateService {
+            self
+        }
+
+        fn attachment_service(&self) -> &Self::AttachmentService {
+            self
+        }
+
+        fn environment_service(&self) -> &Self::EnvironmentService {
+            self
+        }
+
+        fn compaction_service(&self) -> &Self::CompactionService {
+            self
+        }
+
+        fn workflow_service(&self) -> &Self::WorkflowService {
+            self
+        }
+
+        fn suggestion_service(&self) -> &Self::SuggestionService {
+            self
+        }
+
+        fn mcp_config_manager(&self) -> &Self::McpConfigManager {
+            self
+        }
+    }
+
+    fn tool_call(name: &str) -> ToolCallFull {
+        ToolCallFull {
+            name: ToolName::new(name),
+            call_id: Some(ToolCallId::new(format!("call_{name}"))),
+            arguments: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_message_with_content_and_tool_calls() {
+        let fixture = Fixture::new(vec![
+            ChatCompletionMessage::assistant(Content::full("Let me read the file"))
+                .add_tool_call(tool_call("read")),
+            ChatCompletionMessage::assistant(Content::full("All done"))
+                .add_tool_call(tool_call("complete")),
+        ]);
+        let agent = Agent::new("test-agent")
+            .model(ModelId::new("test-model"))
+            .tool_supported(true)
+            .subscribe(vec!["test_event".to_string()]);
+        let conversation = Conversation::new(
+            ConversationId::generate(),
+            Workflow::new().agents(vec![agent]),
+            vec![],
+        );
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let orch = Orchestrator::new(Arc::new(fixture), conversation, Some(Arc::new(tx)));
+
+        orch.dispatch(Event::new("test_event", "Read the file"))
+            .await
+            .unwrap();
+
+        // Prose is rendered before the tools of the same message are executed
+        let mut actual = Vec::new();
+        while let Ok(message) = rx.try_recv() {
+            match message.unwrap().message {
+                ChatResponse::Text { text, is_complete: true, .. } => {
+                    actual.push(format!("text: {text}"))
+                }
+                ChatResponse::ToolCallStart(call) => actual.push(format!("start: {}", call.name)),
+                ChatResponse::ToolCallEnd(result) => actual.push(format!("end: {}", result.name)),
+                _ => {}
+            }
+        }
+        let expected = vec![
+            "text: Let me read the file",
+            "start: read",
+            "end: read",
+            "text: All done",
+            "start: complete",
+            "end: complete",
+        ];
+        assert_eq!(actual, expected);
+
+        // The prose is stored along with the tool call and followed by its result
+        let context = orch
+            .get_conversation()
+            .await
+            .unwrap()
+            .context(&AgentId::new("test-agent"))
+            .cloned()
+            .unwrap();
+        let actual = context
+            .messages
+            .iter()
+            .map(|message| match message {
+                ContextMessage::Text(message) => format!(
+                    "{}: {} {:?}",
+                    message.role,
+                    message.content,
+                    message
+                        .tool_calls
+                        .iter()
+                        .flatten()
+                        .map(|call| call.name.as_str())
+                        .collect::<Vec<_>>()
+                ),
+                ContextMessage::Tool(result) => {
+                    format!("Tool: {}", result.output.as_str().unwrap())
+                }
+                ContextMessage::Image(_) => "Image".to_string(),
+            })
+            .collect::<Vec<_>>();
+        let expected = vec![
+            "User: \"Read the file\" []",
+            "Assistant: Let me read the file [\"read\"]",
+            "Tool: read done",
+            "Assistant: All done []",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_mutating_tools_wait_for_plan_approval() {
+        let plan = ToolCallFull {
+            name: ToolName::plan(),
+            call_id: Some(ToolCallId::new("call_plan")),
+            arguments: serde_json::json!({
+                "goal": "Greet the user",
+                "steps": [{ "description": "Create hello.txt", "files": ["hello.txt"] }]
+            }),
+        };
+        let create = || tool_call("forge_tool_fs_create");
+        let fixture = Fixture::new(vec![
+            ChatCompletionMessage::assistant(Content::full("Here is my plan")).add_tool_call(plan),
+            ChatCompletionMessage::assistant(Content::full("Creating it")).add_tool_call(create()),
+            ChatCompletionMessage::assistant(Content::full("Please review the plan"))
+                .add_tool_call(tool_call("forge_tool_attempt_completion")),
+            ChatCompletionMessage::assistant(Content::full("Creating it")).add_tool_call(create()),
+            ChatCompletionMessage::assistant(Content::full("All done"))
+                .add_tool_call(tool_call("forge_tool_attempt_completion")),
+        ]);
+        let agent = Agent::new("test-agent")
+            .model(ModelId::new("test-model"))
+            .tool_supported(true)
+            .subscribe(vec!["test_event".to_string()]);
+        let conversation = Conversation::new(
+            ConversationId::generate(),
+            Workflow::new().agents(vec![agent]),
+            vec![],
+        );
+        let orch = Orchestrator::new(Arc::new(fixture), conversation, None);
+        let tool_outputs = || async {
+            orch.get_conversation()
+                .await
+                .unwrap()
+                .context(&AgentId::new("test-agent"))
+                .unwrap()
+                .messages
+                .iter()
+                // Failures may be followed by a backtrace
+                .filter_map(|message| match message {
+                    ContextMessage::Tool(result) => {
+                        Some(result.output.as_str()?.lines().next()?.to_string())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        orch.dispatch(Event::new("test_event", "Greet me"))
+            .await
+            .unwrap();
+        let before = tool_outputs().await;
+        let approved = orch.conversation.write().await.approve_plan();
+        orch.dispatch(Event::new("test_event", "Approved"))
+            .await
+            .unwrap();
+        let after = tool_outputs().await;
+
+        // The file is only created once the plan is approved
+        assert_eq!(
+            before,
+            vec!["forge_tool_plan done", "{{> partial-plan-approval.hbs}}"]
+        );
+        assert_eq!(
+            after,
+            vec![
+                "forge_tool_plan done",
+                "{{> partial-plan-approval.hbs}}",
+                "forge_tool_fs_create done",
+            ]
+        );
+        let expected = Plan {
+            goal: "Greet the user".to_string(),
+            steps: vec![PlanStep {
+                description: "Create hello.txt".to_string(),
+                files: vec!["hello.txt".to_string()],
+            }],
+            approved: true,
+        };
+        assert_eq!(approved, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_refusal_ends_the_turn() {
+        // A second chat request would find no scripted response and panic
+        let fixture = Fixture::new(vec![ChatCompletionMessage::default()
+            .refusal("I can't help with that.")
+            .finish_reason(FinishReason::Stop)]);
+        let agent = Agent::new("test-agent")
+            .model(ModelId::new("test-model"))
+            .tool_supported(true)
+            .subscribe(vec!["test_event".to_string()]);
+        let conversation = Conversation::new(
+            ConversationId::generate(),
+            Workflow::new().agents(vec![agent]),
+            vec![],
+        );
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let orch = Orchestrator::new(Arc::new(fixture), conversation, Some(Arc::new(tx)));
+
+        orch.dispatch(Event::new("test_event", "Do something"))
+            .await
+            .unwrap();
+
+        let mut actual = Vec::new();
+        while let Ok(message) = rx.try_recv() {
+            if let ChatResponse::Refusal { reason } = message.unwrap().message {
+                actual.push(reason);
+            }
+        }
+        let expected = vec![Some("I can't help with that.".to_string())];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_think_budget_refuses_fourth_think() {
+        let think = || tool_call("forge_tool_think");
+        let fixture = Fixture::new(vec![
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(think()),
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(think()),
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(think()),
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(think()),
+            ChatCompletionMessage::assistant(Content::full("All done"))
+                .add_tool_call(tool_call("complete")),
+        ]);
+        let agent = Agent::new("test-agent")
+            .model(ModelId::new("test-model"))
+            .tool_supported(true)
+            .subscribe(vec!["test_event".to_string()]);
+        let conversation = Conversation::new(
+            ConversationId::generate(),
+            Workflow::new().agents(vec![agent]),
+            vec![],
+        );
+        let orch = Orchestrator::new(Arc::new(fixture), conversation, None);
+
+        orch.dispatch(Event::new("test_event", "Think hard"))
+            .await
+            .unwrap();
+
+        let context = orch
+            .get_conversation()
+            .await
+            .unwrap()
+            .context(&AgentId::new("test-agent"))
+            .cloned()
+            .unwrap();
+        let actual = context
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                // Failures may be followed by a backtrace
+                ContextMessage::Tool(result) => Some((
+                    result.output.as_str()?.lines().next()?.to_string(),
+                    result.is_error(),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let done = ("forge_tool_think done".to_string(), false);
+        let expected = vec![
+            done.clone(),
+            done.clone(),
+            done,
+            ("{{> partial-think-limit.hbs}}".to_string(), true),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_calls_refused_for_the_plan_keep_the_think_budget() {
+        let plan = ToolCallFull {
+            name: ToolName::plan(),
+            call_id: Some(ToolCallId::new("call_plan")),
+            arguments: serde_json::json!({ "goal": "Greet the user", "steps": [] }),
+        };
+        let think = || tool_call("forge_tool_think");
+        let fixture = Fixture::new(vec![
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(plan),
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(think()),
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(think()),
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(think()),
+            ChatCompletionMessage::assistant(Content::full(""))
+                .add_tool_call(tool_call("forge_tool_fs_create")),
+            ChatCompletionMessage::assistant(Content::full("")).add_tool_call(think()),
+            ChatCompletionMessage::assistant(Content::full("Please review the plan"))
+                .add_tool_call(tool_call("forge_tool_attempt_completion")),
+        ]);
+        let agent = Agent::new("test-agent")
+            .model(ModelId::new("test-model"))
+            .tool_supported(true)
+            .subscribe(vec!["test_event".to_string()]);
+        let conversation = Conversation::new(
+            ConversationId::generate(),
+            Workflow::new().agents(vec![agent]),
+            vec![],
+        );
+        let orch = Orchestrator::new(Arc::new(fixture), conversation, None);
+
+        orch.dispatch(Event::new("test_event", "Greet me"))
+            .await
+            .unwrap();
+
+        let context = orch
+            .get_conversation()
+            .await
+            .unwrap()
+            .context(&AgentId::new("test-agent"))
+            .cloned()
+            .unwrap();
+        let actual = context
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                // Failures may be followed by a backtrace
+                ContextMessage::Tool(result) => Some((
+                    result.output.as_str()?.lines().next()?.to_string(),
+                    result.is_error(),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let done = ("forge_tool_think done".to_string(), false);
+        let expected = vec![
+            ("forge_tool_plan done".to_string(), false),
+            done.clone(),
+            done.clone(),
+            done,
+            ("{{> partial-plan-approval.hbs}}".to_string(), true),
+            ("{{> partial-think-limit.hbs}}".to_string(), true),
+        ];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::{Display, Formatter};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A step of a plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PlanStep {
+    /// What the step does and why
+    pub description: String,
+    /// Paths of the files the step creates, modifies or removes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+}
+
+/// Input type for the plan tool
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlanInput {
+    /// What the plan achieves once carried out
+    pub goal: String,
+    /// The steps of the plan, in the order they are carried out
+    pub steps: Vec<PlanStep>,
+}
+
+/// A plan the agent proposes for a large task, stored on the conversation.
+/// Until the user approves it, the agent may only use tools that don't change
+/// anything.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub goal: String,
+    pub steps: Vec<PlanStep>,
+    #[serde(default)]
+    pub approved: bool,
+}
+
+impl From<PlanInput> for Plan {
+    fn from(input: PlanInput) -> Self {
+        Self { goal: input.goal, steps: input.steps, approved: false }
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.goal)?;
+        for (index, step) in self.steps.iter().enumerate() {
+            write!(f, "\n{}. {}", index + 1, step.description)?;
+            if !step.files.is_empty() {
+                write!(f, "\n   files: {}", step.files.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn plan() -> Plan {
+        Plan::from(PlanInput {
+            goal: "Rename the config loader".to_string(),
+            steps: vec![
+                PlanStep {
+                    description: "Rename load_config to read_config".to_string(),
+                    files: vec!["src/config.rs".to_string(), "src/main.rs".to_string()],
+                },
+                PlanStep { description: "Run the tests".to_string(), files: vec![] },
+            ],
+        })
+    }
+
+    #[test]
+    fn test_plan_display() {
+        let actual = plan().to_string();
+
+        let expected = "Rename the config loader\n1. Rename load_config to read_config\n   files: src/config.rs, src/main.rs\n2. Run the tests";
+        assert_eq!(actual, expected);
+    }
+}
@@ -4,8 +4,28 @@ use url::Url;
 /// Providers that can be used.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Provider {
-    OpenAI { url: Url, key: Option<String> },
-    Anthropic { url: Url, key: String },
+    OpenAI {
+        url: Url,
+        key: Option<String>,
+    },
+    Anthropic {
+        url: Url,
+        key: String,
+    },
+    Ollama {
+        url: Url,
+    },
+    /// Azure OpenAI, the URL points at a deployment and holds the API version
+    /// when given
+    Azure {
+        url: Url,
+        key: Option<String>,
+    },
+    /// Groq's OpenAI compatible API
+    Groq {
+        url: Url,
+        key: Option<String>,
+    },
 }
 
 impl Provider {
@@ -19,7 +39,10 @@ impl Provider {
                     *set_url = Url::parse(&format!("{url}/")).unwrap();
                 }
             }
-            Provider::Anthropic { .. } => {}
+            Provider::Anthropic { .. }
+            | Provider::Ollama { .. }
+            | Provider::Azure { .. }
+            | Provider::Groq { .. } => {}
         }
     }
 
@@ -33,7 +56,40 @@ impl Provider {
                     *set_url = Url::parse(&format!("{url}/")).unwrap();
                 }
             }
-            Provider::OpenAI { .. } => {}
+            Provider::OpenAI { .. }
+            | Provider::Ollama { .. }
+            | Provider::Azure { .. }
+            | Provider::Groq { .. } => {}
+        }
+    }
+
+    /// Creates the provider serving `url`. Ollama is recognised by its default
+    /// local address, Azure OpenAI and Groq by their host, any other URL is
+    /// treated as OpenAI compatible.
+    pub fn from_url(mut url: Url, key: Option<String>) -> Provider {
+        let is_ollama = matches!(url.host_str(), Some("localhost" | "127.0.0.1"))
+            && url.port() == Some(Self::OLLAMA_PORT);
+        let is_azure = url
+            .host_str()
+            .is_some_and(|host| host.ends_with(Self::AZURE_HOST_SUFFIX));
+        let is_groq = url.host_str() == Some(Self::GROQ_HOST);
+
+        if is_ollama {
+            // Ollama's API lives at the root, regardless of the path given
+            url.set_path("/");
+            Provider::Ollama { url }
+        } else if is_azure {
+            Provider::Azure { url, key }
+        } else if is_groq {
+            // Paths are joined onto the URL, which drops its last segment
+            // unless it ends with a slash
+            if !url.path().ends_with('/') {
+                let path = format!("{}/", url.path());
+                url.set_path(&path);
+            }
+            Provider::Groq { url, key }
+        } else {
+            Provider::OpenAI { url, key }
         }
     }
 
@@ -65,10 +121,20 @@ impl Provider {
         }
     }
 
+    pub fn groq(key: &str) -> Provider {
+        Provider::Groq {
+            url: Url::parse(Provider::GROQ_URL).unwrap(),
+            key: Some(key.into()),
+        }
+    }
+
     pub fn key(&self) -> Option<&str> {
         match self {
-            Provider::OpenAI { key, .. } => key.as_deref(),
+            Provider::OpenAI { key, .. }
+            | Provider::Azure { key, .. }
+            | Provider::Groq { key, .. } => key.as_deref(),
             Provider::Anthropic { key, .. } => Some(key),
+            Provider::Ollama { .. } => None,
         }
     }
 }
@@ -78,42 +144,73 @@ impl Provider {
     pub const OPENAI_URL: &str = "https://api.openai.com/v1/";
     pub const ANTHROPIC_URL: &str = "https://api.anthropic.com/v1/";
     pub const ANTINOMY_URL: &str = "https://antinomy.ai/api/v1/";
+    pub const OLLAMA_PORT: u16 = 11434;
+    pub const AZURE_HOST_SUFFIX: &str = ".openai.azure.com";
+    pub const GROQ_URL: &str = "https://api.groq.com/openai/v1/";
+    pub const GROQ_HOST: &str = "api.groq.com";
 
     /// Converts the provider to it's base URL
     pub fn to_base_url(&self) -> Url {
         match self {
             Provider::OpenAI { url, .. } => url.clone(),
             Provider::Anthropic { url, .. } => url.clone(),
+            Provider::Ollama { url } => url.clone(),
+            Provider::Azure { url, .. } => url.clone(),
+            Provider::Groq { url, .. } => url.clone(),
         }
     }
 
     pub fn is_antinomy(&self) -> bool {
         match self {
             Provider::OpenAI { url, .. } => url.as_str().starts_with(Self::ANTINOMY_URL),
-            Provider::Anthropic { .. } => false,
+            Provider::Anthropic { .. }
+            | Provider::Ollama { .. }
+            | Provider::Azure { .. }
+            | Provider::Groq { .. } => false,
         }
     }
 
     pub fn is_open_router(&self) -> bool {
         match self {
             Provider::OpenAI { url, .. } => url.as_str().starts_with(Self::OPEN_ROUTER_URL),
-            Provider::Anthropic { .. } => false,
+            Provider::Anthropic { .. }
+            | Provider::Ollama { .. }
+            | Provider::Azure { .. }
+            | Provider::Groq { .. } => false,
         }
     }
 
     pub fn is_open_ai(&self) -> bool {
         match self {
             Provider::OpenAI { url, .. } => url.as_str().starts_with(Self::OPENAI_URL),
-            Provider::Anthropic { .. } => false,
+            Provider::Anthropic { .. }
+            | Provider::Ollama { .. }
+            | Provider::Azure { .. }
+            | Provider::Groq { .. } => false,
         }
     }
 
     pub fn is_anthropic(&self) -> bool {
         match self {
-            Provider::OpenAI { .. } => false,
+            Provider::OpenAI { .. }
+            | Provider::Ollama { .. }
+            | Provider::Azure { .. }
+            | Provider::Groq { .. } => false,
             Provider::Anthropic { url, .. } => url.as_str().starts_with(Self::ANTHROPIC_URL),
         }
     }
+
+    pub fn is_ollama(&self) -> bool {
+        matches!(self, Provider::Ollama { .. })
+    }
+
+    pub fn is_azure(&self) -> bool {
+        matches!(self, Provider::Azure { .. })
+    }
+
+    pub fn is_groq(&self) -> bool {
+        matches!(self, Provider::Groq { .. })
+    }
 }
 
 #[cfg(test)]
@@ -219,4 +316,50 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_from_url_recognises_ollama() {
+        let actual = [
+            "http://localhost:11434",
+            "http://127.0.0.1:11434/v1/",
+            "http://localhost:8080/v1/",
+        ]
+        .map(|url| Provider::from_url(Url::from_str(url).unwrap(), None));
+
+        let expected = [
+            Provider::Ollama { url: Url::from_str("http://localhost:11434/").unwrap() },
+            Provider::Ollama { url: Url::from_str("http://127.0.0.1:11434/").unwrap() },
+            Provider::OpenAI {
+                url: Url::from_str("http://localhost:8080/v1/").unwrap(),
+                key: None,
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_from_url_recognises_azure() {
+        let url = Url::from_str(
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-06-01",
+        )
+        .unwrap();
+
+        let actual = Provider::from_url(url.clone(), Some("key".to_string()));
+
+        let expected = Provider::Azure { url, key: Some("key".to_string()) };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_from_url_recognises_groq() {
+        let url = Url::from_str("https://api.groq.com/openai/v1").unwrap();
+
+        let actual = Provider::from_url(url, Some("key".to_string()));
+
+        let expected = Provider::Groq {
+            url: Url::from_str(Provider::GROQ_URL).unwrap(),
+            key: Some("key".to_string()),
+        };
+        assert_eq!(actual, expected);
+    }
 }
@@ -0,0 +1,86 @@
+use std::future::Future;
+
+use derive_more::derive::Display;
+use serde::{Deserialize, Serialize};
+use tracing::Instrument;
+use uuid::Uuid;
+
+tokio::task_local! {
+    static REQUEST_ID: RequestId;
+}
+
+/// Correlates everything that happens while serving a single chat request:
+/// the orchestrator, the tool calls it makes and the requests sent to the
+/// provider.
+#[derive(Debug, Display, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct RequestId(Uuid);
+
+impl RequestId {
+    /// Header used to forward the id to providers so that their logs can be
+    /// matched with ours
+    pub const HEADER: &'static str = "X-Request-Id";
+
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    /// Returns the id of the request that the current task is serving, if any
+    pub fn current() -> Option<Self> {
+        REQUEST_ID.try_with(|id| *id).ok()
+    }
+
+    /// Runs the future inside a `request` span carrying this id, making the id
+    /// available through [`RequestId::current`] for the duration of the future
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        let span = tracing::info_span!("request", request_id = %self);
+        REQUEST_ID.scope(self, future.instrument(span)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_stream::Captured;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_current_outside_scope() {
+        assert_eq!(RequestId::current(), None);
+    }
+
+    #[tokio::test]
+    async fn test_current_inside_scope() {
+        let fixture = RequestId::generate();
+
+        let actual = fixture.scope(async { RequestId::current() }).await;
+
+        assert_eq!(actual, Some(fixture));
+    }
+
+    #[tokio::test]
+    async fn test_id_appears_in_child_spans() {
+        let fixture = RequestId::generate();
+        let captured = Captured::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(captured.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // A span's parent is the span current where it's created, so the child
+        // span is created inside the scope
+        fixture
+            .scope(async {
+                async { tracing::info!("calling tool") }
+                    .instrument(tracing::info_span!("tool_call"))
+                    .await
+            })
+            .await;
+
+        let actual = captured.contents();
+        let expected = format!("request{{request_id={fixture}}}:tool_call: ");
+        assert!(actual.contains(&expected), "{actual}");
+    }
+}
@@ -1,13 +1,62 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Environment variables holding the API keys of the providers, which commands
+/// don't inherit unless the workflow allows it
+pub const SECRET_ENV_VARS: [&str; 6] = [
+    "FORGE_KEY",
+    "OPENROUTER_API_KEY",
+    "OPENAI_API_KEY",
+    "ANTHROPIC_API_KEY",
+    "GROQ_API_KEY",
+    "AZURE_OPENAI_API_KEY",
+];
+
+/// Changes to the environment a command inherits from forge
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandEnv {
+    /// Variables set for the command, taking precedence over inherited ones
+    pub vars: HashMap<String, String>,
+    /// Inherited variables that are removed from the command's environment
+    pub removed: Vec<String>,
+}
+
 /// Output from a command execution
 pub struct CommandOutput {
     pub command: String,
     pub stdout: String,
     pub stderr: String,
     pub exit_code: Option<i32>,
+    /// Time after which the command was stopped for running too long, the
+    /// output then only holds what was printed until that point
+    pub timed_out: Option<Duration>,
 }
 
 impl CommandOutput {
     pub fn success(&self) -> bool {
-        self.exit_code.is_none_or(|code| code >= 0)
+        self.timed_out.is_none() && self.exit_code.is_none_or(|code| code == 0)
+    }
+}
+
+/// The stream of a command that a line of output was printed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A line printed by a command while it runs, without its line ending
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputLine {
+    pub stream: OutputStream,
+    pub text: String,
+}
+
+impl OutputLine {
+    pub fn new(stream: OutputStream, text: impl ToString) -> Self {
+        Self { stream, text: text.to_string() }
     }
 }
@@ -0,0 +1,78 @@
+use derive_setters::Setters;
+use merge::Merge;
+use serde::{Deserialize, Serialize};
+
+use crate::{Compact, ModelId};
+
+/// Workflow wide settings for the summaries generated while compacting the
+/// context of an agent. Unset values fall back to each agent's compaction
+/// settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Merge, Setters)]
+#[setters(strip_option, into)]
+pub struct SummarizeConfig {
+    /// Model used to generate summaries, usually a cheaper/faster model than
+    /// the one used by the agents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub model: Option<ModelId>,
+
+    /// Template rendered to request a summary. It has access to the `context`
+    /// being summarized and the `summary_tag` the summary should be wrapped
+    /// in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub prompt: Option<String>,
+}
+
+impl SummarizeConfig {
+    /// Overrides the compaction settings of an agent with the configured
+    /// values
+    pub fn apply(&self, compact: &mut Compact) {
+        if let Some(model) = self.model.clone() {
+            compact.model = model;
+        }
+
+        if let Some(prompt) = self.prompt.clone() {
+            compact.prompt = Some(prompt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_apply_overrides_configured_values() {
+        let fixture = SummarizeConfig::default()
+            .model(ModelId::new("cheap-model"))
+            .prompt("Summarize tersely: {{context}}");
+        let mut actual = Compact::new(ModelId::new("agent-model"));
+        fixture.apply(&mut actual);
+
+        assert_eq!(
+            (actual.model, actual.prompt),
+            (
+                ModelId::new("cheap-model"),
+                Some("Summarize tersely: {{context}}".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn test_apply_keeps_agent_values_when_unset() {
+        let fixture = SummarizeConfig::default();
+        let mut actual = Compact::new(ModelId::new("agent-model")).prompt("Agent prompt");
+        fixture.apply(&mut actual);
+
+        assert_eq!(
+            (actual.model, actual.prompt),
+            (
+                ModelId::new("agent-model"),
+                Some("Agent prompt".to_string())
+            )
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use crate::{estimate_token_count, Context, ModelId};
+
+/// Counts the number of tokens a piece of text consumes for a model
+pub trait TokenCounter: Send + Sync {
+    /// Returns the number of tokens in `text`
+    fn count(&self, text: &str) -> usize;
+
+    /// Estimates the number of tokens the context will consume once sent
+    fn estimate_tokens(&self, context: &Context) -> usize {
+        self.count(&context.to_text())
+    }
+}
+
+/// Model agnostic counter that assumes ~4 characters per token
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicCounter;
+
+impl TokenCounter for HeuristicCounter {
+    fn count(&self, text: &str) -> usize {
+        estimate_token_count(text.len())
+    }
+}
+
+/// Exact counter for OpenAI-family models using their BPE tokenizers
+#[cfg(feature = "tiktoken")]
+#[derive(Clone)]
+pub struct TiktokenCounter {
+    bpe: Arc<parking_lot::Mutex<tiktoken_rs::CoreBPE>>,
+}
+
+#[cfg(feature = "tiktoken")]
+impl TiktokenCounter {
+    /// Creates a counter for the given model, returns `None` if the model
+    /// doesn't use a known OpenAI tokenizer
+    pub fn for_model(model: &ModelId) -> Option<Self> {
+        use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+
+        // Provider prefixed ids such as `openai/gpt-4o` are looked up by name
+        let name = model.as_str().rsplit('/').next().unwrap_or_default();
+        let bpe = match get_tokenizer(name)? {
+            Tokenizer::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            Tokenizer::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+            _ => return None,
+        };
+
+        Some(Self { bpe })
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl TokenCounter for TiktokenCounter {
+    fn count(&self, text: &str) -> usize {
+        self.bpe.lock().encode_ordinary(text).len()
+    }
+}
+
+/// Picks the most accurate token counter available for the model
+#[cfg_attr(not(feature = "tiktoken"), allow(unused_variables))]
+pub fn token_counter(model: &ModelId) -> Arc<dyn TokenCounter> {
+    #[cfg(feature = "tiktoken")]
+    if let Some(counter) = TiktokenCounter::for_model(model) {
+        return Arc::new(counter);
+    }
+
+    Arc::new(HeuristicCounter)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ContextMessage;
+
+    #[test]
+    fn test_heuristic_count() {
+        let fixture = "a".repeat(400);
+        let actual = HeuristicCounter.count(&fixture);
+        let expected = 100;
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_heuristic_estimate_tokens() {
+        let fixture = Context::default()
+            .add_message(ContextMessage::system("You are a helpful assistant"))
+            .add_message(ContextMessage::user("Hello there", None));
+        let actual = HeuristicCounter.estimate_tokens(&fixture);
+        let expected = fixture.to_text().len() / 4;
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn test_tiktoken_within_tolerance_of_heuristic() {
+        let fixture = include_str!("../../../README.md");
+        let tiktoken = TiktokenCounter::for_model(&ModelId::new("openai/gpt-4o"))
+            .unwrap()
+            .count(fixture);
+        let heuristic = HeuristicCounter.count(fixture);
+
+        let ratio = heuristic as f64 / tiktoken as f64;
+        assert!(
+            (0.5..=1.5).contains(&ratio),
+            "heuristic {heuristic} vs tiktoken {tiktoken}"
+        );
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn test_token_counter_falls_back_for_unknown_models() {
+        let fixture = "a".repeat(400);
+        let actual = (
+            token_counter(&ModelId::new("anthropic/claude-3.7-sonnet")).count(&fixture),
+            TiktokenCounter::for_model(&ModelId::new("anthropic/claude-3.7-sonnet")).is_none(),
+        );
+        let expected = (100, true);
+        assert_eq!(actual, expected);
+    }
+}
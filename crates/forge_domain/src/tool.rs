@@ -2,7 +2,7 @@ use schemars::JsonSchema;
 use serde_json::Value;
 
 use crate::{
-    ExecutableTool, NamedTool, ToolCallContext, ToolDefinition, ToolDescription, ToolOutput,
+    ExecutableTool, Impact, NamedTool, ToolCallContext, ToolDefinition, ToolDescription, ToolOutput,
 };
 
 struct JsonTool<T>(T);
@@ -28,6 +28,20 @@ where
         let input: T::Input = serde_json::from_value(input)?;
         self.0.call(context, input).await
     }
+
+    async fn impact(
+        &self,
+        context: &ToolCallContext,
+        input: &Self::Input,
+    ) -> anyhow::Result<Option<Impact>> {
+        let input: T::Input = serde_json::from_value(input.clone())?;
+        self.0.impact(context, &input).await
+    }
+
+    fn is_read_only(&self, input: &Self::Input) -> bool {
+        serde_json::from_value::<T::Input>(input.clone())
+            .is_ok_and(|input| self.0.is_read_only(&input))
+    }
 }
 
 pub struct Tool {
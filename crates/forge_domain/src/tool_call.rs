@@ -5,6 +5,43 @@ use serde_json::Value;
 
 use crate::{extract_tag_content, Error, Result, ToolName};
 
+/// Keys whose values are credentials, such as the headers passed to the fetch
+/// tool
+const REDACTED_KEYS: [&str; 3] = ["authorization", "proxy-authorization", "cookie"];
+
+/// Replaces the values of credential keys at any depth of `value`, so that it
+/// can be logged or shown. Strings holding JSON, such as tool call arguments
+/// in provider requests, are redacted too.
+pub fn redact_credentials(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if REDACTED_KEYS.iter().any(|k| key.eq_ignore_ascii_case(k)) {
+                        Value::String("[REDACTED]".to_string())
+                    } else {
+                        redact_credentials(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_credentials).collect()),
+        Value::String(text) if text.starts_with('{') => match serde_json::from_str::<Value>(text) {
+            Ok(parsed) => {
+                let redacted = redact_credentials(&parsed);
+                if redacted == parsed {
+                    value.clone()
+                } else {
+                    Value::String(redacted.to_string())
+                }
+            }
+            Err(_) => value.clone(),
+        },
+        value => value.clone(),
+    }
+}
+
 /// Unique identifier for a using a tool
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(transparent)]
@@ -73,6 +110,15 @@ impl ToolCallFull {
         Self { name: tool_name, call_id: None, arguments: Value::default() }
     }
 
+    /// A copy of the call with credentials in its arguments redacted, to be
+    /// shown or kept once the call has been made
+    pub fn redacted(&self) -> Self {
+        Self {
+            arguments: redact_credentials(&self.arguments),
+            ..self.clone()
+        }
+    }
+
     pub fn try_from_parts(parts: &[ToolCallPart]) -> Result<Vec<Self>> {
         if parts.is_empty() {
             return Ok(vec![]);
@@ -137,9 +183,44 @@ impl ToolCallFull {
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
+    use serde_json::json;
 
     use super::*;
 
+    #[test]
+    fn test_redact_credentials() {
+        let fixture = json!({
+            "url": "https://api.example.com",
+            "headers": {"Authorization": "Bearer secret", "Cookie": "session=secret", "Accept": "application/json"},
+            "proxies": [{"proxy-authorization": "Basic c2VjcmV0"}]
+        });
+
+        let actual = redact_credentials(&fixture);
+
+        let expected = json!({
+            "url": "https://api.example.com",
+            "headers": {"Authorization": "[REDACTED]", "Cookie": "[REDACTED]", "Accept": "application/json"},
+            "proxies": [{"proxy-authorization": "[REDACTED]"}]
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_redact_credentials_in_serialized_arguments() {
+        let fixture = json!({
+            "tool_calls": [{"arguments": r#"{"headers":{"cookie":"session=secret"}}"#}],
+            "content": "{not json"
+        });
+
+        let actual = redact_credentials(&fixture);
+
+        let expected = json!({
+            "tool_calls": [{"arguments": r#"{"headers":{"cookie":"[REDACTED]"}}"#}],
+            "content": "{not json"
+        });
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_multiple_calls() {
         let input = [
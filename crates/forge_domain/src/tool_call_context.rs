@@ -4,7 +4,7 @@ use derive_setters::Setters;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::RwLock;
 
-use crate::{Agent, AgentMessage, ChatResponse};
+use crate::{Agent, AgentMessage, ChatResponse, ConversationId, OutputLine};
 
 /// Type alias for Arc<Sender<Result<AgentMessage<ChatResponse>>>>
 type ArcSender = Arc<Sender<anyhow::Result<AgentMessage<ChatResponse>>>>;
@@ -15,6 +15,9 @@ pub struct ToolCallContext {
     #[setters(strip_option)]
     pub agent: Option<Agent>,
     pub sender: Option<ArcSender>,
+    /// The conversation the tool is called in
+    #[setters(strip_option)]
+    pub conversation_id: Option<ConversationId>,
     /// Indicates whether the tool execution has been completed
     /// This is wrapped in an RWLock for thread-safety
     #[setters(skip)]
@@ -27,6 +30,7 @@ impl ToolCallContext {
         Self {
             agent: None,
             sender: None,
+            conversation_id: None,
             is_complete: Arc::new(RwLock::new(false)),
         }
     }
@@ -83,6 +87,19 @@ impl ToolCallContext {
             Ok(())
         }
     }
+
+    /// Sends a line printed by a command that is still running
+    pub async fn send_output_line(&self, line: OutputLine) -> anyhow::Result<()> {
+        if let Some(agent) = &self.agent {
+            self.send(AgentMessage::new(
+                agent.id.clone(),
+                ChatResponse::ToolOutputChunk(line),
+            ))
+            .await
+        } else {
+            Ok(())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,10 +1,13 @@
+use std::time::Duration;
+
 use derive_setters::Setters;
 use schemars::schema::RootSchema;
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::{NamedTool, ToolCallContext, ToolName, ToolOutput};
+use crate::{Error, Impact, NamedTool, Result, ToolCallContext, ToolName, ToolOutput};
 
 ///
 /// Refer to the specification over here:
@@ -16,6 +19,16 @@ pub struct ToolDefinition {
     pub description: String,
     pub input_schema: RootSchema,
     pub output_schema: Option<RootSchema>,
+
+    /// Maximum time a single call to the tool may run before it is cancelled,
+    /// falls back to the service wide default when not set
+    #[serde(skip)]
+    pub timeout: Option<Duration>,
+
+    /// Set for tools that already keep their output within a budget of their
+    /// own, so the service doesn't clip it a second time
+    #[serde(skip)]
+    pub limits_output: bool,
 }
 
 impl ToolDefinition {
@@ -26,6 +39,41 @@ impl ToolDefinition {
             description: String::new(),
             input_schema: schemars::schema_for!(()), // Empty input schema
             output_schema: None,
+            timeout: None,
+            limits_output: false,
+        }
+    }
+
+    /// Checks the arguments of a call against the input schema of the tool,
+    /// so that a malformed call is reported back instead of reaching the
+    /// tool. Every violation is listed, letting the model fix them in one go.
+    /// Schemas that can't be compiled, such as broken ones advertised by MCP
+    /// servers, are not enforced.
+    pub fn validate(&self, arguments: &Value) -> Result<()> {
+        let schema = serde_json::to_value(&self.input_schema).map_err(Error::ToolCallArgument)?;
+        let validator = match jsonschema::validator_for(&schema) {
+            Ok(validator) => validator,
+            Err(error) => {
+                tracing::warn!(tool = %self.name, %error, "Invalid tool input schema");
+                return Ok(());
+            }
+        };
+
+        let errors: Vec<_> = validator
+            .iter_errors(arguments)
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                if path.is_empty() {
+                    format!("- {error}")
+                } else {
+                    format!("- {path}: {error}")
+                }
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ToolCallSchema { name: self.name.clone(), errors })
         }
     }
 }
@@ -44,6 +92,8 @@ where
             description: t.description(),
             input_schema: input,
             output_schema: Some(output),
+            timeout: None,
+            limits_output: false,
         }
     }
 }
@@ -54,11 +104,77 @@ pub trait ToolDescription {
 
 #[async_trait::async_trait]
 pub trait ExecutableTool {
-    type Input: DeserializeOwned;
+    type Input: DeserializeOwned + Send + Sync;
 
     async fn call(
         &self,
         context: ToolCallContext,
         input: Self::Input,
     ) -> anyhow::Result<ToolOutput>;
+
+    /// Estimates what calling the tool with `input` would change, without
+    /// changing anything. Tools that don't modify anything return `None`.
+    async fn impact(
+        &self,
+        _context: &ToolCallContext,
+        _input: &Self::Input,
+    ) -> anyhow::Result<Option<Impact>> {
+        Ok(None)
+    }
+
+    /// Whether calling the tool with `input` only reads or reasons, without
+    /// changing anything. Only such calls are allowed while a plan awaits
+    /// approval.
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Deserialize, JsonSchema)]
+    #[allow(dead_code)]
+    struct SearchInput {
+        pattern: String,
+        max_results: Option<u64>,
+    }
+
+    fn fixture() -> ToolDefinition {
+        ToolDefinition::new("search").input_schema(schemars::schema_for!(SearchInput))
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_arguments() {
+        let actual = fixture().validate(&json!({"pattern": "fn main", "max_results": 5}));
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_validate_missing_required_field() {
+        let actual = fixture()
+            .validate(&json!({"max_results": 5}))
+            .unwrap_err()
+            .to_string();
+
+        let expected = "Invalid arguments for tool 'search':\n- \"pattern\" is a required property";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_validate_wrong_type() {
+        let actual = fixture()
+            .validate(&json!({"pattern": 42}))
+            .unwrap_err()
+            .to_string();
+
+        let expected =
+            "Invalid arguments for tool 'search':\n- /pattern: 42 is not of type \"string\"";
+        assert_eq!(actual, expected);
+    }
 }
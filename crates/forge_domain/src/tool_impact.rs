@@ -0,0 +1,110 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// What a tool call would change, estimated without running it so that it can
+/// be shown to the user before the call is approved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Impact {
+    /// Writes `bytes` bytes to a file, `existing` being the size of the file
+    /// it replaces, if any
+    Write {
+        path: PathBuf,
+        bytes: usize,
+        existing: Option<u64>,
+        append: bool,
+    },
+    /// Deletes a file of `bytes` bytes
+    Remove { path: PathBuf, bytes: u64 },
+    /// Runs the commands a shell command line is made of in `cwd`, after
+    /// snapshotting the files in it if `snapshot` is set
+    Command {
+        commands: Vec<String>,
+        cwd: PathBuf,
+        snapshot: bool,
+    },
+}
+
+impl Display for Impact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Write { path, bytes, append: true, .. } => {
+                write!(f, "Appends {bytes} bytes to {}", path.display())
+            }
+            Self::Write { path, bytes, existing: Some(existing), .. } => write!(
+                f,
+                "Overwrites {} ({existing} bytes) with {bytes} bytes",
+                path.display()
+            ),
+            Self::Write { path, bytes, existing: None, .. } => {
+                write!(f, "Creates {} with {bytes} bytes", path.display())
+            }
+            Self::Remove { path, bytes } => {
+                write!(f, "Deletes {} ({bytes} bytes)", path.display())
+            }
+            Self::Command { commands, cwd, snapshot } => {
+                let count = match commands.len() {
+                    1 => "1 command".to_string(),
+                    n => format!("{n} commands"),
+                };
+                write!(
+                    f,
+                    "Runs {count} in {}: `{}`",
+                    cwd.display(),
+                    commands.join("`, `")
+                )?;
+                if *snapshot {
+                    write!(f, ", snapshotting the files there first")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let fixture = [
+            Impact::Write {
+                path: "/a.txt".into(),
+                bytes: 12,
+                existing: Some(40),
+                append: false,
+            },
+            Impact::Write {
+                path: "/a.txt".into(),
+                bytes: 12,
+                existing: None,
+                append: false,
+            },
+            Impact::Write {
+                path: "/a.txt".into(),
+                bytes: 12,
+                existing: Some(40),
+                append: true,
+            },
+            Impact::Remove { path: "/a.txt".into(), bytes: 40 },
+            Impact::Command {
+                commands: vec!["cargo fmt".to_string(), "git diff".to_string()],
+                cwd: "/project".into(),
+                snapshot: true,
+            },
+        ];
+
+        let actual = fixture.iter().map(Impact::to_string).collect::<Vec<_>>();
+
+        let expected = vec![
+            "Overwrites /a.txt (40 bytes) with 12 bytes",
+            "Creates /a.txt with 12 bytes",
+            "Appends 12 bytes to /a.txt",
+            "Deletes /a.txt (40 bytes)",
+            "Runs 2 commands in /project: `cargo fmt`, `git diff`, snapshotting the files there first",
+        ];
+        assert_eq!(actual, expected);
+    }
+}
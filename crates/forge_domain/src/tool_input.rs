@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use schemars::JsonSchema;
@@ -27,6 +28,14 @@ pub enum ToolInput {
     #[serde(rename = "forge_tool_fs_remove")]
     FSRemove(FSRemoveInput),
 
+    /// Input for the file move tool
+    #[serde(rename = "forge_tool_fs_move")]
+    FSMove(FSMoveInput),
+
+    /// Input for the file copy tool
+    #[serde(rename = "forge_tool_fs_copy")]
+    FSCopy(FSCopyInput),
+
     /// Input for the file patch tool
     #[serde(rename = "forge_tool_fs_patch")]
     FSPatch(FSPatchInput),
@@ -56,7 +65,13 @@ pub enum ToolInput {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FSReadInput {
     /// The path of the file to read, always provide absolute paths.
-    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// The absolute paths of up to 10 files to read in one call, used instead
+    /// of path. The files are returned in the given order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
 
     /// Optional start position in characters (0-based). If provided, reading
     /// will start from this character position.
@@ -67,6 +82,13 @@ pub struct FSReadInput {
     /// will end at this character position.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end_char: Option<u64>,
+
+    /// If set to true, comments are removed from source files in supported
+    /// languages. Removed comments leave blank lines, so line numbers stay
+    /// the same.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub strip_comments: bool,
 }
 
 /// Input type for the file write tool
@@ -78,6 +100,7 @@ pub struct FSWriteInput {
     /// The content to write to the file. ALWAYS provide the COMPLETE intended
     /// content of the file, without any truncation or omissions. You MUST
     /// include ALL parts of the file, even if they haven't been modified.
+    /// When appending, provide only the content to add.
     pub content: String,
 
     /// If set to true, existing files will be overwritten. If not set and the
@@ -86,19 +109,26 @@ pub struct FSWriteInput {
     #[serde(default)]
     #[serde(skip_serializing_if = "is_default")]
     pub overwrite: bool,
+
+    /// If set to true, the content is added to the end of the file instead of
+    /// replacing it, creating the file if it doesn't exist. Overwrite is not
+    /// needed when appending.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub append: bool,
 }
 
 /// Input type for the file search tool
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct FSSearchInput {
     /// The absolute path of the directory or file to search in. If it's a
     /// directory, it will be searched recursively. If it's a file path,
     /// only that specific file will be searched.
     pub path: String,
 
-    /// The regular expression pattern to search for in file contents. Uses Rust
-    /// regex syntax. If not provided, only file name matching will be
-    /// performed.
+    /// The pattern to search for in file contents. Uses Rust regex syntax
+    /// unless `is_regex` is false. If not provided, only file name matching
+    /// will be performed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub regex: Option<String>,
 
@@ -106,6 +136,49 @@ pub struct FSSearchInput {
     /// If not provided, it will search all files (*).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_pattern: Option<String>,
+
+    /// Whether to also search files excluded by .gitignore, .ignore and the
+    /// global git excludes, such as build output or dependencies, and noisy
+    /// files such as lock files and minified bundles. Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_ignored: Option<bool>,
+
+    /// Whether to also search hidden files and directories, whose names start
+    /// with a dot. Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_hidden: Option<bool>,
+
+    /// Whether `regex` is a regular expression. When false it is searched
+    /// for as literal text. Defaults to true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_regex: Option<bool>,
+
+    /// Whether letter case must match exactly. Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+
+    /// Whether matches must start and end at word boundaries. Defaults to
+    /// false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub whole_word: Option<bool>,
+
+    /// Number of lines to show before each match, at most 10. Defaults to 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_before: Option<usize>,
+
+    /// Number of lines to show after each match, at most 10. Defaults to 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_after: Option<usize>,
+
+    /// Maximum number of results (matching lines, or files when no regex is
+    /// given) to return. Defaults to 200.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<usize>,
+
+    /// Number of results to skip, used to fetch the next page of a truncated
+    /// search. Defaults to 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
 }
 
 /// Input type for the file remove tool
@@ -115,6 +188,42 @@ pub struct FSRemoveInput {
     pub path: String,
 }
 
+/// Input type for the file move tool
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FSMoveInput {
+    /// The path of the file to move (absolute path required)
+    pub source: String,
+
+    /// The new path of the file (absolute path required). Missing parent
+    /// directories are created.
+    pub destination: String,
+
+    /// If set to true, an existing file at the destination will be
+    /// overwritten. If not set and the destination exists, an error will be
+    /// returned.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub overwrite: bool,
+}
+
+/// Input type for the file copy tool
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FSCopyInput {
+    /// The path of the file to copy (absolute path required)
+    pub source: String,
+
+    /// The path of the copy (absolute path required), which must be inside
+    /// the project directory. Missing parent directories are created.
+    pub destination: String,
+
+    /// If set to true, an existing file at the destination will be
+    /// overwritten. If not set and the destination exists, an error will be
+    /// returned.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub overwrite: bool,
+}
+
 /// Operation types that can be performed on matched text
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -150,6 +259,12 @@ pub struct FSPatchInput {
     /// The content to use for the operation (replacement text, text to
     /// prepend/append, or target text for swap operations)
     pub content: String,
+
+    /// If set to true, the change is only previewed as a diff and the file is
+    /// left untouched.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub dry_run: bool,
 }
 
 /// Input type for the file undo tool
@@ -160,13 +275,16 @@ pub struct FSUndoInput {
 }
 
 /// Input type for the shell command tool
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ShellInput {
     /// The shell command to execute.
     pub command: String,
 
-    /// The working directory where the command should be executed.
-    pub cwd: PathBuf,
+    /// The directory to run the command in, absolute or relative to the
+    /// current directory. The current directory starts at the project root
+    /// and is used when this is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
 
     /// Whether to preserve ANSI escape codes in the output.
     /// If true, ANSI escape codes will be preserved in the output.
@@ -174,6 +292,50 @@ pub struct ShellInput {
     #[serde(default)]
     #[serde(skip_serializing_if = "is_default")]
     pub keep_ansi: bool,
+
+    /// Whether a non-zero exit code should be reported as a failure.
+    /// If true, the call fails when the command exits with a non-zero code.
+    /// If false (default), the output is returned along with the exit code so
+    /// it can be inspected, for eg: `grep` exits with 1 when nothing matches.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub fail_on_nonzero: bool,
+
+    /// Whether the command may modify or delete many files at once, for eg:
+    /// `cargo fmt` or `git checkout .`. If true, the files in the working
+    /// directory are snapshotted before the command runs so the changes can be
+    /// reverted.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub destructive: bool,
+
+    /// Maximum number of seconds the command may run, at most 3600. A command
+    /// that runs longer is stopped along with every process it started, and
+    /// the output it printed so far is returned. Defaults to 300 seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Whether the directory the command runs in becomes the current
+    /// directory for later commands. A command that ends with a plain
+    /// `cd <dir>` always makes `<dir>` the current directory when it succeeds.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub persist_cwd: bool,
+
+    /// Whether to return the whole output. By default a long output is cut
+    /// down to its first and last lines, and the full output is saved to files
+    /// listed in the result. The output is still clipped to the overall limit
+    /// of a tool result.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub full_output: bool,
+
+    /// Environment variables to set for the command, for eg:
+    /// `{"RUST_LOG": "debug"}`. They are added to the environment the command
+    /// inherits.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
 }
 
 /// Input type for the net fetch tool
@@ -13,6 +13,16 @@ impl ToolName {
 }
 
 impl ToolName {
+    /// Name of the tool agents use to reason without taking an action
+    pub fn think() -> Self {
+        ToolName::new("forge_tool_think")
+    }
+
+    /// Name of the tool agents use to propose a plan for the user to approve
+    pub fn plan() -> Self {
+        ToolName::new("forge_tool_plan")
+    }
+
     pub fn into_string(self) -> String {
         self.0
     }
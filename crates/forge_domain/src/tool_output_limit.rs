@@ -0,0 +1,37 @@
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+
+/// Budget for the output of a single tool call. Outputs exceeding it are
+/// clipped in the middle, keeping their head and tail.
+#[derive(Debug, Clone, Serialize, Deserialize, Setters, PartialEq)]
+#[setters(into)]
+pub struct ToolOutputLimit {
+    /// Maximum number of bytes kept from the output
+    pub max_bytes: usize,
+
+    /// Maximum number of lines kept from the output
+    pub max_lines: usize,
+
+    /// Whether the full output of a clipped result is written to a temp file,
+    /// whose path is included in the result
+    pub save_full_output: bool,
+
+    /// Number of lines kept from the start of a clipped shell command output
+    pub shell_head_lines: usize,
+
+    /// Number of lines kept from the end of a clipped shell command output,
+    /// where errors usually are
+    pub shell_tail_lines: usize,
+}
+
+impl Default for ToolOutputLimit {
+    fn default() -> Self {
+        Self {
+            max_bytes: 40_000,
+            max_lines: 2_000,
+            save_full_output: true,
+            shell_head_lines: 100,
+            shell_tail_lines: 300,
+        }
+    }
+}
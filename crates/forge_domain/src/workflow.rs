@@ -7,7 +7,7 @@ use serde_json::Value;
 
 use crate::temperature::Temperature;
 use crate::update::Update;
-use crate::{Agent, AgentId, ModelId, TopK, TopP};
+use crate::{Agent, AgentId, CommandPolicy, ModelId, SummarizeConfig, TopK, TopP};
 
 /// Configuration for a workflow that contains all settings
 /// required to initialize a workflow.
@@ -34,6 +34,11 @@ pub struct Workflow {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub commands: Vec<Command>,
 
+    /// Named sequences of prompts and commands that can be run with '/run'
+    #[merge(strategy = crate::merge::vec::append)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub macros: Vec<Macro>,
+
     /// Default model ID to use for agents in this workflow
     #[merge(strategy = crate::merge::option)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -100,6 +105,38 @@ pub struct Workflow {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[merge(strategy = crate::merge::option)]
     pub tool_supported: Option<bool>,
+
+    /// Summarization settings used by all agents when compacting their
+    /// context, for eg: a cheaper model or a terser prompt. If not specified,
+    /// each agent's compaction settings will be used.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub summarize: Option<SummarizeConfig>,
+
+    /// Fraction of the model's context window (0.0 to 1.0) that triggers
+    /// automatic compaction for all agents with compaction configured. If not
+    /// specified, each agent's `context_window_ratio` will be used.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub compact_threshold: Option<f64>,
+
+    /// Environment variables holding provider API keys, such as
+    /// `OPENAI_API_KEY`, that shell commands of all agents may inherit. Those
+    /// variables are removed from the environment of commands otherwise.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub env_allowlist: Option<Vec<String>>,
+
+    /// Which shell commands all agents may run without asking, which are
+    /// refused and which need the user's approval. Commands aren't checked
+    /// when not specified.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[merge(strategy = crate::merge::option)]
+    pub command_policy: Option<CommandPolicy>,
 }
 
 impl Default for Workflow {
@@ -121,6 +158,42 @@ pub struct Command {
     pub prompt: Option<String>,
 }
 
+/// A named sequence of steps run one after another. Each step is either a
+/// prompt for the agent or a command such as '/compact' or '!cargo fmt'.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Merge, Setters)]
+#[setters(strip_option, into)]
+pub struct Macro {
+    #[merge(strategy = crate::merge::std::overwrite)]
+    pub name: String,
+
+    #[merge(strategy = crate::merge::option)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Steps in the order they are run, `{arg}` is replaced by the text given
+    /// after the macro's name
+    #[merge(strategy = crate::merge::std::overwrite)]
+    pub steps: Vec<String>,
+}
+
+impl Macro {
+    /// Placeholder replaced by the argument of the macro
+    pub const ARG: &str = "{arg}";
+
+    /// Whether any step expects an argument
+    pub fn takes_arg(&self) -> bool {
+        self.steps.iter().any(|step| step.contains(Self::ARG))
+    }
+
+    /// Returns the steps with `{arg}` replaced by `arg`
+    pub fn expand(&self, arg: &str) -> Vec<String> {
+        self.steps
+            .iter()
+            .map(|step| step.replace(Self::ARG, arg))
+            .collect()
+    }
+}
+
 impl Workflow {
     /// Creates a new empty workflow with all fields set to their empty state.
     /// This is useful for testing where you want to build a workflow from
@@ -130,6 +203,7 @@ impl Workflow {
             agents: Vec::new(),
             variables: HashMap::new(),
             commands: Vec::new(),
+            macros: Vec::new(),
             model: None,
             max_walker_depth: None,
             custom_rules: None,
@@ -138,6 +212,10 @@ impl Workflow {
             top_k: None,
             tool_supported: None,
             updates: None,
+            summarize: None,
+            compact_threshold: None,
+            env_allowlist: None,
+            command_policy: None,
         }
     }
 
@@ -226,4 +304,23 @@ mod tests {
         // Assert
         assert_eq!(base.tool_supported, Some(true));
     }
+
+    #[test]
+    fn test_macro_steps_substitute_arg() {
+        let fixture = Macro::default().name("release").steps(vec![
+            "Bump the version to {arg}".to_string(),
+            "!cargo test".to_string(),
+        ]);
+
+        let actual = (fixture.takes_arg(), fixture.expand("1.2.0"));
+
+        let expected = (
+            true,
+            vec![
+                "Bump the version to 1.2.0".to_string(),
+                "!cargo test".to_string(),
+            ],
+        );
+        assert_eq!(actual, expected);
+    }
 }
@@ -0,0 +1,168 @@
+use std::path::Path;
+
+pub use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+use crate::error::{Error, Result};
+
+/// Works out the encoding of a file's content: the one named by its byte order
+/// mark, otherwise UTF-8 when the content is valid UTF-8, otherwise latin-1
+/// (windows-1252), in which any sequence of bytes is valid.
+pub fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        encoding
+    } else if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    }
+}
+
+/// Decodes a file's content in the encoding detected for it, dropping the byte
+/// order mark
+pub fn decode(bytes: &[u8]) -> (String, &'static Encoding) {
+    let (content, encoding, _) = decode_checked(bytes);
+    (content, encoding)
+}
+
+/// Decodes like [`decode`], also telling whether malformed sequences, such as
+/// an unpaired UTF-16 surrogate, were replaced with U+FFFD
+pub(crate) fn decode_checked(bytes: &[u8]) -> (String, &'static Encoding, bool) {
+    let encoding = detect_encoding(bytes);
+    let (content, lossy) = encoding.decode_with_bom_removal(bytes);
+    (content.into_owned(), encoding, lossy)
+}
+
+/// Encodes content to be written in `encoding`. UTF-16 content starts with a
+/// byte order mark, so that its encoding is detected when it's read again.
+pub fn encode(content: &str, encoding: &'static Encoding) -> Result<Vec<u8>> {
+    // The encoding standard only decodes UTF-16, so encoding_rs can't encode it
+    let utf16 = |to_bytes: fn(u16) -> [u8; 2]| -> Vec<u8> {
+        std::iter::once('\u{FEFF}' as u16)
+            .chain(content.encode_utf16())
+            .flat_map(to_bytes)
+            .collect()
+    };
+    if encoding == UTF_16LE {
+        return Ok(utf16(u16::to_le_bytes));
+    }
+    if encoding == UTF_16BE {
+        return Ok(utf16(u16::to_be_bytes));
+    }
+
+    let (bytes, _, unmappable) = encoding.encode(content);
+    if unmappable {
+        return Err(Error::Unencodable(encoding.name()));
+    }
+    Ok(bytes.into_owned())
+}
+
+impl crate::ForgeFS {
+    /// Reads a file in the encoding detected for it, returning the content
+    /// along with the encoding so that it can be written back the same way
+    pub async fn read_with_encoding<T: AsRef<Path>>(
+        path: T,
+    ) -> Result<(String, &'static Encoding)> {
+        Self::read(path).await.map(|bytes| decode(&bytes))
+    }
+
+    /// Writes content to a file in `encoding`
+    pub async fn write_with_encoding<T: AsRef<Path>>(
+        path: T,
+        content: &str,
+        encoding: &'static Encoding,
+    ) -> Result<()> {
+        Self::write(path, encode(content, encoding)?).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ForgeFS;
+
+    fn utf16le(content: &str) -> Vec<u8> {
+        [0xFF, 0xFE]
+            .into_iter()
+            .chain(content.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        let actual = [
+            detect_encoding(b"plain"),
+            detect_encoding("h\u{e9}llo".as_bytes()),
+            detect_encoding(b"h\xe9llo"),
+            detect_encoding(&utf16le("hi")),
+            detect_encoding(b"\xFE\xFF\x00h\x00i"),
+            detect_encoding(b""),
+        ];
+
+        let expected = [UTF_8, UTF_8, WINDOWS_1252, UTF_16LE, UTF_16BE, UTF_8];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_encode() {
+        let actual = [
+            encode("h\u{e9}llo", UTF_8).unwrap(),
+            encode("h\u{e9}llo", WINDOWS_1252).unwrap(),
+            encode("h\u{e9}", UTF_16LE).unwrap(),
+            encode("h\u{e9}", UTF_16BE).unwrap(),
+        ];
+
+        let expected = [
+            "h\u{e9}llo".as_bytes().to_vec(),
+            b"h\xe9llo".to_vec(),
+            b"\xFF\xFEh\x00\xe9\x00".to_vec(),
+            b"\xFE\xFF\x00h\x00\xe9".to_vec(),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_encode_unmappable() {
+        let actual = encode("snow \u{2603}", WINDOWS_1252).unwrap_err();
+
+        assert_eq!(
+            actual.to_string(),
+            "Content can't be represented in the windows-1252 encoding of the file"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_utf16_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, utf16le("caf\u{e9}\r\nline two\r\n")).unwrap();
+
+        let (content, encoding) = ForgeFS::read_with_encoding(&path).await.unwrap();
+        let edited = content.replace("two", "2");
+        ForgeFS::write_with_encoding(&path, &edited, encoding)
+            .await
+            .unwrap();
+
+        let actual = std::fs::read(&path).unwrap();
+        assert_eq!(content, "caf\u{e9}\r\nline two\r\n");
+        assert_eq!(encoding, UTF_16LE);
+        assert_eq!(actual, utf16le("caf\u{e9}\r\nline 2\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_latin1_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.txt");
+        std::fs::write(&path, b"na\xefve\n").unwrap();
+
+        let (content, encoding) = ForgeFS::read_with_encoding(&path).await.unwrap();
+        ForgeFS::write_with_encoding(&path, &content, encoding)
+            .await
+            .unwrap();
+
+        let actual = std::fs::read(&path).unwrap();
+        assert_eq!(content, "na\u{ef}ve\n");
+        assert_eq!(actual, b"na\xefve\n");
+    }
+}
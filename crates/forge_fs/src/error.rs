@@ -1,3 +1,5 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::string::FromUtf8Error;
 
 use thiserror::Error;
@@ -14,9 +16,85 @@ pub enum Error {
     #[error("Start position {start} is greater than end position {end}")]
     StartGreaterThanEnd { start: u64, end: u64 },
 
+    #[error("Content can't be represented in the {0} encoding of the file")]
+    Unencodable(&'static str),
+
     #[error("UTF-8 validation failed: {0}")]
     Utf8ValidationFailed(#[from] FromUtf8Error),
 
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Failed to {operation} {}", path.display())]
+    NotFound {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to {operation} {}", path.display())]
+    PermissionDenied {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to {operation} {}", path.display())]
+    IsADirectory {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to {operation} {}", path.display())]
+    Io {
+        operation: &'static str,
+        path: PathBuf,
+        kind: ErrorKind,
+        #[source]
+        source: std::io::Error,
+    },
 }
+
+impl Error {
+    /// Wraps an IO error raised while performing `operation` on `path`,
+    /// picking the variant from the kind of the error
+    pub(crate) fn io(operation: &'static str, path: &Path, source: std::io::Error) -> Self {
+        let path = path.to_path_buf();
+        match source.kind() {
+            ErrorKind::NotFound => Self::NotFound { operation, path, source },
+            ErrorKind::PermissionDenied => Self::PermissionDenied { operation, path, source },
+            ErrorKind::IsADirectory => Self::IsADirectory { operation, path, source },
+            kind => Self::Io { operation, path, kind, source },
+        }
+    }
+
+    /// Path the failed IO operation was performed on
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::NotFound { path, .. }
+            | Self::PermissionDenied { path, .. }
+            | Self::IsADirectory { path, .. }
+            | Self::Io { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Kind of the underlying IO error, if the error came from an IO operation
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            Self::NotFound { .. } => Some(ErrorKind::NotFound),
+            Self::PermissionDenied { .. } => Some(ErrorKind::PermissionDenied),
+            Self::IsADirectory { .. } => Some(ErrorKind::IsADirectory),
+            Self::Io { kind, .. } => Some(*kind),
+            Self::IoError(error) => Some(error.kind()),
+            _ => None,
+        }
+    }
+}
+
+/// Result type returned by ForgeFS operations
+pub type Result<T> = std::result::Result<T, Error>;
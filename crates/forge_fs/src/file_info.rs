@@ -1,3 +1,5 @@
+use crate::{Encoding, GitBlameSummary, UTF_8};
+
 /// Information about a file or file range read operation
 #[derive(Debug, Clone, PartialEq)]
 pub struct FileInfo {
@@ -9,12 +11,47 @@ pub struct FileInfo {
 
     /// Total number of characters in the file
     pub total_chars: u64,
+
+    /// Last commit that changed the file, when it is tracked by git
+    pub git_blame_summary: Option<GitBlameSummary>,
+
+    /// Encoding the file was decoded from
+    pub encoding: &'static Encoding,
+
+    /// Whether bytes that aren't valid in the encoding were replaced with
+    /// U+FFFD
+    pub lossy: bool,
 }
 
 impl FileInfo {
     /// Creates a new FileInfo with the specified parameters
     pub fn new(start_char: u64, end_char: u64, total_chars: u64) -> Self {
-        Self { start_char, end_char, total_chars }
+        Self {
+            start_char,
+            end_char,
+            total_chars,
+            git_blame_summary: None,
+            encoding: UTF_8,
+            lossy: false,
+        }
+    }
+
+    /// Attaches the git blame summary of the file
+    pub fn git_blame_summary(mut self, summary: Option<GitBlameSummary>) -> Self {
+        self.git_blame_summary = summary;
+        self
+    }
+
+    /// Records the encoding the file was decoded from
+    pub fn encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Records whether invalid bytes were replaced while decoding the file
+    pub fn lossy(mut self, lossy: bool) -> Self {
+        self.lossy = lossy;
+        self
     }
 
     /// Returns true if this represents a partial file read
@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{Error, Result};
+
+/// Portable metadata about a file or directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileMetadata {
+    /// Whether the path, or the target of the symlink, is a directory
+    pub is_dir: bool,
+
+    /// Size in bytes of the file, or of the symlink target
+    pub size: u64,
+
+    /// Last modification time, when the platform reports it
+    pub modified: Option<DateTime<Utc>>,
+
+    /// Creation time, when the platform and file system report it
+    pub created: Option<DateTime<Utc>>,
+
+    /// Target of the path when it is a symlink
+    pub symlink_target: Option<PathBuf>,
+
+    /// Unix permission bits, `None` on other platforms
+    pub permissions: Option<u32>,
+}
+
+impl crate::ForgeFS {
+    /// Reads the metadata of `path` without following it when it is a
+    /// symlink, so the link itself is reported along with its target.
+    /// Dangling symlinks report the metadata of the link.
+    pub async fn file_metadata<T: AsRef<Path>>(path: T) -> Result<FileMetadata> {
+        let path = path.as_ref();
+        let link = tokio::fs::symlink_metadata(path)
+            .await
+            .map_err(|e| Error::io("get metadata for", path, e))?;
+
+        let symlink_target = if link.file_type().is_symlink() {
+            Some(
+                tokio::fs::read_link(path)
+                    .await
+                    .map_err(|e| Error::io("read symlink", path, e))?,
+            )
+        } else {
+            None
+        };
+
+        let meta = match symlink_target {
+            Some(_) => tokio::fs::metadata(path).await.unwrap_or(link),
+            None => link,
+        };
+
+        Ok(FileMetadata {
+            is_dir: meta.is_dir(),
+            size: meta.len(),
+            modified: meta.modified().ok().map(DateTime::from),
+            created: meta.created().ok().map(DateTime::from),
+            symlink_target,
+            permissions: permissions(&meta),
+        })
+    }
+}
+
+#[cfg(unix)]
+fn permissions(meta: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(meta.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn permissions(_meta: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use crate::ForgeFS;
+
+    #[tokio::test]
+    async fn test_file_metadata_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        tokio::fs::write(&path, "hello").await.unwrap();
+
+        let actual = ForgeFS::file_metadata(&path).await.unwrap();
+
+        assert_eq!((actual.is_dir, actual.size), (false, 5));
+        assert_eq!(actual.symlink_target, None);
+        assert!(actual.modified.is_some());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_file_metadata_symlink() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        tokio::fs::create_dir(&target).await.unwrap();
+        tokio::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o750))
+            .await
+            .unwrap();
+        let link = temp_dir.path().join("link");
+        tokio::fs::symlink(&target, &link).await.unwrap();
+
+        let actual = ForgeFS::file_metadata(&link).await.unwrap();
+
+        assert_eq!(
+            (actual.is_dir, actual.symlink_target, actual.permissions),
+            (true, Some(target), Some(0o750))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_metadata_missing() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let actual = ForgeFS::file_metadata(temp_dir.path().join("missing")).await;
+
+        assert!(matches!(actual, Err(crate::Error::NotFound { .. })));
+    }
+}
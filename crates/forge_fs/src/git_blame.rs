@@ -0,0 +1,172 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset};
+use git2::{ErrorCode, Repository};
+
+/// Version control details about the last commit that changed a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitBlameSummary {
+    /// Full hash of the commit
+    pub last_commit_hash: String,
+
+    /// First line of the commit message
+    pub last_commit_message: String,
+
+    /// Name of the commit author
+    pub last_commit_author: String,
+
+    /// Commit date in RFC 3339 format, in the author's timezone
+    pub last_commit_date: String,
+}
+
+impl crate::ForgeFS {
+    /// Summarizes the git blame of a file, returning the most recent commit
+    /// among the ones that last touched each of its lines.
+    ///
+    /// Returns `None` when the file isn't inside a git repository or has not
+    /// been committed yet.
+    pub async fn git_blame_summary<T: AsRef<Path>>(path: T) -> Result<Option<GitBlameSummary>> {
+        let path = path.as_ref().to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            blame_summary(&path)
+                .with_context(|| format!("Failed to read git blame for {}", path.display()))
+        })
+        .await?
+    }
+}
+
+fn blame_summary(path: &Path) -> Result<Option<GitBlameSummary>> {
+    let path = path.canonicalize()?;
+
+    // Walks up from the file until a `.git` directory is found
+    let repo = match Repository::discover(&path) {
+        Ok(repo) => repo,
+        Err(error) if error.code() == ErrorCode::NotFound => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+    let Some(workdir) = repo.workdir() else {
+        return Ok(None);
+    };
+    let relative = path.strip_prefix(workdir.canonicalize()?)?;
+
+    // Untracked files and repositories without commits have nothing to blame
+    let head = match repo.head() {
+        Ok(head) => head.peel_to_tree()?,
+        Err(error) if error.code() == ErrorCode::UnbornBranch => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+    if head.get_path(relative).is_err() {
+        return Ok(None);
+    }
+
+    let blame = repo.blame_file(relative, None)?;
+    let Some(hunk) = blame
+        .iter()
+        .max_by_key(|hunk| hunk.final_signature().when().seconds())
+    else {
+        return Ok(None);
+    };
+
+    let commit = repo.find_commit(hunk.final_commit_id())?;
+    let time = commit.time();
+    let date = FixedOffset::east_opt(time.offset_minutes() * 60)
+        .zip(DateTime::from_timestamp(time.seconds(), 0))
+        .map(|(offset, date)| date.with_timezone(&offset).to_rfc3339())
+        .unwrap_or_default();
+
+    let author = commit.author().name().unwrap_or_default().to_string();
+
+    Ok(Some(GitBlameSummary {
+        last_commit_hash: commit.id().to_string(),
+        last_commit_message: commit.summary().unwrap_or_default().to_string(),
+        last_commit_author: author,
+        last_commit_date: date,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use git2::{Signature, Time};
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::ForgeFS;
+
+    fn commit_file(repo: &Repository, name: &str, content: &str, message: &str, seconds: i64) {
+        let workdir = repo.workdir().unwrap();
+        std::fs::write(workdir.join(name), content).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+        let signature =
+            Signature::new("Jane Doe", "jane@example.com", &Time::new(seconds, 60)).unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        let parents = parent.iter().collect::<Vec<_>>();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_git_blame_summary_last_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "file.txt", "one\n", "Add file", 1_700_000_000);
+        commit_file(
+            &repo,
+            "file.txt",
+            "one\ntwo\n",
+            "Add line\n\nDetails",
+            1_700_003_600,
+        );
+        let head = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+        let actual = ForgeFS::git_blame_summary(temp_dir.path().join("file.txt"))
+            .await
+            .unwrap();
+
+        let expected = Some(GitBlameSummary {
+            last_commit_hash: head.to_string(),
+            last_commit_message: "Add line".to_string(),
+            last_commit_author: "Jane Doe".to_string(),
+            last_commit_date: "2023-11-15T00:13:20+01:00".to_string(),
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_git_blame_summary_untracked_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "file.txt", "one\n", "Add file", 1_700_000_000);
+        std::fs::write(temp_dir.path().join("untracked.txt"), "new").unwrap();
+
+        let actual = ForgeFS::git_blame_summary(temp_dir.path().join("untracked.txt"))
+            .await
+            .unwrap();
+
+        assert_eq!(actual, None);
+    }
+
+    #[tokio::test]
+    async fn test_git_blame_summary_outside_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("file.txt");
+        std::fs::write(&file, "content").unwrap();
+
+        let actual = ForgeFS::git_blame_summary(&file).await.unwrap();
+
+        assert_eq!(actual, None);
+    }
+}
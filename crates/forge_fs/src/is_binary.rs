@@ -1,39 +1,59 @@
-use anyhow::Result;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 
+/// Number of leading bytes examined to tell text from binary content
+const SAMPLE_SIZE: usize = 8192;
+
 impl crate::ForgeFS {
     /// Checks if a file is binary by examining its content.
     /// This version takes a path and opens the file itself.
     #[cfg(test)]
-    async fn is_binary_path<T: AsRef<std::path::Path>>(path: T) -> Result<(bool, String)> {
-        use anyhow::Context;
+    async fn is_binary_path<T: AsRef<std::path::Path>>(
+        path: T,
+    ) -> crate::error::Result<(bool, String)> {
+        use crate::Error;
 
         let path_ref = path.as_ref();
         let mut file = File::open(path_ref)
             .await
-            .with_context(|| format!("Failed to open file {}", path_ref.display()))?;
+            .map_err(|e| Error::io("open file", path_ref, e))?;
 
-        Self::is_binary(&mut file).await
+        Self::is_binary(&mut file)
+            .await
+            .map_err(|e| Error::io("read file", path_ref, e))
     }
 
     /// Checks if a file is binary by examining its content.
     /// This version takes an already opened file handle, allowing for reuse
     /// of the same file handle across multiple operations.
     /// This is a crate-private implementation detail.
-    pub(crate) async fn is_binary(file: &mut File) -> Result<(bool, String)> {
+    pub(crate) async fn is_binary(file: &mut File) -> std::io::Result<(bool, String)> {
         // Read sample data
-        let mut sample = vec![0; 8192];
+        let mut sample = vec![0; SAMPLE_SIZE];
         let bytes_read = file.read(&mut sample).await?;
         sample.truncate(bytes_read);
 
+        Ok(Self::classify(&sample))
+    }
+
+    /// Checks if content is text, or binary, from a sample of its first bytes.
+    /// Returns whether it's text along with a description of its type.
+    pub(crate) fn classify(sample: &[u8]) -> (bool, String) {
+        let sample = &sample[..sample.len().min(SAMPLE_SIZE)];
+
         // Handle empty files
-        if bytes_read == 0 {
-            return Ok((true, "Empty file".into()));
+        if sample.is_empty() {
+            return (true, "Empty file".into());
+        }
+
+        // Text with a byte order mark, such as UTF-16, is full of zero bytes but
+        // is decoded like any other text
+        if let Some((encoding, _)) = crate::Encoding::for_bom(sample) {
+            return (true, format!("Text file ({})", encoding.name()));
         }
 
         // Get file type info
-        let is_text = match infer::get(&sample) {
+        let is_text = match infer::get(sample) {
             Some(info) => matches!(
                 info.matcher_type(),
                 infer::MatcherType::Text | infer::MatcherType::Doc
@@ -41,11 +61,11 @@ impl crate::ForgeFS {
             None => true, // Assume text if type can't be determined
         };
 
-        let description = infer::get(&sample)
+        let description = infer::get(sample)
             .map(|info| info.mime_type().to_string())
             .unwrap_or_else(|| "Text file (no specific format detected)".into());
 
-        Ok((is_text, description))
+        (is_text, description)
     }
 }
 
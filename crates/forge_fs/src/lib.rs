@@ -3,22 +3,31 @@
 //! A file system abstraction layer that standardizes error handling for file
 //! operations.
 //!
-//! ForgeFS wraps tokio's filesystem operations with consistent error context
-//! using anyhow::Context. Each method provides standardized error messages in
-//! the format "Failed to [operation] [path]", ensuring uniform error reporting
-//! throughout the application while preserving the original error cause.
+//! ForgeFS wraps tokio's filesystem operations with structured errors. IO
+//! failures are reported as [`Error`] variants carrying the path and the kind
+//! of the failure, displayed in the format "Failed to [operation] [path]",
+//! ensuring uniform error reporting throughout the application while
+//! preserving the original error cause.
 
+mod encoding;
 mod error;
 mod file_info;
+mod file_metadata;
 mod file_size;
+mod git_blame;
 mod is_binary;
 mod meta;
 mod read;
 mod read_range;
 mod write;
 
-pub use crate::error::Error;
+pub use crate::encoding::{
+    decode, detect_encoding, encode, Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252,
+};
+pub use crate::error::{Error, Result};
 pub use crate::file_info::FileInfo;
+pub use crate::file_metadata::FileMetadata;
+pub use crate::git_blame::GitBlameSummary;
 
 /// ForgeFS provides a standardized interface for file system operations
 /// with consistent error handling.
@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use anyhow::{Context, Result};
+use crate::error::{Error, Result};
 
 impl crate::ForgeFS {
     pub fn exists<T: AsRef<Path>>(path: T) -> bool {
@@ -14,6 +14,6 @@ impl crate::ForgeFS {
     pub async fn read_dir<T: AsRef<Path>>(path: T) -> Result<tokio::fs::ReadDir> {
         tokio::fs::read_dir(path.as_ref())
             .await
-            .with_context(|| format!("Failed to read directory {}", path.as_ref().display()))
+            .map_err(|e| Error::io("read directory", path.as_ref(), e))
     }
 }
@@ -1,23 +1,107 @@
 use std::path::Path;
 
-use anyhow::{Context, Result};
+use crate::error::{Error, Result};
+
+/// Decodes `bytes` as UTF-8, replacing invalid sequences with U+FFFD. The
+/// flag tells whether any replacement occurred.
+pub(crate) fn decode_lossy(bytes: Vec<u8>) -> (String, bool) {
+    match String::from_utf8(bytes) {
+        Ok(content) => (content, false),
+        Err(error) => (String::from_utf8_lossy(error.as_bytes()).into_owned(), true),
+    }
+}
 
 impl crate::ForgeFS {
     pub async fn read_utf8<T: AsRef<Path>>(path: T) -> Result<String> {
-        Self::read(path)
+        Self::read_utf8_lossy(path)
             .await
-            .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+            .map(|(content, _)| content)
+    }
+
+    /// Reads a file as UTF-8, replacing invalid sequences instead of failing.
+    /// Returns the content along with whether any bytes were replaced.
+    pub async fn read_utf8_lossy<T: AsRef<Path>>(path: T) -> Result<(String, bool)> {
+        Self::read(path).await.map(decode_lossy)
     }
 
     pub async fn read<T: AsRef<Path>>(path: T) -> Result<Vec<u8>> {
         tokio::fs::read(path.as_ref())
             .await
-            .with_context(|| format!("Failed to read file {}", path.as_ref().display()))
+            .map_err(|e| Error::io("read file", path.as_ref(), e))
     }
 
     pub async fn read_to_string<T: AsRef<Path>>(path: T) -> Result<String> {
         tokio::fs::read_to_string(path.as_ref())
             .await
-            .with_context(|| format!("Failed to read file as string {}", path.as_ref().display()))
+            .map_err(|e| Error::io("read file as string", path.as_ref(), e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::ErrorKind;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Error;
+
+    #[tokio::test]
+    async fn test_read_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.txt");
+
+        let actual = crate::ForgeFS::read(&path).await.unwrap_err();
+
+        assert!(matches!(actual, Error::NotFound { .. }));
+        assert_eq!(actual.kind(), Some(ErrorKind::NotFound));
+        assert_eq!(actual.path(), Some(path.as_path()));
+        assert_eq!(
+            actual.to_string(),
+            format!("Failed to read file {}", path.display())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_utf8_lossy_replaces_invalid_bytes() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"before \x80 after").unwrap();
+
+        let actual = crate::ForgeFS::read_utf8_lossy(file.path()).await.unwrap();
+
+        let expected = ("before \u{FFFD} after".to_string(), true);
+        assert_eq!(actual, expected);
+        assert!(crate::ForgeFS::read_to_string(file.path()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_utf8_lossy_valid_content() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "héllo").unwrap();
+
+        let actual = crate::ForgeFS::read_utf8_lossy(file.path()).await.unwrap();
+
+        let expected = ("héllo".to_string(), false);
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_read_without_permission() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Privileged users can read the file regardless of its permissions
+        let Err(actual) = crate::ForgeFS::read_to_string(file.path()).await else {
+            return;
+        };
+
+        assert!(matches!(actual, Error::PermissionDenied { .. }));
+        assert_eq!(actual.kind(), Some(ErrorKind::PermissionDenied));
+        assert_eq!(
+            actual.to_string(),
+            format!("Failed to read file as string {}", file.path().display())
+        );
     }
 }
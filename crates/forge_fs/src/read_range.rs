@@ -1,10 +1,10 @@
 use std::cmp;
 use std::path::Path;
 
-use anyhow::{Context, Result};
-
-use crate::error::Error;
+use crate::encoding::decode_checked;
+use crate::error::{Error, Result};
 use crate::file_info::FileInfo;
+use crate::Encoding;
 
 impl crate::ForgeFS {
     /// Reads a specific range of characters from a file.
@@ -23,25 +23,60 @@ impl crate::ForgeFS {
         // Open the file for binary check
         let mut file = tokio::fs::File::open(path_ref)
             .await
-            .with_context(|| format!("Failed to open file {}", path_ref.display()))?;
+            .map_err(|e| Error::io("open file", path_ref, e))?;
 
         // Check if the file is binary
-        let (is_text, file_type) = Self::is_binary(&mut file).await?;
+        let (is_text, file_type) = Self::is_binary(&mut file)
+            .await
+            .map_err(|e| Error::io("read file", path_ref, e))?;
         if !is_text {
-            return Err(Error::BinaryFileNotSupported(file_type).into());
+            return Err(Error::BinaryFileNotSupported(file_type));
         }
 
-        // Read the file content
-        let content = tokio::fs::read_to_string(path_ref)
+        // Read the file content in the encoding detected for it, the same way it
+        // is read before being edited. A few invalid bytes shouldn't make an
+        // otherwise readable text file unreadable.
+        let (content, encoding, lossy) = tokio::fs::read(path_ref)
             .await
-            .with_context(|| format!("Failed to read file content from {}", path_ref.display()))?;
+            .map(|bytes| decode_checked(&bytes))
+            .map_err(|e| Error::io("read file content from", path_ref, e))?;
+
+        Self::char_range(content, encoding, lossy, start_char, end_char)
+    }
 
+    /// Reads a specific range of characters from content that was already
+    /// loaded, such as a downloaded file, with the same checks and results as
+    /// [`Self::read_range_utf8`].
+    pub fn range_utf8(
+        bytes: Vec<u8>,
+        start_char: u64,
+        end_char: u64,
+    ) -> Result<(String, FileInfo)> {
+        let (is_text, file_type) = Self::classify(&bytes);
+        if !is_text {
+            return Err(Error::BinaryFileNotSupported(file_type));
+        }
+
+        let (content, encoding, lossy) = decode_checked(&bytes);
+        Self::char_range(content, encoding, lossy, start_char, end_char)
+    }
+
+    // Extracts the requested character range from decoded content
+    fn char_range(
+        content: String,
+        encoding: &'static Encoding,
+        lossy: bool,
+        start_char: u64,
+        end_char: u64,
+    ) -> Result<(String, FileInfo)> {
         let total_chars = content.chars().count() as u64;
 
         // Validate and normalize the character range
         let (start_pos, end_pos) =
             Self::validate_char_range_bounds(total_chars, start_char, end_char)?;
-        let info = FileInfo::new(start_pos, end_pos, total_chars);
+        let info = FileInfo::new(start_pos, end_pos, total_chars)
+            .encoding(encoding)
+            .lossy(lossy);
 
         // Return empty result for empty ranges
         if start_pos == end_pos {
@@ -71,7 +106,7 @@ impl crate::ForgeFS {
     ) -> Result<(u64, u64)> {
         // Check if start is beyond file size
         if start_pos > total_chars {
-            return Err(Error::StartBeyondFileSize { start: start_pos, total: total_chars }.into());
+            return Err(Error::StartBeyondFileSize { start: start_pos, total: total_chars });
         }
 
         // Cap end position at file size
@@ -79,7 +114,7 @@ impl crate::ForgeFS {
 
         // Check if start is greater than end
         if start_pos > end_pos {
-            return Err(Error::StartGreaterThanEnd { start: start_pos, end: end_pos }.into());
+            return Err(Error::StartGreaterThanEnd { start: start_pos, end: end_pos });
         }
 
         Ok((start_pos, end_pos))
@@ -164,6 +199,48 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_read_range_utf8_latin1() -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        fs::write(file.path(), b"na\xefve \x80\nnext line").await?;
+
+        let (result, info) = crate::ForgeFS::read_range_utf8(file.path(), 0, 100).await?;
+
+        assert_eq!(result, "na\u{ef}ve \u{20ac}\nnext line");
+        assert_eq!(info.encoding, crate::WINDOWS_1252);
+        assert!(!info.lossy);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_range_utf8_utf16() -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        let content: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("caf\u{e9}\r\n".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        fs::write(file.path(), content).await?;
+
+        let (result, info) = crate::ForgeFS::read_range_utf8(file.path(), 0, 100).await?;
+
+        assert_eq!(result, "caf\u{e9}\r\n");
+        assert_eq!(info.encoding, crate::UTF_16LE);
+        assert!(!info.lossy);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_range_utf8_invalid_bytes() -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        fs::write(file.path(), b"\xFF\xFEh\x00\x00\xD8i\x00").await?;
+
+        let (result, info) = crate::ForgeFS::read_range_utf8(file.path(), 0, 100).await?;
+
+        assert_eq!(result, "h\u{FFFD}i");
+        assert!(info.lossy);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_utf8_boundary_handling() -> Result<()> {
         let content = "Hello 世界! こんにちは! Привет!";
@@ -182,4 +259,15 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_range_utf8_from_bytes() -> Result<()> {
+        let (result, info) = crate::ForgeFS::range_utf8(b"0123456789".to_vec(), 2, 5)?;
+        assert_eq!(result, "234");
+        assert_eq!(info.total_chars, 10);
+
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        assert!(crate::ForgeFS::range_utf8(png, 0, 10).is_err());
+        Ok(())
+    }
 }
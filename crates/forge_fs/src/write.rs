@@ -1,23 +1,275 @@
+use std::io::Write;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use anyhow::{Context, Result};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::{Error, Result};
+
+/// Bytes written to the temporary file of an atomic write between checks for
+/// cancellation
+const ATOMIC_WRITE_CHUNK: usize = 64 * 1024;
+
+/// Flags the atomic write it belongs to as cancelled when dropped before the
+/// write completes
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it over
+/// `path`, unless `cancelled` is raised first. The temporary file is removed
+/// when dropped, so it never outlives a failed or cancelled write.
+fn write_atomic_blocking(
+    path: &Path,
+    contents: &[u8],
+    cancelled: &AtomicBool,
+) -> std::io::Result<()> {
+    let cancelled_error =
+        || std::io::Error::new(std::io::ErrorKind::Interrupted, "write cancelled");
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let existing = std::fs::metadata(path).ok();
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".forge-").suffix(".tmp");
+    // Temporary files are private by default; a new file gets the mode any
+    // other created file would, which the umask then narrows
+    #[cfg(unix)]
+    if existing.is_none() {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(std::fs::Permissions::from_mode(0o666));
+    }
+    let mut file = builder.tempfile_in(dir)?;
+
+    for chunk in contents.chunks(ATOMIC_WRITE_CHUNK) {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(cancelled_error());
+        }
+        file.write_all(chunk)?;
+    }
+    file.as_file().sync_all()?;
+    if let Some(metadata) = existing {
+        file.as_file().set_permissions(metadata.permissions())?;
+    }
+
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(cancelled_error());
+    }
+    file.persist(path).map_err(|error| error.error)?;
+    Ok(())
+}
 
 impl crate::ForgeFS {
     pub async fn create_dir_all<T: AsRef<Path>>(path: T) -> Result<()> {
         tokio::fs::create_dir_all(path.as_ref())
             .await
-            .with_context(|| format!("Failed to create dir {}", path.as_ref().display()))
+            .map_err(|e| Error::io("create dir", path.as_ref(), e))
     }
 
     pub async fn write<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, contents: U) -> Result<()> {
         tokio::fs::write(path.as_ref(), contents)
             .await
-            .with_context(|| format!("Failed to write file {}", path.as_ref().display()))
+            .map_err(|e| Error::io("write file", path.as_ref(), e))
+    }
+
+    /// Replaces the content of `path` without ever leaving it partly written:
+    /// the content goes to a temporary file in the same directory, which is
+    /// renamed over `path` once complete. Dropping the future before then, for
+    /// eg: when the tool call writing the file is cancelled, leaves `path` as
+    /// it was and removes the temporary file. The permissions of the file are
+    /// kept, and symlinks are written through rather than replaced.
+    pub async fn write_atomic<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, contents: U) -> Result<()> {
+        let path = path.as_ref();
+        // Renaming over a symlink would replace the link instead of its target
+        let target = tokio::fs::canonicalize(path)
+            .await
+            .unwrap_or_else(|_| path.to_path_buf());
+        let contents = contents.as_ref().to_vec();
+
+        // The blocking task can't be aborted, so it is told to stop instead
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _guard = CancelOnDrop(cancelled.clone());
+        tokio::task::spawn_blocking(move || write_atomic_blocking(&target, &contents, &cancelled))
+            .await
+            .map_err(std::io::Error::other)
+            .and_then(|result| result)
+            .map_err(|e| Error::io("write file", path, e))
+    }
+
+    /// Appends to the end of a file, creating it if it doesn't exist
+    pub async fn append<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, contents: U) -> Result<()> {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())
+            .await
+            .map_err(|e| Error::io("open file", path.as_ref(), e))?;
+        file.write_all(contents.as_ref())
+            .await
+            .map_err(|e| Error::io("append file", path.as_ref(), e))?;
+        // Tokio hands the write to a background task, which may not be done yet
+        file.flush()
+            .await
+            .map_err(|e| Error::io("append file", path.as_ref(), e))
     }
 
     pub async fn remove_file<T: AsRef<Path>>(path: T) -> Result<()> {
         tokio::fs::remove_file(path.as_ref())
             .await
-            .with_context(|| format!("Failed to remove file {}", path.as_ref().display()))
+            .map_err(|e| Error::io("remove file", path.as_ref(), e))
+    }
+
+    pub async fn rename<T: AsRef<Path>, U: AsRef<Path>>(from: T, to: U) -> Result<()> {
+        tokio::fs::rename(from.as_ref(), to.as_ref())
+            .await
+            .map_err(|e| Error::io("rename", from.as_ref(), e))
+    }
+
+    /// Copies the content and permissions of a file, returning the number of
+    /// bytes copied
+    pub async fn copy<T: AsRef<Path>, U: AsRef<Path>>(from: T, to: U) -> Result<u64> {
+        tokio::fs::copy(from.as_ref(), to.as_ref())
+            .await
+            .map_err(|e| Error::io("copy file", from.as_ref(), e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use crate::ForgeFS;
+
+    /// Names of the entries of `dir`
+    fn entries(dir: &TempDir) -> Vec<String> {
+        let mut entries = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries
+    }
+
+    #[tokio::test]
+    async fn test_write_atomic_replaces_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        tokio::fs::write(&path, "original").await.unwrap();
+
+        ForgeFS::write_atomic(&path, "updated").await.unwrap();
+
+        let actual = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(actual, "updated");
+        assert_eq!(entries(&temp_dir), vec!["file.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_write_atomic_cancelled_keeps_original() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        tokio::fs::write(&path, "original").await.unwrap();
+        let contents = vec![b'x'; 64 * 1024 * 1024];
+
+        // Dropped after its first poll, while the content is being written
+        let write = ForgeFS::write_atomic(&path, contents);
+        let _ = tokio::time::timeout(std::time::Duration::ZERO, write).await;
+
+        // The blocking task notices the cancellation and removes its temp file
+        let mut attempts = 0;
+        while entries(&temp_dir).len() > 1 && attempts < 500 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            attempts += 1;
+        }
+        let actual = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(actual, "original");
+        assert_eq!(entries(&temp_dir), vec!["file.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_atomic_keeps_permissions_and_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("script.sh");
+        let link = temp_dir.path().join("link.sh");
+        tokio::fs::write(&target, "echo old").await.unwrap();
+        tokio::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o750))
+            .await
+            .unwrap();
+        tokio::fs::symlink(&target, &link).await.unwrap();
+
+        ForgeFS::write_atomic(&link, "echo new").await.unwrap();
+
+        let mode = tokio::fs::metadata(&target)
+            .await
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o750);
+        assert_eq!(
+            tokio::fs::read_to_string(&target).await.unwrap(),
+            "echo new"
+        );
+        assert!(tokio::fs::symlink_metadata(&link)
+            .await
+            .unwrap()
+            .file_type()
+            .is_symlink());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_atomic_new_file_follows_umask() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("new.txt");
+        let reference = temp_dir.path().join("reference.txt");
+        std::fs::write(&reference, "content").unwrap();
+
+        ForgeFS::write_atomic(&path, "content").await.unwrap();
+
+        let mode = |path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&path), mode(&reference));
+    }
+
+    #[tokio::test]
+    async fn test_append_creates_and_accumulates() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("log.txt");
+
+        ForgeFS::append(&path, "first\n").await.unwrap();
+        ForgeFS::append(&path, "second\n").await.unwrap();
+
+        let actual = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(actual, "first\nsecond\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_copy_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("script.sh");
+        let to = temp_dir.path().join("copy.sh");
+        tokio::fs::write(&from, "echo hi").await.unwrap();
+        tokio::fs::set_permissions(&from, std::fs::Permissions::from_mode(0o750))
+            .await
+            .unwrap();
+
+        let copied = ForgeFS::copy(&from, &to).await.unwrap();
+
+        let mode = tokio::fs::metadata(&to).await.unwrap().permissions().mode();
+        assert_eq!((copied, mode & 0o777), (7, 0o750));
+        assert_eq!(tokio::fs::read_to_string(&from).await.unwrap(), "echo hi");
     }
 }
@@ -1,11 +1,15 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
+use std::sync::OnceLock;
 
-use forge_domain::{Environment, Provider, RetryConfig};
+use anyhow::Context;
+use forge_domain::{Config, ConfigLayer, Environment, Provider};
+use forge_walker::DEFAULT_IGNORE_PATTERNS;
+use reqwest::Url;
 
 pub struct ForgeEnvironmentService {
     restricted: bool,
-    is_env_loaded: RwLock<bool>,
+    config: OnceLock<Config>,
 }
 
 type ProviderSearch = (&'static str, Box<dyn FnOnce(&str) -> Provider>);
@@ -17,14 +21,14 @@ impl ForgeEnvironmentService {
     /// * `unrestricted` - If true, use unrestricted shell mode (sh/bash) If
     ///   false, use restricted shell mode (rbash)
     pub fn new(restricted: bool) -> Self {
-        Self { restricted, is_env_loaded: Default::default() }
+        Self { restricted, config: Default::default() }
     }
 
     /// Get path to appropriate shell based on platform and mode
-    fn get_shell_path(&self) -> String {
+    fn get_shell_path(restricted: bool) -> String {
         if cfg!(target_os = "windows") {
             std::env::var("COMSPEC").unwrap_or("cmd.exe".to_string())
-        } else if self.restricted {
+        } else if restricted {
             // Default to rbash in restricted mode
             "/bin/rbash".to_string()
         } else {
@@ -33,16 +37,26 @@ impl ForgeEnvironmentService {
         }
     }
 
-    /// Resolves the provider key and provider from environment variables
+    /// Resolves the providers from environment variables, in the order they
+    /// are looked up. The first one serves requests unless a fallback strategy
+    /// is configured.
     ///
-    /// Returns a tuple of (provider_key, provider)
     /// Panics if no API key is found in the environment
-    fn resolve_provider(&self) -> Provider {
-        let keys: [ProviderSearch; 4] = [
+    fn resolve_providers(&self) -> Vec<Provider> {
+        // Ollama is unauthenticated by default and Azure OpenAI has a key of its
+        // own, so both are detected from the URL alone
+        let url_provider = std::env::var("OPENAI_URL")
+            .ok()
+            .and_then(|url| Url::parse(&url).ok())
+            .map(|url| Provider::from_url(url, std::env::var("AZURE_OPENAI_API_KEY").ok()))
+            .filter(|provider| provider.is_ollama() || provider.is_azure());
+
+        let keys: [ProviderSearch; 5] = [
             ("FORGE_KEY", Box::new(Provider::antinomy)),
             ("OPENROUTER_API_KEY", Box::new(Provider::open_router)),
             ("OPENAI_API_KEY", Box::new(Provider::openai)),
             ("ANTHROPIC_API_KEY", Box::new(Provider::anthropic)),
+            ("GROQ_API_KEY", Box::new(Provider::groq)),
         ];
 
         let env_variables = keys
@@ -51,86 +65,137 @@ impl ForgeEnvironmentService {
             .collect::<Vec<_>>()
             .join(", ");
 
-        keys.into_iter()
-            .find_map(|(key, fun)| {
-                std::env::var(key).ok().map(|key| {
-                    let mut provider = fun(&key);
+        let keyed = keys.into_iter().filter_map(|(key, fun)| {
+            std::env::var(key).ok().map(|key| {
+                let mut provider = fun(&key);
 
+                // The URL already selected a provider of its own
+                if url_provider.is_none() {
                     if let Ok(url) = std::env::var("OPENAI_URL") {
                         provider.open_ai_url(url);
                     }
+                }
 
-                    // Check for Anthropic URL override
-                    if let Ok(url) = std::env::var("ANTHROPIC_URL") {
-                        provider.anthropic_url(url);
+                // An OpenAI URL pointing at Groq selects the Groq provider
+                if let Provider::OpenAI { url, key } = &provider {
+                    if url.host_str() == Some(Provider::GROQ_HOST) {
+                        provider = Provider::Groq { url: url.clone(), key: key.clone() };
                     }
+                }
 
-                    provider
-                })
+                // Check for Anthropic URL override
+                if let Ok(url) = std::env::var("ANTHROPIC_URL") {
+                    provider.anthropic_url(url);
+                }
+
+                provider
             })
-            .unwrap_or_else(|| panic!("No API key found. Please set one of: {env_variables}"))
+        });
+
+        let providers = url_provider
+            .clone()
+            .into_iter()
+            .chain(keyed)
+            .collect::<Vec<_>>();
+        if providers.is_empty() {
+            panic!("No API key found. Please set one of: {env_variables}");
+        }
+        providers
     }
 
-    /// Resolves retry configuration from environment variables or returns
-    /// defaults
-    fn resolve_retry_config(&self) -> RetryConfig {
-        // Parse initial backoff in milliseconds
-        let initial_backoff_ms = std::env::var("FORGE_RETRY_INITIAL_BACKOFF_MS")
-            .ok()
-            .and_then(|val| val.parse::<u64>().ok())
-            .unwrap_or(200); // Default value
-
-        // Parse backoff factor
-        let backoff_factor = std::env::var("FORGE_RETRY_BACKOFF_FACTOR")
-            .ok()
-            .and_then(|val| val.parse::<u64>().ok())
-            .unwrap_or(2); // Default value
+    /// Loads the configuration from the config file, the `FORGE_*`
+    /// environment variables and the command line, in increasing order of
+    /// priority
+    fn load_config(&self, path: &Path) -> anyhow::Result<Config> {
+        let file = match std::fs::read_to_string(path) {
+            Ok(content) => Some(Self::upgrade_config(path, content)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+            Err(error) => {
+                return Err(error).with_context(|| format!("Failed to read {}", path.display()))
+            }
+        };
 
-        // Parse maximum retry attempts
-        let max_retry_attempts = std::env::var("FORGE_RETRY_MAX_ATTEMPTS")
-            .ok()
-            .and_then(|val| val.parse::<usize>().ok())
-            .unwrap_or(8); // Default value
+        let env = std::env::vars().collect::<HashMap<_, _>>();
+        let mut cli = ConfigLayer::default();
+        if self.restricted {
+            cli = cli.restricted(true);
+        }
 
-        // Parse retry status codes
-        let retry_status_codes = std::env::var("FORGE_RETRY_STATUS_CODES")
-            .ok()
-            .map(|val| {
-                val.split(',')
-                    .filter_map(|code| code.trim().parse::<u16>().ok())
-                    .collect::<Vec<u16>>()
-            })
-            .unwrap_or_else(|| vec![429, 500, 502, 503, 504]); // Default values
+        Config::load(file.as_deref(), &env, cli)
+            .with_context(|| format!("Failed to load the configuration from {}", path.display()))
+    }
 
-        RetryConfig {
-            initial_backoff_ms,
-            backoff_factor,
-            max_retry_attempts,
-            retry_status_codes,
+    /// Upgrades a config file written for an older schema version, writing it
+    /// back so that the upgrade happens once. The upgraded contents are used
+    /// even if the file can't be written.
+    fn upgrade_config(path: &Path, content: String) -> anyhow::Result<String> {
+        let Some(upgraded) = ConfigLayer::upgrade_yaml(&content)
+            .with_context(|| format!("Failed to load the configuration from {}", path.display()))?
+        else {
+            return Ok(content);
+        };
+
+        if let Err(error) = std::fs::write(path, &upgraded) {
+            tracing::warn!(%error, path = %path.display(), "Failed to write the upgraded config file");
         }
+
+        Ok(upgraded)
+    }
+
+    /// Adds the extra ignore patterns of the configuration to the built-in
+    /// ones
+    fn ignore_patterns(config: &Config) -> Vec<String> {
+        DEFAULT_IGNORE_PATTERNS
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .chain(config.ignore_patterns.iter().cloned())
+            .collect()
     }
 
     fn get(&self) -> Environment {
         let cwd = std::env::current_dir().unwrap_or(PathBuf::from("."));
-        if !self.is_env_loaded.read().map(|v| *v).unwrap_or_default() {
-            *self.is_env_loaded.write().unwrap() = true;
+        let base_path = dirs::home_dir()
+            .map(|a| a.join("forge"))
+            .unwrap_or(PathBuf::from(".").join("forge"));
+
+        // The `.env` files are loaded first so that they can configure forge too
+        let config = self.config.get_or_init(|| {
             Self::dot_env(&cwd);
-        }
+            self.load_config(&base_path.join("config.yaml"))
+                .unwrap_or_else(|error| panic!("{error:#}"))
+        });
 
-        let provider = self.resolve_provider();
-        let retry_config = self.resolve_retry_config();
+        let mut providers = self.resolve_providers().into_iter();
+        let provider = providers.next().expect("at least one provider is resolved");
+        let fallback_providers = match config.provider_fallback {
+            Some(_) => providers.collect(),
+            None => vec![],
+        };
 
         Environment {
             os: std::env::consts::OS.to_string(),
             pid: std::process::id(),
             cwd,
-            shell: self.get_shell_path(),
-            base_path: dirs::home_dir()
-                .map(|a| a.join("forge"))
-                .unwrap_or(PathBuf::from(".").join("forge")),
+            shell: Self::get_shell_path(config.restricted),
+            base_path,
             home: dirs::home_dir(),
             provider,
-            retry_config,
+            retry_config: config.retry.clone(),
+            tool_output_limit: config.tool_output_limit.clone(),
+            ignore_patterns: Self::ignore_patterns(config),
+            shell_timeout_secs: config.shell_timeout_secs,
+            syntax_check: config.syntax_check,
+            restricted: config.restricted,
+            tool_timeout_secs: config.tool_timeout_secs,
+            verbose: config.verbose,
+            model: config.model.clone(),
+            temperature: config.temperature,
+            top_p: config.top_p,
+            top_k: config.top_k,
+            snapshot_compression: config.snapshot_compression,
+            fetch_max_bytes: config.fetch_max_bytes,
+            provider_fallback: config.provider_fallback,
+            fallback_providers,
         }
     }
 
@@ -243,6 +308,46 @@ mod tests {
         assert_eq!(env::var("C1").unwrap(), "3");
     }
 
+    #[test]
+    fn test_load_config_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "shell_timeout_secs: 42\nrestricted: false\n").unwrap();
+
+        let actual = ForgeEnvironmentService::new(true)
+            .load_config(&path)
+            .unwrap();
+
+        assert_eq!(actual.shell_timeout_secs, 42);
+        assert!(actual.restricted);
+    }
+
+    #[test]
+    fn test_load_config_upgrades_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "retry_status_codes: \"429, 503\"\n").unwrap();
+
+        let actual = ForgeEnvironmentService::new(false)
+            .load_config(&path)
+            .unwrap();
+
+        assert_eq!(actual.retry.retry_status_codes, vec![429, 503]);
+        let upgraded = fs::read_to_string(&path).unwrap();
+        assert!(upgraded.contains("version: 2"), "{upgraded}");
+    }
+
+    #[test]
+    fn test_load_config_without_file() {
+        let dir = tempdir().unwrap();
+
+        let actual = ForgeEnvironmentService::new(false)
+            .load_config(&dir.path().join("config.yaml"))
+            .unwrap();
+
+        assert!(!actual.restricted);
+    }
+
     #[test]
     fn test_custom_scenario_with_std_env_precedence() {
         let (_root, cwd) = setup_envs(vec![("a/b", "A2=1"), ("a", "A2=2")]);
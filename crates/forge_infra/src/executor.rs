@@ -1,11 +1,13 @@
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
-use forge_domain::{CommandOutput, Environment};
+use forge_domain::{CommandEnv, CommandOutput, Environment, OutputLine, OutputStream};
 use forge_services::CommandExecutorService;
 use tokio::io::AsyncReadExt;
-use tokio::process::Command;
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::Mutex;
 
 /// Service for executing shell commands
@@ -23,7 +25,12 @@ impl ForgeCommandExecutorService {
         Self { restricted, env, ready: Arc::new(Mutex::new(())) }
     }
 
-    fn prepare_command(&self, command_str: &str, working_dir: Option<&Path>) -> Command {
+    fn prepare_command(
+        &self,
+        command_str: &str,
+        working_dir: Option<&Path>,
+        env: &CommandEnv,
+    ) -> Command {
         // Create a basic command
         let is_windows = cfg!(target_os = "windows");
         let shell = if self.restricted && !is_windows {
@@ -50,6 +57,11 @@ impl ForgeCommandExecutorService {
         // Other common tools
         command.env("GREP_OPTIONS", "--color=always"); // GNU grep
 
+        for name in &env.removed {
+            command.env_remove(name);
+        }
+        command.envs(&env.vars);
+
         let parameter = if is_windows { "/C" } else { "-c" };
         command.arg(parameter);
 
@@ -81,23 +93,76 @@ impl ForgeCommandExecutorService {
         &self,
         command: String,
         working_dir: &Path,
+        env: &CommandEnv,
+        timeout: Option<Duration>,
+        output_lines: Option<UnboundedSender<OutputLine>>,
     ) -> anyhow::Result<CommandOutput> {
         let ready = self.ready.lock().await;
 
-        let mut prepared_command = self.prepare_command(&command, Some(working_dir));
+        let mut prepared_command = self.prepare_command(&command, Some(working_dir), env);
+        // A command that can time out leads a process group of its own, so that
+        // whatever it started can be stopped with it
+        #[cfg(unix)]
+        if timeout.is_some() {
+            prepared_command.process_group(0);
+        }
 
         // Spawn the command
         let mut child = prepared_command.spawn()?;
 
+        // The terminal goes to the command's process group while it runs, so that
+        // Ctrl-C and prompts for input reach it as they would without a group
+        #[cfg(unix)]
+        let foreground = timeout
+            .and(child.id())
+            .and_then(|pid| Foreground::hand_to(pid as libc::pid_t));
+
         let mut stdout_pipe = child.stdout.take();
         let mut stderr_pipe = child.stderr.take();
 
-        // Stream the output of the command to stdout and stderr concurrently
-        let (status, stdout_buffer, stderr_buffer) = tokio::try_join!(
-            child.wait(),
-            stream(&mut stdout_pipe, io::stdout()),
-            stream(&mut stderr_pipe, io::stderr())
-        )?;
+        // Output is collected as it arrives, so that a command which times out
+        // still reports what it printed
+        let mut stdout_buffer = Vec::new();
+        let mut stderr_buffer = Vec::new();
+
+        // Stream the output of the command to stdout and stderr concurrently, or
+        // line by line to `output_lines`
+        let stdout_lines = output_lines
+            .as_ref()
+            .map(|sender| (sender, OutputStream::Stdout));
+        let stderr_lines = output_lines
+            .as_ref()
+            .map(|sender| (sender, OutputStream::Stderr));
+        let run = async {
+            tokio::try_join!(
+                child.wait(),
+                stream(
+                    &mut stdout_pipe,
+                    io::stdout(),
+                    stdout_lines,
+                    &mut stdout_buffer
+                ),
+                stream(
+                    &mut stderr_pipe,
+                    io::stderr(),
+                    stderr_lines,
+                    &mut stderr_buffer
+                )
+            )
+        };
+        let status = match timeout {
+            Some(limit) => tokio::time::timeout(limit, run).await.ok(),
+            None => Some(run.await),
+        }
+        .transpose()?
+        .map(|(status, _, _)| status);
+
+        if status.is_none() {
+            tracing::warn!(command = %command, "Command timed out");
+            kill(&mut child).await;
+        }
+        #[cfg(unix)]
+        drop(foreground);
 
         // Drop happens after `try_join` due to <https://github.com/tokio-rs/tokio/issues/4309>
         drop(stdout_pipe);
@@ -107,32 +172,137 @@ impl ForgeCommandExecutorService {
         Ok(CommandOutput {
             stdout: String::from_utf8_lossy(&stdout_buffer).into_owned(),
             stderr: String::from_utf8_lossy(&stderr_buffer).into_owned(),
-            exit_code: status.code(),
+            exit_code: status.and_then(|status| status.code()),
             command,
+            timed_out: timeout.filter(|_| status.is_none()),
         })
     }
 }
 
-/// reads the output from A and writes it to W
+/// Makes a process group the foreground group of the terminal, handing the
+/// terminal back to the previous group when dropped
+#[cfg(unix)]
+struct Foreground {
+    previous: libc::pid_t,
+}
+
+#[cfg(unix)]
+impl Foreground {
+    /// Hands the terminal to `group`, unless there is no terminal or this
+    /// process isn't in its foreground group
+    fn hand_to(group: libc::pid_t) -> Option<Self> {
+        // SAFETY: these calls only query the terminal and the process group
+        let previous = unsafe {
+            if libc::isatty(libc::STDIN_FILENO) == 0 {
+                return None;
+            }
+            libc::tcgetpgrp(libc::STDIN_FILENO)
+        };
+        if previous != unsafe { libc::getpgrp() } || !set_foreground(group) {
+            return None;
+        }
+
+        // A command that read the terminal before getting it has been stopped
+        // SAFETY: killpg only sends a signal and has no memory safety requirements
+        unsafe {
+            libc::killpg(group, libc::SIGCONT);
+        }
+        Some(Self { previous })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Foreground {
+    fn drop(&mut self) {
+        if !set_foreground(self.previous) {
+            tracing::warn!("Failed to take back the terminal");
+        }
+    }
+}
+
+/// Sets the foreground process group of the terminal. Doing so from a
+/// background group stops the process with SIGTTOU, so the signal is blocked
+/// meanwhile.
+#[cfg(unix)]
+fn set_foreground(group: libc::pid_t) -> bool {
+    // SAFETY: the signal sets are initialized by sigemptyset and
+    // pthread_sigmask before they are read
+    unsafe {
+        let mut blocked = std::mem::zeroed::<libc::sigset_t>();
+        let mut previous = std::mem::zeroed::<libc::sigset_t>();
+        libc::sigemptyset(&mut blocked);
+        libc::sigaddset(&mut blocked, libc::SIGTTOU);
+        libc::pthread_sigmask(libc::SIG_BLOCK, &blocked, &mut previous);
+        let result = libc::tcsetpgrp(libc::STDIN_FILENO, group);
+        libc::pthread_sigmask(libc::SIG_SETMASK, &previous, std::ptr::null_mut());
+        result == 0
+    }
+}
+
+/// Stops a command along with every process it started
+async fn kill(child: &mut Child) {
+    // Commands that can time out lead their own process group, see
+    // `execute_command_internal`
+    #[cfg(unix)]
+    {
+        if let Some(pid) = child.id() {
+            // SAFETY: killpg only sends a signal and has no memory safety requirements
+            unsafe {
+                libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+    }
+    if let Err(error) = child.kill().await {
+        tracing::warn!(%error, "Failed to kill timed out command");
+    }
+}
+
+/// reads the output from A and appends it to `output`, writing it to W or,
+/// when `lines` is given, sending every completed line there instead
 async fn stream<A: AsyncReadExt + Unpin, W: Write>(
     io: &mut Option<A>,
     mut writer: W,
-) -> io::Result<Vec<u8>> {
-    let mut output = Vec::new();
+    lines: Option<(&UnboundedSender<OutputLine>, OutputStream)>,
+    output: &mut Vec<u8>,
+) -> io::Result<()> {
     if let Some(io) = io.as_mut() {
         let mut buff = [0; 1024];
+        // Start of the line that hasn't been sent yet
+        let mut line_start = 0;
         loop {
             let n = io.read(&mut buff).await?;
             if n == 0 {
                 break;
             }
-            writer.write_all(&buff[..n])?;
-            // note: flush is necessary else we get the cursor could not be found error.
-            writer.flush()?;
             output.extend_from_slice(&buff[..n]);
+            match lines {
+                Some((sender, stream)) => {
+                    while let Some(end) = output[line_start..].iter().position(|&b| b == b'\n') {
+                        let end = line_start + end;
+                        send_line(sender, stream, &output[line_start..end]);
+                        line_start = end + 1;
+                    }
+                }
+                None => {
+                    writer.write_all(&buff[..n])?;
+                    // note: flush is necessary else we get the cursor could not be found error.
+                    writer.flush()?;
+                }
+            }
+        }
+        // Output that doesn't end with a line break
+        if let Some((sender, stream)) = lines.filter(|_| line_start < output.len()) {
+            send_line(sender, stream, &output[line_start..]);
         }
     }
-    Ok(output)
+    Ok(())
+}
+
+fn send_line(sender: &UnboundedSender<OutputLine>, stream: OutputStream, line: &[u8]) {
+    let text = String::from_utf8_lossy(line);
+    // Nobody listening for the lines doesn't stop the command, whose output is
+    // still collected
+    let _ = sender.send(OutputLine::new(stream, text.trim_end_matches('\r')));
 }
 
 /// The implementation for CommandExecutorService
@@ -142,12 +312,16 @@ impl CommandExecutorService for ForgeCommandExecutorService {
         &self,
         command: String,
         working_dir: PathBuf,
+        env: CommandEnv,
+        timeout: Option<Duration>,
+        output_lines: Option<UnboundedSender<OutputLine>>,
     ) -> anyhow::Result<CommandOutput> {
-        self.execute_command_internal(command, &working_dir).await
+        self.execute_command_internal(command, &working_dir, &env, timeout, output_lines)
+            .await
     }
 
     async fn execute_command_raw(&self, command: &str) -> anyhow::Result<std::process::ExitStatus> {
-        let mut prepared_command = self.prepare_command(command, None);
+        let mut prepared_command = self.prepare_command(command, None, &CommandEnv::default());
 
         // overwrite the stdin, stdout and stderr to inherit
         prepared_command
@@ -161,7 +335,7 @@ impl CommandExecutorService for ForgeCommandExecutorService {
 
 #[cfg(test)]
 mod tests {
-    use forge_domain::Provider;
+    use forge_domain::{Compression, Provider};
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -181,6 +355,21 @@ mod tests {
             base_path: PathBuf::from("/base"),
             provider: Provider::open_router("test-key"),
             retry_config: Default::default(),
+            tool_output_limit: Default::default(),
+            ignore_patterns: vec![],
+            shell_timeout_secs: 300,
+            syntax_check: true,
+            restricted: false,
+            tool_timeout_secs: 300,
+            verbose: false,
+            model: None,
+            temperature: None,
+            top_p: None,
+            top_k: None,
+            snapshot_compression: Compression::None,
+            fetch_max_bytes: 512 * 1024,
+            provider_fallback: None,
+            fallback_providers: vec![],
         }
     }
 
@@ -191,7 +380,13 @@ mod tests {
         let dir = ".";
 
         let actual = fixture
-            .execute_command(cmd.to_string(), PathBuf::new().join(dir))
+            .execute_command(
+                cmd.to_string(),
+                PathBuf::new().join(dir),
+                CommandEnv::default(),
+                None,
+                None,
+            )
             .await
             .unwrap();
 
@@ -200,6 +395,7 @@ mod tests {
             stderr: "".to_string(),
             command: "echo \"hello world\"".into(),
             exit_code: Some(0),
+            timed_out: None,
         };
 
         if cfg!(target_os = "windows") {
@@ -210,4 +406,151 @@ mod tests {
         assert_eq!(actual.stderr, expected.stderr);
         assert_eq!(actual.success(), expected.success());
     }
+
+    #[tokio::test]
+    async fn test_command_within_timeout() {
+        let fixture = ForgeCommandExecutorService::new(false, test_env());
+
+        let actual = fixture
+            .execute_command(
+                "echo done".to_string(),
+                PathBuf::from("."),
+                CommandEnv::default(),
+                Some(Duration::from_secs(30)),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(actual.stdout.trim(), "done");
+        assert_eq!(actual.exit_code, Some(0));
+        assert_eq!(actual.timed_out, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_timeout_keeps_partial_output() {
+        let fixture = ForgeCommandExecutorService::new(false, test_env());
+        let timeout = Duration::from_millis(500);
+        let started = std::time::Instant::now();
+
+        let actual = fixture
+            .execute_command(
+                "echo started; sleep 600".to_string(),
+                PathBuf::from("."),
+                CommandEnv::default(),
+                Some(timeout),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(actual.stdout, "started\n");
+        assert_eq!(actual.exit_code, None);
+        assert_eq!(actual.timed_out, Some(timeout));
+        assert!(!actual.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_timeout_kills_spawned_processes() {
+        let fixture = ForgeCommandExecutorService::new(false, test_env());
+
+        let actual = fixture
+            .execute_command(
+                "sleep 600 & echo $!; wait".to_string(),
+                PathBuf::from("."),
+                CommandEnv::default(),
+                Some(Duration::from_millis(500)),
+                None,
+            )
+            .await
+            .unwrap();
+        let pid: libc::pid_t = actual.stdout.trim().parse().unwrap();
+
+        // The killed process is reaped by init once its parent is gone, which can
+        // take a moment
+        let mut alive = true;
+        for _ in 0..50 {
+            // SAFETY: signal 0 only checks whether the process exists
+            alive = unsafe { libc::kill(pid, 0) } == 0;
+            if !alive {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(
+            !alive,
+            "process {pid} started by the command is still running"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_streams_output_lines() {
+        let fixture = ForgeCommandExecutorService::new(false, test_env());
+        let (sender, mut lines) = tokio::sync::mpsc::unbounded_channel();
+        let command = "for i in 1 2 3 4 5 6 7 8 9 10; do echo line $i; sleep 0.05; done; \
+                       echo oops >&2; printf partial";
+
+        let run = tokio::spawn(async move {
+            fixture
+                .execute_command(
+                    command.to_string(),
+                    PathBuf::from("."),
+                    CommandEnv::default(),
+                    None,
+                    Some(sender),
+                )
+                .await
+        });
+
+        let first = lines.recv().await.unwrap();
+        assert!(!run.is_finished(), "output arrived after the command ended");
+        let mut actual = vec![first];
+        // The sender is dropped once the command is done, which ends the lines
+        while let Some(line) = lines.recv().await {
+            actual.push(line);
+        }
+        let output = run.await.unwrap().unwrap();
+
+        // The two streams are read concurrently, so only the order within each
+        // one is known
+        let (stdout, stderr): (Vec<_>, Vec<_>) = actual
+            .into_iter()
+            .partition(|line| line.stream == OutputStream::Stdout);
+        let mut expected: Vec<String> = (1..=10).map(|i| format!("line {i}")).collect();
+        expected.push("partial".to_string());
+        let stdout: Vec<String> = stdout.into_iter().map(|line| line.text).collect();
+        assert_eq!(stdout, expected);
+        assert_eq!(stderr, vec![OutputLine::new(OutputStream::Stderr, "oops")]);
+        let expected_stdout: String = (1..=10).map(|i| format!("line {i}\n")).collect();
+        assert_eq!(output.stdout, format!("{expected_stdout}partial"));
+        assert_eq!(output.stderr, "oops\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_env() {
+        let fixture = ForgeCommandExecutorService::new(false, test_env());
+        std::env::set_var("FORGE_EXECUTOR_TEST_SECRET", "hunter2");
+        let env = CommandEnv {
+            vars: [("RUST_LOG".to_string(), "debug".to_string())].into(),
+            removed: vec!["FORGE_EXECUTOR_TEST_SECRET".to_string()],
+        };
+
+        let actual = fixture
+            .execute_command(
+                "echo \"log=$RUST_LOG secret=$FORGE_EXECUTOR_TEST_SECRET\"".to_string(),
+                PathBuf::from("."),
+                env,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(actual.stdout, "log=debug secret=\n");
+    }
 }
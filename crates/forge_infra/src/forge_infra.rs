@@ -1,15 +1,18 @@
 use std::sync::Arc;
 
 use forge_domain::EnvironmentService;
-use forge_services::Infrastructure;
+use forge_services::{Infrastructure, WebClient};
 
 use crate::env::ForgeEnvironmentService;
 use crate::executor::ForgeCommandExecutorService;
+use crate::fs_copy::ForgeFileCopyService;
 use crate::fs_create_dirs::ForgeCreateDirsService;
 use crate::fs_meta::ForgeFileMetaService;
+use crate::fs_move::ForgeFileMoveService;
 use crate::fs_read::ForgeFileReadService;
 use crate::fs_remove::ForgeFileRemoveService;
 use crate::fs_snap::ForgeFileSnapshotService;
+use crate::fs_watch::ForgeFileWatchService;
 use crate::fs_write::ForgeFileWriteService;
 use crate::inquire::ForgeInquire;
 use crate::mcp_server::ForgeMcpServer;
@@ -22,6 +25,9 @@ pub struct ForgeInfra {
     file_snapshot_service: Arc<ForgeFileSnapshotService>,
     file_meta_service: Arc<ForgeFileMetaService>,
     file_remove_service: Arc<ForgeFileRemoveService<ForgeFileSnapshotService>>,
+    file_move_service: Arc<ForgeFileMoveService<ForgeFileSnapshotService>>,
+    file_copy_service: Arc<ForgeFileCopyService<ForgeFileSnapshotService>>,
+    file_watch_service: Arc<ForgeFileWatchService>,
     create_dirs_service: Arc<ForgeCreateDirsService>,
     command_executor_service: Arc<ForgeCommandExecutorService>,
     inquire_service: Arc<ForgeInquire>,
@@ -34,12 +40,18 @@ impl ForgeInfra {
         let env = environment_service.get_environment();
         let file_snapshot_service = Arc::new(ForgeFileSnapshotService::new(env.clone()));
         Self {
-            file_read_service: Arc::new(ForgeFileReadService::new()),
+            file_read_service: Arc::new(ForgeFileReadService::new(
+                WebClient::new(env.fetch_cache_path()),
+                env.fetch_max_bytes,
+            )),
             file_write_service: Arc::new(ForgeFileWriteService::new(file_snapshot_service.clone())),
             file_meta_service: Arc::new(ForgeFileMetaService),
             file_remove_service: Arc::new(ForgeFileRemoveService::new(
                 file_snapshot_service.clone(),
             )),
+            file_move_service: Arc::new(ForgeFileMoveService::new(file_snapshot_service.clone())),
+            file_copy_service: Arc::new(ForgeFileCopyService::new(file_snapshot_service.clone())),
+            file_watch_service: Arc::new(ForgeFileWatchService::default()),
             environment_service,
             file_snapshot_service,
             create_dirs_service: Arc::new(ForgeCreateDirsService),
@@ -60,6 +72,9 @@ impl Infrastructure for ForgeInfra {
     type FsMetaService = ForgeFileMetaService;
     type FsSnapshotService = ForgeFileSnapshotService;
     type FsRemoveService = ForgeFileRemoveService<ForgeFileSnapshotService>;
+    type FsMoveService = ForgeFileMoveService<ForgeFileSnapshotService>;
+    type FsCopyService = ForgeFileCopyService<ForgeFileSnapshotService>;
+    type FsWatchService = ForgeFileWatchService;
     type FsCreateDirsService = ForgeCreateDirsService;
     type CommandExecutorService = ForgeCommandExecutorService;
     type InquireService = ForgeInquire;
@@ -89,6 +104,18 @@ impl Infrastructure for ForgeInfra {
         &self.file_remove_service
     }
 
+    fn file_move_service(&self) -> &Self::FsMoveService {
+        &self.file_move_service
+    }
+
+    fn file_copy_service(&self) -> &Self::FsCopyService {
+        &self.file_copy_service
+    }
+
+    fn file_watch_service(&self) -> &Self::FsWatchService {
+        &self.file_watch_service
+    }
+
     fn create_dirs_service(&self) -> &Self::FsCreateDirsService {
         &self.create_dirs_service
     }
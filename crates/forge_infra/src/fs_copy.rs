@@ -0,0 +1,28 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use forge_fs::ForgeFS;
+use forge_services::{FsCopyService, FsSnapshotService};
+
+pub struct ForgeFileCopyService<S> {
+    snaps: Arc<S>,
+}
+
+impl<S> ForgeFileCopyService<S> {
+    pub fn new(snaps: Arc<S>) -> Self {
+        Self { snaps }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: FsSnapshotService> FsCopyService for ForgeFileCopyService<S> {
+    async fn copy(&self, from: &Path, to: &Path) -> anyhow::Result<()> {
+        // Snapshot whatever the copy replaces so that it can be undone
+        if ForgeFS::exists(to) {
+            let _ = self.snaps.create_snapshot(to, None).await?;
+        }
+
+        ForgeFS::copy(from, to).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use std::future::Future;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Arc;
+
+use forge_fs::ForgeFS;
+use forge_services::{FsMoveService, FsSnapshotService};
+
+pub struct ForgeFileMoveService<S> {
+    snaps: Arc<S>,
+}
+
+impl<S> ForgeFileMoveService<S> {
+    pub fn new(snaps: Arc<S>) -> Self {
+        Self { snaps }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: FsSnapshotService> FsMoveService for ForgeFileMoveService<S> {
+    async fn rename(&self, from: &Path, to: &Path) -> anyhow::Result<()> {
+        // Both paths are snapshotted so that undo can bring back the original
+        // file and whatever the move replaced
+        let _ = self.snaps.create_snapshot(from, None).await?;
+        if ForgeFS::exists(to) {
+            let _ = self.snaps.create_snapshot(to, None).await?;
+        }
+
+        move_file(from, to, ForgeFS::rename).await
+    }
+}
+
+/// Moves `from` to `to` with `rename`, copying the file and removing the
+/// original instead when `rename` fails because the paths are on different
+/// devices
+async fn move_file<'a, R, Fut>(from: &'a Path, to: &'a Path, rename: R) -> anyhow::Result<()>
+where
+    R: FnOnce(&'a Path, &'a Path) -> Fut,
+    Fut: Future<Output = forge_fs::Result<()>>,
+{
+    match rename(from, to).await {
+        Err(error) if error.kind() == Some(ErrorKind::CrossesDevices) => {
+            ForgeFS::copy(from, to).await?;
+            Ok(ForgeFS::remove_file(from).await?)
+        }
+        result => Ok(result?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_move_file_renames() {
+        let dir = TempDir::new().unwrap();
+        let from = dir.path().join("a.txt");
+        let to = dir.path().join("b.txt");
+        std::fs::write(&from, "content").unwrap();
+
+        move_file(&from, &to, ForgeFS::rename).await.unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "content");
+    }
+
+    #[tokio::test]
+    async fn test_move_file_copies_across_devices() {
+        let dir = TempDir::new().unwrap();
+        let from = dir.path().join("a.txt");
+        let to = dir.path().join("b.txt");
+        std::fs::write(&from, "content").unwrap();
+        let cross_device = |from: &Path, _: &Path| {
+            let error = std::io::Error::from(ErrorKind::CrossesDevices);
+            let error = forge_fs::Error::Io {
+                operation: "rename",
+                path: from.to_path_buf(),
+                kind: error.kind(),
+                source: error,
+            };
+            async move { Err(error) }
+        };
+
+        move_file(&from, &to, cross_device).await.unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "content");
+    }
+
+    #[tokio::test]
+    async fn test_move_file_reports_other_errors() {
+        let dir = TempDir::new().unwrap();
+        let from = dir.path().join("missing.txt");
+        let to = dir.path().join("b.txt");
+
+        let actual = move_file(&from, &to, ForgeFS::rename).await;
+
+        assert!(actual.is_err());
+        assert!(!to.exists());
+    }
+}
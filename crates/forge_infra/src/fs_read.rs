@@ -1,30 +1,42 @@
 use std::path::Path;
 
 use anyhow::Result;
-use forge_services::FsReadService;
+use forge_services::{http_url, FsReadService, WebClient};
+use reqwest::Url;
 
-pub struct ForgeFileReadService;
-
-impl Default for ForgeFileReadService {
-    fn default() -> Self {
-        Self
-    }
+/// Reads files from the file system, and files served over HTTP or HTTPS when
+/// their URL is given in place of a path
+pub struct ForgeFileReadService {
+    web: WebClient,
+    max_fetch_bytes: usize,
 }
 
 impl ForgeFileReadService {
-    pub fn new() -> Self {
-        Self
+    /// URLs are downloaded through `web`, like pages fetched by the fetch
+    /// tool, failing once they're larger than `max_fetch_bytes`
+    pub fn new(web: WebClient, max_fetch_bytes: usize) -> Self {
+        Self { web, max_fetch_bytes }
+    }
+
+    async fn fetch(&self, url: Url) -> Result<Vec<u8>> {
+        self.web.get(&url, self.max_fetch_bytes).await
     }
 }
 
 #[async_trait::async_trait]
 impl FsReadService for ForgeFileReadService {
     async fn read_utf8(&self, path: &Path) -> Result<String> {
-        forge_fs::ForgeFS::read_utf8(path).await
+        match http_url(path) {
+            Some(url) => Ok(String::from_utf8_lossy(&self.fetch(url).await?).into_owned()),
+            None => Ok(forge_fs::ForgeFS::read_utf8(path).await?),
+        }
     }
 
     async fn read(&self, path: &Path) -> Result<Vec<u8>> {
-        forge_fs::ForgeFS::read(path).await
+        match http_url(path) {
+            Some(url) => self.fetch(url).await,
+            None => Ok(forge_fs::ForgeFS::read(path).await?),
+        }
     }
 
     async fn range_read_utf8(
@@ -33,6 +45,80 @@ impl FsReadService for ForgeFileReadService {
         start_char: u64,
         end_char: u64,
     ) -> Result<(String, forge_fs::FileInfo)> {
-        forge_fs::ForgeFS::read_range_utf8(path, start_char, end_char).await
+        match http_url(path) {
+            Some(url) => {
+                let content = self.fetch(url).await?;
+                Ok(forge_fs::ForgeFS::range_utf8(
+                    content, start_char, end_char,
+                )?)
+            }
+            None => Ok(forge_fs::ForgeFS::read_range_utf8(path, start_char, end_char).await?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A reader caching downloads in a temporary directory
+    fn reader(max_fetch_bytes: usize) -> (ForgeFileReadService, TempDir) {
+        let cache_dir = TempDir::new().unwrap();
+        let web = WebClient::new(cache_dir.path().to_path_buf());
+        (ForgeFileReadService::new(web, max_fetch_bytes), cache_dir)
+    }
+
+    #[tokio::test]
+    async fn test_read_url() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/notes.txt")
+            .with_body("0123456789")
+            .create_async()
+            .await;
+        let url = format!("{}/notes.txt", server.url());
+        let (fixture, _cache_dir) = reader(1024);
+
+        let (actual, _) = fixture
+            .range_read_utf8(Path::new(&url), 2, 5)
+            .await
+            .unwrap();
+
+        assert_eq!(actual, "234");
+    }
+
+    #[tokio::test]
+    async fn test_read_url_larger_than_limit() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/large.txt")
+            .with_body("0123456789")
+            .create_async()
+            .await;
+        let url = format!("{}/large.txt", server.url());
+        let (fixture, _cache_dir) = reader(4);
+
+        let actual = fixture.read(Path::new(&url)).await;
+
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_url_respects_robots_txt() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/robots.txt")
+            .with_body("User-agent: *\nDisallow: /private")
+            .create_async()
+            .await;
+        let url = format!("{}/private/notes.txt", server.url());
+        let (fixture, _cache_dir) = reader(1024);
+
+        let actual = fixture.read(Path::new(&url)).await.unwrap_err().to_string();
+
+        assert!(actual.contains("robots.txt"), "{actual}");
     }
 }
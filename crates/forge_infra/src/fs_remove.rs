@@ -17,7 +17,15 @@ impl<S> ForgeFileRemoveService<S> {
 #[async_trait::async_trait]
 impl<S: FsSnapshotService> FileRemoveService for ForgeFileRemoveService<S> {
     async fn remove(&self, path: &Path) -> anyhow::Result<()> {
-        let _ = self.snaps.create_snapshot(path).await?;
+        let _ = self.snaps.create_snapshot(path, None).await?;
+        Ok(forge_fs::ForgeFS::remove_file(path).await?)
+    }
+
+    async fn remove_with_cause(&self, path: &Path, cause: &str) -> anyhow::Result<()> {
+        let _ = self
+            .snaps
+            .create_snapshot(path, Some(cause.to_string()))
+            .await?;
         Ok(forge_fs::ForgeFS::remove_file(path).await?)
     }
 }
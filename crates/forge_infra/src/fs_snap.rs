@@ -1,10 +1,16 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::Result;
-use forge_domain::Environment;
+use anyhow::{Context, Result};
+use forge_domain::{Compression, Environment};
+use forge_fs::ForgeFS;
 use forge_services::FsSnapshotService;
-use forge_snaps::Snapshot;
+use forge_snaps::{
+    ExportReport, ImportReport, ImportStrategy, PurgePolicy, Snapshot, SnapshotCompression,
+    SnapshotId, SnapshotSelector, SnapshotSummary, TreeRestoreReport, TreeSnapshotInfo,
+    VerificationIssue,
+};
+use similar::TextDiff;
 
 pub struct ForgeFileSnapshotService {
     inner: Arc<forge_snaps::SnapshotService>,
@@ -12,21 +18,245 @@ pub struct ForgeFileSnapshotService {
 
 impl ForgeFileSnapshotService {
     pub fn new(env: Environment) -> Self {
+        let compression = match env.snapshot_compression {
+            Compression::None => SnapshotCompression::None,
+            Compression::Zstd => SnapshotCompression::Zstd,
+            Compression::Gzip => SnapshotCompression::Gzip,
+        };
         Self {
-            inner: Arc::new(forge_snaps::SnapshotService::new(env.snapshot_path())),
+            inner: Arc::new(
+                forge_snaps::SnapshotService::new(env.snapshot_path()).compression(compression),
+            ),
         }
     }
+
+    /// Keeps the snapshots in `directory` rather than the environment's
+    #[cfg(test)]
+    pub(crate) fn in_directory(directory: std::path::PathBuf) -> Self {
+        Self {
+            inner: Arc::new(forge_snaps::SnapshotService::new(directory)),
+        }
+    }
+
+    /// Reads the snapshot of the file at `index`, `0` being the most recent
+    /// one and `-1` the oldest
+    async fn read_snapshot(&self, file_path: &Path, index: isize) -> Result<String> {
+        let index = match usize::try_from(index) {
+            Ok(index) => index,
+            Err(_) => {
+                let count = self.list_snapshots(file_path).await?.len();
+                count.checked_sub(index.unsigned_abs()).with_context(|| {
+                    format!(
+                        "Snapshot index {index} is out of range, {} has {count} snapshots",
+                        file_path.display()
+                    )
+                })?
+            }
+        };
+        let content = self
+            .inner
+            .read_snapshot(file_path.to_path_buf(), SnapshotSelector::Index(index))
+            .await?;
+        Ok(String::from_utf8_lossy(&content).into_owned())
+    }
+}
+
+/// Formats the changes from `old` to `new` as a unified diff of the file
+fn unified_diff(file_path: &Path, old: (&str, &str), new: (&str, &str)) -> String {
+    let (old_label, old) = old;
+    let (new_label, new) = new;
+    let path = file_path.display();
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(
+            &format!("{path} ({old_label})"),
+            &format!("{path} ({new_label})"),
+        )
+        .to_string()
 }
 
 #[async_trait::async_trait]
 impl FsSnapshotService for ForgeFileSnapshotService {
     // Creation
-    async fn create_snapshot(&self, file_path: &Path) -> Result<Snapshot> {
-        self.inner.create_snapshot(file_path.to_path_buf()).await
+    async fn create_snapshot(&self, file_path: &Path, cause: Option<String>) -> Result<Snapshot> {
+        self.inner
+            .create_snapshot(file_path.to_path_buf(), cause)
+            .await
     }
 
     // Undo
     async fn undo_snapshot(&self, file_path: &Path) -> Result<()> {
         self.inner.undo_snapshot(file_path.to_path_buf()).await
     }
+
+    // Restore
+    async fn restore_to(
+        &self,
+        file_path: &Path,
+        selector: SnapshotSelector,
+        dest: &Path,
+        overwrite: bool,
+    ) -> Result<()> {
+        self.inner
+            .restore_to(file_path.to_path_buf(), selector, dest, overwrite)
+            .await
+    }
+
+    // Tagging
+    async fn tag_snapshot(&self, file_path: &Path, timestamp: &str, tag: &str) -> Result<()> {
+        self.inner
+            .tag_snapshot(file_path.to_path_buf(), timestamp, tag)
+            .await
+    }
+
+    async fn restore_by_tag(&self, file_path: &Path, tag: &str) -> Result<()> {
+        self.inner
+            .restore_by_tag(file_path.to_path_buf(), tag)
+            .await
+    }
+
+    // Listing
+    async fn list_all(&self) -> Result<Vec<SnapshotSummary>> {
+        self.inner.list_all().await
+    }
+
+    async fn list_snapshots(&self, file_path: &Path) -> Result<Vec<Snapshot>> {
+        self.inner.list_snapshots(file_path.to_path_buf()).await
+    }
+
+    // Diffing
+    async fn diff_snapshots(
+        &self,
+        file_path: &Path,
+        from_index: isize,
+        to_index: isize,
+    ) -> Result<String> {
+        let from = self.read_snapshot(file_path, from_index).await?;
+        let to = self.read_snapshot(file_path, to_index).await?;
+        Ok(unified_diff(
+            file_path,
+            (&format!("snapshot {from_index}"), &from),
+            (&format!("snapshot {to_index}"), &to),
+        ))
+    }
+
+    async fn diff_snapshot_and_current(&self, file_path: &Path, index: isize) -> Result<String> {
+        let snapshot = self.read_snapshot(file_path, index).await?;
+        let (current, _) = ForgeFS::read_utf8_lossy(file_path).await?;
+        Ok(unified_diff(
+            file_path,
+            (&format!("snapshot {index}"), &snapshot),
+            ("current", &current),
+        ))
+    }
+
+    // Trees
+    async fn create_tree_snapshot(
+        &self,
+        root: &Path,
+        include_globs: &[String],
+        exclude_globs: &[String],
+    ) -> Result<TreeSnapshotInfo> {
+        self.inner
+            .create_tree_snapshot(root.to_path_buf(), include_globs, exclude_globs)
+            .await
+    }
+
+    async fn restore_tree(&self, id: &SnapshotId) -> Result<TreeRestoreReport> {
+        self.inner.restore_tree(id).await
+    }
+
+    async fn verify(&self, file_path: &Path) -> Result<Vec<VerificationIssue>> {
+        self.inner.verify(file_path.to_path_buf()).await
+    }
+
+    async fn verify_all(&self) -> Result<Vec<VerificationIssue>> {
+        self.inner.verify_all().await
+    }
+
+    // Cleanup
+    async fn purge_excess(&self, file_path: &Path, keep: usize) -> Result<usize> {
+        self.inner.purge_excess(file_path.to_path_buf(), keep).await
+    }
+
+    async fn purge(&self, policy: PurgePolicy) -> Result<usize> {
+        self.inner.purge_with_policy(&policy).await
+    }
+
+    // Archives
+    async fn export_archive(&self, dest: &Path, root: &Path) -> Result<ExportReport> {
+        self.inner.export_archive(dest, root, None).await
+    }
+
+    async fn import_archive(
+        &self,
+        src: &Path,
+        root: &Path,
+        strategy: ImportStrategy,
+    ) -> Result<ImportReport> {
+        self.inner.import_archive(src, root, strategy).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A file with two snapshots, the latest content being left unsnapshotted
+    async fn fixture() -> (TempDir, ForgeFileSnapshotService, std::path::PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let service = ForgeFileSnapshotService::in_directory(dir.path().join("snapshots"));
+        let path = dir.path().join("notes.txt");
+        for content in ["one\ntwo\nthree\n", "one\n2\nthree\nfour\n"] {
+            ForgeFS::write(&path, content).await.unwrap();
+            service.create_snapshot(&path, None).await.unwrap();
+        }
+        ForgeFS::write(&path, "one\n2\nthree\nfour\nfive\n")
+            .await
+            .unwrap();
+        (dir, service, path)
+    }
+
+    #[tokio::test]
+    async fn test_diff_snapshots() {
+        let (_dir, service, path) = fixture().await;
+
+        let actual = service.diff_snapshots(&path, 1, 0).await.unwrap();
+
+        let path = path.display();
+        let expected = format!(
+            "--- {path} (snapshot 1)\n+++ {path} (snapshot 0)\n@@ -1,3 +1,4 @@\n one\n-two\n+2\n three\n+four\n"
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_diff_snapshots_negative_index() {
+        let (_dir, service, path) = fixture().await;
+
+        let actual = service.diff_snapshots(&path, -1, -2).await.unwrap();
+
+        let path_display = path.display();
+        let expected = format!(
+            "--- {path_display} (snapshot -1)\n+++ {path_display} (snapshot -2)\n@@ -1,3 +1,4 @@\n one\n-two\n+2\n three\n+four\n"
+        );
+        assert_eq!(actual, expected);
+        assert!(service.diff_snapshots(&path, -3, 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_diff_snapshot_and_current() {
+        let (_dir, service, path) = fixture().await;
+
+        let actual = service.diff_snapshot_and_current(&path, 0).await.unwrap();
+
+        let path = path.display();
+        let expected = format!(
+            "--- {path} (snapshot 0)\n+++ {path} (current)\n@@ -2,3 +2,4 @@\n 2\n three\n four\n+five\n"
+        );
+        assert_eq!(actual, expected);
+    }
 }
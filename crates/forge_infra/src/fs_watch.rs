@@ -0,0 +1,267 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+use forge_domain::{FileEvent, FileEventKind};
+use forge_services::FsWatchService;
+use futures::stream::{self, BoxStream, StreamExt};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::time::{timeout_at, Instant};
+
+/// How long a path has to stay quiet before its changes are reported
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(100);
+
+pub struct ForgeFileWatchService {
+    debounce: Duration,
+}
+
+impl ForgeFileWatchService {
+    /// Creates a watcher that reports the changes of a path once no further
+    /// change has happened to it for `debounce`
+    pub fn new(debounce: Duration) -> Self {
+        Self { debounce }
+    }
+}
+
+impl Default for ForgeFileWatchService {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEBOUNCE)
+    }
+}
+
+/// Maps a notify event to the changes it describes, dropping accesses and
+/// events that don't say what happened
+fn file_events(event: Event) -> Vec<FileEvent> {
+    let kind = match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            FileEventKind::Created
+        }
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            FileEventKind::Removed
+        }
+        // Both ends of a rename are also reported on their own
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => return Vec::new(),
+        EventKind::Modify(_) => FileEventKind::Modified,
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => return Vec::new(),
+    };
+    event
+        .paths
+        .into_iter()
+        .map(|path| FileEvent::new(path, kind))
+        .collect()
+}
+
+/// Combines two successive changes of the same path into the one they amount
+/// to
+fn merge(first: FileEventKind, then: FileEventKind) -> FileEventKind {
+    match (first, then) {
+        // A new file that is then written is still new
+        (FileEventKind::Created, FileEventKind::Modified) => FileEventKind::Created,
+        // Editors that save by replacing the file remove and recreate it
+        (FileEventKind::Removed, FileEventKind::Created) => FileEventKind::Modified,
+        (_, then) => then,
+    }
+}
+
+/// A change waiting for its path to settle
+struct Pending {
+    event: FileEvent,
+    changed_at: Instant,
+}
+
+/// Changes waiting for their paths to settle, one per path in the order the
+/// paths first changed. Each path settles on its own, so a busy file doesn't
+/// hold back the changes of its siblings.
+#[derive(Default)]
+struct Burst(Vec<Pending>);
+
+impl Burst {
+    fn add(&mut self, event: FileEvent, now: Instant) {
+        match self
+            .0
+            .iter_mut()
+            .find(|pending| pending.event.path == event.path)
+        {
+            Some(pending) => {
+                pending.event.kind = merge(pending.event.kind, event.kind);
+                pending.changed_at = now;
+            }
+            None => self.0.push(Pending { event, changed_at: now }),
+        }
+    }
+
+    /// When the next path settles, `None` when no change is pending
+    fn deadline(&self, debounce: Duration) -> Option<Instant> {
+        self.0
+            .iter()
+            .map(|pending| pending.changed_at + debounce)
+            .min()
+    }
+
+    /// Takes the changes of the paths that have been quiet for `debounce`
+    fn settled(&mut self, now: Instant, debounce: Duration) -> Vec<FileEvent> {
+        let (settled, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.0)
+            .into_iter()
+            .partition(|pending| pending.changed_at + debounce <= now);
+        self.0 = pending;
+        settled.into_iter().map(|pending| pending.event).collect()
+    }
+}
+
+struct Watch {
+    /// Dropping the watcher stops the events
+    _watcher: RecommendedWatcher,
+    events: UnboundedReceiver<FileEvent>,
+    burst: Burst,
+    ready: std::vec::IntoIter<FileEvent>,
+    debounce: Duration,
+}
+
+impl Watch {
+    /// Waits for the next paths to settle and yields their changes one at a
+    /// time
+    async fn next(mut self) -> Option<(FileEvent, Self)> {
+        loop {
+            if let Some(event) = self.ready.next() {
+                return Some((event, self));
+            }
+
+            let settled = self.burst.settled(Instant::now(), self.debounce);
+            if !settled.is_empty() {
+                self.ready = settled.into_iter();
+                continue;
+            }
+
+            let event = match self.burst.deadline(self.debounce) {
+                Some(deadline) => match timeout_at(deadline, self.events.recv()).await {
+                    Ok(event) => event,
+                    // The next path has settled
+                    Err(_) => continue,
+                },
+                None => self.events.recv().await,
+            };
+            match event {
+                Some(event) => self.burst.add(event, Instant::now()),
+                // Once the watcher is gone, what's pending is reported as it is
+                None if self.burst.0.is_empty() => return None,
+                None => {
+                    let pending = std::mem::take(&mut self.burst.0);
+                    self.ready = pending
+                        .into_iter()
+                        .map(|pending| pending.event)
+                        .collect::<Vec<_>>()
+                        .into_iter();
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl FsWatchService for ForgeFileWatchService {
+    async fn watch(&self, path: &Path) -> anyhow::Result<BoxStream<'static, FileEvent>> {
+        let (tx, rx) = unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            // Errors of the underlying watcher leave nothing to report
+            if let Ok(event) = event {
+                for event in file_events(event) {
+                    let _ = tx.send(event);
+                }
+            }
+        })?;
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", path.display()))?;
+
+        let watch = Watch {
+            _watcher: watcher,
+            events: rx,
+            burst: Burst::default(),
+            ready: Vec::new().into_iter(),
+            debounce: self.debounce,
+        };
+        Ok(stream::unfold(watch, Watch::next).boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let actual = [
+            merge(FileEventKind::Created, FileEventKind::Modified),
+            merge(FileEventKind::Removed, FileEventKind::Created),
+            merge(FileEventKind::Modified, FileEventKind::Removed),
+            merge(FileEventKind::Modified, FileEventKind::Modified),
+        ];
+
+        let expected = [
+            FileEventKind::Created,
+            FileEventKind::Modified,
+            FileEventKind::Removed,
+            FileEventKind::Modified,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_watch_debounces_modifications() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().canonicalize().unwrap().join("file.txt");
+        tokio::fs::write(&path, "before").await.unwrap();
+        let service = ForgeFileWatchService::new(Duration::from_millis(200));
+        let mut events = service.watch(&path).await.unwrap();
+
+        // Each write truncates the file and then fills it, raising several
+        // events in quick succession
+        tokio::fs::write(&path, "after").await.unwrap();
+        tokio::fs::write(&path, "after again").await.unwrap();
+
+        let first = timeout(Duration::from_secs(5), events.next()).await;
+        let second = timeout(Duration::from_secs(1), events.next()).await;
+
+        assert_eq!(
+            first.unwrap(),
+            Some(FileEvent::new(path, FileEventKind::Modified))
+        );
+        assert!(second.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_file_next_to_busy_sibling() {
+        let dir = TempDir::new().unwrap();
+        let dir = dir.path().canonicalize().unwrap();
+        let path = dir.join("file.txt");
+        let sibling = dir.join("busy.log");
+        tokio::fs::write(&path, "before").await.unwrap();
+        tokio::fs::write(&sibling, "").await.unwrap();
+        let service = ForgeFileWatchService::new(Duration::from_millis(200));
+        let mut events = service.watch(&dir).await.unwrap();
+
+        // The sibling changes more often than the debounce, for longer than
+        // the file is given to be reported
+        let busy = tokio::spawn(async move {
+            for i in 0..150 {
+                tokio::fs::write(&sibling, i.to_string()).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+        tokio::fs::write(&path, "after").await.unwrap();
+
+        let actual = timeout(Duration::from_secs(2), events.next()).await;
+        busy.abort();
+
+        assert_eq!(
+            actual.unwrap(),
+            Some(FileEvent::new(path, FileEventKind::Modified))
+        );
+    }
+}
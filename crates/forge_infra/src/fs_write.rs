@@ -5,24 +5,75 @@ use anyhow::Result;
 use bytes::Bytes;
 use forge_services::{FsSnapshotService, FsWriteService};
 
+/// Options of [`ForgeFileWriteService`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsWriteOptions {
+    /// Snapshots the previous content of a file before it's written or
+    /// appended to, so that the change can be undone. Worth turning off for
+    /// high-frequency writes such as log appends.
+    pub auto_snapshot: bool,
+}
+
+impl Default for FsWriteOptions {
+    fn default() -> Self {
+        Self { auto_snapshot: true }
+    }
+}
+
 pub struct ForgeFileWriteService<S> {
     snaps: Arc<S>,
+    options: FsWriteOptions,
 }
 
 impl<S> ForgeFileWriteService<S> {
     pub fn new(snaps: Arc<S>) -> Self {
-        Self { snaps }
+        Self { snaps, options: FsWriteOptions::default() }
+    }
+
+    pub fn with_options(mut self, options: FsWriteOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<S: FsSnapshotService> ForgeFileWriteService<S> {
+    /// Snapshots the previous content of `path`, if any and if enabled
+    async fn snapshot(&self, path: &Path, cause: Option<String>) -> Result<()> {
+        if self.options.auto_snapshot && forge_fs::ForgeFS::exists(path) {
+            let _ = self.snaps.create_snapshot(path, cause).await?;
+        }
+        Ok(())
+    }
+
+    /// Snapshots the previous content of `path`, if any, before writing
+    async fn write_snapshotted(
+        &self,
+        path: &Path,
+        contents: Bytes,
+        cause: Option<String>,
+    ) -> Result<()> {
+        self.snapshot(path, cause).await?;
+
+        // A cancelled tool call must not leave the file half written
+        Ok(forge_fs::ForgeFS::write_atomic(path, contents).await?)
     }
 }
 
 #[async_trait::async_trait]
 impl<S: FsSnapshotService> FsWriteService for ForgeFileWriteService<S> {
     async fn write(&self, path: &Path, contents: Bytes) -> Result<()> {
-        if forge_fs::ForgeFS::exists(path) {
-            let _ = self.snaps.create_snapshot(path).await?;
-        }
+        self.write_snapshotted(path, contents, None).await
+    }
 
-        Ok(forge_fs::ForgeFS::write(path, contents.to_vec()).await?)
+    async fn write_with_cause(&self, path: &Path, contents: Bytes, cause: &str) -> Result<()> {
+        self.write_snapshotted(path, contents, Some(cause.to_string()))
+            .await
+    }
+
+    async fn append(&self, path: &Path, contents: Bytes) -> Result<()> {
+        self.snapshot(path, None).await?;
+
+        Ok(forge_fs::ForgeFS::append(path, contents).await?)
     }
 
     async fn write_temp(&self, prefix: &str, ext: &str, content: &str) -> anyhow::Result<PathBuf> {
@@ -39,3 +90,46 @@ impl<S: FsSnapshotService> FsWriteService for ForgeFileWriteService<S> {
         Ok(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::fs_snap::ForgeFileSnapshotService;
+
+    /// Writes and appends to a file, returning how many snapshots of it were
+    /// taken
+    async fn snapshots_taken(options: FsWriteOptions) -> usize {
+        let dir = TempDir::new().unwrap();
+        let snaps = Arc::new(ForgeFileSnapshotService::in_directory(
+            dir.path().join("snapshots"),
+        ));
+        let service = ForgeFileWriteService::new(snaps.clone()).with_options(options);
+        let path = dir.path().join("notes.txt");
+
+        service.write(&path, "one\n".into()).await.unwrap();
+        service.write(&path, "two\n".into()).await.unwrap();
+        service.append(&path, "three\n".into()).await.unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "two\nthree\n");
+        snaps.list_snapshots(&path).await.unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn test_write_snapshots_existing_files() {
+        let actual = snapshots_taken(FsWriteOptions::default()).await;
+
+        // The first write creates the file, so there is nothing to snapshot
+        assert_eq!(actual, 2);
+    }
+
+    #[tokio::test]
+    async fn test_write_without_auto_snapshot() {
+        let actual = snapshots_taken(FsWriteOptions { auto_snapshot: false }).await;
+
+        assert_eq!(actual, 0);
+    }
+}
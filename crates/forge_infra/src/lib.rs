@@ -3,11 +3,14 @@ pub mod executor;
 mod env;
 mod error;
 mod forge_infra;
+mod fs_copy;
 mod fs_create_dirs;
 mod fs_meta;
+mod fs_move;
 mod fs_read;
 mod fs_remove;
 mod fs_snap;
+mod fs_watch;
 mod fs_write;
 mod inquire;
 mod mcp_client;
@@ -15,3 +18,5 @@ mod mcp_server;
 
 pub use executor::ForgeCommandExecutorService;
 pub use forge_infra::*;
+pub use fs_read::ForgeFileReadService;
+pub use fs_write::{ForgeFileWriteService, FsWriteOptions};
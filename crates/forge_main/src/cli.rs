@@ -63,6 +63,26 @@ pub struct Cli {
 #[derive(Subcommand, Debug, Clone)]
 pub enum TopLevelCommand {
     Mcp(McpCommandGroup),
+
+    /// Inspect forge configuration
+    Config(ConfigCommandGroup),
+}
+
+/// Group of config-related commands
+#[derive(Parser, Debug, Clone)]
+pub struct ConfigCommandGroup {
+    /// Subcommands under `config`
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Print the JSON schema of the config file
+    Schema,
+
+    /// Print the location of the config file
+    Path,
 }
 
 /// Group of MCP-related commands
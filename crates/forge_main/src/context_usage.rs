@@ -0,0 +1,71 @@
+use forge_api::Usage;
+
+use crate::info::Info;
+
+/// Number of cells in the bar showing how full the context window is
+const BAR_WIDTH: usize = 20;
+
+/// How much of the model's context window the conversation takes up, along
+/// with the tokens used over the session
+pub struct ContextUsage {
+    /// Estimated number of tokens in the conversation context
+    pub tokens: u64,
+    /// The model's context window, when known
+    pub context_length: Option<u64>,
+    /// Tokens used by every request of the session
+    pub session: Usage,
+}
+
+/// Renders a bar filled in proportion to `tokens` out of `context_length`,
+/// followed by the percentage
+fn usage_bar(tokens: u64, context_length: u64) -> String {
+    let ratio = (tokens as f64 / context_length.max(1) as f64).min(1.0);
+    let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+    format!(
+        "[{}{}] {:.0}%",
+        "█".repeat(filled),
+        "░".repeat(BAR_WIDTH - filled),
+        ratio * 100.0
+    )
+}
+
+impl From<&ContextUsage> for Info {
+    fn from(value: &ContextUsage) -> Self {
+        let mut info = Info::new()
+            .add_title("Context")
+            .add_key_value("Tokens", format!("~{}", value.tokens));
+
+        info = match value.context_length {
+            Some(length) => info
+                .add_key_value("Window", length)
+                .add_key_value("Used", usage_bar(value.tokens, length)),
+            None => info.add_key_value("Window", "unknown"),
+        };
+
+        info.add_title("Session")
+            .add_key_value("Prompt", value.session.prompt_tokens)
+            .add_key_value("Completion", value.session.completion_tokens)
+            .add_key_value("Total", value.session.total_tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_usage_bar() {
+        let actual = usage_bar(32_000, 128_000);
+        let expected = "[█████░░░░░░░░░░░░░░░] 25%";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_usage_bar_caps_at_full() {
+        let actual = usage_bar(150_000, 128_000);
+        let expected = "[████████████████████] 100%";
+        assert_eq!(actual, expected);
+    }
+}
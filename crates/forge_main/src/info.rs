@@ -106,6 +106,10 @@ impl From<&UIState> for Info {
             .add_key_value("Completion", usage.completion_tokens)
             .add_key_value("Total", usage.total_tokens);
 
+        if let Some(cost) = usage.cost {
+            info = info.add_key_value("Cost", format!("${cost:.4}"));
+        }
+
         info
     }
 }
@@ -1,6 +1,7 @@
 mod banner;
 mod cli;
 mod completer;
+mod context_usage;
 mod editor;
 mod info;
 mod input;
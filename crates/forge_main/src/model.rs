@@ -1,6 +1,11 @@
+use std::path::PathBuf;
+use std::process::ExitStatus;
 use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
 
-use forge_api::{Model, Workflow};
+use anyhow::Context;
+use forge_api::{Macro, Model, Workflow};
+use forge_snaps::{ImportStrategy, Snapshot, SnapshotId, SnapshotSelector, SnapshotSummary};
 use strum::{EnumProperty, IntoEnumIterator};
 use strum_macros::{EnumIter, EnumProperty};
 
@@ -33,6 +38,65 @@ impl From<&[Model]> for Info {
     }
 }
 
+fn humanize_bytes(bytes: u64) -> String {
+    if bytes >= 1_000_000 {
+        format!("{:.1} MB", bytes as f64 / 1_000_000.0)
+    } else if bytes >= 1_000 {
+        format!("{:.1} KB", bytes as f64 / 1_000.0)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+impl From<&[SnapshotSummary]> for Info {
+    fn from(summaries: &[SnapshotSummary]) -> Self {
+        let mut info = Info::new().add_title("Snapshots");
+
+        for summary in summaries.iter() {
+            let newest = chrono::DateTime::<chrono::Local>::from(UNIX_EPOCH + summary.newest);
+            info = info.add_key_value(
+                summary.original_path.display(),
+                format!(
+                    "{} snapshots, {}, last {}",
+                    summary.count,
+                    humanize_bytes(summary.total_bytes),
+                    newest.format("%Y-%m-%d %H:%M:%S")
+                ),
+            );
+        }
+
+        info
+    }
+}
+
+impl From<&[Snapshot]> for Info {
+    fn from(snapshots: &[Snapshot]) -> Self {
+        let mut info = Info::new().add_title("Snapshot history");
+        if let Some(snapshot) = snapshots.first() {
+            info = info.add_key_value("Path", &snapshot.path);
+        }
+
+        for (index, snapshot) in snapshots.iter().enumerate() {
+            let time = chrono::DateTime::<chrono::Local>::from(UNIX_EPOCH + snapshot.timestamp);
+            let tag = snapshot
+                .tag
+                .as_ref()
+                .map(|tag| format!("[{tag}] "))
+                .unwrap_or_default();
+            info = info.add_key_value(
+                format!("#{index} {}", time.format("%Y-%m-%d %H:%M:%S")),
+                format!(
+                    "{tag}{}, {}",
+                    snapshot.cause.as_deref().unwrap_or("unknown cause"),
+                    humanize_bytes(snapshot.size)
+                ),
+            );
+        }
+
+        info
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ForgeCommand {
     pub name: String,
@@ -51,12 +115,16 @@ impl From<&Workflow> for ForgeCommandManager {
 #[derive(Debug)]
 pub struct ForgeCommandManager {
     commands: Arc<Mutex<Vec<ForgeCommand>>>,
+    macros: Arc<Mutex<Vec<Macro>>>,
 }
 
 impl Default for ForgeCommandManager {
     fn default() -> Self {
         let commands = Self::default_commands();
-        ForgeCommandManager { commands: Arc::new(Mutex::new(commands)) }
+        ForgeCommandManager {
+            commands: Arc::new(Mutex::new(commands)),
+            macros: Default::default(),
+        }
     }
 }
 
@@ -90,6 +158,34 @@ impl ForgeCommandManager {
         }));
 
         *guard = commands;
+        *self.macros.lock().unwrap() = workflow.macros.clone();
+    }
+
+    /// Parses the steps of the macro `name` into the commands they run, in
+    /// order, with `{arg}` replaced by `arg`
+    pub fn expand_macro(&self, name: &str, arg: &str) -> anyhow::Result<Vec<Command>> {
+        let found = self
+            .macros
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|candidate| candidate.name == name)
+            .cloned();
+        let found = found.with_context(|| format!("Macro '{name}' is not defined"))?;
+        if found.takes_arg() && arg.trim().is_empty() {
+            anyhow::bail!("Macro '{name}' expects an argument. Usage: /run {name} <arg>");
+        }
+
+        found
+            .expand(arg)
+            .iter()
+            .map(|step| match self.parse(step)? {
+                Command::Run { .. } => {
+                    anyhow::bail!("Macro '{name}' can't run another macro: {step}")
+                }
+                command => Ok(command),
+            })
+            .collect()
     }
 
     /// Finds a command by name.
@@ -166,6 +262,8 @@ impl ForgeCommandManager {
         // TODO: Can leverage Clap to parse commands and provide correct error messages
         match command {
             "/compact" => Ok(Command::Compact),
+            "/pin" => Ok(Command::Pin),
+            "/approve" => Ok(Command::Approve),
             "/new" => Ok(Command::New),
             "/info" => Ok(Command::Info),
             "/exit" => Ok(Command::Exit),
@@ -182,6 +280,14 @@ impl ForgeCommandManager {
             "/help" => Ok(Command::Help),
             "/model" => Ok(Command::Model),
             "/tools" => Ok(Command::Tools),
+            "/run" => {
+                let (name, arg) = parameters
+                    .split_first()
+                    .context("Usage: /run <macro> [arg]")?;
+                Ok(Command::Run { name: name.to_string(), arg: arg.join(" ") })
+            }
+            "/tokens" => Ok(Command::Tokens),
+            "/snapshots" => Ok(Command::Snapshots(SnapshotCommand::parse(&parameters)?)),
             text => {
                 let parts = text.split_ascii_whitespace().collect::<Vec<&str>>();
 
@@ -204,6 +310,127 @@ impl ForgeCommandManager {
     }
 }
 
+/// Subcommands of the '/snapshots' command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SnapshotCommand {
+    /// Lists every file with snapshots
+    #[default]
+    List,
+    /// Lists the snapshots of `path` along with what they were taken for
+    History { path: PathBuf },
+    /// Writes a snapshot of `path` to `dest` without modifying `path`
+    Restore {
+        path: PathBuf,
+        dest: PathBuf,
+        selector: SnapshotSelector,
+        overwrite: bool,
+    },
+    /// Tags the snapshot of `path` taken at `timestamp`
+    Tag {
+        path: PathBuf,
+        timestamp: String,
+        tag: String,
+    },
+    /// Restores the files captured by a tree snapshot
+    RestoreTree { id: SnapshotId },
+    /// Checks the snapshots, only those of `path` when given, for corrupted
+    /// or missing payloads
+    Verify { path: Option<PathBuf> },
+    /// Writes the snapshots of the working directory to an archive at `dest`
+    Export { dest: PathBuf },
+    /// Imports the snapshots of an archive into the working directory
+    Import {
+        src: PathBuf,
+        strategy: ImportStrategy,
+    },
+}
+
+impl SnapshotCommand {
+    pub const USAGE: &str = "/snapshots [list [path]] | /snapshots restore <path> --to <dest> [--index <n> | --timestamp <timestamp> | --label <text> | --tag <tag>] [--overwrite] | /snapshots tag <path> <timestamp> <tag> | /snapshots restore-tree <id> | /snapshots verify [path] | /snapshots export <dest> | /snapshots import <src> [--skip | --overwrite]";
+
+    /// Parses the parameters following '/snapshots'
+    fn parse(parameters: &[&str]) -> anyhow::Result<Self> {
+        let usage = || format!("Usage: {}", Self::USAGE);
+        let args = match parameters.split_first() {
+            None | Some((&"list", [])) => return Ok(Self::List),
+            Some((&"list", [path])) => return Ok(Self::History { path: PathBuf::from(path) }),
+            Some((&"restore", args)) => args,
+            Some((&"tag", [path, timestamp, tag])) => {
+                return Ok(Self::Tag {
+                    path: PathBuf::from(path),
+                    timestamp: timestamp.to_string(),
+                    tag: tag.to_string(),
+                })
+            }
+            Some((&"restore-tree", [id])) => {
+                let id = SnapshotId::parse(id)
+                    .with_context(|| format!("Invalid tree snapshot id '{id}'"))?;
+                return Ok(Self::RestoreTree { id });
+            }
+            Some((&"verify", [])) => return Ok(Self::Verify { path: None }),
+            Some((&"verify", [path])) => {
+                return Ok(Self::Verify { path: Some(PathBuf::from(path)) })
+            }
+            Some((&"export", [dest])) => return Ok(Self::Export { dest: PathBuf::from(dest) }),
+            Some((&"import", [src, options @ ..])) => {
+                let strategy = match options {
+                    [] => ImportStrategy::Merge,
+                    ["--skip"] => ImportStrategy::Skip,
+                    ["--overwrite"] => ImportStrategy::Overwrite,
+                    _ => anyhow::bail!("{}", usage()),
+                };
+                return Ok(Self::Import { src: PathBuf::from(src), strategy });
+            }
+            Some((subcommand, _)) => {
+                anyhow::bail!("Unknown snapshots command '{subcommand}'. {}", usage())
+            }
+        };
+
+        let mut path = None;
+        let mut dest = None;
+        let mut selector = SnapshotSelector::Previous;
+        let mut overwrite = false;
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match *arg {
+                "--to" => dest = Some(PathBuf::from(args.next().with_context(usage)?)),
+                "--index" => {
+                    let index = args.next().with_context(usage)?;
+                    let index = index
+                        .parse()
+                        .with_context(|| format!("Invalid snapshot index '{index}'"))?;
+                    selector = SnapshotSelector::Index(index);
+                }
+                "--timestamp" => {
+                    let timestamp = args.next().with_context(usage)?;
+                    selector = SnapshotSelector::Timestamp(timestamp.to_string());
+                }
+                "--label" => {
+                    let label = args.next().with_context(usage)?;
+                    selector = SnapshotSelector::Label(label.to_string());
+                }
+                "--tag" => {
+                    let tag = args.next().with_context(usage)?;
+                    selector = SnapshotSelector::Tag(tag.to_string());
+                }
+                "--overwrite" => overwrite = true,
+                value if path.is_none() && !value.starts_with("--") => {
+                    path = Some(PathBuf::from(value))
+                }
+                _ => anyhow::bail!("Unexpected argument '{arg}'. {}", usage()),
+            }
+        }
+
+        Ok(Self::Restore {
+            path: path.with_context(usage)?,
+            dest: dest.with_context(usage)?,
+            selector,
+            overwrite,
+        })
+    }
+}
+
 /// Represents user input types in the chat application.
 ///
 /// This enum encapsulates all forms of input including:
@@ -216,6 +443,14 @@ pub enum Command {
     /// '/compact' command.
     #[strum(props(usage = "Compact the conversation context"))]
     Compact,
+    /// Pin the last message so that it survives compaction.
+    /// This can be triggered with the '/pin' command.
+    #[strum(props(usage = "Pin the last message so it is never summarized"))]
+    Pin,
+    /// Approve the plan proposed by the agent so that it carries it out.
+    /// This can be triggered with the '/approve' command.
+    #[strum(props(usage = "Approve the proposed plan and start carrying it out"))]
+    Approve,
     /// Start a new conversation while preserving history.
     /// This can be triggered with the '/new' command.
     #[strum(props(usage = "Start a new conversation"))]
@@ -257,6 +492,20 @@ pub enum Command {
     /// This can be triggered with the '/tools' command.
     #[strum(props(usage = "List all available tools with their descriptions and schema"))]
     Tools,
+    /// Show how full the context window is and the tokens used this session
+    /// This can be triggered with the '/tokens' command.
+    #[strum(props(usage = "Show context window usage and the tokens used this session"))]
+    Tokens,
+    /// Runs the steps of a macro defined in the workflow one after another
+    /// This can be triggered with the '/run <macro> [arg]' command.
+    #[strum(props(usage = "Run the steps of a macro in order (use /run <macro> [arg])"))]
+    Run { name: String, arg: String },
+    /// Works with the snapshots taken before files were modified
+    /// This can be triggered with the '/snapshots' command.
+    #[strum(props(
+        usage = "List files with snapshots or restore one to another path (use /snapshots restore <path> --to <dest>)"
+    ))]
+    Snapshots(SnapshotCommand),
     /// Handles custom command defined in workflow file.
     Custom(PartialEvent),
     /// Executes a native shell command.
@@ -269,6 +518,8 @@ impl Command {
     pub fn name(&self) -> &str {
         match self {
             Command::Compact => "/compact",
+            Command::Pin => "/pin",
+            Command::Approve => "/approve",
             Command::New => "/new",
             Command::Message(_) => "/message",
             Command::Update => "/update",
@@ -280,6 +531,9 @@ impl Command {
             Command::Dump(_) => "/dump",
             Command::Model => "/model",
             Command::Tools => "/tools",
+            Command::Tokens => "/tokens",
+            Command::Run { .. } => "/run",
+            Command::Snapshots(_) => "/snapshots",
             Command::Custom(event) => &event.name,
             Command::Shell(_) => "!shell",
         }
@@ -291,8 +545,20 @@ impl Command {
     }
 }
 
+/// Fails when the shell command a macro step runs exits unsuccessfully, so
+/// the steps after it are dropped
+pub fn ensure_step_succeeded(command: &str, status: ExitStatus) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        status.success(),
+        "Macro step '!{command}' failed with {status}"
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use forge_snaps::SnapshotCompression;
+
     use super::*;
 
     #[test]
@@ -490,4 +756,244 @@ mod tests {
             "Shell command should not be in default commands"
         );
     }
+
+    #[test]
+    fn test_parse_snapshots_restore() {
+        let fixture = ForgeCommandManager::default();
+        let actual = fixture
+            .parse("/snapshots restore src/main.rs --to src/main.rs.old --index 2 --overwrite")
+            .unwrap();
+        let expected = Command::Snapshots(SnapshotCommand::Restore {
+            path: PathBuf::from("src/main.rs"),
+            dest: PathBuf::from("src/main.rs.old"),
+            selector: SnapshotSelector::Index(2),
+            overwrite: true,
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_snapshots_restore_requires_destination() {
+        let fixture = ForgeCommandManager::default();
+        let actual = fixture.parse("/snapshots restore src/main.rs").unwrap_err();
+        assert!(actual.to_string().contains(SnapshotCommand::USAGE));
+    }
+
+    #[test]
+    fn test_parse_snapshots_list() {
+        let fixture = ForgeCommandManager::default();
+        let actual = (
+            fixture.parse("/snapshots").unwrap(),
+            fixture.parse("/snapshots list").unwrap(),
+        );
+        let expected = (
+            Command::Snapshots(SnapshotCommand::List),
+            Command::Snapshots(SnapshotCommand::List),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_snapshots_restore_tree() {
+        let fixture = ForgeCommandManager::default();
+        let actual = fixture
+            .parse("/snapshots restore-tree 67e55044-10b1-426f-9247-bb680e5fe0c8")
+            .unwrap();
+        let expected = Command::Snapshots(SnapshotCommand::RestoreTree {
+            id: SnapshotId::parse("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap(),
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_snapshots_verify() {
+        let fixture = ForgeCommandManager::default();
+        let actual = (
+            fixture.parse("/snapshots verify").unwrap(),
+            fixture.parse("/snapshots verify src/main.rs").unwrap(),
+        );
+        let expected = (
+            Command::Snapshots(SnapshotCommand::Verify { path: None }),
+            Command::Snapshots(SnapshotCommand::Verify {
+                path: Some(PathBuf::from("src/main.rs")),
+            }),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_snapshots_export_import() {
+        let fixture = ForgeCommandManager::default();
+        let actual = (
+            fixture.parse("/snapshots export backup.tar.gz").unwrap(),
+            fixture.parse("/snapshots import backup.tar.gz").unwrap(),
+            fixture
+                .parse("/snapshots import backup.tar.gz --overwrite")
+                .unwrap(),
+        );
+        let expected = (
+            Command::Snapshots(SnapshotCommand::Export { dest: PathBuf::from("backup.tar.gz") }),
+            Command::Snapshots(SnapshotCommand::Import {
+                src: PathBuf::from("backup.tar.gz"),
+                strategy: ImportStrategy::Merge,
+            }),
+            Command::Snapshots(SnapshotCommand::Import {
+                src: PathBuf::from("backup.tar.gz"),
+                strategy: ImportStrategy::Overwrite,
+            }),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_snapshots_history_and_label() {
+        let fixture = ForgeCommandManager::default();
+        let actual = (
+            fixture.parse("/snapshots list src/main.rs").unwrap(),
+            fixture
+                .parse("/snapshots restore src/main.rs --to main.rs.old --label refactor")
+                .unwrap(),
+        );
+        let expected = (
+            Command::Snapshots(SnapshotCommand::History { path: PathBuf::from("src/main.rs") }),
+            Command::Snapshots(SnapshotCommand::Restore {
+                path: PathBuf::from("src/main.rs"),
+                dest: PathBuf::from("main.rs.old"),
+                selector: SnapshotSelector::Label("refactor".to_string()),
+                overwrite: false,
+            }),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_snapshot_history_info_shows_cause() {
+        let snapshot = |cause: Option<&str>| Snapshot {
+            id: SnapshotId::default(),
+            timestamp: std::time::Duration::from_secs(1_700_000_000),
+            path: "/project/src/main.rs".to_string(),
+            hash: "hash".to_string(),
+            size: 2_048,
+            cause: cause.map(str::to_string),
+            mode: None,
+            readonly: false,
+            compression: SnapshotCompression::None,
+            compressed_size: None,
+            tag: None,
+        };
+        let fixture = vec![
+            snapshot(Some("forge_tool_fs_patch: replace \"fn foo()\"")),
+            snapshot(None),
+            Snapshot { tag: Some("stable".to_string()), ..snapshot(Some("manual")) },
+        ];
+
+        let actual = Info::from(fixture.as_slice()).to_string();
+
+        assert!(actual.contains("/project/src/main.rs"));
+        assert!(actual.contains("forge_tool_fs_patch: replace \"fn foo()\", 2.0 KB"));
+        assert!(actual.contains("unknown cause, 2.0 KB"));
+        assert!(actual.contains("[stable] manual, 2.0 KB"));
+    }
+
+    #[test]
+    fn test_parse_snapshots_tag() {
+        let fixture = ForgeCommandManager::default();
+        let actual = (
+            fixture
+                .parse("/snapshots tag src/main.rs 2025-01-02_03-04-05-000000000 stable")
+                .unwrap(),
+            fixture
+                .parse("/snapshots restore src/main.rs --to main.rs.old --tag stable")
+                .unwrap(),
+        );
+        let expected = (
+            Command::Snapshots(SnapshotCommand::Tag {
+                path: PathBuf::from("src/main.rs"),
+                timestamp: "2025-01-02_03-04-05-000000000".to_string(),
+                tag: "stable".to_string(),
+            }),
+            Command::Snapshots(SnapshotCommand::Restore {
+                path: PathBuf::from("src/main.rs"),
+                dest: PathBuf::from("main.rs.old"),
+                selector: SnapshotSelector::Tag("stable".to_string()),
+                overwrite: false,
+            }),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    fn macro_manager() -> ForgeCommandManager {
+        let fixture = ForgeCommandManager::default();
+        fixture.register_all(&Workflow::new().macros(vec![
+            Macro::default().name("ship").steps(vec![
+                "Commit the changes with the message: {arg}".to_string(),
+                "!git push".to_string(),
+            ]),
+            Macro::default()
+                .name("nested")
+                .steps(vec!["/run ship".to_string()]),
+        ]));
+        fixture
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_macro_with_failing_step() {
+        let fixture = ForgeCommandManager::default();
+        fixture.register_all(
+            &Workflow::new().macros(vec![Macro::default().name("check").steps(vec![
+                "!exit 3".to_string(),
+                "Summarize the result".to_string(),
+            ])]),
+        );
+        let steps = fixture.expand_macro("check", "").unwrap();
+        let Command::Shell(command) = &steps[0] else {
+            panic!("expected a shell step, got {:?}", steps[0]);
+        };
+        let status = std::process::Command::new("sh")
+            .args(["-c", command.as_str()])
+            .status()
+            .unwrap();
+
+        let actual = ensure_step_succeeded(command, status)
+            .unwrap_err()
+            .to_string();
+
+        let expected = "Macro step '!exit 3' failed with exit status: 3";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_run() {
+        let fixture = ForgeCommandManager::default();
+        let actual = fixture.parse("/run ship fix the build").unwrap();
+        let expected = Command::Run { name: "ship".to_string(), arg: "fix the build".to_string() };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_expand_macro_runs_steps_in_order() {
+        let fixture = macro_manager();
+        let actual = fixture.expand_macro("ship", "fix the build").unwrap();
+        let expected = vec![
+            Command::Message("Commit the changes with the message: fix the build".to_string()),
+            Command::Shell("git push".to_string()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_expand_macro_requires_arg() {
+        let fixture = macro_manager();
+        let actual = fixture.expand_macro("ship", " ").unwrap_err().to_string();
+        let expected = "Macro 'ship' expects an argument. Usage: /run ship <arg>";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_expand_macro_rejects_nested_macros() {
+        let fixture = macro_manager();
+        let actual = fixture.expand_macro("nested", "").is_err();
+        assert!(actual);
+    }
 }
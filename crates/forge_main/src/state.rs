@@ -30,6 +30,8 @@ impl std::fmt::Display for Mode {
 pub struct UIState {
     pub conversation_id: Option<ConversationId>,
     pub usage: Usage,
+    /// Tokens used by every request since the conversation started
+    pub session_usage: Usage,
     pub mode: Mode,
     pub is_first: bool,
     pub model: Option<ModelId>,
@@ -46,6 +48,7 @@ impl UIState {
         Self {
             conversation_id: Default::default(),
             usage: Default::default(),
+            session_usage: Default::default(),
             mode,
             is_first: true,
             model: workflow.model,
@@ -54,6 +57,19 @@ impl UIState {
     }
 }
 
+impl UIState {
+    /// Records the usage of a request, adding it to the session's usage
+    pub fn record_usage(&mut self, usage: Usage) {
+        self.session_usage.prompt_tokens += usage.prompt_tokens;
+        self.session_usage.completion_tokens += usage.completion_tokens;
+        self.session_usage.total_tokens += usage.total_tokens;
+        if let Some(cost) = usage.cost {
+            *self.session_usage.cost.get_or_insert(0.0) += cost;
+        }
+        self.usage = usage;
+    }
+}
+
 impl From<UIState> for ForgePrompt {
     fn from(state: UIState) -> Self {
         ForgePrompt {
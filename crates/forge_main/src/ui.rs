@@ -1,13 +1,14 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use colored::Colorize;
 use forge_api::{
-    AgentMessage, ChatRequest, ChatResponse, Conversation, ConversationId, Event, Model, ModelId,
-    Workflow, API,
+    token_counter, AgentId, AgentMessage, ChatRequest, ChatResponse, Conversation, ConversationId,
+    Event, Model, ModelId, SnapshotApi, Workflow, API,
 };
 use forge_display::{MarkdownFormat, TitleFormat};
-use forge_domain::{McpConfig, McpServerConfig, Scope};
+use forge_domain::{ConfigLayer, McpConfig, McpServerConfig, OutputStream, Scope};
 use forge_fs::ForgeFS;
 use forge_spinner::SpinnerManager;
 use forge_tracker::ToolCallPayload;
@@ -19,10 +20,11 @@ use serde::Deserialize;
 use serde_json::Value;
 use tokio_stream::StreamExt;
 
-use crate::cli::{Cli, McpCommand, TopLevelCommand, Transport};
+use crate::cli::{Cli, ConfigCommand, McpCommand, TopLevelCommand, Transport};
+use crate::context_usage::ContextUsage;
 use crate::info::Info;
 use crate::input::Console;
-use crate::model::{Command, ForgeCommandManager};
+use crate::model::{ensure_step_succeeded, Command, ForgeCommandManager, SnapshotCommand};
 use crate::state::{Mode, UIState};
 use crate::update::on_update;
 use crate::{banner, TRACKER};
@@ -57,11 +59,13 @@ pub struct UI<F> {
     command: Arc<ForgeCommandManager>,
     cli: Cli,
     spinner: SpinnerManager,
+    /// Commands of the running macro that are still to run
+    macro_steps: VecDeque<Command>,
     #[allow(dead_code)] // The guard is kept alive by being held in the struct
     _guard: forge_tracker::Guard,
 }
 
-impl<F: API> UI<F> {
+impl<F: API + SnapshotApi> UI<F> {
     /// Writes a line to the console output
     /// Takes anything that implements ToString trait
     fn writeln<T: ToString>(&mut self, content: T) -> anyhow::Result<()> {
@@ -149,6 +153,7 @@ impl<F: API> UI<F> {
             cli,
             command,
             spinner: SpinnerManager::new(),
+            macro_steps: VecDeque::new(),
             markdown: MarkdownFormat::new(),
             _guard: forge_tracker::init_tracing(env.log_path(), TRACKER.clone())?,
         })
@@ -199,6 +204,7 @@ impl<F: API> UI<F> {
             tokio::select! {
                 _ = tokio::signal::ctrl_c() => {
                     tracing::info!("User interrupted operation with Ctrl+C");
+                    self.stop_macro()?;
                 }
                 result = self.on_command(command) => {
                     match result {
@@ -214,6 +220,7 @@ impl<F: API> UI<F> {
                             );
                             self.spinner.stop(None)?;
                             eprintln!("{}", TitleFormat::error(format!("{error:?}")));
+                            self.stop_macro()?;
                         },
                     }
                 }
@@ -221,8 +228,11 @@ impl<F: API> UI<F> {
 
             self.spinner.stop(None)?;
 
-            // Centralized prompt call at the end of the loop
-            command = self.prompt().await?;
+            // Run the next step of a macro, if any, before asking for input
+            command = match self.macro_steps.pop_front() {
+                Some(step) => step,
+                None => self.prompt().await?,
+            };
         }
     }
 
@@ -303,6 +313,16 @@ impl<F: API> UI<F> {
                     )))?;
                 }
             },
+            TopLevelCommand::Config(config_command) => match config_command.command {
+                ConfigCommand::Schema => {
+                    let schema = serde_json::to_string_pretty(&ConfigLayer::json_schema())?;
+                    self.writeln(schema)?;
+                }
+                ConfigCommand::Path => {
+                    let path = self.api.environment().config_path();
+                    self.writeln(path.display())?;
+                }
+            },
         }
         Ok(())
     }
@@ -313,6 +333,27 @@ impl<F: API> UI<F> {
                 self.spinner.start(Some("Compacting"))?;
                 self.on_compaction().await?;
             }
+            Command::Pin => {
+                let conversation_id = self.init_conversation().await?;
+                match self.api.pin_last_message(&conversation_id).await? {
+                    Some(content) => {
+                        self.writeln(TitleFormat::action("Pinned message").sub_title(content))?
+                    }
+                    None => self.writeln(TitleFormat::info("No message to pin"))?,
+                }
+            }
+            Command::Approve => {
+                let conversation_id = self.init_conversation().await?;
+                match self.api.approve_plan(&conversation_id).await? {
+                    Some(plan) => {
+                        self.writeln(TitleFormat::action("Approved plan").sub_title(plan.goal))?;
+                        self.spinner.start(None)?;
+                        self.on_message("The plan is approved, carry it out.".to_string())
+                            .await?;
+                    }
+                    None => self.writeln(TitleFormat::info("No plan to approve"))?,
+                }
+            }
             Command::Dump(format) => {
                 self.spinner.start(Some("Creating a conversation dump"))?;
                 self.on_dump(format).await?;
@@ -346,6 +387,17 @@ impl<F: API> UI<F> {
                 let output = format_tools(&tools);
                 self.writeln(output)?;
             }
+            Command::Run { name, arg } => {
+                let steps = self.command.expand_macro(&name, &arg)?;
+                self.writeln(
+                    TitleFormat::action(format!("Running macro {name}"))
+                        .sub_title(format!("{} steps", steps.len())),
+                )?;
+                self.macro_steps.extend(steps);
+            }
+            Command::Tokens => {
+                self.on_tokens().await?;
+            }
             Command::Update => {
                 on_update(self.api.clone(), None).await;
             }
@@ -361,23 +413,158 @@ impl<F: API> UI<F> {
                 self.on_model_selection().await?;
             }
             Command::Shell(ref command) => {
-                self.api.execute_shell_command_raw(command).await?;
+                let status = self.api.execute_shell_command_raw(command).await?;
+                // A failing command stops the macro it is a step of
+                if !self.macro_steps.is_empty() {
+                    ensure_step_succeeded(command, status)?;
+                }
+            }
+            Command::Snapshots(SnapshotCommand::List) => {
+                let snapshots = self.api.snapshots().await?;
+                if snapshots.is_empty() {
+                    self.writeln(TitleFormat::info("No snapshots found"))?;
+                } else {
+                    self.writeln(Info::from(snapshots.as_slice()))?;
+                }
+            }
+            Command::Snapshots(SnapshotCommand::History { path }) => {
+                let snapshots = self.api.file_snapshots(&path).await?;
+                if snapshots.is_empty() {
+                    self.writeln(TitleFormat::info(format!(
+                        "No snapshots found for {}",
+                        path.display()
+                    )))?;
+                } else {
+                    self.writeln(Info::from(snapshots.as_slice()))?;
+                }
+            }
+            Command::Snapshots(SnapshotCommand::Restore { path, dest, selector, overwrite }) => {
+                self.api
+                    .restore_snapshot(&path, selector, &dest, overwrite)
+                    .await?;
+                self.writeln(
+                    TitleFormat::action(format!("Restored snapshot of {}", path.display()))
+                        .sub_title(dest.display().to_string()),
+                )?;
+            }
+            Command::Snapshots(SnapshotCommand::Tag { path, timestamp, tag }) => {
+                self.api.tag_snapshot(&path, &timestamp, &tag).await?;
+                self.writeln(
+                    TitleFormat::action(format!("Tagged snapshot of {} as {tag}", path.display()))
+                        .sub_title(timestamp),
+                )?;
+            }
+            Command::Snapshots(SnapshotCommand::RestoreTree { id }) => {
+                let report = self.api.restore_tree_snapshot(&id).await?;
+                self.writeln(TitleFormat::action(format!(
+                    "Restored tree snapshot {id}: {} reverted, {} recreated",
+                    report.restored.len(),
+                    report.recreated.len()
+                )))?;
+                for path in report.untracked {
+                    self.writeln(
+                        TitleFormat::info("Left untouched").sub_title(path.display().to_string()),
+                    )?;
+                }
+            }
+            Command::Snapshots(SnapshotCommand::Verify { path }) => {
+                let issues = self.api.verify_snapshots(path.as_deref()).await?;
+                if issues.is_empty() {
+                    self.writeln(TitleFormat::action("All snapshots verified"))?;
+                }
+                for issue in issues {
+                    self.writeln(TitleFormat::error(issue.to_string()))?;
+                }
+            }
+            Command::Snapshots(SnapshotCommand::Export { dest }) => {
+                let report = self.api.export_snapshots(&dest).await?;
+                self.writeln(
+                    TitleFormat::action(format!(
+                        "Exported {} snapshots ({} payloads)",
+                        report.snapshots, report.objects
+                    ))
+                    .sub_title(dest.display().to_string()),
+                )?;
+                for path in report.skipped {
+                    self.writeln(
+                        TitleFormat::info("Outside the working directory")
+                            .sub_title(path.display().to_string()),
+                    )?;
+                }
+            }
+            Command::Snapshots(SnapshotCommand::Import { src, strategy }) => {
+                let report = self.api.import_snapshots(&src, strategy).await?;
+                self.writeln(TitleFormat::action(format!(
+                    "Imported {} snapshots, {} skipped",
+                    report.imported, report.skipped
+                )))?;
             }
         }
 
         Ok(false)
     }
 
+    /// Drops the remaining steps of the running macro
+    fn stop_macro(&mut self) -> Result<()> {
+        if self.macro_steps.is_empty() {
+            return Ok(());
+        }
+
+        let skipped = self.macro_steps.len();
+        self.macro_steps.clear();
+        self.writeln(TitleFormat::info(format!(
+            "Stopped the macro, skipped {skipped} remaining steps"
+        )))
+    }
+
     async fn on_compaction(&mut self) -> Result<(), anyhow::Error> {
         let conversation_id = self.init_conversation().await?;
         let compaction_result = self.api.compact_conversation(&conversation_id).await?;
         let token_reduction = compaction_result.token_reduction_percentage();
         let message_reduction = compaction_result.message_reduction_percentage();
-        let content = TitleFormat::action(format!("Context size reduced by {token_reduction:.1}% (tokens), {message_reduction:.1}% (messages)"));
+        let content = TitleFormat::action(format!(
+            "Context size reduced by {token_reduction:.1}% (tokens), {message_reduction:.1}% (messages)"
+        ))
+        .sub_title(format!(
+            "{} → {} tokens, {} saved",
+            compaction_result.original_tokens,
+            compaction_result.compacted_tokens,
+            compaction_result.tokens_saved()
+        ));
         self.writeln(content)?;
         Ok(())
     }
 
+    async fn on_tokens(&mut self) -> Result<()> {
+        let conversation_id = self.init_conversation().await?;
+        let conversation = self
+            .api
+            .conversation(&conversation_id)
+            .await?
+            .context(format!("Conversation: {conversation_id} was not found"))?;
+
+        let model = match conversation.main_model() {
+            Ok(model) => model,
+            Err(_) => self.state.model.clone().context("No model selected")?,
+        };
+        let tokens = conversation
+            .context(&AgentId::new(Conversation::MAIN_AGENT_NAME))
+            .map_or(0, |context| token_counter(&model).estimate_tokens(context));
+        let context_length = self
+            .get_models()
+            .await?
+            .into_iter()
+            .find(|candidate| candidate.id == model)
+            .and_then(|candidate| candidate.context_length);
+
+        let usage = ContextUsage {
+            tokens: tokens as u64,
+            context_length,
+            session: self.state.session_usage.clone(),
+        };
+        self.writeln(Info::from(&usage))
+    }
+
     /// Select a model from the available models
     /// Returns Some(ModelId) if a model was selected, or None if selection was
     /// canceled
@@ -508,7 +695,9 @@ impl<F: API> UI<F> {
     /// Initialize the state of the UI
     async fn init_state(&mut self) -> Result<Workflow> {
         let mut workflow = self.api.read_workflow(self.cli.workflow.as_deref()).await?;
-        if workflow.model.is_none() {
+        let env = self.api.environment();
+        // A model set in the configuration saves asking for one
+        if workflow.model.is_none() && env.model.is_none() {
             workflow.model = Some(
                 self.select_model()
                     .await?
@@ -517,13 +706,14 @@ impl<F: API> UI<F> {
         }
         let mut base_workflow = Workflow::default();
         base_workflow.merge(workflow.clone());
+        base_workflow.model = base_workflow.model.or(env.model);
         on_update(self.api.clone(), base_workflow.updates.as_ref()).await;
         self.api
             .write_workflow(self.cli.workflow.as_deref(), &workflow)
             .await?;
 
         self.command.register_all(&base_workflow);
-        self.state = UIState::new(base_workflow).provider(self.api.environment().provider);
+        self.state = UIState::new(base_workflow).provider(env.provider);
 
         Ok(workflow)
     }
@@ -546,7 +736,7 @@ impl<F: API> UI<F> {
     }
 
     async fn on_chat(&mut self, chat: ChatRequest) -> Result<()> {
-        let mut stream = self.api.chat(chat).await?;
+        let mut stream = self.api.chat(chat).await?.monitored("chat");
 
         while let Some(message) = stream.next().await {
             match message {
@@ -612,9 +802,19 @@ impl<F: API> UI<F> {
                     self.writeln(text)?;
                 }
             }
+            ChatResponse::Refusal { reason } => {
+                self.writeln(TitleFormat {
+                    sub_title: reason,
+                    ..TitleFormat::error("The provider declined to answer (content policy)")
+                })?;
+            }
             ChatResponse::ToolCallStart(_) => {
                 self.spinner.stop(None)?;
             }
+            ChatResponse::ToolOutputChunk(line) => match line.stream {
+                OutputStream::Stdout => self.writeln(line.text)?,
+                OutputStream::Stderr => self.writeln(line.text.red())?,
+            },
             ChatResponse::ToolCallEnd(toolcall_result) => {
                 // Only track toolcall name in case of success else track the error.
                 let payload = if toolcall_result.is_error() {
@@ -629,12 +829,12 @@ impl<F: API> UI<F> {
                 tokio::spawn(TRACKER.dispatch(forge_tracker::EventKind::ToolCall(payload)));
 
                 self.spinner.start(None)?;
-                if !self.cli.verbose {
+                if !self.cli.verbose && !self.api.environment().verbose {
                     return Ok(());
                 }
             }
             ChatResponse::Usage(usage) => {
-                self.state.usage = usage;
+                self.state.record_usage(usage);
             }
         }
         Ok(())
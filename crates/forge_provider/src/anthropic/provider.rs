@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use anyhow::Context as _;
 use derive_builder::Builder;
 use forge_domain::{ChatCompletionMessage, Context, Model, ModelId, ResultStream};
@@ -10,6 +12,8 @@ use tracing::debug;
 use super::request::Request;
 use super::response::{EventData, ListModelResponse};
 use crate::error::Error;
+use crate::request_logger::{log_events, log_request, RequestLogger};
+use crate::retry::RetryAfter;
 use crate::utils::format_http_context;
 
 #[derive(Clone, Builder)]
@@ -18,6 +22,8 @@ pub struct Anthropic {
     api_key: String,
     base_url: Url,
     anthropic_version: String,
+    #[builder(default)]
+    request_logger: Option<Arc<dyn RequestLogger>>,
 }
 
 impl Anthropic {
@@ -25,6 +31,11 @@ impl Anthropic {
         AnthropicBuilder::default()
     }
 
+    pub fn with_request_logger(mut self, logger: Arc<dyn RequestLogger>) -> Self {
+        self.request_logger = Some(logger);
+        self
+    }
+
     fn url(&self, path: &str) -> anyhow::Result<Url> {
         // Validate the path doesn't contain certain patterns
         if path.contains("://") || path.contains("..") {
@@ -71,6 +82,7 @@ impl Anthropic {
 
         let url = self.url("/messages")?;
         debug!(url = %url, model = %model, "Connecting Upstream");
+        let started = log_request(self.request_logger.as_ref(), &request).await?;
         let es = self
             .client
             .post(url.clone())
@@ -79,7 +91,7 @@ impl Anthropic {
             .eventsource()
             .with_context(|| format_http_context(None, "POST", &url))?;
 
-        let stream = es
+        let stream = log_events(es, self.request_logger.clone(), started)
             .take_while(|message| !matches!(message, Err(reqwest_eventsource::Error::StreamEnded)))
             .then(|event| async {
                 match event {
@@ -106,17 +118,19 @@ impl Anthropic {
                         reqwest_eventsource::Error::StreamEnded => None,
                         reqwest_eventsource::Error::InvalidStatusCode(_, response) => {
                             let status = response.status();
+                            let error = RetryAfter::attach(
+                                Error::InvalidStatusCode(status.as_u16()).into(),
+                                response.headers(),
+                            );
                             let body = response.text().await.ok();
-                            Some(Err(Error::InvalidStatusCode(status.as_u16())).with_context(
-                                || match body {
-                                    Some(body) => {
-                                        format!("Invalid status code: {status} Reason: {body}")
-                                    }
-                                    None => {
-                                        format!("Invalid status code: {status} Reason: [Unknown]")
-                                    }
-                                },
-                            ))
+                            Some(Err(error).with_context(|| match body {
+                                Some(body) => {
+                                    format!("Invalid status code: {status} Reason: {body}")
+                                }
+                                None => {
+                                    format!("Invalid status code: {status} Reason: [Unknown]")
+                                }
+                            }))
                         }
                         reqwest_eventsource::Error::InvalidContentType(_, ref response) => {
                             let status_code = response.status();
@@ -159,28 +173,31 @@ impl Anthropic {
                     .with_context(|| ctx_msg)
                     .with_context(|| "Failed to fetch models")
             }
-            Ok(response) => match response.error_for_status() {
-                Ok(response) => {
-                    let ctx_msg = format_http_context(Some(response.status()), "GET", &url);
-                    match response.text().await {
-                        Ok(text) => {
-                            let response: ListModelResponse = serde_json::from_str(&text)
+            Ok(response) => {
+                let headers = response.headers().clone();
+                match response.error_for_status() {
+                    Ok(response) => {
+                        let ctx_msg = format_http_context(Some(response.status()), "GET", &url);
+                        match response.text().await {
+                            Ok(text) => {
+                                let response: ListModelResponse = serde_json::from_str(&text)
+                                    .with_context(|| ctx_msg)
+                                    .with_context(|| "Failed to deserialize models response")?;
+                                Ok(response.data.into_iter().map(Into::into).collect())
+                            }
+                            Err(err) => Err(err)
                                 .with_context(|| ctx_msg)
-                                .with_context(|| "Failed to deserialize models response")?;
-                            Ok(response.data.into_iter().map(Into::into).collect())
+                                .with_context(|| "Failed to decode response into text"),
                         }
-                        Err(err) => Err(err)
+                    }
+                    Err(err) => {
+                        let ctx_msg = format_http_context(err.status(), "GET", &url);
+                        Err(RetryAfter::attach(err.into(), &headers))
                             .with_context(|| ctx_msg)
-                            .with_context(|| "Failed to decode response into text"),
+                            .with_context(|| "Failed because of a non 200 status code".to_string())
                     }
                 }
-                Err(err) => {
-                    let ctx_msg = format_http_context(err.status(), "GET", &url);
-                    Err(err)
-                        .with_context(|| ctx_msg)
-                        .with_context(|| "Failed because of a non 200 status code".to_string())
-                }
-            },
+            }
         }
     }
 }
@@ -63,6 +63,8 @@ pub enum StopReason {
     MaxTokens,
     StopSequence,
     ToolUse,
+    /// Claude declined to answer for safety reasons
+    Refusal,
 }
 
 impl From<StopReason> for forge_domain::FinishReason {
@@ -72,6 +74,7 @@ impl From<StopReason> for forge_domain::FinishReason {
             StopReason::MaxTokens => forge_domain::FinishReason::Length,
             StopReason::StopSequence => forge_domain::FinishReason::Stop,
             StopReason::ToolUse => forge_domain::FinishReason::ToolCalls,
+            StopReason::Refusal => forge_domain::FinishReason::ContentFilter,
         }
     }
 }
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use derive_builder::Builder;
+use forge_domain::{ChatCompletionMessage, Context, Model, ModelId, ResultStream};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Client, Url};
+use reqwest_eventsource::RequestBuilderExt;
+use tracing::debug;
+
+use crate::forge_provider::{into_chat_stream, MakeOpenAiCompat, Request, Transformer};
+use crate::request_logger::{log_request, RequestLogger};
+use crate::utils::format_http_context;
+
+/// API version used unless the URL or [`Azure::with_api_version`] sets one
+pub const DEFAULT_API_VERSION: &str = "2024-02-01";
+
+/// Talks to an Azure OpenAI deployment. Requests follow the OpenAI format but
+/// are routed by deployment rather than model and authenticate with an
+/// `api-key` header.
+#[derive(Clone, Builder)]
+pub struct Azure {
+    client: Client,
+    /// Root of the Azure OpenAI resource
+    base_url: Url,
+    #[builder(default)]
+    api_key: Option<String>,
+    deployment: String,
+    #[builder(default = "DEFAULT_API_VERSION.to_string()")]
+    api_version: String,
+    #[builder(default)]
+    request_logger: Option<Arc<dyn RequestLogger>>,
+}
+
+/// Extracts the deployment name from a URL such as
+/// `https://{resource}.openai.azure.com/openai/deployments/{deployment}/...`
+pub(crate) fn deployment(url: &Url) -> Option<String> {
+    let mut segments = url.path_segments()?;
+    segments.find(|segment| *segment == "deployments")?;
+    segments
+        .next()
+        .filter(|deployment| !deployment.is_empty())
+        .map(str::to_string)
+}
+
+/// Reads the `api-version` query parameter of `url`
+pub(crate) fn api_version(url: &Url) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == "api-version")
+        .map(|(_, value)| value.into_owned())
+}
+
+impl Azure {
+    pub fn builder() -> AzureBuilder {
+        AzureBuilder::default()
+    }
+
+    pub fn with_request_logger(mut self, logger: Arc<dyn RequestLogger>) -> Self {
+        self.request_logger = Some(logger);
+        self
+    }
+
+    pub fn with_api_version(mut self, version: &str) -> Self {
+        self.api_version = version.to_string();
+        self
+    }
+
+    fn url(&self, path: &str) -> anyhow::Result<Url> {
+        let path = format!("openai/deployments/{}/{path}", self.deployment);
+        let mut url = self
+            .base_url
+            .join(&path)
+            .with_context(|| format!("Failed to append {} to base URL: {}", path, self.base_url))?;
+        url.query_pairs_mut()
+            .append_pair("api-version", &self.api_version);
+        Ok(url)
+    }
+
+    fn headers(&self) -> anyhow::Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = &self.api_key {
+            headers.insert("api-key", HeaderValue::from_str(api_key)?);
+        }
+        Ok(headers)
+    }
+
+    pub async fn chat(
+        &self,
+        model: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        let request = MakeOpenAiCompat.transform(
+            Request::from(context)
+                .model(ModelId::new(&self.deployment))
+                .stream(true),
+        );
+
+        let url = self.url("chat/completions")?;
+        debug!(url = %url, model = %model, "Connecting Upstream");
+
+        let started = log_request(self.request_logger.as_ref(), &request).await?;
+        let es = self
+            .client
+            .post(url.clone())
+            .headers(self.headers()?)
+            .json(&request)
+            .eventsource()
+            .with_context(|| format_http_context(None, "POST", &url))?;
+
+        Ok(Box::pin(into_chat_stream(
+            es,
+            self.request_logger.clone(),
+            started,
+            url,
+        )))
+    }
+
+    /// A deployment serves a single model, addressed by the deployment name
+    pub async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        Ok(vec![Model {
+            id: ModelId::new(&self.deployment),
+            name: Some(self.deployment.clone()),
+            description: None,
+            context_length: None,
+            tools_supported: Some(true),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio_stream::StreamExt;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[test]
+    fn test_deployment_and_api_version_from_url() {
+        let url = Url::parse(
+            "https://res.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-06-01",
+        )
+        .unwrap();
+
+        let actual = (deployment(&url), api_version(&url));
+
+        let expected = (Some("gpt-4o".to_string()), Some("2024-06-01".to_string()));
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_chat_targets_deployment_with_api_key() {
+        let server = MockServer::start().await;
+        let chunk = serde_json::json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4o",
+            "created": 0,
+            "object": "chat.completion.chunk",
+            "choices": [{"finish_reason": "stop", "delta": {"content": "Hello"}}]
+        });
+        Mock::given(method("POST"))
+            .and(path("/openai/deployments/gpt-4o/chat/completions"))
+            .and(query_param("api-version", "2024-06-01"))
+            .and(header("api-key", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                format!("data: {chunk}\n\ndata: [DONE]\n\n"),
+                "text/event-stream",
+            ))
+            .mount(&server)
+            .await;
+        let fixture = Azure::builder()
+            .client(Client::new())
+            .base_url(Url::parse(&server.uri()).unwrap())
+            .api_key(Some("test-key".to_string()))
+            .deployment("gpt-4o".to_string())
+            .build()
+            .unwrap()
+            .with_api_version("2024-06-01");
+
+        let actual = fixture
+            .chat(&ModelId::new("gpt-4o"), Context::default())
+            .await
+            .unwrap()
+            .map(|message| message.unwrap().content.unwrap().as_str().to_string())
+            .collect::<Vec<_>>()
+            .await;
+
+        let expected = vec!["Hello".to_string()];
+        assert_eq!(actual, expected);
+    }
+}
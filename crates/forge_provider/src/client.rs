@@ -4,27 +4,39 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::{Context as _, Result};
+use backon::Retryable;
 use forge_domain::{
     ChatCompletionMessage, Context, Model, ModelId, Provider, ProviderService, ResultStream,
 };
 use reqwest::redirect::Policy;
 use tokio::sync::RwLock;
 use tokio_stream::StreamExt;
+use tracing::warn;
 
 use crate::anthropic::Anthropic;
-use crate::forge_provider::ForgeProvider;
-use crate::retry::into_retry;
+use crate::azure::{self, Azure, DEFAULT_API_VERSION};
+use crate::cost::CostAccumulator;
+use crate::forge_provider::{ForgeProvider, ModelParams};
+use crate::groq::Groq;
+use crate::ollama::Ollama;
+use crate::request_logger::RequestLogger;
+use crate::retry::{get_retry_after, into_retry, should_retry, RetryConfig};
 
 #[derive(Clone)]
 pub struct Client {
     retry_status_codes: Arc<Vec<u16>>,
+    retry_config: Arc<RetryConfig>,
     inner: Arc<InnerClient>,
     models_cache: Arc<RwLock<HashMap<ModelId, Model>>>,
+    cost_accumulator: Option<Arc<CostAccumulator>>,
 }
 
 enum InnerClient {
     OpenAICompat(ForgeProvider),
     Anthropic(Anthropic),
+    Ollama(Ollama),
+    Azure(Azure),
+    Groq(Groq),
 }
 
 impl Client {
@@ -56,25 +68,182 @@ impl Client {
                         format!("Failed to initialize Anthropic client with URL: {url}")
                     })?,
             ),
+
+            Provider::Ollama { url } => InnerClient::Ollama(
+                Ollama::builder()
+                    .client(client)
+                    .base_url(url.clone())
+                    .build()
+                    .with_context(|| {
+                        format!("Failed to initialize Ollama client with URL: {url}")
+                    })?,
+            ),
+
+            Provider::Azure { url, key } => InnerClient::Azure(
+                Azure::builder()
+                    .client(client)
+                    .base_url(url.join("/")?)
+                    .api_key(key.clone())
+                    .deployment(azure::deployment(url).with_context(|| {
+                        format!("Azure OpenAI URL doesn't name a deployment: {url}")
+                    })?)
+                    .build()
+                    .with_context(|| {
+                        format!("Failed to initialize Azure OpenAI client with URL: {url}")
+                    })?
+                    .with_api_version(
+                        &azure::api_version(url).unwrap_or(DEFAULT_API_VERSION.to_string()),
+                    ),
+            ),
+
+            Provider::Groq { url, key } => InnerClient::Groq(
+                Groq::builder()
+                    .client(client)
+                    .base_url(url.clone())
+                    .api_key(key.clone())
+                    .build()
+                    .with_context(|| format!("Failed to initialize Groq client with URL: {url}"))?,
+            ),
         };
 
         Ok(Self {
             inner: Arc::new(inner),
             retry_status_codes: Arc::new(retry_status_codes),
+            retry_config: Arc::new(RetryConfig::default()),
             models_cache: Arc::new(RwLock::new(HashMap::new())),
+            cost_accumulator: None,
         })
     }
 
+    /// Logs the body of every chat request and the events streamed back
+    pub fn with_request_logger(mut self, logger: Arc<dyn RequestLogger>) -> Self {
+        let inner = match self.inner.as_ref() {
+            InnerClient::OpenAICompat(provider) => {
+                InnerClient::OpenAICompat(provider.clone().with_request_logger(logger))
+            }
+            InnerClient::Anthropic(provider) => {
+                InnerClient::Anthropic(provider.clone().with_request_logger(logger))
+            }
+            InnerClient::Ollama(provider) => {
+                InnerClient::Ollama(provider.clone().with_request_logger(logger))
+            }
+            InnerClient::Azure(provider) => {
+                InnerClient::Azure(provider.clone().with_request_logger(logger))
+            }
+            InnerClient::Groq(provider) => {
+                InnerClient::Groq(provider.clone().with_request_logger(logger))
+            }
+        };
+        self.inner = Arc::new(inner);
+        self
+    }
+
+    /// Tracks the cost of chat requests using the pricing the provider
+    /// publishes with its models. Only OpenAI compatible providers that price
+    /// their models, such as OpenRouter, report a cost.
+    pub fn with_cost_accumulator(mut self, accumulator: Arc<CostAccumulator>) -> Self {
+        if let InnerClient::OpenAICompat(provider) = self.inner.as_ref() {
+            self.inner = Arc::new(InnerClient::OpenAICompat(
+                provider.clone().with_cost_accumulator(accumulator.clone()),
+            ));
+        }
+        self.cost_accumulator = Some(accumulator);
+        self
+    }
+
+    /// Total cost in USD of the chat requests made since cost tracking was
+    /// enabled
+    pub fn cost_so_far(&self) -> f64 {
+        self.cost_accumulator
+            .as_ref()
+            .map_or(0.0, |accumulator| accumulator.total())
+    }
+
+    /// Drops the parameters of requests to the models matching the regex
+    /// `pattern` as described by `params`. Only applies to OpenAI compatible
+    /// providers, such as OpenRouter.
+    pub fn with_model_params(mut self, pattern: &str, params: ModelParams) -> Result<Self> {
+        if let InnerClient::OpenAICompat(provider) = self.inner.as_ref() {
+            self.inner = Arc::new(InnerClient::OpenAICompat(
+                provider.clone().with_model_params(pattern, params)?,
+            ));
+        }
+        Ok(self)
+    }
+
+    /// Overrides how requests failing with a rate limit or server error are
+    /// retried
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry_config = Arc::new(config);
+        self
+    }
+
+    /// Runs the request, attempting it again with exponential backoff while
+    /// it fails with one of the retry status codes or a transport error. A
+    /// delay requested by the server through `Retry-After` takes precedence
+    /// over the backoff, up to the maximum delay of the retry config.
+    ///
+    /// Errors left once the attempts run out aren't marked retryable, so that
+    /// the orchestrator doesn't retry them again.
+    async fn with_backoff<A, F, Fut>(&self, request: F) -> anyhow::Result<A>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<A>>,
+    {
+        let max_delay = self.retry_config.max_delay;
+        let codes = self.retry_status_codes.clone();
+        request
+            .retry(self.retry_config.backoff())
+            .when(move |error| should_retry(error, &codes))
+            .adjust(move |error, delay| {
+                delay.map(|delay| {
+                    get_retry_after(error).map_or(delay, |retry_after| retry_after.min(max_delay))
+                })
+            })
+            .notify(|error, delay| {
+                warn!(error = %error, delay_ms = delay.as_millis() as u64, "Retrying request");
+            })
+            .await
+    }
+
+    async fn inner_chat(
+        &self,
+        model: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        let mut stream = match self.inner.as_ref() {
+            InnerClient::OpenAICompat(provider) => provider.chat(model, context).await,
+            InnerClient::Anthropic(provider) => provider.chat(model, context).await,
+            InnerClient::Ollama(provider) => provider.chat(model, context).await,
+            InnerClient::Azure(provider) => provider.chat(model, context).await,
+            InnerClient::Groq(provider) => provider.chat(model, context).await,
+        }?;
+
+        // The request is only sent once the stream is polled, so a failed status
+        // surfaces as the first item
+        match stream.next().await {
+            Some(Err(error)) => Err(error),
+            first => Ok(Box::pin(tokio_stream::iter(first).chain(stream))),
+        }
+    }
+
     fn retry<A>(&self, result: anyhow::Result<A>) -> anyhow::Result<A> {
         let codes = &self.retry_status_codes;
         result.map_err(move |e| into_retry(e, codes))
     }
 
     pub async fn refresh_models(&self) -> anyhow::Result<Vec<Model>> {
-        let models = self.clone().retry(match self.inner.as_ref() {
-            InnerClient::OpenAICompat(provider) => provider.models().await,
-            InnerClient::Anthropic(provider) => provider.models().await,
-        })?;
+        let models = self
+            .with_backoff(|| async {
+                match self.inner.as_ref() {
+                    InnerClient::OpenAICompat(provider) => provider.models().await,
+                    InnerClient::Anthropic(provider) => provider.models().await,
+                    InnerClient::Ollama(provider) => provider.models().await,
+                    InnerClient::Azure(provider) => provider.models().await,
+                    InnerClient::Groq(provider) => provider.models().await,
+                }
+            })
+            .await?;
 
         // Update the cache with all fetched models
         {
@@ -96,11 +265,12 @@ impl ProviderService for Client {
         model: &ModelId,
         context: Context,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
-        let chat_stream = self.clone().retry(match self.inner.as_ref() {
-            InnerClient::OpenAICompat(provider) => provider.chat(model, context).await,
-            InnerClient::Anthropic(provider) => provider.chat(model, context).await,
-        })?;
+        let chat_stream = self
+            .with_backoff(|| self.inner_chat(model, context.clone()))
+            .await?;
 
+        // The client can't resume a stream that fails midway, so those errors
+        // are left for the orchestrator to retry
         let this = self.clone();
         Ok(Box::pin(
             chat_stream.map(move |item| this.clone().retry(item)),
@@ -129,8 +299,13 @@ impl ProviderService for Client {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use forge_domain::Provider;
+    use pretty_assertions::assert_eq;
     use reqwest::Url;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
 
@@ -161,4 +336,237 @@ mod tests {
         assert!(result.is_err()); // Expected to fail since we're not hitting a
                                   // real API
     }
+
+    #[tokio::test]
+    async fn test_chat_retries_rate_limited_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        let chunk = serde_json::json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4o",
+            "created": 0,
+            "object": "chat.completion.chunk",
+            "choices": [{"finish_reason": "stop", "delta": {"content": "Hello"}}]
+        });
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                format!("data: {chunk}\n\ndata: [DONE]\n\n"),
+                "text/event-stream",
+            ))
+            .mount(&server)
+            .await;
+        let provider = Provider::OpenAI {
+            url: Url::parse(&format!("{}/", server.uri())).unwrap(),
+            key: Some("test-key".to_string()),
+        };
+        let client = Client::new(provider, vec![429]).unwrap().with_retry_config(
+            RetryConfig::default()
+                .base_delay(Duration::from_millis(1))
+                .jitter(false),
+        );
+
+        let messages = client
+            .chat(&ModelId::new("gpt-4o"), Context::default())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+
+        let actual = messages
+            .into_iter()
+            .filter_map(|message| message.unwrap().content)
+            .map(|content| content.as_str().to_string())
+            .collect::<Vec<_>>();
+        let expected = vec!["Hello".to_string()];
+        assert_eq!(actual, expected);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_chat_exhausted_retries_are_not_retried_again() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .mount(&server)
+            .await;
+        let provider = Provider::OpenAI {
+            url: Url::parse(&format!("{}/", server.uri())).unwrap(),
+            key: Some("test-key".to_string()),
+        };
+        let client = Client::new(provider, vec![429]).unwrap().with_retry_config(
+            RetryConfig::default()
+                .max_attempts(2u32)
+                .base_delay(Duration::from_millis(1))
+                .jitter(false),
+        );
+
+        let error = match client
+            .chat(&ModelId::new("gpt-4o"), Context::default())
+            .await
+        {
+            Ok(_) => panic!("expected the request to fail"),
+            Err(error) => error,
+        };
+
+        let actual = matches!(
+            error.downcast_ref::<forge_domain::Error>(),
+            Some(forge_domain::Error::Retryable(_))
+        );
+        assert!(!actual, "{error:#}");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_retry_config_from_environment() {
+        let fixture = forge_domain::RetryConfig::default()
+            .initial_backoff_ms(200u64)
+            .backoff_factor(3u64)
+            .max_retry_attempts(8usize);
+
+        let actual = RetryConfig::from(&fixture);
+
+        let expected = RetryConfig::default()
+            .max_attempts(9u32)
+            .base_delay(Duration::from_millis(200))
+            .factor(3.0);
+        assert_eq!(actual, expected);
+    }
+
+    /// Starts a server that rate limits the first chat request, asking to
+    /// retry after `retry_after`, and answers the next ones
+    async fn rate_limited_server(retry_after: &str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", retry_after))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        let chunk = serde_json::json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4o",
+            "created": 0,
+            "object": "chat.completion.chunk",
+            "choices": [{"finish_reason": "stop", "delta": {"content": "Hello"}}]
+        });
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                format!("data: {chunk}\n\ndata: [DONE]\n\n"),
+                "text/event-stream",
+            ))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    /// Sends a chat request, returning how long it took to succeed
+    async fn timed_chat(server: &MockServer, config: RetryConfig) -> Duration {
+        let provider = Provider::OpenAI {
+            url: Url::parse(&format!("{}/", server.uri())).unwrap(),
+            key: Some("test-key".to_string()),
+        };
+        let client = Client::new(provider, vec![429])
+            .unwrap()
+            .with_retry_config(config);
+
+        let start = std::time::Instant::now();
+        client
+            .chat(&ModelId::new("gpt-4o"), Context::default())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        start.elapsed()
+    }
+
+    #[tokio::test]
+    async fn test_chat_waits_for_retry_after() {
+        let server = rate_limited_server("1").await;
+        let config = RetryConfig::default()
+            .base_delay(Duration::from_millis(1))
+            .jitter(false);
+
+        let actual = timed_chat(&server, config).await;
+
+        assert!(actual >= Duration::from_secs(1), "retried after {actual:?}");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_chat_caps_retry_after() {
+        let server = rate_limited_server("3600").await;
+        let config = RetryConfig::default()
+            .base_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(50))
+            .jitter(false);
+
+        let actual = timed_chat(&server, config).await;
+
+        assert!(actual < Duration::from_secs(60), "retried after {actual:?}");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_chat_tracks_cost_from_model_pricing() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"id": "gpt-4o", "pricing": {"prompt": "0.25", "completion": "0.5"}}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let chunk = serde_json::json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4o",
+            "created": 0,
+            "object": "chat.completion.chunk",
+            "choices": [{"finish_reason": "stop", "delta": {"content": "Hello"}}],
+            "usage": {"prompt_tokens": 4, "completion_tokens": 2, "total_tokens": 6}
+        });
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                format!("data: {chunk}\n\ndata: [DONE]\n\n"),
+                "text/event-stream",
+            ))
+            .mount(&server)
+            .await;
+        let provider = Provider::OpenAI {
+            url: Url::parse(&format!("{}/", server.uri())).unwrap(),
+            key: Some("test-key".to_string()),
+        };
+        let client = Client::new(provider, vec![])
+            .unwrap()
+            .with_cost_accumulator(Arc::new(CostAccumulator::new()));
+
+        let mut costs = Vec::new();
+        for _ in 0..2 {
+            let messages = client
+                .chat(&ModelId::new("gpt-4o"), Context::default())
+                .await
+                .unwrap()
+                .collect::<Vec<_>>()
+                .await;
+            costs.extend(
+                messages
+                    .into_iter()
+                    .filter_map(|message| message.unwrap().usage?.cost),
+            );
+        }
+
+        assert_eq!(costs, vec![2.0, 2.0]);
+        assert_eq!(client.cost_so_far(), 4.0);
+    }
 }
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use forge_domain::{ModelId, Usage};
+use tokio::sync::RwLock;
+
+use crate::forge_provider::{Model, Pricing};
+
+/// How long the pricing fetched from `/models` is used before it's refreshed
+pub const PRICING_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Price of a model in USD
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Price {
+    prompt: f64,
+    completion: f64,
+    request: f64,
+}
+
+impl Price {
+    /// Parses the per token prices, which OpenRouter sends as decimal strings
+    fn from_pricing(pricing: &Pricing) -> Self {
+        let parse = |price: &Option<String>| {
+            price
+                .as_deref()
+                .and_then(|price| price.parse::<f64>().ok())
+                .unwrap_or_default()
+        };
+        Self {
+            prompt: parse(&pricing.prompt),
+            completion: parse(&pricing.completion),
+            request: parse(&pricing.request),
+        }
+    }
+
+    fn cost(&self, usage: &Usage) -> f64 {
+        self.prompt * usage.prompt_tokens as f64
+            + self.completion * usage.completion_tokens as f64
+            + self.request
+    }
+}
+
+#[derive(Default)]
+struct PricingCache {
+    fetched_at: Option<Instant>,
+    prices: HashMap<ModelId, Price>,
+}
+
+/// Keeps a running total, in USD, of what the chat requests of a provider
+/// cost. The cost reported by the provider with the usage of a response is
+/// used when present, otherwise it's computed from the model's pricing.
+#[derive(Default)]
+pub struct CostAccumulator {
+    pricing: RwLock<PricingCache>,
+    total: Mutex<f64>,
+}
+
+impl CostAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the pricing has never been fetched or is older than
+    /// [`PRICING_TTL`]
+    pub(crate) async fn is_stale(&self) -> bool {
+        self.pricing
+            .read()
+            .await
+            .fetched_at
+            .is_none_or(|fetched_at| fetched_at.elapsed() >= PRICING_TTL)
+    }
+
+    pub(crate) async fn update_pricing(&self, models: &[Model]) {
+        let prices = models
+            .iter()
+            .filter_map(|model| {
+                let pricing = model.pricing.as_ref()?;
+                Some((model.id.clone(), Price::from_pricing(pricing)))
+            })
+            .collect();
+        *self.pricing.write().await = PricingCache { fetched_at: Some(Instant::now()), prices };
+    }
+
+    pub(crate) async fn price(&self, model: &ModelId) -> Option<Price> {
+        self.pricing.read().await.prices.get(model).copied()
+    }
+
+    /// Fills in the cost of `usage` when the provider didn't report it and
+    /// adds it to the total
+    pub(crate) fn record(&self, price: Option<Price>, usage: &mut Usage) {
+        if usage.cost.is_none() {
+            usage.cost = price.map(|price| price.cost(usage));
+        }
+        if let Some(cost) = usage.cost {
+            *self.total.lock().unwrap() += cost;
+        }
+    }
+
+    /// Total cost in USD of the requests recorded so far
+    pub fn total(&self) -> f64 {
+        *self.total.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn model(id: &str, prompt: &str, completion: &str) -> Model {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "pricing": {"prompt": prompt, "completion": completion, "request": "0"}
+        }))
+        .unwrap()
+    }
+
+    fn usage(prompt_tokens: u64, completion_tokens: u64) -> Usage {
+        Usage { prompt_tokens, completion_tokens, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_record_prices_usage_and_accumulates() {
+        let fixture = CostAccumulator::new();
+        assert!(fixture.is_stale().await);
+        fixture
+            .update_pricing(&[model("gpt-4o", "0.25", "0.5")])
+            .await;
+        let price = fixture.price(&ModelId::new("gpt-4o")).await;
+
+        let mut first = usage(4, 2);
+        let mut second = usage(2, 0);
+        fixture.record(price, &mut first);
+        fixture.record(price, &mut second);
+
+        assert!(!fixture.is_stale().await);
+        assert_eq!(first.cost, Some(2.0));
+        assert_eq!(fixture.total(), 2.5);
+    }
+
+    #[tokio::test]
+    async fn test_record_prefers_reported_cost() {
+        let fixture = CostAccumulator::new();
+        fixture
+            .update_pricing(&[model("gpt-4o", "0.25", "0.5")])
+            .await;
+        let price = fixture.price(&ModelId::new("gpt-4o")).await;
+
+        let mut reported = usage(4, 2);
+        reported.cost = Some(0.5);
+        let mut unpriced = usage(4, 2);
+        fixture.record(price, &mut reported);
+        fixture.record(None, &mut unpriced);
+
+        assert_eq!(unpriced.cost, None);
+        assert_eq!(fixture.total(), 0.5);
+    }
+}
@@ -24,6 +24,10 @@ pub enum Error {
 
     #[error("Invalid Status Code: {0}")]
     InvalidStatusCode(u16),
+
+    #[error("{0}")]
+    #[from(skip)]
+    Ollama(String),
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -0,0 +1,226 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Context as _;
+use forge_domain::{
+    ChatCompletionMessage, Context, FallbackStrategy, Model, ModelId, ProviderService, ResultStream,
+};
+use futures::future::{select_ok, BoxFuture};
+use futures::FutureExt;
+use tokio_stream::StreamExt;
+use tracing::warn;
+
+use crate::retry::is_unavailable;
+
+/// Serves requests from a list of providers so that an outage of one of them,
+/// such as a rate limit or a server error, is transparently covered by the
+/// others. Errors caused by the request itself, such as an invalid key, are
+/// returned immediately.
+pub struct FallbackProvider {
+    providers: Vec<Box<dyn ProviderService>>,
+    strategy: FallbackStrategy,
+    next: AtomicUsize,
+}
+
+impl FallbackProvider {
+    pub fn new(providers: Vec<Box<dyn ProviderService>>, strategy: FallbackStrategy) -> Self {
+        Self { providers, strategy, next: AtomicUsize::new(0) }
+    }
+
+    /// Indices of the providers in the order they should be tried
+    fn order(&self) -> Vec<usize> {
+        let count = self.providers.len();
+        let start = match self.strategy {
+            FallbackStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % count,
+            FallbackStrategy::FirstSuccess | FallbackStrategy::Fastest => 0,
+        };
+        (0..count).map(|offset| (start + offset) % count).collect()
+    }
+
+    /// Runs `request` against the providers in order until one succeeds or
+    /// fails with an error that isn't caused by the provider being
+    /// unavailable
+    async fn first_success<'a, A, F>(&'a self, request: F) -> anyhow::Result<A>
+    where
+        F: Fn(&'a dyn ProviderService) -> BoxFuture<'a, anyhow::Result<A>>,
+    {
+        if self.providers.is_empty() {
+            anyhow::bail!("No providers configured for fallback");
+        }
+
+        let mut last_error = None;
+        for index in self.order() {
+            match request(self.providers[index].as_ref()).await {
+                Ok(result) => return Ok(result),
+                Err(error) if is_unavailable(&error) => {
+                    warn!(provider = index, error = %error, "Provider unavailable, falling back");
+                    last_error = Some(error);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| anyhow::anyhow!("No providers configured for fallback"))
+            .context("All providers are unavailable"))
+    }
+}
+
+/// Starts the chat and waits for its first message, so that providers failing
+/// only once the stream is polled are detected before any output is used
+async fn chat(
+    provider: &dyn ProviderService,
+    model: &ModelId,
+    context: Context,
+) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+    let mut stream = provider.chat(model, context).await?;
+    match stream.next().await {
+        Some(Err(error)) => Err(error),
+        first => Ok(Box::pin(tokio_stream::iter(first).chain(stream))),
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderService for FallbackProvider {
+    async fn chat(
+        &self,
+        model: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        match self.strategy {
+            FallbackStrategy::Fastest => {
+                let requests = self
+                    .providers
+                    .iter()
+                    .map(|provider| chat(provider.as_ref(), model, context.clone()).boxed());
+                let (stream, _) = select_ok(requests).await.context("All providers failed")?;
+                Ok(stream)
+            }
+            FallbackStrategy::FirstSuccess | FallbackStrategy::RoundRobin => {
+                self.first_success(|provider| chat(provider, model, context.clone()).boxed())
+                    .await
+            }
+        }
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        self.first_success(|provider| provider.models()).await
+    }
+
+    async fn model(&self, model: &ModelId) -> anyhow::Result<Option<Model>> {
+        self.first_success(|provider| provider.model(model)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use forge_domain::Content;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::error::Error;
+
+    /// Answers every chat with its name, or fails with the given status code
+    struct MockProvider {
+        name: &'static str,
+        status: Option<u16>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl MockProvider {
+        fn boxed(
+            name: &'static str,
+            status: Option<u16>,
+        ) -> (Box<dyn ProviderService>, Arc<AtomicUsize>) {
+            let calls = Arc::new(AtomicUsize::new(0));
+            (Box::new(Self { name, status, calls: calls.clone() }), calls)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderService for MockProvider {
+        async fn chat(
+            &self,
+            _: &ModelId,
+            _: Context,
+        ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            match self.status {
+                Some(code) => Err(Error::InvalidStatusCode(code).into()),
+                None => Ok(Box::pin(tokio_stream::iter([Ok(
+                    ChatCompletionMessage::assistant(Content::full(self.name)),
+                )]))),
+            }
+        }
+
+        async fn models(&self) -> anyhow::Result<Vec<Model>> {
+            Ok(vec![])
+        }
+
+        async fn model(&self, _: &ModelId) -> anyhow::Result<Option<Model>> {
+            Ok(None)
+        }
+    }
+
+    async fn answer(provider: &FallbackProvider) -> anyhow::Result<String> {
+        let message = provider
+            .chat(&ModelId::new("gpt-4o"), Context::default())
+            .await?
+            .next()
+            .await
+            .unwrap()?;
+        Ok(message.content.unwrap().as_str().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_first_success_falls_back_on_unavailable_provider() {
+        let (primary, _) = MockProvider::boxed("primary", Some(503));
+        let (secondary, _) = MockProvider::boxed("secondary", None);
+        let fixture =
+            FallbackProvider::new(vec![primary, secondary], FallbackStrategy::FirstSuccess);
+
+        let actual = answer(&fixture).await.unwrap();
+
+        assert_eq!(actual, "secondary");
+    }
+
+    #[tokio::test]
+    async fn test_first_success_propagates_request_errors() {
+        let (primary, _) = MockProvider::boxed("primary", Some(400));
+        let (secondary, calls) = MockProvider::boxed("secondary", None);
+        let fixture =
+            FallbackProvider::new(vec![primary, secondary], FallbackStrategy::FirstSuccess);
+
+        let actual = answer(&fixture).await.unwrap_err();
+
+        assert_eq!(actual.to_string(), "Invalid Status Code: 400");
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_rotates_providers() {
+        let (first, _) = MockProvider::boxed("first", None);
+        let (second, _) = MockProvider::boxed("second", None);
+        let fixture = FallbackProvider::new(vec![first, second], FallbackStrategy::RoundRobin);
+
+        let actual = vec![
+            answer(&fixture).await.unwrap(),
+            answer(&fixture).await.unwrap(),
+            answer(&fixture).await.unwrap(),
+        ];
+
+        assert_eq!(actual, vec!["first", "second", "first"]);
+    }
+
+    #[tokio::test]
+    async fn test_fastest_skips_failing_providers() {
+        let (primary, _) = MockProvider::boxed("primary", Some(429));
+        let (secondary, _) = MockProvider::boxed("secondary", None);
+        let fixture = FallbackProvider::new(vec![primary, secondary], FallbackStrategy::Fastest);
+
+        let actual = answer(&fixture).await.unwrap();
+
+        assert_eq!(actual, "secondary");
+    }
+}
@@ -1,4 +1,5 @@
 mod model;
+mod model_params;
 mod parameters;
 mod request;
 mod response;
@@ -6,4 +7,10 @@ mod tool_choice;
 mod transformers;
 
 mod provider;
+pub(crate) use model::{Model, Pricing};
+pub use model_params::{ModelParams, Param};
+pub(crate) use provider::into_chat_stream;
 pub use provider::ForgeProvider;
+pub(crate) use request::Request;
+pub(crate) use response::Response;
+pub(crate) use transformers::{DropToolCalls, MakeOpenAiCompat, Transformer};
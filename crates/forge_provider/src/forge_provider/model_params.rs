@@ -0,0 +1,156 @@
+use anyhow::Context as _;
+use derive_setters::Setters;
+use forge_domain::ModelId;
+use regex::Regex;
+
+use crate::forge_provider::request::Request;
+
+/// A sampling parameter of a chat request that some models reject
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    Temperature,
+    TopP,
+    TopK,
+    FrequencyPenalty,
+    PresencePenalty,
+    RepetitionPenalty,
+    Seed,
+    MinP,
+    TopA,
+}
+
+impl Param {
+    /// Removes the parameter from `request`
+    pub(crate) fn clear(&self, request: &mut Request) {
+        match self {
+            Param::Temperature => request.temperature = None,
+            Param::TopP => request.top_p = None,
+            Param::TopK => request.top_k = None,
+            Param::FrequencyPenalty => request.frequency_penalty = None,
+            Param::PresencePenalty => request.presence_penalty = None,
+            Param::RepetitionPenalty => request.repetition_penalty = None,
+            Param::Seed => request.seed = None,
+            Param::MinP => request.min_p = None,
+            Param::TopA => request.top_a = None,
+        }
+    }
+}
+
+/// The parameters a model rejects, and the range it accepts for the others
+#[derive(Debug, Clone, Default, PartialEq, Setters)]
+#[setters(strip_option)]
+pub struct ModelParams {
+    /// Parameters removed from requests to the model
+    #[setters(skip)]
+    pub unsupported: Vec<Param>,
+    /// Highest temperature the model accepts, higher values are lowered to it
+    pub max_temperature: Option<f32>,
+}
+
+impl ModelParams {
+    pub fn unsupported(mut self, param: Param) -> Self {
+        self.unsupported.push(param);
+        self
+    }
+}
+
+/// Parameter restrictions of the models whose id matches a pattern. The
+/// restrictions of the first matching pattern apply.
+#[derive(Debug, Clone)]
+pub struct ModelParamsTable(Vec<(Regex, ModelParams)>);
+
+impl ModelParamsTable {
+    pub fn empty() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Adds the restrictions of the models matching the regex `pattern`,
+    /// taking precedence over the ones already in the table
+    pub fn insert(&mut self, pattern: &str, params: ModelParams) -> anyhow::Result<()> {
+        let regex =
+            Regex::new(pattern).with_context(|| format!("Invalid model pattern: {pattern}"))?;
+        self.0.insert(0, (regex, params));
+        Ok(())
+    }
+
+    pub fn get(&self, model: &ModelId) -> Option<&ModelParams> {
+        self.0
+            .iter()
+            .find(|(regex, _)| regex.is_match(model.as_str()))
+            .map(|(_, params)| params)
+    }
+}
+
+impl Default for ModelParamsTable {
+    /// Restrictions of known models that fail requests with a parameter they
+    /// don't support
+    fn default() -> Self {
+        let mut table = Self::empty();
+        let known = [
+            // OpenAI reasoning models only accept the default sampling
+            (
+                r"(^|/)o[134](-|$)",
+                ModelParams::default()
+                    .unsupported(Param::Temperature)
+                    .unsupported(Param::TopP)
+                    .unsupported(Param::FrequencyPenalty)
+                    .unsupported(Param::PresencePenalty),
+            ),
+            // Anthropic accepts temperatures up to 1.0 and has no penalties
+            (
+                r"anthropic/|claude",
+                ModelParams::default()
+                    .max_temperature(1.0)
+                    .unsupported(Param::FrequencyPenalty)
+                    .unsupported(Param::PresencePenalty),
+            ),
+        ];
+        for (pattern, params) in known {
+            table
+                .insert(pattern, params)
+                .expect("Known model patterns are valid");
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_get_matches_known_models() {
+        let fixture = ModelParamsTable::default();
+
+        let actual = [
+            "anthropic/claude-3.7-sonnet",
+            "openai/o3-mini",
+            "openai/gpt-4o",
+        ]
+        .map(|model| fixture.get(&ModelId::new(model)).is_some());
+
+        assert_eq!(actual, [true, true, false]);
+    }
+
+    #[test]
+    fn test_insert_takes_precedence() {
+        let mut fixture = ModelParamsTable::default();
+        let params = ModelParams::default().unsupported(Param::TopK);
+        fixture.insert("claude-3.7", params.clone()).unwrap();
+
+        let actual = fixture.get(&ModelId::new("anthropic/claude-3.7-sonnet"));
+
+        assert_eq!(actual, Some(&params));
+    }
+
+    #[test]
+    fn test_insert_rejects_invalid_pattern() {
+        let mut fixture = ModelParamsTable::empty();
+
+        let actual = fixture.insert("claude(", ModelParams::default());
+
+        assert!(actual.is_err());
+    }
+}
@@ -1,25 +1,38 @@
+use std::sync::Arc;
+use std::time::Instant;
+
 use anyhow::{Context as _, Result};
 use derive_builder::Builder;
 use forge_domain::{
-    self, ChatCompletionMessage, Context as ChatContext, ModelId, Provider, ResultStream,
+    self, ChatCompletionMessage, Context as ChatContext, ModelId, Provider, RequestId, ResultStream,
 };
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use reqwest::{Client, Url};
-use reqwest_eventsource::{Event, RequestBuilderExt};
-use tokio_stream::StreamExt;
-use tracing::debug;
+use reqwest_eventsource::{Event, EventSource, RequestBuilderExt};
+use tokio_stream::{Stream, StreamExt};
+use tracing::{debug, warn};
 
 use super::model::{ListModelResponse, Model};
+use super::model_params::{ModelParams, ModelParamsTable};
 use super::request::Request;
 use super::response::Response;
+use crate::cost::CostAccumulator;
 use crate::error::Error;
 use crate::forge_provider::transformers::{ProviderPipeline, Transformer};
+use crate::request_logger::{log_events, log_request, RequestLogger};
+use crate::retry::RetryAfter;
 use crate::utils::format_http_context;
 
 #[derive(Clone, Builder)]
 pub struct ForgeProvider {
     client: Client,
     provider: Provider,
+    #[builder(default)]
+    request_logger: Option<Arc<dyn RequestLogger>>,
+    #[builder(default)]
+    cost_accumulator: Option<Arc<CostAccumulator>>,
+    #[builder(default)]
+    model_params: ModelParamsTable,
 }
 
 impl ForgeProvider {
@@ -27,6 +40,25 @@ impl ForgeProvider {
         ForgeProviderBuilder::default()
     }
 
+    pub fn with_request_logger(mut self, logger: Arc<dyn RequestLogger>) -> Self {
+        self.request_logger = Some(logger);
+        self
+    }
+
+    /// Prices the usage of every chat response and adds it to `accumulator`
+    pub fn with_cost_accumulator(mut self, accumulator: Arc<CostAccumulator>) -> Self {
+        self.cost_accumulator = Some(accumulator);
+        self
+    }
+
+    /// Drops the parameters of requests to the models matching the regex
+    /// `pattern` as described by `params`, in addition to the restrictions of
+    /// known models
+    pub fn with_model_params(mut self, pattern: &str, params: ModelParams) -> Result<Self> {
+        self.model_params.insert(pattern, params)?;
+        Ok(self)
+    }
+
     fn url(&self, path: &str) -> anyhow::Result<Url> {
         // Validate the path doesn't contain certain patterns
         if path.contains("://") || path.contains("..") {
@@ -62,6 +94,13 @@ impl ForgeProvider {
             reqwest::header::CONNECTION,
             HeaderValue::from_static("keep-alive"),
         );
+        // Lets the provider's logs be matched with the request that caused them
+        if let Some(request_id) = RequestId::current() {
+            headers.insert(
+                RequestId::HEADER,
+                HeaderValue::from_str(&request_id.to_string()).unwrap(),
+            );
+        }
         headers
     }
 
@@ -71,7 +110,7 @@ impl ForgeProvider {
         context: ChatContext,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
         let mut request = Request::from(context).model(model.clone()).stream(true);
-        request = ProviderPipeline::new(&self.provider).transform(request);
+        request = ProviderPipeline::new(&self.provider, &self.model_params).transform(request);
 
         let url = self.url("chat/completions")?;
 
@@ -83,6 +122,15 @@ impl ForgeProvider {
             "Connecting Upstream"
         );
 
+        let price = match &self.cost_accumulator {
+            Some(accumulator) => {
+                self.refresh_pricing(accumulator).await;
+                accumulator.price(model).await
+            }
+            None => None,
+        };
+
+        let started = log_request(self.request_logger.as_ref(), &request).await?;
         let es = self
             .client
             .post(url.clone())
@@ -91,73 +139,41 @@ impl ForgeProvider {
             .eventsource()
             .with_context(|| format_http_context(None, "POST", &url))?;
 
-        let stream = es
-            .take_while(|message| !matches!(message, Err(reqwest_eventsource::Error::StreamEnded)))
-            .then(|event| async {
-                match event {
-                    Ok(event) => match event {
-                        Event::Open => None,
-                        Event::Message(event) if ["[DONE]", ""].contains(&event.data.as_str()) => {
-                            debug!("Received completion from Upstream");
-                            None
-                        }
-                        Event::Message(message) => Some(
-                            serde_json::from_str::<Response>(&message.data)
-                                .with_context(|| {
-                                    format!(
-                                        "Failed to parse Forge Provider response: {}",
-                                        message.data
-                                    )
-                                })
-                                .and_then(|response| {
-                                    ChatCompletionMessage::try_from(response.clone()).with_context(
-                                        || {
-                                            format!(
-                                                "Failed to create completion message: {}",
-                                                message.data
-                                            )
-                                        },
-                                    )
-                                }),
-                        ),
-                    },
-                    Err(error) => match error {
-                        reqwest_eventsource::Error::StreamEnded => None,
-                        reqwest_eventsource::Error::InvalidStatusCode(_, response) => {
-                            let status = response.status();
-                            let body = response.text().await.ok();
-                            Some(Err(Error::InvalidStatusCode(status.as_u16())).with_context(
-                                || match body {
-                                    Some(body) => {
-                                        format!("{status} Reason: {body}")
-                                    }
-                                    None => {
-                                        format!("{status} Reason: [Unknown]")
-                                    }
-                                },
-                            ))
-                        }
-                        reqwest_eventsource::Error::InvalidContentType(_, ref response) => {
-                            let status_code = response.status();
-                            debug!(response = ?response, "Invalid content type");
-                            Some(Err(error).with_context(|| format!("Http Status: {status_code}")))
-                        }
-                        error => {
-                            debug!(error = %error, "Failed to receive chat completion event");
-                            Some(Err(error.into()))
-                        }
-                    },
+        let accumulator = self.cost_accumulator.clone();
+        Ok(Box::pin(
+            into_chat_stream(es, self.request_logger.clone(), started, url).map(move |message| {
+                let mut message = message?;
+                if let (Some(accumulator), Some(usage)) = (&accumulator, &mut message.usage) {
+                    accumulator.record(price, usage);
                 }
-            })
-            .filter_map(move |response| {
-                response
-                    .map(|result| result.with_context(|| format_http_context(None, "POST", &url)))
-            });
+                Ok(message)
+            }),
+        ))
+    }
 
-        Ok(Box::pin(stream))
+    /// Fetches the pricing of the models once it's older than
+    /// [`crate::cost::PRICING_TTL`]. Failing to do so only leaves the cost of
+    /// the request unknown.
+    async fn refresh_pricing(&self, accumulator: &CostAccumulator) {
+        if !accumulator.is_stale().await {
+            return;
+        }
+        match self.list_models().await {
+            Ok(models) => accumulator.update_pricing(&models).await,
+            Err(error) => warn!(error = %error, "Failed to refresh model pricing"),
+        }
     }
 
     async fn inner_models(&self) -> Result<Vec<forge_domain::Model>> {
+        Ok(self
+            .list_models()
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn list_models(&self) -> Result<Vec<Model>> {
         let url = self.url("models")?;
         debug!(url = %url, "Fetching models");
         match self.fetch_models(url.clone()).await {
@@ -169,7 +185,7 @@ impl ForgeProvider {
                 let data: ListModelResponse = serde_json::from_str(&response)
                     .with_context(|| format_http_context(None, "GET", &url))
                     .with_context(|| "Failed to deserialize models response")?;
-                Ok(data.data.into_iter().map(Into::into).collect())
+                Ok(data.data)
             }
         }
     }
@@ -184,13 +200,14 @@ impl ForgeProvider {
         {
             Ok(response) => {
                 let ctx_message = format_http_context(Some(response.status()), "GET", &url);
+                let headers = response.headers().clone();
                 match response.error_for_status() {
                     Ok(response) => Ok(response
                         .text()
                         .await
                         .with_context(|| ctx_message)
                         .with_context(|| "Failed to decode response into text")?),
-                    Err(err) => Err(err)
+                    Err(err) => Err(RetryAfter::attach(err.into(), &headers))
                         .with_context(|| ctx_message)
                         .with_context(|| "Failed because of a non 200 status code"),
                 }
@@ -205,6 +222,76 @@ impl ForgeProvider {
     }
 }
 
+/// Turns the server-sent events of an OpenAI compatible chat completion into
+/// completion messages
+pub(crate) fn into_chat_stream(
+    es: EventSource,
+    request_logger: Option<Arc<dyn RequestLogger>>,
+    started: Instant,
+    url: Url,
+) -> impl Stream<Item = anyhow::Result<ChatCompletionMessage>> {
+    log_events(es, request_logger, started)
+        .take_while(|message| !matches!(message, Err(reqwest_eventsource::Error::StreamEnded)))
+        .then(|event| async {
+            match event {
+                Ok(event) => match event {
+                    Event::Open => None,
+                    Event::Message(event) if ["[DONE]", ""].contains(&event.data.as_str()) => {
+                        debug!("Received completion from Upstream");
+                        None
+                    }
+                    Event::Message(message) => Some(
+                        serde_json::from_str::<Response>(&message.data)
+                            .with_context(|| {
+                                format!("Failed to parse Forge Provider response: {}", message.data)
+                            })
+                            .and_then(|response| {
+                                ChatCompletionMessage::try_from(response.clone()).with_context(
+                                    || {
+                                        format!(
+                                            "Failed to create completion message: {}",
+                                            message.data
+                                        )
+                                    },
+                                )
+                            }),
+                    ),
+                },
+                Err(error) => match error {
+                    reqwest_eventsource::Error::StreamEnded => None,
+                    reqwest_eventsource::Error::InvalidStatusCode(_, response) => {
+                        let status = response.status();
+                        let error = RetryAfter::attach(
+                            Error::InvalidStatusCode(status.as_u16()).into(),
+                            response.headers(),
+                        );
+                        let body = response.text().await.ok();
+                        Some(Err(error).with_context(|| match body {
+                            Some(body) => {
+                                format!("{status} Reason: {body}")
+                            }
+                            None => {
+                                format!("{status} Reason: [Unknown]")
+                            }
+                        }))
+                    }
+                    reqwest_eventsource::Error::InvalidContentType(_, ref response) => {
+                        let status_code = response.status();
+                        debug!(response = ?response, "Invalid content type");
+                        Some(Err(error).with_context(|| format!("Http Status: {status_code}")))
+                    }
+                    error => {
+                        debug!(error = %error, "Failed to receive chat completion event");
+                        Some(Err(error.into()))
+                    }
+                },
+            }
+        })
+        .filter_map(move |response| {
+            response.map(|result| result.with_context(|| format_http_context(None, "POST", &url)))
+        })
+}
+
 impl ForgeProvider {
     pub async fn chat(
         &self,
@@ -239,6 +326,7 @@ impl From<Model> for forge_domain::Model {
 #[cfg(test)]
 mod tests {
     use anyhow::Context;
+    use pretty_assertions::assert_eq;
 
     use super::*;
 
@@ -258,4 +346,33 @@ mod tests {
         assert!(message.is_err());
         Ok(())
     }
+
+    fn fixture() -> ForgeProvider {
+        ForgeProvider::builder()
+            .client(Client::new())
+            .provider(Provider::open_router("test-key"))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_headers_forward_request_id() {
+        let request_id = RequestId::generate();
+
+        let actual = request_id
+            .scope(async { fixture().headers() })
+            .await
+            .get(RequestId::HEADER)
+            .map(|value| value.to_str().unwrap().to_string());
+
+        let expected = Some(request_id.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_headers_without_request_id() {
+        let actual = fixture().headers();
+
+        assert!(actual.get(RequestId::HEADER).is_none());
+    }
 }
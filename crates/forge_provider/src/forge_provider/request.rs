@@ -393,6 +393,7 @@ mod tests {
             content: "Hello".to_string(),
             tool_calls: None,
             model: ModelId::new("gpt-3.5-turbo").into(),
+            pinned: false,
         });
         let router_message = Message::from(user_message);
         assert_json_snapshot!(router_message);
@@ -415,6 +416,7 @@ mod tests {
             content: xml_content.to_string(),
             tool_calls: None,
             model: ModelId::new("gpt-3.5-turbo").into(),
+            pinned: false,
         });
         let router_message = Message::from(message);
         assert_json_snapshot!(router_message);
@@ -433,6 +435,7 @@ mod tests {
             content: "Using tool".to_string(),
             tool_calls: Some(vec![tool_call]),
             model: ModelId::new("gpt-3.5-turbo").into(),
+            pinned: false,
         });
         let router_message = Message::from(assistant_message);
         assert_json_snapshot!(router_message);
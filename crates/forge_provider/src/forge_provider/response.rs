@@ -32,6 +32,8 @@ pub struct ResponseUsage {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub total_tokens: u64,
+    /// Price of the request in USD, reported by OpenRouter
+    pub cost: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -84,6 +86,7 @@ impl From<ResponseUsage> for Usage {
             prompt_tokens: usage.prompt_tokens,
             completion_tokens: usage.completion_tokens,
             total_tokens: usage.total_tokens,
+            cost: usage.cost,
             ..Default::default()
         }
     }
@@ -113,6 +116,7 @@ impl TryFrom<Response> for ChatCompletionMessage {
                                     .clone()
                                     .and_then(|s| FinishReason::from_str(&s).ok()),
                             );
+                            resp.refusal = message.refusal.clone();
                             if let Some(tool_calls) = &message.tool_calls {
                                 for tool_call in tool_calls {
                                     resp = resp.add_tool_call(ToolCallFull {
@@ -139,6 +143,7 @@ impl TryFrom<Response> for ChatCompletionMessage {
                                     .clone()
                                     .and_then(|s| FinishReason::from_str(&s).ok()),
                             );
+                            resp.refusal = delta.refusal.clone();
                             if let Some(tool_calls) = &delta.tool_calls {
                                 for tool_call in tool_calls {
                                     resp = resp.add_tool_call(ToolCallPart {
@@ -200,6 +205,19 @@ mod tests {
         assert!(Fixture::test_response_compatibility(event));
     }
 
+    #[test]
+    fn test_refusal_response() {
+        let event = r#"{"id":"chatcmpl-B2YWm7kLnXp0dQ4tFvS9hRcZa1GeJ","object":"chat.completion","created":1739949102,"model":"gpt-4o-2024-08-06","system_fingerprint":"fp_00428b782a","choices":[{"index":0,"message":{"role":"assistant","content":null,"refusal":"I'm sorry, I can't assist with that request."},"logprobs":null,"finish_reason":"stop"}]}"#;
+        let response = serde_json::from_str::<Response>(event).unwrap();
+
+        let actual = ChatCompletionMessage::try_from(response).unwrap();
+
+        assert_eq!(
+            actual.refusal.as_deref(),
+            Some("I'm sorry, I can't assist with that request.")
+        );
+    }
+
     #[test]
     fn test_responses() -> anyhow::Result<()> {
         let input = include_str!("./responses.jsonl").split("\n");
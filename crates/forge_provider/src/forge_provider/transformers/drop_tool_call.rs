@@ -54,6 +54,7 @@ mod tests {
                     content: "Using tool".to_string(),
                     tool_calls: Some(vec![tool_call]),
                     model: None,
+                    pinned: false,
                 }),
                 ContextMessage::Tool(tool_result),
             ],
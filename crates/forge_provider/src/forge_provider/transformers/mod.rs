@@ -4,9 +4,12 @@ mod identity;
 mod make_openai_compat;
 mod pipeline;
 mod set_cache;
+mod strip_unsupported_params;
 mod tool_choice;
 mod transformer;
 mod when;
 
+pub use drop_tool_call::DropToolCalls;
+pub use make_openai_compat::MakeOpenAiCompat;
 pub use pipeline::ProviderPipeline;
 pub use transformer::Transformer;
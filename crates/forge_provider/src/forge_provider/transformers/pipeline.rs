@@ -4,18 +4,21 @@ use super::drop_tool_call::DropToolCalls;
 use super::identity::Identity;
 use super::make_openai_compat::MakeOpenAiCompat;
 use super::set_cache::SetCache;
+use super::strip_unsupported_params::StripUnsupportedParams;
 use super::tool_choice::SetToolChoice;
 use super::Transformer;
+use crate::forge_provider::model_params::ModelParamsTable;
 use crate::forge_provider::request::Request;
 use crate::forge_provider::tool_choice::ToolChoice;
 
 /// Pipeline for transforming requests based on the provider type
-pub struct ProviderPipeline<'a>(&'a Provider);
+pub struct ProviderPipeline<'a>(&'a Provider, &'a ModelParamsTable);
 
 impl<'a> ProviderPipeline<'a> {
-    /// Creates a new provider pipeline for the given provider
-    pub fn new(provider: &'a Provider) -> Self {
-        Self(provider)
+    /// Creates a new provider pipeline for the given provider, dropping the
+    /// parameters `model_params` lists as unsupported by the requested model
+    pub fn new(provider: &'a Provider, model_params: &'a ModelParamsTable) -> Self {
+        Self(provider, model_params)
     }
 }
 
@@ -32,6 +35,9 @@ impl Transformer for ProviderPipeline<'_> {
         let open_ai_compat =
             MakeOpenAiCompat.when(move |_| !self.0.is_open_router() || !self.0.is_antinomy());
 
-        or_transformers.combine(open_ai_compat).transform(request)
+        or_transformers
+            .combine(open_ai_compat)
+            .combine(StripUnsupportedParams::new(self.1))
+            .transform(request)
     }
 }
@@ -56,18 +56,21 @@ mod tests {
                         content: c.to_string(),
                         tool_calls: None,
                         model: None,
+                        pinned: false,
                     }),
                     'u' => ContextMessage::Text(TextMessage {
                         role: Role::User,
                         content: c.to_string(),
                         tool_calls: None,
                         model: ModelId::new("gpt-4").into(),
+                        pinned: false,
                     }),
                     'a' => ContextMessage::Text(TextMessage {
                         role: Role::Assistant,
                         content: c.to_string(),
                         tool_calls: None,
                         model: None,
+                        pinned: false,
                     }),
                     _ => {
                         panic!("Invalid character in test message");
@@ -0,0 +1,84 @@
+use super::Transformer;
+use crate::forge_provider::model_params::ModelParamsTable;
+use crate::forge_provider::request::Request;
+
+/// Removes the parameters the requested model rejects and clamps the ones
+/// outside of the range it accepts
+pub struct StripUnsupportedParams<'a>(&'a ModelParamsTable);
+
+impl<'a> StripUnsupportedParams<'a> {
+    pub fn new(table: &'a ModelParamsTable) -> Self {
+        Self(table)
+    }
+}
+
+impl Transformer for StripUnsupportedParams<'_> {
+    fn transform(&self, mut request: Request) -> Request {
+        let Some(params) = request.model.as_ref().and_then(|model| self.0.get(model)) else {
+            return request;
+        };
+
+        for param in &params.unsupported {
+            param.clear(&mut request);
+        }
+
+        if let (Some(max), Some(temperature)) = (params.max_temperature, request.temperature) {
+            request.temperature = Some(temperature.min(max));
+        }
+
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::ModelId;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn request(model: &str) -> Request {
+        Request::default()
+            .model(ModelId::new(model))
+            .temperature(1.5)
+            .top_p(0.9)
+            .frequency_penalty(0.5)
+    }
+
+    #[test]
+    fn test_strips_and_clamps_for_anthropic() {
+        let table = ModelParamsTable::default();
+
+        let actual =
+            StripUnsupportedParams::new(&table).transform(request("anthropic/claude-3.5-sonnet"));
+
+        assert_eq!(
+            (actual.temperature, actual.top_p, actual.frequency_penalty),
+            (Some(1.0), Some(0.9), None)
+        );
+    }
+
+    #[test]
+    fn test_strips_sampling_for_reasoning_models() {
+        let table = ModelParamsTable::default();
+
+        let actual = StripUnsupportedParams::new(&table).transform(request("openai/o3-mini"));
+
+        assert_eq!(
+            (actual.temperature, actual.top_p, actual.frequency_penalty),
+            (None, None, None)
+        );
+    }
+
+    #[test]
+    fn test_keeps_params_of_unrestricted_models() {
+        let table = ModelParamsTable::default();
+
+        let actual = StripUnsupportedParams::new(&table).transform(request("openai/gpt-4o"));
+
+        assert_eq!(
+            (actual.temperature, actual.top_p, actual.frequency_penalty),
+            (Some(1.5), Some(0.9), Some(0.5))
+        );
+    }
+}
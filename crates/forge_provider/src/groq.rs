@@ -0,0 +1,337 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use derive_builder::Builder;
+use forge_domain::{ChatCompletionMessage, Context, Model, ModelId, ResultStream};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::{Client, Url};
+use reqwest_eventsource::RequestBuilderExt;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::error::Error;
+use crate::forge_provider::{
+    into_chat_stream, DropToolCalls, MakeOpenAiCompat, Request, Response, Transformer,
+};
+use crate::request_logger::{log_request, RequestLogger};
+use crate::retry::RetryAfter;
+use crate::utils::format_http_context;
+
+/// Talks to Groq's OpenAI compatible API. Groq doesn't stream tool calls the
+/// way OpenAI does, so requests that define tools are sent without streaming
+/// and the full response is returned as a single message.
+#[derive(Clone, Builder)]
+pub struct Groq {
+    client: Client,
+    base_url: Url,
+    #[builder(default)]
+    api_key: Option<String>,
+    #[builder(default)]
+    request_logger: Option<Arc<dyn RequestLogger>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListModelResponse {
+    data: Vec<GroqModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroqModel {
+    id: ModelId,
+    owned_by: Option<String>,
+    context_window: Option<u64>,
+    active: Option<bool>,
+}
+
+impl From<GroqModel> for Model {
+    fn from(value: GroqModel) -> Self {
+        Model {
+            name: Some(value.id.as_str().to_string()),
+            id: value.id,
+            description: value.owned_by,
+            context_length: value.context_window,
+            tools_supported: Some(true),
+        }
+    }
+}
+
+impl Groq {
+    pub fn builder() -> GroqBuilder {
+        GroqBuilder::default()
+    }
+
+    pub fn with_request_logger(mut self, logger: Arc<dyn RequestLogger>) -> Self {
+        self.request_logger = Some(logger);
+        self
+    }
+
+    fn url(&self, path: &str) -> anyhow::Result<Url> {
+        self.base_url
+            .join(path)
+            .with_context(|| format!("Failed to append {} to base URL: {}", path, self.base_url))
+    }
+
+    fn headers(&self) -> anyhow::Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = &self.api_key {
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {api_key}"))?,
+            );
+        }
+        Ok(headers)
+    }
+
+    pub async fn chat(
+        &self,
+        model: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        // Without tool definitions the tool calls of earlier turns can't be
+        // validated, so they are replayed as plain messages
+        let request = DropToolCalls
+            .when(|request| request.tools.is_none())
+            .combine(MakeOpenAiCompat)
+            .transform(Request::from(context).model(model.clone()));
+        let stream = request.tools.is_none();
+        let request = request.stream(stream);
+
+        let url = self.url("chat/completions")?;
+        debug!(url = %url, model = %model, stream, "Connecting Upstream");
+        let started = log_request(self.request_logger.as_ref(), &request).await?;
+
+        if stream {
+            let es = self
+                .client
+                .post(url.clone())
+                .headers(self.headers()?)
+                .json(&request)
+                .eventsource()
+                .with_context(|| format_http_context(None, "POST", &url))?;
+
+            return Ok(Box::pin(into_chat_stream(
+                es,
+                self.request_logger.clone(),
+                started,
+                url,
+            )));
+        }
+
+        let response = self
+            .client
+            .post(url.clone())
+            .headers(self.headers()?)
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format_http_context(None, "POST", &url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error = RetryAfter::attach(
+                Error::InvalidStatusCode(status.as_u16()).into(),
+                response.headers(),
+            );
+            let body = response.text().await.ok();
+            return Err(error)
+                .with_context(|| match body {
+                    Some(body) => format!("{status} Reason: {body}"),
+                    None => format!("{status} Reason: [Unknown]"),
+                })
+                .with_context(|| format_http_context(Some(status), "POST", &url));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .with_context(|| format_http_context(Some(status), "POST", &url))?;
+        if let Some(logger) = &self.request_logger {
+            logger
+                .log_response(&body, started.elapsed().as_millis() as u64)
+                .await;
+        }
+
+        let message = serde_json::from_value::<Response>(body.clone())
+            .with_context(|| format!("Failed to parse Groq response: {body}"))
+            .and_then(ChatCompletionMessage::try_from)
+            .with_context(|| format_http_context(Some(status), "POST", &url))?;
+
+        Ok(Box::pin(tokio_stream::once(Ok(message))))
+    }
+
+    /// Lists the models that are currently served, Groq keeps deprecated
+    /// models in the list but marks them as inactive
+    pub async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        let url = self.url("models")?;
+        debug!(url = %url, "Fetching models");
+
+        let response = self
+            .client
+            .get(url.clone())
+            .headers(self.headers()?)
+            .send()
+            .await
+            .with_context(|| format_http_context(None, "GET", &url))
+            .with_context(|| "Failed to fetch models")?;
+
+        let ctx_msg = format_http_context(Some(response.status()), "GET", &url);
+        let headers = response.headers().clone();
+        let text = match response.error_for_status() {
+            Ok(response) => response
+                .text()
+                .await
+                .with_context(|| ctx_msg.clone())
+                .with_context(|| "Failed to decode response into text")?,
+            Err(err) => {
+                return Err(RetryAfter::attach(err.into(), &headers))
+                    .with_context(|| ctx_msg)
+                    .with_context(|| "Failed because of a non 200 status code")
+            }
+        };
+
+        parse_models(&text)
+            .with_context(|| ctx_msg)
+            .with_context(|| "Failed to deserialize models response")
+    }
+}
+
+fn parse_models(text: &str) -> anyhow::Result<Vec<Model>> {
+    let response: ListModelResponse = serde_json::from_str(text)?;
+    Ok(response
+        .data
+        .into_iter()
+        .filter(|model| model.active.unwrap_or(true))
+        .map(Into::into)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::ToolDefinition;
+    use pretty_assertions::assert_eq;
+    use tokio_stream::StreamExt;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    const MODELS: &str = include_str!("groq_models.json");
+
+    fn fixture(server: &MockServer) -> Groq {
+        Groq::builder()
+            .client(Client::new())
+            .base_url(Url::parse(&format!("{}/", server.uri())).unwrap())
+            .api_key(Some("test-key".to_string()))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_models_skips_inactive() {
+        let actual = parse_models(MODELS)
+            .unwrap()
+            .into_iter()
+            .map(|model| (model.id.as_str().to_string(), model.context_length))
+            .collect::<Vec<_>>();
+
+        let expected = vec![
+            ("llama-3.3-70b-versatile".to_string(), Some(131072)),
+            ("llama-3.1-8b-instant".to_string(), Some(131072)),
+            ("gemma2-9b-it".to_string(), Some(8192)),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_models_authenticates_with_bearer_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .and(header("authorization", "Bearer test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(MODELS, "application/json"))
+            .mount(&server)
+            .await;
+
+        let actual = fixture(&server).models().await.unwrap().len();
+
+        assert_eq!(actual, 3);
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_tools_is_not_streamed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({"stream": false})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "llama-3.3-70b-versatile",
+                "choices": [{
+                    "index": 0,
+                    "logprobs": null,
+                    "finish_reason": "tool_calls",
+                    "message": {
+                        "role": "assistant",
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": {"name": "forge_tool_fs_read", "arguments": "{\"path\":\"/a\"}"}
+                        }]
+                    }
+                }],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+            })))
+            .mount(&server)
+            .await;
+        let context = Context::default().add_tool(ToolDefinition::new("forge_tool_fs_read"));
+
+        let messages = fixture(&server)
+            .chat(&ModelId::new("llama-3.3-70b-versatile"), context)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+
+        let actual = messages
+            .into_iter()
+            .flat_map(|message| message.unwrap().tool_calls)
+            .filter_map(|call| call.as_full().map(|call| call.name.as_str().to_string()))
+            .collect::<Vec<_>>();
+        let expected = vec!["forge_tool_fs_read".to_string()];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_chat_without_tools_is_streamed() {
+        let server = MockServer::start().await;
+        let chunk = serde_json::json!({
+            "id": "chatcmpl-1",
+            "model": "llama-3.3-70b-versatile",
+            "created": 0,
+            "object": "chat.completion.chunk",
+            "choices": [{"finish_reason": "stop", "delta": {"content": "Hello"}}]
+        });
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({"stream": true})))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                format!("data: {chunk}\n\ndata: [DONE]\n\n"),
+                "text/event-stream",
+            ))
+            .mount(&server)
+            .await;
+
+        let actual = fixture(&server)
+            .chat(&ModelId::new("llama-3.3-70b-versatile"), Context::default())
+            .await
+            .unwrap()
+            .map(|message| message.unwrap().content.unwrap().as_str().to_string())
+            .collect::<Vec<_>>()
+            .await;
+
+        let expected = vec!["Hello".to_string()];
+        assert_eq!(actual, expected);
+    }
+}
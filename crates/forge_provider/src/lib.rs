@@ -1,9 +1,20 @@
 mod anthropic;
+mod azure;
 mod client;
+mod cost;
 mod error;
+mod fallback;
 mod forge_provider;
+mod groq;
+mod ollama;
+mod request_logger;
 mod retry;
 mod utils;
 
 // Re-export from builder.rs
 pub use client::Client;
+pub use cost::CostAccumulator;
+pub use fallback::FallbackProvider;
+pub use forge_provider::{ModelParams, Param};
+pub use request_logger::{FileRequestLogger, RequestLogger};
+pub use retry::RetryConfig;
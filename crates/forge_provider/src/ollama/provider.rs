@@ -0,0 +1,224 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Context as _;
+use derive_builder::Builder;
+use forge_domain::{ChatCompletionMessage, Context, Model, ModelId, ResultStream};
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::{Client, Url};
+use tracing::debug;
+
+use super::request::Request;
+use super::response::{ListModelResponse, Response};
+use crate::error::Error;
+use crate::request_logger::{log_request, RequestLogger};
+use crate::retry::RetryAfter;
+use crate::utils::format_http_context;
+
+/// Talks to Ollama's native API, which is unauthenticated by default and
+/// streams newline delimited JSON instead of server-sent events
+#[derive(Clone, Builder)]
+pub struct Ollama {
+    client: Client,
+    base_url: Url,
+    #[builder(default)]
+    request_logger: Option<Arc<dyn RequestLogger>>,
+}
+
+impl Ollama {
+    pub fn builder() -> OllamaBuilder {
+        OllamaBuilder::default()
+    }
+
+    pub fn with_request_logger(mut self, logger: Arc<dyn RequestLogger>) -> Self {
+        self.request_logger = Some(logger);
+        self
+    }
+
+    fn url(&self, path: &str) -> anyhow::Result<Url> {
+        // Validate the path doesn't contain certain patterns
+        if path.contains("://") || path.contains("..") {
+            anyhow::bail!("Invalid path: Contains forbidden patterns");
+        }
+
+        // Remove leading slash to avoid double slashes
+        let path = path.trim_start_matches('/');
+
+        self.base_url
+            .join(path)
+            .with_context(|| format!("Failed to append {} to base URL: {}", path, self.base_url))
+    }
+}
+
+impl Ollama {
+    pub async fn chat(
+        &self,
+        model: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        let request = Request::try_from(context)?
+            .model(model.as_str())
+            .stream(true);
+
+        let url = self.url("api/chat")?;
+        debug!(url = %url, model = %model, "Connecting Upstream");
+        let started = log_request(self.request_logger.as_ref(), &request).await?;
+        let response = self
+            .client
+            .post(url.clone())
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format_http_context(None, "POST", &url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error = RetryAfter::attach(
+                Error::InvalidStatusCode(status.as_u16()).into(),
+                response.headers(),
+            );
+            let body = response.text().await.ok();
+            return Err(error)
+                .with_context(|| match body {
+                    Some(body) => format!("{status} Reason: {body}"),
+                    None => format!("{status} Reason: [Unknown]"),
+                })
+                .with_context(|| format_http_context(Some(status), "POST", &url));
+        }
+
+        let logger = self.request_logger.clone();
+        let stream = lines(response)
+            .then(move |line| log_line(logger.clone(), started, line))
+            .map(move |line| {
+                line.and_then(|line| {
+                    serde_json::from_str::<Response>(&line)
+                        .with_context(|| format!("Failed to parse Ollama response: {line}"))
+                })
+                .and_then(ChatCompletionMessage::try_from)
+                .with_context(|| format_http_context(None, "POST", &url))
+            });
+
+        Ok(Box::pin(stream))
+    }
+
+    pub async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        let url = self.url("api/tags")?;
+        debug!(url = %url, "Fetching models");
+
+        let response = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .with_context(|| format_http_context(None, "GET", &url))
+            .with_context(|| "Failed to fetch models")?;
+
+        let ctx_msg = format_http_context(Some(response.status()), "GET", &url);
+        let headers = response.headers().clone();
+        let text = match response.error_for_status() {
+            Ok(response) => response
+                .text()
+                .await
+                .with_context(|| ctx_msg.clone())
+                .with_context(|| "Failed to decode response into text")?,
+            Err(err) => {
+                return Err(RetryAfter::attach(err.into(), &headers))
+                    .with_context(|| ctx_msg)
+                    .with_context(|| "Failed because of a non 200 status code")
+            }
+        };
+
+        let response: ListModelResponse = serde_json::from_str(&text)
+            .with_context(|| ctx_msg)
+            .with_context(|| "Failed to deserialize models response")?;
+        Ok(response.models.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Splits the response body into its non-empty lines, a line may span
+/// several chunks
+fn lines(response: reqwest::Response) -> impl Stream<Item = anyhow::Result<String>> {
+    stream::unfold(Some((response, Vec::new())), |state| async move {
+        let (mut response, mut buffer) = state?;
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                buffer.extend_from_slice(&chunk);
+                let mut lines = Vec::new();
+                while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                    let line = buffer.drain(..=end).collect::<Vec<_>>();
+                    lines.push(Ok(String::from_utf8_lossy(&line).trim().to_string()));
+                }
+                Some((lines, Some((response, buffer))))
+            }
+            Ok(None) => Some((
+                vec![Ok(String::from_utf8_lossy(&buffer).trim().to_string())],
+                None,
+            )),
+            Err(error) => Some((vec![Err(error.into())], None)),
+        }
+    })
+    .flat_map(stream::iter)
+    .filter(|line| std::future::ready(!matches!(line, Ok(line) if line.is_empty())))
+}
+
+/// Logs the line if a logger is configured, lines that aren't valid JSON are
+/// logged as a string
+async fn log_line(
+    logger: Option<Arc<dyn RequestLogger>>,
+    started: Instant,
+    line: anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    if let (Some(logger), Ok(line)) = (logger, &line) {
+        let data =
+            serde_json::from_str(line).unwrap_or_else(|_| serde_json::Value::String(line.clone()));
+        logger
+            .log_response(&data, started.elapsed().as_millis() as u64)
+            .await;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chat_streams_newline_delimited_json() {
+        let server = MockServer::start().await;
+        let body = [
+            r#"{"message":{"role":"assistant","content":"Hello"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":" world"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop"}"#,
+        ]
+        .join("\n");
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/x-ndjson"))
+            .mount(&server)
+            .await;
+        let ollama = Ollama::builder()
+            .client(Client::new())
+            .base_url(Url::parse(&server.uri()).unwrap())
+            .build()
+            .unwrap();
+
+        let messages = ollama
+            .chat(&ModelId::new("llama3.2"), Context::default())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+
+        let actual = messages
+            .into_iter()
+            .filter_map(|message| message.unwrap().content)
+            .map(|content| content.as_str().to_string())
+            .collect::<Vec<_>>();
+        let expected = vec!["Hello".to_string(), " world".to_string()];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,243 @@
+use derive_setters::Setters;
+use forge_domain::{ContextMessage, Image, ToolCallFull, ToolResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Body of a request to Ollama's `/api/chat` endpoint.
+///
+/// Ollama doesn't support every OpenAI parameter, sampling parameters are
+/// passed as model options and there is no way to force a tool choice.
+#[derive(Serialize, Default, Setters)]
+#[setters(into, strip_option)]
+pub struct Request {
+    model: String,
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Tool>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<Options>,
+}
+
+impl TryFrom<forge_domain::Context> for Request {
+    type Error = anyhow::Error;
+    fn try_from(context: forge_domain::Context) -> std::result::Result<Self, Self::Error> {
+        let options = Options {
+            temperature: context.temperature.map(|t| t.value()),
+            top_p: context.top_p.map(|t| t.value()),
+            top_k: context.top_k.map(|t| t.value()),
+            num_predict: context.max_tokens,
+        };
+
+        Ok(Self {
+            messages: context.messages.into_iter().map(Message::from).collect(),
+            tools: context
+                .tools
+                .into_iter()
+                .map(Tool::try_from)
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+            options: (!options.is_empty()).then_some(options),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct Options {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<usize>,
+}
+
+impl Options {
+    fn is_empty(&self) -> bool {
+        self.temperature.is_none()
+            && self.top_p.is_none()
+            && self.top_k.is_none()
+            && self.num_predict.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+#[derive(Serialize)]
+pub struct Message {
+    role: Role,
+    content: String,
+    /// Base64 encoded images, without the data URL prefix
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ToolCall>,
+    /// Name of the tool that produced the content of a tool message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+impl From<ContextMessage> for Message {
+    fn from(value: ContextMessage) -> Self {
+        match value {
+            ContextMessage::Text(message) => Message {
+                role: match message.role {
+                    forge_domain::Role::System => Role::System,
+                    forge_domain::Role::User => Role::User,
+                    forge_domain::Role::Assistant => Role::Assistant,
+                },
+                content: message.content,
+                images: vec![],
+                tool_calls: message
+                    .tool_calls
+                    .into_iter()
+                    .flatten()
+                    .map(ToolCall::from)
+                    .collect(),
+                tool_name: None,
+            },
+            ContextMessage::Tool(result) => Message::from(result),
+            ContextMessage::Image(image) => Message {
+                role: Role::User,
+                content: String::new(),
+                images: vec![base64_data(&image)],
+                tool_calls: vec![],
+                tool_name: None,
+            },
+        }
+    }
+}
+
+impl From<ToolResult> for Message {
+    fn from(result: ToolResult) -> Self {
+        let content = result
+            .output
+            .values
+            .iter()
+            .filter_map(|value| value.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Message {
+            role: Role::Tool,
+            content,
+            images: vec![],
+            tool_calls: vec![],
+            tool_name: Some(result.name.to_string()),
+        }
+    }
+}
+
+/// Strips the `data:<mime>;base64,` prefix from the image's URL
+fn base64_data(image: &Image) -> String {
+    image
+        .url()
+        .split_once(',')
+        .map(|(_, data)| data)
+        .unwrap_or(image.url())
+        .to_string()
+}
+
+/// A tool call, Ollama passes arguments as a JSON object rather than a string
+/// and doesn't assign call IDs
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ToolCall {
+    pub function: FunctionCall,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct FunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+impl From<ToolCallFull> for ToolCall {
+    fn from(value: ToolCallFull) -> Self {
+        ToolCall {
+            function: FunctionCall { name: value.name.to_string(), arguments: value.arguments },
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Tool {
+    r#type: &'static str,
+    function: FunctionDefinition,
+}
+
+#[derive(Serialize)]
+pub struct FunctionDefinition {
+    name: String,
+    description: String,
+    parameters: Value,
+}
+
+impl TryFrom<forge_domain::ToolDefinition> for Tool {
+    type Error = anyhow::Error;
+    fn try_from(value: forge_domain::ToolDefinition) -> std::result::Result<Self, Self::Error> {
+        Ok(Tool {
+            r#type: "function",
+            function: FunctionDefinition {
+                name: value.name.to_string(),
+                description: value.description,
+                parameters: serde_json::to_value(value.input_schema)?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::{
+        Context, ContextMessage, ModelId, Temperature, ToolCallId, ToolName, ToolOutput,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_request_conversion() {
+        let model_id = ModelId::new("llama3.2");
+        let context = Context::default()
+            .add_message(ContextMessage::system("You're expert at math."))
+            .add_message(ContextMessage::user(
+                "what's 2 + 2 ?",
+                model_id.clone().into(),
+            ))
+            .add_message(ContextMessage::assistant(
+                "here is the system call.",
+                Some(vec![ToolCallFull {
+                    name: ToolName::new("math"),
+                    call_id: Some(ToolCallId::new("math-1")),
+                    arguments: serde_json::json!({"expression": "2 + 2"}),
+                }]),
+            ))
+            .add_tool_results(vec![ToolResult {
+                name: ToolName::new("math"),
+                call_id: Some(ToolCallId::new("math-1")),
+                output: ToolOutput::text(serde_json::json!({"result": 4}).to_string()),
+            }])
+            .add_message(ContextMessage::Image(Image::new_base64(
+                "aGVsbG8=".to_string(),
+                "image/png",
+            )))
+            .add_tool(
+                forge_domain::ToolDefinition::new("math").description("Evaluates an expression"),
+            )
+            .temperature(Temperature::new(0.5).unwrap());
+        let request = Request::try_from(context)
+            .unwrap()
+            .model(model_id.as_str())
+            .stream(true);
+        insta::assert_snapshot!(serde_json::to_string_pretty(&request).unwrap());
+    }
+}
@@ -0,0 +1,161 @@
+use std::str::FromStr;
+
+use forge_domain::{ChatCompletionMessage, Content, FinishReason, ModelId, ToolCallFull, ToolName};
+use serde::Deserialize;
+
+use super::request::ToolCall;
+use crate::error::Error;
+
+#[derive(Deserialize)]
+pub struct ListModelResponse {
+    pub models: Vec<Model>,
+}
+
+#[derive(Deserialize)]
+pub struct Model {
+    name: String,
+}
+
+impl From<Model> for forge_domain::Model {
+    fn from(value: Model) -> Self {
+        Self {
+            id: ModelId::new(&value.name),
+            name: Some(value.name),
+            description: None,
+            context_length: None,
+            tools_supported: None,
+        }
+    }
+}
+
+/// A line of the newline delimited JSON streamed back by `/api/chat`
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Response {
+    Failure { error: String },
+    Chunk(Chunk),
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct Chunk {
+    #[serde(default)]
+    pub message: Option<ResponseMessage>,
+    #[serde(default)]
+    pub done: bool,
+    #[serde(default)]
+    pub done_reason: Option<String>,
+    /// Number of tokens in the prompt, only sent with the final chunk
+    #[serde(default)]
+    pub prompt_eval_count: Option<u64>,
+    /// Number of tokens generated, only sent with the final chunk
+    #[serde(default)]
+    pub eval_count: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct ResponseMessage {
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+impl TryFrom<Response> for ChatCompletionMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Response) -> Result<Self, Self::Error> {
+        let chunk = match value {
+            Response::Failure { error } => return Err(Error::Ollama(error).into()),
+            Response::Chunk(chunk) => chunk,
+        };
+
+        let mut message = ChatCompletionMessage::default();
+        if let Some(response) = chunk.message {
+            if !response.content.is_empty() {
+                message = message.content(Content::part(response.content));
+            }
+
+            // Tool calls are always sent whole rather than streamed in parts
+            message.tool_calls = response
+                .tool_calls
+                .into_iter()
+                .map(|tool_call| {
+                    ToolCallFull {
+                        name: ToolName::new(tool_call.function.name),
+                        call_id: None,
+                        arguments: tool_call.function.arguments,
+                    }
+                    .into()
+                })
+                .collect();
+        }
+
+        if chunk.done {
+            let prompt_tokens = chunk.prompt_eval_count.unwrap_or_default();
+            let completion_tokens = chunk.eval_count.unwrap_or_default();
+            message = message.usage(forge_domain::Usage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+                ..Default::default()
+            });
+
+            if let Some(reason) = chunk
+                .done_reason
+                .and_then(|reason| FinishReason::from_str(&reason).ok())
+            {
+                message = message.finish_reason(reason);
+            }
+        }
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::ToolCall as DomainToolCall;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_chunk_conversion() {
+        let fixture = [
+            r#"{"model":"llama3.2","message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"math","arguments":{"expression":"2 + 2"}}}]},"done":false}"#,
+            r#"{"model":"llama3.2","message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":12,"eval_count":8}"#,
+        ];
+
+        let actual = fixture
+            .into_iter()
+            .map(|line| {
+                ChatCompletionMessage::try_from(serde_json::from_str::<Response>(line).unwrap())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let expected = vec![
+            ChatCompletionMessage::default().tool_calls(vec![DomainToolCall::Full(ToolCallFull {
+                name: ToolName::new("math"),
+                call_id: None,
+                arguments: serde_json::json!({"expression": "2 + 2"}),
+            })]),
+            ChatCompletionMessage::default()
+                .finish_reason(FinishReason::Stop)
+                .usage(forge_domain::Usage {
+                    prompt_tokens: 12,
+                    completion_tokens: 8,
+                    total_tokens: 20,
+                    ..Default::default()
+                }),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_error_conversion() {
+        let fixture: Response = serde_json::from_str(r#"{"error":"model not found"}"#).unwrap();
+        let actual = ChatCompletionMessage::try_from(fixture).unwrap_err();
+        assert_eq!(actual.to_string(), "model not found");
+    }
+}
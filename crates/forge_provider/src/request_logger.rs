@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use forge_domain::redact_credentials;
+use reqwest_eventsource::Event;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tokio_stream::{Stream, StreamExt};
+use tracing::warn;
+
+/// Captures the raw bodies exchanged with a provider, useful for debugging
+/// provider integrations
+#[async_trait::async_trait]
+pub trait RequestLogger: Send + Sync {
+    /// Called with the body of every request before it is sent
+    async fn log_request(&self, req: &Value);
+
+    /// Called with every response received for a request, along with the time
+    /// elapsed since the request was sent. Streaming responses are logged once
+    /// per event.
+    async fn log_response(&self, resp: &Value, latency_ms: u64);
+}
+
+/// Appends requests and responses as NDJSON lines to a file, with credentials
+/// such as the headers of fetch tool calls redacted
+pub struct FileRequestLogger {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileRequestLogger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), lock: Mutex::new(()) }
+    }
+
+    async fn append(&self, line: Value) {
+        // Serializes writes so that lines from concurrent requests don't interleave
+        let _guard = self.lock.lock().await;
+        if let Err(error) = self.try_append(line).await {
+            warn!(path = %self.path.display(), error = %error, "Failed to log provider request");
+        }
+    }
+
+    async fn try_append(&self, line: Value) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(format!("{line}\n").as_bytes()).await
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestLogger for FileRequestLogger {
+    async fn log_request(&self, req: &Value) {
+        self.append(json!({"type": "request", "body": redact_credentials(req)}))
+            .await
+    }
+
+    async fn log_response(&self, resp: &Value, latency_ms: u64) {
+        self.append(json!({
+            "type": "response",
+            "latency_ms": latency_ms,
+            "body": redact_credentials(resp)
+        }))
+        .await
+    }
+}
+
+/// Logs the serialized request body if a logger is configured
+pub(crate) async fn log_request<T: Serialize>(
+    logger: Option<&Arc<dyn RequestLogger>>,
+    request: &T,
+) -> anyhow::Result<Instant> {
+    if let Some(logger) = logger {
+        logger.log_request(&serde_json::to_value(request)?).await;
+    }
+
+    Ok(Instant::now())
+}
+
+/// Logs the data of every message received on an event stream, data that isn't
+/// valid JSON is logged as a string
+pub(crate) fn log_events<S, E>(
+    stream: S,
+    logger: Option<Arc<dyn RequestLogger>>,
+    started: Instant,
+) -> impl Stream<Item = Result<Event, E>>
+where
+    S: Stream<Item = Result<Event, E>>,
+{
+    stream.then(move |event| {
+        let logger = logger.clone();
+        async move {
+            if let (Some(logger), Ok(Event::Message(message))) = (logger, &event) {
+                let data = serde_json::from_str(&message.data)
+                    .unwrap_or_else(|_| Value::String(message.data.clone()));
+                logger
+                    .log_response(&data, started.elapsed().as_millis() as u64)
+                    .await;
+            }
+            event
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_request_logger_appends_ndjson() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("logs").join("requests.ndjson");
+        let fixture = FileRequestLogger::new(&path);
+
+        fixture.log_request(&json!({"model": "gpt-4o"})).await;
+        fixture.log_response(&json!({"id": "1"}), 42).await;
+
+        let actual = tokio::fs::read_to_string(&path)
+            .await
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        let expected = vec![
+            json!({"type": "request", "body": {"model": "gpt-4o"}}),
+            json!({"type": "response", "latency_ms": 42, "body": {"id": "1"}}),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_file_request_logger_redacts_credentials() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("requests.ndjson");
+        let fixture = FileRequestLogger::new(&path);
+        let arguments = json!({
+            "url": "https://api.example.com",
+            "headers": {"Authorization": "Bearer secret", "Cookie": "session=secret"}
+        });
+
+        fixture
+            .log_request(&json!({
+                "messages": [{"tool_calls": [{"function": {"arguments": arguments.to_string()}}]}]
+            }))
+            .await;
+
+        let actual = tokio::fs::read_to_string(&path).await.unwrap();
+        assert!(!actual.contains("secret"));
+        assert!(actual.contains("[REDACTED]"));
+    }
+}
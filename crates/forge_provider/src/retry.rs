@@ -1,20 +1,155 @@
+use std::time::Duration;
+
+use backon::ExponentialBuilder;
+use chrono::{DateTime, Utc};
+use derive_setters::Setters;
 use forge_domain::Error as DomainError;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
 
 use crate::error::Error;
-pub fn into_retry(error: anyhow::Error, retry_status_codes: &[u16]) -> anyhow::Error {
-    if let Some(code) = get_req_status_code(&error)
-        .or(get_event_req_status_code(&error))
-        .or(get_api_status_code(&error))
-    {
-        if retry_status_codes.contains(&code) {
-            return DomainError::Retryable(error).into();
+
+/// Controls how requests that fail with a rate limit (429) or server (5xx)
+/// error are retried by the client before the error is surfaced
+#[derive(Debug, Clone, PartialEq, Setters)]
+pub struct RetryConfig {
+    /// Total number of attempts, including the first request
+    pub max_attempts: u32,
+    /// Delay before the first retry, multiplied by `factor` on every
+    /// subsequent retry
+    pub base_delay: Duration,
+    /// Multiplier applied to the delay after each retry
+    pub factor: f32,
+    /// Upper bound for the delay between two attempts
+    pub max_delay: Duration,
+    /// Randomizes delays so that concurrent clients don't retry in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            factor: 2.0,
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub(crate) fn backoff(&self) -> ExponentialBuilder {
+        let builder = ExponentialBuilder::default()
+            .with_min_delay(self.base_delay)
+            .with_factor(self.factor)
+            .with_max_delay(self.max_delay)
+            .with_max_times(self.max_attempts.saturating_sub(1) as usize);
+        if self.jitter {
+            builder.with_jitter()
+        } else {
+            builder
+        }
+    }
+}
+
+impl From<&forge_domain::RetryConfig> for RetryConfig {
+    fn from(config: &forge_domain::RetryConfig) -> Self {
+        Self {
+            max_attempts: (config.max_retry_attempts as u32).saturating_add(1),
+            base_delay: Duration::from_millis(config.initial_backoff_ms),
+            factor: config.backoff_factor as f32,
+            ..Self::default()
         }
     }
+}
+
+/// Delay requested by the server through the `Retry-After` header, attached as
+/// context to the error of the failed request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryAfter(pub Duration);
+
+impl std::fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Retry after {} seconds", self.0.as_secs())
+    }
+}
+
+impl RetryAfter {
+    /// Parses the `Retry-After` header, given either as a delay in seconds or
+    /// as the date after which to retry, for eg: `Wed, 21 Oct 2015 07:28:00
+    /// GMT`
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+        Self::parse(value, Utc::now())
+    }
 
-    if is_api_transport_error(&error)
-        || is_req_transport_error(&error)
-        || is_event_transport_error(&error)
-    {
+    /// Parses the value of a `Retry-After` header received at `now`. A date
+    /// that has already passed means retrying right away.
+    fn parse(value: &str, now: DateTime<Utc>) -> Option<Self> {
+        let value = value.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Self(Duration::from_secs(seconds)));
+        }
+
+        let date = DateTime::parse_from_rfc2822(value).ok()?;
+        Some(Self(
+            (date.with_timezone(&Utc) - now)
+                .to_std()
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// Attaches the delay to the error, if the server requested one
+    pub fn attach(error: anyhow::Error, headers: &HeaderMap) -> anyhow::Error {
+        match Self::from_headers(headers) {
+            Some(retry_after) => error.context(retry_after),
+            None => error,
+        }
+    }
+}
+
+/// Returns the delay requested by the server for a failed request
+pub(crate) fn get_retry_after(error: &anyhow::Error) -> Option<Duration> {
+    error
+        .downcast_ref::<RetryAfter>()
+        .map(|retry_after| retry_after.0)
+}
+
+/// Checks if the request failed with a rate limit or server error
+pub(crate) fn is_transient(error: &anyhow::Error) -> bool {
+    get_req_status_code(error)
+        .or(get_event_req_status_code(error))
+        .or(get_api_status_code(error))
+        .is_some_and(|code| code == 429 || (500..600).contains(&code))
+}
+
+/// Checks if the request failed because the provider is unavailable, rather
+/// than because the request itself was rejected, so that another provider may
+/// be able to serve it
+pub(crate) fn is_unavailable(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<DomainError>(),
+        Some(DomainError::Retryable(_))
+    ) || is_transient(error)
+        || is_api_transport_error(error)
+        || is_req_transport_error(error)
+        || is_event_transport_error(error)
+}
+
+/// Checks if the request failed with one of `retry_status_codes` or a
+/// transport error, and is worth attempting again
+pub(crate) fn should_retry(error: &anyhow::Error, retry_status_codes: &[u16]) -> bool {
+    get_req_status_code(error)
+        .or(get_event_req_status_code(error))
+        .or(get_api_status_code(error))
+        .is_some_and(|code| retry_status_codes.contains(&code))
+        || is_api_transport_error(error)
+        || is_req_transport_error(error)
+        || is_event_transport_error(error)
+}
+
+pub fn into_retry(error: anyhow::Error, retry_status_codes: &[u16]) -> anyhow::Error {
+    if should_retry(&error, retry_status_codes) {
         return DomainError::Retryable(error).into();
     }
 
@@ -85,6 +220,7 @@ fn is_event_transport_error(error: &anyhow::Error) -> bool {
 #[cfg(test)]
 mod tests {
     use anyhow::anyhow;
+    use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::error::{Error, ErrorCode, ErrorResponse};
@@ -247,4 +383,45 @@ mod tests {
         // Verify - should not be retryable as 400 is not in retry_codes
         assert!(!is_retryable(actual));
     }
+
+    #[test]
+    fn test_retry_after_is_read_from_error_context() {
+        // Setup
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        let error = RetryAfter::attach(Error::InvalidStatusCode(429).into(), &headers)
+            .context("Invalid status code: 429");
+
+        // Execute
+        let actual = (get_retry_after(&error), is_transient(&error));
+
+        // Verify
+        assert_eq!(actual, (Some(Duration::from_secs(7)), true));
+    }
+
+    #[test]
+    fn test_retry_after_parses_seconds_and_dates() {
+        // Setup
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:26:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // Execute
+        let actual = [
+            " 120 ",
+            "Wed, 21 Oct 2015 07:28:00 GMT",
+            "Wed, 21 Oct 2015 07:00:00 GMT",
+            "soon",
+        ]
+        .map(|value| RetryAfter::parse(value, now));
+
+        // Verify
+        let expected = [
+            Some(RetryAfter(Duration::from_secs(120))),
+            Some(RetryAfter(Duration::from_secs(90))),
+            Some(RetryAfter(Duration::ZERO)),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
 }
@@ -110,22 +110,30 @@ pub mod tests {
     use std::collections::HashMap;
     use std::path::{Path, PathBuf};
     use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
     use base64::Engine;
     use bytes::Bytes;
     use forge_domain::{
-        AttachmentContent, AttachmentService, CommandOutput, Environment, EnvironmentService,
-        Provider, ToolDefinition, ToolName, ToolOutput,
+        AttachmentContent, AttachmentService, CommandEnv, CommandOutput, Compression, Environment,
+        EnvironmentService, FileEvent, OutputLine, OutputStream, Provider, ToolDefinition,
+        ToolName, ToolOutput,
     };
-    use forge_snaps::Snapshot;
+    use forge_snaps::{
+        ExportReport, ImportReport, ImportStrategy, PurgePolicy, Snapshot, SnapshotId,
+        SnapshotSelector, SnapshotSummary, TreeRestoreReport, TreeSnapshotInfo, VerificationIssue,
+    };
+    use futures::stream::BoxStream;
+    use futures::StreamExt;
     use serde_json::Value;
+    use tokio::sync::mpsc::UnboundedSender;
 
     use crate::attachment::ForgeChatRequest;
     use crate::utils::AttachmentExtension;
     use crate::{
-        CommandExecutorService, FileRemoveService, FsCreateDirsService, FsMetaService,
-        FsReadService, FsSnapshotService, FsWriteService, Infrastructure, InquireService,
-        McpClient, McpServer,
+        CommandExecutorService, FileRemoveService, FsCopyService, FsCreateDirsService,
+        FsMetaService, FsMoveService, FsReadService, FsSnapshotService, FsWatchService,
+        FsWriteService, Infrastructure, InquireService, McpClient, McpServer,
     };
 
     #[derive(Debug)]
@@ -143,6 +151,24 @@ pub mod tests {
                 base_path: PathBuf::from("/base"),
                 provider: Provider::open_router("test-key"),
                 retry_config: Default::default(),
+                tool_output_limit: Default::default(),
+                ignore_patterns: forge_walker::DEFAULT_IGNORE_PATTERNS
+                    .iter()
+                    .map(|pattern| pattern.to_string())
+                    .collect(),
+                shell_timeout_secs: 300,
+                syntax_check: true,
+                restricted: false,
+                tool_timeout_secs: 300,
+                verbose: false,
+                model: None,
+                temperature: None,
+                top_p: None,
+                top_k: None,
+                snapshot_compression: Compression::None,
+                fetch_max_bytes: 512 * 1024,
+                provider_fallback: None,
+                fallback_providers: vec![],
             }
         }
     }
@@ -253,6 +279,43 @@ pub mod tests {
         }
     }
 
+    #[async_trait::async_trait]
+    impl FsMoveService for MockFileService {
+        async fn rename(&self, from: &Path, to: &Path) -> anyhow::Result<()> {
+            let mut files = self.files.lock().unwrap();
+            let content = files
+                .iter()
+                .find(|(p, _)| p == from)
+                .map(|(_, content)| content.clone())
+                .ok_or_else(|| anyhow::anyhow!("File not found: {:?}", from))?;
+            files.retain(|(p, _)| p != from && p != to);
+            files.push((to.to_path_buf(), content));
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FsCopyService for MockFileService {
+        async fn copy(&self, from: &Path, to: &Path) -> anyhow::Result<()> {
+            let mut files = self.files.lock().unwrap();
+            let content = files
+                .iter()
+                .find(|(p, _)| p == from)
+                .map(|(_, content)| content.clone())
+                .ok_or_else(|| anyhow::anyhow!("File not found: {:?}", from))?;
+            files.retain(|(p, _)| p != to);
+            files.push((to.to_path_buf(), content));
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FsWatchService for MockFileService {
+        async fn watch(&self, _: &Path) -> anyhow::Result<BoxStream<'static, FileEvent>> {
+            Ok(futures::stream::empty().boxed())
+        }
+    }
+
     #[async_trait::async_trait]
     impl FsCreateDirsService for MockFileService {
         async fn create_dirs(&self, path: &Path) -> anyhow::Result<()> {
@@ -278,6 +341,17 @@ pub mod tests {
             Ok(())
         }
 
+        async fn append(&self, path: &Path, contents: Bytes) -> anyhow::Result<()> {
+            let mut files = self.files.lock().unwrap();
+            match files.iter_mut().find(|(p, _)| p == path) {
+                Some((_, existing)) => {
+                    *existing = Bytes::from([existing.as_ref(), contents.as_ref()].concat())
+                }
+                None => files.push((path.to_path_buf(), contents)),
+            }
+            Ok(())
+        }
+
         async fn write_temp(&self, _: &str, _: &str, content: &str) -> anyhow::Result<PathBuf> {
             let temp_dir = crate::utils::TempDir::new().unwrap();
             let path = temp_dir.path();
@@ -293,13 +367,96 @@ pub mod tests {
 
     #[async_trait::async_trait]
     impl FsSnapshotService for MockSnapService {
-        async fn create_snapshot(&self, _: &Path) -> anyhow::Result<Snapshot> {
+        async fn create_snapshot(&self, _: &Path, _: Option<String>) -> anyhow::Result<Snapshot> {
             unimplemented!()
         }
 
         async fn undo_snapshot(&self, _: &Path) -> anyhow::Result<()> {
             unimplemented!()
         }
+
+        async fn restore_to(
+            &self,
+            _: &Path,
+            _: SnapshotSelector,
+            _: &Path,
+            _: bool,
+        ) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        async fn list_all(&self) -> anyhow::Result<Vec<SnapshotSummary>> {
+            unimplemented!()
+        }
+
+        async fn list_snapshots(&self, _: &Path) -> anyhow::Result<Vec<Snapshot>> {
+            unimplemented!()
+        }
+
+        async fn diff_snapshots(&self, _: &Path, _: isize, _: isize) -> anyhow::Result<String> {
+            unimplemented!()
+        }
+
+        async fn diff_snapshot_and_current(&self, _: &Path, _: isize) -> anyhow::Result<String> {
+            unimplemented!()
+        }
+
+        async fn create_tree_snapshot(
+            &self,
+            root: &Path,
+            _: &[String],
+            _: &[String],
+        ) -> anyhow::Result<TreeSnapshotInfo> {
+            Ok(TreeSnapshotInfo {
+                id: SnapshotId::from(uuid::Uuid::nil()),
+                root: root.to_path_buf(),
+                timestamp: Default::default(),
+                file_count: 0,
+                total_bytes: 0,
+                skipped: vec![],
+            })
+        }
+
+        async fn restore_tree(&self, _: &SnapshotId) -> anyhow::Result<TreeRestoreReport> {
+            unimplemented!()
+        }
+
+        async fn verify(&self, _: &Path) -> anyhow::Result<Vec<VerificationIssue>> {
+            unimplemented!()
+        }
+
+        async fn verify_all(&self) -> anyhow::Result<Vec<VerificationIssue>> {
+            unimplemented!()
+        }
+
+        async fn tag_snapshot(&self, _: &Path, _: &str, _: &str) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        async fn restore_by_tag(&self, _: &Path, _: &str) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        async fn purge_excess(&self, _: &Path, _: usize) -> anyhow::Result<usize> {
+            unimplemented!()
+        }
+
+        async fn purge(&self, _: PurgePolicy) -> anyhow::Result<usize> {
+            unimplemented!()
+        }
+
+        async fn export_archive(&self, _: &Path, _: &Path) -> anyhow::Result<ExportReport> {
+            unimplemented!()
+        }
+
+        async fn import_archive(
+            &self,
+            _: &Path,
+            _: &Path,
+            _: ImportStrategy,
+        ) -> anyhow::Result<ImportReport> {
+            unimplemented!()
+        }
     }
 
     #[async_trait::async_trait]
@@ -319,14 +476,17 @@ pub mod tests {
         }
     }
 
+    /// Stands in for an MCP server with a single tool that echoes its input
     #[async_trait::async_trait]
     impl McpClient for () {
         async fn list(&self) -> anyhow::Result<Vec<ToolDefinition>> {
-            Ok(vec![])
+            Ok(vec![
+                ToolDefinition::new("echo").description("Echoes its input")
+            ])
         }
 
-        async fn call(&self, _: &ToolName, _: Value) -> anyhow::Result<ToolOutput> {
-            Ok(ToolOutput::default())
+        async fn call(&self, tool_name: &ToolName, input: Value) -> anyhow::Result<ToolOutput> {
+            Ok(ToolOutput::text(format!("{}: {input}", tool_name.as_str())))
         }
     }
 
@@ -345,10 +505,44 @@ pub mod tests {
             &self,
             command: String,
             working_dir: PathBuf,
+            env: CommandEnv,
+            _: Option<Duration>,
+            output_lines: Option<UnboundedSender<OutputLine>>,
         ) -> anyhow::Result<CommandOutput> {
             // For test purposes, we'll create outputs that match what the shell tests
             // expect Check for common command patterns
-            if command == "echo 'Hello, World!'" {
+            if let Some(name) = command.strip_prefix("printenv ") {
+                // Sees the environment the command would run with
+                let value = env.vars.get(name).cloned().or_else(|| {
+                    (!env.removed.iter().any(|removed| removed == name))
+                        .then(|| std::env::var(name).ok())
+                        .flatten()
+                });
+                return Ok(CommandOutput {
+                    stdout: value.iter().map(|value| format!("{value}\n")).collect(),
+                    stderr: "".to_string(),
+                    command,
+                    exit_code: Some(if value.is_some() { 0 } else { 1 }),
+                    timed_out: None,
+                });
+            } else if let Some(count) = command.strip_prefix("seq ") {
+                // Prints a line at a time, like a command that takes a while
+                let mut stdout = String::new();
+                for i in 1..=count.trim().parse::<u32>()? {
+                    if let Some(output_lines) = &output_lines {
+                        output_lines.send(OutputLine::new(OutputStream::Stdout, i))?;
+                    }
+                    stdout.push_str(&format!("{i}\n"));
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                return Ok(CommandOutput {
+                    stdout,
+                    stderr: "".to_string(),
+                    command,
+                    exit_code: Some(0),
+                    timed_out: None,
+                });
+            } else if command == "echo 'Hello, World!'" {
                 // When the test_shell_echo looks for this specific command
                 // It's expecting to see "Mock command executed successfully"
                 return Ok(CommandOutput {
@@ -356,6 +550,7 @@ pub mod tests {
                     stderr: "".to_string(),
                     command,
                     exit_code: Some(0),
+                    timed_out: None,
                 });
             } else if command.contains("echo") {
                 if command.contains(">") && command.contains(">&2") {
@@ -375,6 +570,7 @@ pub mod tests {
                         stderr: stderr.to_string(),
                         command,
                         exit_code: Some(0),
+                        timed_out: None,
                     });
                 } else if command.contains(">&2") {
                     // Command with only stderr
@@ -385,6 +581,7 @@ pub mod tests {
                         stderr: format!("{content}\n"),
                         command,
                         exit_code: Some(0),
+                        timed_out: None,
                     });
                 } else {
                     // Standard echo command
@@ -413,6 +610,7 @@ pub mod tests {
                         stderr: "".to_string(),
                         command,
                         exit_code: Some(0),
+                        timed_out: None,
                     });
                 }
             } else if command == "pwd" || command == "cd" {
@@ -422,6 +620,7 @@ pub mod tests {
                     stderr: "".to_string(),
                     command,
                     exit_code: Some(0),
+                    timed_out: None,
                 });
             } else if command == "true" {
                 // true command returns success with no output
@@ -430,6 +629,16 @@ pub mod tests {
                     stderr: "".to_string(),
                     command,
                     exit_code: Some(0),
+                    timed_out: None,
+                });
+            } else if let Some(code) = command.strip_prefix("exit ") {
+                // exit command returns the requested exit code with no output
+                return Ok(CommandOutput {
+                    stdout: "".to_string(),
+                    stderr: "".to_string(),
+                    exit_code: code.trim().parse().ok(),
+                    command,
+                    timed_out: None,
                 });
             } else if command.starts_with("/bin/ls") || command.contains("whoami") {
                 // Full path commands
@@ -438,6 +647,7 @@ pub mod tests {
                     stderr: "".to_string(),
                     command,
                     exit_code: Some(0),
+                    timed_out: None,
                 });
             } else if command == "non_existent_command" {
                 // Command not found
@@ -446,6 +656,7 @@ pub mod tests {
                     stderr: "command not found: non_existent_command\n".to_string(),
                     command,
                     exit_code: Some(-1),
+                    timed_out: None,
                 });
             }
 
@@ -455,6 +666,7 @@ pub mod tests {
                 stderr: "".to_string(),
                 command,
                 exit_code: Some(0),
+                timed_out: None,
             })
         }
 
@@ -503,6 +715,9 @@ pub mod tests {
         type FsReadService = MockFileService;
         type FsWriteService = MockFileService;
         type FsRemoveService = MockFileService;
+        type FsMoveService = MockFileService;
+        type FsCopyService = MockFileService;
+        type FsWatchService = MockFileService;
         type FsMetaService = MockFileService;
         type FsCreateDirsService = MockFileService;
         type FsSnapshotService = MockSnapService;
@@ -534,6 +749,18 @@ pub mod tests {
             &self.file_service
         }
 
+        fn file_move_service(&self) -> &Self::FsMoveService {
+            &self.file_service
+        }
+
+        fn file_copy_service(&self) -> &Self::FsCopyService {
+            &self.file_service
+        }
+
+        fn file_watch_service(&self) -> &Self::FsWatchService {
+            &self.file_service
+        }
+
         fn create_dirs_service(&self) -> &Self::FsCreateDirsService {
             &self.file_service
         }
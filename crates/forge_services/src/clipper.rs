@@ -34,6 +34,16 @@ impl ClipperResult<'_> {
             .as_ref()
             .map(|range| &self.actual[range.clone()])
     }
+
+    /// Get the content left out between the prefix and the suffix
+    pub fn omitted_content(&self) -> &str {
+        let start = self.prefix.as_ref().map_or(0, |range| range.end);
+        let end = self
+            .suffix
+            .as_ref()
+            .map_or(self.actual.len(), |range| range.start);
+        &self.actual[start..end]
+    }
 }
 
 /// A strategy for truncating text content.
@@ -52,6 +62,18 @@ pub enum Clipper {
 
     /// Retains data from the end up to the specified character count
     Suffix(usize),
+
+    /// Retains whole lines from both the beginning and end of the content,
+    /// each side getting half of the budget
+    /// First parameter is the maximum line count
+    /// Second parameter is the maximum byte count
+    Lines(usize, usize),
+
+    /// Retains whole lines from both the beginning and end of the content,
+    /// with separate budgets for each side
+    /// First and second parameters are the line counts of the beginning and end
+    /// Third and fourth parameters are the byte counts of the beginning and end
+    LinesStartEnd(usize, usize, usize, usize),
 }
 
 impl Default for Clipper {
@@ -81,6 +103,23 @@ impl Clipper {
         Self::PrefixSuffix(start, end)
     }
 
+    /// Creates a Clipper that keeps the leading and trailing lines of the
+    /// content within the specified line and byte budgets
+    pub fn from_lines(max_lines: usize, max_bytes: usize) -> Clipper {
+        Self::Lines(max_lines, max_bytes)
+    }
+
+    /// Creates a Clipper that keeps the given numbers of leading and trailing
+    /// lines of the content, each side within its own byte budget
+    pub fn from_start_end_lines(
+        start_lines: usize,
+        end_lines: usize,
+        start_bytes: usize,
+        end_bytes: usize,
+    ) -> Clipper {
+        Self::LinesStartEnd(start_lines, end_lines, start_bytes, end_bytes)
+    }
+
     /// Apply this truncation strategy to the given content
     ///
     /// # Arguments
@@ -104,6 +143,14 @@ impl Clipper {
             Clipper::PrefixSuffix(prefix_limit, suffix_limit) => {
                 self.apply_prefix_suffix(content, char_count, prefix_limit, suffix_limit)
             }
+            Clipper::Lines(max_lines, max_bytes) => self.apply_lines(
+                content,
+                (max_lines - max_lines / 2, max_lines / 2),
+                (max_bytes - max_bytes / 2, max_bytes / 2),
+            ),
+            Clipper::LinesStartEnd(head_lines, tail_lines, head_bytes, tail_bytes) => {
+                self.apply_lines(content, (head_lines, tail_lines), (head_bytes, tail_bytes))
+            }
         }
     }
 
@@ -183,6 +230,66 @@ impl Clipper {
             actual: content,
         }
     }
+
+    /// Helper method to keep the leading and trailing lines of the content. A
+    /// line that doesn't fit the byte budget on its own is cut at a character
+    /// boundary so that some content is always kept.
+    fn apply_lines<'a>(
+        &self,
+        content: &'a str,
+        (head_lines, tail_lines): (usize, usize),
+        (head_bytes, tail_bytes): (usize, usize),
+    ) -> ClipperResult<'a> {
+        let line_count = content.lines().count();
+        if line_count <= head_lines + tail_lines && content.len() <= head_bytes + tail_bytes {
+            return ClipperResult { prefix: None, suffix: None, actual: content };
+        }
+
+        let mut prefix_end = content
+            .split_inclusive('\n')
+            .take(head_lines)
+            .scan(0, |end, line| {
+                *end += line.len();
+                Some(*end)
+            })
+            .take_while(|end| *end <= head_bytes)
+            .last()
+            .unwrap_or(0);
+        if prefix_end == 0 && head_lines > 0 {
+            prefix_end = content
+                .char_indices()
+                .map(|(idx, _)| idx)
+                .take_while(|idx| *idx <= head_bytes)
+                .last()
+                .unwrap_or(0);
+        }
+
+        let mut suffix_start = content
+            .split_inclusive('\n')
+            .rev()
+            .take(tail_lines)
+            .scan(content.len(), |start, line| {
+                *start -= line.len();
+                Some(*start)
+            })
+            .take_while(|start| content.len() - start <= tail_bytes && *start >= prefix_end)
+            .last()
+            .unwrap_or(content.len());
+        if suffix_start == content.len() && tail_lines > 0 {
+            suffix_start = content
+                .char_indices()
+                .map(|(idx, _)| idx)
+                .find(|idx| content.len() - idx <= tail_bytes)
+                .unwrap_or(content.len())
+                .max(prefix_end);
+        }
+
+        ClipperResult {
+            prefix: Some(0..prefix_end),
+            suffix: (suffix_start < content.len()).then_some(suffix_start..content.len()),
+            actual: content,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -259,4 +366,96 @@ mod tests {
         assert!(result.suffix.is_none());
         assert_eq!(result.actual, content);
     }
+
+    fn lines(count: usize) -> String {
+        (1..=count).map(|i| format!("line {i}\n")).collect()
+    }
+
+    #[test]
+    fn test_lines_within_line_budget() {
+        let content = lines(10);
+
+        let result = Clipper::from_lines(10, 1_000).clip(&content);
+
+        assert!(!result.is_truncated());
+    }
+
+    #[test]
+    fn test_lines_one_over_line_budget() {
+        let content = lines(11);
+
+        let result = Clipper::from_lines(10, 1_000).clip(&content);
+
+        assert_eq!(result.prefix_content(), Some(lines(5).as_str()));
+        assert_eq!(
+            result.suffix_content(),
+            Some("line 7\nline 8\nline 9\nline 10\nline 11\n")
+        );
+        assert_eq!(result.omitted_content(), "line 6\n");
+    }
+
+    #[test]
+    fn test_lines_odd_line_budget_favours_head() {
+        let content = lines(10);
+
+        let result = Clipper::from_lines(3, 1_000).clip(&content);
+
+        assert_eq!(result.prefix_content(), Some("line 1\nline 2\n"));
+        assert_eq!(result.suffix_content(), Some("line 10\n"));
+    }
+
+    #[test]
+    fn test_lines_over_byte_budget() {
+        // Every line is 7 bytes, so each half of the budget fits exactly 2 lines
+        let content = lines(9);
+
+        let result = Clipper::from_lines(100, 28).clip(&content);
+
+        assert_eq!(result.prefix_content(), Some("line 1\nline 2\n"));
+        assert_eq!(result.suffix_content(), Some("line 8\nline 9\n"));
+        assert_eq!(result.omitted_content().lines().count(), 5);
+    }
+
+    #[test]
+    fn test_lines_at_byte_budget() {
+        let content = lines(9);
+
+        let result = Clipper::from_lines(100, content.len()).clip(&content);
+
+        assert!(!result.is_truncated());
+    }
+
+    #[test]
+    fn test_lines_single_long_line_is_cut_at_char_boundary() {
+        let content = "é".repeat(20); // 40 bytes, 2 per char
+
+        let result = Clipper::from_lines(10, 12).clip(&content);
+
+        assert_eq!(result.prefix_content(), Some("ééé"));
+        assert_eq!(result.suffix_content(), Some("ééé"));
+        assert_eq!(result.omitted_content(), "é".repeat(14));
+    }
+
+    #[test]
+    fn test_lines_start_end() {
+        let content = lines(20);
+
+        let result = Clipper::from_start_end_lines(2, 5, 1000, 1000).clip(&content);
+
+        assert_eq!(result.prefix_content(), Some("line 1\nline 2\n"));
+        assert_eq!(
+            result.suffix_content(),
+            Some("line 16\nline 17\nline 18\nline 19\nline 20\n")
+        );
+        assert_eq!(result.omitted_content().lines().count(), 13);
+    }
+
+    #[test]
+    fn test_lines_start_end_within_budget() {
+        let content = lines(7);
+
+        let result = Clipper::from_start_end_lines(2, 5, 1000, 1000).clip(&content);
+
+        assert!(!result.is_truncated());
+    }
 }
@@ -57,12 +57,19 @@ impl<T: TemplateService, P: ProviderService> ForgeCompactionService<T, P> {
     ) -> Result<Context> {
         let (start, end) = sequence;
 
-        // Extract the sequence to summarize
-        let sequence_messages = &context.messages[start..=end].to_vec();
+        // Extract the sequence to summarize, pinned messages are kept verbatim
+        // instead of being summarized
+        let (pinned, sequence_messages): (Vec<_>, Vec<_>) = context.messages[start..=end]
+            .iter()
+            .cloned()
+            .partition(|message| message.is_pinned());
+        if sequence_messages.is_empty() {
+            return Ok(context);
+        }
 
         // Generate summary for this sequence
         let summary = self
-            .generate_summary_for_sequence(compact, sequence_messages)
+            .generate_summary_for_sequence(compact, &sequence_messages)
             .await?;
 
         // Log the summary for debugging
@@ -79,11 +86,13 @@ impl<T: TemplateService, P: ProviderService> ForgeCompactionService<T, P> {
         "#
         );
 
-        // Replace the sequence with a single summary message using splice
-        // This removes the sequence and inserts the summary message in-place
+        // Replace the sequence with the pinned messages followed by a single summary
+        // message using splice
         context.messages.splice(
             start..=end,
-            std::iter::once(ContextMessage::assistant(summary, None)),
+            pinned
+                .into_iter()
+                .chain(std::iter::once(ContextMessage::assistant(summary, None))),
         );
 
         Ok(context)
@@ -241,7 +250,9 @@ impl<T: TemplateService, P: ProviderService> CompactionService for ForgeCompacti
 
 #[cfg(test)]
 mod tests {
-    use forge_domain::{ModelId, ToolCallFull, ToolCallId, ToolName, ToolResult};
+    use forge_domain::{
+        estimate_token_count, ModelId, ToolCallFull, ToolCallId, ToolName, ToolResult,
+    };
     use pretty_assertions::assert_eq;
     use serde_json::json;
 
@@ -974,4 +985,141 @@ mod tests {
         let result = find_sequence(&context, 0);
         assert!(result.is_none());
     }
+
+    struct MockTemplate;
+
+    impl TemplateService for MockTemplate {
+        fn render(
+            &self,
+            template: impl ToString,
+            _object: &impl serde::Serialize,
+        ) -> anyhow::Result<String> {
+            Ok(template.to_string())
+        }
+    }
+
+    /// Records every summarization request it receives
+    #[derive(Default)]
+    struct MockProvider {
+        requests: std::sync::Mutex<Vec<(ModelId, Context)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderService for MockProvider {
+        async fn chat(
+            &self,
+            id: &ModelId,
+            context: Context,
+        ) -> forge_domain::ResultStream<ChatCompletionMessage, anyhow::Error> {
+            self.requests.lock().unwrap().push((id.clone(), context));
+            let message = ChatCompletionMessage::assistant(forge_domain::Content::full(
+                "<forge_context_summary>Read the files</forge_context_summary>",
+            ));
+            Ok(Box::pin(futures::stream::iter(vec![Ok(message)])))
+        }
+
+        async fn models(&self) -> anyhow::Result<Vec<forge_domain::Model>> {
+            Ok(vec![])
+        }
+
+        async fn model(&self, _model: &ModelId) -> anyhow::Result<Option<forge_domain::Model>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_long_context_compacted_below_threshold() {
+        let model_id = ModelId::new("gpt-4");
+        let compact = Compact::new(model_id.clone())
+            .context_window_ratio(0.5)
+            .retention_window(2usize);
+        let agent = Agent::new("test-agent").compact(compact.clone());
+        let context_length = 1_000;
+        let long_content = "x".repeat(400);
+        let fixture = (0..10).fold(
+            Context::default()
+                .add_message(ContextMessage::system("System prompt"))
+                .add_message(ContextMessage::user("Do the task", model_id.clone().into())),
+            |context, _| context.add_message(ContextMessage::assistant(&long_content, None)),
+        );
+        let tokens = |context: &Context| estimate_token_count(context.to_text().len()) as u64;
+        assert!(compact.should_compact(&fixture, tokens(&fixture), Some(context_length)));
+
+        let service =
+            ForgeCompactionService::new(Arc::new(MockTemplate), Arc::new(MockProvider::default()));
+        let actual = service.compact_context(&agent, fixture).await.unwrap();
+
+        assert!(!compact.should_compact(&actual, tokens(&actual), Some(context_length)));
+        assert_eq!(actual.messages.len(), 5);
+        assert!(actual.messages[0].has_role(Role::System));
+    }
+
+    #[tokio::test]
+    async fn test_summary_uses_workflow_summarize_config() {
+        let workflow = forge_domain::Workflow::new()
+            .agents(vec![
+                Agent::new("test-agent").compact(Compact::new(ModelId::new("agent-model")))
+            ])
+            .summarize(
+                forge_domain::SummarizeConfig::default()
+                    .model(ModelId::new("cheap-model"))
+                    .prompt("Summarize tersely"),
+            );
+        let conversation = forge_domain::Conversation::new(
+            forge_domain::ConversationId::generate(),
+            workflow,
+            vec![],
+        );
+        let agent = conversation
+            .get_agent(&forge_domain::AgentId::new("test-agent"))
+            .unwrap();
+        let fixture = Context::default()
+            .add_message(ContextMessage::user("Do the task", None))
+            .add_message(ContextMessage::assistant("Reading files", None))
+            .add_message(ContextMessage::assistant("Writing files", None));
+        let provider = Arc::new(MockProvider::default());
+        let service = ForgeCompactionService::new(Arc::new(MockTemplate), provider.clone());
+
+        service.compact_context(agent, fixture).await.unwrap();
+
+        let requests = provider.requests.lock().unwrap();
+        let (model, context) = &requests[0];
+        let actual = (model.clone(), context.messages.clone());
+        let expected = (
+            ModelId::new("cheap-model"),
+            vec![ContextMessage::user(
+                "Summarize tersely",
+                Some(ModelId::new("cheap-model")),
+            )],
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_message_survives_aggressive_compaction() {
+        let model_id = ModelId::new("gpt-4");
+        let agent = Agent::new("test-agent").compact(Compact::new(model_id.clone()));
+        let mut fixture = Context::default()
+            .add_message(ContextMessage::system("System prompt"))
+            .add_message(ContextMessage::user("Do the task", model_id.clone().into()))
+            .add_message(ContextMessage::assistant("Reading files", None))
+            .add_message(ContextMessage::user(
+                "Never touch the production database",
+                model_id.clone().into(),
+            ));
+        fixture.pin_last_user_message();
+        let fixture = fixture
+            .add_message(ContextMessage::assistant("Writing files", None))
+            .add_message(ContextMessage::user("Keep going", model_id.into()))
+            .add_message(ContextMessage::assistant("Running tests", None));
+        let pinned = fixture.messages[3].clone();
+
+        let service =
+            ForgeCompactionService::new(Arc::new(MockTemplate), Arc::new(MockProvider::default()));
+        let actual = service.compact_context(&agent, fixture).await.unwrap();
+
+        assert_eq!(actual.messages.len(), 4);
+        assert_eq!(actual.messages[2], pinned);
+        assert!(actual.messages[3].has_role(Role::Assistant));
+    }
 }
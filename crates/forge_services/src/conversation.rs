@@ -3,8 +3,8 @@ use std::sync::Arc;
 
 use anyhow::{Context as AnyhowContext, Result};
 use forge_domain::{
-    estimate_token_count, AgentId, CompactionResult, CompactionService, Conversation,
-    ConversationId, ConversationService, McpService, Workflow,
+    token_counter, AgentId, Compact, CompactionResult, CompactionService, Conversation,
+    ConversationId, ConversationService, HeuristicCounter, McpService, TokenCounter, Workflow,
 };
 use tokio::sync::Mutex;
 
@@ -80,7 +80,14 @@ impl<C: CompactionService, M: McpService> ConversationService for ForgeConversat
 
         // Identify the main agent and extract existing context
         let main_agent_id = AgentId::new(Conversation::MAIN_AGENT_NAME);
-        let agent = conversation.get_agent(&main_agent_id)?;
+        let mut agent = conversation.get_agent(&main_agent_id)?.clone();
+
+        // Compaction was requested explicitly, so it runs with the default
+        // settings when the agent doesn't configure any
+        if agent.compact.is_none() {
+            agent.compact = agent.model.clone().map(Compact::new);
+        }
+
         let context = conversation
             .state
             .get(&main_agent_id)
@@ -88,17 +95,21 @@ impl<C: CompactionService, M: McpService> ConversationService for ForgeConversat
             .unwrap_or_default();
 
         // Compute original metrics
-        let original_tokens = estimate_token_count(context.to_text().len());
+        let counter: Arc<dyn TokenCounter> = match agent.model.as_ref() {
+            Some(model) => token_counter(model),
+            None => Arc::new(HeuristicCounter),
+        };
+        let original_tokens = counter.estimate_tokens(&context);
         let original_messages = context.messages.len();
 
         // Perform compaction
         let new_context = self
             .compaction_service
-            .compact_context(agent, context.clone())
+            .compact_context(&agent, context.clone())
             .await?;
 
         // Compute compacted metrics
-        let compacted_tokens = estimate_token_count(new_context.to_text().len());
+        let compacted_tokens = counter.estimate_tokens(&new_context);
         let compacted_messages = new_context.messages.len();
 
         // Persist the updated context
@@ -118,3 +129,83 @@ impl<C: CompactionService, M: McpService> ConversationService for ForgeConversat
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::{
+        Agent, Context, ContextMessage, ModelId, Tool, ToolDefinition, ToolName, Workflow,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Replaces everything but the system prompt with a short summary
+    struct MockCompaction;
+
+    #[async_trait::async_trait]
+    impl CompactionService for MockCompaction {
+        async fn compact_context(&self, _: &Agent, context: Context) -> Result<Context> {
+            let mut messages = context.messages.into_iter().take(1).collect::<Vec<_>>();
+            messages.push(ContextMessage::user("Summary of the task", None));
+            Ok(Context::default().messages(messages))
+        }
+    }
+
+    struct MockMcp;
+
+    #[async_trait::async_trait]
+    impl McpService for MockMcp {
+        async fn list(&self) -> Result<Vec<ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn find(&self, _: &ToolName) -> Result<Option<Arc<Tool>>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compact_conversation_reduces_tokens() {
+        let service = ForgeConversationService::new(Arc::new(MockCompaction), Arc::new(MockMcp));
+        let workflow = Workflow::new().agents(vec![
+            Agent::new(Conversation::MAIN_AGENT_NAME).model(ModelId::new("gpt-4"))
+        ]);
+        let conversation = service.create(workflow).await.unwrap();
+        let main_agent_id = AgentId::new(Conversation::MAIN_AGENT_NAME);
+        let fixture = (0..10).fold(
+            Context::default().add_message(ContextMessage::system("System prompt")),
+            |context, i| {
+                context
+                    .add_message(ContextMessage::user(format!("Step {i}"), None))
+                    .add_message(ContextMessage::assistant("x".repeat(400), None))
+            },
+        );
+        service
+            .update(&conversation.id, |conversation| {
+                conversation
+                    .state
+                    .entry(main_agent_id.clone())
+                    .or_default()
+                    .context = Some(fixture);
+            })
+            .await
+            .unwrap();
+
+        let actual = service
+            .compact_conversation(&conversation.id)
+            .await
+            .unwrap();
+
+        assert!(actual.compacted_tokens < actual.original_tokens);
+        assert_eq!(
+            (actual.original_messages, actual.compacted_messages),
+            (21, 2)
+        );
+        let persisted = service.find(&conversation.id).await.unwrap().unwrap().state
+            [&main_agent_id]
+            .context
+            .clone()
+            .unwrap();
+        assert_eq!(persisted.messages.len(), 2);
+    }
+}
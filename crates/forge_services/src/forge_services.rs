@@ -24,7 +24,7 @@ type McpService<F> = ForgeMcpService<ForgeMcpManager<F>, F>;
 #[derive(Clone)]
 pub struct ForgeServices<F> {
     infra: Arc<F>,
-    tool_service: Arc<ForgeToolService<McpService<F>>>,
+    tool_service: Arc<ForgeToolService<F, McpService<F>>>,
     provider_service: Arc<ForgeProviderService>,
     conversation_service: Arc<
         ForgeConversationService<
@@ -76,7 +76,7 @@ impl<F: Infrastructure> ForgeServices<F> {
 }
 
 impl<F: Infrastructure> Services for ForgeServices<F> {
-    type ToolService = ForgeToolService<McpService<F>>;
+    type ToolService = ForgeToolService<F, McpService<F>>;
     type ProviderService = ForgeProviderService;
     type ConversationService = ForgeConversationService<Self::CompactionService, McpService<F>>;
     type TemplateService = ForgeTemplateService;
@@ -135,6 +135,9 @@ impl<F: Infrastructure> Infrastructure for ForgeServices<F> {
     type FsMetaService = F::FsMetaService;
     type FsSnapshotService = F::FsSnapshotService;
     type FsRemoveService = F::FsRemoveService;
+    type FsMoveService = F::FsMoveService;
+    type FsCopyService = F::FsCopyService;
+    type FsWatchService = F::FsWatchService;
     type FsCreateDirsService = F::FsCreateDirsService;
     type CommandExecutorService = F::CommandExecutorService;
     type InquireService = F::InquireService;
@@ -164,6 +167,18 @@ impl<F: Infrastructure> Infrastructure for ForgeServices<F> {
         self.infra.file_remove_service()
     }
 
+    fn file_move_service(&self) -> &Self::FsMoveService {
+        self.infra.file_move_service()
+    }
+
+    fn file_copy_service(&self) -> &Self::FsCopyService {
+        self.infra.file_copy_service()
+    }
+
+    fn file_watch_service(&self) -> &Self::FsWatchService {
+        self.infra.file_watch_service()
+    }
+
     fn create_dirs_service(&self) -> &Self::FsCreateDirsService {
         self.infra.create_dirs_service()
     }
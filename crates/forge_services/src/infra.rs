@@ -1,18 +1,32 @@
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::Result;
 use bytes::Bytes;
 use forge_domain::{
-    CommandOutput, EnvironmentService, McpServerConfig, ToolDefinition, ToolName, ToolOutput,
+    CommandEnv, CommandOutput, EnvironmentService, FileEvent, McpServerConfig, OutputLine,
+    ToolDefinition, ToolName, ToolOutput,
 };
-use forge_snaps::Snapshot;
+use forge_snaps::{
+    ExportReport, ImportReport, ImportStrategy, PurgePolicy, Snapshot, SnapshotId,
+    SnapshotSelector, SnapshotSummary, TreeRestoreReport, TreeSnapshotInfo, VerificationIssue,
+};
+use futures::stream::BoxStream;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Returns the URL given in place of a file path, when `path` is an HTTP or
+/// HTTPS URL
+pub fn http_url(path: &Path) -> Option<reqwest::Url> {
+    path.to_str()
+        .and_then(|path| reqwest::Url::parse(path).ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+}
 
-/// Repository for accessing system environment information
-/// This uses the EnvironmentService trait from forge_domain
 /// A service for reading files from the filesystem.
 ///
 /// This trait provides an abstraction over file reading operations, allowing
-/// for both real file system access and test mocking.
+/// for both real file system access and test mocking. Implementations may
+/// also accept HTTP and HTTPS URLs in place of paths, see [`http_url`].
 #[async_trait::async_trait]
 pub trait FsReadService: Send + Sync {
     /// Reads the content of a file at the specified path.
@@ -44,6 +58,16 @@ pub trait FsReadService: Send + Sync {
         start_char: u64,
         end_char: u64,
     ) -> anyhow::Result<(String, forge_fs::FileInfo)>;
+
+    /// Reads a file in the encoding detected for it, such as UTF-16 or
+    /// latin-1, returning the content along with the encoding so that edits
+    /// can be written back in it.
+    async fn read_with_encoding(
+        &self,
+        path: &Path,
+    ) -> anyhow::Result<(String, &'static forge_fs::Encoding)> {
+        Ok(forge_fs::decode(&self.read(path).await?))
+    }
 }
 
 #[async_trait::async_trait]
@@ -51,6 +75,35 @@ pub trait FsWriteService: Send + Sync {
     /// Writes the content of a file at the specified path.
     async fn write(&self, path: &Path, contents: Bytes) -> anyhow::Result<()>;
 
+    /// Writes the content of a file at the specified path, recording `cause`
+    /// on the snapshot taken of its previous content.
+    async fn write_with_cause(
+        &self,
+        path: &Path,
+        contents: Bytes,
+        _cause: &str,
+    ) -> anyhow::Result<()> {
+        self.write(path, contents).await
+    }
+
+    /// Writes content to the file at the specified path in `encoding`,
+    /// recording `cause` on the snapshot taken of its previous content.
+    async fn write_with_encoding(
+        &self,
+        path: &Path,
+        content: &str,
+        encoding: &'static forge_fs::Encoding,
+        cause: &str,
+    ) -> anyhow::Result<()> {
+        let contents = forge_fs::encode(content, encoding)?;
+        self.write_with_cause(path, Bytes::from(contents), cause)
+            .await
+    }
+
+    /// Appends content to the end of the file at the specified path, creating
+    /// the file if it doesn't exist.
+    async fn append(&self, path: &Path, contents: Bytes) -> anyhow::Result<()>;
+
     /// Writes content to a temporary file with the given prefix and extension,
     /// and returns its path. The file will be kept (not deleted) after
     /// creation.
@@ -66,6 +119,37 @@ pub trait FsWriteService: Send + Sync {
 pub trait FileRemoveService: Send + Sync {
     /// Removes a file at the specified path.
     async fn remove(&self, path: &Path) -> anyhow::Result<()>;
+
+    /// Removes a file at the specified path, recording `cause` on the
+    /// snapshot taken of its content.
+    async fn remove_with_cause(&self, path: &Path, _cause: &str) -> anyhow::Result<()> {
+        self.remove(path).await
+    }
+}
+
+#[async_trait::async_trait]
+pub trait FsMoveService: Send + Sync {
+    /// Moves the file at `from` to `to`, replacing any file at `to`. Falls
+    /// back to copying and removing the original when the paths are on
+    /// different devices.
+    async fn rename(&self, from: &Path, to: &Path) -> anyhow::Result<()>;
+}
+
+#[async_trait::async_trait]
+pub trait FsCopyService: Send + Sync {
+    /// Copies the content of the file at `from` to `to`, replacing any file at
+    /// `to`. The permissions of the original are kept where the platform
+    /// supports it.
+    async fn copy(&self, from: &Path, to: &Path) -> anyhow::Result<()>;
+}
+
+#[async_trait::async_trait]
+pub trait FsWatchService: Send + Sync {
+    /// Watches the file at `path`, or everything below it when it is a
+    /// directory, for files being created, modified or removed. Successive
+    /// events on the same path are debounced into one. Watching stops when
+    /// the stream is dropped.
+    async fn watch(&self, path: &Path) -> anyhow::Result<BoxStream<'static, FileEvent>>;
 }
 
 #[async_trait::async_trait]
@@ -82,21 +166,107 @@ pub trait FsCreateDirsService {
 /// Service for managing file snapshots
 #[async_trait::async_trait]
 pub trait FsSnapshotService: Send + Sync {
-    // Creation
-    async fn create_snapshot(&self, file_path: &Path) -> Result<Snapshot>;
+    /// Snapshots the current content of the file, recording `cause` as the
+    /// reason it was taken
+    async fn create_snapshot(&self, file_path: &Path, cause: Option<String>) -> Result<Snapshot>;
 
     /// Restores the most recent snapshot for the given file path
     async fn undo_snapshot(&self, file_path: &Path) -> Result<()>;
+
+    /// Writes the selected snapshot of the file to `dest` without touching the
+    /// original file. Fails if `dest` exists unless `overwrite` is set.
+    async fn restore_to(
+        &self,
+        file_path: &Path,
+        selector: SnapshotSelector,
+        dest: &Path,
+        overwrite: bool,
+    ) -> Result<()>;
+
+    /// Tags the snapshot of the file taken at `timestamp`. A tag names at most
+    /// one snapshot of a file.
+    async fn tag_snapshot(&self, file_path: &Path, timestamp: &str, tag: &str) -> Result<()>;
+
+    /// Restores the file in place to the snapshot tagged `tag`
+    async fn restore_by_tag(&self, file_path: &Path, tag: &str) -> Result<()>;
+
+    /// Summarizes the snapshots of every file, most recently snapshotted
+    /// files first
+    async fn list_all(&self) -> Result<Vec<SnapshotSummary>>;
+
+    /// Lists the snapshots of the file, most recent first
+    async fn list_snapshots(&self, file_path: &Path) -> Result<Vec<Snapshot>>;
+
+    /// Returns a unified diff from the snapshot of the file at `from_index`
+    /// to the one at `to_index`, empty when their contents are the same. `0`
+    /// is the most recent snapshot, and negative indices count back from the
+    /// oldest one, which is `-1`.
+    async fn diff_snapshots(
+        &self,
+        file_path: &Path,
+        from_index: isize,
+        to_index: isize,
+    ) -> Result<String>;
+
+    /// Returns a unified diff from the snapshot of the file at `index` to the
+    /// current content of the file, indexed as in `diff_snapshots`
+    async fn diff_snapshot_and_current(&self, file_path: &Path, index: isize) -> Result<String>;
+
+    /// Snapshots every file under `root` matching the include globs (all files
+    /// when empty) and none of the exclude globs
+    async fn create_tree_snapshot(
+        &self,
+        root: &Path,
+        include_globs: &[String],
+        exclude_globs: &[String],
+    ) -> Result<TreeSnapshotInfo>;
+
+    /// Restores the files captured by a tree snapshot
+    async fn restore_tree(&self, id: &SnapshotId) -> Result<TreeRestoreReport>;
+
+    /// Checks the snapshots of the file against the payloads they refer to
+    async fn verify(&self, file_path: &Path) -> Result<Vec<VerificationIssue>>;
+
+    /// Checks every snapshot against the payloads they refer to and reports
+    /// payloads no snapshot refers to
+    async fn verify_all(&self) -> Result<Vec<VerificationIssue>>;
+
+    /// Removes all but the `keep` newest snapshots of the file, returning the
+    /// number of snapshots deleted
+    async fn purge_excess(&self, file_path: &Path, keep: usize) -> Result<usize>;
+
+    /// Removes the snapshots of every file that break the limits of `policy`,
+    /// returning the number of snapshots deleted
+    async fn purge(&self, policy: PurgePolicy) -> Result<usize>;
+
+    /// Writes the snapshots of files under `root` to an archive at `dest`
+    async fn export_archive(&self, dest: &Path, root: &Path) -> Result<ExportReport>;
+
+    /// Imports the snapshots of an archive written by `export_archive`,
+    /// remapping their paths onto `root`
+    async fn import_archive(
+        &self,
+        src: &Path,
+        root: &Path,
+        strategy: ImportStrategy,
+    ) -> Result<ImportReport>;
 }
 
 /// Service for executing shell commands
 #[async_trait::async_trait]
 pub trait CommandExecutorService: Send + Sync {
-    /// Executes a shell command and returns the output
+    /// Executes a shell command and returns the output. The command inherits
+    /// the environment of forge with the changes in `env`. A command that
+    /// runs longer than `timeout` is stopped along with every process it
+    /// started, returning the output printed until then. With `output_lines`,
+    /// each line is sent there as it's printed instead of to the terminal.
     async fn execute_command(
         &self,
         command: String,
         working_dir: PathBuf,
+        env: CommandEnv,
+        timeout: Option<Duration>,
+        output_lines: Option<UnboundedSender<OutputLine>>,
     ) -> anyhow::Result<CommandOutput>;
 
     /// execute the shell command on present stdio.
@@ -147,6 +317,9 @@ pub trait Infrastructure: Send + Sync + Clone + 'static {
     type FsMetaService: FsMetaService;
     type FsReadService: FsReadService;
     type FsRemoveService: FileRemoveService;
+    type FsMoveService: FsMoveService;
+    type FsCopyService: FsCopyService;
+    type FsWatchService: FsWatchService;
     type FsSnapshotService: FsSnapshotService;
     type FsWriteService: FsWriteService;
     type FsCreateDirsService: FsCreateDirsService;
@@ -158,6 +331,9 @@ pub trait Infrastructure: Send + Sync + Clone + 'static {
     fn file_meta_service(&self) -> &Self::FsMetaService;
     fn file_read_service(&self) -> &Self::FsReadService;
     fn file_remove_service(&self) -> &Self::FsRemoveService;
+    fn file_move_service(&self) -> &Self::FsMoveService;
+    fn file_copy_service(&self) -> &Self::FsCopyService;
+    fn file_watch_service(&self) -> &Self::FsWatchService;
     fn file_snapshot_service(&self) -> &Self::FsSnapshotService;
     fn file_write_service(&self) -> &Self::FsWriteService;
     fn create_dirs_service(&self) -> &Self::FsCreateDirsService;
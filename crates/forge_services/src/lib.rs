@@ -12,9 +12,11 @@ mod template;
 mod tool_service;
 mod tools;
 mod utils;
+mod web;
 mod workflow;
 
 pub use clipper::*;
 pub use forge_services::*;
 pub use infra::*;
 pub use suggestion::*;
+pub use web::WebClient;
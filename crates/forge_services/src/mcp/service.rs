@@ -127,3 +127,75 @@ impl<R: McpConfigManager, I: Infrastructure> McpService for ForgeMcpService<R, I
         self.find(name).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use forge_domain::{Scope, ToolCallContext};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+    use crate::attachment::tests::MockInfrastructure;
+    use crate::utils::ToolContentExtension;
+
+    struct StubManager(McpConfig);
+
+    #[async_trait::async_trait]
+    impl McpConfigManager for StubManager {
+        async fn read(&self) -> anyhow::Result<McpConfig> {
+            Ok(self.0.clone())
+        }
+
+        async fn write(&self, _: &McpConfig, _: &Scope) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+    }
+
+    /// Connects to a stub server whose only tool echoes its input
+    fn fixture() -> ForgeMcpService<StubManager, MockInfrastructure> {
+        let config = McpConfig::from(BTreeMap::from([(
+            "stub".to_string(),
+            McpServerConfig::new_stdio("stub-server", vec![], None),
+        )]));
+        ForgeMcpService::new(
+            Arc::new(StubManager(config)),
+            Arc::new(MockInfrastructure::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_list_prefixes_remote_tools() {
+        let actual: Vec<_> = fixture()
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|definition| definition.name)
+            .collect();
+
+        let expected = vec![ToolName::new("mcp_stub_tool_echo")];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_call_is_proxied_to_remote_tool() {
+        let service = fixture();
+        let tool = service
+            .find(&ToolName::new("mcp_stub_tool_echo"))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let actual = tool
+            .executable
+            .call(ToolCallContext::default(), json!({"message": "hello"}))
+            .await
+            .unwrap()
+            .into_string();
+
+        let expected = r#"echo: {"message":"hello"}"#;
+        assert_eq!(actual, expected);
+    }
+}
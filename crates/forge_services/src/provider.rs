@@ -2,30 +2,63 @@ use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use forge_domain::{
-    ChatCompletionMessage, Context as ChatContext, EnvironmentService, Model, ModelId,
-    ProviderService, ResultStream,
+    ChatCompletionMessage, Context as ChatContext, Environment, EnvironmentService, Model, ModelId,
+    Provider, ProviderService, ResultStream,
 };
-use forge_provider::Client;
+use forge_provider::{Client, CostAccumulator, FallbackProvider, FileRequestLogger, RetryConfig};
 
 use crate::Infrastructure;
 
 #[derive(Clone)]
 pub struct ForgeProviderService {
     // The provider service implementation
-    client: Arc<Client>,
+    client: Arc<dyn ProviderService>,
 }
 
 impl ForgeProviderService {
     pub fn new<F: Infrastructure>(infra: Arc<F>) -> Self {
         let infra = infra.clone();
         let env = infra.environment_service().get_environment();
-        let provider = env.provider.clone();
-        Self {
-            client: Arc::new(Client::new(provider, env.retry_config.retry_status_codes).unwrap()),
-        }
+
+        let client: Arc<dyn ProviderService> = match env.provider_fallback {
+            Some(strategy) if !env.fallback_providers.is_empty() => {
+                let providers = std::iter::once(&env.provider)
+                    .chain(&env.fallback_providers)
+                    .map(|provider| Box::new(client(&env, provider)) as Box<dyn ProviderService>)
+                    .collect();
+                Arc::new(FallbackProvider::new(providers, strategy))
+            }
+            _ => Arc::new(client(&env, &env.provider)),
+        };
+
+        Self { client }
     }
 }
 
+/// Builds the client talking to `provider`
+fn client(env: &Environment, provider: &Provider) -> Client {
+    let mut client = Client::new(
+        provider.clone(),
+        env.retry_config.retry_status_codes.clone(),
+    )
+    .unwrap()
+    .with_retry_config(RetryConfig::from(&env.retry_config));
+
+    // Captures the raw provider traffic for debugging integration issues
+    if std::env::var("FORGE_LOG_REQUESTS").is_ok_and(|value| value == "1") {
+        let logger = FileRequestLogger::new(env.log_path().join("requests.ndjson"));
+        client = client.with_request_logger(Arc::new(logger));
+    }
+
+    // OpenRouter publishes the price of its models, so the cost of each
+    // response can be reported alongside its usage
+    if provider.is_open_router() || provider.is_antinomy() {
+        client = client.with_cost_accumulator(Arc::new(CostAccumulator::new()));
+    }
+
+    client
+}
+
 #[async_trait::async_trait]
 impl ProviderService for ForgeProviderService {
     async fn chat(
@@ -18,13 +18,10 @@ impl<F> ForgeSuggestionService<F> {
 
 impl<F: Infrastructure> ForgeSuggestionService<F> {
     async fn get_suggestions(&self) -> Result<Vec<File>> {
-        let cwd = self
-            .domain
-            .environment_service()
-            .get_environment()
-            .cwd
-            .clone();
-        let walker = Walker::max_all().cwd(cwd);
+        let env = self.domain.environment_service().get_environment();
+        let walker = Walker::max_all()
+            .cwd(env.cwd)
+            .ignore_patterns(env.ignore_patterns);
 
         let files = walker.get().await?;
         Ok(files
@@ -3,34 +3,39 @@ use std::sync::Arc;
 
 use anyhow::Context as _;
 use forge_domain::{
-    McpService, Tool, ToolCallContext, ToolCallFull, ToolDefinition, ToolName, ToolOutput,
-    ToolResult, ToolService,
+    redact_credentials, EnvironmentService, McpService, Tool, ToolCallContext, ToolCallFull,
+    ToolDefinition, ToolName, ToolOutput, ToolOutputLimit, ToolOutputValue, ToolResult,
+    ToolService,
 };
 use tokio::time::{timeout, Duration};
 use tracing::debug;
 
 use crate::tools::ToolRegistry;
-use crate::Infrastructure;
-
-// Timeout duration for tool calls
-const TOOL_CALL_TIMEOUT: Duration = Duration::from_secs(300);
+use crate::{Clipper, FsWriteService, Infrastructure};
 
 #[derive(Clone)]
-pub struct ForgeToolService<M> {
+pub struct ForgeToolService<F, M> {
     tools: Arc<HashMap<ToolName, Arc<Tool>>>,
     mcp: Arc<M>,
+    infra: Arc<F>,
+    limit: ToolOutputLimit,
+    // Timeout duration for tool calls that don't define their own
+    timeout: Duration,
 }
 
-impl<M: McpService> ForgeToolService<M> {
-    pub fn new<F: Infrastructure>(infra: Arc<F>, mcp: Arc<M>) -> Self {
+impl<F: Infrastructure, M: McpService> ForgeToolService<F, M> {
+    pub fn new(infra: Arc<F>, mcp: Arc<M>) -> Self {
         let registry = ToolRegistry::new(infra.clone());
         let tools = registry.tools();
         let tools: HashMap<ToolName, Arc<Tool>> = tools
             .into_iter()
             .map(|tool| (tool.definition.name.clone(), Arc::new(tool)))
             .collect::<HashMap<_, _>>();
+        let env = infra.environment_service().get_environment();
+        let limit = env.tool_output_limit;
+        let timeout = Duration::from_secs(env.tool_timeout_secs);
 
-        Self { tools: Arc::new(tools), mcp }
+        Self { tools: Arc::new(tools), mcp, infra, limit, timeout }
     }
 
     /// Get a tool by its name. If the tool is not found, it returns an error
@@ -93,23 +98,33 @@ impl<M: McpService> ForgeToolService<M> {
         context: ToolCallContext,
         call: ToolCallFull,
     ) -> anyhow::Result<ToolOutput> {
-        debug!(tool_name = ?call.name, arguments = ?call.arguments, "Executing tool call");
+        debug!(tool_name = ?call.name, arguments = ?redact_credentials(&call.arguments), "Executing tool call");
 
         // Checks if tool is supported by agent and system.
         let tool = self.validate_tool_call(&context, &call.name).await?;
+        tool.definition.validate(&call.arguments)?;
+        match tool.executable.impact(&context, &call.arguments).await {
+            Ok(Some(impact)) => {
+                debug!(tool_name = ?call.name, %impact, "Estimated tool call impact")
+            }
+            Ok(None) => {}
+            Err(error) => {
+                debug!(tool_name = ?call.name, error = ?error, "Failed to estimate tool call impact")
+            }
+        }
 
-        let output = timeout(
-            TOOL_CALL_TIMEOUT,
-            tool.executable.call(context, call.arguments),
-        )
-        .await
-        .with_context(|| {
-            format!(
-                "Tool '{}' timed out after {} minutes",
-                call.name,
-                TOOL_CALL_TIMEOUT.as_secs() / 60
-            )
-        })?;
+        // Dropping the call future on expiry cancels the tool, processes spawned by
+        // it are killed on drop
+        let duration = tool.definition.timeout.unwrap_or(self.timeout);
+        let output = timeout(duration, tool.executable.call(context, call.arguments))
+            .await
+            .with_context(|| {
+                format!(
+                    "Tool '{}' timed out after {} seconds and was cancelled",
+                    call.name,
+                    duration.as_secs_f64()
+                )
+            })?;
 
         if let Err(error) = &output {
             tracing::warn!(cause = %error, tool = ?call.name, "Tool Call Failure");
@@ -117,14 +132,65 @@ impl<M: McpService> ForgeToolService<M> {
 
         output
     }
+
+    /// Clips text that exceeds the output budget in the middle, so that a
+    /// single huge output doesn't blow up the context
+    async fn clip(&self, mut output: ToolOutput) -> ToolOutput {
+        for value in output.values.iter_mut() {
+            let ToolOutputValue::Text(text) = value else {
+                continue;
+            };
+
+            let result = Clipper::from_lines(self.limit.max_lines, self.limit.max_bytes).clip(text);
+            if !result.is_truncated() {
+                continue;
+            }
+
+            let mut clipped = result.prefix_content().unwrap_or_default().to_string();
+            if !clipped.is_empty() && !clipped.ends_with('\n') {
+                clipped.push('\n');
+            }
+            clipped.push_str(&format!(
+                "[... {} lines omitted ...]\n",
+                result.omitted_content().lines().count()
+            ));
+            clipped.push_str(result.suffix_content().unwrap_or_default());
+
+            if self.limit.save_full_output {
+                match self
+                    .infra
+                    .file_write_service()
+                    .write_temp("forge_tool_", ".txt", text)
+                    .await
+                {
+                    Ok(path) => {
+                        clipped.push_str(&format!("\n[full output saved to {}]", path.display()))
+                    }
+                    Err(error) => tracing::warn!(%error, "Failed to save full tool output"),
+                }
+            }
+
+            *text = clipped;
+        }
+
+        output
+    }
 }
 
 #[async_trait::async_trait]
-impl<M: McpService> ToolService for ForgeToolService<M> {
+impl<F: Infrastructure, M: McpService> ToolService for ForgeToolService<F, M> {
     async fn call(&self, context: ToolCallContext, call: ToolCallFull) -> ToolResult {
-        ToolResult::new(call.name.clone())
+        let limits_output = matches!(
+            self.find(&call.name).await,
+            Ok(Some(tool)) if tool.definition.limits_output
+        );
+        let mut result = ToolResult::new(call.name.clone())
             .call_id(call.call_id.clone())
-            .output(self.call(context, call).await)
+            .output(self.call(context, call).await);
+        if !limits_output {
+            result.output = self.clip(result.output).await;
+        }
+        result
     }
 
     async fn list(&self) -> anyhow::Result<Vec<ToolDefinition>> {
@@ -148,10 +214,16 @@ impl<M: McpService> ToolService for ForgeToolService<M> {
 
 #[cfg(test)]
 mod test {
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
     use forge_domain::{Tool, ToolCallContext, ToolCallId, ToolDefinition};
+    use pretty_assertions::assert_eq;
     use serde_json::{json, Value};
 
     use super::*;
+    use crate::attachment::tests::MockInfrastructure;
+    use crate::tools::Stub as Infra;
 
     struct Stub;
 
@@ -166,14 +238,20 @@ mod test {
         }
     }
 
-    impl FromIterator<Tool> for ForgeToolService<Stub> {
+    impl FromIterator<Tool> for ForgeToolService<Infra, Stub> {
         fn from_iter<T: IntoIterator<Item = Tool>>(iter: T) -> Self {
             let tools: HashMap<ToolName, Arc<Tool>> = iter
                 .into_iter()
                 .map(|tool| (tool.definition.name.clone(), Arc::new(tool)))
                 .collect::<HashMap<_, _>>();
 
-            Self { tools: Arc::new(tools), mcp: Arc::new(Stub) }
+            Self {
+                tools: Arc::new(tools),
+                mcp: Arc::new(Stub),
+                infra: Arc::new(Infra::default()),
+                limit: ToolOutputLimit::default(),
+                timeout: Duration::from_secs(300),
+            }
         }
     }
 
@@ -206,6 +284,8 @@ mod test {
                 description: "A test tool that takes too long".to_string(),
                 input_schema: schemars::schema_for!(serde_json::Value),
                 output_schema: Some(schemars::schema_for!(String)),
+                timeout: None,
+                limits_output: false,
             },
             executable: Box::new(SlowTool),
         };
@@ -233,4 +313,194 @@ mod test {
             "Expected 'elapsed' in timeout message"
         );
     }
+
+    /// Sleeps forever, flagging when its call future is dropped
+    struct HangingTool(Arc<AtomicBool>);
+
+    struct DropGuard(Arc<AtomicBool>);
+
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl forge_domain::ExecutableTool for HangingTool {
+        type Input = Value;
+
+        async fn call(
+            &self,
+            _context: ToolCallContext,
+            _input: Self::Input,
+        ) -> anyhow::Result<forge_domain::ToolOutput> {
+            let _guard = DropGuard(self.0.clone());
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_definition_timeout_cancels_call() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let hanging_tool = Tool {
+            definition: ToolDefinition::new("hanging_tool")
+                .input_schema(schemars::schema_for!(Value))
+                .timeout(Duration::from_millis(10)),
+            executable: Box::new(HangingTool(cancelled.clone())),
+        };
+        let service = ForgeToolService::from_iter(vec![hanging_tool]);
+        let call = ToolCallFull {
+            name: ToolName::new("hanging_tool"),
+            arguments: json!({}),
+            call_id: Some(ToolCallId::new("test")),
+        };
+
+        let result = ToolService::call(&service, ToolCallContext::default(), call).await;
+
+        assert!(result.is_error());
+        assert!(result
+            .output
+            .as_str()
+            .unwrap()
+            .contains("Tool 'hanging_tool' timed out after 0.01 seconds"));
+        assert!(cancelled.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_call_rejects_arguments_not_matching_schema() {
+        let called = Arc::new(AtomicBool::new(false));
+        let tool = Tool {
+            definition: ToolDefinition::new("fs_search")
+                .input_schema(schemars::schema_for!(forge_domain::FSSearchInput)),
+            executable: Box::new(FlagTool(called.clone())),
+        };
+        let service = ForgeToolService::from_iter(vec![tool]);
+        let call = ToolCallFull {
+            name: ToolName::new("fs_search"),
+            arguments: json!({"path": 42}),
+            call_id: Some(ToolCallId::new("test")),
+        };
+
+        let actual = ToolService::call(&service, ToolCallContext::default(), call).await;
+
+        assert!(actual.is_error());
+        let output = actual.output.as_str().unwrap();
+        assert!(output.contains("Invalid arguments for tool 'fs_search'"));
+        assert!(output.contains("- /path: 42 is not of type \"string\""));
+        assert!(!called.load(Ordering::SeqCst));
+    }
+
+    /// Flags when it is called
+    struct FlagTool(Arc<AtomicBool>);
+
+    #[async_trait::async_trait]
+    impl forge_domain::ExecutableTool for FlagTool {
+        type Input = Value;
+
+        async fn call(
+            &self,
+            _context: ToolCallContext,
+            _input: Self::Input,
+        ) -> anyhow::Result<forge_domain::ToolOutput> {
+            self.0.store(true, Ordering::SeqCst);
+            Ok(forge_domain::ToolOutput::text("called".to_string()))
+        }
+    }
+
+    /// Prints the given number of numbered lines
+    struct VerboseTool(usize);
+
+    #[async_trait::async_trait]
+    impl forge_domain::ExecutableTool for VerboseTool {
+        type Input = Value;
+
+        async fn call(
+            &self,
+            _context: ToolCallContext,
+            _input: Self::Input,
+        ) -> anyhow::Result<forge_domain::ToolOutput> {
+            Ok(forge_domain::ToolOutput::text(
+                (1..=self.0).map(|i| format!("line {i}\n")).collect(),
+            ))
+        }
+    }
+
+    fn verbose_call() -> ToolCallFull {
+        ToolCallFull {
+            name: ToolName::new("verbose_tool"),
+            arguments: json!({}),
+            call_id: Some(ToolCallId::new("test")),
+        }
+    }
+
+    fn verbose_service(lines: usize, limit: ToolOutputLimit) -> ForgeToolService<Infra, Stub> {
+        let tool = Tool {
+            definition: ToolDefinition::new("verbose_tool")
+                .input_schema(schemars::schema_for!(Value)),
+            executable: Box::new(VerboseTool(lines)),
+        };
+        ForgeToolService { limit, ..ForgeToolService::from_iter(vec![tool]) }
+    }
+
+    #[tokio::test]
+    async fn test_call_clips_output_over_budget() {
+        let limit = ToolOutputLimit::default()
+            .max_lines(4usize)
+            .save_full_output(false);
+        let service = verbose_service(10, limit);
+
+        let actual = ToolService::call(&service, ToolCallContext::default(), verbose_call()).await;
+
+        let expected = "line 1\nline 2\n[... 6 lines omitted ...]\nline 9\nline 10\n";
+        assert_eq!(actual.output.as_str(), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_call_clipped_output_includes_full_output_path() {
+        let limit = ToolOutputLimit::default().max_lines(4usize);
+        let service = verbose_service(10, limit);
+
+        let actual = ToolService::call(&service, ToolCallContext::default(), verbose_call()).await;
+
+        assert!(actual
+            .output
+            .as_str()
+            .unwrap()
+            .ends_with("line 10\n\n[full output saved to forge_tool_.txt]"));
+    }
+
+    #[tokio::test]
+    async fn test_call_keeps_output_within_budget() {
+        let limit = ToolOutputLimit::default().max_lines(10usize);
+        let service = verbose_service(10, limit);
+
+        let actual = ToolService::call(&service, ToolCallContext::default(), verbose_call()).await;
+
+        let expected = (1..=10).map(|i| format!("line {i}\n")).collect::<String>();
+        assert_eq!(actual.output.as_str(), Some(expected.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_call_keeps_full_size_fs_read_output() {
+        let infra = Arc::new(MockInfrastructure::new());
+        let content = (0..4_000)
+            .map(|i| format!("line {i:04}\n"))
+            .collect::<String>();
+        infra
+            .file_write_service()
+            .write(Path::new("/test/large.txt"), content.clone().into())
+            .await
+            .unwrap();
+        let tools = ToolRegistry::new(infra).tools().into_iter();
+        let service = ForgeToolService::from_iter(
+            tools.filter(|tool| tool.definition.name.as_str() == "forge_tool_fs_read"),
+        );
+        let call = ToolCallFull::new(ToolName::new("forge_tool_fs_read"))
+            .arguments(json!({"path": "/test/large.txt"}));
+
+        let actual = ToolService::call(&service, ToolCallContext::default(), call).await;
+
+        assert_eq!(content.len(), 40_000);
+        assert!(actual.output.as_str().unwrap().contains(&content));
+    }
 }
@@ -47,6 +47,10 @@ impl ExecutableTool for Completion {
         // Return success with the message
         Ok(ToolOutput::text(input.result))
     }
+
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -1,32 +1,62 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use forge_display::TitleFormat;
-use forge_domain::{ExecutableTool, NamedTool, ToolCallContext, ToolDescription, ToolOutput};
+use forge_domain::{
+    EnvironmentService, ExecutableTool, NamedTool, ToolCallContext, ToolDescription, ToolOutput,
+};
 use forge_tool_macros::ToolDescription;
-use reqwest::{Client, Url};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Method, StatusCode, Url};
 use schemars::JsonSchema;
 use serde::Deserialize;
+use tracing::warn;
 
+use super::fetch_cache::{CachedResponse, DEFAULT_MAX_AGE};
+use super::readable::readable_markdown;
 use crate::clipper::Clipper;
 use crate::metadata::Metadata;
+use crate::web::{read_body, WebClient};
 use crate::{FsWriteService, Infrastructure};
 
 /// Fetch tool returns the content of MAX_LENGTH.
 const MAX_LENGTH: usize = 40_000;
 
+/// Seconds a fetch may take unless the call allows more
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Response headers reported along with the content
+const RESPONSE_HEADERS: [&str; 6] = [
+    "content-type",
+    "content-length",
+    "www-authenticate",
+    "retry-after",
+    "etag",
+    "last-modified",
+];
+
 /// Retrieves content from URLs as markdown or raw text. Enables access to
 /// current online information including websites, APIs and documentation. Use
 /// for obtaining up-to-date information beyond training data, verifying facts,
-/// or retrieving specific online content. Handles HTTP/HTTPS and converts HTML
-/// to readable markdown by default. Cannot access private/restricted resources
-/// requiring authentication. Respects robots.txt and may be blocked by
-/// anti-scraping measures. For large pages, returns the first 40,000 characters
-/// and stores the complete content in a temporary file for subsequent access.
+/// or retrieving specific online content. Converts HTML to markdown keeping the
+/// main content only, without scripts, styles or navigation; JSON and plain
+/// text are returned as is and binary content is rejected. Supports GET, POST,
+/// PUT and DELETE with custom headers and a body, for eg: to call APIs with a
+/// token in the Authorization header. Reports the response status and key
+/// headers. Follows at most 5 redirects, never forwarding Authorization to
+/// another origin. Downloads stop at max_bytes (512 KB by default) and time out
+/// after timeout_secs (60 by default). GET responses are cached; set no_cache
+/// to refetch. Respects robots.txt. For large pages, returns the first 40,000
+/// characters and stores the complete content in a temporary file for
+/// subsequent access.
 #[derive(Debug, ToolDescription)]
 pub struct Fetch<F> {
-    client: Client,
+    web: WebClient,
     infra: Arc<F>,
+    /// Largest response body downloaded unless the call allows more
+    max_bytes: usize,
 }
 
 impl<F: Infrastructure> NamedTool for Fetch<F> {
@@ -37,7 +67,9 @@ impl<F: Infrastructure> NamedTool for Fetch<F> {
 
 impl<F: Infrastructure> Fetch<F> {
     pub fn new(infra: Arc<F>) -> Self {
-        Self { client: Client::new(), infra }
+        let env = infra.environment_service().get_environment();
+        let web = WebClient::new(env.fetch_cache_path());
+        Self { web, infra, max_bytes: env.fetch_max_bytes }
     }
 }
 
@@ -45,105 +77,274 @@ fn default_raw() -> Option<bool> {
     Some(false)
 }
 
-#[derive(Deserialize, JsonSchema)]
+/// HTTP methods the fetch tool can send
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum FetchMethod {
+    #[default]
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl From<FetchMethod> for Method {
+    fn from(method: FetchMethod) -> Self {
+        match method {
+            FetchMethod::Get => Method::GET,
+            FetchMethod::Post => Method::POST,
+            FetchMethod::Put => Method::PUT,
+            FetchMethod::Delete => Method::DELETE,
+        }
+    }
+}
+
+#[derive(Default, Deserialize, JsonSchema)]
 pub struct FetchInput {
     /// URL to fetch
     url: String,
     /// Get raw content without any markdown conversion (default: false)
     #[serde(default = "default_raw")]
     raw: Option<bool>,
+    /// HTTP method to use (default: GET)
+    #[serde(default)]
+    method: FetchMethod,
+    /// Headers to send with the request, for eg: an Authorization header with
+    /// a bearer token
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    /// Body to send with the request
+    body: Option<String>,
+    /// Content type of the body (default: application/json for methods other
+    /// than GET)
+    content_type: Option<String>,
+    /// Largest response to download in bytes, larger responses fail (default:
+    /// 524288)
+    max_bytes: Option<usize>,
+    /// Seconds the whole fetch may take (default: 60)
+    timeout_secs: Option<u64>,
+    /// Download the page again instead of using the cached copy (default:
+    /// false)
+    #[serde(default)]
+    no_cache: bool,
 }
 
-impl<F: Infrastructure> Fetch<F> {
-    async fn check_robots_txt(&self, url: &Url) -> Result<()> {
-        let robots_url = format!("{}://{}/robots.txt", url.scheme(), url.authority());
-        let robots_response = self.client.get(&robots_url).send().await;
-
-        if let Ok(robots) = robots_response {
-            if robots.status().is_success() {
-                let robots_content = robots.text().await.unwrap_or_default();
-                let path = url.path();
-                for line in robots_content.lines() {
-                    if let Some(disallowed) = line.strip_prefix("Disallow: ") {
-                        let disallowed = disallowed.trim();
-                        let disallowed = if !disallowed.starts_with('/') {
-                            format!("/{disallowed}")
-                        } else {
-                            disallowed.to_string()
-                        };
-                        let path = if !path.starts_with('/') {
-                            format!("/{path}")
-                        } else {
-                            path.to_string()
-                        };
-                        if path.starts_with(&disallowed) {
-                            return Err(anyhow!(
-                                "URL {} cannot be fetched due to robots.txt restrictions",
-                                url
-                            ));
-                        }
-                    }
-                }
+impl FetchInput {
+    /// Whether the response may be cached. Responses to requests with headers
+    /// or a body may depend on them, for eg: on a token, so only plain GET
+    /// requests are.
+    fn is_cacheable(&self) -> bool {
+        self.method == FetchMethod::Get && self.headers.is_empty() && self.body.is_none()
+    }
+
+    /// Headers to send, with the content type of the body unless one was
+    /// passed explicitly. Authorization values are marked sensitive so they
+    /// never show up in debug output.
+    fn header_map(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        let content_type = self.content_type.as_deref().or(match self.method {
+            FetchMethod::Get => None,
+            _ => Some("application/json"),
+        });
+        if let (Some(_), Some(content_type)) = (&self.body, content_type) {
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_str(content_type)
+                    .with_context(|| format!("Invalid content type: {content_type}"))?,
+            );
+        }
+
+        for (name, value) in self.headers.iter() {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("Invalid header name: {name}"))?;
+            let mut value = HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid value for header {name}"))?;
+            if name == AUTHORIZATION {
+                value.set_sensitive(true);
             }
+            headers.insert(name, value);
         }
-        Ok(())
+        Ok(headers)
     }
+}
+
+/// Whether content of the given MIME type is text that can be returned
+fn is_text(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || [
+            "json",
+            "xml",
+            "javascript",
+            "yaml",
+            "toml",
+            "csv",
+            "urlencoded",
+        ]
+        .iter()
+        .any(|kind| mime.contains(kind))
+}
+
+/// Converts HTML pages to markdown unless `raw` is set, returning the content
+/// and a note about how it was converted
+fn convert(content_type: &str, page_raw: String, raw: bool) -> (String, String) {
+    let is_page_html = page_raw[..100.min(page_raw.len())].contains("<html")
+        || content_type.contains("text/html")
+        || content_type.is_empty();
+
+    if is_page_html && !raw {
+        (readable_markdown(&page_raw), String::new())
+    } else {
+        (
+            page_raw,
+            format!(
+                "Content type {content_type} cannot be simplified to markdown; Raw content provided instead"
+            ),
+        )
+    }
+}
 
+/// Content of a cached page, with metadata telling that it came from the
+/// cache. `status` is the answer of the server when it was asked whether the
+/// page changed.
+fn cached_content(
+    url: &Url,
+    cached: &CachedResponse,
+    status: Option<StatusCode>,
+    raw: bool,
+) -> (String, String, Metadata) {
+    let metadata = Metadata::default()
+        .add("URL", url)
+        .add_optional("status", status)
+        .add_optional(
+            "content-type",
+            Some(&cached.content_type).filter(|content_type| !content_type.is_empty()),
+        )
+        .add_optional("etag", cached.etag.as_ref())
+        .add_optional("last-modified", cached.last_modified.as_ref())
+        .add("cache", cached.note());
+    let (content, prefix) = convert(&cached.content_type, cached.body.clone(), raw);
+    (content, prefix, metadata)
+}
+
+impl<F: Infrastructure> Fetch<F> {
+    /// Sends the request and returns the content, a note about how it was
+    /// converted, and metadata holding the response status and headers
     async fn fetch_url(
         &self,
         url: &Url,
         context: &ToolCallContext,
-        force_raw: bool,
-    ) -> Result<(String, String)> {
-        self.check_robots_txt(url).await?;
+        input: &FetchInput,
+    ) -> Result<(String, String, Metadata)> {
+        let raw = input.raw.unwrap_or(false);
+        let cached = if input.is_cacheable() && !input.no_cache {
+            self.web.cache().load(url).await
+        } else {
+            None
+        };
 
-        let response = self
-            .client
-            .get(url.as_str())
+        // Without validators the server can't be asked whether the page changed,
+        // so a recent copy is served as is
+        if let Some(cached) = cached
+            .as_ref()
+            .filter(|cached| !cached.has_validators() && cached.age() < DEFAULT_MAX_AGE)
+        {
+            context
+                .send_text(TitleFormat::debug("GET cached").sub_title(url.as_str()))
+                .await?;
+            return Ok(cached_content(url, cached, None, raw));
+        }
+
+        self.web.check_robots_txt(url).await?;
+
+        let method = Method::from(input.method);
+        let mut headers = input.header_map()?;
+        if let Some(cached) = &cached {
+            headers.extend(cached.conditional_headers());
+        }
+        let mut request = self
+            .web
+            .client()
+            .request(method.clone(), url.as_str())
+            .headers(headers);
+        if let Some(body) = &input.body {
+            request = request.body(body.clone());
+        }
+
+        let response = request
             .send()
             .await
             .map_err(|e| anyhow!("Failed to fetch URL {}: {}", url, e))?;
 
+        let status = response.status();
         context
-            .send_text(
-                TitleFormat::debug(format!("GET {}", response.status())).sub_title(url.as_str()),
-            )
+            .send_text(TitleFormat::debug(format!("{method} {status}")).sub_title(url.as_str()))
             .await?;
 
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "Failed to fetch {} - status code {}",
-                url,
-                response.status()
-            ));
+        if let (StatusCode::NOT_MODIFIED, Some(cached)) = (status, &cached) {
+            return Ok(cached_content(url, cached, Some(status), raw));
         }
 
+        let metadata = RESPONSE_HEADERS.into_iter().fold(
+            Metadata::default().add("URL", url).add("status", status),
+            |metadata, name| {
+                metadata.add_optional(
+                    name,
+                    response.headers().get(name).and_then(|v| v.to_str().ok()),
+                )
+            },
+        );
+
         let content_type = response
             .headers()
             .get("content-type")
             .and_then(|v| v.to_str().ok())
             .unwrap_or("")
             .to_string();
+        let max_bytes = input.max_bytes.unwrap_or(self.max_bytes);
+
+        if !status.is_success() {
+            // The body usually tells why the request failed, for eg: an expired token
+            let page_raw = read_body(url, response, max_bytes)
+                .await
+                .map(|body| String::from_utf8_lossy(&body).into_owned())
+                .unwrap_or_default();
+            let body = Clipper::from_start(2_000).clip(&page_raw);
+            let body = body.prefix_content().unwrap_or(&page_raw);
+            return Err(anyhow!(
+                "Failed to fetch {} - status code {}\n{}{}",
+                url,
+                status,
+                metadata,
+                body
+            ));
+        }
 
-        let page_raw = response
-            .text()
-            .await
-            .map_err(|e| anyhow!("Failed to read response content from {}: {}", url, e))?;
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        if !mime.is_empty() && !is_text(&mime) {
+            bail!(
+                "Cannot fetch {url}: its content type {mime} is binary, only text can be fetched"
+            );
+        }
 
-        let is_page_html = page_raw[..100.min(page_raw.len())].contains("<html")
-            || content_type.contains("text/html")
-            || content_type.is_empty();
+        let response_headers = response.headers().clone();
+        let page_raw =
+            String::from_utf8_lossy(&read_body(url, response, max_bytes).await?).into_owned();
 
-        if is_page_html && !force_raw {
-            let content = html2md::parse_html(&page_raw);
-            Ok((content, String::new()))
-        } else {
-            Ok((
-                page_raw,
-                format!(
-                    "Content type {content_type} cannot be simplified to markdown; Raw content provided instead"),
-            ))
+        // The fetch succeeds even when the page can't be cached
+        if input.is_cacheable() {
+            let cached = CachedResponse::new(url, &content_type, &page_raw, &response_headers);
+            if let Err(error) = self.web.cache().store(&cached).await {
+                warn!(url = %url, error = ?error, "Failed to cache fetched page");
+            }
         }
+
+        let (content, prefix) = convert(&content_type, page_raw, raw);
+        Ok((content, prefix, metadata))
     }
 }
 
@@ -159,9 +360,13 @@ impl<F: Infrastructure> ExecutableTool for Fetch<F> {
         let url = Url::parse(&input.url)
             .with_context(|| format!("Failed to parse URL: {}", input.url))?;
 
-        let (content, prefix) = self
-            .fetch_url(&url, &context, input.raw.unwrap_or(false))
-            .await?;
+        let timeout_secs = input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let (content, prefix, metadata) = tokio::time::timeout(
+            Duration::from_secs(timeout_secs),
+            self.fetch_url(&url, &context, &input),
+        )
+        .await
+        .map_err(|_| anyhow!("Fetching {url} timed out after {timeout_secs} seconds"))??;
 
         let original_length = content.len();
         let end = MAX_LENGTH.min(original_length);
@@ -182,8 +387,7 @@ impl<F: Infrastructure> ExecutableTool for Fetch<F> {
         };
 
         // Build metadata with all required fields in a single fluent chain
-        let metadata = Metadata::default()
-            .add("URL", url)
+        let metadata = metadata
             .add("total_chars", original_length)
             .add("start_char", "0")
             .add("end_char", end.to_string())
@@ -212,22 +416,35 @@ impl<F: Infrastructure> ExecutableTool for Fetch<F> {
             "{metadata}{output}{truncation_tag}",
         )))
     }
+
+    fn is_read_only(&self, input: &Self::Input) -> bool {
+        input.method == FetchMethod::Get
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use mockito::Matcher;
     use regex::Regex;
+    use tempfile::TempDir;
     use tokio::runtime::Runtime;
 
     use super::*;
     use crate::attachment::tests::MockInfrastructure;
     use crate::utils::ToolContentExtension;
+    use crate::web::MAX_REDIRECTS;
 
-    async fn setup() -> (Fetch<MockInfrastructure>, mockito::ServerGuard) {
+    /// The fetch tool, caching in a temporary directory, and a server to
+    /// fetch from
+    async fn setup() -> (Fetch<MockInfrastructure>, mockito::ServerGuard, TempDir) {
         let server = mockito::Server::new_async().await;
         let infra = Arc::new(MockInfrastructure::new());
-        let fetch = Fetch { client: Client::new(), infra };
-        (fetch, server)
+        let cache_dir = TempDir::new().unwrap();
+        let fetch = Fetch {
+            web: WebClient::new(cache_dir.path().to_path_buf()),
+            ..Fetch::new(infra)
+        };
+        (fetch, server, cache_dir)
     }
 
     fn normalize_port(content: String) -> String {
@@ -252,7 +469,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_html_content() {
-        let (fetch, mut server) = setup().await;
+        let (fetch, mut server, _cache_dir) = setup().await;
 
         server
             .mock("GET", "/test.html")
@@ -277,7 +494,11 @@ mod tests {
             .with_body("User-agent: *\nAllow: /")
             .create();
 
-        let input = FetchInput { url: format!("{}/test.html", server.url()), raw: Some(false) };
+        let input = FetchInput {
+            url: format!("{}/test.html", server.url()),
+            raw: Some(false),
+            ..Default::default()
+        };
 
         let result = fetch
             .call(ToolCallContext::default(), input)
@@ -290,7 +511,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_raw_content() {
-        let (fetch, mut server) = setup().await;
+        let (fetch, mut server, _cache_dir) = setup().await;
 
         let raw_content = "This is raw text content";
         server
@@ -307,7 +528,11 @@ mod tests {
             .with_body("User-agent: *\nAllow: /")
             .create();
 
-        let input = FetchInput { url: format!("{}/test.txt", server.url()), raw: Some(true) };
+        let input = FetchInput {
+            url: format!("{}/test.txt", server.url()),
+            raw: Some(true),
+            ..Default::default()
+        };
 
         let result = fetch
             .call(ToolCallContext::default(), input)
@@ -320,7 +545,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_with_robots_txt_denied() {
-        let (fetch, mut server) = setup().await;
+        let (fetch, mut server, _cache_dir) = setup().await;
 
         // Mock robots.txt request
         server
@@ -338,7 +563,11 @@ mod tests {
             .with_body("<html><body>Test page</body></html>")
             .create();
 
-        let input = FetchInput { url: format!("{}/test/page.html", server.url()), raw: None };
+        let input = FetchInput {
+            url: format!("{}/test/page.html", server.url()),
+            raw: None,
+            ..Default::default()
+        };
 
         let result = fetch.call(ToolCallContext::default(), input).await;
         assert!(result.is_err());
@@ -351,7 +580,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_with_pagination() {
-        let (fetch, mut server) = setup().await;
+        let (fetch, mut server, _cache_dir) = setup().await;
 
         let long_content = format!("{}{}", "A".repeat(5000), "B".repeat(5000));
         server
@@ -369,7 +598,11 @@ mod tests {
             .create();
 
         // First page
-        let input = FetchInput { url: format!("{}/long.txt", server.url()), raw: Some(true) };
+        let input = FetchInput {
+            url: format!("{}/long.txt", server.url()),
+            raw: Some(true),
+            ..Default::default()
+        };
 
         let result = fetch
             .call(ToolCallContext::default(), input)
@@ -383,7 +616,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_large_content_temp_file() {
-        let (fetch, mut server) = setup().await;
+        let (fetch, mut server, _cache_dir) = setup().await;
 
         // Instead of using a very large content (50,000 chars), use just 102 chars
         // This still tests the truncation functionality but with a much smaller dataset
@@ -408,7 +641,11 @@ mod tests {
             .with_body("User-agent: *\nAllow: /")
             .create();
 
-        let input = FetchInput { url: format!("{}/large.txt", server.url()), raw: Some(true) };
+        let input = FetchInput {
+            url: format!("{}/large.txt", server.url()),
+            raw: Some(true),
+            ..Default::default()
+        };
 
         // Execute the fetch
         let context = ToolCallContext::default();
@@ -443,13 +680,14 @@ mod tests {
 
     #[test]
     fn test_fetch_invalid_url() {
-        let fetch = Fetch {
-            client: Client::new(),
-            infra: Arc::new(MockInfrastructure::new()),
-        };
+        let fetch = Fetch::new(Arc::new(MockInfrastructure::new()));
         let rt = Runtime::new().unwrap();
 
-        let input = FetchInput { url: "not a valid url".to_string(), raw: None };
+        let input = FetchInput {
+            url: "not a valid url".to_string(),
+            raw: None,
+            ..Default::default()
+        };
 
         let result = rt.block_on(fetch.call(ToolCallContext::default(), input));
 
@@ -457,9 +695,20 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("parse"));
     }
 
+    #[test]
+    fn test_fetch_is_read_only_for_get() {
+        let fetch = Fetch::new(Arc::new(MockInfrastructure::new()));
+        let input = |method| FetchInput { method, ..Default::default() };
+
+        let actual = [FetchMethod::Get, FetchMethod::Post, FetchMethod::Delete]
+            .map(|method| fetch.is_read_only(&input(method)));
+
+        assert_eq!(actual, [true, false, false]);
+    }
+
     #[tokio::test]
     async fn test_fetch_404() {
-        let (fetch, mut server) = setup().await;
+        let (fetch, mut server, _cache_dir) = setup().await;
 
         server.mock("GET", "/not-found").with_status(404).create();
 
@@ -470,10 +719,425 @@ mod tests {
             .with_body("User-agent: *\nAllow: /")
             .create();
 
-        let input = FetchInput { url: format!("{}/not-found", server.url()), raw: None };
+        let input = FetchInput {
+            url: format!("{}/not-found", server.url()),
+            raw: None,
+            ..Default::default()
+        };
 
         let result = fetch.call(ToolCallContext::default(), input).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("404"));
     }
+
+    #[tokio::test]
+    async fn test_fetch_post_json_body() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+
+        let mock = server
+            .mock("POST", "/api/items")
+            .match_header("content-type", "application/json")
+            .match_header("authorization", "Bearer secret")
+            .match_body(Matcher::Json(serde_json::json!({"name": "forge"})))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_header("etag", "\"v1\"")
+            .with_body(r#"{"id":1,"name":"forge"}"#)
+            .create_async()
+            .await;
+
+        let input = FetchInput {
+            url: format!("{}/api/items", server.url()),
+            method: FetchMethod::Post,
+            headers: HashMap::from([("Authorization".to_string(), "Bearer secret".to_string())]),
+            body: Some(r#"{"name":"forge"}"#.to_string()),
+            ..Default::default()
+        };
+
+        let result = fetch
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap()
+            .into_string();
+        mock.assert_async().await;
+        insta::assert_snapshot!(normalize_port(result));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_401() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+
+        server
+            .mock("GET", "/api/me")
+            .with_status(401)
+            .with_header("www-authenticate", "Bearer error=\"invalid_token\"")
+            .with_body("token expired")
+            .create_async()
+            .await;
+
+        let input = FetchInput {
+            url: format!("{}/api/me", server.url()),
+            headers: HashMap::from([("Authorization".to_string(), "Bearer stale".to_string())]),
+            ..Default::default()
+        };
+
+        let actual = fetch
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(actual.contains("status code 401 Unauthorized"), "{actual}");
+        assert!(
+            actual.contains("www-authenticate: Bearer error=\"invalid_token\""),
+            "{actual}"
+        );
+        assert!(actual.contains("token expired"), "{actual}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_redirect_strips_authorization_cross_origin() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+        let mut other = mockito::Server::new_async().await;
+
+        server
+            .mock("GET", "/moved")
+            .with_status(302)
+            .with_header("location", "/here")
+            .create_async()
+            .await;
+        let same_origin = server
+            .mock("GET", "/here")
+            .match_header("authorization", "Bearer secret")
+            .with_status(200)
+            .with_body("same origin")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/away")
+            .with_status(302)
+            .with_header("location", &format!("{}/there", other.url()))
+            .create_async()
+            .await;
+        let cross_origin = other
+            .mock("GET", "/there")
+            .match_header("authorization", Matcher::Missing)
+            .with_status(200)
+            .with_body("other origin")
+            .create_async()
+            .await;
+
+        for path in ["/moved", "/away"] {
+            let input = FetchInput {
+                url: format!("{}{path}", server.url()),
+                raw: Some(true),
+                headers: HashMap::from([(
+                    "Authorization".to_string(),
+                    "Bearer secret".to_string(),
+                )]),
+                ..Default::default()
+            };
+            fetch.call(ToolCallContext::default(), input).await.unwrap();
+        }
+
+        same_origin.assert_async().await;
+        cross_origin.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_redirect_limit() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+
+        for hop in 0..=MAX_REDIRECTS {
+            server
+                .mock("GET", format!("/hop/{hop}").as_str())
+                .with_status(302)
+                .with_header("location", &format!("/hop/{}", hop + 1))
+                .create_async()
+                .await;
+        }
+
+        let input = FetchInput { url: format!("{}/hop/0", server.url()), ..Default::default() };
+
+        let actual = fetch.call(ToolCallContext::default(), input).await;
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_extracts_main_content() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+
+        server
+            .mock("GET", "/docs")
+            .with_status(200)
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_body(include_str!("fixtures/docs_page.html"))
+            .create_async()
+            .await;
+
+        let input = FetchInput { url: format!("{}/docs", server.url()), ..Default::default() };
+
+        let actual = fetch
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap()
+            .into_string();
+        assert!(actual.contains("Configuration\n=========="), "{actual}");
+        assert!(!actual.contains("Products"), "{actual}");
+        assert!(!actual.contains("trackPageView"), "{actual}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_json_passes_through() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+
+        let body = r#"{"items":[{"name":"<b>forge</b>"}]}"#;
+        server
+            .mock("GET", "/api/items")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let input = FetchInput {
+            url: format!("{}/api/items", server.url()),
+            ..Default::default()
+        };
+
+        let actual = fetch
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap()
+            .into_string();
+        assert!(actual.ends_with(&format!("---\n{body}")), "{actual}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_binary_content() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+
+        server
+            .mock("GET", "/logo.png")
+            .with_status(200)
+            .with_header("content-type", "image/png")
+            .with_body([0x89, b'P', b'N', b'G', 0, 0])
+            .create_async()
+            .await;
+
+        let input = FetchInput {
+            url: format!("{}/logo.png", server.url()),
+            ..Default::default()
+        };
+
+        let actual = fetch
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(actual.contains("image/png"), "{actual}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_aborts_past_max_bytes() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+
+        let body = "A".repeat(2048);
+        server
+            .mock("GET", "/sized.txt")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body(&body)
+            .create_async()
+            .await;
+        // Without a content length the download has to be cut short
+        server
+            .mock("GET", "/streamed.txt")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_chunked_body(move |writer| {
+                for _ in 0..8 {
+                    writer.write_all(&[b'A'; 256])?;
+                }
+                Ok(())
+            })
+            .create_async()
+            .await;
+
+        for path in ["/sized.txt", "/streamed.txt"] {
+            let input = FetchInput {
+                url: format!("{}{path}", server.url()),
+                max_bytes: Some(1024),
+                ..Default::default()
+            };
+
+            let actual = fetch
+                .call(ToolCallContext::default(), input)
+                .await
+                .unwrap_err()
+                .to_string();
+            assert!(actual.contains("larger than 1024 bytes"), "{actual}");
+        }
+
+        let input = FetchInput {
+            url: format!("{}/sized.txt", server.url()),
+            max_bytes: Some(4096),
+            raw: Some(true),
+            ..Default::default()
+        };
+        let actual = fetch
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap()
+            .into_string();
+        assert!(actual.contains(&body));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_timeout() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+        let fetch = Fetch::new(Arc::new(MockInfrastructure::new()));
+
+        let input = FetchInput {
+            url: format!("http://{address}/slow"),
+            timeout_secs: Some(1),
+            ..Default::default()
+        };
+
+        let actual = fetch
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap_err()
+            .to_string();
+        server.abort();
+        assert!(actual.contains("timed out after 1 seconds"), "{actual}");
+    }
+
+    fn robots(server: &mut mockito::ServerGuard) {
+        server
+            .mock("GET", "/robots.txt")
+            .with_status(200)
+            .with_body("User-agent: *\nAllow: /")
+            .create();
+    }
+
+    async fn fetch_page(fetch: &Fetch<MockInfrastructure>, url: String, no_cache: bool) -> String {
+        let input = FetchInput { url, no_cache, ..Default::default() };
+        fetch
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap()
+            .into_string()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_revalidates_cached_page() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+        robots(&mut server);
+        let last_modified = "Wed, 21 Oct 2015 07:28:00 GMT";
+        let page = server
+            .mock("GET", "/docs.html")
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_header("etag", "\"v1\"")
+            .with_header("last-modified", last_modified)
+            .with_body("<html><body><p>Cached docs</p></body></html>")
+            .expect(1)
+            .create();
+        let not_modified = server
+            .mock("GET", "/docs.html")
+            .match_header("if-none-match", "\"v1\"")
+            .match_header("if-modified-since", last_modified)
+            .with_status(304)
+            .expect(1)
+            .create();
+        let url = format!("{}/docs.html", server.url());
+
+        let first = fetch_page(&fetch, url.clone(), false).await;
+        let second = fetch_page(&fetch, url, false).await;
+
+        page.assert();
+        not_modified.assert();
+        assert!(!first.contains("served from cache"), "{first}");
+        assert!(second.contains("status: 304 Not Modified"), "{second}");
+        assert!(
+            second.contains("cache: served from cache (age 0s)"),
+            "{second}"
+        );
+        assert!(second.contains("Cached docs"), "{second}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_serves_recent_page_without_validators() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+        robots(&mut server);
+        let page = server
+            .mock("GET", "/data.json")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"answer": 42}"#)
+            .expect(1)
+            .create();
+        let url = format!("{}/data.json", server.url());
+
+        fetch_page(&fetch, url.clone(), false).await;
+        let actual = fetch_page(&fetch, url, false).await;
+
+        page.assert();
+        assert!(actual.contains("cache: served from cache"), "{actual}");
+        assert!(actual.contains(r#"{"answer": 42}"#), "{actual}");
+        assert!(!actual.contains("status:"), "{actual}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_no_cache_bypasses_cache() {
+        let (fetch, mut server, _cache_dir) = setup().await;
+        robots(&mut server);
+        let page = server
+            .mock("GET", "/docs.html")
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_header("etag", "\"v1\"")
+            .with_body("<html><body><p>Fresh docs</p></body></html>")
+            .expect(2)
+            .create();
+        let url = format!("{}/docs.html", server.url());
+
+        fetch_page(&fetch, url.clone(), false).await;
+        let actual = fetch_page(&fetch, url, true).await;
+
+        page.assert();
+        assert!(!actual.contains("served from cache"), "{actual}");
+        assert!(actual.contains("Fresh docs"), "{actual}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_does_not_cache_requests_with_headers() {
+        let (fetch, mut server, cache_dir) = setup().await;
+        robots(&mut server);
+        server
+            .mock("GET", "/private")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body("secret")
+            .create();
+
+        let input = FetchInput {
+            url: format!("{}/private", server.url()),
+            headers: HashMap::from([("Authorization".to_string(), "Bearer token".to_string())]),
+            ..Default::default()
+        };
+        fetch.call(ToolCallContext::default(), input).await.unwrap();
+
+        assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 0);
+    }
 }
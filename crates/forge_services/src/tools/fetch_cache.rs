@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use forge_fs::ForgeFS;
+use reqwest::header::{HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// How long a response without an ETag or Last-Modified header is served from
+/// the cache before it is fetched again
+pub(crate) const DEFAULT_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+
+/// A response kept on disk so that fetching the same URL again costs a
+/// revalidation at most
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CachedResponse {
+    pub url: String,
+    pub content_type: String,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// Time the body was downloaded, since the UNIX epoch
+    pub fetched_at: Duration,
+}
+
+impl CachedResponse {
+    pub fn new(url: &Url, content_type: &str, body: &str, headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            url: url.to_string(),
+            content_type: content_type.to_string(),
+            body: body.to_string(),
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+            fetched_at: now(),
+        }
+    }
+
+    /// Time since the body was downloaded
+    pub fn age(&self) -> Duration {
+        now().saturating_sub(self.fetched_at)
+    }
+
+    /// Whether the server gave a way to check that the body is still current
+    pub fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+
+    /// Headers asking the server to answer 304 if the body is still current
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let validators = [
+            (IF_NONE_MATCH, &self.etag),
+            (IF_MODIFIED_SINCE, &self.last_modified),
+        ];
+        for (name, value) in validators {
+            if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+
+    /// Notes where the content came from, for eg: `served from cache (age
+    /// 4m)`
+    pub fn note(&self) -> String {
+        let age = self.age().as_secs();
+        let age = match age {
+            0..60 => format!("{age}s"),
+            60..3600 => format!("{}m", age / 60),
+            _ => format!("{}h", age / 3600),
+        };
+        format!("served from cache (age {age})")
+    }
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Responses to GET requests stored on disk, one file per URL
+#[derive(Debug, Clone)]
+pub(crate) struct FetchCache {
+    dir: PathBuf,
+}
+
+impl FetchCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, url: &Url) -> PathBuf {
+        let hash = blake3::hash(url.as_str().as_bytes()).to_hex();
+        self.dir.join(format!("{hash}.json"))
+    }
+
+    /// The response stored for `url`, if any. Unreadable entries count as
+    /// missing.
+    pub async fn load(&self, url: &Url) -> Option<CachedResponse> {
+        let content = ForgeFS::read(self.path(url)).await.ok()?;
+        serde_json::from_slice::<CachedResponse>(&content)
+            .ok()
+            .filter(|cached| cached.url == url.as_str())
+    }
+
+    pub async fn store(&self, response: &CachedResponse) -> Result<()> {
+        let url = Url::parse(&response.url)?;
+        ForgeFS::create_dir_all(&self.dir).await?;
+        ForgeFS::write(self.path(&url), serde_json::to_vec(response)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture(age: Duration) -> CachedResponse {
+        CachedResponse {
+            url: "https://example.com/docs".to_string(),
+            content_type: "text/html".to_string(),
+            body: "<p>docs</p>".to_string(),
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+            fetched_at: now() - age,
+        }
+    }
+
+    #[test]
+    fn test_note() {
+        let actual = [5, 240, 7_300]
+            .map(|secs| fixture(Duration::from_secs(secs)).note())
+            .to_vec();
+
+        let expected = vec![
+            "served from cache (age 5s)",
+            "served from cache (age 4m)",
+            "served from cache (age 2h)",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_conditional_headers() {
+        let actual = fixture(Duration::ZERO).conditional_headers();
+
+        assert_eq!(actual.get(IF_NONE_MATCH).unwrap(), "\"v1\"");
+        assert!(actual.get(IF_MODIFIED_SINCE).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_store_and_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = FetchCache::new(dir.path().join("fetch_cache"));
+        let fixture = fixture(Duration::ZERO);
+        let url = Url::parse(&fixture.url).unwrap();
+
+        cache.store(&fixture).await.unwrap();
+
+        assert_eq!(cache.load(&url).await, Some(fixture));
+        let other = Url::parse("https://example.com/other").unwrap();
+        assert_eq!(cache.load(&other).await, None);
+    }
+}
@@ -103,4 +103,8 @@ impl<F: Infrastructure> ExecutableTool for Followup<F> {
             }
         }
     }
+
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        true
+    }
 }
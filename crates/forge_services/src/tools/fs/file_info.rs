@@ -1,12 +1,13 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
 use forge_display::TitleFormat;
 use forge_domain::{
     EnvironmentService, ExecutableTool, NamedTool, ToolCallContext, ToolDescription, ToolName,
     ToolOutput,
 };
+use forge_fs::{FileMetadata, ForgeFS, GitBlameSummary};
 use forge_tool_macros::ToolDescription;
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -18,13 +19,20 @@ use crate::Infrastructure;
 pub struct FSFileInfoInput {
     /// The path of the file or directory to inspect (absolute path required)
     pub path: String,
+    /// Whether to also report the last commit that changed the file. Use true
+    /// to include it, false or omit to skip it.
+    pub git: Option<bool>,
 }
 
+/// Largest file whose git blame is read, blaming bigger files takes too long
+const MAX_BLAME_FILE_SIZE: u64 = 1024 * 1024;
+
 /// Request to retrieve detailed metadata about a file or directory at the
 /// specified path. Returns comprehensive information including size, creation
-/// time, last modified time, permissions, and type. Path must be absolute. Use
-/// this when you need to understand file characteristics without reading the
-/// actual content.
+/// time, last modified time, permissions, and type. When `git` is set, files
+/// tracked by git also report the hash, message, author and date of the last
+/// commit that changed them. Path must be absolute. Use this when you need to
+/// understand file characteristics without reading the actual content.
 #[derive(ToolDescription)]
 pub struct FSFileInfo<F> {
     infra: Arc<F>,
@@ -58,6 +66,60 @@ impl<F: Infrastructure> FSFileInfo<F> {
     }
 }
 
+/// Renders the metadata of a file as one `key: value` line per property
+fn format_file_info(meta: &FileMetadata, blame: Option<&GitBlameSummary>) -> String {
+    let timestamp = |time: Option<DateTime<Utc>>| {
+        time.map_or("unavailable".to_string(), |time| {
+            time.to_rfc3339_opts(SecondsFormat::Secs, true)
+        })
+    };
+
+    let symlink = meta
+        .symlink_target
+        .as_ref()
+        .map_or("no".to_string(), |target| {
+            format!("-> {}", target.display())
+        });
+
+    let mut lines = vec![
+        format!("type: {}", if meta.is_dir { "directory" } else { "file" }),
+        format!("size: {}", format_size(meta.size)),
+        format!("modified: {}", timestamp(meta.modified)),
+        format!("created: {}", timestamp(meta.created)),
+        format!("symlink: {symlink}"),
+    ];
+    if let Some(permissions) = meta.permissions {
+        lines.push(format!("permissions: {permissions:04o}"));
+    }
+    if let Some(blame) = blame {
+        lines.push(format!(
+            "last commit: {} {} ({}, {})",
+            blame.last_commit_hash,
+            blame.last_commit_message,
+            blame.last_commit_author,
+            blame.last_commit_date
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Formats a size in bytes using binary units, e.g. `1.5 KiB`
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {} ({bytes} bytes)", UNITS[unit])
+}
+
 #[async_trait::async_trait]
 impl<F: Infrastructure> ExecutableTool for FSFileInfo<F> {
     type Input = FSFileInfoInput;
@@ -70,19 +132,38 @@ impl<F: Infrastructure> ExecutableTool for FSFileInfo<F> {
         let path = Path::new(&input.path);
         assert_absolute_path(path)?;
 
-        let meta = tokio::fs::metadata(&input.path)
-            .await
-            .with_context(|| format!("Failed to get metadata for '{}'", input.path))?;
+        let meta = ForgeFS::file_metadata(path).await?;
+        let blame = if input.git.unwrap_or_default()
+            && !meta.is_dir
+            && meta.size <= MAX_BLAME_FILE_SIZE
+        {
+            // The commit is extra information, failing to read it doesn't fail the call
+            ForgeFS::git_blame_summary(path)
+                .await
+                .inspect_err(|error| tracing::debug!(error = %error, "Failed to read git blame"))
+                .ok()
+                .flatten()
+        } else {
+            None
+        };
+
+        let output = format_file_info(&meta, blame.as_ref());
 
         context
             .send_text(TitleFormat::debug("Info").title(self.format_display_path(path)?))
             .await?;
-        Ok(ToolOutput::text(format!("{meta:?}")))
+        Ok(ToolOutput::text(output))
+    }
+
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        true
     }
 }
 
 #[cfg(test)]
 mod test {
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
     use tokio::fs;
 
     use super::*;
@@ -100,14 +181,37 @@ mod test {
         let result = fs_info
             .call(
                 ToolCallContext::default(),
-                FSFileInfoInput { path: file_path.to_string_lossy().to_string() },
+                FSFileInfoInput { path: file_path.to_string_lossy().to_string(), git: None },
             )
             .await
             .unwrap();
 
-        assert!(result.contains("FileType"));
-        assert!(result.contains("permissions"));
-        assert!(result.contains("modified"));
+        assert!(result.contains("type: file"));
+        assert!(result.contains("size: 12 B"));
+        assert!(result.contains("modified: "));
+    }
+
+    #[tokio::test]
+    async fn test_fs_file_info_git_outside_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        fs::write(&file_path, "test content").await.unwrap();
+
+        let stub = Arc::new(crate::tools::registry::tests::Stub::default());
+        let fs_info = FSFileInfo::new(stub);
+        let result = fs_info
+            .call(
+                ToolCallContext::default(),
+                FSFileInfoInput {
+                    path: file_path.to_string_lossy().to_string(),
+                    git: Some(true),
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(result.contains("type: file"));
+        assert!(!result.contains("last commit"));
     }
 
     #[tokio::test]
@@ -122,14 +226,68 @@ mod test {
         let result = fs_info
             .call(
                 ToolCallContext::default(),
-                FSFileInfoInput { path: dir_path.to_string_lossy().to_string() },
+                FSFileInfoInput { path: dir_path.to_string_lossy().to_string(), git: None },
             )
             .await
             .unwrap();
 
-        assert!(result.contains("FileType"));
-        assert!(result.contains("permissions"));
-        assert!(result.contains("modified"));
+        assert!(result.contains("type: directory"));
+        assert!(result.contains("modified: "));
+    }
+
+    /// Reads the metadata of `path` with fixed timestamps so the output is
+    /// stable across runs
+    async fn fixture_metadata(path: &Path, mode: u32) -> FileMetadata {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+                .await
+                .unwrap();
+        }
+        let time = DateTime::parse_from_rfc3339("2024-05-01T12:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let meta = ForgeFS::file_metadata(path).await.unwrap();
+        FileMetadata { modified: Some(time), created: Some(time), ..meta }
+    }
+
+    #[tokio::test]
+    async fn test_format_file_info_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        fs::write(&file_path, "a".repeat(1536)).await.unwrap();
+        let meta = fixture_metadata(&file_path, 0o644).await;
+
+        let actual = format_file_info(&meta, None);
+
+        assert_snapshot!(actual);
+    }
+
+    #[tokio::test]
+    async fn test_format_file_info_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path().join("test_dir");
+        fs::create_dir(&dir_path).await.unwrap();
+        // Directory sizes depend on the file system
+        let meta = FileMetadata { size: 4096, ..fixture_metadata(&dir_path, 0o755).await };
+
+        let actual = format_file_info(&meta, None);
+
+        assert_snapshot!(actual);
+    }
+
+    #[test]
+    fn test_format_size() {
+        let actual = [0, 1023, 1024, 5 * 1024 * 1024 + 512 * 1024].map(format_size);
+
+        let expected = [
+            "0 B".to_string(),
+            "1023 B".to_string(),
+            "1.0 KiB (1024 bytes)".to_string(),
+            "5.5 MiB (5767168 bytes)".to_string(),
+        ];
+        assert_eq!(actual, expected);
     }
 
     #[tokio::test]
@@ -143,7 +301,10 @@ mod test {
         let result = fs_info
             .call(
                 ToolCallContext::default(),
-                FSFileInfoInput { path: nonexistent_path.to_string_lossy().to_string() },
+                FSFileInfoInput {
+                    path: nonexistent_path.to_string_lossy().to_string(),
+                    git: None,
+                },
             )
             .await;
 
@@ -158,7 +319,7 @@ mod test {
         let result = fs_info
             .call(
                 ToolCallContext::default(),
-                FSFileInfoInput { path: "relative/path.txt".to_string() },
+                FSFileInfoInput { path: "relative/path.txt".to_string(), git: None },
             )
             .await;
 
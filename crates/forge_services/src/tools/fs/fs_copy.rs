@@ -0,0 +1,228 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use forge_display::TitleFormat;
+use forge_domain::{
+    EnvironmentService, ExecutableTool, FSCopyInput, NamedTool, ToolCallContext, ToolDescription,
+    ToolName, ToolOutput,
+};
+use forge_tool_macros::ToolDescription;
+
+use crate::utils::{assert_absolute_path, assert_path_within, format_display_path, is_same_file};
+use crate::{FsCopyService, FsCreateDirsService, FsMetaService, Infrastructure};
+
+// Using FSCopyInput from forge_domain
+
+/// Copies a file from the source path to the destination path, keeping its
+/// permissions where possible. Missing parent directories of the destination
+/// are created. The destination must be inside the project directory and
+/// differ from the source. Fails if the destination exists unless overwrite is
+/// set. Both paths must be absolute. Use this instead of reading and writing a
+/// file to duplicate it.
+#[derive(ToolDescription)]
+pub struct FSCopy<T>(Arc<T>);
+
+impl<T: Infrastructure> FSCopy<T> {
+    pub fn new(infra: Arc<T>) -> Self {
+        Self(infra)
+    }
+
+    /// Formats a path for display, converting absolute paths to relative when
+    /// possible
+    fn format_display_path(&self, path: &Path) -> anyhow::Result<String> {
+        let env = self.0.environment_service().get_environment();
+        format_display_path(path, env.cwd.as_path())
+    }
+}
+
+impl<T> NamedTool for FSCopy<T> {
+    fn tool_name() -> ToolName {
+        ToolName::new("forge_tool_fs_copy")
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: Infrastructure> ExecutableTool for FSCopy<T> {
+    type Input = FSCopyInput;
+
+    async fn call(
+        &self,
+        context: ToolCallContext,
+        input: Self::Input,
+    ) -> anyhow::Result<ToolOutput> {
+        let source = Path::new(&input.source);
+        let destination = Path::new(&input.destination);
+        assert_absolute_path(source)?;
+        assert_absolute_path(destination)?;
+
+        let env = self.0.environment_service().get_environment();
+        assert_path_within(destination, &env.cwd)?;
+        if is_same_file(source, destination) {
+            return Err(anyhow::anyhow!(
+                "Cannot copy a file onto itself: {}",
+                input.source
+            ));
+        }
+
+        let meta = self.0.file_meta_service();
+        if !meta.exists(source).await? {
+            return Err(anyhow::anyhow!("File not found: {}", input.source));
+        }
+        if !meta.is_file(source).await? {
+            return Err(anyhow::anyhow!("Path is not a file: {}", input.source));
+        }
+        if meta.exists(destination).await? && !input.overwrite {
+            return Err(anyhow::anyhow!(
+                "Destination already exists: {}. Set overwrite to replace it.",
+                input.destination
+            ));
+        }
+
+        if let Some(parent) = destination.parent() {
+            self.0.create_dirs_service().create_dirs(parent).await?;
+        }
+        self.0.file_copy_service().copy(source, destination).await?;
+
+        context
+            .send_text(TitleFormat::debug("Copy").title(format!(
+                "{} -> {}",
+                self.format_display_path(source)?,
+                self.format_display_path(destination)?
+            )))
+            .await?;
+
+        Ok(ToolOutput::text(format!(
+            "Successfully copied file: {} -> {}",
+            input.source, input.destination
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::attachment::tests::MockInfrastructure;
+    use crate::utils::ToolContentExtension;
+    use crate::FsReadService;
+
+    // The mock environment uses /test as the project directory, which already
+    // holds /test/file1.txt
+    const SOURCE: &str = "/test/file1.txt";
+
+    fn input(destination: &str) -> FSCopyInput {
+        FSCopyInput {
+            source: SOURCE.to_string(),
+            destination: destination.to_string(),
+            overwrite: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fs_copy_creates_parent_dirs() {
+        let infra = Arc::new(MockInfrastructure::new());
+
+        let result = FSCopy::new(infra.clone())
+            .call(ToolCallContext::default(), input("/test/nested/copy.txt"))
+            .await
+            .unwrap();
+
+        let source = infra
+            .file_read_service()
+            .read_utf8(Path::new(SOURCE))
+            .await
+            .unwrap();
+        let copy = infra
+            .file_read_service()
+            .read_utf8(Path::new("/test/nested/copy.txt"))
+            .await
+            .unwrap();
+        assert!(result.contains("Successfully copied file"));
+        assert_eq!(copy, source);
+        assert!(infra
+            .file_meta_service()
+            .exists(Path::new("/test/nested"))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_fs_copy_existing_destination() {
+        let infra = Arc::new(MockInfrastructure::new());
+        let fs_copy = FSCopy::new(infra.clone());
+
+        let refused = fs_copy
+            .call(ToolCallContext::default(), input("/test/image.png"))
+            .await;
+        let unchanged = infra
+            .file_read_service()
+            .read_utf8(Path::new("/test/image.png"))
+            .await
+            .unwrap();
+        let overwritten = fs_copy
+            .call(
+                ToolCallContext::default(),
+                FSCopyInput { overwrite: true, ..input("/test/image.png") },
+            )
+            .await;
+        let replaced = infra
+            .file_read_service()
+            .read_utf8(Path::new("/test/image.png"))
+            .await
+            .unwrap();
+
+        assert!(refused
+            .unwrap_err()
+            .to_string()
+            .contains("Destination already exists"));
+        assert_eq!(unchanged, "mock-binary-content");
+        assert!(overwritten.is_ok());
+        assert_eq!(replaced, "This is a text file content");
+    }
+
+    #[tokio::test]
+    async fn test_fs_copy_onto_itself() {
+        let infra = Arc::new(MockInfrastructure::new());
+
+        let result = FSCopy::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSCopyInput { overwrite: true, ..input("/test/./file1.txt") },
+            )
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("onto itself"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_copy_outside_project() {
+        let infra = Arc::new(MockInfrastructure::new());
+
+        let result = FSCopy::new(infra)
+            .call(ToolCallContext::default(), input("/test/../etc/file1.txt"))
+            .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("outside of the project directory"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_copy_nonexistent_source() {
+        let infra = Arc::new(MockInfrastructure::new());
+
+        let result = FSCopy::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSCopyInput {
+                    source: "/test/missing.txt".to_string(),
+                    ..input("/test/b.txt")
+                },
+            )
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("File not found"));
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
-use std::path::Path;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::Context;
@@ -10,7 +11,7 @@ use forge_domain::{
 };
 use forge_tool_macros::ToolDescription;
 use forge_walker::Walker;
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 
 use crate::metadata::Metadata;
 use crate::utils::{assert_absolute_path, format_display_path};
@@ -18,6 +19,16 @@ use crate::{Clipper, FsWriteService, Infrastructure};
 
 const MAX_SEARCH_CHAR_LIMIT: usize = 40_000;
 
+/// Upper bound in bytes of a compiled search pattern, so that pathological
+/// patterns fail to compile instead of exhausting memory
+const MAX_REGEX_SIZE: usize = 1 << 20;
+
+/// Upper bound of the context lines shown on either side of a match
+const MAX_CONTEXT_LINES: usize = 10;
+
+/// Number of results returned per page unless the input asks otherwise
+const DEFAULT_MAX_RESULTS: usize = 200;
+
 // Using FSSearchInput from forge_domain
 
 // Helper to handle FSSearchInput functionality
@@ -36,6 +47,71 @@ impl FSSearchHelper<'_> {
         self.0.file_pattern.as_ref()
     }
 
+    fn include_ignored(&self) -> bool {
+        self.0.include_ignored.unwrap_or(false)
+    }
+
+    fn include_hidden(&self) -> bool {
+        self.0.include_hidden.unwrap_or(false)
+    }
+
+    fn is_regex(&self) -> bool {
+        self.0.is_regex.unwrap_or(true)
+    }
+
+    fn case_sensitive(&self) -> bool {
+        self.0.case_sensitive.unwrap_or(false)
+    }
+
+    fn whole_word(&self) -> bool {
+        self.0.whole_word.unwrap_or(false)
+    }
+
+    fn context_before(&self) -> usize {
+        self.0.context_before.unwrap_or(0).min(MAX_CONTEXT_LINES)
+    }
+
+    fn context_after(&self) -> usize {
+        self.0.context_after.unwrap_or(0).min(MAX_CONTEXT_LINES)
+    }
+
+    fn max_results(&self) -> usize {
+        self.0.max_results.unwrap_or(DEFAULT_MAX_RESULTS).max(1)
+    }
+
+    fn offset(&self) -> usize {
+        self.0.offset.unwrap_or(0)
+    }
+
+    /// Range of the result indices that belong to the requested page
+    fn page(&self) -> std::ops::Range<usize> {
+        self.offset()..self.offset().saturating_add(self.max_results())
+    }
+
+    /// Compiles the content pattern, escaping it when it is literal text and
+    /// anchoring it to word boundaries when whole words are requested
+    fn content_regex(&self) -> anyhow::Result<Option<Regex>> {
+        let Some(pattern) = self.regex() else {
+            return Ok(None);
+        };
+
+        let mut expr = if self.is_regex() {
+            pattern.clone()
+        } else {
+            regex::escape(pattern)
+        };
+        if self.whole_word() {
+            expr = format!(r"\b(?:{expr})\b");
+        }
+
+        RegexBuilder::new(&expr)
+            .case_insensitive(!self.case_sensitive())
+            .size_limit(MAX_REGEX_SIZE)
+            .build()
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("Invalid regex pattern '{pattern}': {e}"))
+    }
+
     fn get_file_pattern(&self) -> anyhow::Result<Option<glob::Pattern>> {
         Ok(match &self.0.file_pattern {
             Some(pattern) => Some(
@@ -67,14 +143,19 @@ impl FSSearchHelper<'_> {
 }
 
 /// Recursively searches directories for files by content (regex) and/or name
-/// (glob pattern). Provides context-rich results with line numbers for content
-/// matches. Two modes: content search (when regex provided) or file finder
-/// (when regex omitted). Uses case-insensitive Rust regex syntax. Requires
-/// absolute paths. Avoids binary files and excluded directories. Best for code
-/// exploration, API usage discovery, configuration settings, or finding
-/// patterns across projects. For large pages, returns the first 40,000
-/// characters and stores the complete content in a temporary file for
-/// subsequent access.
+/// (glob pattern). Two modes: content search (when regex provided) or file
+/// finder (when regex omitted). Uses case-insensitive Rust regex syntax by
+/// default; set is_regex to false for literal text, case_sensitive to match
+/// letter case and whole_word to only match whole words. Set context_before
+/// and context_after (up to 10) to include surrounding lines: matching lines
+/// are shown as path:line:content, context lines as path-line-content and
+/// separate blocks are divided by --.
+/// Requires absolute paths. Avoids binary files, files excluded by .gitignore
+/// or .ignore and noisy files such as lock files and minified bundles unless
+/// include_ignored is set, and hidden files unless include_hidden is set.
+/// Returns at most max_results results (200 by default), pass offset to fetch
+/// the following pages. For large pages, returns the first 40,000 characters
+/// and stores the complete content in a temporary file for subsequent access.
 #[derive(ToolDescription)]
 pub struct FSFind<F>(Arc<F>);
 
@@ -129,79 +210,123 @@ impl<F: Infrastructure> FSFind<F> {
         context.send_text(title_format).await?;
 
         // Create content regex pattern if provided
-        let regex = match helper.regex() {
-            Some(regex) => {
-                let pattern = format!("(?i){regex}"); // Case-insensitive by default
-                Some(
-                    Regex::new(&pattern)
-                        .with_context(|| format!("Invalid regex pattern: {regex}"))?,
-                )
-            }
-            None => None,
-        };
+        let regex = helper.content_regex()?;
+        let (before, after) = (helper.context_before(), helper.context_after());
+        let with_context = before > 0 || after > 0;
 
-        let paths = retrieve_file_paths(path).await?;
+        let env = self.0.environment_service().get_environment();
+        let Walked { paths, skipped } = retrieve_file_paths(
+            path,
+            helper.include_ignored(),
+            helper.include_hidden(),
+            env.ignore_patterns,
+        )
+        .await?;
+        let scanned = paths.iter().filter(|path| !path.is_dir()).count();
 
+        let page = helper.page();
         let mut matches = Vec::new();
+        // Results found so far, across all pages
+        let mut found = 0;
+        let mut searched = 0;
+        let mut with_matches = 0;
 
+        // Paths are sorted, so every call visits them in the same order and the
+        // pages are stable
         for path in paths {
+            // Once a result past the page is found the page is complete and the
+            // results are known to be truncated
+            if found > page.end {
+                break;
+            }
+
             if !helper.match_file_path(path.as_path())? {
                 continue;
             }
+            searched += 1;
 
             // File name only search mode
-            if regex.is_none() {
-                matches.push((self.format_display_path(&path)?).to_string());
+            let Some(regex) = &regex else {
+                if page.contains(&found) {
+                    matches.push((self.format_display_path(&path)?).to_string());
+                }
+                found += 1;
+                with_matches += 1;
                 continue;
-            }
+            };
 
             // Content matching mode - read and search file contents
             let content = match forge_fs::ForgeFS::read_to_string(&path).await {
                 Ok(content) => content,
-                Err(e) => {
-                    // Skip binary or unreadable files silently
-                    if let Some(e) = e
-                        .downcast_ref::<std::io::ErrorKind>()
-                        .map(|e| std::io::ErrorKind::InvalidData.eq(e))
-                    {
-                        matches.push(format!(
-                            "Error reading {}: {}",
-                            self.format_display_path(&path)?,
-                            e
-                        ));
-                    }
-                    continue;
-                }
+                // Skip binary or unreadable files silently
+                Err(_) => continue,
             };
 
             // Process the file line by line to find content matches
-            if let Some(regex) = &regex {
-                let mut found_match = false;
-
-                for (line_num, line) in content.lines().enumerate() {
-                    if regex.is_match(line) {
-                        found_match = true;
-                        // Format match in ripgrep style: filepath:line_num:content
-                        matches.push(format!(
-                            "{}:{}:{}",
-                            self.format_display_path(&path)?,
-                            line_num + 1,
-                            line
-                        ));
-                    }
+            let lines = content.lines().collect::<Vec<_>>();
+            let matched = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| regex.is_match(line))
+                .map(|(line_num, _)| line_num)
+                .collect::<Vec<_>>();
+
+            // Files without matching content are left out of the results
+            if matched.is_empty() {
+                continue;
+            }
+            with_matches += 1;
+
+            // Only the matches that fall on the requested page are shown
+            let shown = matched
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| page.contains(&(found + index)))
+                .map(|(_, line_num)| *line_num)
+                .collect::<Vec<_>>();
+            found += matched.len();
+
+            let display_path = self.format_display_path(&path)?;
+            let windows = context_windows(&shown, lines.len(), before, after);
+            for window in windows {
+                if with_context && !matches.is_empty() {
+                    matches.push("--".to_string());
                 }
-
-                // If no matches found in content but we're looking for content,
-                // don't add this file to matches
-                if !found_match && helper.regex().is_some() {
-                    continue;
+                // Format in ripgrep style: filepath:line_num:content for
+                // matches and filepath-line_num-content for context
+                for line_num in window {
+                    let separator = if matched.binary_search(&line_num).is_ok() {
+                        ':'
+                    } else {
+                        '-'
+                    };
+                    matches.push(format!(
+                        "{display_path}{separator}{}{separator}{}",
+                        line_num + 1,
+                        lines[line_num]
+                    ));
                 }
             }
         }
 
         // Format and return results
+        if found == 0 {
+            return Ok(format!(
+                "No matches found. Scanned {scanned} files, skipped {skipped} ignored paths."
+            ));
+        }
+
+        let has_more = found > page.end;
+        let total_matches = if has_more {
+            format!("{found}+")
+        } else {
+            found.to_string()
+        };
         if matches.is_empty() {
-            return Ok("No matches found.".to_string());
+            return Ok(format!(
+                "No results at offset {}, the search found {total_matches} results.",
+                page.start
+            ));
         }
 
         let mut formatted_output = GrepFormat::new(matches.clone());
@@ -218,9 +343,25 @@ impl<F: Infrastructure> FSFind<F> {
             .add("path", input.path)
             .add_optional("regex", input.regex)
             .add_optional("file_pattern", input.file_pattern)
+            .add("files_scanned", scanned)
+            .add("paths_skipped", skipped)
+            .add("files_searched", searched)
+            .add("files_with_matches", with_matches)
+            .add("total_matches", &total_matches)
             .add("total_chars", matches.len())
             .add("start_char", 0);
 
+        let page_tag = if has_more {
+            format!(
+                "\n<truncation>results truncated, showing results {}-{} of {total_matches} matches, refine your query or pass offset {} for the next page</truncation>",
+                page.start + 1,
+                page.end,
+                page.end
+            )
+        } else {
+            String::new()
+        };
+
         let truncated_result = Clipper::from_start(max_char_limit).clip(&matches);
         if let Some(truncated) = truncated_result.prefix_content() {
             let path = self
@@ -236,35 +377,71 @@ impl<F: Infrastructure> FSFind<F> {
             let truncation_tag = format!("\n<truncation>content is truncated to {} chars, remaining content can be read from path:{}</truncation>", 
             max_char_limit,path.to_string_lossy());
 
-            Ok(format!("{metadata}{truncated}{truncation_tag}"))
+            Ok(format!("{metadata}{truncated}{truncation_tag}{page_tag}"))
         } else {
             let metadata = metadata.add("end_char", matches.len());
-            Ok(format!("{metadata}{matches}"))
+            Ok(format!("{metadata}{matches}{page_tag}"))
         }
     }
 }
 
-async fn retrieve_file_paths(dir: &Path) -> anyhow::Result<Vec<std::path::PathBuf>> {
+/// Returns the line ranges to show for the matched lines of a file, each
+/// widened by the requested context and merged with the previous range when
+/// they overlap or touch, so that no line is shown twice
+fn context_windows(
+    matched: &[usize],
+    line_count: usize,
+    before: usize,
+    after: usize,
+) -> Vec<RangeInclusive<usize>> {
+    let mut windows: Vec<RangeInclusive<usize>> = Vec::new();
+    for &line_num in matched {
+        let start = line_num.saturating_sub(before);
+        let end = (line_num + after).min(line_count.saturating_sub(1));
+        match windows.last_mut() {
+            Some(last) if start <= *last.end() + 1 => *last = *last.start()..=end,
+            _ => windows.push(start..=end),
+        }
+    }
+    windows
+}
+
+/// Paths found by walking a directory
+struct Walked {
+    paths: Vec<PathBuf>,
+    /// Number of files and directories left out because of ignore rules
+    skipped: usize,
+}
+
+async fn retrieve_file_paths(
+    dir: &Path,
+    include_ignored: bool,
+    include_hidden: bool,
+    ignore_patterns: Vec<String>,
+) -> anyhow::Result<Walked> {
     if dir.is_dir() {
-        // note: Paths needs mutable to avoid flaky tests.
-        #[allow(unused_mut)]
-        let mut paths = Walker::max_all()
+        let walked = Walker::max_all()
             .cwd(dir.to_path_buf())
-            .get()
+            .skip_ignored(!include_ignored)
+            .skip_hidden(!include_hidden)
+            .ignore_patterns(ignore_patterns)
+            .walk()
             .await
-            .with_context(|| format!("Failed to walk directory '{}'", dir.display()))?
+            .with_context(|| format!("Failed to walk directory '{}'", dir.display()))?;
+
+        let mut paths = walked
+            .files
             .into_iter()
-            .map(|file| dir.join(file.path))
+            .map(|file| dir.join(file.path.trim_start_matches('/')))
             .collect::<HashSet<_>>()
             .into_iter()
             .collect::<Vec<_>>();
 
-        #[cfg(test)]
         paths.sort();
 
-        Ok(paths)
+        Ok(Walked { paths, skipped: walked.skipped })
     } else {
-        Ok(Vec::from_iter([dir.to_path_buf()]))
+        Ok(Walked { paths: Vec::from_iter([dir.to_path_buf()]), skipped: 0 })
     }
 }
 
@@ -288,6 +465,10 @@ impl<F: Infrastructure> ExecutableTool for FSFind<F> {
             .await?;
         Ok(ToolOutput::text(result))
     }
+
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -321,7 +502,7 @@ mod test {
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     regex: Some("test".to_string()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await
@@ -351,6 +532,7 @@ mod test {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     regex: Some("test".to_string()),
                     file_pattern: Some("*.rs".to_string()),
+                    ..Default::default()
                 },
             )
             .await
@@ -380,8 +562,8 @@ mod test {
                 ToolCallContext::default(),
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
-                    regex: None,
                     file_pattern: Some("test*.txt".to_string()),
+                    ..Default::default()
                 },
             )
             .await
@@ -409,7 +591,7 @@ mod test {
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     regex: Some("test".to_string()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await
@@ -443,7 +625,7 @@ mod test {
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     regex: Some("test".to_string()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await
@@ -473,7 +655,7 @@ mod test {
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     regex: Some("test".to_string()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await
@@ -499,7 +681,7 @@ mod test {
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     regex: Some("nonexistent".to_string()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await
@@ -526,8 +708,7 @@ mod test {
                 ToolCallContext::default(),
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
-                    regex: None,
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await
@@ -549,7 +730,7 @@ mod test {
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     regex: Some("[invalid".to_string()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await;
@@ -561,6 +742,267 @@ mod test {
             .contains("Invalid regex pattern"));
     }
 
+    /// Searches the files of `dir` for `pattern` with the given options
+    async fn search_with(
+        dir: &TempDir,
+        pattern: &str,
+        is_regex: bool,
+        case_sensitive: bool,
+        whole_word: bool,
+    ) -> anyhow::Result<String> {
+        let infra = Arc::new(MockInfrastructure::new());
+        FSFind::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSSearchInput {
+                    path: dir.path().to_string_lossy().to_string(),
+                    regex: Some(pattern.to_string()),
+                    is_regex: Some(is_regex),
+                    case_sensitive: Some(case_sensitive),
+                    whole_word: Some(whole_word),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map(|output| output.into_string())
+    }
+
+    /// Searches `test.txt` for "match" and returns the result lines with the
+    /// directory stripped from the paths
+    async fn search_context(content: &str, before: usize, after: usize) -> Vec<String> {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("test.txt"), content)
+            .await
+            .unwrap();
+        let infra = Arc::new(MockInfrastructure::new());
+        let output = FSFind::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSSearchInput {
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    regex: Some("match".to_string()),
+                    context_before: Some(before),
+                    context_after: Some(after),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        let prefix = format!("{}/", temp_dir.path().display());
+        output
+            .lines()
+            .filter(|line| line.starts_with(&prefix) || *line == "--")
+            .map(|line| line.trim_start_matches(&prefix).to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_context_merges_overlapping_windows() {
+        let content = "one\ntwo\nmatch a\nthree\nmatch b\nfour\nfive\nsix\nseven\nmatch c\neight";
+
+        let actual = search_context(content, 1, 1).await;
+
+        let expected = vec![
+            "test.txt-2-two",
+            "test.txt:3:match a",
+            "test.txt-4-three",
+            "test.txt:5:match b",
+            "test.txt-6-four",
+            "--",
+            "test.txt-9-seven",
+            "test.txt:10:match c",
+            "test.txt-11-eight",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_context_at_file_boundaries() {
+        let content = "match first\ntwo\nthree\nfour\nmatch last";
+
+        let actual = search_context(content, 2, 2).await;
+
+        let expected = vec![
+            "test.txt:1:match first",
+            "test.txt-2-two",
+            "test.txt-3-three",
+            "test.txt-4-four",
+            "test.txt:5:match last",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_context_is_capped() {
+        let content = (1..=30)
+            .map(|n| {
+                if n == 15 {
+                    "match".to_string()
+                } else {
+                    n.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let actual = search_context(&content, 50, 50).await;
+
+        assert_eq!(actual.first().unwrap(), "test.txt-5-5");
+        assert_eq!(actual.last().unwrap(), "test.txt-25-25");
+    }
+
+    /// Searches a directory of 30 files with two matching lines each and
+    /// returns the matching lines of the page along with the full output
+    async fn search_page(max_results: usize, offset: usize) -> (Vec<String>, String) {
+        let temp_dir = TempDir::new().unwrap();
+        for n in 0..30 {
+            fs::write(
+                temp_dir.path().join(format!("file{n:02}.txt")),
+                "needle one\nhay\nneedle two",
+            )
+            .await
+            .unwrap();
+        }
+        let infra = Arc::new(MockInfrastructure::new());
+        let output = FSFind::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSSearchInput {
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    regex: Some("needle".to_string()),
+                    max_results: Some(max_results),
+                    offset: Some(offset),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        let prefix = format!("{}/", temp_dir.path().display());
+        let lines = output
+            .lines()
+            .filter(|line| line.starts_with(&prefix))
+            .map(|line| line.trim_start_matches(&prefix).to_string())
+            .collect();
+        (lines, output)
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_pages_do_not_overlap() {
+        let (first, first_output) = search_page(25, 0).await;
+        let (second, _) = search_page(25, 25).await;
+
+        assert_eq!(first.len(), 25);
+        assert_eq!(second.len(), 25);
+        assert_eq!(first.first().unwrap(), "file00.txt:1:needle one");
+        assert_eq!(second.first().unwrap(), "file12.txt:3:needle two");
+        assert!(first.iter().all(|line| !second.contains(line)));
+        assert!(first_output.contains("results truncated"));
+        assert!(first_output.contains("pass offset 25"));
+        assert!(first_output.contains("total_matches: 26+"));
+        // The search stops once the page is full
+        assert!(first_output.contains("files_searched: 13"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_last_page() {
+        let (actual, output) = search_page(25, 50).await;
+
+        assert_eq!(actual.len(), 10);
+        assert_eq!(actual.last().unwrap(), "file29.txt:3:needle two");
+        assert!(!output.contains("results truncated"));
+        assert!(output.contains("files_searched: 30"));
+        assert!(output.contains("files_with_matches: 30"));
+        assert!(output.contains("total_matches: 60"));
+    }
+
+    #[test]
+    fn test_context_windows() {
+        // Adjacent windows merge, like overlapping ones
+        let actual = context_windows(&[0, 3, 4, 9], 10, 1, 1);
+
+        let expected = vec![0..=5, 8..=9];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_regex_is_line_based() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "fn render_header() {}\nfn\nrender_footer() {}\nfn parse() {}",
+        )
+        .await
+        .unwrap();
+
+        let actual = search_with(&temp_dir, r"fn\s+render_\w+", true, false, false)
+            .await
+            .unwrap();
+
+        assert!(actual.contains(":1:fn render_header() {}"));
+        assert!(!actual.contains("render_footer"));
+        assert!(!actual.contains("parse"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_case_insensitive_literal() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("test.txt"),
+            "Total (USD): 10\ntotal (usd): 20\ntotal usd: 30",
+        )
+        .await
+        .unwrap();
+
+        let insensitive = search_with(&temp_dir, "total (usd)", false, false, false)
+            .await
+            .unwrap();
+        let sensitive = search_with(&temp_dir, "total (usd)", false, true, false)
+            .await
+            .unwrap();
+
+        assert!(insensitive.contains(":1:Total (USD): 10"));
+        assert!(insensitive.contains(":2:total (usd): 20"));
+        assert!(!insensitive.contains("total usd: 30"));
+        assert!(!sensitive.contains("Total (USD): 10"));
+        assert!(sensitive.contains(":2:total (usd): 20"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_whole_word() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("test.txt"),
+            "let cat = 1;\nconcatenate();\ncat_name();",
+        )
+        .await
+        .unwrap();
+
+        let actual = search_with(&temp_dir, "cat", false, false, true)
+            .await
+            .unwrap();
+
+        assert!(actual.contains(":1:let cat = 1;"));
+        assert!(!actual.contains("concatenate"));
+        assert!(!actual.contains("cat_name"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_invalid_regex_reports_pattern_and_cause() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let actual = search_with(&temp_dir, "fn (render", true, false, false)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(actual.contains("Invalid regex pattern 'fn (render'"));
+        assert!(actual.contains("unclosed group"));
+    }
+
     #[tokio::test]
     async fn test_fs_search_relative_path() {
         let infra = Arc::new(MockInfrastructure::new());
@@ -571,7 +1013,7 @@ mod test {
                 FSSearchInput {
                     path: "relative/path".to_string(),
                     regex: Some("test".to_string()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await;
@@ -582,6 +1024,124 @@ mod test {
             .to_string()
             .contains("Path must be absolute"));
     }
+
+    #[tokio::test]
+    async fn test_fs_search_respects_gitignore() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // .gitignore is only honored inside a git repository
+        fs::create_dir(temp_dir.path().join(".git")).await.unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), "target/\n")
+            .await
+            .unwrap();
+        fs::create_dir_all(temp_dir.path().join("src"))
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("src/lib.rs"), "fn needle() {}")
+            .await
+            .unwrap();
+        fs::create_dir_all(temp_dir.path().join("target/debug"))
+            .await
+            .unwrap();
+        fs::write(
+            temp_dir.path().join("target/debug/build.rs"),
+            "fn needle() {}",
+        )
+        .await
+        .unwrap();
+
+        let infra = Arc::new(MockInfrastructure::new());
+        let fs_search = FSFind::new(infra);
+        let search = |include_ignored| {
+            fs_search.call(
+                ToolCallContext::default(),
+                FSSearchInput {
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    regex: Some("needle".to_string()),
+                    include_ignored,
+                    ..Default::default()
+                },
+            )
+        };
+        let default = search(None).await.unwrap().into_string();
+        let included = search(Some(true)).await.unwrap().into_string();
+
+        assert!(default.contains("lib.rs"));
+        assert!(!default.contains("build.rs"));
+        assert!(default.contains("paths_skipped: 1"));
+        assert!(included.contains("lib.rs"));
+        assert!(included.contains("build.rs"));
+        assert!(included.contains("paths_skipped: 0"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_skips_lock_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("main.rs"), "// needle")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("Cargo.lock"), "# needle")
+            .await
+            .unwrap();
+
+        let infra = Arc::new(MockInfrastructure::new());
+        let fs_search = FSFind::new(infra);
+        let search = |include_ignored| {
+            fs_search.call(
+                ToolCallContext::default(),
+                FSSearchInput {
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    regex: Some("needle".to_string()),
+                    include_ignored,
+                    ..Default::default()
+                },
+            )
+        };
+        let default = search(None).await.unwrap().into_string();
+        let included = search(Some(true)).await.unwrap().into_string();
+
+        assert!(default.contains("main.rs"));
+        assert!(!default.contains("Cargo.lock"));
+        assert!(default.contains("paths_skipped: 1"));
+        assert!(included.contains("main.rs"));
+        assert!(included.contains("Cargo.lock"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_hidden_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("main.rs"), "// needle")
+            .await
+            .unwrap();
+        fs::create_dir_all(temp_dir.path().join(".github"))
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join(".github/ci.yml"), "# needle")
+            .await
+            .unwrap();
+
+        let infra = Arc::new(MockInfrastructure::new());
+        let fs_search = FSFind::new(infra);
+        let search = |include_hidden| {
+            fs_search.call(
+                ToolCallContext::default(),
+                FSSearchInput {
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    regex: Some("needle".to_string()),
+                    include_hidden,
+                    ..Default::default()
+                },
+            )
+        };
+        let default = search(None).await.unwrap().into_string();
+        let included = search(Some(true)).await.unwrap().into_string();
+
+        assert!(default.contains("main.rs"));
+        assert!(!default.contains("ci.yml"));
+        assert!(included.contains("main.rs"));
+        assert!(included.contains("ci.yml"));
+    }
+
     #[tokio::test]
     async fn test_format_display_path() {
         let temp_dir = TempDir::new().unwrap();
@@ -627,7 +1187,7 @@ mod test {
                 FSSearchInput {
                     path: temp_dir.path().join("best.txt").display().to_string(),
                     regex: Some("nice".to_string()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await
@@ -644,8 +1204,7 @@ mod test {
                 ToolCallContext::default(),
                 FSSearchInput {
                     path: temp_dir.path().join("best.txt").display().to_string(),
-                    regex: None,
-                    file_pattern: None,
+                    ..Default::default()
                 },
             )
             .await
@@ -670,7 +1229,7 @@ mod test {
                 FSSearchInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     regex: Some("content*".into()),
-                    file_pattern: None,
+                    ..Default::default()
                 },
                 100,
             )
@@ -1,11 +1,13 @@
 use std::path::Path;
 
 use anyhow::Context;
+use chrono::SecondsFormat;
 use forge_domain::{
     ExecutableTool, NamedTool, ToolCallContext, ToolDescription, ToolName, ToolOutput,
 };
+use forge_fs::ForgeFS;
 use forge_tool_macros::ToolDescription;
-use forge_walker::Walker;
+use forge_walker::{File, Walker};
 use schemars::JsonSchema;
 use serde::Deserialize;
 
@@ -18,17 +20,59 @@ pub struct FSListInput {
     /// Whether to list files recursively. Use true for recursive listing, false
     /// or omit for top-level only.
     pub recursive: Option<bool>,
+    /// How many levels of directories to descend into when listing
+    /// recursively. Omit to list the whole tree.
+    pub max_depth: Option<usize>,
 }
 
+/// Maximum number of entries listed before the output is truncated
+const MAX_ENTRIES: usize = 2000;
+
+/// Version control directories, which are never listed
+const VCS_DIRS: [&str; 6] = [".git", ".hg", ".svn", ".bzr", "_darcs", "CVS"];
+
 /// Request to list files and directories within the specified directory. If
-/// recursive is true, it will list all files and directories recursively. If
-/// recursive is false or not provided, it will only list the top-level
-/// contents. The path must be absolute. Do not use this tool to confirm the
-/// existence of files you may have created, as the user will let you know if
-/// the files were created successfully or not.
+/// recursive is true, it will list all files and directories recursively as a
+/// tree, indenting entries by their depth, down to max_depth levels if given.
+/// If recursive is false or not provided, it will only list the top-level
+/// contents. Directories come before files at every level and files are listed
+/// with their size in bytes and last modified time. Files ignored by
+/// .gitignore and version control directories such as .git are not listed,
+/// and at most 2000 entries are returned. The path must be absolute. Do not
+/// use this tool to confirm the existence of files you may have created, as
+/// the user will let you know if the files were created successfully or not.
 #[derive(Default, ToolDescription)]
-pub struct FSList {
-    sorted: bool,
+pub struct FSList;
+
+/// Sort key placing every directory right before its own entries, with the
+/// directories of each level ahead of its files and both sorted by name
+fn tree_key(entry: &File) -> Vec<(bool, &str)> {
+    let mut key: Vec<_> = entry
+        .path
+        .trim_end_matches('/')
+        .split('/')
+        .map(|name| (false, name))
+        .collect();
+    if let Some(last) = key.last_mut() {
+        last.0 = !entry.is_dir();
+    }
+    key
+}
+
+/// Renders the size and modification time of a file as tag attributes,
+/// leaving out whatever can't be read
+async fn file_attributes(path: &Path) -> String {
+    let Ok(meta) = ForgeFS::file_metadata(path).await else {
+        return String::new();
+    };
+    let mut attributes = format!(" size=\"{}\"", meta.size);
+    if let Some(modified) = meta.modified {
+        attributes.push_str(&format!(
+            " modified=\"{}\"",
+            modified.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ));
+    }
+    attributes
 }
 
 impl NamedTool for FSList {
@@ -55,7 +99,11 @@ impl ExecutableTool for FSList {
 
         let mut paths = Vec::new();
         let recursive = input.recursive.unwrap_or(false);
-        let max_depth = if recursive { usize::MAX } else { 1 };
+        let max_depth = if recursive {
+            input.max_depth.unwrap_or(usize::MAX).max(1)
+        } else {
+            1
+        };
 
         let walker = Walker::max_all()
             .cwd(dir.to_path_buf())
@@ -66,24 +114,38 @@ impl ExecutableTool for FSList {
             .await
             .with_context(|| format!("Failed to read directory contents from '{}'", input.path))?;
 
-        // Sort the files for consistent snapshots
-        if self.sorted {
-            files.sort_by(|a, b| a.path.cmp(&b.path));
+        files.sort_by(|a, b| tree_key(a).cmp(&tree_key(b)));
+
+        // Skip the root directory itself and anything under version control
+        // directories
+        files.retain(|entry| {
+            !entry.path.is_empty()
+                && entry.path != "/"
+                && !entry.path.split('/').any(|name| VCS_DIRS.contains(&name))
+        });
+
+        // Symbolic links aren't followed by the walker, so a link pointing back up
+        // the tree is listed without being descended into
+        let total = files.len();
+        for entry in files.into_iter().take(MAX_ENTRIES) {
+            let depth = entry.path.trim_end_matches('/').matches('/').count();
+            let indent = "  ".repeat(depth);
+            if entry.is_dir() {
+                paths.push(format!("{indent}<dir path=\"{}\">", entry.path));
+            } else {
+                let attributes = file_attributes(&dir.join(&entry.path)).await;
+                paths.push(format!(
+                    "{indent}<file path=\"{}\"{attributes}>",
+                    entry.path
+                ));
+            };
         }
 
-        for entry in files {
-            // Skip the root directory itself
-            if entry.path == dir.to_string_lossy() {
-                continue;
-            }
-
-            if !entry.path.is_empty() {
-                if entry.is_dir() {
-                    paths.push(format!("<dir path=\"{}\">", entry.path));
-                } else {
-                    paths.push(format!("<file path=\"{}\">", entry.path));
-                };
-            }
+        if total > MAX_ENTRIES {
+            paths.push(format!(
+                "<truncated>...{} more entries not shown, only the first {MAX_ENTRIES} are listed. List a subdirectory or lower max_depth...</truncated>",
+                total - MAX_ENTRIES
+            ));
         }
 
         Ok(ToolOutput::text(format!(
@@ -94,40 +156,50 @@ impl ExecutableTool for FSList {
             paths.join("\n")
         )))
     }
+
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
 mod test {
     use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+    use regex::Regex;
     use tokio::fs;
 
     use super::*;
     use crate::utils::{TempDir, ToolContentExtension};
 
-    impl FSList {
-        fn new(sorted: bool) -> Self {
-            Self { sorted }
-        }
+    /// Removes the temporary directory and the modification times, which
+    /// change on every run
+    fn normalize(output: &str) -> String {
+        Regex::new(r#"modified="[^"]*""#)
+            .unwrap()
+            .replace_all(&TempDir::normalize(output), r#"modified="[MODIFIED]""#)
+            .to_string()
     }
 
     #[tokio::test]
     async fn test_fs_list_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList;
         let result = fs_list
             .call(
                 ToolCallContext::default(),
                 FSListInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     recursive: None,
+                    max_depth: None,
                 },
             )
             .await
             .unwrap()
             .into_string();
 
-        assert_snapshot!(TempDir::normalize(result.as_str()));
+        assert_snapshot!(normalize(result.as_str()));
     }
 
     #[tokio::test]
@@ -143,20 +215,21 @@ mod test {
         fs::create_dir(temp_dir.path().join("dir1")).await.unwrap();
         fs::create_dir(temp_dir.path().join("dir2")).await.unwrap();
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList;
         let result = fs_list
             .call(
                 ToolCallContext::default(),
                 FSListInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     recursive: None,
+                    max_depth: None,
                 },
             )
             .await
             .unwrap()
             .into_string();
 
-        assert_snapshot!(TempDir::normalize(result.as_str()));
+        assert_snapshot!(normalize(result.as_str()));
     }
 
     #[tokio::test]
@@ -164,13 +237,14 @@ mod test {
         let temp_dir = TempDir::new().unwrap();
         let nonexistent_dir = temp_dir.path().join("nonexistent");
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList;
         let result = fs_list
             .call(
                 ToolCallContext::default(),
                 FSListInput {
                     path: nonexistent_dir.to_string_lossy().to_string(),
                     recursive: None,
+                    max_depth: None,
                 },
             )
             .await;
@@ -192,13 +266,14 @@ mod test {
             .await
             .unwrap();
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList;
         let result = fs_list
             .call(
                 ToolCallContext::default(),
                 FSListInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     recursive: None,
+                    max_depth: None,
                 },
             )
             .await
@@ -229,7 +304,7 @@ mod test {
             .await
             .unwrap();
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList;
 
         // Test recursive listing
         let result = fs_list
@@ -238,22 +313,150 @@ mod test {
                 FSListInput {
                     path: temp_dir.path().to_string_lossy().to_string(),
                     recursive: Some(true),
+                    max_depth: None,
+                },
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        assert_snapshot!(normalize(result.as_str()));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_fs_list_tree_with_depth_limit_and_symlink_loop() {
+        let temp_dir = TempDir::new().unwrap();
+
+        fs::create_dir_all(temp_dir.path().join("a/b/c"))
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("a/top.txt"), "top")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("a/b/middle.txt"), "middle")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("a/b/c/bottom.txt"), "bottom")
+            .await
+            .unwrap();
+        // .gitignore is only honored inside a git repository
+        fs::create_dir(temp_dir.path().join(".git")).await.unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), "ignored.txt")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("a/ignored.txt"), "ignored")
+            .await
+            .unwrap();
+        std::os::unix::fs::symlink(
+            temp_dir.path().join("a"),
+            temp_dir.path().join("a/b/c/loop"),
+        )
+        .unwrap();
+
+        let fs_list = FSList;
+        let list = |max_depth| {
+            fs_list.call(
+                ToolCallContext::default(),
+                FSListInput {
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    recursive: Some(true),
+                    max_depth,
+                },
+            )
+        };
+        let full = list(None).await.unwrap().into_string();
+        let shallow = list(Some(2)).await.unwrap().into_string();
+
+        assert_snapshot!(normalize(full.as_str()));
+        assert_snapshot!(normalize(shallow.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_fs_list_directories_first_without_vcs() {
+        let temp_dir = TempDir::new().unwrap();
+
+        fs::write(temp_dir.path().join("b.txt"), "b").await.unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "a").await.unwrap();
+        fs::create_dir_all(temp_dir.path().join("z_dir/y_dir"))
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("z_dir/x.txt"), "x")
+            .await
+            .unwrap();
+        fs::create_dir(temp_dir.path().join("CVS")).await.unwrap();
+        fs::write(temp_dir.path().join("CVS/Entries"), "entries")
+            .await
+            .unwrap();
+
+        let result = FSList
+            .call(
+                ToolCallContext::default(),
+                FSListInput {
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    recursive: Some(true),
+                    max_depth: None,
                 },
             )
             .await
             .unwrap()
             .into_string();
 
-        assert_snapshot!(TempDir::normalize(result.as_str()));
+        let actual: Vec<_> = normalize(&result)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect();
+        let expected = vec![
+            r#"<file_list path="[TEMP_DIR]">"#,
+            r#"<dir path="z_dir/">"#,
+            r#"<dir path="z_dir/y_dir/">"#,
+            r#"<file path="z_dir/x.txt" size="1" modified="[MODIFIED]">"#,
+            r#"<file path="a.txt" size="1" modified="[MODIFIED]">"#,
+            r#"<file path="b.txt" size="1" modified="[MODIFIED]">"#,
+            "</file_list>",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_list_entry_cap() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..MAX_ENTRIES + 5 {
+            fs::write(temp_dir.path().join(format!("file{i:04}.txt")), "")
+                .await
+                .unwrap();
+        }
+
+        let result = FSList
+            .call(
+                ToolCallContext::default(),
+                FSListInput {
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    recursive: None,
+                    max_depth: None,
+                },
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        assert_eq!(result.matches("<file path=").count(), MAX_ENTRIES);
+        assert!(result.contains(r#"<file path="file0000.txt""#));
+        assert!(!result.contains(r#"<file path="file2000.txt""#));
+        assert!(result.contains("5 more entries not shown"));
     }
 
     #[tokio::test]
     async fn test_fs_list_relative_path() {
-        let fs_list = FSList::new(true);
+        let fs_list = FSList;
         let result = fs_list
             .call(
                 ToolCallContext::default(),
-                FSListInput { path: "relative/path".to_string(), recursive: None },
+                FSListInput {
+                    path: "relative/path".to_string(),
+                    recursive: None,
+                    max_depth: None,
+                },
             )
             .await;
 
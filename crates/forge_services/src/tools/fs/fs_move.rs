@@ -0,0 +1,221 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use forge_display::TitleFormat;
+use forge_domain::{
+    EnvironmentService, ExecutableTool, FSMoveInput, NamedTool, ToolCallContext, ToolDescription,
+    ToolName, ToolOutput,
+};
+use forge_tool_macros::ToolDescription;
+
+use crate::utils::{assert_absolute_path, format_display_path};
+use crate::{FsCreateDirsService, FsMetaService, FsMoveService, Infrastructure};
+
+// Using FSMoveInput from forge_domain
+
+/// Moves or renames a file from the source path to the destination path,
+/// keeping its content and permissions. Missing parent directories of the
+/// destination are created. Fails if the destination exists unless overwrite
+/// is set. Both paths must be absolute. Use this instead of reading, writing
+/// and removing a file to relocate it.
+#[derive(ToolDescription)]
+pub struct FSMove<T>(Arc<T>);
+
+impl<T: Infrastructure> FSMove<T> {
+    pub fn new(infra: Arc<T>) -> Self {
+        Self(infra)
+    }
+
+    /// Formats a path for display, converting absolute paths to relative when
+    /// possible
+    fn format_display_path(&self, path: &Path) -> anyhow::Result<String> {
+        let env = self.0.environment_service().get_environment();
+        format_display_path(path, env.cwd.as_path())
+    }
+}
+
+impl<T> NamedTool for FSMove<T> {
+    fn tool_name() -> ToolName {
+        ToolName::new("forge_tool_fs_move")
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: Infrastructure> ExecutableTool for FSMove<T> {
+    type Input = FSMoveInput;
+
+    async fn call(
+        &self,
+        context: ToolCallContext,
+        input: Self::Input,
+    ) -> anyhow::Result<ToolOutput> {
+        let source = Path::new(&input.source);
+        let destination = Path::new(&input.destination);
+        assert_absolute_path(source)?;
+        assert_absolute_path(destination)?;
+
+        let meta = self.0.file_meta_service();
+        if !meta.exists(source).await? {
+            return Err(anyhow::anyhow!("File not found: {}", input.source));
+        }
+        if !meta.is_file(source).await? {
+            return Err(anyhow::anyhow!("Path is not a file: {}", input.source));
+        }
+        if meta.exists(destination).await? && !input.overwrite {
+            return Err(anyhow::anyhow!(
+                "Destination already exists: {}. Set overwrite to replace it.",
+                input.destination
+            ));
+        }
+
+        if let Some(parent) = destination.parent() {
+            self.0.create_dirs_service().create_dirs(parent).await?;
+        }
+        self.0
+            .file_move_service()
+            .rename(source, destination)
+            .await?;
+
+        context
+            .send_text(TitleFormat::debug("Move").title(format!(
+                "{} -> {}",
+                self.format_display_path(source)?,
+                self.format_display_path(destination)?
+            )))
+            .await?;
+
+        Ok(ToolOutput::text(format!(
+            "Successfully moved file: {} -> {}",
+            input.source, input.destination
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::attachment::tests::MockInfrastructure;
+    use crate::utils::{TempDir, ToolContentExtension};
+    use crate::{FsReadService, FsWriteService};
+
+    async fn fixture(temp_dir: &TempDir, files: &[&str]) -> Arc<MockInfrastructure> {
+        let infra = Arc::new(MockInfrastructure::new());
+        for file in files {
+            infra
+                .file_write_service()
+                .write(&temp_dir.path().join(file), Bytes::from(file.to_string()))
+                .await
+                .unwrap();
+        }
+        infra
+    }
+
+    fn input(temp_dir: &TempDir, source: &str, destination: &str) -> FSMoveInput {
+        FSMoveInput {
+            source: temp_dir.path().join(source).to_string_lossy().to_string(),
+            destination: temp_dir
+                .path()
+                .join(destination)
+                .to_string_lossy()
+                .to_string(),
+            overwrite: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fs_move_success() {
+        let temp_dir = TempDir::new().unwrap();
+        let infra = fixture(&temp_dir, &["a.txt"]).await;
+
+        let result = FSMove::new(infra.clone())
+            .call(
+                ToolCallContext::default(),
+                input(&temp_dir, "a.txt", "nested/b.txt"),
+            )
+            .await
+            .unwrap();
+
+        let moved = infra
+            .file_read_service()
+            .read_utf8(&temp_dir.path().join("nested/b.txt"))
+            .await
+            .unwrap();
+        assert!(result.contains("Successfully moved file"));
+        assert_eq!(moved, "a.txt");
+        assert!(!infra
+            .file_meta_service()
+            .exists(&temp_dir.path().join("a.txt"))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_fs_move_existing_destination() {
+        let temp_dir = TempDir::new().unwrap();
+        let infra = fixture(&temp_dir, &["a.txt", "b.txt"]).await;
+        let fs_move = FSMove::new(infra.clone());
+
+        let refused = fs_move
+            .call(
+                ToolCallContext::default(),
+                input(&temp_dir, "a.txt", "b.txt"),
+            )
+            .await;
+        let overwritten = fs_move
+            .call(
+                ToolCallContext::default(),
+                FSMoveInput { overwrite: true, ..input(&temp_dir, "a.txt", "b.txt") },
+            )
+            .await;
+
+        assert!(refused
+            .unwrap_err()
+            .to_string()
+            .contains("Destination already exists"));
+        assert!(overwritten.is_ok());
+        let content = infra
+            .file_read_service()
+            .read_utf8(&temp_dir.path().join("b.txt"))
+            .await
+            .unwrap();
+        assert_eq!(content, "a.txt");
+    }
+
+    #[tokio::test]
+    async fn test_fs_move_nonexistent_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let infra = fixture(&temp_dir, &[]).await;
+
+        let result = FSMove::new(infra)
+            .call(
+                ToolCallContext::default(),
+                input(&temp_dir, "a.txt", "b.txt"),
+            )
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("File not found"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_move_relative_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let infra = fixture(&temp_dir, &["a.txt"]).await;
+
+        let result = FSMove::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSMoveInput {
+                    destination: "b.txt".to_string(),
+                    ..input(&temp_dir, "a.txt", "")
+                },
+            )
+            .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Path must be absolute"));
+    }
+}
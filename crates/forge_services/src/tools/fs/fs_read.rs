@@ -3,20 +3,28 @@ use std::fmt::Write;
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::{bail, Context};
+use anyhow::bail;
 use forge_display::TitleFormat;
 use forge_domain::{
     EnvironmentService, ExecutableTool, FSReadInput, NamedTool, ToolCallContext, ToolDescription,
     ToolName, ToolOutput,
 };
 use forge_tool_macros::ToolDescription;
+use futures::future::join_all;
 
+use crate::tools::syn::strip_comments;
 use crate::utils::{assert_absolute_path, format_display_path};
-use crate::{FsReadService, Infrastructure};
+use crate::{http_url, FsReadService, Infrastructure};
 
 // Define maximum character limits
 const MAX_RANGE_SIZE: u64 = 40_000;
 
+/// Maximum number of files that can be read in one call
+const MAX_PATHS: usize = 10;
+
+/// Metadata line added to files whose comments were removed
+const STRIPPED_NOTE: &str = "note: comments were removed, line numbers are unchanged";
+
 /// Ensures that the given character range is valid and doesn't exceed the
 /// maximum size
 ///
@@ -46,8 +54,8 @@ pub fn assert_valid_range(start_char: u64, end_char: u64) -> anyhow::Result<()>
 
 /// Reads file contents at specified path. Use for analyzing code, config files,
 /// documentation or text data. Extracts text from PDF/DOCX files and preserves
-/// original formatting. Returns content as string. Always use absolute paths.
-/// Read-only with no file modifications.
+/// original formatting. Always use absolute paths or HTTP(S) URLs. Read-only
+/// with no file modifications.
 ///
 /// Files larger than 40,000 characters will automatically be read using range
 /// functionality, returning only the first 40,000 characters by default. For
@@ -55,6 +63,12 @@ pub fn assert_valid_range(start_char: u64, end_char: u64) -> anyhow::Result<()>
 /// parameters. The total range must not exceed 40,000 characters (an error will
 /// be thrown if (end_char - start_char) > 40,000). Binary files are
 /// automatically detected and rejected.
+///
+/// Pass up to 10 absolute paths in paths instead of path to read related files
+/// in one call. Each file gets its own header in the given order, unreadable
+/// files don't fail the others and the combined content is kept within 40,000
+/// characters by cutting the largest files first. Set strip_comments to
+/// leave out the comments of source files.
 #[derive(ToolDescription)]
 pub struct FSRead<F>(Arc<F>);
 
@@ -142,14 +156,34 @@ impl<F: Infrastructure> FSRead<F> {
         Ok(())
     }
 
-    /// Helper function to read a file with range constraints
+    /// Reads the requested file, or each of the requested files
     async fn call(
         &self,
         context: ToolCallContext,
         input: FSReadInput,
     ) -> anyhow::Result<ToolOutput> {
-        let path = Path::new(&input.path);
-        assert_absolute_path(path)?;
+        let paths: Vec<_> = input
+            .path
+            .iter()
+            .chain(input.paths.iter().flatten())
+            .map(String::as_str)
+            .collect();
+        match paths.as_slice() {
+            [] => bail!("Either path or paths must be provided"),
+            [path] => self.read_single(context, &input, path).await,
+            paths => self.read_many(context, &input, paths).await,
+        }
+    }
+
+    /// Helper function to read a file with range constraints
+    async fn read_single(
+        &self,
+        context: ToolCallContext,
+        input: &FSReadInput,
+        input_path: &str,
+    ) -> anyhow::Result<ToolOutput> {
+        let path = Path::new(input_path);
+        assert_readable_path(path)?;
 
         let start_char = input.start_char.unwrap_or(0);
         let end_char = input.end_char.unwrap_or(MAX_RANGE_SIZE.saturating_sub(1));
@@ -162,10 +196,11 @@ impl<F: Infrastructure> FSRead<F> {
             .file_read_service()
             .range_read_utf8(path, start_char, end_char)
             .await
-            .with_context(|| format!("Failed to read file content from {}", input.path))?;
+            .map_err(|error| describe_read_error(input_path, error))?;
+        let (content, is_stripped) = strip_if_requested(input, path, content);
 
         // Create and send the title using the extracted method
-        self.create_and_send_title(&context, &input, path, start_char, end_char, &file_info)
+        self.create_and_send_title(&context, input, path, start_char, end_char, &file_info)
             .await?;
 
         // Determine if the user requested an explicit range
@@ -188,6 +223,19 @@ impl<F: Infrastructure> FSRead<F> {
             writeln!(response, "end_char: {}", file_info.end_char)?;
             writeln!(response, "total_chars: {}", file_info.total_chars)?;
         }
+        if file_info.encoding != forge_fs::UTF_8 {
+            writeln!(response, "encoding: {}", file_info.encoding.name())?;
+        }
+        if file_info.lossy {
+            writeln!(
+                response,
+                "warning: bytes that are not valid {} were replaced with U+FFFD",
+                file_info.encoding.name()
+            )?;
+        }
+        if is_stripped {
+            writeln!(response, "{STRIPPED_NOTE}")?;
+        }
 
         writeln!(response, "---")?;
 
@@ -196,6 +244,158 @@ impl<F: Infrastructure> FSRead<F> {
 
         Ok(ToolOutput::text(response))
     }
+
+    /// Reads several files concurrently. Files that can't be read are reported
+    /// in place of their content, and the combined content is cut to
+    /// MAX_RANGE_SIZE characters.
+    async fn read_many(
+        &self,
+        context: ToolCallContext,
+        input: &FSReadInput,
+        paths: &[&str],
+    ) -> anyhow::Result<ToolOutput> {
+        if paths.len() > MAX_PATHS {
+            bail!(
+                "At most {MAX_PATHS} files can be read at once, {} were requested",
+                paths.len()
+            )
+        }
+        if input.start_char.is_some() || input.end_char.is_some() {
+            bail!("start_char and end_char can only be used when reading a single path")
+        }
+
+        let reads: Vec<_> = join_all(paths.iter().map(|path| self.read_file(path)))
+            .await
+            .into_iter()
+            .zip(paths)
+            .map(|(read, path)| {
+                read.map(|(content, file_info)| {
+                    let (content, is_stripped) =
+                        strip_if_requested(input, Path::new(path), content);
+                    (content, file_info, is_stripped)
+                })
+            })
+            .collect();
+
+        let lengths: Vec<_> = reads
+            .iter()
+            .map(|read| {
+                read.as_ref()
+                    .map_or(0, |(content, ..)| content.chars().count())
+            })
+            .collect();
+        let shares = share_budget(&lengths, MAX_RANGE_SIZE as usize);
+
+        let mut response = String::new();
+        for (((path, read), length), share) in paths.iter().zip(reads).zip(lengths).zip(shares) {
+            writeln!(response, "---")?;
+            writeln!(response, "path: {path}")?;
+            let (content, file_info, is_stripped) = match read {
+                Ok(read) => read,
+                Err(error) => {
+                    writeln!(response, "error: {error:#}")?;
+                    writeln!(response, "---")?;
+                    continue;
+                }
+            };
+
+            let is_cut = share < length;
+            let shown = content.chars().take(share).collect::<String>();
+            if is_cut || file_info.total_chars > file_info.end_char {
+                let end_char = file_info.start_char + shown.chars().count() as u64;
+                writeln!(response, "start_char: {}", file_info.start_char)?;
+                writeln!(response, "end_char: {end_char}")?;
+                writeln!(response, "total_chars: {}", file_info.total_chars)?;
+            }
+            if file_info.encoding != forge_fs::UTF_8 {
+                writeln!(response, "encoding: {}", file_info.encoding.name())?;
+            }
+            if file_info.lossy {
+                writeln!(
+                    response,
+                    "warning: bytes that are not valid {} were replaced with U+FFFD",
+                    file_info.encoding.name()
+                )?;
+            }
+            if is_stripped {
+                writeln!(response, "{STRIPPED_NOTE}")?;
+            }
+            if is_cut {
+                writeln!(
+                    response,
+                    "note: cut to fit the combined limit of {MAX_RANGE_SIZE} characters, read this file on its own to see the rest"
+                )?;
+            }
+            writeln!(response, "---")?;
+            writeln!(response, "{shown}")?;
+        }
+
+        let display_paths = paths
+            .iter()
+            .map(|path| self.format_display_path(Path::new(path)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        context
+            .send_text(TitleFormat::debug("Read").sub_title(display_paths.join(", ")))
+            .await?;
+
+        Ok(ToolOutput::text(response))
+    }
+
+    /// Reads up to MAX_RANGE_SIZE characters of a file
+    async fn read_file(&self, input_path: &str) -> anyhow::Result<(String, forge_fs::FileInfo)> {
+        let path = Path::new(input_path);
+        assert_readable_path(path)?;
+        self.0
+            .file_read_service()
+            .range_read_utf8(path, 0, MAX_RANGE_SIZE.saturating_sub(1))
+            .await
+            .map_err(|error| describe_read_error(input_path, error))
+    }
+}
+
+/// Checks that `path` is absolute, unless it's a URL to fetch the content of
+fn assert_readable_path(path: &Path) -> anyhow::Result<()> {
+    match http_url(path) {
+        Some(_) => Ok(()),
+        None => assert_absolute_path(path),
+    }
+}
+
+/// Removes the comments from `content` when the input asks for it and the
+/// language of the file is supported. Returns whether comments were removed.
+fn strip_if_requested(input: &FSReadInput, path: &Path, content: String) -> (String, bool) {
+    if !input.strip_comments {
+        return (content, false);
+    }
+    match strip_comments(path, &content) {
+        Some(stripped) => (stripped, true),
+        None => (content, false),
+    }
+}
+
+/// Splits `budget` characters between files of the given lengths, cutting the
+/// largest files first. Returns how many characters of each file fit.
+fn share_budget(lengths: &[usize], budget: usize) -> Vec<usize> {
+    let mut order: Vec<_> = (0..lengths.len()).collect();
+    order.sort_by_key(|&i| lengths[i]);
+
+    let mut shares = lengths.to_vec();
+    let mut remaining = budget;
+    for (position, &i) in order.iter().enumerate() {
+        let fair = remaining / (order.len() - position);
+        if lengths[i] <= fair {
+            remaining -= lengths[i];
+            continue;
+        }
+
+        // The files left are at least as long as this one, so all of them are cut
+        // to the same length
+        for &j in &order[position..] {
+            shares[j] = fair;
+        }
+        break;
+    }
+    shares
 }
 
 impl<F> NamedTool for FSRead<F> {
@@ -204,6 +404,23 @@ impl<F> NamedTool for FSRead<F> {
     }
 }
 
+/// Adds a hint the model can act on to failures caused by a bad path
+fn describe_read_error(path: &str, error: anyhow::Error) -> anyhow::Error {
+    let message = match error.downcast_ref::<forge_fs::Error>() {
+        Some(forge_fs::Error::NotFound { .. }) => format!(
+            "File not found: {path}. Check the path or look for the file with forge_tool_fs_search"
+        ),
+        Some(forge_fs::Error::IsADirectory { .. }) => {
+            format!("{path} is a directory, not a file. List its contents with forge_tool_fs_list")
+        }
+        Some(forge_fs::Error::PermissionDenied { .. }) => {
+            format!("Permission denied reading {path}")
+        }
+        _ => format!("Failed to read file content from {path}"),
+    };
+    error.context(message)
+}
+
 #[async_trait::async_trait]
 impl<F: Infrastructure> ExecutableTool for FSRead<F> {
     type Input = FSReadInput;
@@ -215,6 +432,10 @@ impl<F: Infrastructure> ExecutableTool for FSRead<F> {
     ) -> anyhow::Result<ToolOutput> {
         self.call(context, input).await
     }
+
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -226,7 +447,8 @@ mod test {
 
     use super::*;
     use crate::attachment::tests::MockInfrastructure;
-    use crate::utils::TempDir;
+    use crate::utils::{TempDir, ToolContentExtension};
+    use crate::FsWriteService;
 
     // Helper function to test relative paths
     async fn test_with_mock(path: &str) -> anyhow::Result<ToolOutput> {
@@ -235,7 +457,13 @@ mod test {
         fs_read
             .call(
                 ToolCallContext::default(),
-                FSReadInput { path: path.to_string(), start_char: None, end_char: None },
+                FSReadInput {
+                    path: Some(path.to_string()),
+                    paths: None,
+                    start_char: None,
+                    end_char: None,
+                    strip_comments: false,
+                },
             )
             .await
     }
@@ -277,9 +505,11 @@ mod test {
             .call(
                 ToolCallContext::default(),
                 FSReadInput {
-                    path: file_path.to_string_lossy().to_string(),
+                    path: Some(file_path.to_string_lossy().to_string()),
+                    paths: None,
                     start_char: Some(10),
                     end_char: Some(20),
+                    strip_comments: false,
                 },
             )
             .await;
@@ -307,9 +537,11 @@ mod test {
             .call(
                 ToolCallContext::default(),
                 FSReadInput {
-                    path: file_path.to_string_lossy().to_string(),
+                    path: Some(file_path.to_string_lossy().to_string()),
+                    paths: None,
                     start_char: Some(20),
                     end_char: Some(10),
+                    strip_comments: false,
                 },
             )
             .await;
@@ -327,6 +559,32 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_describe_read_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.txt").display().to_string();
+        let directory = temp_dir.path().display().to_string();
+
+        let not_found = forge_fs::ForgeFS::read_range_utf8(&missing, 0, 10)
+            .await
+            .unwrap_err();
+        let is_directory = forge_fs::ForgeFS::read_range_utf8(&directory, 0, 10)
+            .await
+            .unwrap_err();
+
+        let actual = vec![
+            describe_read_error(&missing, not_found.into()).to_string(),
+            describe_read_error(&directory, is_directory.into()).to_string(),
+        ];
+        let expected = vec![
+            format!(
+                "File not found: {missing}. Check the path or look for the file with forge_tool_fs_search"
+            ),
+            format!("{directory} is a directory, not a file. List its contents with forge_tool_fs_list"),
+        ];
+        assert_eq!(actual, expected);
+    }
+
     #[tokio::test]
     async fn test_fs_read_empty_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -434,6 +692,9 @@ mod test {
             type FsMetaService = crate::attachment::tests::MockFileService;
             type FsCreateDirsService = crate::attachment::tests::MockFileService;
             type FsRemoveService = crate::attachment::tests::MockFileService;
+            type FsMoveService = crate::attachment::tests::MockFileService;
+            type FsCopyService = crate::attachment::tests::MockFileService;
+            type FsWatchService = crate::attachment::tests::MockFileService;
             type FsSnapshotService = crate::attachment::tests::MockSnapService;
             type CommandExecutorService = ();
             type InquireService = ();
@@ -460,6 +721,18 @@ mod test {
                 self.inner.file_remove_service()
             }
 
+            fn file_move_service(&self) -> &Self::FsMoveService {
+                self.inner.file_move_service()
+            }
+
+            fn file_copy_service(&self) -> &Self::FsCopyService {
+                self.inner.file_copy_service()
+            }
+
+            fn file_watch_service(&self) -> &Self::FsWatchService {
+                self.inner.file_watch_service()
+            }
+
             fn create_dirs_service(&self) -> &Self::FsCreateDirsService {
                 self.inner.create_dirs_service()
             }
@@ -492,9 +765,11 @@ mod test {
             .call(
                 ToolCallContext::default(),
                 FSReadInput {
-                    path: "/test/large_file.txt".to_string(),
+                    path: Some("/test/large_file.txt".to_string()),
+                    paths: None,
                     start_char: None,
                     end_char: None,
+                    strip_comments: false,
                 },
             )
             .await;
@@ -517,6 +792,154 @@ mod test {
         }
     }
 
+    fn many(paths: &[&str]) -> FSReadInput {
+        FSReadInput {
+            path: None,
+            paths: Some(paths.iter().map(|path| path.to_string()).collect()),
+            start_char: None,
+            end_char: None,
+            strip_comments: false,
+        }
+    }
+
+    async fn write_mock(infra: &MockInfrastructure, path: &str, content: String) {
+        infra
+            .file_write_service()
+            .write(Path::new(path), content.into())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_many_reports_missing_files() {
+        let infra = Arc::new(MockInfrastructure::new());
+
+        let result = FSRead::new(infra)
+            .call(
+                ToolCallContext::default(),
+                many(&["/test/missing.txt", "/test/file1.txt"]),
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(result.contains(
+            "path: /test/missing.txt\nerror: Failed to read file content from /test/missing.txt"
+        ));
+        assert!(result.contains("path: /test/file1.txt\n---\nThis is a text file content\n"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_many_keeps_request_order() {
+        let infra = Arc::new(MockInfrastructure::new());
+        write_mock(&infra, "/test/b.txt", "second".to_string()).await;
+        write_mock(&infra, "/test/a.txt", "third".to_string()).await;
+
+        let result = FSRead::new(infra)
+            .call(
+                ToolCallContext::default(),
+                many(&["/test/file1.txt", "/test/b.txt", "/test/a.txt"]),
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        let actual: Vec<_> = ["This is a text file content", "second", "third"]
+            .iter()
+            .map(|content| result.find(content).unwrap())
+            .collect();
+        let mut expected = actual.clone();
+        expected.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_many_cuts_largest_files_first() {
+        let infra = Arc::new(MockInfrastructure::new());
+        write_mock(&infra, "/test/big.txt", "@".repeat(39_000)).await;
+        write_mock(&infra, "/test/mid.txt", "%".repeat(10_000)).await;
+
+        let result = FSRead::new(infra)
+            .call(
+                ToolCallContext::default(),
+                many(&["/test/big.txt", "/test/file1.txt", "/test/mid.txt"]),
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        // The small files are kept whole and the big one takes what is left of
+        // the combined budget
+        assert!(result.contains("This is a text file content"));
+        assert_eq!(result.matches('%').count(), 10_000);
+        assert_eq!(
+            result.matches('@').count(),
+            MAX_RANGE_SIZE as usize - 10_000 - "This is a text file content".len()
+        );
+        assert_eq!(result.matches("note: cut to fit").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_many_rejects_ranges() {
+        let infra = Arc::new(MockInfrastructure::new());
+
+        let result = FSRead::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSReadInput {
+                    start_char: Some(10),
+                    ..many(&["/test/file1.txt", "/test/image.png"])
+                },
+            )
+            .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("only be used when reading a single path"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_strips_comments() {
+        let infra = Arc::new(MockInfrastructure::new());
+        write_mock(
+            &infra,
+            "/test/lib.rs",
+            "// header\nfn main() {} // entry\n".to_string(),
+        )
+        .await;
+        write_mock(&infra, "/test/notes.txt", "// kept".to_string()).await;
+
+        let result = FSRead::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSReadInput {
+                    strip_comments: true,
+                    ..many(&["/test/lib.rs", "/test/notes.txt"])
+                },
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(result.contains(&format!(
+            "path: /test/lib.rs\n{STRIPPED_NOTE}\n---\n\nfn main() {{}}\n"
+        )));
+        assert!(result.contains("path: /test/notes.txt\n---\n// kept\n"));
+    }
+
+    #[test]
+    fn test_share_budget() {
+        let actual = [
+            share_budget(&[10, 20, 30], 100),
+            share_budget(&[100, 30, 100], 130),
+            share_budget(&[90, 10, 60], 100),
+        ];
+
+        let expected = [vec![10, 20, 30], vec![50, 30, 50], vec![45, 10, 45]];
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_description() {
         let infra = Arc::new(MockInfrastructure::new());
@@ -534,6 +957,46 @@ mod test {
             .contains("Path must be absolute"));
     }
 
+    #[tokio::test]
+    async fn test_fs_read_url() {
+        let infra = Arc::new(MockInfrastructure::new());
+        let url = "https://example.com/notes.txt";
+        infra
+            .file_write_service()
+            .write(Path::new(url), "remote notes".into())
+            .await
+            .unwrap();
+
+        let actual = FSRead::new(infra)
+            .call(
+                ToolCallContext::default(),
+                FSReadInput {
+                    path: Some(url.to_string()),
+                    paths: None,
+                    start_char: None,
+                    end_char: None,
+                    strip_comments: false,
+                },
+            )
+            .await
+            .unwrap();
+
+        let expected = "---\npath: https://example.com/notes.txt\n---\nremote notes\n";
+        assert_eq!(actual.as_str().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_http_url() {
+        let actual = [
+            "https://example.com/a.txt",
+            "http://localhost:8080/a",
+            "ftp://example.com/a",
+            "/home/user/a.txt",
+        ]
+        .map(|path| http_url(Path::new(path)).is_some());
+        assert_eq!(actual, [true, true, false, false]);
+    }
+
     #[tokio::test]
     async fn test_format_display_path() {
         let temp_dir = TempDir::new().unwrap();
@@ -2,13 +2,13 @@ use std::path::Path;
 use std::sync::Arc;
 
 use forge_domain::{
-    ExecutableTool, FSRemoveInput, NamedTool, ToolCallContext, ToolDescription, ToolName,
+    ExecutableTool, FSRemoveInput, Impact, NamedTool, ToolCallContext, ToolDescription, ToolName,
     ToolOutput,
 };
 use forge_tool_macros::ToolDescription;
 
 use crate::utils::assert_absolute_path;
-use crate::{FileRemoveService, FsMetaService, Infrastructure};
+use crate::{FileRemoveService, FsMetaService, FsReadService, Infrastructure};
 
 // Using FSRemoveInput from forge_domain
 
@@ -53,13 +53,33 @@ impl<T: Infrastructure> ExecutableTool for FSRemove<T> {
         }
 
         // Remove the file
-        self.0.file_remove_service().remove(path).await?;
+        let cause = format!("{}: remove", Self::tool_name());
+        self.0
+            .file_remove_service()
+            .remove_with_cause(path, &cause)
+            .await?;
 
         Ok(ToolOutput::text(format!(
             "Successfully removed file: {}",
             input.path
         )))
     }
+
+    async fn impact(
+        &self,
+        _context: &ToolCallContext,
+        input: &Self::Input,
+    ) -> anyhow::Result<Option<Impact>> {
+        let path = Path::new(&input.path);
+        assert_absolute_path(path)?;
+        if !self.0.file_meta_service().is_file(path).await? {
+            // The call fails without removing anything
+            return Ok(None);
+        }
+
+        let bytes = self.0.file_read_service().read(path).await?.len() as u64;
+        Ok(Some(Impact::Remove { path: path.to_path_buf(), bytes }))
+    }
 }
 
 #[cfg(test)]
@@ -175,4 +195,30 @@ mod test {
             .to_string()
             .contains("Path must be absolute"));
     }
+
+    #[tokio::test]
+    async fn test_fs_remove_impact() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        let infra = Arc::new(MockInfrastructure::new());
+        infra
+            .file_write_service()
+            .write(file_path.as_path(), Bytes::from("test content"))
+            .await
+            .unwrap();
+
+        let fs_remove = FSRemove::new(infra.clone());
+        let actual = fs_remove
+            .impact(
+                &ToolCallContext::default(),
+                &FSRemoveInput { path: file_path.to_string_lossy().to_string() },
+            )
+            .await
+            .unwrap();
+
+        let expected = Some(Impact::Remove { path: file_path.clone(), bytes: 12 });
+        assert_eq!(actual, expected);
+        // Estimating the impact leaves the file alone
+        assert!(infra.file_meta_service().exists(&file_path).await.unwrap());
+    }
 }
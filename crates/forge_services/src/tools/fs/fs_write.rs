@@ -5,12 +5,13 @@ use std::sync::Arc;
 use anyhow::Context;
 use bytes::Bytes;
 use console::strip_ansi_codes;
-use forge_display::{DiffFormat, TitleFormat};
+use forge_display::{detect_language_from_path, DiffFormat, TitleFormat};
+use forge_fs::{Encoding, UTF_8};
 // Using FSWriteInput from forge_domain
 use forge_domain::ToolOutput;
 use forge_domain::{
-    EnvironmentService, ExecutableTool, FSWriteInput, NamedTool, ToolCallContext, ToolDescription,
-    ToolName,
+    EnvironmentService, ExecutableTool, FSWriteInput, Impact, NamedTool, ToolCallContext,
+    ToolDescription, ToolName,
 };
 use forge_tool_macros::ToolDescription;
 
@@ -21,7 +22,8 @@ use crate::{FsMetaService, FsReadService, FsWriteService, Infrastructure};
 /// Use it to create a new file at a specified path with the provided content.
 /// Always provide absolute paths for file locations. The tool
 /// automatically handles the creation of any missing intermediary directories
-/// in the specified path.
+/// in the specified path. Set append to add content to the end of a file,
+/// such as a log, without rewriting it.
 /// IMPORTANT: DO NOT attempt to use this tool to move or rename files, use the
 /// shell tool instead.
 #[derive(ToolDescription)]
@@ -66,8 +68,14 @@ impl<F: Infrastructure> ExecutableTool for FSWrite<F> {
         let path = Path::new(&input.path);
         assert_absolute_path(path)?;
 
-        // Validate file content if it's a supported language file
-        let syntax_warning = syn::validate(&input.path, &input.content);
+        // Validate file content if it's a supported language file, appended
+        // content is only a fragment of the file so it isn't validated
+        let env = self.0.environment_service().get_environment();
+        let syntax_warning = if input.append || !env.syntax_check {
+            None
+        } else {
+            syn::validate(&input.path, &input.content)
+        };
 
         // Create parent directories if they don't exist
         if let Some(parent) = Path::new(&input.path).parent() {
@@ -81,7 +89,7 @@ impl<F: Infrastructure> ExecutableTool for FSWrite<F> {
 
         // If file exists and overwrite flag is not set, return an error with the
         // existing content
-        if file_exists && !input.overwrite {
+        if file_exists && !input.overwrite && !input.append {
             let existing_content = self.0.file_read_service().read_utf8(path).await?;
             return Err(anyhow::anyhow!(
                 "File already exists at {}. If you need to overwrite it, set overwrite to true.\n\nExisting content:\n{}",
@@ -90,44 +98,73 @@ impl<F: Infrastructure> ExecutableTool for FSWrite<F> {
             ));
         }
 
-        // record the file content before they're modified
-        let old_content = if file_exists {
+        // record the file content before they're modified, along with its
+        // encoding so that the file is written back in it
+        let (old_content, mut encoding) = if file_exists {
             // if file already exists, we should be able to read it.
-            self.0.file_read_service().read_utf8(path).await?
+            self.0.file_read_service().read_with_encoding(path).await?
         } else {
             // if file doesn't exist, we should record it as an empty string.
-            "".to_string()
+            ("".to_string(), UTF_8)
         };
 
         // Write file only after validation passes and directories are created
-        self.0
-            .file_write_service()
-            .write(Path::new(&input.path), Bytes::from(input.content.clone()))
-            .await?;
+        if input.append && encoding == UTF_8 {
+            self.0
+                .file_write_service()
+                .append(path, Bytes::from(input.content.clone()))
+                .await?;
+        } else if input.append {
+            // Appended bytes would have to be in the encoding of the file, so the
+            // file is written again in full instead
+            let cause = format!("{}: append", Self::tool_name());
+            let content = format!("{old_content}{}", input.content);
+            encoding = writable_encoding(&content, encoding);
+            self.0
+                .file_write_service()
+                .write_with_encoding(path, &content, encoding, &cause)
+                .await?;
+        } else {
+            let cause = format!("{}: overwrite", Self::tool_name());
+            encoding = writable_encoding(&input.content, encoding);
+            self.0
+                .file_write_service()
+                .write_with_encoding(path, &input.content, encoding, &cause)
+                .await?;
+        }
 
         let mut result = String::new();
 
         writeln!(result, "---")?;
         writeln!(result, "path: {}", &input.path)?;
-        if file_exists {
+        if input.append {
+            writeln!(result, "operation: APPEND")?;
+        } else if file_exists {
             writeln!(result, "operation: OVERWRITE")?;
         } else {
             writeln!(result, "operation: CREATE")?;
         }
         writeln!(result, "total_chars: {}", input.content.len())?;
+        if encoding != UTF_8 {
+            writeln!(result, "encoding: {}", encoding.name())?;
+        }
         if let Some(warning) = syntax_warning {
             writeln!(result, "Warning: {}", &warning.to_string())?;
         }
         writeln!(result, "---")?;
 
         // record the file content after they're modified
-        let new_content = self.0.file_read_service().read_utf8(path).await?;
-        let diff = DiffFormat::format(&old_content, &new_content);
-        let title = if file_exists {
+        let (new_content, _) = self.0.file_read_service().read_with_encoding(path).await?;
+        let language = detect_language_from_path(path);
+        let diff =
+            DiffFormat::with_syntax_highlight(language.as_deref()).diff(&old_content, &new_content);
+        if file_exists {
             writeln!(result, "{}", strip_ansi_codes(&diff))?;
-            "Overwrite"
-        } else {
-            "Create"
+        }
+        let title = match (input.append, file_exists) {
+            (true, _) => "Append",
+            (false, true) => "Overwrite",
+            (false, false) => "Create",
         };
 
         // Use the formatted path for display
@@ -144,6 +181,38 @@ impl<F: Infrastructure> ExecutableTool for FSWrite<F> {
 
         Ok(ToolOutput::text(result))
     }
+
+    async fn impact(
+        &self,
+        _context: &ToolCallContext,
+        input: &Self::Input,
+    ) -> anyhow::Result<Option<Impact>> {
+        let path = Path::new(&input.path);
+        assert_absolute_path(path)?;
+        let existing = if self.0.file_meta_service().is_file(path).await? {
+            Some(self.0.file_read_service().read(path).await?.len() as u64)
+        } else {
+            None
+        };
+
+        Ok(Some(Impact::Write {
+            path: path.to_path_buf(),
+            bytes: input.content.len(),
+            existing,
+            append: input.append,
+        }))
+    }
+}
+
+/// Returns the encoding `content` is written in: the one of the file, unless
+/// it can't represent the content, such as an emoji in a latin-1 file. The
+/// file is then converted to UTF-8 rather than not written at all.
+fn writable_encoding(content: &str, encoding: &'static Encoding) -> &'static Encoding {
+    if forge_fs::encode(content, encoding).is_ok() {
+        encoding
+    } else {
+        UTF_8
+    }
 }
 
 #[cfg(test)]
@@ -186,6 +255,7 @@ mod test {
                     path: file_path.to_string_lossy().to_string(),
                     content: content.to_string(),
                     overwrite: false,
+                    append: false,
                 },
             )
             .await
@@ -219,6 +289,7 @@ mod test {
                     path: file_path.to_string_lossy().to_string(),
                     content: "fn main() { let x = ".to_string(),
                     overwrite: false,
+                    append: false,
                 },
             )
             .await;
@@ -244,6 +315,7 @@ mod test {
                     path: file_path.to_string_lossy().to_string(),
                     content: content.to_string(),
                     overwrite: false,
+                    append: false,
                 },
             )
             .await;
@@ -279,6 +351,7 @@ mod test {
                     path: nested_path.to_string_lossy().to_string(),
                     content: content.to_string(),
                     overwrite: false,
+                    append: false,
                 },
             )
             .await
@@ -321,6 +394,7 @@ mod test {
                     path: deep_path.to_string_lossy().to_string(),
                     content: content.to_string(),
                     overwrite: false,
+                    append: false,
                 },
             )
             .await
@@ -364,6 +438,7 @@ mod test {
                     path: path_str,
                     content: content.to_string(),
                     overwrite: false,
+                    append: false,
                 },
             )
             .await
@@ -401,6 +476,7 @@ mod test {
                     path: "relative/path/file.txt".to_string(),
                     content: "test content".to_string(),
                     overwrite: false,
+                    append: false,
                 },
             )
             .await;
@@ -435,6 +511,7 @@ mod test {
                     path: file_path.to_string_lossy().to_string(),
                     content: "New content".to_string(),
                     overwrite: false,
+                    append: false,
                 },
             )
             .await;
@@ -458,6 +535,101 @@ mod test {
         assert_eq!(content, original_content);
     }
 
+    #[tokio::test]
+    async fn test_fs_write_append_accumulates() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("nested/log.txt");
+        let infra = Arc::new(MockInfrastructure::new());
+        let fs_write = FSWrite::new(infra.clone());
+        let input = |content: &str| FSWriteInput {
+            path: file_path.to_string_lossy().to_string(),
+            content: content.to_string(),
+            overwrite: false,
+            append: true,
+        };
+
+        let first = fs_write
+            .call(ToolCallContext::default(), input("first\n"))
+            .await
+            .unwrap()
+            .into_string();
+        let second = fs_write
+            .call(ToolCallContext::default(), input("second\n"))
+            .await
+            .unwrap()
+            .into_string();
+
+        let actual = infra
+            .file_read_service()
+            .read_utf8(&file_path)
+            .await
+            .unwrap();
+        assert_eq!(actual, "first\nsecond\n");
+        assert!(first.contains("operation: APPEND"));
+        assert!(second.contains("+second"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_keeps_latin1_encoding() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("legacy.txt");
+        let infra = Arc::new(MockInfrastructure::new());
+        infra
+            .file_write_service()
+            .write(&file_path, Bytes::from_static(b"na\xefve\n"))
+            .await
+            .unwrap();
+        let input = |content: &str, append: bool| FSWriteInput {
+            path: file_path.to_string_lossy().to_string(),
+            content: content.to_string(),
+            overwrite: !append,
+            append,
+        };
+        let fs_write = FSWrite::new(infra.clone());
+
+        let overwritten = fs_write
+            .call(ToolCallContext::default(), input("caf\u{e9}\n", false))
+            .await
+            .unwrap()
+            .into_string();
+        fs_write
+            .call(ToolCallContext::default(), input("cr\u{e8}me\n", true))
+            .await
+            .unwrap();
+
+        let actual = infra.file_read_service().read(&file_path).await.unwrap();
+        assert_eq!(actual, b"caf\xe9\ncr\xe8me\n");
+        assert!(overwritten.contains("encoding: windows-1252"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_converts_latin1_to_utf8_when_needed() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("legacy.txt");
+        let infra = Arc::new(MockInfrastructure::new());
+        infra
+            .file_write_service()
+            .write(&file_path, Bytes::from_static(b"na\xefve\n"))
+            .await
+            .unwrap();
+        let input = FSWriteInput {
+            path: file_path.to_string_lossy().to_string(),
+            content: "snow \u{2603}\n".to_string(),
+            overwrite: true,
+            append: false,
+        };
+
+        let result = FSWrite::new(infra.clone())
+            .call(ToolCallContext::default(), input)
+            .await
+            .unwrap()
+            .into_string();
+
+        let actual = infra.file_read_service().read(&file_path).await.unwrap();
+        assert_eq!(actual, "snow \u{2603}\n".as_bytes());
+        assert!(!result.contains("encoding:"));
+    }
+
     #[tokio::test]
     async fn test_format_display_path() {
         let temp_dir = TempDir::new().unwrap();
@@ -500,6 +672,7 @@ mod test {
                     path: file_path.to_string_lossy().to_string(),
                     content: new_content.to_string(),
                     overwrite: true,
+                    append: false,
                 },
             )
             .await;
@@ -520,4 +693,54 @@ mod test {
             .unwrap();
         assert_eq!(content, new_content);
     }
+
+    #[tokio::test]
+    async fn test_fs_write_impact() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("existing.txt");
+        let new = temp_dir.path().join("new.txt");
+        let infra = Arc::new(MockInfrastructure::new());
+        infra
+            .file_write_service()
+            .write(&existing, Bytes::from("Original content"))
+            .await
+            .unwrap();
+
+        let fs_write = FSWrite::new(infra.clone());
+        let input = |path: &Path| FSWriteInput {
+            path: path.to_string_lossy().to_string(),
+            content: "New content".to_string(),
+            overwrite: true,
+            append: false,
+        };
+        let actual = (
+            fs_write
+                .impact(&ToolCallContext::default(), &input(&existing))
+                .await
+                .unwrap(),
+            fs_write
+                .impact(&ToolCallContext::default(), &input(&new))
+                .await
+                .unwrap(),
+        );
+
+        let expected = (
+            Some(Impact::Write {
+                path: existing.clone(),
+                bytes: 11,
+                existing: Some(16),
+                append: false,
+            }),
+            Some(Impact::Write { path: new.clone(), bytes: 11, existing: None, append: false }),
+        );
+        assert_eq!(actual, expected);
+        // Nothing is written while estimating
+        let content = infra
+            .file_read_service()
+            .read_utf8(&existing)
+            .await
+            .unwrap();
+        assert_eq!(content, "Original content");
+        assert!(!infra.file_meta_service().exists(&new).await.unwrap());
+    }
 }
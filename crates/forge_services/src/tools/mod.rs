@@ -1,10 +1,16 @@
 mod completion;
 mod fetch;
+pub(crate) mod fetch_cache;
 mod followup;
 mod fs;
 mod patch;
+mod plan;
+mod readable;
 mod registry;
 mod shell;
 mod syn;
+mod think;
 
+#[cfg(test)]
+pub(crate) use registry::tests::Stub;
 pub use registry::ToolRegistry;
@@ -2,8 +2,7 @@ use std::fmt::Write;
 use std::path::Path;
 use std::sync::Arc;
 
-use bytes::Bytes;
-use forge_display::{DiffFormat, TitleFormat};
+use forge_display::{detect_language_from_path, DiffFormat, TitleFormat};
 use forge_domain::{
     EnvironmentService, ExecutableTool, FSPatchInput, NamedTool, PatchOperation, ToolCallContext,
     ToolDescription, ToolName, ToolOutput,
@@ -12,12 +11,13 @@ use forge_tool_macros::ToolDescription;
 use thiserror::Error;
 use tokio::fs;
 
-// No longer using dissimilar for fuzzy matching
 use crate::tools::syn;
 use crate::utils::{assert_absolute_path, format_display_path};
 use crate::{FsWriteService, Infrastructure};
 
-// Removed fuzzy matching threshold as we only use exact matching now
+/// Lowest normalized Levenshtein similarity at which a region of the file is
+/// taken as a fuzzy match for the search text
+const FUZZY_THRESHOLD: f64 = 0.9;
 
 /// A match found in the source text. Represents a range in the source text that
 /// can be used for extraction or replacement operations. Stores the position
@@ -47,8 +47,173 @@ impl Range {
             .find(search)
             .map(|start| Self::new(start, search.len()))
     }
+}
+
+/// How much the search text had to be relaxed to match the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MatchKind {
+    Exact,
+    /// Equal once trailing whitespace is removed from every line
+    TrailingWhitespace,
+    /// Equal once leading and trailing whitespace is removed from every line
+    Whitespace,
+    /// Similar enough, with the normalized Levenshtein similarity
+    Fuzzy(f64),
+}
 
-    // Fuzzy matching removed - we only use exact matching
+impl std::fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchKind::Exact => write!(f, "exact"),
+            MatchKind::TrailingWhitespace => write!(f, "ignoring trailing whitespace"),
+            MatchKind::Whitespace => write!(f, "ignoring leading and trailing whitespace"),
+            MatchKind::Fuzzy(similarity) => {
+                write!(f, "fuzzy ({:.0}% similar)", similarity * 100.0)
+            }
+        }
+    }
+}
+
+/// The region of the source the search text was matched to
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Match {
+    range: Range,
+    kind: MatchKind,
+}
+
+impl Match {
+    /// Finds `search` in `source`, trying in order an exact match, a match
+    /// ignoring trailing whitespace, a match ignoring leading and trailing
+    /// whitespace, and finally the most similar region. Only an exact match
+    /// may have several candidates, of which the first is used; otherwise
+    /// candidates tying for the match make it fail.
+    fn find(source: &str, search: &str) -> Result<Self, Error> {
+        if let Some(range) = Range::find_exact(source, search) {
+            return Ok(Self { range, kind: MatchKind::Exact });
+        }
+
+        let lines = Line::split(source);
+        let search_lines = search.lines().collect::<Vec<_>>();
+        let count = search_lines.len();
+        if count == 0 || count > lines.len() {
+            return Err(Error::NoMatch(search.to_string()));
+        }
+
+        // Windows of as many source lines as the search text has, keeping the
+        // line ending of the last one only if the search text ends with one
+        let windows = lines.windows(count).enumerate();
+        let range = |window: &[Line]| {
+            let last = window[count - 1];
+            let end = if search.ends_with('\n') {
+                last.next
+            } else {
+                last.end
+            };
+            Range::new(window[0].start, end - window[0].start)
+        };
+        let found = |candidates: Vec<(usize, Range)>, kind| match candidates.as_slice() {
+            [] => Ok(None),
+            [(_, range)] => Ok(Some(Self { range: *range, kind })),
+            _ => Err(Error::AmbiguousMatch(
+                candidates.iter().map(|(index, _)| index + 1).collect(),
+            )),
+        };
+
+        let normalizations = [
+            (
+                MatchKind::TrailingWhitespace,
+                str::trim_end as fn(&str) -> &str,
+            ),
+            (MatchKind::Whitespace, str::trim),
+        ];
+        for (kind, normalize) in normalizations {
+            let candidates = windows
+                .clone()
+                .filter(|(_, window)| {
+                    window
+                        .iter()
+                        .zip(&search_lines)
+                        .all(|(line, search)| normalize(line.text(source)) == normalize(search))
+                })
+                .map(|(index, window)| (index, range(window)))
+                .collect();
+            if let Some(found) = found(candidates, kind)? {
+                return Ok(found);
+            }
+        }
+
+        // Indentation is left out of the comparison, as it's the usual drift
+        let trimmed = |lines: &mut dyn Iterator<Item = &str>| {
+            lines.map(str::trim).collect::<Vec<_>>().join("\n")
+        };
+        let needle = trimmed(&mut search_lines.iter().copied());
+        let needle_len = needle.chars().count();
+        let mut best = 0.0;
+        let mut candidates = Vec::new();
+        for (index, window) in windows {
+            let text = trimmed(&mut window.iter().map(|line| line.text(source)));
+
+            // The difference in length bounds the similarity, which rules out
+            // most windows without computing their distance
+            let len = text.chars().count();
+            let bound = len.min(needle_len) as f64 / len.max(needle_len).max(1) as f64;
+            if bound < FUZZY_THRESHOLD || bound < best {
+                continue;
+            }
+
+            let similarity = strsim::normalized_levenshtein(&text, &needle);
+            if similarity < FUZZY_THRESHOLD || similarity < best {
+                continue;
+            }
+            if similarity > best {
+                best = similarity;
+                candidates.clear();
+            }
+            candidates.push((index, range(window)));
+        }
+
+        found(candidates, MatchKind::Fuzzy(best))?.ok_or_else(|| Error::NoMatch(search.to_string()))
+    }
+
+    /// First and last line of the match, counting from one
+    fn lines(&self, source: &str) -> (usize, usize) {
+        let first = source[..self.range.start].matches('\n').count() + 1;
+        let matched = &source[std::ops::Range::from(self.range)];
+        let last = first + matched.trim_end_matches('\n').matches('\n').count();
+        (first, last)
+    }
+}
+
+/// A line of the source, as byte offsets
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    /// Start of the line
+    start: usize,
+    /// End of the line's text, before its line ending
+    end: usize,
+    /// Start of the next line, after the line ending
+    next: usize,
+}
+
+impl Line {
+    fn split(source: &str) -> Vec<Self> {
+        let mut start = 0;
+        source
+            .split_inclusive('\n')
+            .map(|line| {
+                let text = line.strip_suffix('\n').unwrap_or(line);
+                let text = text.strip_suffix('\r').unwrap_or(text);
+                let next = start + line.len();
+                let line = Self { start, end: start + text.len(), next };
+                start = next;
+                line
+            })
+            .collect()
+    }
+
+    fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.start..self.end]
+    }
 }
 
 impl From<Range> for std::ops::Range<usize> {
@@ -57,8 +222,6 @@ impl From<Range> for std::ops::Range<usize> {
     }
 }
 
-// MatchSequence struct and implementation removed - we only use exact matching
-
 #[derive(Debug, Error)]
 enum Error {
     #[error("Failed to read/write file: {0}")]
@@ -67,32 +230,50 @@ enum Error {
     NoMatch(String),
     #[error("Could not find swap target text: {0}")]
     NoSwapTarget(String),
+    #[error(
+        "Search text matches several places equally well, starting at lines {}. Add surrounding lines to the search text to tell them apart.",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    AmbiguousMatch(Vec<usize>),
 }
 
+/// Applies `operation` to `source`, returning the patched source along with
+/// the match the search text was applied to. An empty search text doesn't
+/// match any region.
 fn apply_replacement(
     source: String,
     search: &str,
     operation: &PatchOperation,
     content: &str,
-) -> Result<String, Error> {
+) -> Result<(String, Option<Match>), Error> {
     // Handle empty search string - only certain operations make sense here
     if search.is_empty() {
-        return match operation {
+        let patched = match operation {
             // Append to the end of the file
-            PatchOperation::Append => Ok(format!("{source}{content}")),
+            PatchOperation::Append => format!("{source}{content}"),
             // Prepend to the beginning of the file
-            PatchOperation::Prepend => Ok(format!("{content}{source}")),
+            PatchOperation::Prepend => format!("{content}{source}"),
             // Replace is equivalent to completely replacing the file
-            PatchOperation::Replace => Ok(content.to_string()),
+            PatchOperation::Replace => content.to_string(),
             // Swap doesn't make sense with empty search - keep source unchanged
-            PatchOperation::Swap => Ok(source),
+            PatchOperation::Swap => source,
         };
+        return Ok((patched, None));
     }
 
-    // Find the exact match to operate on
-    let patch =
-        Range::find_exact(&source, search).ok_or_else(|| Error::NoMatch(search.to_string()))?;
+    // Find the match to operate on
+    let found = Match::find(&source, search)?;
+    let patched = apply_operation(&source, found.range, operation, content)?;
+    Ok((patched, Some(found)))
+}
 
+/// Applies `operation` to the `patch` region of `source`
+fn apply_operation(
+    source: &str,
+    patch: Range,
+    operation: &PatchOperation,
+    content: &str,
+) -> Result<String, Error> {
     // Apply the operation based on its type
     match operation {
         // Prepend content before the matched text
@@ -122,7 +303,7 @@ fn apply_replacement(
         // Swap with another text in the source
         PatchOperation::Swap => {
             // Find the target text to swap with
-            let target_patch = Range::find_exact(&source, content)
+            let target_patch = Range::find_exact(source, content)
                 .ok_or_else(|| Error::NoSwapTarget(content.to_string()))?;
 
             // Handle the case where patches overlap
@@ -168,13 +349,42 @@ fn apply_replacement(
 
 // Using FSPatchInput from forge_domain
 
+/// Short description of a patch, recorded as the cause of the snapshot taken
+/// before it's applied
+fn describe_patch(patch: &FSPatchInput) -> String {
+    let operation = match patch.operation {
+        PatchOperation::Prepend => "prepend to",
+        PatchOperation::Append => "append to",
+        PatchOperation::Replace => "replace",
+        PatchOperation::Swap => "swap",
+    };
+    let search = patch
+        .search
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .chars()
+        .take(40)
+        .collect::<String>();
+
+    if search.is_empty() {
+        format!("{operation} end of file")
+    } else {
+        format!("{operation} \"{search}\"")
+    }
+}
+
 /// Modifies files with targeted text operations on matched patterns. Supports
 /// prepend, append, replace, swap, delete operations on first pattern
 /// occurrence. Ideal for precise changes to configs, code, or docs while
 /// preserving context. Not suitable for complex refactoring or modifying all
 /// pattern occurrences - use forge_tool_fs_create instead for complete
-/// rewrites and forge_tool_fs_undo for undoing the last operation. Fails if
-/// search pattern isn't found.
+/// rewrites and forge_tool_fs_undo for undoing the last operation. Search
+/// pattern matching tolerates whitespace and small differences, the result
+/// showing the region matched when it isn't exact. Fails if the search pattern
+/// isn't found or several regions match it equally well. Set dry_run to
+/// preview the change as a diff without modifying the file.
 #[derive(ToolDescription)]
 pub struct ApplyPatchJson<F>(Arc<F>);
 
@@ -216,16 +426,19 @@ impl<F: Infrastructure> ExecutableTool for ApplyPatchJson<F> {
         let path = Path::new(&patch.path);
         assert_absolute_path(path)?;
 
-        // Read the original content once
-        let mut current_content = fs::read_to_string(path)
+        // Read the original content once, keeping its encoding to write it back
+        // in the same way
+        let (mut current_content, encoding) = fs::read(path)
             .await
+            .map(|bytes| forge_fs::decode(&bytes))
             .map_err(Error::FileOperation)?;
 
         // Save the old content before modification for diff generation
         let old_content = current_content.clone();
 
         // Apply the replacement
-        current_content = apply_replacement(
+        let found;
+        (current_content, found) = apply_replacement(
             current_content,
             &patch.search,
             &patch.operation,
@@ -236,33 +449,68 @@ impl<F: Infrastructure> ExecutableTool for ApplyPatchJson<F> {
         let display_path = self.format_display_path(path)?;
 
         // Generate diff between old and new content
-        let diff = DiffFormat::format(&old_content, &current_content);
-
-        // Write final content to file after all patches are applied
-        self.0
-            .file_write_service()
-            .write(path, Bytes::from(current_content.clone()))
-            .await?;
+        let language = detect_language_from_path(path);
+        let diff = DiffFormat::with_syntax_highlight(language.as_deref())
+            .diff(&old_content, &current_content);
+
+        // Write final content to file after all patches are applied, unless the
+        // change is only previewed
+        if !patch.dry_run {
+            let cause = format!("{}: {}", Self::tool_name(), describe_patch(&patch));
+            self.0
+                .file_write_service()
+                .write_with_encoding(path, &current_content, encoding, &cause)
+                .await?;
+        }
 
         let mut result = String::new();
 
         writeln!(result, "---")?;
         writeln!(result, "path: {}", path.display())?;
         writeln!(result, "total_chars: {}", current_content.len())?;
+        if patch.dry_run {
+            writeln!(result, "dry_run: true, the file was not modified")?;
+        }
+
+        // A relaxed match may not be the region the search text meant, so
+        // it's shown for the agent to check
+        let relaxed = found.filter(|found| found.kind != MatchKind::Exact);
+        if let Some(found) = relaxed {
+            let (first, last) = found.lines(&old_content);
+            writeln!(result, "match: {} at lines {first}-{last}", found.kind)?;
+        }
 
         // Check for syntax errors
-        if let Some(warning) = syn::validate(path, &current_content).map(|e| e.to_string()) {
+        let syntax_check = self.0.environment_service().get_environment().syntax_check;
+        if let Some(warning) = syntax_check
+            .then(|| syn::validate(path, &current_content))
+            .flatten()
+        {
             writeln!(result, "warning:{warning}")?;
         }
 
         writeln!(result, "---")?;
 
+        if let Some(found) = relaxed {
+            let matched = &old_content[std::ops::Range::from(found.range)];
+            writeln!(
+                result,
+                "Matched region:\n{}\n",
+                matched.trim_end_matches('\n')
+            )?;
+        }
+
         writeln!(result, "{}", console::strip_ansi_codes(&diff).as_ref())?;
 
+        let title = if patch.dry_run {
+            "Patch (preview)"
+        } else {
+            "Patch"
+        };
         context
             .send_text(format!(
                 "{}",
-                TitleFormat::debug("Patch").sub_title(display_path)
+                TitleFormat::debug(title).sub_title(display_path)
             ))
             .await?;
 
@@ -272,6 +520,10 @@ impl<F: Infrastructure> ExecutableTool for ApplyPatchJson<F> {
         // Return the final result
         Ok(ToolOutput::text(result))
     }
+
+    fn is_read_only(&self, input: &Self::Input) -> bool {
+        input.dry_run
+    }
 }
 
 #[cfg(test)]
@@ -377,7 +629,7 @@ mod test {
                     &op_result.operation.operation,
                     &op_result.operation.content,
                 ) {
-                    Ok(content) => {
+                    Ok((content, _)) => {
                         // Update the current content for the next operation
                         current_content = content.clone();
                         Ok(content)
@@ -439,6 +691,276 @@ mod test {
         insta::assert_debug_snapshot!(test);
     }
 
+    #[test]
+    fn multi_hunk_patch_tests() {
+        // Several independent edits applied one after another to the same source
+        let test = PatchTest::new(
+            "fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\nfn sub(a: i32, b: i32) -> i32 {\n    a - b\n}\n",
+        )
+        .replace("fn add(a: i32, b: i32)", "fn add(a: i64, b: i64)")
+        .replace(") -> i32 {\n    a + b", ") -> i64 {\n    a + b")
+        .prepend("fn sub", "/// Subtracts two numbers\n")
+        .append("    a - b\n}\n", "\nfn mul(a: i32, b: i32) -> i32 {\n    a * b\n}\n")
+        .execute_all();
+
+        insta::assert_debug_snapshot!(test);
+    }
+
+    #[test]
+    fn deletion_patch_tests() {
+        // Deleting content is a replace with empty content
+        let test = PatchTest::new("use std::fmt;\nuse std::io;\n\nfn main() {}\n")
+            .replace("use std::io;\n", "")
+            .replace("\n\n", "\n")
+            .replace("", "")
+            .execute_all();
+
+        insta::assert_debug_snapshot!(test);
+    }
+
+    #[test]
+    fn whitespace_patch_tests() {
+        // Trailing whitespace differences fall back to a whitespace tolerant
+        // match, which replaces the whitespace along with the line
+        let test = PatchTest::new("let x = 1;   \nlet y = 2;\t\nlet z = 3;\n")
+            .replace("let x = 1;\n", "let x = 10;\n")
+            .replace("let y = 2;\n", "let y = 20;\n")
+            .execute_all();
+
+        insta::assert_debug_snapshot!(test);
+    }
+
+    #[test]
+    fn test_match_tab_indentation_drift() {
+        let source = "fn main() {\n\tlet x = 1;\n\tprintln!(\"{x}\");\n}\n";
+        let search = "    let x = 1;\n    println!(\"{x}\");\n";
+
+        let actual = Match::find(source, search).unwrap();
+
+        let expected = Match { range: Range::new(12, 30), kind: MatchKind::Whitespace };
+        assert_eq!(actual, expected);
+        assert_eq!(actual.lines(source), (2, 3));
+    }
+
+    #[test]
+    fn test_match_trailing_whitespace() {
+        let source = "a\nlet x = 1;  \r\nlet y = 2;\r\n";
+
+        let actual = Match::find(source, "let x = 1;\nlet y = 2;").unwrap();
+
+        let expected = Match {
+            range: Range::new(2, 24),
+            kind: MatchKind::TrailingWhitespace,
+        };
+        assert_eq!(actual, expected);
+        assert_eq!(&source[2..26], "let x = 1;  \r\nlet y = 2;");
+    }
+
+    #[test]
+    fn test_match_fuzzy() {
+        let source =
+            "fn total(items: &[Item]) -> u64 {\n    items.iter().map(|item| item.price).sum()\n}\n";
+        let search =
+            "fn total(items: &[Item]) -> u64 {\n    items.iter().map(|i| i.price).sum()\n}";
+
+        let actual = Match::find(source, search).unwrap();
+
+        assert!(
+            matches!(actual.kind, MatchKind::Fuzzy(similarity) if similarity >= FUZZY_THRESHOLD)
+        );
+        assert_eq!(actual.range, Range::new(0, source.len() - 1));
+    }
+
+    #[test]
+    fn test_match_ambiguous_candidates() {
+        let source = "if a {\n\treturn;\n}\nif b {\n    return;\n}\n";
+
+        let actual = Match::find(source, "\t\treturn;").unwrap_err().to_string();
+
+        let expected = "Search text matches several places equally well, starting at lines 2, 5. Add surrounding lines to the search text to tell them apart.";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_match_without_similar_region() {
+        let actual = Match::find("let x = 1;\n", "fn main() {}");
+
+        assert!(matches!(actual, Err(Error::NoMatch(_))));
+    }
+
+    #[tokio::test]
+    async fn test_patch_reports_relaxed_match() {
+        use crate::attachment::tests::MockInfrastructure;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.rs");
+        fs::write(&path, "fn main() {\n\tlet x = 1;\n}\n")
+            .await
+            .unwrap();
+        let fixture = FSPatchInput {
+            path: path.display().to_string(),
+            search: "    let x = 1;".to_string(),
+            operation: forge_domain::PatchOperation::Replace,
+            content: "\tlet x = 2;".to_string(),
+            dry_run: false,
+        };
+
+        let actual = ApplyPatchJson::new(Arc::new(MockInfrastructure::new()))
+            .call(ToolCallContext::default(), fixture)
+            .await
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        assert!(
+            actual.contains("match: ignoring leading and trailing whitespace at lines 2-2"),
+            "{actual}"
+        );
+        assert!(
+            actual.contains("Matched region:\n\tlet x = 1;\n"),
+            "{actual}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_patch_nonexistent_file() {
+        use crate::attachment::tests::MockInfrastructure;
+
+        let temp_dir = TempDir::new().unwrap();
+        let fixture = FSPatchInput {
+            path: temp_dir.path().join("new_file.txt").display().to_string(),
+            search: "".to_string(),
+            operation: forge_domain::PatchOperation::Append,
+            content: "Hello".to_string(),
+            dry_run: false,
+        };
+
+        let actual = ApplyPatchJson::new(Arc::new(MockInfrastructure::new()))
+            .call(ToolCallContext::default(), fixture)
+            .await;
+
+        // New files must be created with the write tool, never the patch tool
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_patch_keeps_utf16_encoding() {
+        use crate::attachment::tests::MockInfrastructure;
+        use crate::FsReadService;
+
+        let utf16le = |content: &str| -> Vec<u8> {
+            [0xFF, 0xFE]
+                .into_iter()
+                .chain(content.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect()
+        };
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("notes.txt");
+        fs::write(&path, utf16le("caf\u{e9}\r\nline two\r\n"))
+            .await
+            .unwrap();
+        let fixture = FSPatchInput {
+            path: path.display().to_string(),
+            search: "two".to_string(),
+            operation: forge_domain::PatchOperation::Replace,
+            content: "2".to_string(),
+            dry_run: false,
+        };
+        let infra = Arc::new(MockInfrastructure::new());
+
+        ApplyPatchJson::new(infra.clone())
+            .call(ToolCallContext::default(), fixture)
+            .await
+            .unwrap();
+
+        let actual = infra.file_read_service().read(&path).await.unwrap();
+        let expected = utf16le("caf\u{e9}\r\nline 2\r\n");
+        assert_eq!(actual, expected);
+    }
+
+    fn preview_fixture(path: &Path, dry_run: bool) -> FSPatchInput {
+        FSPatchInput {
+            path: path.display().to_string(),
+            search: "let x = 1;".to_string(),
+            operation: forge_domain::PatchOperation::Replace,
+            content: "let x = 2;".to_string(),
+            dry_run,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_patch_preview_leaves_file_untouched() {
+        use crate::attachment::tests::MockInfrastructure;
+        use crate::FsReadService;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.rs");
+        fs::write(&path, "fn main() {\n    let x = 1;\n}\n")
+            .await
+            .unwrap();
+        let infra = Arc::new(MockInfrastructure::new());
+
+        let output = ApplyPatchJson::new(infra.clone())
+            .call(ToolCallContext::default(), preview_fixture(&path, true))
+            .await
+            .unwrap();
+
+        // Snapshots are taken by the write service, so nothing written means
+        // no snapshot either
+        let actual = (
+            fs::read_to_string(&path).await.unwrap(),
+            infra.file_read_service().read(&path).await.is_err(),
+        );
+        let expected = ("fn main() {\n    let x = 1;\n}\n".to_string(), true);
+        assert_eq!(actual, expected);
+        assert!(output.as_str().unwrap().contains("dry_run: true"));
+    }
+
+    #[test]
+    fn test_patch_is_read_only_when_dry_run() {
+        use crate::attachment::tests::MockInfrastructure;
+
+        let patch = ApplyPatchJson::new(Arc::new(MockInfrastructure::new()));
+        let path = Path::new("/project/main.rs");
+
+        let actual =
+            [true, false].map(|dry_run| patch.is_read_only(&preview_fixture(path, dry_run)));
+
+        assert_eq!(actual, [true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_patch_preview_matches_applied_change() {
+        use crate::attachment::tests::MockInfrastructure;
+        use crate::FsReadService;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.rs");
+        fs::write(&path, "fn main() {\n    let x = 1;\n}\n")
+            .await
+            .unwrap();
+        let infra = Arc::new(MockInfrastructure::new());
+        let tool = ApplyPatchJson::new(infra.clone());
+
+        let preview = tool
+            .call(ToolCallContext::default(), preview_fixture(&path, true))
+            .await
+            .unwrap();
+        let applied = tool
+            .call(ToolCallContext::default(), preview_fixture(&path, false))
+            .await
+            .unwrap();
+
+        let actual = preview
+            .as_str()
+            .unwrap()
+            .replace("dry_run: true, the file was not modified\n", "");
+        assert_eq!(actual, applied.as_str().unwrap());
+        let actual = infra.file_read_service().read(&path).await.unwrap();
+        assert_eq!(actual, b"fn main() {\n    let x = 2;\n}\n".to_vec());
+    }
+
     // The previous individual tests are removed since they're now consolidated
 
     #[tokio::test]
@@ -462,4 +984,29 @@ mod test {
         assert!(display_path.is_ok());
         assert_eq!(display_path.unwrap(), file_path.display().to_string());
     }
+
+    #[test]
+    fn test_describe_patch() {
+        let patch = |search: &str, operation| FSPatchInput {
+            path: "/test.rs".to_string(),
+            search: search.to_string(),
+            operation,
+            content: "fn bar() {}".to_string(),
+            dry_run: false,
+        };
+
+        let actual = vec![
+            describe_patch(&patch(
+                "\n    fn foo() {\n    }",
+                forge_domain::PatchOperation::Replace,
+            )),
+            describe_patch(&patch("", forge_domain::PatchOperation::Append)),
+        ];
+
+        let expected = vec![
+            "replace \"fn foo() {\"".to_string(),
+            "append to end of file".to_string(),
+        ];
+        assert_eq!(actual, expected);
+    }
 }
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use forge_display::TitleFormat;
+use forge_domain::{
+    ExecutableTool, NamedTool, Plan, PlanInput, ToolCallContext, ToolDescription, ToolName,
+    ToolOutput,
+};
+use forge_tool_macros::ToolDescription;
+
+/// Use this tool before starting a large task, one touching many files or
+/// hard to undo, to propose a plan for the user to review. Give the goal and
+/// the ordered steps, listing the files each step creates, modifies or
+/// removes. Calling it again replaces the plan, for example to apply the
+/// changes the user asked for. Until the user approves the plan only tools
+/// that don't change anything can be used, so end your turn with
+/// forge_tool_attempt_completion after proposing it.
+#[derive(Debug, Default, ToolDescription)]
+pub struct ProposePlan;
+
+impl NamedTool for ProposePlan {
+    fn tool_name() -> ToolName {
+        ToolName::plan()
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ProposePlan {
+    type Input = PlanInput;
+
+    async fn call(&self, context: ToolCallContext, input: Self::Input) -> Result<ToolOutput> {
+        anyhow::ensure!(!input.steps.is_empty(), "A plan needs at least one step");
+
+        // The orchestrator keeps the plan on the conversation, this only shows it
+        let plan = Plan::from(input);
+        context
+            .send_text(TitleFormat::action("Plan").sub_title("use /approve to approve it"))
+            .await?;
+        context.send_text(&plan).await?;
+
+        Ok(ToolOutput::text(
+            "Plan proposed, don't make any changes until the user approves it.".to_string(),
+        ))
+    }
+
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::PlanStep;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::utils::ToolContentExtension;
+
+    #[tokio::test]
+    async fn test_propose_plan() {
+        let fixture = PlanInput {
+            goal: "Greet the user".to_string(),
+            steps: vec![PlanStep {
+                description: "Create hello.txt".to_string(),
+                files: vec!["hello.txt".to_string()],
+            }],
+        };
+
+        let actual = ProposePlan
+            .call(ToolCallContext::default(), fixture)
+            .await
+            .unwrap();
+
+        let expected = "Plan proposed, don't make any changes until the user approves it.";
+        assert_eq!(actual.into_string(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_propose_plan_without_steps() {
+        let fixture = PlanInput { goal: "Greet the user".to_string(), steps: vec![] };
+
+        let actual = ProposePlan.call(ToolCallContext::default(), fixture).await;
+
+        assert!(actual.is_err());
+    }
+}
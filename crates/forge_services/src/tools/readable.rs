@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use html2md::containers::ContainerHandler;
+use html2md::{Handle, NodeData, StructuredPrinter, TagHandler, TagHandlerFactory};
+
+/// Elements dropped along with everything inside them, as they hold code,
+/// styling or page furniture rather than content
+const BOILERPLATE: [&str; 10] = [
+    "head", "script", "style", "noscript", "template", "svg", "form", "nav", "aside", "footer",
+];
+
+/// Containers dropped when their ARIA role marks them as page furniture
+const LANDMARK_CONTAINERS: [&str; 3] = ["div", "section", "header"];
+const BOILERPLATE_ROLES: [&str; 5] = [
+    "banner",
+    "navigation",
+    "contentinfo",
+    "complementary",
+    "search",
+];
+
+/// Elements that pages use to mark their main content
+const MAIN_CONTENT: [&str; 2] = ["main", "article"];
+
+// Private use characters bracketing the main content in the converted text
+const MAIN_START: char = '\u{E000}';
+const MAIN_END: char = '\u{E001}';
+
+/// Converts an HTML page to markdown, keeping its main content only. Scripts,
+/// styles and navigation are dropped, and when the page marks its main content
+/// with `<main>` or `<article>` everything around it is dropped as well.
+/// Headings, code blocks, links and tables are kept.
+pub fn readable_markdown(html: &str) -> String {
+    let markdown = html2md::parse_html_custom(html, &handlers());
+    let content = main_content(&markdown).unwrap_or(&markdown);
+    content
+        .replace([MAIN_START, MAIN_END], "")
+        .trim()
+        .to_string()
+}
+
+fn handlers() -> HashMap<String, Box<dyn TagHandlerFactory>> {
+    let mut handlers: HashMap<String, Box<dyn TagHandlerFactory>> = HashMap::new();
+    for tag in BOILERPLATE {
+        handlers.insert(tag.to_string(), Box::new(Factory(|| Box::new(Skip))));
+    }
+    for tag in LANDMARK_CONTAINERS {
+        handlers.insert(
+            tag.to_string(),
+            Box::new(Factory(|| Box::new(Landmark::default()))),
+        );
+    }
+    for tag in MAIN_CONTENT {
+        handlers.insert(tag.to_string(), Box::new(Factory(|| Box::new(MainContent))));
+    }
+    handlers
+}
+
+/// The text between the outermost markers of the first main content element
+fn main_content(markdown: &str) -> Option<&str> {
+    let start = markdown.find(MAIN_START)? + MAIN_START.len_utf8();
+    let mut depth = 1;
+    for (i, c) in markdown[start..].char_indices() {
+        match c {
+            MAIN_START => depth += 1,
+            MAIN_END if depth == 1 => return Some(&markdown[start..start + i]),
+            MAIN_END => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+struct Factory(fn() -> Box<dyn TagHandler>);
+
+impl TagHandlerFactory for Factory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        (self.0)()
+    }
+}
+
+/// Drops the element and its descendants
+struct Skip;
+
+impl TagHandler for Skip {
+    fn handle(&mut self, _: &Handle, _: &mut StructuredPrinter) {}
+
+    fn after_handle(&mut self, _: &mut StructuredPrinter) {}
+
+    fn skip_descendants(&self) -> bool {
+        true
+    }
+}
+
+/// Converts a container like html2md does, unless its role marks it as
+/// navigation or another kind of page furniture
+#[derive(Default)]
+struct Landmark {
+    container: ContainerHandler,
+    skip: bool,
+}
+
+impl TagHandler for Landmark {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        if let NodeData::Element { attrs, .. } = &tag.data {
+            self.skip = attrs.borrow().iter().any(|attr| {
+                &*attr.name.local == "role" && BOILERPLATE_ROLES.contains(&&*attr.value)
+            });
+        }
+        if !self.skip {
+            self.container.handle(tag, printer);
+        }
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        if !self.skip {
+            self.container.after_handle(printer);
+        }
+    }
+
+    fn skip_descendants(&self) -> bool {
+        self.skip
+    }
+}
+
+/// Brackets the main content with markers, so that it can be cut out of the
+/// converted page
+struct MainContent;
+
+impl TagHandler for MainContent {
+    fn handle(&mut self, _: &Handle, printer: &mut StructuredPrinter) {
+        printer.append_str(&MAIN_START.to_string());
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        printer.append_str(&MAIN_END.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_readable_markdown_drops_boilerplate() {
+        let fixture = include_str!("fixtures/docs_page.html");
+
+        let actual = readable_markdown(fixture);
+
+        insta::assert_snapshot!(actual);
+        for boilerplate in [
+            "trackPageView",
+            "font-family",
+            "Products",
+            "Sign up for our newsletter",
+            "Copyright",
+            "Related articles",
+            "Skip to content",
+        ] {
+            assert!(!actual.contains(boilerplate), "{boilerplate} in {actual}");
+        }
+    }
+
+    #[test]
+    fn test_readable_markdown_keeps_code_blocks() {
+        let fixture = include_str!("fixtures/docs_page.html");
+
+        let actual = readable_markdown(fixture);
+
+        let expected = "```\nlet config = Config::load(\"forge.yaml\")?;\nconfig.validate()?;\n```";
+        assert!(actual.contains(expected), "{actual}");
+    }
+
+    #[test]
+    fn test_readable_markdown_without_main_content() {
+        let fixture = r#"<html><body>
+            <nav><a href="/">Home</a></nav>
+            <div role="navigation"><a href="/docs">Docs</a></div>
+            <h1>Title</h1>
+            <p>Body with a <a href="https://example.com">link</a>.</p>
+            <script>alert("hi")</script>
+        </body></html>"#;
+
+        let actual = readable_markdown(fixture);
+
+        let expected = "Title\n==========\n\nBody with a [link](https://example.com).";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_main_content_nested() {
+        let fixture = format!("a{MAIN_START}b{MAIN_START}c{MAIN_END}d{MAIN_END}e");
+
+        let actual = main_content(&fixture);
+
+        let expected = Some(format!("b{MAIN_START}c{MAIN_END}d"));
+        assert_eq!(actual.map(str::to_string), expected);
+    }
+}
@@ -6,7 +6,9 @@ use super::completion::Completion;
 use super::fetch::Fetch;
 use super::fs::*;
 use super::patch::*;
-use super::shell::Shell;
+use super::plan::ProposePlan;
+use super::shell::{Shell, CALL_TIMEOUT};
+use super::think::Think;
 use crate::tools::followup::Followup;
 use crate::Infrastructure;
 
@@ -21,19 +23,30 @@ impl<F: Infrastructure> ToolRegistry<F> {
 
     /// Returns all available tools configured with the given infrastructure
     pub fn tools(&self) -> Vec<Tool> {
+        let mut shell = Tool::from(Shell::new(self.infra.clone()));
+        shell.definition.timeout = Some(CALL_TIMEOUT);
+        let mut fs_read = Tool::from(FSRead::new(self.infra.clone()));
+        fs_read.definition.limits_output = true;
+        let mut fetch = Tool::from(Fetch::new(self.infra.clone()));
+        fetch.definition.limits_output = true;
+
         vec![
-            FSRead::new(self.infra.clone()).into(),
+            fs_read,
             FSWrite::new(self.infra.clone()).into(),
             FSRemove::new(self.infra.clone()).into(),
-            FSList::default().into(),
+            FSMove::new(self.infra.clone()).into(),
+            FSCopy::new(self.infra.clone()).into(),
+            FSList.into(),
             FSFind::new(self.infra.clone()).into(),
             FSFileInfo::new(self.infra.clone()).into(),
             FsUndo::new(self.infra.clone()).into(),
             ApplyPatchJson::new(self.infra.clone()).into(),
-            Shell::new(self.infra.clone()).into(),
+            shell,
             Completion.into(),
             Followup::new(self.infra.clone()).into(),
-            Fetch::new(self.infra.clone()).into(),
+            fetch,
+            Think.into(),
+            ProposePlan.into(),
         ]
     }
 }
@@ -42,19 +55,27 @@ impl<F: Infrastructure> ToolRegistry<F> {
 pub mod tests {
 
     use std::path::{Path, PathBuf};
+    use std::time::Duration;
 
     use bytes::Bytes;
     use forge_domain::{
-        CommandOutput, Environment, EnvironmentService, Provider, ToolDefinition, ToolName,
-        ToolOutput,
+        CommandEnv, CommandOutput, Compression, Environment, EnvironmentService, FileEvent,
+        OutputLine, Provider, ToolDefinition, ToolName, ToolOutput,
+    };
+    use forge_snaps::{
+        ExportReport, ImportReport, ImportStrategy, PurgePolicy, Snapshot, SnapshotId,
+        SnapshotSelector, SnapshotSummary, TreeRestoreReport, TreeSnapshotInfo, VerificationIssue,
     };
-    use forge_snaps::Snapshot;
+    use futures::stream::BoxStream;
+    use pretty_assertions::assert_eq;
     use serde_json::Value;
+    use tokio::sync::mpsc::UnboundedSender;
 
     use super::*;
     use crate::{
-        CommandExecutorService, FileRemoveService, FsCreateDirsService, FsMetaService,
-        FsReadService, FsSnapshotService, FsWriteService, InquireService, McpClient, McpServer,
+        CommandExecutorService, FileRemoveService, FsCopyService, FsCreateDirsService,
+        FsMetaService, FsMoveService, FsReadService, FsSnapshotService, FsWatchService,
+        FsWriteService, InquireService, McpClient, McpServer,
     };
 
     /// Create a default test environment
@@ -73,6 +94,21 @@ pub mod tests {
                 pid: std::process::id(),
                 provider: Provider::anthropic("test-key"),
                 retry_config: Default::default(),
+                tool_output_limit: Default::default(),
+                ignore_patterns: vec![],
+                shell_timeout_secs: 300,
+                syntax_check: true,
+                restricted: false,
+                tool_timeout_secs: 300,
+                verbose: false,
+                model: None,
+                temperature: None,
+                top_p: None,
+                top_k: None,
+                snapshot_compression: Compression::None,
+                fetch_max_bytes: 512 * 1024,
+                provider_fallback: None,
+                fallback_providers: vec![],
             },
         }
     }
@@ -121,20 +157,100 @@ pub mod tests {
             unimplemented!()
         }
 
-        async fn write_temp(&self, _: &str, _: &str, _: &str) -> anyhow::Result<PathBuf> {
+        async fn append(&self, _: &Path, _: Bytes) -> anyhow::Result<()> {
             unimplemented!()
         }
+
+        async fn write_temp(&self, prefix: &str, ext: &str, _: &str) -> anyhow::Result<PathBuf> {
+            Ok(PathBuf::from(format!("{prefix}{ext}")))
+        }
     }
 
     #[async_trait::async_trait]
     impl FsSnapshotService for Stub {
-        async fn create_snapshot(&self, _: &Path) -> anyhow::Result<Snapshot> {
+        async fn create_snapshot(&self, _: &Path, _: Option<String>) -> anyhow::Result<Snapshot> {
             unimplemented!()
         }
 
         async fn undo_snapshot(&self, _: &Path) -> anyhow::Result<()> {
             Ok(())
         }
+
+        async fn restore_to(
+            &self,
+            _: &Path,
+            _: SnapshotSelector,
+            _: &Path,
+            _: bool,
+        ) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        async fn list_all(&self) -> anyhow::Result<Vec<SnapshotSummary>> {
+            unimplemented!()
+        }
+
+        async fn list_snapshots(&self, _: &Path) -> anyhow::Result<Vec<Snapshot>> {
+            unimplemented!()
+        }
+
+        async fn diff_snapshots(&self, _: &Path, _: isize, _: isize) -> anyhow::Result<String> {
+            unimplemented!()
+        }
+
+        async fn diff_snapshot_and_current(&self, _: &Path, _: isize) -> anyhow::Result<String> {
+            unimplemented!()
+        }
+
+        async fn create_tree_snapshot(
+            &self,
+            _: &Path,
+            _: &[String],
+            _: &[String],
+        ) -> anyhow::Result<TreeSnapshotInfo> {
+            unimplemented!()
+        }
+
+        async fn restore_tree(&self, _: &SnapshotId) -> anyhow::Result<TreeRestoreReport> {
+            unimplemented!()
+        }
+
+        async fn verify(&self, _: &Path) -> anyhow::Result<Vec<VerificationIssue>> {
+            unimplemented!()
+        }
+
+        async fn verify_all(&self) -> anyhow::Result<Vec<VerificationIssue>> {
+            unimplemented!()
+        }
+
+        async fn tag_snapshot(&self, _: &Path, _: &str, _: &str) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        async fn restore_by_tag(&self, _: &Path, _: &str) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        async fn purge_excess(&self, _: &Path, _: usize) -> anyhow::Result<usize> {
+            unimplemented!()
+        }
+
+        async fn purge(&self, _: PurgePolicy) -> anyhow::Result<usize> {
+            unimplemented!()
+        }
+
+        async fn export_archive(&self, _: &Path, _: &Path) -> anyhow::Result<ExportReport> {
+            unimplemented!()
+        }
+
+        async fn import_archive(
+            &self,
+            _: &Path,
+            _: &Path,
+            _: ImportStrategy,
+        ) -> anyhow::Result<ImportReport> {
+            unimplemented!()
+        }
     }
 
     #[async_trait::async_trait]
@@ -155,6 +271,27 @@ pub mod tests {
         }
     }
 
+    #[async_trait::async_trait]
+    impl FsMoveService for Stub {
+        async fn rename(&self, _: &Path, _: &Path) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FsCopyService for Stub {
+        async fn copy(&self, _: &Path, _: &Path) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FsWatchService for Stub {
+        async fn watch(&self, _: &Path) -> anyhow::Result<BoxStream<'static, FileEvent>> {
+            unimplemented!()
+        }
+    }
+
     #[async_trait::async_trait]
     impl FsCreateDirsService for Stub {
         async fn create_dirs(&self, _: &Path) -> anyhow::Result<()> {
@@ -164,7 +301,14 @@ pub mod tests {
 
     #[async_trait::async_trait]
     impl CommandExecutorService for Stub {
-        async fn execute_command(&self, _: String, _: PathBuf) -> anyhow::Result<CommandOutput> {
+        async fn execute_command(
+            &self,
+            _: String,
+            _: PathBuf,
+            _: CommandEnv,
+            _: Option<Duration>,
+            _: Option<UnboundedSender<OutputLine>>,
+        ) -> anyhow::Result<CommandOutput> {
             unimplemented!()
         }
         async fn execute_command_raw(&self, _: &str) -> anyhow::Result<std::process::ExitStatus> {
@@ -233,6 +377,9 @@ pub mod tests {
         type FsReadService = Stub;
         type FsWriteService = Stub;
         type FsRemoveService = Stub;
+        type FsMoveService = Stub;
+        type FsCopyService = Stub;
+        type FsWatchService = Stub;
         type FsMetaService = Stub;
         type FsSnapshotService = Stub;
         type FsCreateDirsService = Stub;
@@ -265,6 +412,18 @@ pub mod tests {
             self
         }
 
+        fn file_move_service(&self) -> &Self::FsMoveService {
+            self
+        }
+
+        fn file_copy_service(&self) -> &Self::FsCopyService {
+            self
+        }
+
+        fn file_watch_service(&self) -> &Self::FsWatchService {
+            self
+        }
+
         fn create_dirs_service(&self) -> &Self::FsCreateDirsService {
             self
         }
@@ -282,6 +441,21 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_single_patch_tool() {
+        let fixture = ToolRegistry::new(Arc::new(stub()));
+
+        let actual = fixture
+            .tools()
+            .into_iter()
+            .map(|tool| tool.definition.name)
+            .filter(|name| name.as_str().contains("patch"))
+            .collect::<Vec<_>>();
+
+        let expected = vec![ToolName::new("forge_tool_fs_patch")];
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_tool_description_length() {
         const MAX_DESCRIPTION_LENGTH: usize = 1024;
@@ -1,23 +1,44 @@
-// PathBuf now comes from the ShellInput in forge_domain
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::bail;
 use forge_display::TitleFormat;
 use forge_domain::{
-    CommandOutput, Environment, EnvironmentService, ExecutableTool, NamedTool, ShellInput,
-    ToolCallContext, ToolDescription, ToolName, ToolOutput,
+    split_command, CommandEnv, CommandOutput, CommandPolicy, CommandVerdict, ConversationId,
+    Environment, EnvironmentService, ExecutableTool, Impact, NamedTool, OutputLine, ShellInput,
+    ToolCallContext, ToolDescription, ToolName, ToolOutput, SECRET_ENV_VARS,
 };
+use forge_snaps::TreeSnapshotInfo;
 use forge_tool_macros::ToolDescription;
 use strip_ansi_escapes::strip;
+use tokio::sync::mpsc::unbounded_channel;
 
+use crate::infra::InquireService;
 use crate::metadata::Metadata;
-use crate::{Clipper, ClipperResult, CommandExecutorService, FsWriteService, Infrastructure};
+use crate::utils::{assert_path_within, normalize_path};
+use crate::{
+    Clipper, ClipperResult, CommandExecutorService, FsSnapshotService, FsWriteService,
+    Infrastructure,
+};
+
+/// Option of the approval prompt that lets a command run
+const APPROVE: &str = "Run";
+
+/// Number of bytes to keep at the start of a truncated stream
+const HEAD_BYTES: usize = 4_000;
 
-/// Number of characters to keep at the start of truncated output
-const PREFIX_CHARS: usize = 10_000;
+/// Number of bytes to keep at the end of a truncated stream, which is where
+/// errors usually are
+const TAIL_BYTES: usize = 12_000;
 
-/// Number of characters to keep at the end of truncated output
-const SUFFIX_CHARS: usize = 10_000;
+/// Longest time a command may be allowed to run
+const MAX_TIMEOUT_SECS: u64 = 3600;
+
+/// Time after which a call to the shell tool is cancelled. Commands are
+/// stopped by the tool itself before that, keeping the output printed so far.
+pub(crate) const CALL_TIMEOUT: Duration = Duration::from_secs(MAX_TIMEOUT_SECS + 60);
 
 // Using ShellInput from forge_domain
 
@@ -30,13 +51,19 @@ fn strip_ansi(content: String) -> String {
 /// stderr is commonly used for warnings and progress info, so success is
 /// determined by exit status, not stderr presence. Returns Ok(output) on
 /// success or Err(output) on failure, with a status message if both streams are
-/// empty.
+/// empty. When `fail_on_nonzero` is false a non-zero exit code is still
+/// reported in the metadata but the output is returned as Ok. The directory
+/// the command ran in and the id of the tree snapshot taken before the
+/// command, if any, are reported in the metadata. A stream clipped by
+/// `clipper` is saved in full to a temp file listed in the metadata.
 async fn format_output<F: Infrastructure>(
     infra: &Arc<F>,
     mut output: CommandOutput,
+    cwd: Option<&Path>,
+    tree_snapshot: Option<&TreeSnapshotInfo>,
     keep_ansi: bool,
-    prefix_chars: usize,
-    suffix_chars: usize,
+    fail_on_nonzero: bool,
+    clipper: Option<Clipper>,
 ) -> anyhow::Result<String> {
     let mut formatted_output = String::new();
 
@@ -48,60 +75,65 @@ async fn format_output<F: Infrastructure>(
     // Create metadata
     let mut metadata = Metadata::default()
         .add("command", &output.command)
-        .add_optional("exit_code", output.exit_code);
+        .add_optional("cwd", cwd.map(Path::display))
+        .add_optional("exit_code", output.exit_code)
+        .add_optional(
+            "timed_out_after_secs",
+            output.timed_out.map(|timeout| timeout.as_secs()),
+        )
+        .add_optional("tree_snapshot", tree_snapshot.map(|snapshot| &snapshot.id));
 
     let mut is_truncated = false;
 
-    // Format stdout if not empty
-    if !output.stdout.trim().is_empty() {
-        let result = Clipper::from_start_end(prefix_chars, suffix_chars).clip(&output.stdout);
-
-        if result.is_truncated() {
-            metadata = metadata.add("total_stdout_chars", output.stdout.len());
-            is_truncated = true;
+    let streams = [
+        (
+            "stdout",
+            &output.stdout,
+            ["total_stdout_lines", "total_stdout_bytes", "stdout_file"],
+        ),
+        (
+            "stderr",
+            &output.stderr,
+            ["total_stderr_lines", "total_stderr_bytes", "stderr_file"],
+        ),
+    ];
+    for (tag, content, [lines_key, bytes_key, file_key]) in streams {
+        if content.trim().is_empty() {
+            continue;
         }
-        formatted_output.push_str(&tag_output(result, "stdout", &output.stdout));
-    }
-
-    // Format stderr if not empty
-    if !output.stderr.trim().is_empty() {
         if !formatted_output.is_empty() {
             formatted_output.push('\n');
         }
-        let result = Clipper::from_start_end(prefix_chars, suffix_chars).clip(&output.stderr);
 
+        let result = match &clipper {
+            Some(clipper) => clipper.clone().clip(content),
+            None => ClipperResult { actual: content, prefix: None, suffix: None },
+        };
         if result.is_truncated() {
-            metadata = metadata.add("total_stderr_chars", output.stderr.len());
+            // The file holds nothing but the stream, so that its lines can be
+            // read by the numbers shown in the output
+            let path = infra
+                .file_write_service()
+                .write_temp(&format!("forge_shell_{tag}_"), ".txt", content)
+                .await?;
+            metadata = metadata
+                .add(lines_key, content.lines().count())
+                .add(bytes_key, content.len())
+                .add(file_key, path.display());
             is_truncated = true;
         }
-        formatted_output.push_str(&tag_output(result, "stderr", &output.stderr));
+        formatted_output.push_str(&tag_output(result, tag));
     }
 
-    // Add temp file path if output is truncated
     if is_truncated {
-        let path = infra
-            .file_write_service()
-            .write_temp(
-                "forge_shell_",
-                ".md",
-                &format!(
-                    "command:{}\n<stdout>{}</stdout>\n<stderr>{}</stderr>",
-                    output.command, output.stdout, output.stderr
-                ),
-            )
-            .await?;
-
-        metadata = metadata
-            .add("temp_file", path.display())
-            .add("truncated", "true");
-        formatted_output.push_str(&format!(
-            "<truncate>content is truncated, remaining content can be read from path:{}</truncate>",
-            path.display()
-        ));
+        metadata = metadata.add("truncated", "true");
+        formatted_output.push_str(
+            "<truncate>content is truncated, the full output can be read from the files in the metadata using the line numbers shown</truncate>",
+        );
     }
 
     // Handle empty outputs
-    let result = if formatted_output.is_empty() {
+    let mut result = if formatted_output.is_empty() {
         if output.success() {
             "Command executed successfully with no output.".to_string()
         } else {
@@ -111,58 +143,191 @@ async fn format_output<F: Infrastructure>(
         formatted_output
     };
 
-    if output.success() {
+    if let Some(timeout) = output.timed_out {
+        result.push_str(&format!(
+            "\n<timeout>command timed out after {}s, output may be incomplete</timeout>",
+            timeout.as_secs()
+        ));
+    }
+
+    if output.success() || !fail_on_nonzero {
         Ok(format!("{metadata}{result}"))
     } else {
         bail!(format!("{metadata}{result}"))
     }
 }
 
-/// Helper function to format potentially truncated output for stdout or stderr
-fn tag_output(result: ClipperResult, tag: &str, content: &str) -> String {
-    let mut formatted_output = String::default();
-    match (result.prefix, result.suffix) {
-        (Some(prefix), Some(suffix)) => {
-            let truncated_chars = content.len() - prefix.len() - suffix.len();
-            let prefix_content = &content[prefix.clone()];
-            let suffix_content = &content[suffix.clone()];
-
-            formatted_output.push_str(&format!(
-                "<{} chars=\"{}-{}\">\n{}\n</{}>\n",
-                tag, prefix.start, prefix.end, prefix_content, tag
-            ));
-            formatted_output.push_str(&format!(
-                "<truncated>...{tag} truncated ({truncated_chars} characters not shown)...</truncated>\n"
-            ));
-            formatted_output.push_str(&format!(
-                "<{} chars=\"{}-{}\">\n{}\n</{}>\n",
-                tag, suffix.start, suffix.end, suffix_content, tag
-            ));
-        }
-        _ => formatted_output.push_str(&format!("<{tag}>\n{content}\n</{tag}>")),
+/// Helper function to format potentially truncated output for stdout or
+/// stderr. The kept parts of truncated output are labelled with their line
+/// numbers.
+fn tag_output(result: ClipperResult, tag: &str) -> String {
+    let Some(prefix) = result.prefix_content() else {
+        return format!("<{tag}>\n{}\n</{tag}>", result.actual);
+    };
+
+    let total_lines = result.actual.lines().count();
+    let omitted_lines = result.omitted_content().lines().count();
+    let mut formatted_output = tag_lines(tag, 1, prefix);
+    formatted_output.push_str(&format!(
+        "<truncated>... {omitted_lines} lines omitted ...</truncated>\n"
+    ));
+    if let Some(suffix) = result.suffix_content() {
+        let first_line = total_lines - suffix.lines().count() + 1;
+        formatted_output.push_str(&tag_lines(tag, first_line, suffix));
     }
 
     formatted_output
 }
 
+/// Wraps part of an output, which starts at line `first_line`, in a tag
+fn tag_lines(tag: &str, first_line: usize, content: &str) -> String {
+    let last_line = first_line + content.lines().count().max(1) - 1;
+    let content = content.strip_suffix('\n').unwrap_or(content);
+    format!("<{tag} lines=\"{first_line}-{last_line}\">\n{content}\n</{tag}>\n")
+}
+
+/// The changes to the environment of a command: the variables set by the call,
+/// and the removal of the provider API keys that aren't in `allowlist`
+fn command_env(vars: HashMap<String, String>, allowlist: &[String]) -> CommandEnv {
+    let removed = SECRET_ENV_VARS
+        .iter()
+        .filter(|name| !allowlist.iter().any(|allowed| allowed == *name))
+        .map(|name| name.to_string())
+        .collect();
+    CommandEnv { vars, removed }
+}
+
+/// Values of the provider API keys forge runs with. They are redacted from the
+/// output of every command, including those allowed to inherit them.
+fn secret_values() -> Vec<String> {
+    SECRET_ENV_VARS
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Replaces every occurrence of the secrets in `text` with `***`
+fn redact(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .fold(text.to_string(), |text, secret| text.replace(secret, "***"))
+}
+
+/// Returns the directory a command changes into at its very end, for eg: `sub`
+/// for `cargo build && cd sub`. Changes that depend on the shell, such as
+/// `cd ~` or `cd $DIR`, are not recognised.
+fn trailing_cd(command: &str) -> Option<&str> {
+    let last = command.trim().rsplit(['\n', ';', '&', '|']).next()?.trim();
+    let dir = last.strip_prefix("cd")?;
+    if !dir.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let dir = dir.trim();
+    let quoted = ['\'', '"']
+        .iter()
+        .find_map(|quote| dir.strip_prefix(*quote)?.strip_suffix(*quote));
+    let dir = quoted.unwrap_or(dir);
+    let is_plain = !dir.is_empty()
+        && dir != "-"
+        && !dir.starts_with('~')
+        && (quoted.is_some() || !dir.contains(char::is_whitespace))
+        && !dir.contains(['$', '`', '*', '?', '\\', '\'', '"']);
+    is_plain.then_some(dir)
+}
+
 /// Executes shell commands with safety measures using restricted bash (rbash).
 /// Prevents potentially harmful operations like absolute path execution and
 /// directory changes. Use for file system interaction, running utilities,
 /// installing packages, or executing build commands. For operations requiring
 /// unrestricted access, advise users to run forge CLI with '-u' flag. Returns
 /// complete output including stdout, stderr, and exit code for diagnostic
-/// purposes.
+/// purposes. Commands are stopped after timeout_secs (300 by default) along
+/// with any processes they started, so never run commands that don't exit on
+/// their own, such as watchers or servers, in the foreground. Commands run in
+/// the current directory unless cwd is set. A command ending with `cd <dir>`,
+/// or setting persist_cwd, changes the current directory for later commands,
+/// and the result reports the directory each command ran in. Long output is
+/// cut down to its first and last lines unless full_output is set. Set env to
+/// pass extra environment variables.
 #[derive(ToolDescription)]
 pub struct Shell<I> {
     env: Environment,
     infra: Arc<I>,
+    /// Directory later commands of each conversation run in by default, set
+    /// by earlier commands of the conversation. The project root is used until
+    /// then.
+    cwd: Mutex<HashMap<Option<ConversationId>, PathBuf>>,
 }
 
 impl<I: Infrastructure> Shell<I> {
     /// Create a new Shell with environment configuration
     pub fn new(infra: Arc<I>) -> Self {
         let env = infra.environment_service().get_environment();
-        Self { env, infra }
+        Self { env, infra, cwd: Mutex::new(HashMap::new()) }
+    }
+
+    /// The directory commands of the conversation run in when they don't set
+    /// one
+    fn current_dir(&self, context: &ToolCallContext) -> PathBuf {
+        self.cwd
+            .lock()
+            .ok()
+            .and_then(|cwd| cwd.get(&context.conversation_id).cloned())
+            .unwrap_or_else(|| self.env.cwd.clone())
+    }
+
+    /// Resolves the directory a command runs in, which a restricted shell
+    /// keeps inside the project directory
+    fn working_dir(
+        &self,
+        context: &ToolCallContext,
+        cwd: Option<&Path>,
+    ) -> anyhow::Result<PathBuf> {
+        let current = self.current_dir(context);
+        let dir = match cwd {
+            Some(cwd) => normalize_path(&current.join(cwd)),
+            None => current,
+        };
+        if self.env.restricted {
+            assert_path_within(&dir, &self.env.cwd)?;
+        }
+        Ok(dir)
+    }
+
+    /// Refuses commands denied by `policy`, asking the user to approve those
+    /// it leaves to them, showing the estimated `impact` of the command
+    async fn check_policy(
+        &self,
+        policy: &CommandPolicy,
+        command: &str,
+        impact: Option<Impact>,
+    ) -> anyhow::Result<()> {
+        let reason = match policy.evaluate(command) {
+            CommandVerdict::Allow => return Ok(()),
+            CommandVerdict::Deny(reason) => reason,
+            CommandVerdict::Ask(segments) => {
+                let segments = segments.join("`, `");
+                let mut question = format!("Run `{command}`? No allow rule covers `{segments}`");
+                if let Some(impact) = impact {
+                    question = format!("{question}\n{impact}");
+                }
+                let answer = self
+                    .infra
+                    .inquire_service()
+                    .select_one(
+                        &question,
+                        vec![APPROVE.to_string(), "Don't run".to_string()],
+                    )
+                    .await?;
+                if answer.as_deref() == Some(APPROVE) {
+                    return Ok(());
+                }
+                format!("the user didn't approve `{segments}`")
+            }
+        };
+
+        Err(forge_domain::Error::CommandDenied { command: command.to_string(), reason }.into())
     }
 }
 
@@ -190,68 +355,180 @@ impl<I: Infrastructure> ExecutableTool for Shell<I> {
 
         context.send_text(title_format).await?;
 
-        let output = self
-            .infra
-            .command_executor_service()
-            .execute_command(input.command, input.cwd)
-            .await?;
+        if let Some(policy) = context
+            .agent
+            .as_ref()
+            .and_then(|agent| agent.command_policy.as_ref())
+        {
+            let impact = self.impact(&context, &input).await?;
+            self.check_policy(policy, &input.command, impact).await?;
+        }
+
+        let cwd = self.working_dir(&context, input.cwd.as_deref())?;
+
+        let tree_snapshot = if input.destructive {
+            Some(
+                self.infra
+                    .file_snapshot_service()
+                    .create_tree_snapshot(&cwd, &[], &[])
+                    .await?,
+            )
+        } else {
+            None
+        };
+
+        let timeout_secs = input
+            .timeout_secs
+            .unwrap_or(self.env.shell_timeout_secs)
+            .clamp(1, MAX_TIMEOUT_SECS);
+        let allowlist = context
+            .agent
+            .as_ref()
+            .and_then(|agent| agent.env_allowlist.as_deref())
+            .unwrap_or_default();
+        let env = command_env(input.env.clone(), allowlist);
+        let secrets = secret_values();
+
+        // Lines are shown as the command prints them, while the whole output is
+        // still collected for the result
+        let (output_lines, mut printed) = unbounded_channel();
+        let execute = self.infra.command_executor_service().execute_command(
+            input.command.clone(),
+            cwd.clone(),
+            env,
+            Some(Duration::from_secs(timeout_secs)),
+            Some(output_lines),
+        );
+        let forward = async {
+            while let Some(line) = printed.recv().await {
+                let text = redact(&line.text, &secrets);
+                context
+                    .send_output_line(OutputLine { text, ..line })
+                    .await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+        let (mut output, ()) = tokio::try_join!(execute, forward)?;
+        output.stdout = redact(&output.stdout, &secrets);
+        output.stderr = redact(&output.stderr, &secrets);
+
+        let next_cwd = trailing_cd(&input.command)
+            .filter(|_| output.success())
+            .map(|dir| normalize_path(&cwd.join(dir)))
+            .or_else(|| input.persist_cwd.then(|| cwd.clone()))
+            .filter(|dir| !self.env.restricted || assert_path_within(dir, &self.env.cwd).is_ok());
+        if let (Some(dir), Ok(mut current)) = (next_cwd, self.cwd.lock()) {
+            current.insert(context.conversation_id.clone(), dir);
+        }
 
+        let limit = &self.env.tool_output_limit;
+        let clipper = (!input.full_output).then(|| {
+            Clipper::from_start_end_lines(
+                limit.shell_head_lines,
+                limit.shell_tail_lines,
+                HEAD_BYTES,
+                TAIL_BYTES,
+            )
+        });
         let result = format_output(
             &self.infra,
             output,
+            Some(&cwd),
+            tree_snapshot.as_ref(),
             input.keep_ansi,
-            PREFIX_CHARS,
-            SUFFIX_CHARS,
+            input.fail_on_nonzero,
+            clipper,
         )
         .await?;
         Ok(ToolOutput::text(result))
     }
+
+    async fn impact(
+        &self,
+        context: &ToolCallContext,
+        input: &Self::Input,
+    ) -> anyhow::Result<Option<Impact>> {
+        let commands = split_command(&input.command)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let cwd = self.working_dir(context, input.cwd.as_deref())?;
+        Ok(Some(Impact::Command {
+            commands,
+            cwd,
+            snapshot: input.destructive,
+        }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     #[tokio::test]
-    async fn test_format_output_with_different_max_chars() {
+    async fn test_format_output_with_different_line_limits() {
         let infra = Arc::new(MockInfrastructure::new());
+        let stdout: String = (1..=10).map(|i| format!("line {i}\n")).collect();
 
-        // Test with small truncation values that will truncate the string
+        // Test with small limits that will truncate the output
         let small_output = CommandOutput {
-            stdout: "ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string(),
+            stdout: stdout.clone(),
             stderr: "".to_string(),
             command: "echo".into(),
             exit_code: Some(0),
+            timed_out: None,
         };
-        let small_result = format_output(&infra, small_output, false, 5, 5)
-            .await
-            .unwrap();
+        let clipper = Clipper::from_start_end_lines(2, 3, 1000, 1000);
+        let small_result = format_output(
+            &infra,
+            small_output,
+            None,
+            None,
+            false,
+            false,
+            Some(clipper),
+        )
+        .await
+        .unwrap();
         insta::assert_snapshot!(
             "format_output_small_truncation",
             TempDir::normalize(&small_result)
         );
 
-        // Test with large values that won't cause truncation
+        // Test with large limits that won't cause truncation
         let large_output = CommandOutput {
-            stdout: "ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string(),
+            stdout,
             stderr: "".to_string(),
             command: "echo".into(),
             exit_code: Some(0),
+            timed_out: None,
         };
-        let large_result = format_output(&infra, large_output, false, 100, 100)
-            .await
-            .unwrap();
+        let clipper = Clipper::from_start_end_lines(100, 300, 1000, 1000);
+        let large_result = format_output(
+            &infra,
+            large_output,
+            None,
+            None,
+            false,
+            false,
+            Some(clipper),
+        )
+        .await
+        .unwrap();
         insta::assert_snapshot!(
             "format_output_no_truncation",
             TempDir::normalize(&large_result)
         );
     }
+
     use std::env;
     use std::sync::Arc;
 
+    use forge_domain::{Agent, ChatResponse, PolicyDefault, ToolOutputLimit};
     use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::attachment::tests::MockInfrastructure;
     use crate::utils::{TempDir, ToolContentExtension};
+    use crate::FsReadService;
 
     /// Platform-specific error message patterns for command not found errors
     #[cfg(target_os = "windows")]
@@ -276,8 +553,8 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: "echo 'Hello, World!'".to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -285,6 +562,25 @@ mod tests {
         assert!(result.contains("Mock command executed successfully"));
     }
 
+    #[tokio::test]
+    async fn test_shell_destructive_takes_tree_snapshot() {
+        let infra = Arc::new(MockInfrastructure::new());
+        let shell = Shell::new(infra);
+        let result = shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput {
+                    command: "cargo fmt".to_string(),
+                    keep_ansi: true,
+                    destructive: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(result.contains("tree_snapshot: 00000000-0000-0000-0000-000000000000"));
+    }
+
     #[tokio::test]
     async fn test_shell_stderr_with_success() {
         let infra = Arc::new(MockInfrastructure::new());
@@ -299,8 +595,8 @@ mod tests {
                     } else {
                         "echo 'to stderr' >&2; echo 'to stdout'".to_string()
                     },
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -317,8 +613,8 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: "echo 'to stdout' && echo 'to stderr' >&2".to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -326,6 +622,67 @@ mod tests {
         insta::assert_snapshot!(&result.into_string());
     }
 
+    #[tokio::test]
+    async fn test_shell_exit_code_zero() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+        let result = shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput {
+                    command: "exit 0".to_string(),
+                    keep_ansi: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        insta::assert_snapshot!(&result.into_string());
+    }
+
+    #[tokio::test]
+    async fn test_shell_exit_code_nonzero_fails() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+        let result = shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput {
+                    command: "exit 1".to_string(),
+                    keep_ansi: true,
+                    fail_on_nonzero: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        insta::assert_snapshot!(result.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_shell_exit_code_nonzero_allowed() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+        let result = shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput {
+                    command: "exit 1".to_string(),
+                    keep_ansi: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        insta::assert_snapshot!(&result.into_string());
+    }
+
+    #[test]
+    fn test_shell_input_fail_on_nonzero_defaults_to_false() {
+        let fixture = serde_json::json!({"command": "ls", "cwd": "/tmp"});
+        let actual = serde_json::from_value::<ShellInput>(fixture)
+            .unwrap()
+            .fail_on_nonzero;
+        assert!(!actual);
+    }
+
     #[tokio::test]
     async fn test_shell_with_working_directory() {
         let infra = Arc::new(MockInfrastructure::new());
@@ -341,8 +698,9 @@ mod tests {
                     } else {
                         "pwd".to_string()
                     },
-                    cwd: temp_dir.clone(),
+                    cwd: Some(temp_dir.clone()),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -353,6 +711,322 @@ mod tests {
         );
     }
 
+    fn shell_input(command: &str) -> ShellInput {
+        ShellInput {
+            command: command.to_string(),
+            keep_ansi: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shell_streams_output_lines() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+        let (sender, mut messages) = tokio::sync::mpsc::channel(100);
+        let context = ToolCallContext::default()
+            .agent(Agent::new("test"))
+            .sender(Some(Arc::new(sender)));
+
+        let call = tokio::spawn(async move { shell.call(context, shell_input("seq 10")).await });
+
+        // The context is dropped once the call is done, which ends the messages
+        let mut lines = Vec::new();
+        while let Some(message) = messages.recv().await {
+            if let ChatResponse::ToolOutputChunk(line) = message.unwrap().message {
+                if lines.is_empty() {
+                    assert!(
+                        !call.is_finished(),
+                        "output arrived after the command ended"
+                    );
+                }
+                lines.push(line.text);
+            }
+        }
+        let actual = call.await.unwrap().unwrap();
+
+        let expected: Vec<String> = (1..=10).map(|i| i.to_string()).collect();
+        assert_eq!(lines, expected);
+        assert!(actual.contains(&format!("{}\n", expected.join("\n"))));
+    }
+
+    #[tokio::test]
+    async fn test_shell_injects_env() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        let actual = shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput {
+                    env: [("RUST_LOG".to_string(), "debug".to_string())].into(),
+                    ..shell_input("printenv RUST_LOG")
+                },
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(actual.contains("<stdout>\ndebug\n\n</stdout>"));
+    }
+
+    #[test]
+    fn test_command_env_removes_provider_keys() {
+        let allowlist = vec!["OPENAI_API_KEY".to_string()];
+
+        let actual = command_env(HashMap::new(), &allowlist).removed;
+
+        assert!(actual.contains(&"FORGE_KEY".to_string()));
+        assert!(actual.contains(&"ANTHROPIC_API_KEY".to_string()));
+        assert!(!actual.contains(&"OPENAI_API_KEY".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_shell_redacts_secrets() {
+        let secret = "gsk-shell-redaction-test";
+        env::set_var("GROQ_API_KEY", secret);
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        let actual = shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput {
+                    env: [("LEAK".to_string(), format!("key={secret}"))].into(),
+                    ..shell_input("printenv LEAK")
+                },
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(actual.contains("<stdout>\nkey=***\n\n</stdout>"));
+        assert!(!actual.contains(secret));
+    }
+
+    fn policy_context() -> ToolCallContext {
+        let policy = CommandPolicy {
+            allow: vec!["printenv".to_string(), "seq".to_string()],
+            deny: vec![r"^git\s+push".parse().unwrap()],
+            default: PolicyDefault::Ask,
+        };
+        ToolCallContext::default().agent(Agent::new("test").command_policy(policy))
+    }
+
+    #[tokio::test]
+    async fn test_shell_policy_allows_command() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        let actual = shell
+            .call(policy_context(), shell_input("seq 2"))
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(actual.contains("<stdout>\n1\n2\n\n</stdout>"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_policy_denies_command() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        let actual = shell
+            .call(policy_context(), shell_input("git push origin main"))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            actual.to_string(),
+            r"Command 'git push origin main' was not run: `git push origin main` matches the deny rule `^git\s+push`"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shell_policy_denies_compound_command() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        let actual = shell
+            .call(policy_context(), shell_input("seq 2 && git push --force"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            actual.downcast_ref::<forge_domain::Error>(),
+            Some(forge_domain::Error::CommandDenied { .. })
+        ));
+        assert!(actual.to_string().contains("`git push --force` matches"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_policy_asks_for_approval() {
+        // The mock approves by picking the first option
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        let actual = shell
+            .call(policy_context(), shell_input("echo 'Hello, World!'"))
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(actual.contains("Mock command executed successfully"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_impact() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        let actual = shell
+            .impact(
+                &ToolCallContext::default(),
+                &ShellInput {
+                    cwd: Some(PathBuf::from("sub")),
+                    destructive: true,
+                    ..shell_input("cargo fmt && git diff | head -n 5")
+                },
+            )
+            .await
+            .unwrap();
+
+        let expected = Some(Impact::Command {
+            commands: vec![
+                "cargo fmt".to_string(),
+                "git diff".to_string(),
+                "head -n 5".to_string(),
+            ],
+            cwd: PathBuf::from("/test/sub"),
+            snapshot: true,
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_shell_relative_cwd() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        let actual = shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput {
+                    cwd: Some(PathBuf::from("crates/../sub")),
+                    ..shell_input("pwd")
+                },
+            )
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(actual.contains("cwd: /test/sub\n"));
+        assert!(actual.contains("<stdout>\n/test/sub\n"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_restricted_cwd_outside_project() {
+        let mut shell = Shell::new(Arc::new(MockInfrastructure::new()));
+        shell.env.restricted = true;
+
+        let actual = shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput { cwd: Some(PathBuf::from("../..")), ..shell_input("pwd") },
+            )
+            .await;
+
+        assert!(actual
+            .unwrap_err()
+            .to_string()
+            .contains("outside of the project directory"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_persists_cwd() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+
+        shell
+            .call(
+                ToolCallContext::default(),
+                shell_input("cargo build && cd sub"),
+            )
+            .await
+            .unwrap();
+        let after_cd = shell
+            .call(ToolCallContext::default(), shell_input("pwd"))
+            .await
+            .unwrap()
+            .into_string();
+        shell
+            .call(
+                ToolCallContext::default(),
+                ShellInput {
+                    cwd: Some(PathBuf::from("nested")),
+                    persist_cwd: true,
+                    full_output: false,
+                    ..shell_input("true")
+                },
+            )
+            .await
+            .unwrap();
+        let after_persist = shell
+            .call(ToolCallContext::default(), shell_input("pwd"))
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(after_cd.contains("<stdout>\n/test/sub\n"));
+        assert!(after_persist.contains("<stdout>\n/test/sub/nested\n"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_cwd_is_kept_per_conversation() {
+        let shell = Shell::new(Arc::new(MockInfrastructure::new()));
+        let first = ToolCallContext::default().conversation_id(ConversationId::generate());
+        let second = ToolCallContext::default().conversation_id(ConversationId::generate());
+
+        shell
+            .call(first.clone(), shell_input("cd sub"))
+            .await
+            .unwrap();
+        let in_first = shell
+            .call(first, shell_input("pwd"))
+            .await
+            .unwrap()
+            .into_string();
+        let in_second = shell
+            .call(second, shell_input("pwd"))
+            .await
+            .unwrap()
+            .into_string();
+
+        assert!(in_first.contains("<stdout>\n/test/sub\n"));
+        assert!(in_second.contains("<stdout>\n/test\n"));
+    }
+
+    #[test]
+    fn test_trailing_cd() {
+        let fixture = [
+            "cd sub",
+            "make && cd 'my dir'",
+            "echo done; cd ../other",
+            "cd sub && make",
+            "cd",
+            "cd my dir",
+            "cd ~/src",
+            "cd $HOME",
+            "cdk deploy",
+        ];
+
+        let actual: Vec<_> = fixture.iter().map(|command| trailing_cd(command)).collect();
+
+        let expected = vec![
+            Some("sub"),
+            Some("my dir"),
+            Some("../other"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
     #[tokio::test]
     async fn test_shell_invalid_command() {
         let shell = Shell::new(Arc::new(MockInfrastructure::new()));
@@ -361,8 +1035,9 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: "non_existent_command".to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    fail_on_nonzero: true,
+                    ..Default::default()
                 },
             )
             .await;
@@ -390,8 +1065,8 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: "".to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await;
@@ -425,8 +1100,9 @@ mod tests {
                     } else {
                         "pwd".to_string()
                     },
-                    cwd: current_dir.clone(),
+                    cwd: Some(current_dir.clone()),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -445,6 +1121,7 @@ mod tests {
                             "pwd"
                         }
                     )
+                    .add("cwd", current_dir.display())
                     .add("exit_code", 0)
                     .to_string(),
                 current_dir.display()
@@ -460,8 +1137,8 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: "echo 'first' && echo 'second'".to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -477,8 +1154,8 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: "true".to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -496,8 +1173,8 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: "echo ''".to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -515,8 +1192,8 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: "echo $PATH".to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await
@@ -540,8 +1217,8 @@ mod tests {
                 ToolCallContext::default(),
                 ShellInput {
                     command: cmd.to_string(),
-                    cwd: env::current_dir().unwrap(),
                     keep_ansi: true,
+                    ..Default::default()
                 },
             )
             .await;
@@ -554,6 +1231,33 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_format_output_timed_out() {
+        let infra = Arc::new(MockInfrastructure::new());
+        let output = || CommandOutput {
+            stdout: "partial".to_string(),
+            stderr: "".to_string(),
+            command: "npm install".into(),
+            exit_code: None,
+            timed_out: Some(Duration::from_secs(5)),
+        };
+
+        let allowed = format_output(&infra, output(), None, None, false, false, None)
+            .await
+            .unwrap();
+        let failed = format_output(&infra, output(), None, None, false, true, None).await;
+
+        assert!(allowed.contains("timed_out_after_secs: 5"));
+        assert!(!allowed.contains("exit_code"));
+        assert!(allowed.contains("<stdout>\npartial\n</stdout>"));
+        assert!(allowed
+            .ends_with("<timeout>command timed out after 5s, output may be incomplete</timeout>"));
+        assert!(failed
+            .unwrap_err()
+            .to_string()
+            .contains("command timed out after 5s"));
+    }
+
     #[tokio::test]
     async fn test_format_output_ansi_handling() {
         let infra = Arc::new(MockInfrastructure::new());
@@ -563,8 +1267,9 @@ mod tests {
             stderr: "\x1b[31mWarning\x1b[0m".to_string(),
             command: "ls -la".into(),
             exit_code: Some(0),
+            timed_out: None,
         };
-        let preserved = format_output(&infra, ansi_output, true, PREFIX_CHARS, SUFFIX_CHARS)
+        let preserved = format_output(&infra, ansi_output, None, None, true, false, None)
             .await
             .unwrap();
         insta::assert_snapshot!("format_output_ansi_preserved", preserved);
@@ -575,8 +1280,9 @@ mod tests {
             stderr: "\x1b[31mWarning\x1b[0m".to_string(),
             command: "ls -la".into(),
             exit_code: Some(0),
+            timed_out: None,
         };
-        let stripped = format_output(&infra, ansi_output, false, PREFIX_CHARS, SUFFIX_CHARS)
+        let stripped = format_output(&infra, ansi_output, None, None, false, false, None)
             .await
             .unwrap();
         insta::assert_snapshot!("format_output_ansi_stripped", stripped);
@@ -585,24 +1291,21 @@ mod tests {
     #[tokio::test]
     async fn test_format_output_with_large_command_output() {
         let infra = Arc::new(MockInfrastructure::new());
-        // Using tiny PREFIX_CHARS and SUFFIX_CHARS values (30) to test truncation with
-        // minimal content This creates very small snapshots while still testing
-        // the truncation logic
-        const TINY_PREFIX: usize = 30;
-        const TINY_SUFFIX: usize = 30;
-
-        // Create a test string just long enough to trigger truncation with our small
-        // prefix/suffix values
-        let test_string = "ABCDEFGHIJKLMNOPQRSTUVWXYZ".repeat(4); // 104 characters
+        // Using tiny limits to test truncation with minimal content. This creates
+        // very small snapshots while still testing the truncation logic, where
+        // the byte budget of the end is used up before its line budget
+        let clipper = Clipper::from_start_end_lines(2, 5, 1000, 15);
+        let test_string: String = (1..=10).map(|i| format!("line {i}\n")).collect();
 
         let ansi_output = CommandOutput {
             stdout: test_string.clone(),
             stderr: test_string,
             command: "ls -la".into(),
             exit_code: Some(0),
+            timed_out: None,
         };
 
-        let preserved = format_output(&infra, ansi_output, false, TINY_PREFIX, TINY_SUFFIX)
+        let preserved = format_output(&infra, ansi_output, None, None, false, false, Some(clipper))
             .await
             .unwrap();
         // Use a specific name for the snapshot instead of auto-generated name
@@ -611,4 +1314,71 @@ mod tests {
             TempDir::normalize(&preserved)
         );
     }
+
+    #[tokio::test]
+    async fn test_format_output_keeps_head_and_tail_lines() {
+        let infra = Arc::new(MockInfrastructure::new());
+        let stdout: String = (1..=10_000).map(|i| format!("line {i}\n")).collect();
+        let output = CommandOutput {
+            stdout: stdout.clone(),
+            stderr: "".to_string(),
+            command: "cargo test".into(),
+            exit_code: Some(101),
+            timed_out: None,
+        };
+        let limit = ToolOutputLimit::default();
+        let clipper = Clipper::from_start_end_lines(
+            limit.shell_head_lines,
+            limit.shell_tail_lines,
+            HEAD_BYTES,
+            TAIL_BYTES,
+        );
+
+        let actual = format_output(&infra, output, None, None, false, false, Some(clipper))
+            .await
+            .unwrap();
+
+        assert!(actual.contains("total_stdout_lines: 10000\n"));
+        assert!(actual.contains(&format!("total_stdout_bytes: {}\n", stdout.len())));
+        assert!(actual.contains("<stdout lines=\"1-100\">\nline 1\nline 2\n"));
+        assert!(actual.contains("line 100\n</stdout>\n"));
+        assert!(actual.contains("<truncated>... 9600 lines omitted ...</truncated>\n"));
+        assert!(actual.contains("<stdout lines=\"9701-10000\">\nline 9701\n"));
+        assert!(actual.contains("line 10000\n</stdout>\n"));
+        assert!(!actual.contains("line 101\n"));
+        assert!(!actual.contains("line 9700\n"));
+
+        // The omitted lines can be read back from the saved output
+        let path = actual
+            .lines()
+            .find_map(|line| line.strip_prefix("stdout_file: "))
+            .unwrap();
+        let saved = infra
+            .file_read_service()
+            .read_utf8(Path::new(path))
+            .await
+            .unwrap();
+        assert_eq!(saved, stdout);
+        assert_eq!(saved.lines().nth(4999), Some("line 5000"));
+    }
+
+    #[tokio::test]
+    async fn test_format_output_full_output() {
+        let infra = Arc::new(MockInfrastructure::new());
+        let stdout: String = (1..=10_000).map(|i| format!("line {i}\n")).collect();
+        let output = CommandOutput {
+            stdout: stdout.clone(),
+            stderr: "".to_string(),
+            command: "cargo test".into(),
+            exit_code: Some(0),
+            timed_out: None,
+        };
+
+        let actual = format_output(&infra, output, None, None, false, false, None)
+            .await
+            .unwrap();
+
+        assert!(actual.ends_with(&format!("<stdout>\n{stdout}\n</stdout>")));
+        assert!(!actual.contains("truncated"));
+    }
 }
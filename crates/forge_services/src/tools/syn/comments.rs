@@ -0,0 +1,147 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::Path;
+
+use tree_sitter::{Node, Parser};
+
+use super::validate::extension;
+
+/// Removes the comments from source code, keeping the line breaks inside them
+/// so that every line keeps its number. Lines that held nothing but comments
+/// are left blank.
+///
+/// # Returns
+/// * `Some(String)` - The content without comments
+/// * `None` - If the language of the file isn't supported
+pub fn strip_comments(path: impl AsRef<Path>, content: &str) -> Option<String> {
+    let ext = path.as_ref().extension()?.to_str()?;
+    let language = extension(ext)?;
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).ok()?;
+    let tree = parser.parse(content, None)?;
+
+    let mut comments = Vec::new();
+    collect_comments(tree.root_node(), &mut comments);
+
+    let mut stripped = String::with_capacity(content.len());
+    // Lines whose comments left trailing whitespace behind
+    let mut touched = BTreeSet::new();
+    let mut last = 0;
+    for (bytes, rows) in comments {
+        stripped.push_str(&content[last..bytes.start]);
+        stripped.extend(content[bytes.clone()].chars().filter(|c| *c == '\n'));
+        touched.extend(rows);
+        last = bytes.end;
+    }
+    stripped.push_str(&content[last..]);
+
+    Some(
+        stripped
+            .split_inclusive('\n')
+            .enumerate()
+            .map(|(row, line)| {
+                if !touched.contains(&row) {
+                    return line.to_string();
+                }
+                let code = line.trim_end();
+                let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                format!("{code}{ending}")
+            })
+            .collect(),
+    )
+}
+
+/// Collects the byte range and rows of every comment below `node`. Parts of
+/// the source that couldn't be parsed are left alone, since what looks like a
+/// comment there may be something else.
+fn collect_comments(node: Node, comments: &mut Vec<(Range<usize>, Range<usize>)>) {
+    if node.is_error() {
+        return;
+    }
+    if node.kind().ends_with("comment") {
+        let rows = node.start_position().row..node.end_position().row + 1;
+        comments.push((node.byte_range(), rows));
+        return;
+    }
+    for i in 0..node.child_count() {
+        if let Some(child) = node.child(i) {
+            collect_comments(child, comments);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_strip_comments_rust() {
+        let fixture = r#"// Copyright (c) Example Corp.
+// Licensed under MIT.
+
+/// Adds two numbers
+fn add(a: i32, b: i32) -> i32 {
+    /* the sum,
+       which can overflow */
+    a + b // done
+}
+
+fn url() -> &'static str {
+    "https://example.com/* not a comment */"
+}
+"#;
+
+        let actual = strip_comments("lib.rs", fixture).unwrap();
+
+        let expected = r#"
+
+
+
+fn add(a: i32, b: i32) -> i32 {
+
+
+    a + b
+}
+
+fn url() -> &'static str {
+    "https://example.com/* not a comment */"
+}
+"#;
+        assert_eq!(actual, expected);
+        assert_eq!(actual.lines().count(), fixture.lines().count());
+    }
+
+    #[test]
+    fn test_strip_comments_python() {
+        let fixture = r##"#!/usr/bin/env python3
+# License: MIT
+
+def greet(name):
+    """Docstrings are strings, not comments."""
+    message = "# not a comment"  # but this is
+    return message + name
+"##;
+
+        let actual = strip_comments("greet.py", fixture).unwrap();
+
+        let expected = r##"
+
+
+def greet(name):
+    """Docstrings are strings, not comments."""
+    message = "# not a comment"
+    return message + name
+"##;
+        assert_eq!(actual, expected);
+        assert_eq!(actual.lines().count(), fixture.lines().count());
+    }
+
+    #[test]
+    fn test_strip_comments_unsupported() {
+        assert_eq!(strip_comments("notes.txt", "# heading"), None);
+        assert_eq!(strip_comments("Makefile", "# target"), None);
+    }
+}
@@ -1,3 +1,5 @@
+mod comments;
 mod validate;
 
+pub use comments::strip_comments;
 pub use validate::validate;
@@ -1,7 +1,7 @@
 use std::path::Path;
 
 use thiserror::Error;
-use tree_sitter::{Language, LanguageError, Parser};
+use tree_sitter::{Language, LanguageError, Node, Parser};
 
 /// Represents possible errors that can occur during syntax validation
 #[derive(Debug, Error, PartialEq)]
@@ -14,11 +14,15 @@ pub enum Error {
     Language(#[from] LanguageError),
     /// Failed to parse the content
     #[error(
-        "Syntax error found in file with extension {extension}. Hint: Please retry in raw mode without HTML-encoding angle brackets."
+        "Syntax error found in file with extension {extension} at line {line}, column {column}. Hint: Please retry in raw mode without HTML-encoding angle brackets."
     )]
     Parse {
         file_path: String,
         extension: String,
+        /// Line of the first error, starting at 1
+        line: usize,
+        /// Byte offset of the first error within its line, starting at 1
+        column: usize,
     },
 }
 
@@ -95,17 +99,39 @@ pub fn validate(path: impl AsRef<Path>, content: &str) -> Option<Error> {
         return Some(Error::Parse {
             file_path: path.display().to_string(),
             extension: ext.to_string(),
+            line: 1,
+            column: 1,
         });
     };
 
     // Find syntax errors in the tree
     let root_node = tree.root_node();
-    (root_node.has_error() || root_node.is_error()).then(|| Error::Parse {
+    if !root_node.has_error() && !root_node.is_error() {
+        return None;
+    }
+    let position = first_error(root_node).unwrap_or(root_node).start_position();
+    Some(Error::Parse {
         file_path: path.display().to_string(),
         extension: ext.to_string(),
+        line: position.row + 1,
+        column: position.column + 1,
     })
 }
 
+/// Finds the first node, in the order of the source, that couldn't be parsed
+/// or that the parser had to assume, such as a missing semicolon
+fn first_error(node: Node) -> Option<Node> {
+    if node.is_error() || node.is_missing() {
+        return Some(node);
+    }
+    if !node.has_error() {
+        return None;
+    }
+    (0..node.child_count())
+        .filter_map(|i| node.child(i))
+        .find_map(first_error)
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -184,7 +210,23 @@ mod tests {
         let error = validate(&path, "fn main() { let x = ").unwrap();
         assert_eq!(
             error.to_string(),
-            "Syntax error found in file with extension rs. Hint: Please retry in raw mode without HTML-encoding angle brackets."
+            "Syntax error found in file with extension rs at line 1, column 1. Hint: Please retry in raw mode without HTML-encoding angle brackets."
         );
     }
+
+    #[test]
+    fn test_error_location() {
+        let path = PathBuf::from("test.rs");
+        let fixture = "fn main() {\n    let x = 1\n    let y = 2;\n}";
+
+        let actual = validate(&path, fixture);
+
+        let expected = Some(Error::Parse {
+            file_path: "test.rs".to_string(),
+            extension: "rs".to_string(),
+            line: 2,
+            column: 14,
+        });
+        assert_eq!(actual, expected);
+    }
 }
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use forge_domain::{
+    ExecutableTool, NamedTool, ToolCallContext, ToolDescription, ToolName, ToolOutput,
+};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Use this tool to think through a problem before acting, for example to plan
+/// the next steps, weigh alternatives or reflect on the result of a previous
+/// tool call. It doesn't change anything and only records the thought. Only a
+/// few consecutive thoughts are allowed, after which you must take an action
+/// with one of the other tools.
+#[derive(Debug, Default, ToolDescription)]
+pub struct Think;
+
+impl NamedTool for Think {
+    fn tool_name() -> ToolName {
+        ToolName::think()
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ThinkInput {
+    /// The thought to record
+    pub thought: String,
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for Think {
+    type Input = ThinkInput;
+
+    async fn call(&self, _context: ToolCallContext, input: Self::Input) -> Result<ToolOutput> {
+        Ok(ToolOutput::text(input.thought))
+    }
+
+    fn is_read_only(&self, _input: &Self::Input) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::utils::ToolContentExtension;
+
+    #[tokio::test]
+    async fn test_think() {
+        let fixture = ThinkInput { thought: "Read the config before patching it".to_string() };
+
+        let actual = Think
+            .call(ToolCallContext::default(), fixture)
+            .await
+            .unwrap();
+
+        assert_eq!(actual.into_string(), "Read the config before patching it");
+    }
+}
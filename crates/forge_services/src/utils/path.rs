@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use anyhow::bail;
 
@@ -18,6 +18,77 @@ pub fn assert_absolute_path(path: &Path) -> anyhow::Result<()> {
     }
 }
 
+/// Resolves `.` and `..` components of a path without touching the file
+/// system, so that paths to files that don't exist yet can be compared
+pub fn normalize_path(path: &Path) -> PathBuf {
+    path.components()
+        .fold(PathBuf::new(), |mut normalized, component| {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            }
+            normalized
+        })
+}
+
+/// Resolves symbolic links and `.` and `..` components of a path. The part
+/// of the path that doesn't exist yet is resolved lexically, so that paths to
+/// files about to be created can be compared too.
+pub fn resolve_path(path: &Path) -> PathBuf {
+    let normalized = normalize_path(path);
+    normalized
+        .ancestors()
+        .find_map(|ancestor| {
+            let resolved = ancestor.canonicalize().ok()?;
+            let rest = normalized.strip_prefix(ancestor).ok()?;
+            Some(resolved.join(rest))
+        })
+        .unwrap_or(normalized)
+}
+
+/// Whether both paths point to the same file, either through symbolic links
+/// or as hard links of one another
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    if resolve_path(a) == resolve_path(b) {
+        return true;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let (Ok(a), Ok(b)) = (a.metadata(), b.metadata()) {
+            return a.dev() == b.dev() && a.ino() == b.ino();
+        }
+    }
+
+    false
+}
+
+/// Ensures that the given path doesn't point outside of `root`, following
+/// symbolic links
+///
+/// # Arguments
+/// * `path` - The absolute path to validate
+/// * `root` - The directory the path must be inside of
+///
+/// # Returns
+/// * `Ok(())` if the path is inside `root`
+/// * `Err` with an error message otherwise
+pub fn assert_path_within(path: &Path, root: &Path) -> anyhow::Result<()> {
+    if resolve_path(path).starts_with(resolve_path(root)) {
+        Ok(())
+    } else {
+        bail!(
+            "Path {} is outside of the project directory {}",
+            path.display(),
+            root.display()
+        )
+    }
+}
+
 /// Formats a path for display, converting absolute paths to relative when
 /// possible
 ///
@@ -103,6 +174,50 @@ mod tests {
         assert!(assert_absolute_path(path).is_err());
     }
 
+    #[test]
+    fn test_normalize_path() {
+        let actual = normalize_path(Path::new("/home/user/./projects/../docs/file.md"));
+        assert_eq!(actual, PathBuf::from("/home/user/docs/file.md"));
+    }
+
+    #[test]
+    fn test_path_within_root() {
+        let root = Path::new("/home/user/projects");
+        assert!(assert_path_within(Path::new("/home/user/projects/a/b.txt"), root).is_ok());
+        assert!(assert_path_within(Path::new("/home/user/projects/../b.txt"), root).is_err());
+        assert!(assert_path_within(Path::new("/home/user/projects-old/b.txt"), root).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_within_root_through_symlink() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let link = root.path().join("link");
+        std::os::unix::fs::symlink(outside.path(), &link).unwrap();
+
+        let actual = assert_path_within(&link.join("file.txt"), root.path());
+
+        assert!(actual.is_err());
+        assert!(assert_path_within(&root.path().join("new/file.txt"), root.path()).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_same_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        std::fs::write(&file, "content").unwrap();
+        let symlink = dir.path().join("symlink.txt");
+        std::os::unix::fs::symlink(&file, &symlink).unwrap();
+        let hard_link = dir.path().join("hard_link.txt");
+        std::fs::hard_link(&file, &hard_link).unwrap();
+
+        assert!(is_same_file(&file, &symlink));
+        assert!(is_same_file(&file, &hard_link));
+        assert!(!is_same_file(&file, &dir.path().join("other.txt")));
+    }
+
     #[test]
     fn test_cwd() {
         let cwd = Path::new("/home/user/projects");
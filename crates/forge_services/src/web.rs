@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Response, StatusCode, Url};
+use tracing::warn;
+
+use crate::tools::fetch_cache::{CachedResponse, FetchCache, DEFAULT_MAX_AGE};
+
+/// Redirects followed before the request fails
+pub(crate) const MAX_REDIRECTS: usize = 5;
+
+/// Time allowed to connect to the server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time allowed between two reads of the response before the download fails
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Downloads from the web, for the fetch tool and for URLs read in place of
+/// files, so both share the same timeouts, robots.txt check and cache
+#[derive(Debug, Clone)]
+pub struct WebClient {
+    client: Client,
+    cache: FetchCache,
+}
+
+impl WebClient {
+    /// Creates a client that caches responses in `cache_dir`
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self { client: client(), cache: FetchCache::new(cache_dir) }
+    }
+
+    pub(crate) fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub(crate) fn cache(&self) -> &FetchCache {
+        &self.cache
+    }
+
+    pub(crate) async fn check_robots_txt(&self, url: &Url) -> Result<()> {
+        let robots_url = format!("{}://{}/robots.txt", url.scheme(), url.authority());
+        let robots_response = self.client.get(&robots_url).send().await;
+
+        if let Ok(robots) = robots_response {
+            if robots.status().is_success() {
+                let robots_content = robots.text().await.unwrap_or_default();
+                let path = url.path();
+                for line in robots_content.lines() {
+                    if let Some(disallowed) = line.strip_prefix("Disallow: ") {
+                        let disallowed = disallowed.trim();
+                        let disallowed = if !disallowed.starts_with('/') {
+                            format!("/{disallowed}")
+                        } else {
+                            disallowed.to_string()
+                        };
+                        let path = if !path.starts_with('/') {
+                            format!("/{path}")
+                        } else {
+                            path.to_string()
+                        };
+                        if path.starts_with(&disallowed) {
+                            return Err(anyhow!(
+                                "URL {} cannot be fetched due to robots.txt restrictions",
+                                url
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Downloads the file at `url` the way the fetch tool runs a plain GET:
+    /// a cached copy is reused while it's current, robots.txt is respected and
+    /// the download fails once it grows past `max_bytes`. Only text is cached.
+    pub async fn get(&self, url: &Url, max_bytes: usize) -> Result<Vec<u8>> {
+        let cached = self.cache.load(url).await;
+        if let Some(cached) = cached
+            .as_ref()
+            .filter(|cached| !cached.has_validators() && cached.age() < DEFAULT_MAX_AGE)
+        {
+            return Ok(cached.body.clone().into_bytes());
+        }
+
+        self.check_robots_txt(url).await?;
+
+        let mut request = self.client.get(url.clone());
+        if let Some(cached) = &cached {
+            request = request.headers(cached.conditional_headers());
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch {url}"))?;
+        if let (StatusCode::NOT_MODIFIED, Some(cached)) = (response.status(), cached) {
+            return Ok(cached.body.into_bytes());
+        }
+        let response = response
+            .error_for_status()
+            .with_context(|| format!("Failed to fetch {url}"))?;
+
+        let headers = response.headers().clone();
+        let content_type = headers
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let body = read_body(url, response, max_bytes).await?;
+
+        if let Ok(text) = std::str::from_utf8(&body) {
+            let cached = CachedResponse::new(url, &content_type, text, &headers);
+            if let Err(error) = self.cache.store(&cached).await {
+                warn!(url = %url, error = ?error, "Failed to cache fetched page");
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// Builds the HTTP client. On redirects to another host or port reqwest drops
+/// the Authorization header, so tokens only reach the origin they were meant
+/// for.
+fn client() -> Client {
+    Client::builder()
+        .redirect(Policy::limited(MAX_REDIRECTS))
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .build()
+        .expect("Failed to initialize the HTTP client")
+}
+
+/// Downloads the body of the response, giving up as soon as it grows past
+/// `max_bytes` rather than buffering all of it
+pub(crate) async fn read_body(
+    url: &Url,
+    mut response: Response,
+    max_bytes: usize,
+) -> Result<Vec<u8>> {
+    let too_large =
+        || anyhow!("Response from {url} is larger than {max_bytes} bytes and was not downloaded");
+    if response
+        .content_length()
+        .is_some_and(|length| length > max_bytes as u64)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| anyhow!("Failed to read response content from {}: {}", url, e))?
+    {
+        if body.len() + chunk.len() > max_bytes {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use forge_snaps::{SnapshotCompression, SnapshotService};
+use tempfile::TempDir;
+
+/// Builds about 1 MiB of source-like text
+fn text_file() -> String {
+    let mut content = String::with_capacity(1 << 20);
+    let mut line = 0;
+    while content.len() < 1 << 20 {
+        content.push_str(&format!(
+            "    let value_{line} = compute(\"entry {line}\", {}); // step {line}\n",
+            line % 97
+        ));
+        line += 1;
+    }
+    content
+}
+
+fn snapshot_creation(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let file = workspace.path().join("large.rs");
+    std::fs::write(&file, text_file()).unwrap();
+
+    let mut group = c.benchmark_group("create_snapshot_1mib");
+    for (name, compression) in [
+        ("none", SnapshotCompression::None),
+        ("zstd", SnapshotCompression::Zstd),
+        ("gzip", SnapshotCompression::Gzip),
+    ] {
+        // Disk usage doesn't vary between runs, so it's reported once
+        let snapshots = TempDir::new().unwrap();
+        let snapshot = runtime
+            .block_on(
+                SnapshotService::new(snapshots.path().to_path_buf())
+                    .compression(compression)
+                    .create_snapshot(file.clone(), None),
+            )
+            .unwrap();
+        println!(
+            "{name}: {} bytes on disk for {} bytes of content",
+            snapshot.compressed_size.unwrap_or(snapshot.size),
+            snapshot.size
+        );
+
+        // Every run stores into an empty directory, otherwise the payload
+        // would be deduplicated and only the metadata written
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter_batched(
+                || TempDir::new().unwrap(),
+                |snapshots| {
+                    let file = file.clone();
+                    async move {
+                        SnapshotService::new(snapshots.path().to_path_buf())
+                            .compression(compression)
+                            .create_snapshot(file, None)
+                            .await
+                            .unwrap();
+                        snapshots
+                    }
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, snapshot_creation);
+criterion_main!(benches);
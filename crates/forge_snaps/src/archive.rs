@@ -0,0 +1,677 @@
+//! Archives for moving snapshots between machines or backing them up.
+//!
+//! [`SnapshotService::export_archive`] writes the snapshots of a workspace to
+//! a gzipped tar archive (`.tar.gz`) holding:
+//! - `snapshots/<id>.json`, the metadata of every snapshot, with its path
+//!   relative to the workspace root
+//! - `objects/<hash>`, the content of the snapshots, once per blake3 hash
+//!
+//! [`SnapshotService::export_snapshot`] writes a single snapshot to a zstd
+//! compressed tar archive (`.tar.zst`) holding:
+//! - `snapshot.json`, the metadata of the snapshot
+//! - `content`, the content of the snapshot
+//!
+//! Metadata is the JSON of a [`Snapshot`]. Content is archived decompressed and
+//! the metadata records no compression, as importing compresses the content
+//! again as configured for the target.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use forge_fs::ForgeFS;
+
+use crate::snapshot::{
+    hash_content, hash_path, is_valid_hash, read_object, resolve_path, Snapshot,
+    SnapshotCompression, OBJECTS_DIR,
+};
+use crate::tag::snapshot_timestamp;
+use crate::tree::TREES_DIR;
+use crate::{RestorationError, SnapshotSelector, SnapshotService};
+
+/// Directory of an archive holding the snapshot metadata, payloads are kept
+/// under [`OBJECTS_DIR`] as in the snapshots directory
+const ARCHIVE_SNAPSHOTS_DIR: &str = "snapshots";
+
+/// Entry of a single snapshot archive holding the snapshot metadata
+const SNAPSHOT_ENTRY: &str = "snapshot.json";
+
+/// Entry of a single snapshot archive holding the snapshot content
+const CONTENT_ENTRY: &str = "content";
+
+/// How imported snapshots are reconciled with the snapshots already stored
+/// for the same file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStrategy {
+    /// Adds the imported snapshots to the existing history of the file
+    Merge,
+    /// Leaves files that already have snapshots untouched
+    Skip,
+    /// Replaces the existing history of the file with the imported snapshots
+    Overwrite,
+}
+
+/// Outcome of exporting snapshots to an archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportReport {
+    /// Number of snapshots written to the archive
+    pub snapshots: usize,
+
+    /// Number of distinct payloads written to the archive
+    pub objects: usize,
+
+    /// Files outside the workspace root, their snapshots are left out
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Outcome of importing snapshots from an archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Number of snapshots added to the snapshots directory
+    pub imported: usize,
+
+    /// Number of snapshots left out because they were already present or
+    /// their file already had snapshots
+    pub skipped: usize,
+}
+
+/// Joins `path` onto `base`, refusing paths that could point outside of it
+fn confine(base: &Path, path: &Path) -> Result<PathBuf> {
+    let is_relative = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_relative {
+        bail!(
+            "Archive entry {} escapes {}",
+            path.display(),
+            base.display()
+        );
+    }
+
+    Ok(base.join(path))
+}
+
+fn append<W: Write>(builder: &mut tar::Builder<W>, path: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, path, data)
+        .with_context(|| format!("Failed to add {path} to snapshot archive"))
+}
+
+/// Reads every file of a tar archive
+fn read_entries<R: Read>(archive: R) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut archive = tar::Archive::new(archive);
+    let mut entries = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path()?.into_owned();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        entries.push((path, content));
+    }
+
+    Ok(entries)
+}
+
+/// Writes `archive` to `dest`, creating its parent directories
+async fn write_archive(dest: &Path, archive: Vec<u8>) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        ForgeFS::create_dir_all(parent).await?;
+    }
+    Ok(ForgeFS::write(dest, archive).await?)
+}
+
+impl SnapshotService {
+    /// Loads the metadata of every file snapshot, skipping unreadable ones
+    async fn all_snapshots(&self) -> Result<Vec<Snapshot>> {
+        let mut snapshots = Vec::new();
+        if !ForgeFS::exists(&self.snapshots_directory) {
+            return Ok(snapshots);
+        }
+
+        let mut dirs = ForgeFS::read_dir(&self.snapshots_directory).await?;
+        while let Some(entry) = dirs.next_entry().await? {
+            if entry.file_name() == OBJECTS_DIR
+                || entry.file_name() == TREES_DIR
+                || !entry.path().is_dir()
+            {
+                continue;
+            }
+
+            for file in Self::snapshot_files(&entry.path()).await? {
+                if let Ok(snapshot) = Snapshot::load(&file).await {
+                    snapshots.push(snapshot);
+                }
+            }
+        }
+
+        snapshots.sort_by_key(|snapshot| snapshot.timestamp);
+        Ok(snapshots)
+    }
+
+    /// Writes the snapshots of files under `root`, taken at or after `since`
+    /// when given, to a gzipped tar archive at `dest`. Paths are stored
+    /// relative to `root` so the archive can be imported into another
+    /// workspace.
+    pub async fn export_archive(
+        &self,
+        dest: &Path,
+        root: &Path,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<ExportReport> {
+        let root = root.canonicalize()?;
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let mut objects = HashSet::new();
+        let mut report = ExportReport::default();
+
+        for mut snapshot in self.all_snapshots().await? {
+            let taken_at = DateTime::<Utc>::from(UNIX_EPOCH + snapshot.timestamp);
+            if since.is_some_and(|since| taken_at < since) {
+                continue;
+            }
+
+            let path = PathBuf::from(&snapshot.path);
+            let Ok(relative) = path.strip_prefix(&root) else {
+                if !report.skipped.contains(&path) {
+                    report.skipped.push(path);
+                }
+                continue;
+            };
+
+            if objects.insert(snapshot.hash.clone()) {
+                let content = read_object(&self.snapshots_directory, &snapshot.hash).await?;
+                append(
+                    &mut builder,
+                    &format!("{OBJECTS_DIR}/{}", snapshot.hash),
+                    &content,
+                )?;
+            }
+
+            // Payloads are archived decompressed, importing compresses them
+            // again as configured for the target
+            snapshot.path = relative.display().to_string();
+            snapshot.compression = SnapshotCompression::None;
+            snapshot.compressed_size = None;
+            append(
+                &mut builder,
+                &format!("{ARCHIVE_SNAPSHOTS_DIR}/{}.json", snapshot.id),
+                &serde_json::to_vec(&snapshot)?,
+            )?;
+            report.snapshots += 1;
+        }
+
+        write_archive(dest, builder.into_inner()?.finish()?).await?;
+
+        report.objects = objects.len();
+        Ok(report)
+    }
+
+    /// Imports the snapshots of an archive written by
+    /// [`SnapshotService::export_archive`], remapping their paths onto
+    /// `root`. The whole archive is validated before anything is written.
+    pub async fn import_archive(
+        &self,
+        src: &Path,
+        root: &Path,
+        strategy: ImportStrategy,
+    ) -> Result<ImportReport> {
+        let root = root.canonicalize()?;
+        let mut files: BTreeMap<String, Vec<Snapshot>> = BTreeMap::new();
+        let mut objects = HashMap::new();
+
+        let archive = ForgeFS::read(src).await?;
+        for (path, content) in read_entries(GzDecoder::new(archive.as_slice()))? {
+            confine(&self.snapshots_directory, &path)?;
+
+            if path.starts_with(ARCHIVE_SNAPSHOTS_DIR) {
+                let mut snapshot: Snapshot =
+                    serde_json::from_slice(&content).with_context(|| {
+                        format!("Failed to parse snapshot metadata {}", path.display())
+                    })?;
+                if !is_valid_hash(&snapshot.hash) {
+                    bail!(
+                        "Snapshot {} has an invalid hash {:?}",
+                        snapshot.id,
+                        snapshot.hash
+                    );
+                }
+                snapshot.path = confine(&root, Path::new(&snapshot.path))?
+                    .display()
+                    .to_string();
+                files
+                    .entry(snapshot.path.clone())
+                    .or_default()
+                    .push(snapshot);
+            } else if path.starts_with(OBJECTS_DIR) {
+                let hash = hash_content(&content);
+                if path != Path::new(OBJECTS_DIR).join(&hash) {
+                    bail!("Payload {} doesn't match its content", path.display());
+                }
+                objects.insert(hash, content);
+            } else {
+                bail!("Unexpected entry {} in snapshot archive", path.display());
+            }
+        }
+
+        // Payloads missing from the archive must already be in the store, and
+        // intact, as the imported snapshots will refer to them
+        for snapshot in files.values().flatten() {
+            if objects.contains_key(&snapshot.hash) {
+                continue;
+            }
+            let content = read_object(&self.snapshots_directory, &snapshot.hash)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Payload of snapshot {} is missing from the archive",
+                        snapshot.id
+                    )
+                })?;
+            let actual = hash_content(&content);
+            if actual != snapshot.hash {
+                return Err(RestorationError::ChecksumMismatch {
+                    snapshot: src.to_path_buf(),
+                    expected: snapshot.hash.clone(),
+                    actual,
+                }
+                .into());
+            }
+            objects.insert(actual, content);
+        }
+
+        let mut report = ImportReport::default();
+        for (path, snapshots) in files {
+            let existing = self.snapshots_directory.join(hash_path(&path));
+            let existing = if ForgeFS::exists(&existing) {
+                Self::snapshot_files(&existing).await?
+            } else {
+                Vec::new()
+            };
+
+            match strategy {
+                ImportStrategy::Skip if !existing.is_empty() => {
+                    report.skipped += snapshots.len();
+                    continue;
+                }
+                ImportStrategy::Overwrite => {
+                    for file in existing.iter() {
+                        self.remove_snapshot_file(file).await?;
+                    }
+                }
+                ImportStrategy::Merge | ImportStrategy::Skip => {}
+            }
+
+            for mut snapshot in snapshots {
+                let metadata = snapshot.snapshot_path(Some(self.snapshots_directory.clone()));
+                if ForgeFS::exists(&metadata) {
+                    report.skipped += 1;
+                    continue;
+                }
+
+                let content = &objects[&snapshot.hash];
+                snapshot
+                    .save(&self.snapshots_directory, content, self.compression)
+                    .await?;
+                report.imported += 1;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Writes the snapshot of `path` taken at `timestamp` to a zstd
+    /// compressed tar archive at `dest`
+    pub async fn export_snapshot(&self, path: PathBuf, timestamp: &str, dest: &Path) -> Result<()> {
+        let selector = SnapshotSelector::Timestamp(timestamp.to_string());
+        let snapshot_path = self.select_snapshot(&path, &selector).await?;
+        let mut snapshot = Snapshot::load(&snapshot_path).await?;
+        let content = read_object(&self.snapshots_directory, &snapshot.hash).await?;
+        snapshot.compression = SnapshotCompression::None;
+        snapshot.compressed_size = None;
+
+        let encoder = zstd::Encoder::new(Vec::new(), zstd::DEFAULT_COMPRESSION_LEVEL)?;
+        let mut builder = tar::Builder::new(encoder);
+        append(
+            &mut builder,
+            SNAPSHOT_ENTRY,
+            &serde_json::to_vec(&snapshot)?,
+        )?;
+        append(&mut builder, CONTENT_ENTRY, &content)?;
+        write_archive(dest, builder.into_inner()?.finish()?).await
+    }
+
+    /// Imports the snapshot of an archive written by
+    /// [`SnapshotService::export_snapshot`] as a snapshot of `path`, keeping
+    /// the time it was taken at. Fails with
+    /// [`RestorationError::ChecksumMismatch`] if the content doesn't match
+    /// its hash, or if `path` already has a snapshot taken at that time.
+    /// Returns the timestamp of the imported snapshot.
+    pub async fn import_snapshot(&self, path: PathBuf, archive: &Path) -> Result<String> {
+        let data = ForgeFS::read(archive).await?;
+        let mut snapshot = None;
+        let mut content = None;
+        for (entry, data) in read_entries(zstd::Decoder::new(data.as_slice())?)? {
+            if entry == Path::new(SNAPSHOT_ENTRY) {
+                let metadata: Snapshot = serde_json::from_slice(&data).with_context(|| {
+                    format!("Failed to parse snapshot metadata in {}", archive.display())
+                })?;
+                snapshot = Some(metadata);
+            } else if entry == Path::new(CONTENT_ENTRY) {
+                content = Some(data);
+            } else {
+                bail!("Unexpected entry {} in snapshot archive", entry.display());
+            }
+        }
+        let mut snapshot = snapshot
+            .with_context(|| format!("Snapshot metadata is missing from {}", archive.display()))?;
+        let content = content
+            .with_context(|| format!("Snapshot content is missing from {}", archive.display()))?;
+
+        let actual = hash_content(&content);
+        if actual != snapshot.hash {
+            return Err(RestorationError::ChecksumMismatch {
+                snapshot: archive.to_path_buf(),
+                expected: snapshot.hash,
+                actual,
+            }
+            .into());
+        }
+
+        snapshot.path = resolve_path(&path)?.display().to_string();
+        let metadata = snapshot.snapshot_path(Some(self.snapshots_directory.clone()));
+        let timestamp = snapshot_timestamp(&metadata)
+            .with_context(|| format!("Invalid snapshot path {}", metadata.display()))?;
+        if ForgeFS::exists(&metadata) {
+            bail!("{path:?} already has a snapshot taken at {timestamp}");
+        }
+
+        snapshot
+            .save(&self.snapshots_directory, &content, self.compression)
+            .await?;
+        Ok(timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    struct Fixture {
+        temp_dir: TempDir,
+        root: PathBuf,
+        service: SnapshotService,
+    }
+
+    impl Fixture {
+        async fn new(name: &str) -> Result<Self> {
+            let temp_dir = TempDir::new()?;
+            let root = temp_dir.path().join(name);
+            ForgeFS::create_dir_all(&root).await?;
+            let root = root.canonicalize()?;
+            let service = SnapshotService::new(temp_dir.path().join("snapshots"));
+            Ok(Self { temp_dir, root, service })
+        }
+
+        async fn snapshot(&self, path: &str, content: &str) -> Result<Snapshot> {
+            let path = self.root.join(path);
+            ForgeFS::write(&path, content).await?;
+            self.service.create_snapshot(path, None).await
+        }
+
+        fn archive(&self) -> PathBuf {
+            self.temp_dir.path().join("export.tar.gz")
+        }
+
+        async fn contents(&self, path: &str) -> Result<Vec<String>> {
+            let mut contents = Vec::new();
+            for snapshot in self.service.list_snapshots(self.root.join(path)).await? {
+                let content =
+                    read_object(&self.service.snapshots_directory, &snapshot.hash).await?;
+                contents.push(String::from_utf8(content)?);
+            }
+            Ok(contents)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() -> Result<()> {
+        // Arrange
+        let source = Fixture::new("source").await?;
+        source.snapshot("main.rs", "fn main() {}").await?;
+        source.snapshot("main.rs", "fn main() { todo!() }").await?;
+        let target = Fixture::new("target").await?;
+        ForgeFS::write(target.root.join("main.rs"), "").await?;
+
+        // Act
+        let exported = source
+            .service
+            .export_archive(&source.archive(), &source.root, None)
+            .await?;
+        let imported = target
+            .service
+            .import_archive(&source.archive(), &target.root, ImportStrategy::Merge)
+            .await?;
+
+        // Assert
+        assert_eq!(
+            exported,
+            ExportReport { snapshots: 2, objects: 2, skipped: vec![] }
+        );
+        assert_eq!(imported, ImportReport { imported: 2, skipped: 0 });
+        assert_eq!(
+            target.contents("main.rs").await?,
+            vec!["fn main() { todo!() }", "fn main() {}"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_import_recompresses_payloads() -> Result<()> {
+        // Arrange
+        let mut source = Fixture::new("source").await?;
+        source.service = SnapshotService::new(source.service.snapshots_directory.clone())
+            .compression(SnapshotCompression::Zstd);
+        source.snapshot("main.rs", "fn main() {}").await?;
+        let mut target = Fixture::new("target").await?;
+        target.service = SnapshotService::new(target.service.snapshots_directory.clone())
+            .compression(SnapshotCompression::Gzip);
+
+        // Act
+        source
+            .service
+            .export_archive(&source.archive(), &source.root, None)
+            .await?;
+        target
+            .service
+            .import_archive(&source.archive(), &target.root, ImportStrategy::Merge)
+            .await?;
+
+        // Assert
+        let imported = target
+            .service
+            .list_snapshots(target.root.join("main.rs"))
+            .await?;
+        assert_eq!(imported[0].compression, SnapshotCompression::Gzip);
+        assert_eq!(target.contents("main.rs").await?, vec!["fn main() {}"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_since_leaves_out_older_snapshots() -> Result<()> {
+        let fixture = Fixture::new("source").await?;
+        fixture.snapshot("main.rs", "old").await?;
+        let recent = fixture.snapshot("main.rs", "new").await?;
+        let since = DateTime::<Utc>::from(UNIX_EPOCH + recent.timestamp);
+
+        let actual = fixture
+            .service
+            .export_archive(&fixture.archive(), &fixture.root, Some(since))
+            .await?;
+
+        let expected = ExportReport { snapshots: 1, objects: 1, skipped: vec![] };
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    /// Writes a single snapshot archive holding `entries`
+    fn write_snapshot_archive(dest: &Path, entries: &[(&str, &[u8])]) -> Result<()> {
+        let mut builder = tar::Builder::new(zstd::Encoder::new(Vec::new(), 0)?);
+        for (path, data) in entries {
+            append(&mut builder, path, data)?;
+        }
+        std::fs::write(dest, builder.into_inner()?.finish()?)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_import_snapshot_round_trip() -> Result<()> {
+        // Arrange
+        let source = Fixture::new("source").await?;
+        let snapshot = source.snapshot("main.rs", "fn main() {}").await?;
+        source.snapshot("main.rs", "fn main() { todo!() }").await?;
+        let timestamp = snapshot_timestamp(&snapshot.snapshot_path(None)).unwrap();
+        let archive = source.temp_dir.path().join("main.tar.zst");
+        let target = Fixture::new("target").await?;
+
+        // Act
+        source
+            .service
+            .export_snapshot(source.root.join("main.rs"), &timestamp, &archive)
+            .await?;
+        let actual = target
+            .service
+            .import_snapshot(target.root.join("main.rs"), &archive)
+            .await?;
+
+        // Assert
+        assert_eq!(actual, timestamp);
+        assert_eq!(target.contents("main.rs").await?, vec!["fn main() {}"]);
+        let imported = target
+            .service
+            .list_snapshots(target.root.join("main.rs"))
+            .await?;
+        assert_eq!(imported[0].id, snapshot.id);
+        assert_eq!(
+            imported[0].path,
+            target.root.join("main.rs").display().to_string()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_snapshot_twice_fails() -> Result<()> {
+        let fixture = Fixture::new("source").await?;
+        let snapshot = fixture.snapshot("main.rs", "fn main() {}").await?;
+        let timestamp = snapshot_timestamp(&snapshot.snapshot_path(None)).unwrap();
+        let archive = fixture.temp_dir.path().join("main.tar.zst");
+        let path = fixture.root.join("main.rs");
+        fixture
+            .service
+            .export_snapshot(path.clone(), &timestamp, &archive)
+            .await?;
+
+        let actual = fixture.service.import_snapshot(path, &archive).await;
+
+        assert!(actual
+            .unwrap_err()
+            .to_string()
+            .contains("already has a snapshot"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_snapshot_rejects_corrupted_content() -> Result<()> {
+        let source = Fixture::new("source").await?;
+        let snapshot = source.snapshot("main.rs", "fn main() {}").await?;
+        let archive = source.temp_dir.path().join("main.tar.zst");
+        write_snapshot_archive(
+            &archive,
+            &[
+                (SNAPSHOT_ENTRY, &serde_json::to_vec(&snapshot)?),
+                (CONTENT_ENTRY, b"fn main() { corrupted() }"),
+            ],
+        )?;
+        let target = Fixture::new("target").await?;
+
+        let actual = target
+            .service
+            .import_snapshot(target.root.join("main.rs"), &archive)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            actual.downcast_ref::<RestorationError>(),
+            Some(RestorationError::ChecksumMismatch { .. })
+        ));
+        assert!(target
+            .contents("main.rs")
+            .await
+            .unwrap_or_default()
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_entries_escaping_snapshot_dir() -> Result<()> {
+        let fixture = Fixture::new("target").await?;
+        let content = b"malicious";
+        let mut header = tar::Header::new_gnu();
+        // Written directly since the tar builder refuses `..` in paths
+        header.as_old_mut().name[..10].copy_from_slice(b"../escaped");
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        builder.append(&header, &content[..])?;
+        ForgeFS::write(fixture.archive(), builder.into_inner()?.finish()?).await?;
+
+        let actual = fixture
+            .service
+            .import_archive(&fixture.archive(), &fixture.root, ImportStrategy::Merge)
+            .await;
+
+        assert!(actual.unwrap_err().to_string().contains("escapes"));
+        assert!(!ForgeFS::exists(fixture.temp_dir.path().join("escaped")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_invalid_hash() -> Result<()> {
+        let fixture = Fixture::new("target").await?;
+        let snapshot = Snapshot {
+            path: "main.rs".to_string(),
+            hash: "../../escaped".to_string(),
+            ..fixture.snapshot("main.rs", "fn main() {}").await?
+        };
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        append(
+            &mut builder,
+            &format!("{ARCHIVE_SNAPSHOTS_DIR}/{}.json", snapshot.id),
+            &serde_json::to_vec(&snapshot)?,
+        )?;
+        ForgeFS::write(fixture.archive(), builder.into_inner()?.finish()?).await?;
+
+        let actual = fixture
+            .service
+            .import_archive(&fixture.archive(), &fixture.root, ImportStrategy::Merge)
+            .await;
+
+        assert!(actual.unwrap_err().to_string().contains("invalid hash"));
+        Ok(())
+    }
+}
@@ -1,7 +1,17 @@
 // Export the modules
+mod archive;
+mod purge;
 mod service;
 mod snapshot;
+mod tag;
+mod tree;
+mod verify;
 
 // Re-export the SnapshotInfo struct and SnapshotId
+pub use archive::{ExportReport, ImportReport, ImportStrategy};
+pub use purge::PurgePolicy;
 pub use service::*;
-pub use snapshot::{Snapshot, SnapshotId};
+pub use snapshot::{Snapshot, SnapshotCompression, SnapshotId};
+pub use tag::TagError;
+pub use tree::{SkippedFile, TreeRestoreReport, TreeSnapshotInfo, DEFAULT_MAX_TREE_FILE_SIZE};
+pub use verify::{RestorationError, VerificationIssue};
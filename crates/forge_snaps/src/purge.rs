@@ -0,0 +1,215 @@
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use forge_fs::ForgeFS;
+use tracing::warn;
+
+use crate::snapshot::{Snapshot, OBJECTS_DIR};
+use crate::tree::TREES_DIR;
+use crate::SnapshotService;
+
+/// Limits on the snapshots kept for every file. Snapshots breaking any of them
+/// are removed, limits left unset don't remove anything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PurgePolicy {
+    /// Removes snapshots taken more than this many days ago
+    pub older_than_days: Option<u32>,
+    /// Keeps at most this many of the newest snapshots of each file
+    pub max_per_file: Option<usize>,
+}
+
+impl SnapshotService {
+    /// Removes all but the `keep` newest snapshots of `path`, returning the
+    /// number of snapshots deleted
+    pub async fn purge_excess(&self, path: PathBuf, keep: usize) -> Result<usize> {
+        let snapshot_dir = self.file_snapshot_dir(&path)?;
+        let policy = PurgePolicy { max_per_file: Some(keep), ..Default::default() };
+        self.purge_dir(&snapshot_dir, &policy, SystemTime::now())
+            .await
+    }
+
+    /// Applies `policy` to the snapshots of every file, returning the number
+    /// of snapshots deleted. Tree snapshots are left alone.
+    pub async fn purge_with_policy(&self, policy: &PurgePolicy) -> Result<usize> {
+        if !ForgeFS::exists(&self.snapshots_directory) {
+            return Ok(0);
+        }
+
+        let now = SystemTime::now();
+        let mut purged = 0;
+        let mut dirs = ForgeFS::read_dir(&self.snapshots_directory).await?;
+        while let Some(entry) = dirs.next_entry().await? {
+            if entry.file_name() == OBJECTS_DIR
+                || entry.file_name() == TREES_DIR
+                || !entry.path().is_dir()
+            {
+                continue;
+            }
+            purged += self.purge_dir(&entry.path(), policy, now).await?;
+        }
+
+        Ok(purged)
+    }
+
+    /// Removes the snapshots in `snapshot_dir` that break `policy`. The
+    /// snapshots are ordered by the time recorded in their metadata so that
+    /// the newest ones are kept. Snapshots with unreadable metadata are left
+    /// in place, as their age is unknown.
+    async fn purge_dir(
+        &self,
+        snapshot_dir: &Path,
+        policy: &PurgePolicy,
+        now: SystemTime,
+    ) -> Result<usize> {
+        if !ForgeFS::exists(snapshot_dir) {
+            return Ok(0);
+        }
+
+        let mut snapshots = Vec::new();
+        for file in Self::snapshot_files(snapshot_dir).await? {
+            match Snapshot::load(&file).await {
+                Ok(snapshot) => snapshots.push((snapshot.timestamp, file)),
+                Err(error) => {
+                    warn!(path = %file.display(), error = ?error, "Skipping unreadable snapshot")
+                }
+            }
+        }
+        snapshots.sort_by_key(|(timestamp, _)| Reverse(*timestamp));
+
+        let cutoff = policy.older_than_days.map(|days| {
+            let age = Duration::from_secs(u64::from(days) * 24 * 60 * 60);
+            now.checked_sub(age)
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .unwrap_or_default()
+        });
+        let keep = policy.max_per_file.unwrap_or(usize::MAX);
+
+        let mut purged = 0;
+        for (index, (timestamp, file)) in snapshots.iter().enumerate() {
+            if index >= keep || cutoff.is_some_and(|cutoff| *timestamp < cutoff) {
+                self.remove_snapshot_file(file).await?;
+                purged += 1;
+            }
+        }
+
+        Ok(purged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    struct Fixture {
+        temp_dir: TempDir,
+        service: SnapshotService,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let temp_dir = TempDir::new().unwrap();
+            let service = SnapshotService::new(temp_dir.path().join("snapshots"));
+            Self { temp_dir, service }
+        }
+
+        /// Snapshots `name` once per entry of `ages`, recording each snapshot
+        /// as taken that many days ago
+        async fn snapshots(&self, name: &str, ages: &[u32]) -> PathBuf {
+            let file = self.temp_dir.path().join(name);
+            for (i, age) in ages.iter().enumerate() {
+                ForgeFS::write(&file, format!("{name} {i}")).await.unwrap();
+                let mut snapshot = self
+                    .service
+                    .create_snapshot(file.clone(), None)
+                    .await
+                    .unwrap();
+
+                // Only the metadata is rewritten, so the file name no longer
+                // tells the snapshot's age
+                let path = snapshot.snapshot_path(Some(self.service.snapshots_directory.clone()));
+                snapshot.timestamp -= DAY * *age;
+                ForgeFS::write(&path, serde_json::to_vec(&snapshot).unwrap())
+                    .await
+                    .unwrap();
+            }
+            file
+        }
+
+        async fn ages(&self, file: &Path) -> Vec<u64> {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+            self.service
+                .list_snapshots(file.to_path_buf())
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|snapshot| (now - snapshot.timestamp).as_secs() / DAY.as_secs())
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_purge_excess_keeps_newest() {
+        let fixture = Fixture::new();
+        // Taken newest first, so the newest snapshots have the oldest names
+        let file = fixture.snapshots("a.txt", &[1, 2, 3, 4, 5]).await;
+
+        let actual = fixture.service.purge_excess(file.clone(), 2).await.unwrap();
+
+        assert_eq!(actual, 3);
+        let mut ages = fixture.ages(&file).await;
+        ages.sort();
+        assert_eq!(ages, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_purge_excess_without_snapshots() {
+        let fixture = Fixture::new();
+
+        let actual = fixture
+            .service
+            .purge_excess(fixture.temp_dir.path().join("missing.txt"), 1)
+            .await
+            .unwrap();
+
+        assert_eq!(actual, 0);
+    }
+
+    #[tokio::test]
+    async fn test_purge_with_policy() {
+        let fixture = Fixture::new();
+        let a = fixture.snapshots("a.txt", &[0, 1, 2, 40]).await;
+        let b = fixture.snapshots("b.txt", &[45, 50]).await;
+        let c = fixture.snapshots("c.txt", &[3]).await;
+
+        let policy = PurgePolicy { older_than_days: Some(30), max_per_file: Some(2) };
+        let actual = fixture.service.purge_with_policy(&policy).await.unwrap();
+
+        assert_eq!(actual, 4);
+        let mut ages = fixture.ages(&a).await;
+        ages.sort();
+        assert_eq!(ages, vec![0, 1]);
+        assert_eq!(fixture.ages(&b).await, Vec::<u64>::new());
+        assert_eq!(fixture.ages(&c).await, vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_purge_with_default_policy_keeps_everything() {
+        let fixture = Fixture::new();
+        let file = fixture.snapshots("a.txt", &[0, 400]).await;
+
+        let actual = fixture
+            .service
+            .purge_with_policy(&PurgePolicy::default())
+            .await
+            .unwrap();
+
+        assert_eq!(actual, 0);
+        assert_eq!(fixture.ages(&file).await.len(), 2);
+    }
+}
@@ -1,41 +1,126 @@
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use forge_fs::ForgeFS;
+use tracing::warn;
 
-use crate::snapshot::Snapshot;
+use crate::snapshot::{
+    hash_content, hash_path, read_object, resolve_path, Snapshot, SnapshotCompression, OBJECTS_DIR,
+};
+use crate::tag::{snapshot_timestamp, tagged_timestamp, tags_by_timestamp, untag};
+use crate::tree::{DEFAULT_MAX_TREE_FILE_SIZE, TREES_DIR};
+use crate::RestorationError;
+
+/// Selects one of the snapshots stored for a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotSelector {
+    /// The snapshot taken at the given timestamp, formatted as in the
+    /// snapshot's file name (`YYYY-MM-DD_HH-MM-SS-nnnnnnnnn`)
+    Timestamp(String),
+    /// The snapshot at the given position, `0` being the most recent one
+    Index(usize),
+    /// The most recent snapshot
+    Previous,
+    /// The most recent snapshot whose cause contains the given text
+    Label(String),
+    /// The snapshot given the tag
+    Tag(String),
+}
+
+/// Aggregated information about the snapshots of a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    /// Path of the file the snapshots were taken of
+    pub original_path: PathBuf,
+    /// Number of snapshots stored for the file
+    pub count: usize,
+    /// Time of the most recent snapshot, since the UNIX epoch
+    pub newest: Duration,
+    /// Time of the oldest snapshot, since the UNIX epoch
+    pub oldest: Duration,
+    /// Combined size of the file contents captured by the snapshots
+    pub total_bytes: u64,
+}
+
+impl SnapshotSummary {
+    fn new(snapshot: &Snapshot) -> Self {
+        Self {
+            original_path: PathBuf::from(&snapshot.path),
+            count: 1,
+            newest: snapshot.timestamp,
+            oldest: snapshot.timestamp,
+            total_bytes: snapshot.size,
+        }
+    }
+
+    fn add(&mut self, snapshot: &Snapshot) {
+        self.count += 1;
+        self.newest = self.newest.max(snapshot.timestamp);
+        self.oldest = self.oldest.min(snapshot.timestamp);
+        self.total_bytes += snapshot.size;
+    }
+}
 
 /// Implementation of the SnapshotService
 #[derive(Debug)]
 pub struct SnapshotService {
     /// Base directory for storing snapshots
-    snapshots_directory: PathBuf,
+    pub(crate) snapshots_directory: PathBuf,
+
+    /// Files larger than this are left out of tree snapshots
+    pub(crate) max_tree_file_size: u64,
+
+    /// How newly stored payloads are compressed
+    pub(crate) compression: SnapshotCompression,
 }
 
 impl SnapshotService {
     /// Create a new FileSystemSnapshotService with a specific home path
     pub fn new(snapshot_base_dir: PathBuf) -> Self {
-        Self { snapshots_directory: snapshot_base_dir }
+        Self {
+            snapshots_directory: snapshot_base_dir,
+            max_tree_file_size: DEFAULT_MAX_TREE_FILE_SIZE,
+            compression: SnapshotCompression::None,
+        }
+    }
+
+    /// Sets how payloads are compressed when they are stored. Payloads
+    /// stored before keep their compression and are still read back.
+    pub fn compression(mut self, compression: SnapshotCompression) -> Self {
+        self.compression = compression;
+        self
     }
 }
 
 impl SnapshotService {
-    pub async fn create_snapshot(&self, path: PathBuf) -> Result<Snapshot> {
-        let snapshot = Snapshot::create(path).await?;
-
-        // Create intermediary directories if they don't exist
-        let snapshot_path = snapshot.snapshot_path(Some(self.snapshots_directory.clone()));
-        if let Some(parent) = PathBuf::from(&snapshot_path).parent() {
-            ForgeFS::create_dir_all(parent).await?;
-        }
+    /// Snapshots the current content of `path`, recording `cause` as the
+    /// reason it was taken
+    pub async fn create_snapshot(&self, path: PathBuf, cause: Option<String>) -> Result<Snapshot> {
+        let content = ForgeFS::read(&path).await?;
+        let permissions = tokio::fs::metadata(&path).await?.permissions();
+        let mut snapshot = Snapshot::new(&path, &content)?.permissions(&permissions);
+        snapshot.cause = cause;
 
+        // Identical content is stored only once, so this becomes a metadata-only
+        // operation when the payload already exists
         snapshot
-            .save(Some(self.snapshots_directory.clone()))
+            .save(&self.snapshots_directory, &content, self.compression)
             .await?;
 
         Ok(snapshot)
     }
 
+    /// Directory holding all the snapshots of `path`
+    pub(crate) fn file_snapshot_dir(&self, path: &Path) -> Result<PathBuf> {
+        let path = resolve_path(path)?;
+        Ok(self
+            .snapshots_directory
+            .join(hash_path(&path.display().to_string())))
+    }
+
     /// Find the most recent snapshot for a given path based on filename
     /// timestamp
     async fn find_recent_snapshot(snapshot_dir: &PathBuf) -> Result<Option<PathBuf>> {
@@ -56,31 +141,343 @@ impl SnapshotService {
         Ok(latest_path)
     }
 
-    pub async fn undo_snapshot(&self, path: PathBuf) -> Result<()> {
-        let snapshot = Snapshot::create(path.clone()).await?;
+    /// Lists the metadata files of every snapshot stored in `snapshot_dir`
+    pub(crate) async fn snapshot_files(snapshot_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut dir = ForgeFS::read_dir(snapshot_dir).await?;
+
+        while let Some(entry) = dir.next_entry().await? {
+            if entry.file_name().to_string_lossy().ends_with(".snap") {
+                files.push(entry.path());
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Counts the snapshots, across all files and trees, that point at `hash`
+    async fn count_references(&self, hash: &str) -> Result<usize> {
+        let mut count = self.count_tree_references(hash).await?;
+        let mut dirs = ForgeFS::read_dir(&self.snapshots_directory).await?;
+
+        while let Some(entry) = dirs.next_entry().await? {
+            if entry.file_name() == OBJECTS_DIR
+                || entry.file_name() == TREES_DIR
+                || !entry.path().is_dir()
+            {
+                continue;
+            }
+
+            for file in Self::snapshot_files(&entry.path()).await? {
+                if let Ok(snapshot) = Snapshot::load(&file).await {
+                    if snapshot.hash == hash {
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Removes a snapshot's metadata file along with its tag, and deletes its
+    /// payload once no other snapshot refers to it
+    pub(crate) async fn remove_snapshot_file(&self, snapshot_path: &Path) -> Result<()> {
+        let snapshot = Snapshot::load(snapshot_path).await.ok();
+        ForgeFS::remove_file(snapshot_path).await?;
+        untag(snapshot_path).await?;
+
+        if let Some(snapshot) = snapshot {
+            let object_path = snapshot.object_path(&self.snapshots_directory);
+            if ForgeFS::exists(&object_path) && self.count_references(&snapshot.hash).await? == 0 {
+                ForgeFS::remove_file(&object_path).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the content captured by the snapshot stored at `snapshot_path`,
+    /// failing with [`RestorationError::ChecksumMismatch`] if it no longer
+    /// matches the hash recorded when the snapshot was taken
+    async fn snapshot_content(
+        &self,
+        snapshot_path: &Path,
+        snapshot: Option<&Snapshot>,
+    ) -> Result<Vec<u8>> {
+        let Some(snapshot) = snapshot else {
+            // Snapshots taken before content addressing hold the payload directly
+            return Ok(ForgeFS::read(snapshot_path).await?);
+        };
+
+        let content = read_object(&self.snapshots_directory, &snapshot.hash).await?;
+        let actual = hash_content(&content);
+        if actual != snapshot.hash {
+            return Err(RestorationError::ChecksumMismatch {
+                snapshot: snapshot_path.to_path_buf(),
+                expected: snapshot.hash.clone(),
+                actual,
+            }
+            .into());
+        }
+        Ok(content)
+    }
+
+    /// Writes the content captured by the snapshot stored at `snapshot_path`
+    /// to `dest` along with the file's permissions, recreating missing parent
+    /// directories
+    async fn write_snapshot(&self, snapshot_path: &Path, dest: &Path) -> Result<()> {
+        let snapshot = Snapshot::load(snapshot_path).await.ok();
+        let content = self
+            .snapshot_content(snapshot_path, snapshot.as_ref())
+            .await?;
 
-        // All the snaps for `path` are stored in `snapshot.path_hash()` directory.
-        let snapshot_dir = self.snapshots_directory.join(snapshot.path_hash());
+        if let Some(parent) = dest.parent() {
+            ForgeFS::create_dir_all(parent).await?;
+        }
+        ForgeFS::write(dest, content).await?;
+
+        if let Some(snapshot) = snapshot {
+            snapshot.restore_permissions(dest).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Resolves `selector` to the metadata file of one of the snapshots of
+    /// `path`
+    pub(crate) async fn select_snapshot(
+        &self,
+        path: &Path,
+        selector: &SnapshotSelector,
+    ) -> Result<PathBuf> {
+        // All the snaps for `path` are stored in the path's hash directory.
+        let snapshot_dir = self.file_snapshot_dir(path)?;
 
         // Check if the `snapshot_dir` exists
         if !ForgeFS::exists(&snapshot_dir) {
             return Err(anyhow::anyhow!("No snapshots found for {:?}", path));
         }
 
+        match selector {
+            SnapshotSelector::Previous => Self::find_recent_snapshot(&snapshot_dir)
+                .await?
+                .context(format!("No valid snapshots found for {path:?}")),
+            SnapshotSelector::Index(index) => {
+                // File names start with the timestamp, so they sort chronologically
+                let mut files = Self::snapshot_files(&snapshot_dir).await?;
+                files.sort_by(|a, b| b.cmp(a));
+                let count = files.len();
+                files.into_iter().nth(*index).with_context(|| {
+                    format!(
+                        "Snapshot index {index} is out of range, {path:?} has {count} snapshots"
+                    )
+                })
+            }
+            SnapshotSelector::Label(label) => {
+                let snapshot = self
+                    .find_by_label(path.to_path_buf(), label)
+                    .await?
+                    .with_context(|| format!("No snapshot of {path:?} matches label '{label}'"))?;
+                Ok(snapshot.snapshot_path(Some(self.snapshots_directory.clone())))
+            }
+            SnapshotSelector::Tag(tag) => {
+                let timestamp = tagged_timestamp(&snapshot_dir, tag)
+                    .await?
+                    .with_context(|| format!("No snapshot of {path:?} is tagged '{tag}'"))?;
+                Ok(snapshot_dir.join(format!("{timestamp}.snap")))
+            }
+            SnapshotSelector::Timestamp(timestamp) => {
+                let snapshot_path = snapshot_dir.join(format!("{timestamp}.snap"));
+                if !ForgeFS::exists(&snapshot_path) {
+                    return Err(anyhow::anyhow!(
+                        "No snapshot found for {:?} at {}",
+                        path,
+                        timestamp
+                    ));
+                }
+                Ok(snapshot_path)
+            }
+        }
+    }
+
+    /// Writes the content of the selected snapshot of `path` to `dest`,
+    /// leaving both the original file and the snapshot untouched. Fails if
+    /// `dest` already exists unless `overwrite` is set.
+    pub async fn restore_to(
+        &self,
+        path: PathBuf,
+        selector: SnapshotSelector,
+        dest: &Path,
+        overwrite: bool,
+    ) -> Result<()> {
+        if !overwrite && ForgeFS::exists(dest) {
+            return Err(anyhow::anyhow!(
+                "Destination {} already exists, pass overwrite to replace it",
+                dest.display()
+            ));
+        }
+
+        let snapshot_path = self.select_snapshot(&path, &selector).await?;
+        self.write_snapshot(&snapshot_path, dest).await
+    }
+
+    /// Reads the content captured by the selected snapshot of `path`
+    pub async fn read_snapshot(
+        &self,
+        path: PathBuf,
+        selector: SnapshotSelector,
+    ) -> Result<Vec<u8>> {
+        let snapshot_path = self.select_snapshot(&path, &selector).await?;
+        let snapshot = Snapshot::load(&snapshot_path).await.ok();
+        self.snapshot_content(&snapshot_path, snapshot.as_ref())
+            .await
+    }
+
+    /// Restores `path` in place to the snapshot at `index`, `0` being the
+    /// most recent one
+    pub async fn restore_by_index(&self, path: PathBuf, index: usize) -> Result<()> {
+        let dest = path.clone();
+        self.restore_to(path, SnapshotSelector::Index(index), &dest, true)
+            .await
+    }
+
+    /// Restores `path` in place to the snapshot taken at `timestamp`
+    pub async fn restore_by_timestamp(&self, path: PathBuf, timestamp: &str) -> Result<()> {
+        let dest = path.clone();
+        let selector = SnapshotSelector::Timestamp(timestamp.to_string());
+        self.restore_to(path, selector, &dest, true).await
+    }
+
+    /// Recreates `path`, deleted along with its parent directories or not,
+    /// from its most recent snapshot. The snapshot is kept.
+    pub async fn restore_deleted(&self, path: PathBuf) -> Result<()> {
+        if ForgeFS::exists(&path) {
+            return Err(anyhow::anyhow!(
+                "{} still exists, only deleted files can be restored",
+                path.display()
+            ));
+        }
+
+        let dest = path.clone();
+        self.restore_to(path, SnapshotSelector::Previous, &dest, false)
+            .await
+    }
+
+    pub async fn undo_snapshot(&self, path: PathBuf) -> Result<()> {
         // Retrieve the latest snapshot path
-        let snapshot_path = Self::find_recent_snapshot(&snapshot_dir)
-            .await?
-            .context(format!("No valid snapshots found for {path:?}"))?;
+        let snapshot_path = self
+            .select_snapshot(&path, &SnapshotSelector::Previous)
+            .await?;
 
-        // Restore the content
-        let content = ForgeFS::read(&snapshot_path).await?;
-        ForgeFS::write(&path, content).await?;
+        // Restore the content, the file may have been deleted since
+        self.write_snapshot(&snapshot_path, &path).await?;
 
         // Remove the used snapshot
-        ForgeFS::remove_file(&snapshot_path).await?;
+        self.remove_snapshot_file(&snapshot_path).await?;
 
         Ok(())
     }
+
+    /// Lists the snapshots of `path`, most recent first, so that positions
+    /// match [`SnapshotSelector::Index`]. Snapshots without readable metadata
+    /// are skipped.
+    pub async fn list_snapshots(&self, path: PathBuf) -> Result<Vec<Snapshot>> {
+        let snapshot_dir = self.file_snapshot_dir(&path)?;
+        if !ForgeFS::exists(&snapshot_dir) {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Self::snapshot_files(&snapshot_dir).await?;
+        files.sort_by(|a, b| b.cmp(a));
+        let mut tags = tags_by_timestamp(&snapshot_dir).await?;
+
+        let mut snapshots = Vec::with_capacity(files.len());
+        for file in files {
+            match Snapshot::load(&file).await {
+                Ok(mut snapshot) => {
+                    snapshot.tag = snapshot_timestamp(&file).and_then(|time| tags.remove(&time));
+                    snapshots.push(snapshot)
+                }
+                Err(error) => {
+                    warn!(path = %file.display(), error = ?error, "Skipping unreadable snapshot")
+                }
+            }
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Finds the most recent snapshot of `path` whose cause contains `label`
+    pub async fn find_by_label(&self, path: PathBuf, label: &str) -> Result<Option<Snapshot>> {
+        Ok(self
+            .list_snapshots(path)
+            .await?
+            .into_iter()
+            .find(|snapshot| {
+                snapshot
+                    .cause
+                    .as_deref()
+                    .is_some_and(|cause| cause.contains(label))
+            }))
+    }
+
+    /// Summarizes the snapshots of every file, most recently snapshotted files
+    /// first. Only the metadata is read; unreadable metadata files are skipped.
+    pub async fn list_all(&self) -> Result<Vec<SnapshotSummary>> {
+        if !ForgeFS::exists(&self.snapshots_directory) {
+            return Ok(Vec::new());
+        }
+
+        let mut summaries: HashMap<String, SnapshotSummary> = HashMap::new();
+        let mut dirs = ForgeFS::read_dir(&self.snapshots_directory).await?;
+
+        while let Some(entry) = dirs.next_entry().await? {
+            if entry.file_name() == OBJECTS_DIR
+                || entry.file_name() == TREES_DIR
+                || !entry.path().is_dir()
+            {
+                continue;
+            }
+
+            for file in Self::snapshot_files(&entry.path()).await? {
+                let snapshot = match Snapshot::load(&file).await {
+                    Ok(snapshot) => snapshot,
+                    Err(error) => {
+                        warn!(path = %file.display(), error = ?error, "Skipping unreadable snapshot");
+                        continue;
+                    }
+                };
+
+                summaries
+                    .entry(snapshot.path.clone())
+                    .and_modify(|summary| summary.add(&snapshot))
+                    .or_insert_with(|| SnapshotSummary::new(&snapshot));
+            }
+        }
+
+        let mut summaries = summaries.into_values().collect::<Vec<_>>();
+        summaries.sort_by_key(|summary| Reverse(summary.newest));
+
+        Ok(summaries)
+    }
+
+    /// Removes every snapshot of `path`, returning the number of snapshots
+    /// deleted. Payloads still referenced by snapshots of other files are
+    /// kept.
+    pub async fn purge(&self, path: PathBuf) -> Result<usize> {
+        let snapshot_dir = self.file_snapshot_dir(&path)?;
+        if !ForgeFS::exists(&snapshot_dir) {
+            return Ok(0);
+        }
+
+        let files = Self::snapshot_files(&snapshot_dir).await?;
+        for file in files.iter() {
+            self.remove_snapshot_file(file).await?;
+        }
+
+        Ok(files.len())
+    }
 }
 
 #[cfg(test)]
@@ -91,8 +488,8 @@ mod tests {
 
     // Test helpers
     struct TestContext {
-        _temp_dir: TempDir,
-        _snapshots_dir: PathBuf,
+        temp_dir: TempDir,
+        snapshots_dir: PathBuf,
         test_file: PathBuf,
         service: SnapshotService,
     }
@@ -104,16 +501,11 @@ mod tests {
             let test_file = temp_dir.path().join("test.txt");
             let service = SnapshotService::new(snapshots_dir.clone());
 
-            Ok(Self {
-                _temp_dir: temp_dir,
-                _snapshots_dir: snapshots_dir,
-                test_file,
-                service,
-            })
+            Ok(Self { temp_dir, snapshots_dir, test_file, service })
         }
 
         async fn write_content(&self, content: &str) -> Result<()> {
-            ForgeFS::write(&self.test_file, content.as_bytes()).await
+            Ok(ForgeFS::write(&self.test_file, content.as_bytes()).await?)
         }
 
         async fn read_content(&self) -> Result<String> {
@@ -122,12 +514,31 @@ mod tests {
         }
 
         async fn create_snapshot(&self) -> Result<Snapshot> {
-            self.service.create_snapshot(self.test_file.clone()).await
+            self.service
+                .create_snapshot(self.test_file.clone(), None)
+                .await
+        }
+
+        async fn create_labeled_snapshot(&self, content: &str, cause: &str) -> Result<Snapshot> {
+            self.write_content(content).await?;
+            self.service
+                .create_snapshot(self.test_file.clone(), Some(cause.to_string()))
+                .await
         }
 
         async fn undo_snapshot(&self) -> Result<()> {
             self.service.undo_snapshot(self.test_file.clone()).await
         }
+
+        async fn object_count(&self) -> Result<usize> {
+            let objects_dir = self.snapshots_dir.join(OBJECTS_DIR);
+            let mut count = 0;
+            let mut dir = ForgeFS::read_dir(&objects_dir).await?;
+            while dir.next_entry().await?.is_some() {
+                count += 1;
+            }
+            Ok(count)
+        }
     }
 
     #[tokio::test]
@@ -166,6 +577,50 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_restore_deleted_recreates_directory_and_mode() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let dir = ctx.temp_dir.path().join("bin");
+        let script = dir.join("run.sh");
+        ForgeFS::create_dir_all(&dir).await?;
+        ForgeFS::write(&script, "#!/bin/sh\necho hi\n").await?;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+        ctx.service.create_snapshot(script.clone(), None).await?;
+        std::fs::remove_dir_all(&dir)?;
+
+        // Act
+        ctx.service.restore_deleted(script.clone()).await?;
+
+        // Assert
+        let actual = (
+            String::from_utf8(ForgeFS::read(&script).await?)?,
+            std::fs::metadata(&script)?.permissions().mode() & 0o777,
+        );
+        let expected = ("#!/bin/sh\necho hi\n".to_string(), 0o755);
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undo_snapshot_of_deleted_file() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        ctx.write_content("Initial content").await?;
+        ctx.create_snapshot().await?;
+        ForgeFS::remove_file(&ctx.test_file).await?;
+
+        // Act
+        ctx.undo_snapshot().await?;
+
+        // Assert
+        assert_eq!(ctx.read_content().await?, "Initial content");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_undo_snapshot_no_snapshots() -> Result<()> {
         // Arrange
@@ -227,4 +682,503 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_identical_content_shares_object() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let other_file = ctx.temp_dir.path().join("other.txt");
+        ctx.write_content("Shared content").await?;
+        ForgeFS::write(&other_file, "Shared content").await?;
+
+        // Act
+        let first = ctx.create_snapshot().await?;
+        let second = ctx
+            .service
+            .create_snapshot(other_file.clone(), None)
+            .await?;
+
+        // Assert
+        assert_eq!(first.hash, second.hash);
+        assert_eq!(ctx.object_count().await?, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_purge_keeps_shared_object() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let other_file = ctx.temp_dir.path().join("other.txt");
+        ctx.write_content("Shared content").await?;
+        ForgeFS::write(&other_file, "Shared content").await?;
+        let snapshot = ctx.create_snapshot().await?;
+        ctx.service
+            .create_snapshot(other_file.clone(), None)
+            .await?;
+
+        // Act
+        let purged = ctx.service.purge(ctx.test_file.clone()).await?;
+
+        // Assert
+        assert_eq!(purged, 1);
+        assert!(ForgeFS::exists(snapshot.object_path(&ctx.snapshots_dir)));
+
+        // Act
+        ctx.service.purge(other_file).await?;
+
+        // Assert
+        assert!(!ForgeFS::exists(snapshot.object_path(&ctx.snapshots_dir)));
+        assert_eq!(ctx.object_count().await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undo_restores_deduplicated_content() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+
+        // Act
+        ctx.write_content("Same content").await?;
+        ctx.create_snapshot().await?;
+        ctx.write_content("Other content").await?;
+        ctx.create_snapshot().await?;
+        ctx.write_content("Same content").await?;
+        ctx.create_snapshot().await?;
+        ctx.write_content("Final content").await?;
+        ctx.undo_snapshot().await?;
+        ctx.undo_snapshot().await?;
+        ctx.undo_snapshot().await?;
+
+        // Assert
+        assert_eq!(ctx.read_content().await?, "Same content");
+        assert_eq!(ctx.object_count().await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_to_by_index() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let dest = ctx.temp_dir.path().join("restored").join("test.txt.old");
+        for content in ["First content", "Second content", "Third content"] {
+            ctx.write_content(content).await?;
+            ctx.create_snapshot().await?;
+        }
+        ctx.write_content("Final content").await?;
+
+        // Act
+        ctx.service
+            .restore_to(
+                ctx.test_file.clone(),
+                SnapshotSelector::Index(2),
+                &dest,
+                false,
+            )
+            .await?;
+
+        // Assert
+        assert_eq!(
+            String::from_utf8(ForgeFS::read(&dest).await?)?,
+            "First content"
+        );
+        assert_eq!(ctx.read_content().await?, "Final content");
+        assert_eq!(ctx.object_count().await?, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_snapshot() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        for content in ["First content", "Second content"] {
+            ctx.write_content(content).await?;
+            ctx.create_snapshot().await?;
+        }
+        ctx.write_content("Final content").await?;
+
+        // Act
+        let actual = ctx
+            .service
+            .read_snapshot(ctx.test_file.clone(), SnapshotSelector::Index(1))
+            .await?;
+
+        // Assert
+        assert_eq!(String::from_utf8(actual)?, "First content");
+        assert_eq!(ctx.read_content().await?, "Final content");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_to_existing_destination() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let dest = ctx.temp_dir.path().join("test.txt.old");
+        ctx.write_content("Snapshot content").await?;
+        ctx.create_snapshot().await?;
+        ForgeFS::write(&dest, "Existing content").await?;
+
+        // Act
+        let result = ctx
+            .service
+            .restore_to(
+                ctx.test_file.clone(),
+                SnapshotSelector::Previous,
+                &dest,
+                false,
+            )
+            .await;
+
+        // Assert
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+        assert_eq!(
+            String::from_utf8(ForgeFS::read(&dest).await?)?,
+            "Existing content"
+        );
+
+        // Act
+        ctx.service
+            .restore_to(
+                ctx.test_file.clone(),
+                SnapshotSelector::Previous,
+                &dest,
+                true,
+            )
+            .await?;
+
+        // Assert
+        assert_eq!(
+            String::from_utf8(ForgeFS::read(&dest).await?)?,
+            "Snapshot content"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_to_no_snapshots() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let dest = ctx.temp_dir.path().join("test.txt.old");
+        ctx.write_content("test content").await?;
+
+        // Act
+        let result = ctx
+            .service
+            .restore_to(
+                ctx.test_file.clone(),
+                SnapshotSelector::Index(0),
+                &dest,
+                false,
+            )
+            .await;
+
+        // Assert
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("No snapshots found"));
+        assert!(!ForgeFS::exists(&dest));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_by_index_in_place() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        ctx.write_content("First content").await?;
+        ctx.create_snapshot().await?;
+        ctx.write_content("Second content").await?;
+        ctx.create_snapshot().await?;
+
+        // Act
+        ctx.service
+            .restore_by_index(ctx.test_file.clone(), 1)
+            .await?;
+
+        // Assert
+        assert_eq!(ctx.read_content().await?, "First content");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_all() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let first = ctx.temp_dir.path().join("first.txt");
+        let second = ctx.temp_dir.path().join("second.txt");
+        for content in ["a", "bb", "ccc"] {
+            ForgeFS::write(&first, content).await?;
+            ctx.service.create_snapshot(first.clone(), None).await?;
+        }
+        ForgeFS::write(&second, "dddd").await?;
+        let second_snapshot = ctx.service.create_snapshot(second.clone(), None).await?;
+        ctx.write_content("eeeee").await?;
+        let oldest = ctx.create_snapshot().await?;
+        ctx.write_content("ffffff").await?;
+        let newest = ctx.create_snapshot().await?;
+
+        // Act
+        let actual = ctx.service.list_all().await?;
+
+        // Assert
+        let actual = actual
+            .into_iter()
+            .map(|summary| {
+                let name = summary.original_path.file_name().unwrap().to_owned();
+                (name, summary.count, summary.total_bytes)
+            })
+            .collect::<Vec<_>>();
+        let expected = vec![
+            ("test.txt".into(), 2, 11),
+            ("second.txt".into(), 1, 4),
+            ("first.txt".into(), 3, 6),
+        ];
+        assert_eq!(actual, expected);
+
+        let summaries = ctx.service.list_all().await?;
+        assert_eq!(summaries[0].newest, newest.timestamp);
+        assert_eq!(summaries[0].oldest, oldest.timestamp);
+        assert_eq!(summaries[1].newest, second_snapshot.timestamp);
+        assert_eq!(summaries[1].oldest, second_snapshot.timestamp);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_all_skips_corrupt_metadata() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        ctx.write_content("Hello").await?;
+        let snapshot = ctx.create_snapshot().await?;
+        let snapshot_dir = ctx.snapshots_dir.join(snapshot.path_hash());
+        ForgeFS::write(snapshot_dir.join("corrupt.snap"), "not json").await?;
+
+        // Act
+        let actual = ctx.service.list_all().await?;
+
+        // Assert
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].count, 1);
+        assert_eq!(actual[0].total_bytes, 5);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_all_without_snapshots() -> Result<()> {
+        let ctx = TestContext::new().await?;
+        let actual = ctx.service.list_all().await?;
+        assert_eq!(actual, vec![]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_snapshot_records_cause() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let snapshot = ctx
+            .create_labeled_snapshot("Hello", "apply_patch: rename function foo→bar")
+            .await?;
+
+        // Act
+        let actual =
+            Snapshot::load(&snapshot.snapshot_path(Some(ctx.snapshots_dir.clone()))).await?;
+
+        // Assert
+        assert_eq!(
+            actual.cause.as_deref(),
+            Some("apply_patch: rename function foo→bar")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_metadata_without_cause() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        ctx.write_content("Hello").await?;
+        let snapshot = ctx.create_snapshot().await?;
+        let snapshot_path = snapshot.snapshot_path(Some(ctx.snapshots_dir.clone()));
+        let mut metadata: serde_json::Value =
+            serde_json::from_slice(&ForgeFS::read(&snapshot_path).await?)?;
+        metadata.as_object_mut().unwrap().remove("cause");
+        ForgeFS::write(&snapshot_path, serde_json::to_vec(&metadata)?).await?;
+
+        // Act
+        let actual = Snapshot::load(&snapshot_path).await?;
+
+        // Assert
+        assert_eq!(actual.cause, None);
+        assert_eq!(actual.hash, snapshot.hash);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_snapshots_most_recent_first() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        ctx.create_labeled_snapshot("First", "fs_create: first")
+            .await?;
+        ctx.write_content("Second").await?;
+        ctx.create_snapshot().await?;
+        ctx.create_labeled_snapshot("Third", "apply_patch: third")
+            .await?;
+
+        // Act
+        let actual = ctx.service.list_snapshots(ctx.test_file.clone()).await?;
+
+        // Assert
+        let actual = actual
+            .into_iter()
+            .map(|snapshot| snapshot.cause)
+            .collect::<Vec<_>>();
+        let expected = vec![
+            Some("apply_patch: third".to_string()),
+            None,
+            Some("fs_create: first".to_string()),
+        ];
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_by_label() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        ctx.create_labeled_snapshot("Before", "apply_patch: big refactor")
+            .await?;
+        let expected = ctx
+            .create_labeled_snapshot("During", "apply_patch: big refactor, part 2")
+            .await?;
+        ctx.create_labeled_snapshot("After", "fs_create: cleanup")
+            .await?;
+
+        // Act
+        let actual = ctx
+            .service
+            .find_by_label(ctx.test_file.clone(), "big refactor")
+            .await?;
+        let missing = ctx
+            .service
+            .find_by_label(ctx.test_file.clone(), "unknown")
+            .await?;
+
+        // Assert
+        assert_eq!(actual.map(|snapshot| snapshot.id), Some(expected.id));
+        assert!(missing.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_to_by_label() -> Result<()> {
+        // Arrange
+        let ctx = TestContext::new().await?;
+        let dest = ctx.temp_dir.path().join("test.txt.old");
+        ctx.create_labeled_snapshot("Before refactor", "apply_patch: big refactor")
+            .await?;
+        ctx.create_labeled_snapshot("After refactor", "fs_create: cleanup")
+            .await?;
+
+        // Act
+        ctx.service
+            .restore_to(
+                ctx.test_file.clone(),
+                SnapshotSelector::Label("refactor".to_string()),
+                &dest,
+                false,
+            )
+            .await?;
+
+        // Assert
+        assert_eq!(
+            String::from_utf8(ForgeFS::read(&dest).await?)?,
+            "Before refactor"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_compressed_snapshots() -> Result<()> {
+        for (compression, extension) in [
+            (SnapshotCompression::Zstd, "zst"),
+            (SnapshotCompression::Gzip, "gz"),
+        ] {
+            // Arrange
+            let mut ctx = TestContext::new().await?;
+            ctx.service = SnapshotService::new(ctx.snapshots_dir.clone()).compression(compression);
+            let content = "fn main() {}\n".repeat(1000);
+            ctx.write_content(&content).await?;
+
+            // Act
+            let snapshot = ctx.create_snapshot().await?;
+            ctx.write_content("Modified content").await?;
+            ctx.service
+                .restore_by_index(ctx.test_file.clone(), 0)
+                .await?;
+            let by_index = ctx.read_content().await?;
+            ctx.write_content("Modified content").await?;
+            let timestamp = snapshot.snapshot_path(None);
+            let timestamp = timestamp.file_stem().unwrap().to_string_lossy();
+            ctx.service
+                .restore_by_timestamp(ctx.test_file.clone(), &timestamp)
+                .await?;
+            let by_timestamp = ctx.read_content().await?;
+
+            // Assert
+            let object = snapshot.object_path(&ctx.snapshots_dir);
+            assert_eq!(snapshot.compression, compression);
+            assert_eq!(object.extension().unwrap(), extension);
+            assert!(snapshot.compressed_size.unwrap() < snapshot.size);
+            assert_eq!(
+                tokio::fs::metadata(&object).await?.len(),
+                snapshot.compressed_size.unwrap()
+            );
+            assert_eq!(by_index, content);
+            assert_eq!(by_timestamp, content);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compressed_snapshot_reuses_stored_payload() -> Result<()> {
+        // Arrange
+        let mut ctx = TestContext::new().await?;
+        ctx.write_content("Same content").await?;
+        let plain = ctx.create_snapshot().await?;
+        ctx.service =
+            SnapshotService::new(ctx.snapshots_dir.clone()).compression(SnapshotCompression::Zstd);
+
+        // Act
+        let reused = ctx.create_snapshot().await?;
+        ctx.write_content("New content").await?;
+        let compressed = ctx.create_snapshot().await?;
+
+        // Assert
+        assert_eq!(plain.compression, SnapshotCompression::None);
+        assert_eq!(reused.compression, SnapshotCompression::None);
+        assert_eq!(reused.compressed_size, None);
+        assert_eq!(compressed.compression, SnapshotCompression::Zstd);
+        assert_eq!(ctx.object_count().await?, 2);
+
+        // Act
+        ctx.service.purge(ctx.test_file.clone()).await?;
+
+        // Assert
+        assert_eq!(ctx.object_count().await?, 0);
+
+        Ok(())
+    }
 }
@@ -1,8 +1,12 @@
 use std::fmt::{Display, Formatter};
 use std::hash::Hasher;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use forge_fs::ForgeFS;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -46,10 +50,71 @@ impl From<Uuid> for SnapshotId {
     }
 }
 
+/// Name of the directory, relative to the snapshots directory, holding the
+/// content-addressed payloads
+pub const OBJECTS_DIR: &str = "objects";
+
+/// How a payload is compressed in the objects directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotCompression {
+    /// Stored as a plain copy of the file
+    #[default]
+    None,
+    /// Compressed with zstd, which is fast to compress and decompress
+    Zstd,
+    /// Compressed with gzip, which is slower but more widely supported
+    Gzip,
+}
+
+impl SnapshotCompression {
+    const ALL: [Self; 3] = [Self::None, Self::Zstd, Self::Gzip];
+
+    /// Suffix added to the hash in the name of a payload compressed this way,
+    /// so that the compression can be told from the objects directory alone
+    fn extension(self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Zstd => ".zst",
+            Self::Gzip => ".gz",
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+
+    pub fn compress(self, content: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(content.to_vec()),
+            Self::Zstd => Ok(zstd::encode_all(content, zstd::DEFAULT_COMPRESSION_LEVEL)?),
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(content)?;
+                Ok(encoder.finish()?)
+            }
+        }
+    }
+
+    pub fn decompress(self, content: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(content.to_vec()),
+            Self::Zstd => Ok(zstd::decode_all(content)?),
+            Self::Gzip => {
+                let mut decompressed = Vec::new();
+                GzDecoder::new(content).read_to_end(&mut decompressed)?;
+                Ok(decompressed)
+            }
+        }
+    }
+}
+
 /// Represents information about a file snapshot
 ///
-/// Contains details about when the snapshot was created,
-/// the original file path, the snapshot location, and file size.
+/// Contains details about when the snapshot was created, the original file
+/// path and the hash of the content it refers to. Snapshots are persisted as
+/// metadata files while the content itself is stored once per unique hash in
+/// the objects directory.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snapshot {
     /// Unique ID for the file
@@ -60,25 +125,97 @@ pub struct Snapshot {
 
     /// Original file path that is being processed
     pub path: String,
+
+    /// blake3 hash of the file content, identifies the stored payload
+    pub hash: String,
+
+    /// Size of the file content in bytes
+    pub size: u64,
+
+    /// What the snapshot was taken for, e.g. the tool about to modify the
+    /// file and a short description of the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+
+    /// Unix permission bits of the file, only recorded on Unix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+
+    /// Whether the file was read-only, reapplied on platforms without Unix
+    /// permission bits
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub readonly: bool,
+
+    /// How the payload is compressed in the objects directory
+    #[serde(default, skip_serializing_if = "SnapshotCompression::is_none")]
+    pub compression: SnapshotCompression,
+
+    /// Size of the payload on disk, only recorded when it is compressed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed_size: Option<u64>,
+
+    /// Tag given to the snapshot by the user. Tags are kept in a file next to
+    /// the metadata files, and only filled in when listing snapshots.
+    #[serde(skip)]
+    pub tag: Option<String>,
 }
 
 impl Snapshot {
-    pub async fn create(path: PathBuf) -> anyhow::Result<Self> {
-        let path = path.canonicalize()?;
+    /// Creates a snapshot of `content` for the file at `path`
+    pub fn new(path: &Path, content: &[u8]) -> anyhow::Result<Self> {
+        let path = resolve_path(path)?;
         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?;
 
         Ok(Self {
             id: SnapshotId::new(),
             timestamp,
             path: path.display().to_string(),
+            hash: hash_content(content),
+            size: content.len() as u64,
+            cause: None,
+            mode: None,
+            readonly: false,
+            compression: SnapshotCompression::None,
+            compressed_size: None,
+            tag: None,
         })
     }
 
+    /// Records the permissions of the file, so they can be reapplied when it
+    /// is restored
+    pub fn permissions(mut self, permissions: &std::fs::Permissions) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            self.mode = Some(permissions.mode() & 0o7777);
+        }
+        self.readonly = permissions.readonly();
+        self
+    }
+
+    /// Reapplies the permissions recorded for the file to `path`
+    pub async fn restore_permissions(&self, path: &Path) -> anyhow::Result<()> {
+        let mut permissions = tokio::fs::metadata(path).await?.permissions();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            match self.mode {
+                Some(mode) => permissions.set_mode(mode),
+                None => return Ok(()),
+            }
+        }
+        #[cfg(not(unix))]
+        permissions.set_readonly(self.readonly);
+
+        tokio::fs::set_permissions(path, permissions)
+            .await
+            .with_context(|| format!("Failed to restore permissions of {}", path.display()))
+    }
+
     /// Create a hash of a file path for storage
     pub fn path_hash(&self) -> String {
-        let mut hasher = fnv_rs::Fnv64::default();
-        hasher.write(self.path.as_bytes());
-        format!("{:x}", hasher.finish())
+        hash_path(&self.path)
     }
 
     /// Create a snapshot filename from a path and timestamp
@@ -99,10 +236,143 @@ impl Snapshot {
         }
     }
 
-    pub async fn save(&self, path: Option<PathBuf>) -> anyhow::Result<()> {
-        let content = ForgeFS::read(&self.path).await?;
-        let path = self.snapshot_path(path);
-        ForgeFS::write(path, content).await?;
+    /// Path of the payload referenced by this snapshot
+    pub fn object_path(&self, snapshots_dir: &Path) -> PathBuf {
+        object_path(snapshots_dir, &self.hash, self.compression)
+    }
+
+    /// Persists the snapshot metadata, storing `content` in the objects
+    /// directory with `compression` only if no other snapshot has stored it
+    /// already. The compression of the stored payload is recorded in the
+    /// metadata, which differs from `compression` when the payload was
+    /// stored by an earlier snapshot.
+    pub async fn save(
+        &mut self,
+        snapshots_dir: &Path,
+        content: &[u8],
+        compression: SnapshotCompression,
+    ) -> anyhow::Result<()> {
+        let (compression, stored_size) =
+            store_object(snapshots_dir, &self.hash, content, compression).await?;
+        self.compression = compression;
+        self.compressed_size = (!compression.is_none()).then_some(stored_size);
+
+        let path = self.snapshot_path(Some(snapshots_dir.to_path_buf()));
+        if let Some(parent) = path.parent() {
+            ForgeFS::create_dir_all(parent).await?;
+        }
+        ForgeFS::write(path, serde_json::to_vec(self)?).await?;
         Ok(())
     }
+
+    /// Loads snapshot metadata from a `.snap` file
+    pub async fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = ForgeFS::read(path).await?;
+        serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse snapshot metadata {}", path.display()))
+    }
+}
+
+/// Path of the payload with the given hash when compressed with `compression`
+fn object_path(snapshots_dir: &Path, hash: &str, compression: SnapshotCompression) -> PathBuf {
+    snapshots_dir
+        .join(OBJECTS_DIR)
+        .join(format!("{hash}{}", compression.extension()))
+}
+
+/// Finds the payload with the given hash along with how it is compressed.
+/// A payload is only ever stored once, whatever its compression.
+pub(crate) fn find_object(
+    snapshots_dir: &Path,
+    hash: &str,
+) -> Option<(PathBuf, SnapshotCompression)> {
+    SnapshotCompression::ALL
+        .into_iter()
+        .find_map(|compression| {
+            let path = object_path(snapshots_dir, hash, compression);
+            ForgeFS::exists(&path).then_some((path, compression))
+        })
+}
+
+/// Stores `content` as the payload with the given hash unless it is stored
+/// already, returning how the stored payload is compressed and its size on
+/// disk
+pub(crate) async fn store_object(
+    snapshots_dir: &Path,
+    hash: &str,
+    content: &[u8],
+    compression: SnapshotCompression,
+) -> anyhow::Result<(SnapshotCompression, u64)> {
+    if let Some((path, compression)) = find_object(snapshots_dir, hash) {
+        return Ok((compression, tokio::fs::metadata(path).await?.len()));
+    }
+
+    let path = object_path(snapshots_dir, hash, compression);
+    if let Some(parent) = path.parent() {
+        ForgeFS::create_dir_all(parent).await?;
+    }
+    let compressed = compression.compress(content)?;
+    ForgeFS::write(&path, &compressed).await?;
+    Ok((compression, compressed.len() as u64))
+}
+
+/// Reads the payload with the given hash, decompressing it
+pub(crate) async fn read_object(snapshots_dir: &Path, hash: &str) -> anyhow::Result<Vec<u8>> {
+    let (path, compression) = find_object(snapshots_dir, hash)
+        .with_context(|| format!("Payload {hash} is missing from the snapshots directory"))?;
+    compression
+        .decompress(&ForgeFS::read(&path).await?)
+        .with_context(|| format!("Failed to decompress payload {}", path.display()))
+}
+
+/// Makes `path` absolute and resolves symlinks like [`Path::canonicalize`],
+/// but also accepts paths that no longer exist, such as deleted files, by
+/// resolving their closest existing ancestor
+pub fn resolve_path(path: &Path) -> anyhow::Result<PathBuf> {
+    if let Ok(path) = path.canonicalize() {
+        return Ok(path);
+    }
+
+    let path = std::path::absolute(path)?;
+    let mut missing = Vec::new();
+    let mut ancestor = path.as_path();
+    while !ancestor.exists() {
+        missing.push(
+            ancestor
+                .file_name()
+                .with_context(|| format!("Failed to resolve path {}", path.display()))?,
+        );
+        ancestor = ancestor
+            .parent()
+            .with_context(|| format!("Failed to resolve path {}", path.display()))?;
+    }
+
+    Ok(missing
+        .into_iter()
+        .rev()
+        .fold(ancestor.canonicalize()?, |resolved, name| {
+            resolved.join(name)
+        }))
+}
+
+/// Create a hash of a file path, used as the directory holding all the
+/// snapshots of that file
+pub fn hash_path(path: &str) -> String {
+    let mut hasher = fnv_rs::Fnv64::default();
+    hasher.write(path.as_bytes());
+    format!("{:x}", hasher.finish())
+}
+
+/// Create the content address of a payload
+pub fn hash_content(content: &[u8]) -> String {
+    blake3::hash(content).to_hex().to_string()
+}
+
+/// Checks that `hash` has the form of a hash returned by [`hash_content`], so
+/// that it is safe to use as a file name in the objects directory
+pub(crate) fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 64
+        && hash
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
 }
@@ -0,0 +1,323 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use forge_fs::ForgeFS;
+
+use crate::{SnapshotSelector, SnapshotService};
+
+/// Name of the file, next to the snapshots of a file, that maps the tags
+/// given to them to the timestamps of the snapshots they were given to
+pub(crate) const TAGS_FILE: &str = "tags.json";
+
+/// Why a snapshot couldn't be tagged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagError {
+    /// Tags must be non-empty and made of letters, digits and hyphens
+    InvalidTag(String),
+    /// Another snapshot of the same file already has the tag
+    TagAlreadyExists { tag: String, timestamp: String },
+}
+
+impl Display for TagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidTag(tag) => write!(
+                f,
+                "Invalid tag '{tag}', tags must be non-empty and only contain letters, digits and hyphens"
+            ),
+            Self::TagAlreadyExists { tag, timestamp } => write!(
+                f,
+                "Tag '{tag}' is already given to the snapshot taken at {timestamp}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TagError {}
+
+/// Tags of the snapshots of a file, keyed by tag
+type Tags = BTreeMap<String, String>;
+
+impl SnapshotService {
+    /// Tags the snapshot of `path` taken at `timestamp`, replacing the tag it
+    /// had. Each tag names at most one snapshot of a file, so tagging another
+    /// snapshot with a tag in use fails with [`TagError::TagAlreadyExists`].
+    pub async fn tag_snapshot(&self, path: PathBuf, timestamp: &str, tag: &str) -> Result<()> {
+        if tag.is_empty() || !tag.chars().all(|c| c.is_alphanumeric() || c == '-') {
+            return Err(TagError::InvalidTag(tag.to_string()).into());
+        }
+
+        // Fails if there is no snapshot at `timestamp`
+        let selector = SnapshotSelector::Timestamp(timestamp.to_string());
+        self.select_snapshot(&path, &selector).await?;
+
+        let snapshot_dir = self.file_snapshot_dir(&path)?;
+        let mut tags = read_tags(&snapshot_dir).await?;
+        match tags.get(tag) {
+            Some(tagged) if tagged == timestamp => return Ok(()),
+            Some(tagged) => {
+                return Err(TagError::TagAlreadyExists {
+                    tag: tag.to_string(),
+                    timestamp: tagged.clone(),
+                }
+                .into())
+            }
+            None => {}
+        }
+
+        tags.retain(|_, tagged| tagged != timestamp);
+        tags.insert(tag.to_string(), timestamp.to_string());
+        write_tags(&snapshot_dir, &tags).await
+    }
+
+    /// Restores `path` in place to the snapshot tagged `tag`
+    pub async fn restore_by_tag(&self, path: PathBuf, tag: &str) -> Result<()> {
+        let dest = path.clone();
+        let selector = SnapshotSelector::Tag(tag.to_string());
+        self.restore_to(path, selector, &dest, true).await
+    }
+}
+
+/// Timestamp of the snapshot stored at `snapshot_path`, as found in its file
+/// name
+pub(crate) fn snapshot_timestamp(snapshot_path: &Path) -> Option<String> {
+    snapshot_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+}
+
+/// Reads the tags of the snapshots in `snapshot_dir`
+async fn read_tags(snapshot_dir: &Path) -> Result<Tags> {
+    let path = snapshot_dir.join(TAGS_FILE);
+    if !ForgeFS::exists(&path) {
+        return Ok(Tags::new());
+    }
+
+    let content = ForgeFS::read(&path).await?;
+    serde_json::from_slice(&content)
+        .with_context(|| format!("Failed to parse snapshot tags {}", path.display()))
+}
+
+/// Writes the tags of the snapshots in `snapshot_dir`, removing the tags file
+/// once no snapshot is tagged
+async fn write_tags(snapshot_dir: &Path, tags: &Tags) -> Result<()> {
+    let path = snapshot_dir.join(TAGS_FILE);
+    if tags.is_empty() {
+        if ForgeFS::exists(&path) {
+            ForgeFS::remove_file(&path).await?;
+        }
+        return Ok(());
+    }
+
+    ForgeFS::write(&path, serde_json::to_vec_pretty(tags)?).await?;
+    Ok(())
+}
+
+/// Timestamp of the snapshot in `snapshot_dir` tagged `tag`, if any
+pub(crate) async fn tagged_timestamp(snapshot_dir: &Path, tag: &str) -> Result<Option<String>> {
+    Ok(read_tags(snapshot_dir).await?.remove(tag))
+}
+
+/// Tags of the snapshots in `snapshot_dir`, keyed by snapshot timestamp
+pub(crate) async fn tags_by_timestamp(snapshot_dir: &Path) -> Result<HashMap<String, String>> {
+    Ok(read_tags(snapshot_dir)
+        .await?
+        .into_iter()
+        .map(|(tag, timestamp)| (timestamp, tag))
+        .collect())
+}
+
+/// Drops the tag of the snapshot stored at `snapshot_path`, once it is removed
+pub(crate) async fn untag(snapshot_path: &Path) -> Result<()> {
+    let (Some(snapshot_dir), Some(timestamp)) =
+        (snapshot_path.parent(), snapshot_timestamp(snapshot_path))
+    else {
+        return Ok(());
+    };
+
+    let mut tags = read_tags(snapshot_dir).await?;
+    let count = tags.len();
+    tags.retain(|_, tagged| *tagged != timestamp);
+    if tags.len() == count {
+        return Ok(());
+    }
+    write_tags(snapshot_dir, &tags).await
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::Snapshot;
+
+    struct Fixture {
+        _temp_dir: TempDir,
+        file: PathBuf,
+        service: SnapshotService,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let temp_dir = TempDir::new().unwrap();
+            let file = temp_dir.path().join("test.txt");
+            let service = SnapshotService::new(temp_dir.path().join("snapshots"));
+            Self { _temp_dir: temp_dir, file, service }
+        }
+
+        /// Snapshots `content`, returning the timestamp of the snapshot
+        async fn snapshot(&self, content: &str) -> String {
+            ForgeFS::write(&self.file, content).await.unwrap();
+            let snapshot = self
+                .service
+                .create_snapshot(self.file.clone(), None)
+                .await
+                .unwrap();
+            snapshot_timestamp(&snapshot.snapshot_path(None)).unwrap()
+        }
+
+        async fn tags(&self) -> Vec<Option<String>> {
+            self.service
+                .list_snapshots(self.file.clone())
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|snapshot: Snapshot| snapshot.tag)
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore_by_tag() {
+        let fixture = Fixture::new();
+        let first = fixture.snapshot("first").await;
+        fixture.snapshot("second").await;
+        fixture
+            .service
+            .tag_snapshot(fixture.file.clone(), &first, "before-refactor")
+            .await
+            .unwrap();
+        ForgeFS::write(&fixture.file, "third").await.unwrap();
+
+        fixture
+            .service
+            .restore_by_tag(fixture.file.clone(), "before-refactor")
+            .await
+            .unwrap();
+
+        let actual = ForgeFS::read_utf8(&fixture.file).await.unwrap();
+        assert_eq!(actual, "first");
+        assert_eq!(
+            fixture.tags().await,
+            vec![None, Some("before-refactor".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tag_snapshot_rejects_invalid_tags() {
+        let fixture = Fixture::new();
+        let timestamp = fixture.snapshot("first").await;
+
+        for tag in ["", "two words", "v1.0", "a/b"] {
+            let actual = fixture
+                .service
+                .tag_snapshot(fixture.file.clone(), &timestamp, tag)
+                .await
+                .unwrap_err();
+
+            let expected = TagError::InvalidTag(tag.to_string());
+            assert_eq!(actual.downcast_ref(), Some(&expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tag_snapshot_rejects_tag_in_use() {
+        let fixture = Fixture::new();
+        let first = fixture.snapshot("first").await;
+        let second = fixture.snapshot("second").await;
+        fixture
+            .service
+            .tag_snapshot(fixture.file.clone(), &first, "stable")
+            .await
+            .unwrap();
+
+        let actual = fixture
+            .service
+            .tag_snapshot(fixture.file.clone(), &second, "stable")
+            .await
+            .unwrap_err();
+
+        let expected =
+            TagError::TagAlreadyExists { tag: "stable".to_string(), timestamp: first.clone() };
+        assert_eq!(actual.downcast_ref(), Some(&expected));
+        // Tagging the same snapshot again is fine
+        fixture
+            .service
+            .tag_snapshot(fixture.file.clone(), &first, "stable")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tag_snapshot_replaces_previous_tag() {
+        let fixture = Fixture::new();
+        let timestamp = fixture.snapshot("first").await;
+
+        for tag in ["draft", "final"] {
+            fixture
+                .service
+                .tag_snapshot(fixture.file.clone(), &timestamp, tag)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(fixture.tags().await, vec![Some("final".to_string())]);
+        assert!(fixture
+            .service
+            .restore_by_tag(fixture.file.clone(), "draft")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tag_snapshot_missing_snapshot() {
+        let fixture = Fixture::new();
+        fixture.snapshot("first").await;
+
+        let actual = fixture
+            .service
+            .tag_snapshot(fixture.file.clone(), "2020-01-01_00-00-00-000000000", "old")
+            .await;
+
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_removing_snapshot_drops_its_tag() {
+        let fixture = Fixture::new();
+        let timestamp = fixture.snapshot("first").await;
+        fixture
+            .service
+            .tag_snapshot(fixture.file.clone(), &timestamp, "keep")
+            .await
+            .unwrap();
+
+        fixture
+            .service
+            .undo_snapshot(fixture.file.clone())
+            .await
+            .unwrap();
+
+        let snapshot_dir = fixture.service.file_snapshot_dir(&fixture.file).unwrap();
+        assert!(!ForgeFS::exists(snapshot_dir.join(TAGS_FILE)));
+        // A new snapshot can take the tag
+        let timestamp = fixture.snapshot("second").await;
+        fixture
+            .service
+            .tag_snapshot(fixture.file.clone(), &timestamp, "keep")
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use forge_fs::ForgeFS;
+use forge_walker::Walker;
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::snapshot::{find_object, hash_content, read_object, store_object};
+use crate::{SnapshotId, SnapshotService};
+
+/// Name of the directory, relative to the snapshots directory, holding the
+/// manifests of tree snapshots
+pub const TREES_DIR: &str = "trees";
+
+/// Files larger than this are left out of tree snapshots by default
+pub const DEFAULT_MAX_TREE_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// A file captured by a tree snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TreeEntry {
+    /// Path relative to the root of the tree
+    path: String,
+
+    /// blake3 hash of the file content, identifies the stored payload
+    hash: String,
+
+    /// Size of the file content in bytes
+    size: u64,
+}
+
+/// A file left out of a tree snapshot because it exceeded the size limit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFile {
+    /// Path relative to the root of the tree
+    pub path: String,
+
+    /// Size of the file in bytes
+    pub size: u64,
+}
+
+/// Lists the files captured by a tree snapshot, the contents are stored in
+/// the shared objects directory alongside the file snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TreeManifest {
+    id: SnapshotId,
+    timestamp: Duration,
+    root: String,
+    include_globs: Vec<String>,
+    exclude_globs: Vec<String>,
+    entries: Vec<TreeEntry>,
+    skipped: Vec<SkippedFile>,
+}
+
+impl TreeManifest {
+    fn path(snapshots_dir: &Path, id: &SnapshotId) -> PathBuf {
+        snapshots_dir.join(TREES_DIR).join(format!("{id}.json"))
+    }
+
+    async fn save(&self, snapshots_dir: &Path) -> Result<()> {
+        let path = Self::path(snapshots_dir, &self.id);
+        if let Some(parent) = path.parent() {
+            ForgeFS::create_dir_all(parent).await?;
+        }
+        Ok(ForgeFS::write(path, serde_json::to_vec(self)?).await?)
+    }
+
+    async fn load(path: &Path) -> Result<Self> {
+        let content = ForgeFS::read(path).await?;
+        serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse tree snapshot manifest {}", path.display()))
+    }
+}
+
+/// Information about a tree snapshot that was just taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeSnapshotInfo {
+    /// ID to pass to [`SnapshotService::restore_tree`]
+    pub id: SnapshotId,
+
+    /// Directory the snapshot was taken of
+    pub root: PathBuf,
+
+    /// Unix timestamp when the snapshot was created
+    pub timestamp: Duration,
+
+    /// Number of files captured
+    pub file_count: usize,
+
+    /// Combined size of the captured files
+    pub total_bytes: u64,
+
+    /// Files left out because they exceeded the size limit
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Outcome of restoring a tree snapshot, all paths are absolute
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeRestoreReport {
+    /// Files modified since the snapshot that were reverted
+    pub restored: Vec<PathBuf>,
+
+    /// Files deleted since the snapshot that were recreated
+    pub recreated: Vec<PathBuf>,
+
+    /// Files created since the snapshot, these are left untouched
+    pub untracked: Vec<PathBuf>,
+}
+
+/// Include and exclude globs matched against paths relative to the tree root
+struct TreeFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl TreeFilter {
+    fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let compile = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| Pattern::new(glob).with_context(|| format!("Invalid glob: {glob}")))
+                .collect::<Result<Vec<_>>>()
+        };
+
+        Ok(Self { include: compile(include)?, exclude: compile(exclude)? })
+    }
+
+    /// Every file matches when no include globs are given
+    fn matches(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.matches(path)))
+            && !self.exclude.iter().any(|glob| glob.matches(path))
+    }
+
+    /// Lists the files under `root` matching the filter, respecting ignore
+    /// files
+    async fn files(&self, root: &Path) -> Result<Vec<forge_walker::File>> {
+        // Lock files are noisy to read but still need to be restored
+        let mut files = Walker::max_all()
+            .cwd(root.to_path_buf())
+            .ignore_patterns(Vec::new())
+            .get()
+            .await?
+            .into_iter()
+            .filter(|file| !file.is_dir() && self.matches(&file.path))
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+}
+
+impl SnapshotService {
+    /// Sets the size above which files are left out of tree snapshots
+    pub fn max_tree_file_size(mut self, bytes: u64) -> Self {
+        self.max_tree_file_size = bytes;
+        self
+    }
+
+    /// Snapshots every file under `root` that matches `include_globs` (all
+    /// files when empty) and none of `exclude_globs`. Files ignored by
+    /// `.gitignore` or larger than the size limit are left out.
+    pub async fn create_tree_snapshot(
+        &self,
+        root: PathBuf,
+        include_globs: &[String],
+        exclude_globs: &[String],
+    ) -> Result<TreeSnapshotInfo> {
+        let root = root.canonicalize()?;
+        let filter = TreeFilter::new(include_globs, exclude_globs)?;
+
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        for file in filter.files(&root).await? {
+            if file.size > self.max_tree_file_size {
+                warn!(path = %file.path, size = file.size, "Skipping large file in tree snapshot");
+                skipped.push(SkippedFile { path: file.path, size: file.size });
+                continue;
+            }
+
+            let content = ForgeFS::read(root.join(&file.path)).await?;
+            let hash = hash_content(&content);
+            store_object(&self.snapshots_directory, &hash, &content, self.compression).await?;
+
+            entries.push(TreeEntry { path: file.path, hash, size: content.len() as u64 });
+        }
+
+        let manifest = TreeManifest {
+            id: SnapshotId::new(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?,
+            root: root.display().to_string(),
+            include_globs: include_globs.to_vec(),
+            exclude_globs: exclude_globs.to_vec(),
+            entries,
+            skipped,
+        };
+        manifest.save(&self.snapshots_directory).await?;
+
+        Ok(TreeSnapshotInfo {
+            id: manifest.id,
+            root,
+            timestamp: manifest.timestamp,
+            file_count: manifest.entries.len(),
+            total_bytes: manifest.entries.iter().map(|entry| entry.size).sum(),
+            skipped: manifest.skipped,
+        })
+    }
+
+    /// Reverts the files captured by the tree snapshot `id` and recreates
+    /// the ones deleted since. Files created since the snapshot are reported
+    /// but left untouched. Nothing is written unless the content of every
+    /// file to restore is available.
+    pub async fn restore_tree(&self, id: &SnapshotId) -> Result<TreeRestoreReport> {
+        let manifest_path = TreeManifest::path(&self.snapshots_directory, id);
+        if !ForgeFS::exists(&manifest_path) {
+            return Err(anyhow::anyhow!("No tree snapshot found with id {id}"));
+        }
+        let manifest = TreeManifest::load(&manifest_path).await?;
+        let root = PathBuf::from(&manifest.root);
+
+        // Works out the changes upfront so a missing payload aborts the restore
+        // before any file is touched
+        let mut changes = Vec::new();
+        for entry in manifest.entries.iter() {
+            let path = root.join(&entry.path);
+            let deleted = !ForgeFS::exists(&path);
+            if !deleted && hash_content(&ForgeFS::read(&path).await?) == entry.hash {
+                continue;
+            }
+
+            if find_object(&self.snapshots_directory, &entry.hash).is_none() {
+                return Err(anyhow::anyhow!(
+                    "Content of {} is missing from tree snapshot {id}",
+                    entry.path
+                ));
+            }
+            changes.push((path, &entry.hash, deleted));
+        }
+
+        let mut report = TreeRestoreReport::default();
+        for (path, hash, deleted) in changes {
+            let content = read_object(&self.snapshots_directory, hash).await?;
+            if let Some(parent) = path.parent() {
+                ForgeFS::create_dir_all(parent).await?;
+            }
+
+            // Writes next to the target and renames it over, so each file is
+            // either fully restored or left as it was
+            let mut staging = path.clone().into_os_string();
+            staging.push(".forge-restore");
+            ForgeFS::write(&staging, content).await?;
+            tokio::fs::rename(&staging, &path)
+                .await
+                .with_context(|| format!("Failed to restore {}", path.display()))?;
+
+            if deleted {
+                report.recreated.push(path);
+            } else {
+                report.restored.push(path);
+            }
+        }
+
+        let known = manifest
+            .entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .chain(manifest.skipped.iter().map(|file| file.path.as_str()))
+            .collect::<HashSet<_>>();
+        let filter = TreeFilter::new(&manifest.include_globs, &manifest.exclude_globs)?;
+        report.untracked = filter
+            .files(&root)
+            .await?
+            .into_iter()
+            .filter(|file| !known.contains(file.path.as_str()))
+            .map(|file| root.join(file.path))
+            .collect();
+
+        Ok(report)
+    }
+
+    /// Counts the files, across all tree snapshots, that point at `hash`
+    pub(crate) async fn count_tree_references(&self, hash: &str) -> Result<usize> {
+        let trees_dir = self.snapshots_directory.join(TREES_DIR);
+        if !ForgeFS::exists(&trees_dir) {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+        let mut dir = ForgeFS::read_dir(&trees_dir).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            if let Ok(manifest) = TreeManifest::load(&entry.path()).await {
+                count += manifest
+                    .entries
+                    .iter()
+                    .filter(|entry| entry.hash == hash)
+                    .count();
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Lists the payloads referenced by every tree snapshot as the manifest
+    /// path along with the hash and size recorded for the captured file
+    pub(crate) async fn tree_payloads(&self) -> Result<Vec<(PathBuf, String, u64)>> {
+        let trees_dir = self.snapshots_directory.join(TREES_DIR);
+        if !ForgeFS::exists(&trees_dir) {
+            return Ok(Vec::new());
+        }
+
+        let mut payloads = Vec::new();
+        let mut dir = ForgeFS::read_dir(&trees_dir).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            if let Ok(manifest) = TreeManifest::load(&entry.path()).await {
+                payloads.extend(
+                    manifest
+                        .entries
+                        .into_iter()
+                        .map(|tree_entry| (entry.path(), tree_entry.hash, tree_entry.size)),
+                );
+            }
+        }
+
+        Ok(payloads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    struct Fixture {
+        _temp_dir: TempDir,
+        root: PathBuf,
+        service: SnapshotService,
+    }
+
+    impl Fixture {
+        async fn new(files: &[(&str, &str)]) -> Result<Self> {
+            let temp_dir = TempDir::new()?;
+            let root = temp_dir.path().join("project");
+            ForgeFS::create_dir_all(&root).await?;
+            let root = root.canonicalize()?;
+            for (path, content) in files {
+                let path = root.join(path);
+                ForgeFS::create_dir_all(path.parent().unwrap()).await?;
+                ForgeFS::write(path, content.as_bytes()).await?;
+            }
+            let service = SnapshotService::new(temp_dir.path().join("snapshots"));
+
+            Ok(Self { _temp_dir: temp_dir, root, service })
+        }
+
+        async fn read(&self, path: &str) -> Result<String> {
+            Ok(String::from_utf8(
+                ForgeFS::read(self.root.join(path)).await?,
+            )?)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore_tree_reverts_modified_and_deleted_files() -> Result<()> {
+        // Arrange
+        let fixture = Fixture::new(&[
+            ("src/main.rs", "fn main() {}"),
+            ("src/lib.rs", "pub mod foo;"),
+            ("README.md", "# Readme"),
+        ])
+        .await?;
+        let info = fixture
+            .service
+            .create_tree_snapshot(fixture.root.clone(), &[], &[])
+            .await?;
+        ForgeFS::write(fixture.root.join("src/main.rs"), "fn main() { todo!() }").await?;
+        ForgeFS::remove_file(&fixture.root.join("src/lib.rs")).await?;
+        ForgeFS::write(fixture.root.join("NEW.md"), "new").await?;
+
+        // Act
+        let actual = fixture.service.restore_tree(&info.id).await?;
+
+        // Assert
+        let expected = TreeRestoreReport {
+            restored: vec![fixture.root.join("src/main.rs")],
+            recreated: vec![fixture.root.join("src/lib.rs")],
+            untracked: vec![fixture.root.join("NEW.md")],
+        };
+        assert_eq!(actual, expected);
+        assert_eq!(fixture.read("src/main.rs").await?, "fn main() {}");
+        assert_eq!(fixture.read("src/lib.rs").await?, "pub mod foo;");
+        assert_eq!(fixture.read("NEW.md").await?, "new");
+        assert_eq!(info.file_count, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_tree_snapshot_filters_and_skips_large_files() -> Result<()> {
+        // Arrange
+        let fixture = Fixture::new(&[
+            ("src/main.rs", "fn main() {}"),
+            ("src/big.rs", "0123456789abcdef"),
+            ("src/generated.rs", "// generated"),
+            ("README.md", "# Readme"),
+        ])
+        .await?;
+        let service = fixture.service.max_tree_file_size(15);
+
+        // Act
+        let actual = service
+            .create_tree_snapshot(
+                fixture.root.clone(),
+                &["src/**".to_string()],
+                &["**/generated.rs".to_string()],
+            )
+            .await?;
+
+        // Assert
+        assert_eq!(actual.file_count, 1);
+        assert_eq!(actual.total_bytes, 12);
+        assert_eq!(
+            actual.skipped,
+            vec![SkippedFile { path: "src/big.rs".to_string(), size: 16 }]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_tree_unknown_id() -> Result<()> {
+        let fixture = Fixture::new(&[]).await?;
+        let actual = fixture.service.restore_tree(&SnapshotId::new()).await;
+        assert!(actual
+            .unwrap_err()
+            .to_string()
+            .contains("No tree snapshot found"));
+        Ok(())
+    }
+}
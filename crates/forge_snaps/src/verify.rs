@@ -0,0 +1,434 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use forge_fs::ForgeFS;
+
+use crate::snapshot::{
+    find_object, hash_content, read_object, Snapshot, SnapshotCompression, OBJECTS_DIR,
+};
+use crate::tree::TREES_DIR;
+use crate::{SnapshotSelector, SnapshotService};
+
+/// Why the content of a snapshot couldn't be restored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestorationError {
+    /// The payload no longer hashes to the value recorded when the snapshot
+    /// was taken, so restoring it would write corrupted content
+    ChecksumMismatch {
+        snapshot: PathBuf,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl Display for RestorationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ChecksumMismatch { snapshot, expected, actual } => write!(
+                f,
+                "Snapshot {} is corrupted: its content hashes to {actual} instead of {expected}",
+                snapshot.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RestorationError {}
+
+/// A problem found while verifying the stored snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationIssue {
+    /// The payload no longer matches the hash or size recorded in the
+    /// snapshot's metadata
+    Corrupted { snapshot: PathBuf, object: PathBuf },
+
+    /// The payload referenced by the snapshot's metadata doesn't exist
+    MissingPayload { snapshot: PathBuf, object: PathBuf },
+
+    /// A payload that no snapshot refers to
+    OrphanPayload { object: PathBuf },
+
+    /// The snapshot doesn't record a hash of its content, as is the case for
+    /// snapshots taken before content addressing, so it can't be checked
+    Unverifiable { snapshot: PathBuf },
+}
+
+impl Display for VerificationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Corrupted { snapshot, object } => write!(
+                f,
+                "Payload {} of snapshot {} is corrupted",
+                object.display(),
+                snapshot.display()
+            ),
+            Self::MissingPayload { snapshot, object } => write!(
+                f,
+                "Payload {} of snapshot {} is missing",
+                object.display(),
+                snapshot.display()
+            ),
+            Self::OrphanPayload { object } => {
+                write!(
+                    f,
+                    "Payload {} is not used by any snapshot",
+                    object.display()
+                )
+            }
+            Self::Unverifiable { snapshot } => {
+                write!(f, "Snapshot {} has no content hash", snapshot.display())
+            }
+        }
+    }
+}
+
+/// Checks the payloads referenced by snapshots against the hash and size
+/// recorded in their metadata. Payloads are read once no matter how many
+/// snapshots share them.
+#[derive(Default)]
+struct Verifier {
+    /// Hash and size of every payload read so far, `None` if it's missing
+    payloads: HashMap<PathBuf, Option<(String, u64)>>,
+    issues: Vec<VerificationIssue>,
+}
+
+impl Verifier {
+    async fn check(
+        &mut self,
+        snapshot: &Path,
+        object: PathBuf,
+        compression: SnapshotCompression,
+        hash: &str,
+        size: u64,
+    ) {
+        if !self.payloads.contains_key(&object) {
+            let payload = ForgeFS::read(&object).await.ok().map(|content| {
+                compression
+                    .decompress(&content)
+                    .map(|content| (hash_content(&content), content.len() as u64))
+                    // A payload that can't be decompressed matches no hash
+                    .unwrap_or_default()
+            });
+            self.payloads.insert(object.clone(), payload);
+        }
+
+        let snapshot = snapshot.to_path_buf();
+        match &self.payloads[&object] {
+            None => self
+                .issues
+                .push(VerificationIssue::MissingPayload { snapshot, object }),
+            Some((actual_hash, actual_size)) if actual_hash != hash || *actual_size != size => self
+                .issues
+                .push(VerificationIssue::Corrupted { snapshot, object }),
+            Some(_) => {}
+        }
+    }
+
+    /// Checks every snapshot stored in `snapshot_dir`, returning the hashes
+    /// they refer to
+    async fn check_dir(
+        &mut self,
+        snapshots_directory: &Path,
+        snapshot_dir: &Path,
+    ) -> Result<HashSet<String>> {
+        let mut hashes = HashSet::new();
+        for file in SnapshotService::snapshot_files(snapshot_dir).await? {
+            match Snapshot::load(&file).await {
+                Ok(snapshot) => {
+                    let object = snapshot.object_path(snapshots_directory);
+                    self.check(
+                        &file,
+                        object,
+                        snapshot.compression,
+                        &snapshot.hash,
+                        snapshot.size,
+                    )
+                    .await;
+                    hashes.insert(snapshot.hash);
+                }
+                // Snapshots taken before content addressing hold the payload directly
+                Err(_) => self
+                    .issues
+                    .push(VerificationIssue::Unverifiable { snapshot: file }),
+            }
+        }
+
+        Ok(hashes)
+    }
+}
+
+impl SnapshotService {
+    /// Checks that the content of the snapshot of `path` taken at `timestamp`
+    /// still matches the hash recorded when it was taken, without restoring
+    /// it. Fails if the payload is missing or the snapshot records no hash.
+    pub async fn verify_snapshot(&self, path: PathBuf, timestamp: &str) -> Result<bool> {
+        let selector = SnapshotSelector::Timestamp(timestamp.to_string());
+        let snapshot_path = self.select_snapshot(&path, &selector).await?;
+        let snapshot = Snapshot::load(&snapshot_path).await?;
+        let content = read_object(&self.snapshots_directory, &snapshot.hash).await?;
+        Ok(hash_content(&content) == snapshot.hash)
+    }
+
+    /// Checks the snapshots of `path` against the payloads they refer to
+    pub async fn verify(&self, path: PathBuf) -> Result<Vec<VerificationIssue>> {
+        let snapshot_dir = self.file_snapshot_dir(&path)?;
+        if !ForgeFS::exists(&snapshot_dir) {
+            return Err(anyhow::anyhow!("No snapshots found for {:?}", path));
+        }
+
+        let mut verifier = Verifier::default();
+        verifier
+            .check_dir(&self.snapshots_directory, &snapshot_dir)
+            .await?;
+
+        Ok(verifier.issues)
+    }
+
+    /// Checks every file and tree snapshot against the payloads they refer
+    /// to, and reports payloads no snapshot refers to
+    pub async fn verify_all(&self) -> Result<Vec<VerificationIssue>> {
+        if !ForgeFS::exists(&self.snapshots_directory) {
+            return Ok(Vec::new());
+        }
+
+        let mut verifier = Verifier::default();
+        let mut referenced = HashSet::new();
+        let mut dirs = ForgeFS::read_dir(&self.snapshots_directory).await?;
+
+        while let Some(entry) = dirs.next_entry().await? {
+            if entry.file_name() == OBJECTS_DIR
+                || entry.file_name() == TREES_DIR
+                || !entry.path().is_dir()
+            {
+                continue;
+            }
+
+            referenced.extend(
+                verifier
+                    .check_dir(&self.snapshots_directory, &entry.path())
+                    .await?,
+            );
+        }
+
+        for (manifest, hash, size) in self.tree_payloads().await? {
+            let (object, compression) = find_object(&self.snapshots_directory, &hash)
+                .unwrap_or_else(|| {
+                    (
+                        self.snapshots_directory.join(OBJECTS_DIR).join(&hash),
+                        SnapshotCompression::None,
+                    )
+                });
+            verifier
+                .check(&manifest, object, compression, &hash, size)
+                .await;
+            referenced.insert(hash);
+        }
+
+        let objects_dir = self.snapshots_directory.join(OBJECTS_DIR);
+        if ForgeFS::exists(&objects_dir) {
+            let mut objects = ForgeFS::read_dir(&objects_dir).await?;
+            while let Some(entry) = objects.next_entry().await? {
+                // Compressed payloads are named after their hash plus an extension
+                let name = entry.file_name().to_string_lossy().to_string();
+                let hash = name.split('.').next().unwrap_or_default();
+                if !referenced.contains(hash) {
+                    verifier
+                        .issues
+                        .push(VerificationIssue::OrphanPayload { object: entry.path() });
+                }
+            }
+        }
+
+        Ok(verifier.issues)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    struct Fixture {
+        _temp_dir: TempDir,
+        file: PathBuf,
+        service: SnapshotService,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let temp_dir = TempDir::new().unwrap();
+            let file = temp_dir.path().join("test.txt");
+            let service = SnapshotService::new(temp_dir.path().join("snapshots"));
+            ForgeFS::write(&file, "first").await.unwrap();
+            Self { _temp_dir: temp_dir, file, service }
+        }
+
+        async fn snapshot(&self, content: &str) -> Snapshot {
+            ForgeFS::write(&self.file, content).await.unwrap();
+            self.service
+                .create_snapshot(self.file.clone(), None)
+                .await
+                .unwrap()
+        }
+
+        fn object(&self, snapshot: &Snapshot) -> PathBuf {
+            snapshot.object_path(&self.service.snapshots_directory)
+        }
+
+        fn metadata(&self, snapshot: &Snapshot) -> PathBuf {
+            snapshot.snapshot_path(Some(self.service.snapshots_directory.clone()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_corrupted_and_missing_payloads() {
+        let fixture = Fixture::new().await;
+        let corrupted = fixture.snapshot("first").await;
+        let missing = fixture.snapshot("second").await;
+        fixture.snapshot("third").await;
+
+        let mut content = ForgeFS::read(fixture.object(&corrupted)).await.unwrap();
+        content[0] ^= 0xff;
+        ForgeFS::write(fixture.object(&corrupted), content)
+            .await
+            .unwrap();
+        ForgeFS::remove_file(fixture.object(&missing))
+            .await
+            .unwrap();
+
+        let mut actual = fixture.service.verify(fixture.file.clone()).await.unwrap();
+        actual.sort_by_key(|issue| issue.to_string());
+
+        let mut expected = vec![
+            VerificationIssue::Corrupted {
+                snapshot: fixture.metadata(&corrupted),
+                object: fixture.object(&corrupted),
+            },
+            VerificationIssue::MissingPayload {
+                snapshot: fixture.metadata(&missing),
+                object: fixture.object(&missing),
+            },
+        ];
+        expected.sort_by_key(|issue| issue.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_verify_all_reports_orphans_and_unverifiable_snapshots() {
+        let fixture = Fixture::new().await;
+        let snapshot = fixture.snapshot("first").await;
+
+        let orphan = fixture
+            .service
+            .snapshots_directory
+            .join(OBJECTS_DIR)
+            .join(hash_content(b"orphan"));
+        ForgeFS::write(&orphan, "orphan").await.unwrap();
+
+        // Snapshots taken before content addressing hold the raw content
+        let legacy = fixture
+            .metadata(&snapshot)
+            .with_file_name("2020-01-01_00-00-00-000000000.snap");
+        ForgeFS::write(&legacy, "legacy content").await.unwrap();
+
+        let mut actual = fixture.service.verify_all().await.unwrap();
+        actual.sort_by_key(|issue| issue.to_string());
+
+        let expected = vec![
+            VerificationIssue::OrphanPayload { object: orphan },
+            VerificationIssue::Unverifiable { snapshot: legacy },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_verify_all_decompresses_payloads() {
+        let mut fixture = Fixture::new().await;
+        fixture.service = SnapshotService::new(fixture.service.snapshots_directory.clone())
+            .compression(SnapshotCompression::Gzip);
+        fixture.snapshot("first").await;
+        let corrupted = fixture.snapshot("second").await;
+
+        ForgeFS::write(fixture.object(&corrupted), "not gzip")
+            .await
+            .unwrap();
+
+        let actual = fixture.service.verify_all().await.unwrap();
+
+        let expected = vec![VerificationIssue::Corrupted {
+            snapshot: fixture.metadata(&corrupted),
+            object: fixture.object(&corrupted),
+        }];
+        assert_eq!(actual, expected);
+    }
+
+    fn timestamp(snapshot: &Snapshot) -> String {
+        let path = snapshot.snapshot_path(None);
+        path.file_stem().unwrap().to_string_lossy().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_verify_snapshot() {
+        let fixture = Fixture::new().await;
+        let intact = fixture.snapshot("first").await;
+        let corrupted = fixture.snapshot("second").await;
+        ForgeFS::write(fixture.object(&corrupted), "tampered")
+            .await
+            .unwrap();
+
+        let actual = (
+            fixture
+                .service
+                .verify_snapshot(fixture.file.clone(), &timestamp(&intact))
+                .await
+                .unwrap(),
+            fixture
+                .service
+                .verify_snapshot(fixture.file.clone(), &timestamp(&corrupted))
+                .await
+                .unwrap(),
+        );
+
+        assert_eq!(actual, (true, false));
+        assert!(fixture
+            .service
+            .verify_snapshot(fixture.file.clone(), "2020-01-01_00-00-00-000000000")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_corrupted_snapshot() {
+        let fixture = Fixture::new().await;
+        let corrupted = fixture.snapshot("first").await;
+        fixture.snapshot("second").await;
+        ForgeFS::write(fixture.object(&corrupted), "tampered")
+            .await
+            .unwrap();
+
+        let by_index = fixture
+            .service
+            .restore_by_index(fixture.file.clone(), 1)
+            .await
+            .unwrap_err();
+        let by_timestamp = fixture
+            .service
+            .restore_by_timestamp(fixture.file.clone(), &timestamp(&corrupted))
+            .await
+            .unwrap_err();
+
+        let expected = RestorationError::ChecksumMismatch {
+            snapshot: fixture.metadata(&corrupted),
+            expected: corrupted.hash.clone(),
+            actual: hash_content(b"tampered"),
+        };
+        assert_eq!(by_index.downcast_ref(), Some(&expected));
+        assert_eq!(by_timestamp.downcast_ref(), Some(&expected));
+        // The file keeps the content it had
+        let content = ForgeFS::read_utf8(&fixture.file).await.unwrap();
+        assert_eq!(content, "second");
+    }
+}
@@ -0,0 +1,202 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use tracing::{debug, trace, warn};
+
+use crate::MpscStream;
+
+/// Number of items in a row that have to find the buffer full before the
+/// consumer counts as lagging
+const LAG_THRESHOLD: usize = 32;
+
+/// Depth of the buffer between the producer and the consumer of a stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackpressureMetrics {
+    /// Items that were waiting in the buffer when the consumer asked for the
+    /// last item
+    pub depth: usize,
+    /// Largest depth seen so far
+    pub max_depth: usize,
+    /// Items taken from the stream
+    pub received: usize,
+    /// Whether the buffer has been full for the last [`LAG_THRESHOLD`] items
+    pub lagging: bool,
+}
+
+/// Wraps an [`MpscStream`] and samples the depth of its buffer every time an
+/// item is taken. The samples are traced, and a warning is logged once the
+/// buffer stays full, as the producer then keeps waiting on the consumer,
+/// usually because rendering can't keep up.
+pub struct MonitoredStream<T> {
+    stream: MpscStream<T>,
+    name: &'static str,
+    metrics: BackpressureMetrics,
+    /// Depth of the buffer when the consumer started waiting for the next item
+    asked_at_depth: Option<usize>,
+    /// Items in a row that found the buffer full
+    full_streak: usize,
+}
+
+impl<T> MpscStream<T> {
+    /// Monitors the depth of the stream's buffer, `name` identifying the
+    /// stream in traces
+    pub fn monitored(self, name: &'static str) -> MonitoredStream<T> {
+        MonitoredStream {
+            stream: self,
+            name,
+            metrics: BackpressureMetrics::default(),
+            asked_at_depth: None,
+            full_streak: 0,
+        }
+    }
+}
+
+impl<T> MonitoredStream<T> {
+    pub fn metrics(&self) -> BackpressureMetrics {
+        self.metrics
+    }
+
+    fn sample(&mut self, depth: usize) {
+        let capacity = self.stream.capacity();
+        self.metrics.depth = depth;
+        self.metrics.max_depth = self.metrics.max_depth.max(depth);
+        self.metrics.received += 1;
+        trace!(
+            stream = self.name,
+            depth,
+            max_depth = self.metrics.max_depth,
+            "Stream buffer depth"
+        );
+
+        if depth < capacity {
+            if self.metrics.lagging {
+                debug!(stream = self.name, "Stream consumer caught up");
+            }
+            self.full_streak = 0;
+            self.metrics.lagging = false;
+            return;
+        }
+
+        self.full_streak += 1;
+        if self.full_streak == LAG_THRESHOLD {
+            self.metrics.lagging = true;
+            warn!(
+                stream = self.name,
+                capacity,
+                items = self.full_streak,
+                "Stream consumer is lagging behind the producer"
+            );
+        }
+    }
+}
+
+impl<T> Stream for MonitoredStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // The depth is read when the consumer asks for an item rather than when
+        // it arrives, otherwise a consumer that had to wait would count the
+        // item it waited for
+        let stream = &mut *self;
+        let depth = *stream.asked_at_depth.get_or_insert(stream.stream.depth());
+        let poll = Pin::new(&mut stream.stream).poll_next(cx);
+        match poll {
+            Poll::Ready(Some(_)) => {
+                stream.asked_at_depth = None;
+                stream.sample(depth);
+            }
+            Poll::Ready(None) => stream.asked_at_depth = None,
+            Poll::Pending => {}
+        }
+        poll
+    }
+}
+
+impl<T> Drop for MonitoredStream<T> {
+    fn drop(&mut self) {
+        debug!(
+            stream = self.name,
+            received = self.metrics.received,
+            max_depth = self.metrics.max_depth,
+            "Stream closed"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::mpsc_stream::BUFFER_SIZE;
+    use crate::Captured;
+
+    /// Consumes `items` items, waiting `consumer_delay` after each one, from
+    /// a producer that waits `producer_delay` before each one. Returns the
+    /// final metrics and the warnings logged.
+    async fn consume(
+        items: usize,
+        producer_delay: Duration,
+        consumer_delay: Duration,
+    ) -> (BackpressureMetrics, String) {
+        let captured = Captured::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(captured.clone())
+            .with_max_level(tracing::Level::WARN)
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut stream = MpscStream::spawn(move |tx| async move {
+            for i in 0..items {
+                // Even a zero sleep waits for the next tick of the timer
+                if !producer_delay.is_zero() {
+                    tokio::time::sleep(producer_delay).await;
+                }
+                tx.send(i).await.unwrap();
+            }
+        })
+        .monitored("test");
+
+        let mut received = Vec::new();
+        while let Some(item) = stream.next().await {
+            received.push(item);
+            tokio::time::sleep(consumer_delay).await;
+        }
+        assert_eq!(received, (0..items).collect::<Vec<_>>());
+
+        let metrics = stream.metrics();
+        drop(stream);
+        (metrics, captured.contents())
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_warns() {
+        let items = BUFFER_SIZE + LAG_THRESHOLD * 2;
+        let (metrics, logs) = consume(items, Duration::ZERO, Duration::from_millis(1)).await;
+
+        // The consumer catches up once the producer is done
+        assert!(!metrics.lagging);
+        assert_eq!(metrics.max_depth, BUFFER_SIZE);
+        assert_eq!(metrics.received, items);
+        assert_eq!(
+            logs.matches("Stream consumer is lagging behind the producer")
+                .count(),
+            1,
+            "{logs}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fast_consumer_does_not_warn() {
+        let (metrics, logs) =
+            consume(LAG_THRESHOLD * 2, Duration::from_millis(1), Duration::ZERO).await;
+
+        assert!(!metrics.lagging);
+        assert_eq!(metrics.received, LAG_THRESHOLD * 2);
+        assert!(!logs.contains("lagging"), "{logs}");
+    }
+}
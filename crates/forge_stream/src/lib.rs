@@ -1,3 +1,9 @@
+mod backpressure;
+#[cfg(any(test, feature = "test-util"))]
+mod captured;
 mod mpsc_stream;
 
+pub use backpressure::*;
+#[cfg(any(test, feature = "test-util"))]
+pub use captured::*;
 pub use mpsc_stream::*;
@@ -4,6 +4,11 @@ use futures::Stream;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::task::JoinHandle;
 
+/// Items the producer may get ahead of the consumer before it has to wait.
+/// The slack absorbs short stalls of the consumer, so a full buffer means the
+/// consumer is falling behind rather than just busy with the last item.
+pub(crate) const BUFFER_SIZE: usize = 32;
+
 pub struct MpscStream<T> {
     join_handle: JoinHandle<()>,
     receiver: Receiver<T>,
@@ -15,9 +20,19 @@ impl<T> MpscStream<T> {
         F: (FnOnce(Sender<T>) -> S) + Send + 'static,
         S: Future<Output = ()> + Send + 'static,
     {
-        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let (tx, rx) = tokio::sync::mpsc::channel(BUFFER_SIZE);
         MpscStream { join_handle: tokio::spawn(f(tx)), receiver: rx }
     }
+
+    /// Number of items waiting in the buffer
+    pub(crate) fn depth(&self) -> usize {
+        self.receiver.len()
+    }
+
+    /// Number of items the buffer holds before the producer has to wait
+    pub(crate) fn capacity(&self) -> usize {
+        self.receiver.max_capacity()
+    }
 }
 
 impl<T> Stream for MpscStream<T> {
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+/// Ignore files read in every directory, lowest precedence first. The git
+/// ones only apply inside a git repository.
+const IGNORE_FILES: &[(&str, bool)] = &[
+    (".git/info/exclude", true),
+    (".gitignore", true),
+    (".ignore", false),
+];
+
+/// Ignore rules of a single directory
+struct DirRules {
+    matcher: Gitignore,
+    /// Whether the directory belongs to a git repository
+    in_repo: bool,
+}
+
+/// The .gitignore, .ignore and global git exclude rules that apply to the
+/// walked paths. The rules of a directory are read the first time one of its
+/// entries is checked, so a walk reads every ignore file only once.
+pub(crate) struct IgnoreRules {
+    global: Gitignore,
+    dirs: Mutex<HashMap<PathBuf, Arc<DirRules>>>,
+}
+
+impl IgnoreRules {
+    pub(crate) fn new() -> Self {
+        let (global, _) = Gitignore::global();
+        Self { global, dirs: Mutex::new(HashMap::new()) }
+    }
+
+    /// Whether `path` is excluded by the rules of its ancestors. The rules of
+    /// the nearest directory win, and a `!` pattern brings a path back.
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+
+        for dir in parent.ancestors() {
+            let rules = self.rules(dir);
+            let matched = rules.matcher.matched(path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+
+        self.rules(parent).in_repo && self.global.matched(path, is_dir).is_ignore()
+    }
+
+    fn rules(&self, dir: &Path) -> Arc<DirRules> {
+        if let Some(rules) = self.dirs.lock().unwrap().get(dir) {
+            return rules.clone();
+        }
+
+        let in_repo = dir.join(".git").exists()
+            || dir
+                .parent()
+                .is_some_and(|parent| self.rules(parent).in_repo);
+
+        let mut builder = GitignoreBuilder::new(dir);
+        for (name, git_only) in IGNORE_FILES {
+            let file = dir.join(name);
+            if (in_repo || !git_only) && file.is_file() {
+                // Invalid lines are skipped, as git does
+                let _ = builder.add(file);
+            }
+        }
+        let matcher = builder.build().unwrap_or_else(|_| Gitignore::empty());
+
+        let rules = Arc::new(DirRules { matcher, in_repo });
+        self.dirs
+            .lock()
+            .unwrap()
+            .insert(dir.to_path_buf(), rules.clone());
+        rules
+    }
+}
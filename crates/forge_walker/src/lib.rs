@@ -1,3 +1,4 @@
+mod ignore_rules;
 mod walker;
 
-pub use walker::{File, Walker};
+pub use walker::{File, Walked, Walker, DEFAULT_IGNORE_PATTERNS};
@@ -1,11 +1,16 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use derive_setters::Setters;
+use ignore::gitignore::GitignoreBuilder;
 use ignore::WalkBuilder;
 use tokio::task::spawn_blocking;
 
+use crate::ignore_rules::IgnoreRules;
+
 #[derive(Clone, Debug)]
 pub struct File {
     pub path: String,
@@ -19,6 +24,16 @@ impl File {
     }
 }
 
+/// Files found by a walk
+#[derive(Clone, Debug, Default)]
+pub struct Walked {
+    pub files: Vec<File>,
+    /// Number of files and directories left out because of ignore rules or
+    /// noisy file patterns. Ignored directories count as a single entry, as
+    /// their contents are never read.
+    pub skipped: usize,
+}
+
 #[derive(Debug, Clone, Setters)]
 pub struct Walker {
     /// Base directory to start walking from
@@ -41,6 +56,47 @@ pub struct Walker {
 
     /// Whether to skip binary files
     skip_binary: bool,
+
+    /// Whether to skip hidden files and directories. Version control
+    /// directories such as .git are skipped either way.
+    skip_hidden: bool,
+
+    /// Whether to skip files excluded by .gitignore, .ignore and the global
+    /// git excludes
+    skip_ignored: bool,
+
+    /// Gitignore style patterns of noisy files, such as lock files, that are
+    /// skipped along with ignored files. A pattern starting with `!` brings
+    /// back files matched by an earlier one.
+    ignore_patterns: Vec<String>,
+}
+
+/// Files that are rarely worth reading even when they are checked in:
+/// generated lock files, minified bundles and build output
+pub const DEFAULT_IGNORE_PATTERNS: &[&str] = &[
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "Cargo.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "*.min.js",
+    "*.min.css",
+    "*.map",
+    "dist/",
+    "node_modules/",
+];
+
+fn default_ignore_patterns() -> Vec<String> {
+    DEFAULT_IGNORE_PATTERNS
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
 }
 
 const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024; // 1MB
@@ -61,6 +117,9 @@ impl Walker {
             max_files: DEFAULT_MAX_FILES,
             max_total_size: DEFAULT_MAX_TOTAL_SIZE,
             skip_binary: true,
+            skip_hidden: true,
+            skip_ignored: true,
+            ignore_patterns: default_ignore_patterns(),
         }
     }
 
@@ -76,14 +135,23 @@ impl Walker {
             max_files: usize::MAX,
             max_total_size: u64::MAX,
             skip_binary: false,
+            skip_hidden: true,
+            skip_ignored: true,
+            ignore_patterns: default_ignore_patterns(),
         }
     }
 }
 
 impl Walker {
     pub async fn get(&self) -> Result<Vec<File>> {
+        Ok(self.walk().await?.files)
+    }
+
+    /// Walks the directory like [`Walker::get`], also counting the entries
+    /// left out because of ignore rules
+    pub async fn walk(&self) -> Result<Walked> {
         let walker = self.clone();
-        spawn_blocking(move || walker.get_blocking())
+        spawn_blocking(move || walker.walk_blocking())
             .await
             .context("Failed to spawn blocking task")?
     }
@@ -107,17 +175,55 @@ impl Walker {
     /// Blocking function to scan filesystem. Use this when you already have
     /// a runtime or want to avoid spawning a new one.
     pub fn get_blocking(&self) -> Result<Vec<File>> {
+        Ok(self.walk_blocking()?.files)
+    }
+
+    /// Blocking version of [`Walker::walk`]
+    pub fn walk_blocking(&self) -> Result<Walked> {
         let mut files = Vec::new();
         let mut total_size = 0u64;
         let mut dir_entries: HashMap<String, usize> = HashMap::new();
         let mut file_count = 0;
 
+        let mut noisy = GitignoreBuilder::new(&self.cwd);
+        if self.skip_ignored {
+            for pattern in &self.ignore_patterns {
+                noisy
+                    .add_line(None, pattern)
+                    .with_context(|| format!("Invalid ignore pattern: {pattern}"))?;
+            }
+        }
+        let noisy = noisy.build().context("Failed to build ignore patterns")?;
+
+        // Ignore files are applied here rather than by the walker itself, so
+        // that the entries they leave out can be counted without reading the
+        // directories again
+        let rules = self.skip_ignored.then(IgnoreRules::new);
+        let skipped = Arc::new(AtomicUsize::new(0));
+        let counter = skipped.clone();
+
         // TODO: Convert to async and return a stream
         let walk = WalkBuilder::new(&self.cwd)
-            .hidden(true) // Skip hidden files
-            .git_global(true) // Use global gitignore
-            .git_ignore(true) // Use local .gitignore
-            .ignore(true) // Use .ignore files
+            .standard_filters(false)
+            .hidden(self.skip_hidden)
+            .filter_entry(move |entry| {
+                if entry.depth() == 0 {
+                    return true;
+                }
+                if entry.file_name() == ".git" {
+                    return false;
+                }
+                let path = entry.path();
+                let is_dir = entry.file_type().is_some_and(|kind| kind.is_dir());
+                let ignored = noisy.matched(path, is_dir).is_ignore()
+                    || rules
+                        .as_ref()
+                        .is_some_and(|rules| rules.is_ignored(path, is_dir));
+                if ignored {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+                !ignored
+            })
             .max_depth(Some(self.max_depth))
             // TODO: use build_parallel() for better performance
             .build();
@@ -201,7 +307,7 @@ impl Walker {
             }
         }
 
-        Ok(files)
+        Ok(Walked { files, skipped: skipped.load(Ordering::Relaxed) })
     }
 }
 
@@ -357,6 +463,96 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_walker_skips_noisy_files() {
+        let fixture = fixtures::create_sized_files(&[
+            ("Cargo.lock".into(), 10),
+            ("app.min.js".into(), 10),
+            ("main.rs".into(), 10),
+        ])
+        .unwrap();
+        fs::create_dir(fixture.path().join("dist")).unwrap();
+        fs::write(fixture.path().join("dist/bundle.js"), "test").unwrap();
+        let walker = Walker::min_all().cwd(fixture.path().to_path_buf());
+        let paths = |files: Vec<File>| {
+            let mut paths: Vec<_> = files
+                .into_iter()
+                .filter(|f| !f.is_dir())
+                .map(|f| f.path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let default = paths(walker.clone().get().await.unwrap());
+        let overridden = paths(
+            walker
+                .clone()
+                .ignore_patterns(vec![
+                    "Cargo.lock".to_string(),
+                    "*.min.js".to_string(),
+                    "!app.min.js".to_string(),
+                ])
+                .get()
+                .await
+                .unwrap(),
+        );
+        let included = paths(walker.skip_ignored(false).get().await.unwrap());
+
+        assert_eq!(default, vec!["main.rs"]);
+        assert_eq!(overridden, vec!["app.min.js", "dist/bundle.js", "main.rs"]);
+        assert_eq!(
+            included,
+            vec!["Cargo.lock", "app.min.js", "dist/bundle.js", "main.rs"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_walker_counts_ignored_entries() {
+        let fixture =
+            fixtures::create_sized_files(&[("main.rs".into(), 10), ("Cargo.lock".into(), 10)])
+                .unwrap();
+        fs::create_dir(fixture.path().join(".git")).unwrap();
+        fs::write(fixture.path().join(".gitignore"), "target/\n").unwrap();
+        fs::create_dir(fixture.path().join("target")).unwrap();
+        fs::write(fixture.path().join("target/out.rs"), "test").unwrap();
+        let walker = Walker::min_all().cwd(fixture.path().to_path_buf());
+
+        let default = walker.clone().walk().await.unwrap();
+        let included = walker.skip_ignored(false).walk().await.unwrap();
+
+        assert_eq!(default.files.iter().filter(|f| !f.is_dir()).count(), 1);
+        assert_eq!(default.skipped, 2);
+        assert_eq!(included.files.iter().filter(|f| !f.is_dir()).count(), 3);
+        assert_eq!(included.skipped, 0);
+    }
+
+    #[tokio::test]
+    async fn test_walker_skips_hidden_files_unless_asked() {
+        let fixture = fixtures::create_sized_files(&[("main.rs".into(), 10)]).unwrap();
+        fs::create_dir(fixture.path().join(".git")).unwrap();
+        fs::write(fixture.path().join(".git/HEAD"), "test").unwrap();
+        fs::create_dir(fixture.path().join(".config")).unwrap();
+        fs::write(fixture.path().join(".config/settings.toml"), "test").unwrap();
+        let walker = Walker::min_all().cwd(fixture.path().to_path_buf());
+        let paths = |walked: Walked| {
+            let mut paths: Vec<_> = walked
+                .files
+                .into_iter()
+                .filter(|f| !f.is_dir())
+                .map(|f| f.path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let default = paths(walker.clone().walk().await.unwrap());
+        let hidden = paths(walker.skip_hidden(false).walk().await.unwrap());
+
+        assert_eq!(default, vec!["main.rs"]);
+        assert_eq!(hidden, vec![".config/settings.toml", "main.rs"]);
+    }
+
     #[tokio::test]
     async fn test_file_name_and_is_dir() {
         let fixture = fixtures::create_sized_files(&[("test.txt".into(), 100)]).unwrap();